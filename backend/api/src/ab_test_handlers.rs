@@ -0,0 +1,462 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{
+    AbTest, AbTestResult, AbTestStatus, AbTestVariant, CreateAbTestRequest,
+    RecordAbTestMetricRequest, VariantType,
+};
+use uuid::Uuid;
+
+use crate::ab_testing::{self, Analysis, MetricKind};
+use crate::canary_handlers::deployment_of_contract;
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::{db_internal_error, fetch_contract_identity};
+use crate::metrics::AB_TEST_CONVERSIONS;
+use crate::state::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AbTestListQuery {
+    pub contract_id: Option<String>,
+    pub status: Option<AbTestStatus>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VariantQuery {
+    pub user_address: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AbTestDetail {
+    #[serde(flatten)]
+    pub test: AbTest,
+    pub variants: Vec<AbTestVariant>,
+    pub results: Vec<AbTestResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VariantAssignment {
+    pub test_id: Uuid,
+    pub user_address: String,
+    /// Absent when the caller was never assigned and the test is not running
+    pub variant: Option<VariantType>,
+    pub deployment_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AbTestAnalysis {
+    pub test: AbTest,
+    #[serde(flatten)]
+    pub analysis: Analysis,
+}
+
+fn parse_test_id(raw: &str) -> ApiResult<Uuid> {
+    Uuid::parse_str(raw).map_err(|_| {
+        ApiError::bad_request("InvalidTestId", format!("Invalid A/B test ID: {}", raw))
+    })
+}
+
+async fn fetch_test(state: &AppState, id: &str) -> ApiResult<AbTest> {
+    let test_id = parse_test_id(id)?;
+    ab_testing::fetch(&state.db, test_id)
+        .await
+        .map_err(|err| db_internal_error("fetch ab test", err))?
+        .ok_or_else(|| {
+            ApiError::not_found(
+                "AbTestNotFound",
+                format!("No A/B test found with ID: {}", id),
+            )
+        })
+}
+
+fn validate_address(address: &str) -> ApiResult<&str> {
+    let address = address.trim();
+    if address.is_empty() || address.len() > 56 {
+        return Err(ApiError::bad_request(
+            "InvalidUserAddress",
+            "user_address must be a Stellar address of at most 56 characters",
+        ));
+    }
+    Ok(address)
+}
+
+fn percent(field: &str, value: f64, low: f64, high: f64) -> ApiResult<Decimal> {
+    if !(low..=high).contains(&value) {
+        return Err(ApiError::bad_request(
+            "InvalidAbTestConfig",
+            format!("{} must be between {} and {}", field, low, high),
+        ));
+    }
+    Ok(Decimal::from_f64(value).unwrap_or_default().round_dp(2))
+}
+
+/// POST /api/ab-tests — create a draft experiment between two deployments.
+pub async fn create_ab_test(
+    State(state): State<AppState>,
+    Json(req): Json<CreateAbTestRequest>,
+) -> ApiResult<(StatusCode, Json<AbTest>)> {
+    if req.name.trim().is_empty() || req.primary_metric.trim().is_empty() {
+        return Err(ApiError::bad_request(
+            "InvalidAbTestConfig",
+            "name and primary_metric are required",
+        ));
+    }
+    let kind = match req.metric_kind.as_deref() {
+        None => MetricKind::Continuous,
+        Some(raw) => MetricKind::parse(raw).ok_or_else(|| {
+            ApiError::bad_request(
+                "InvalidMetricKind",
+                format!(
+                    "Unknown metric kind '{}'; expected conversion or continuous",
+                    raw
+                ),
+            )
+        })?,
+    };
+    let split = percent(
+        "traffic_split",
+        req.traffic_split.unwrap_or(50.0),
+        0.0,
+        100.0,
+    )?;
+    let significance = percent(
+        "significance_threshold",
+        req.significance_threshold.unwrap_or(95.0),
+        50.0,
+        99.99,
+    )?;
+    let power = percent(
+        "statistical_power",
+        req.statistical_power.unwrap_or(80.0),
+        1.0,
+        99.99,
+    )?;
+    let mde = match req.minimum_detectable_effect {
+        Some(mde) if !(mde > 0.0 && mde.is_finite()) => {
+            return Err(ApiError::bad_request(
+                "InvalidAbTestConfig",
+                "minimum_detectable_effect must be positive",
+            ))
+        }
+        Some(mde) => Decimal::from_f64(mde).map(|d| d.round_dp(4)),
+        None => None,
+    };
+    let min_sample_size = req.min_sample_size.unwrap_or(1000);
+    if min_sample_size < 2 {
+        return Err(ApiError::bad_request(
+            "InvalidAbTestConfig",
+            "min_sample_size must be at least 2",
+        ));
+    }
+
+    let (contract_uuid, _) = fetch_contract_identity(&state, &req.contract_id).await?;
+    let control =
+        deployment_of_contract(&state, contract_uuid, &req.variant_a_deployment_id).await?;
+    let treatment =
+        deployment_of_contract(&state, contract_uuid, &req.variant_b_deployment_id).await?;
+    if control == treatment {
+        return Err(ApiError::unprocessable(
+            "SameDeployment",
+            "An A/B test needs two different deployments",
+        ));
+    }
+
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|err| db_internal_error("begin ab test", err))?;
+    let test: AbTest = sqlx::query_as(
+        "INSERT INTO ab_tests
+            (contract_id, name, description, traffic_split, variant_a_deployment_id,
+             variant_b_deployment_id, primary_metric, hypothesis, significance_threshold,
+             min_sample_size, created_by, metric_kind, higher_is_better,
+             minimum_detectable_effect, statistical_power)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+         RETURNING *",
+    )
+    .bind(contract_uuid)
+    .bind(req.name.trim())
+    .bind(&req.description)
+    .bind(split)
+    .bind(control)
+    .bind(treatment)
+    .bind(req.primary_metric.trim())
+    .bind(&req.hypothesis)
+    .bind(significance)
+    .bind(min_sample_size)
+    .bind(&req.created_by)
+    .bind(kind.as_str())
+    .bind(req.higher_is_better.unwrap_or(true))
+    .bind(mde)
+    .bind(power)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| db_internal_error("create ab test", err))?;
+
+    for (variant, deployment, share) in [
+        (VariantType::Control, control, split),
+        (
+            VariantType::Treatment,
+            treatment,
+            Decimal::ONE_HUNDRED - split,
+        ),
+    ] {
+        sqlx::query(
+            "INSERT INTO ab_test_variants (test_id, variant_type, deployment_id, traffic_percentage)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(test.id)
+        .bind(variant)
+        .bind(deployment)
+        .bind(share)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| db_internal_error("create ab test variant", err))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|err| db_internal_error("commit ab test", err))?;
+
+    Ok((StatusCode::CREATED, Json(test)))
+}
+
+/// GET /api/ab-tests
+pub async fn list_ab_tests(
+    State(state): State<AppState>,
+    Query(query): Query<AbTestListQuery>,
+) -> ApiResult<Json<Vec<AbTest>>> {
+    let contract_uuid = match query.contract_id.as_deref() {
+        Some(id) => Some(fetch_contract_identity(&state, id).await?.0),
+        None => None,
+    };
+    let tests: Vec<AbTest> = sqlx::query_as(
+        "SELECT * FROM ab_tests
+         WHERE ($1::uuid IS NULL OR contract_id = $1)
+           AND ($2::ab_test_status IS NULL OR status = $2)
+         ORDER BY created_at DESC
+         LIMIT $3",
+    )
+    .bind(contract_uuid)
+    .bind(query.status)
+    .bind(query.limit.unwrap_or(50).clamp(1, 200))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list ab tests", err))?;
+    Ok(Json(tests))
+}
+
+/// GET /api/ab-tests/:id — the experiment with its variants and last results.
+pub async fn get_ab_test(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<AbTestDetail>> {
+    let test = fetch_test(&state, &id).await?;
+    let variants: Vec<AbTestVariant> =
+        sqlx::query_as("SELECT * FROM ab_test_variants WHERE test_id = $1 ORDER BY variant_type")
+            .bind(test.id)
+            .fetch_all(&state.db)
+            .await
+            .map_err(|err| db_internal_error("fetch ab test variants", err))?;
+    let results: Vec<AbTestResult> =
+        sqlx::query_as("SELECT * FROM ab_test_results WHERE test_id = $1 ORDER BY variant_type")
+            .bind(test.id)
+            .fetch_all(&state.db)
+            .await
+            .map_err(|err| db_internal_error("fetch ab test results", err))?;
+    Ok(Json(AbTestDetail {
+        test,
+        variants,
+        results,
+    }))
+}
+
+async fn change_status(
+    state: &AppState,
+    id: &str,
+    from: &[AbTestStatus],
+    to: AbTestStatus,
+) -> ApiResult<Json<AbTest>> {
+    let test = fetch_test(state, id).await?;
+    match ab_testing::transition(&state.db, test.id, from, to).await {
+        Ok(Some(updated)) => Ok(Json(updated)),
+        Ok(None) => Err(ApiError::conflict(
+            "InvalidAbTestTransition",
+            format!(
+                "Cannot move an A/B test from {} to {}",
+                ab_testing::status_name(test.status),
+                ab_testing::status_name(to)
+            ),
+        )),
+        Err(sqlx::Error::Database(db)) if db.is_unique_violation() => Err(ApiError::conflict(
+            "AbTestAlreadyRunning",
+            "This contract already has a running A/B test",
+        )),
+        Err(err) => Err(db_internal_error("update ab test status", err)),
+    }
+}
+
+/// POST /api/ab-tests/:id/start
+pub async fn start_ab_test(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<AbTest>> {
+    use AbTestStatus::*;
+    change_status(&state, &id, &[Draft, Paused], Running).await
+}
+
+/// POST /api/ab-tests/:id/pause
+pub async fn pause_ab_test(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<AbTest>> {
+    use AbTestStatus::*;
+    change_status(&state, &id, &[Running], Paused).await
+}
+
+/// POST /api/ab-tests/:id/cancel
+pub async fn cancel_ab_test(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<AbTest>> {
+    use AbTestStatus::*;
+    change_status(&state, &id, &[Draft, Running, Paused], Cancelled).await
+}
+
+/// GET /api/ab-tests/:id/variant?user_address= — the caller's sticky variant.
+pub async fn get_variant(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<VariantQuery>,
+) -> ApiResult<Json<VariantAssignment>> {
+    let address = validate_address(&query.user_address)?;
+    let test = fetch_test(&state, &id).await?;
+    let variant = ab_testing::assign(&state.db, &test, address)
+        .await
+        .map_err(|err| db_internal_error("assign ab test variant", err))?;
+    let deployment_id = variant.map(|v| match v {
+        VariantType::Control => test.variant_a_deployment_id,
+        VariantType::Treatment => test.variant_b_deployment_id,
+    });
+    Ok(Json(VariantAssignment {
+        test_id: test.id,
+        user_address: address.to_string(),
+        variant,
+        deployment_id,
+    }))
+}
+
+/// POST /api/ab-tests/:id/metrics — record one observation.
+pub async fn record_metric(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<RecordAbTestMetricRequest>,
+) -> ApiResult<StatusCode> {
+    if !req.test_id.is_empty() && req.test_id != id {
+        return Err(ApiError::bad_request(
+            "TestIdMismatch",
+            "test_id in the body does not match the path",
+        ));
+    }
+    if req.metric_name.trim().is_empty() || !req.metric_value.is_finite() {
+        return Err(ApiError::bad_request(
+            "InvalidAbTestMetric",
+            "metric_name is required and metric_value must be a finite number",
+        ));
+    }
+    let test = fetch_test(&state, &id).await?;
+    if test.status != AbTestStatus::Running {
+        return Err(ApiError::conflict(
+            "AbTestNotRunning",
+            format!(
+                "A/B test is {}; metrics are only recorded while running",
+                ab_testing::status_name(test.status)
+            ),
+        ));
+    }
+    let primary = req.metric_name.trim() == test.primary_metric;
+    let conversion = MetricKind::parse(&test.metric_kind) == Some(MetricKind::Conversion);
+    if primary && conversion && req.metric_value != 0.0 && req.metric_value != 1.0 {
+        return Err(ApiError::bad_request(
+            "InvalidAbTestMetric",
+            "conversion metrics take the value 0 or 1",
+        ));
+    }
+
+    let address = req
+        .user_address
+        .as_deref()
+        .map(validate_address)
+        .transpose()?;
+    let variant = match (address, req.variant) {
+        (Some(address), requested) => {
+            let assigned = ab_testing::assign(&state.db, &test, address)
+                .await
+                .map_err(|err| db_internal_error("assign ab test variant", err))?;
+            match (assigned, requested) {
+                (Some(assigned), Some(requested)) if assigned != requested => {
+                    return Err(ApiError::unprocessable(
+                        "VariantMismatch",
+                        format!(
+                            "{} is assigned to {}",
+                            address,
+                            ab_testing::variant_name(assigned)
+                        ),
+                    ))
+                }
+                (Some(assigned), _) => assigned,
+                (None, _) => return Err(ApiError::internal("Caller could not be assigned")),
+            }
+        }
+        (None, Some(variant)) => variant,
+        (None, None) => {
+            return Err(ApiError::bad_request(
+                "InvalidAbTestMetric",
+                "Either user_address or variant is required",
+            ))
+        }
+    };
+
+    sqlx::query(
+        "INSERT INTO ab_test_metrics
+            (test_id, variant_type, metric_name, metric_value, user_address, metadata)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(test.id)
+    .bind(variant)
+    .bind(req.metric_name.trim())
+    .bind(
+        Decimal::from_f64(req.metric_value)
+            .unwrap_or_default()
+            .round_dp(4),
+    )
+    .bind(address)
+    .bind(&req.metadata)
+    .execute(&state.db)
+    .await
+    .map_err(|err| db_internal_error("record ab test metric", err))?;
+
+    if primary && conversion && req.metric_value == 1.0 {
+        AB_TEST_CONVERSIONS
+            .with_label_values(&[&test.id.to_string(), ab_testing::variant_name(variant)])
+            .inc();
+    }
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// GET /api/ab-tests/:id/results — analyse now and apply the stopping rule.
+pub async fn get_results(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<AbTestAnalysis>> {
+    let test = fetch_test(&state, &id).await?;
+    let (test, analysis) = ab_testing::evaluate(&state.db, &test)
+        .await
+        .map_err(|err| db_internal_error("analyse ab test", err))?;
+    Ok(Json(AbTestAnalysis { test, analysis }))
+}
@@ -0,0 +1,641 @@
+//! A/B experiment service
+//!
+//! Callers are assigned to a variant by hashing the experiment and address,
+//! and the first assignment is stored so it sticks even if the split changes.
+//! Observations of the primary metric are analysed with
+//! [`experiment_stats`](crate::experiment_stats); a running experiment stops
+//! once either the efficacy boundary is crossed (declaring a winner) or the
+//! planned sample is reached without a significant difference.
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use shared::{AbTest, AbTestStatus, VariantType};
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::experiment_stats::{self as stats, Comparison, Summary, TestMethod};
+use crate::metrics::{AB_TESTS_ACTIVE, AB_TEST_IMPRESSIONS};
+
+const DEFAULT_EVALUATION_INTERVAL_SECS: u64 = 300;
+/// Buckets per percent; callers are placed in 0..10_000.
+const BUCKETS_PER_PERCENT: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKind {
+    /// Each observation is 0 or 1
+    Conversion,
+    Continuous,
+}
+
+impl MetricKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "conversion" => Some(Self::Conversion),
+            "continuous" => Some(Self::Continuous),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Conversion => "conversion",
+            Self::Continuous => "continuous",
+        }
+    }
+}
+
+pub fn variant_name(variant: VariantType) -> &'static str {
+    match variant {
+        VariantType::Control => "control",
+        VariantType::Treatment => "treatment",
+    }
+}
+
+pub fn status_name(status: AbTestStatus) -> &'static str {
+    match status {
+        AbTestStatus::Draft => "draft",
+        AbTestStatus::Running => "running",
+        AbTestStatus::Paused => "paused",
+        AbTestStatus::Completed => "completed",
+        AbTestStatus::Cancelled => "cancelled",
+    }
+}
+
+/// Stable position of `address` in 0..10_000 for this experiment.
+pub fn bucket(test_id: Uuid, address: &str) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(test_id.as_bytes())
+        .chain_update(b":")
+        .chain_update(address.trim().as_bytes())
+        .finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(prefix) % (100 * BUCKETS_PER_PERCENT)
+}
+
+/// `traffic_split` is the control share in percent.
+pub fn variant_for_bucket(bucket: u64, traffic_split: f64) -> VariantType {
+    if (bucket as f64) < traffic_split * BUCKETS_PER_PERCENT as f64 {
+        VariantType::Control
+    } else {
+        VariantType::Treatment
+    }
+}
+
+/// The experiment's analysis settings, decoupled from the row for testing.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub kind: MetricKind,
+    /// Two-sided false-positive rate
+    pub alpha: f64,
+    pub power: f64,
+    pub minimum_detectable_effect: Option<f64>,
+    pub min_sample_size: u64,
+    pub higher_is_better: bool,
+}
+
+impl Plan {
+    pub fn of(test: &AbTest) -> Self {
+        let confidence = test.significance_threshold.to_f64().unwrap_or(95.0);
+        Self {
+            kind: MetricKind::parse(&test.metric_kind).unwrap_or(MetricKind::Continuous),
+            alpha: (1.0 - confidence / 100.0).clamp(1e-6, 0.5),
+            power: test.statistical_power.to_f64().unwrap_or(80.0) / 100.0,
+            minimum_detectable_effect: test.minimum_detectable_effect.and_then(|d| d.to_f64()),
+            min_sample_size: test.min_sample_size.max(2) as u64,
+            higher_is_better: test.higher_is_better,
+        }
+    }
+
+    fn method(&self) -> TestMethod {
+        match self.kind {
+            MetricKind::Conversion => TestMethod::TwoProportionZTest,
+            MetricKind::Continuous => TestMethod::WelchTTest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantStats {
+    pub variant: VariantType,
+    pub sample_size: u64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub confidence_interval: (f64, f64),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Decision {
+    Continue {
+        reason: String,
+    },
+    Winner {
+        variant: VariantType,
+        reason: String,
+    },
+    Inconclusive {
+        reason: String,
+    },
+}
+
+impl Decision {
+    pub fn is_final(&self) -> bool {
+        !matches!(self, Decision::Continue { .. })
+    }
+
+    pub fn winner(&self) -> Option<VariantType> {
+        match self {
+            Decision::Winner { variant, .. } => Some(*variant),
+            _ => None,
+        }
+    }
+
+    pub fn reason(&self) -> &str {
+        match self {
+            Decision::Continue { reason }
+            | Decision::Winner { reason, .. }
+            | Decision::Inconclusive { reason } => reason,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Analysis {
+    pub method: TestMethod,
+    pub confidence_level: f64,
+    pub control: VariantStats,
+    pub treatment: VariantStats,
+    /// Absent until both variants have at least two observations
+    pub comparison: Option<Comparison>,
+    /// (treatment − control) / control
+    pub relative_lift: Option<f64>,
+    /// Per variant, for the minimum detectable effect at the target power
+    pub required_sample_size: Option<u64>,
+    /// Per variant; the larger of `min_sample_size` and the required size
+    pub planned_sample_size: u64,
+    /// Power of the current samples to detect the minimum detectable effect
+    pub power: Option<f64>,
+    pub information_fraction: f64,
+    /// |z| the result must reach at this information fraction
+    pub efficacy_boundary: f64,
+    pub decision: Decision,
+}
+
+fn variant_stats(variant: VariantType, summary: &Summary, plan: &Plan) -> VariantStats {
+    let confidence_interval = match plan.kind {
+        MetricKind::Conversion => stats::wilson_interval(summary, plan.alpha),
+        MetricKind::Continuous => stats::mean_interval(summary, plan.alpha),
+    };
+    VariantStats {
+        variant,
+        sample_size: summary.n,
+        mean: summary.mean,
+        std_dev: summary.std_dev(),
+        confidence_interval,
+    }
+}
+
+/// Analyse the primary metric and apply the stopping rule.
+///
+/// No decision is taken before each variant has `min_sample_size`
+/// observations. After that, a result whose |z| crosses the O'Brien–Fleming
+/// boundary for the current information fraction declares a winner; reaching
+/// the planned sample without crossing it ends the experiment inconclusive.
+pub fn analyse(plan: &Plan, control: Summary, treatment: Summary) -> Analysis {
+    let required_sample_size = plan
+        .minimum_detectable_effect
+        .and_then(|mde| match plan.kind {
+            MetricKind::Conversion => {
+                stats::sample_size_proportions(control.mean, mde, plan.alpha, plan.power)
+            }
+            MetricKind::Continuous => {
+                let pooled_sd = ((control.variance + treatment.variance) / 2.0).sqrt();
+                stats::sample_size_means(pooled_sd, mde, plan.alpha, plan.power)
+            }
+        });
+    let planned_sample_size = required_sample_size.unwrap_or(0).max(plan.min_sample_size);
+
+    let smallest = control.n.min(treatment.n);
+    let information_fraction = (smallest as f64 / planned_sample_size as f64).min(1.0);
+    let efficacy_boundary = stats::efficacy_boundary(plan.alpha, information_fraction);
+
+    let comparison = (smallest >= 2).then(|| match plan.kind {
+        MetricKind::Conversion => stats::two_proportion_z_test(&control, &treatment, plan.alpha),
+        MetricKind::Continuous => stats::welch_t_test(&control, &treatment, plan.alpha),
+    });
+    let relative_lift = comparison
+        .filter(|_| control.mean != 0.0)
+        .map(|c| c.difference / control.mean.abs());
+    let power = match (plan.minimum_detectable_effect, smallest >= 2) {
+        (Some(mde), true) => Some(stats::achieved_power(&control, &treatment, mde, plan.alpha)),
+        _ => None,
+    };
+
+    let decision = match comparison {
+        _ if smallest < plan.min_sample_size => Decision::Continue {
+            reason: format!(
+                "collecting data: {} of {} observations per variant",
+                smallest, plan.min_sample_size
+            ),
+        },
+        Some(c) if c.z_equivalent() >= efficacy_boundary => {
+            let treatment_better = (c.difference > 0.0) == plan.higher_is_better;
+            let variant = if treatment_better {
+                VariantType::Treatment
+            } else {
+                VariantType::Control
+            };
+            Decision::Winner {
+                variant,
+                reason: format!(
+                    "{} is better (p = {:.4}); |z| {:.2} crossed the boundary {:.2} at {:.0}% of the planned sample",
+                    variant_name(variant),
+                    c.p_value,
+                    c.z_equivalent(),
+                    efficacy_boundary,
+                    information_fraction * 100.0
+                ),
+            }
+        }
+        Some(c) if information_fraction >= 1.0 => Decision::Inconclusive {
+            reason: format!(
+                "no significant difference (p = {:.4}) after the planned {} observations per variant",
+                c.p_value, planned_sample_size
+            ),
+        },
+        _ => Decision::Continue {
+            reason: format!(
+                "not significant yet at {:.0}% of the planned {} observations per variant",
+                information_fraction * 100.0,
+                planned_sample_size
+            ),
+        },
+    };
+
+    Analysis {
+        method: plan.method(),
+        confidence_level: (1.0 - plan.alpha) * 100.0,
+        control: variant_stats(VariantType::Control, &control, plan),
+        treatment: variant_stats(VariantType::Treatment, &treatment, plan),
+        comparison,
+        relative_lift,
+        required_sample_size,
+        planned_sample_size,
+        power,
+        information_fraction,
+        efficacy_boundary,
+        decision,
+    }
+}
+
+fn decimal(value: f64, dp: u32) -> Option<Decimal> {
+    value
+        .is_finite()
+        .then(|| Decimal::from_f64(value))
+        .flatten()
+        .map(|d| d.round_dp(dp))
+}
+
+pub async fn fetch(pool: &PgPool, test_id: Uuid) -> Result<Option<AbTest>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM ab_tests WHERE id = $1")
+        .bind(test_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Move an experiment to `to` if it is currently in one of `from`.
+pub async fn transition(
+    pool: &PgPool,
+    test_id: Uuid,
+    from: &[AbTestStatus],
+    to: AbTestStatus,
+) -> Result<Option<AbTest>, sqlx::Error> {
+    let from: Vec<&str> = from.iter().map(|s| status_name(*s)).collect();
+    let test = sqlx::query_as(
+        "UPDATE ab_tests
+         SET status = $2,
+             started_at = CASE WHEN $2 = 'running'::ab_test_status
+                               THEN COALESCE(started_at, NOW()) ELSE started_at END,
+             ended_at = CASE WHEN $2 IN ('completed'::ab_test_status, 'cancelled'::ab_test_status)
+                             THEN NOW() ELSE ended_at END
+         WHERE id = $1 AND status::text = ANY($3)
+         RETURNING *",
+    )
+    .bind(test_id)
+    .bind(to)
+    .bind(&from)
+    .fetch_optional(pool)
+    .await?;
+    refresh_active_gauge(pool).await;
+    Ok(test)
+}
+
+async fn refresh_active_gauge(pool: &PgPool) {
+    if let Ok(count) =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM ab_tests WHERE status = 'running'")
+            .fetch_one(pool)
+            .await
+    {
+        AB_TESTS_ACTIVE.set(count);
+    }
+}
+
+/// The caller's variant, assigning one on first sight. Only running
+/// experiments assign; otherwise an existing assignment is returned, if any.
+pub async fn assign(
+    pool: &PgPool,
+    test: &AbTest,
+    address: &str,
+) -> Result<Option<VariantType>, sqlx::Error> {
+    let existing: Option<VariantType> = sqlx::query_scalar(
+        "SELECT variant_type FROM ab_test_assignments WHERE test_id = $1 AND user_address = $2",
+    )
+    .bind(test.id)
+    .bind(address)
+    .fetch_optional(pool)
+    .await?;
+    if existing.is_some() || test.status != AbTestStatus::Running {
+        return Ok(existing);
+    }
+
+    let split = test.traffic_split.to_f64().unwrap_or(50.0);
+    let variant = variant_for_bucket(bucket(test.id, address), split);
+    let inserted = sqlx::query(
+        "INSERT INTO ab_test_assignments (test_id, user_address, variant_type)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (test_id, user_address) DO NOTHING",
+    )
+    .bind(test.id)
+    .bind(address)
+    .bind(variant)
+    .execute(pool)
+    .await?;
+    if inserted.rows_affected() == 1 {
+        AB_TEST_IMPRESSIONS
+            .with_label_values(&[&test.id.to_string(), variant_name(variant)])
+            .inc();
+        return Ok(Some(variant));
+    }
+
+    // Lost a race with a concurrent first request; the stored row wins.
+    sqlx::query_scalar(
+        "SELECT variant_type FROM ab_test_assignments WHERE test_id = $1 AND user_address = $2",
+    )
+    .bind(test.id)
+    .bind(address)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Per-variant summaries of the primary metric.
+pub async fn summaries(pool: &PgPool, test: &AbTest) -> Result<(Summary, Summary), sqlx::Error> {
+    let rows: Vec<(VariantType, i64, Option<f64>, Option<f64>)> = sqlx::query_as(
+        "SELECT variant_type, COUNT(*), AVG(metric_value)::FLOAT8,
+                VAR_SAMP(metric_value)::FLOAT8
+         FROM ab_test_metrics
+         WHERE test_id = $1 AND metric_name = $2
+         GROUP BY variant_type",
+    )
+    .bind(test.id)
+    .bind(&test.primary_metric)
+    .fetch_all(pool)
+    .await?;
+
+    let mut control = Summary::default();
+    let mut treatment = Summary::default();
+    for (variant, n, mean, variance) in rows {
+        let summary = Summary {
+            n: n.max(0) as u64,
+            mean: mean.unwrap_or(0.0),
+            variance: variance.unwrap_or(0.0),
+        };
+        match variant {
+            VariantType::Control => control = summary,
+            VariantType::Treatment => treatment = summary,
+        }
+    }
+    Ok((control, treatment))
+}
+
+async fn store_results(
+    pool: &PgPool,
+    test: &AbTest,
+    analysis: &Analysis,
+) -> Result<(), sqlx::Error> {
+    let comparison = analysis.comparison.as_ref();
+    let p_value = comparison.map(|c| c.p_value);
+    let winner = analysis.decision.winner();
+
+    let mut tx = pool.begin().await?;
+    for stats in [&analysis.control, &analysis.treatment] {
+        sqlx::query(
+            "INSERT INTO ab_test_results
+                (test_id, variant_type, sample_size, mean_value, std_deviation,
+                 confidence_interval_lower, confidence_interval_upper, p_value,
+                 statistical_significance, is_winner, test_method, test_statistic, calculated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW())
+             ON CONFLICT (test_id, variant_type) DO UPDATE SET
+                sample_size = EXCLUDED.sample_size,
+                mean_value = EXCLUDED.mean_value,
+                std_deviation = EXCLUDED.std_deviation,
+                confidence_interval_lower = EXCLUDED.confidence_interval_lower,
+                confidence_interval_upper = EXCLUDED.confidence_interval_upper,
+                p_value = EXCLUDED.p_value,
+                statistical_significance = EXCLUDED.statistical_significance,
+                is_winner = EXCLUDED.is_winner,
+                test_method = EXCLUDED.test_method,
+                test_statistic = EXCLUDED.test_statistic,
+                calculated_at = NOW()",
+        )
+        .bind(test.id)
+        .bind(stats.variant)
+        .bind(stats.sample_size.min(i32::MAX as u64) as i32)
+        .bind(decimal(stats.mean, 4))
+        .bind(decimal(stats.std_dev, 4))
+        .bind(decimal(stats.confidence_interval.0, 4))
+        .bind(decimal(stats.confidence_interval.1, 4))
+        .bind(p_value.and_then(|p| decimal(p, 6)))
+        .bind(p_value.and_then(|p| decimal((1.0 - p) * 100.0, 2)))
+        .bind(winner == Some(stats.variant))
+        .bind(analysis.method.as_str())
+        .bind(comparison.and_then(|c| decimal(c.statistic, 6)))
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Analyse an experiment, store the results and, if it is running and the
+/// stopping rule says so, complete it.
+pub async fn evaluate(pool: &PgPool, test: &AbTest) -> Result<(AbTest, Analysis), sqlx::Error> {
+    let (control, treatment) = summaries(pool, test).await?;
+    let analysis = analyse(&Plan::of(test), control, treatment);
+    store_results(pool, test, &analysis).await?;
+
+    if test.status != AbTestStatus::Running || !analysis.decision.is_final() {
+        return Ok((test.clone(), analysis));
+    }
+    let completed: Option<AbTest> = sqlx::query_as(
+        "UPDATE ab_tests
+         SET status = 'completed', ended_at = NOW(), winner = $2, stop_reason = $3
+         WHERE id = $1 AND status = 'running'
+         RETURNING *",
+    )
+    .bind(test.id)
+    .bind(analysis.decision.winner())
+    .bind(analysis.decision.reason())
+    .fetch_optional(pool)
+    .await?;
+    refresh_active_gauge(pool).await;
+    if completed.is_some() {
+        tracing::info!(
+            test_id = %test.id,
+            winner = ?analysis.decision.winner(),
+            reason = analysis.decision.reason(),
+            "ab test stopped"
+        );
+    }
+    Ok((completed.unwrap_or_else(|| test.clone()), analysis))
+}
+
+pub async fn evaluate_running(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let tests: Vec<AbTest> = sqlx::query_as("SELECT * FROM ab_tests WHERE status = 'running'")
+        .fetch_all(pool)
+        .await?;
+    let mut stopped = 0;
+    for test in &tests {
+        match evaluate(pool, test).await {
+            Ok((updated, _)) if updated.status == AbTestStatus::Completed => stopped += 1,
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!(test_id = %test.id, error = %err, "ab test evaluation failed")
+            }
+        }
+    }
+    Ok(stopped)
+}
+
+/// Periodically re-analyse running experiments and apply the stopping rule.
+pub fn spawn_ab_test_evaluator(pool: PgPool) {
+    let interval_secs = std::env::var("AB_TEST_EVALUATION_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_EVALUATION_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match evaluate_running(&pool).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(count = n, "ab tests: stopped experiments"),
+                Err(err) => tracing::error!(error = ?err, "ab tests: evaluation failed"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(kind: MetricKind) -> Plan {
+        Plan {
+            kind,
+            alpha: 0.05,
+            power: 0.8,
+            minimum_detectable_effect: None,
+            min_sample_size: 100,
+            higher_is_better: true,
+        }
+    }
+
+    fn conversions(n: u64, rate: f64) -> Summary {
+        Summary {
+            n,
+            mean: rate,
+            variance: rate * (1.0 - rate) * n as f64 / (n - 1) as f64,
+        }
+    }
+
+    #[test]
+    fn assignment_is_sticky_and_follows_split() {
+        let id = Uuid::new_v4();
+        assert_eq!(bucket(id, "GABC"), bucket(id, " GABC "));
+        let control = (0..10_000)
+            .filter(|i| {
+                variant_for_bucket(bucket(id, &format!("G{}", i)), 20.0) == VariantType::Control
+            })
+            .count();
+        assert!((1_800..2_200).contains(&control), "{}", control);
+        assert_eq!(variant_for_bucket(0, 0.0), VariantType::Treatment);
+        assert_eq!(variant_for_bucket(9_999, 100.0), VariantType::Control);
+    }
+
+    #[test]
+    fn waits_for_minimum_sample() {
+        let analysis = analyse(
+            &plan(MetricKind::Conversion),
+            conversions(50, 0.1),
+            conversions(50, 0.9),
+        );
+        assert!(matches!(analysis.decision, Decision::Continue { .. }));
+    }
+
+    #[test]
+    fn declares_winner_in_metric_direction() {
+        let control = conversions(5_000, 0.10);
+        let treatment = conversions(5_000, 0.13);
+        let analysis = analyse(&plan(MetricKind::Conversion), control, treatment);
+        assert_eq!(analysis.decision.winner(), Some(VariantType::Treatment));
+        assert_eq!(analysis.method, TestMethod::TwoProportionZTest);
+
+        let lower_is_better = Plan {
+            higher_is_better: false,
+            ..plan(MetricKind::Conversion)
+        };
+        let analysis = analyse(&lower_is_better, control, treatment);
+        assert_eq!(analysis.decision.winner(), Some(VariantType::Control));
+    }
+
+    #[test]
+    fn early_looks_need_stronger_evidence() {
+        // p ≈ 0.01 would pass a fixed-sample test but not at a quarter of the plan.
+        let plan = Plan {
+            minimum_detectable_effect: Some(0.5),
+            min_sample_size: 200,
+            ..plan(MetricKind::Continuous)
+        };
+        let control = Summary {
+            n: 400,
+            mean: 10.0,
+            variance: 25.0,
+        };
+        let treatment = Summary {
+            n: 400,
+            mean: 10.9,
+            variance: 25.0,
+        };
+        let analysis = analyse(&plan, control, treatment);
+        assert_eq!(analysis.required_sample_size, Some(1_570));
+        assert!(analysis.comparison.unwrap().p_value < 0.05);
+        assert!(matches!(analysis.decision, Decision::Continue { .. }));
+
+        let control = Summary {
+            n: 1_570,
+            ..control
+        };
+        let treatment = Summary {
+            n: 1_570,
+            mean: 10.2,
+            ..treatment
+        };
+        let analysis = analyse(&plan, control, treatment);
+        assert!(matches!(analysis.decision, Decision::Inconclusive { .. }));
+    }
+}
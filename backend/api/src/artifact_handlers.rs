@@ -0,0 +1,442 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::Serialize;
+use shared::ContractVersion;
+
+use crate::artifact_store::{self, Artifact, ArtifactError, ArtifactKind};
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::{db_internal_error, fetch_contract_identity};
+use crate::signing_handlers::create_signing_message;
+use crate::state::AppState;
+
+/// Base64 Ed25519 signature by the version's `publisher_key` over
+/// `{contract_id}:{version}:{sha256}` of the uploaded bytes. For a WASM
+/// upload that is the version's own signature.
+pub const PUBLISHER_SIGNATURE_HEADER: &str = "x-publisher-signature";
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactUploadResponse {
+    pub contract_id: String,
+    pub version: String,
+    #[serde(flatten)]
+    pub artifact: Artifact,
+    /// True when identical bytes were already stored
+    pub deduplicated: bool,
+}
+
+pub(crate) fn artifact_error(err: ArtifactError) -> ApiError {
+    match err {
+        ArtifactError::HashMismatch { expected, actual } => ApiError::unprocessable(
+            "ArtifactHashMismatch",
+            format!(
+                "Uploaded bytes hash to {} but {} was expected",
+                actual, expected
+            ),
+        ),
+        ArtifactError::Invalid(message) => ApiError::bad_request("InvalidArtifact", message),
+        ArtifactError::Database(err) => db_internal_error("artifact metadata", err),
+        err @ (ArtifactError::Missing(_) | ArtifactError::Backend(_)) => {
+            tracing::error!(error = %err, "artifact store operation failed");
+            ApiError::internal("Artifact storage is unavailable")
+        }
+    }
+}
+
+/// Decode a base64 upload field from a JSON request body.
+pub(crate) fn decode_upload(field: &str, value: &str) -> ApiResult<Vec<u8>> {
+    BASE64.decode(value.trim()).map_err(|_| {
+        ApiError::bad_request(
+            "InvalidArtifact",
+            format!("{} must be valid base64", field),
+        )
+    })
+}
+
+/// Store an uploaded artifact, checking it against `expected_sha256`.
+pub(crate) async fn ingest_upload(
+    state: &AppState,
+    kind: ArtifactKind,
+    bytes: &[u8],
+    expected_sha256: Option<&str>,
+) -> ApiResult<(Artifact, bool)> {
+    let (artifact, deduplicated) = artifact_store::ingest(
+        &state.db,
+        state.artifacts.as_ref(),
+        kind,
+        bytes,
+        expected_sha256,
+    )
+    .await
+    .map_err(artifact_error)?;
+    tracing::info!(
+        sha256 = %artifact.sha256,
+        kind = kind.as_str(),
+        size_bytes = artifact.size_bytes,
+        deduplicated,
+        "artifact ingested"
+    );
+    Ok((artifact, deduplicated))
+}
+
+async fn fetch_version(
+    state: &AppState,
+    id: &str,
+    version: &str,
+) -> ApiResult<(String, ContractVersion)> {
+    let (contract_uuid, contract_id) = fetch_contract_identity(state, id).await?;
+    let row: Option<ContractVersion> = sqlx::query_as(
+        "SELECT * FROM contract_versions WHERE contract_id = $1 AND version = $2",
+    )
+    .bind(contract_uuid)
+    .bind(version)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|err| db_internal_error("fetch contract version", err))?;
+    let row = row.ok_or_else(|| {
+        ApiError::not_found(
+            "VersionNotFound",
+            format!("Version '{}' not found for contract {}", version, contract_id),
+        )
+    })?;
+    Ok((contract_id, row))
+}
+
+/// Check that the version's publisher signed the uploaded bytes.
+fn authorize_upload(
+    contract_id: &str,
+    version: &ContractVersion,
+    sha256: &str,
+    headers: &HeaderMap,
+) -> ApiResult<()> {
+    let forbidden =
+        |error: &str, message: &str| ApiError::new(StatusCode::FORBIDDEN, error, message);
+    let publisher_key = version
+        .publisher_key
+        .as_deref()
+        .and_then(|key| BASE64.decode(key.trim()).ok())
+        .and_then(|key| <[u8; 32]>::try_from(key.as_slice()).ok())
+        .and_then(|key| VerifyingKey::from_bytes(&key).ok())
+        .ok_or_else(|| {
+            forbidden(
+                "PublisherKeyRequired",
+                "Artifacts can only be uploaded to versions registered with a publisher_key",
+            )
+        })?;
+    let signature = headers
+        .get(PUBLISHER_SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "MissingSignature",
+                format!(
+                    "Uploads must carry the publisher's signature in {}",
+                    PUBLISHER_SIGNATURE_HEADER
+                ),
+            )
+        })?;
+    let signature = BASE64
+        .decode(signature.trim())
+        .ok()
+        .and_then(|sig| <[u8; 64]>::try_from(sig.as_slice()).ok())
+        .map(|sig| Signature::from_bytes(&sig))
+        .ok_or_else(|| {
+            ApiError::bad_request(
+                "InvalidSignature",
+                "Signature must be a base64-encoded 64-byte Ed25519 signature",
+            )
+        })?;
+    let message = create_signing_message(sha256, contract_id, &version.version);
+    publisher_key.verify(&message, &signature).map_err(|_| {
+        forbidden(
+            "InvalidSignature",
+            "Signature does not verify against the version's publisher_key for these bytes",
+        )
+    })
+}
+
+fn attached(kind: ArtifactKind, version: &ContractVersion) -> Option<&str> {
+    match kind {
+        ArtifactKind::Wasm => version.wasm_artifact_sha256.as_deref(),
+        ArtifactKind::Source => version.source_artifact_sha256.as_deref(),
+    }
+}
+
+fn file_name(kind: ArtifactKind, contract_id: &str, version: &str) -> String {
+    match kind {
+        ArtifactKind::Wasm => format!("{}-{}.wasm", contract_id, version),
+        ArtifactKind::Source => format!("{}-{}-source.tar.gz", contract_id, version),
+    }
+}
+
+async fn download(
+    state: AppState,
+    id: String,
+    version: String,
+    headers: HeaderMap,
+    kind: ArtifactKind,
+) -> ApiResult<Response> {
+    let (contract_id, row) = fetch_version(&state, &id, &version).await?;
+    let sha256 = attached(kind, &row).ok_or_else(|| {
+        ApiError::not_found(
+            "ArtifactNotFound",
+            format!(
+                "No {} artifact has been uploaded for {}@{}",
+                kind.as_str(),
+                contract_id,
+                version
+            ),
+        )
+    })?;
+
+    let etag = format!("\"{}\"", sha256);
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim() == etag));
+    let mut response = if unchanged {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        let bytes = artifact_store::fetch(state.artifacts.as_ref(), sha256)
+            .await
+            .map_err(artifact_error)?;
+        (
+            [
+                (header::CONTENT_TYPE, kind.content_type().to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"{}\"",
+                        file_name(kind, &contract_id, &version)
+                    ),
+                ),
+            ],
+            bytes,
+        )
+            .into_response()
+    };
+
+    let out = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        out.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(sha256) {
+        out.insert("x-content-sha256", value);
+    }
+    // The WASM behind a version is fixed by its wasm_hash; the publisher may
+    // replace the source, so clients revalidate it by ETag.
+    out.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(match kind {
+            ArtifactKind::Wasm => "public, max-age=31536000, immutable",
+            ArtifactKind::Source => "no-cache",
+        }),
+    );
+    Ok(response)
+}
+
+async fn upload(
+    state: AppState,
+    id: String,
+    version: String,
+    headers: HeaderMap,
+    body: Bytes,
+    kind: ArtifactKind,
+) -> ApiResult<(StatusCode, Json<ArtifactUploadResponse>)> {
+    let (contract_id, row) = fetch_version(&state, &id, &version).await?;
+    // Nothing is stored until the publisher's signature over the bytes checks.
+    authorize_upload(
+        &contract_id,
+        &row,
+        &artifact_store::sha256_hex(&body),
+        &headers,
+    )?;
+    // The WASM must be the binary whose hash was registered for the version.
+    let expected = match kind {
+        ArtifactKind::Wasm => Some(row.wasm_hash.as_str()),
+        ArtifactKind::Source => None,
+    };
+    let (artifact, deduplicated) = ingest_upload(&state, kind, &body, expected).await?;
+
+    // A signed upload replaces whatever was attached before.
+    let previous = attached(kind, &row);
+    if previous != Some(artifact.sha256.as_str()) {
+        artifact_store::attach_to_version(&state.db, row.id, kind, &artifact.sha256)
+            .await
+            .map_err(artifact_error)?;
+        tracing::info!(
+            contract_id = %contract_id,
+            version = %version,
+            kind = kind.as_str(),
+            sha256 = %artifact.sha256,
+            previous = ?previous,
+            "artifact attached to version"
+        );
+    }
+
+    let status = if deduplicated {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+    Ok((
+        status,
+        Json(ArtifactUploadResponse {
+            contract_id,
+            version,
+            artifact,
+            deduplicated,
+        }),
+    ))
+}
+
+/// GET /api/contracts/:id/versions/:version/wasm
+pub async fn get_version_wasm(
+    State(state): State<AppState>,
+    Path((id, version)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    download(state, id, version, headers, ArtifactKind::Wasm).await
+}
+
+/// GET /api/contracts/:id/versions/:version/source.tar.gz
+pub async fn get_version_source(
+    State(state): State<AppState>,
+    Path((id, version)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    download(state, id, version, headers, ArtifactKind::Source).await
+}
+
+/// PUT /api/contracts/:id/versions/:version/wasm — raw WASM body whose
+/// SHA-256 must match the version's `wasm_hash`, signed by the publisher.
+pub async fn put_version_wasm(
+    State(state): State<AppState>,
+    Path((id, version)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<(StatusCode, Json<ArtifactUploadResponse>)> {
+    upload(state, id, version, headers, body, ArtifactKind::Wasm).await
+}
+
+/// PUT /api/contracts/:id/versions/:version/source.tar.gz — raw gzip
+/// tarball body signed by the publisher; replaces any earlier source.
+pub async fn put_version_source(
+    State(state): State<AppState>,
+    Path((id, version)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<(StatusCode, Json<ArtifactUploadResponse>)> {
+    upload(state, id, version, headers, body, ArtifactKind::Source).await
+}
+
+/// GET /api/artifacts/:sha256 — metadata for a stored artifact.
+pub async fn get_artifact(
+    State(state): State<AppState>,
+    Path(sha256): Path<String>,
+) -> ApiResult<Json<Artifact>> {
+    artifact_store::find(&state.db, &sha256.to_ascii_lowercase())
+        .await
+        .map_err(artifact_error)?
+        .map(Json)
+        .ok_or_else(|| {
+            ApiError::not_found(
+                "ArtifactNotFound",
+                format!("No artifact stored with SHA-256 {}", sha256),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn hash_mismatch_is_unprocessable() {
+        let err = artifact_error(ArtifactError::HashMismatch {
+            expected: "a".repeat(64),
+            actual: "b".repeat(64),
+        });
+        assert_eq!(err.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn store_failures_do_not_leak_details() {
+        let err = artifact_error(ArtifactError::Backend("s3://secret-bucket".into()));
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!err.to_string().contains("secret-bucket"));
+    }
+
+    fn version(publisher: &SigningKey) -> ContractVersion {
+        ContractVersion {
+            id: uuid::Uuid::new_v4(),
+            contract_id: uuid::Uuid::new_v4(),
+            version: "1.0.0".into(),
+            wasm_hash: "a".repeat(64),
+            source_url: None,
+            commit_hash: None,
+            release_notes: None,
+            created_at: chrono::Utc::now(),
+            state_schema: None,
+            signature: None,
+            publisher_key: Some(BASE64.encode(publisher.verifying_key().to_bytes())),
+            signature_algorithm: None,
+            wasm_artifact_sha256: None,
+            source_artifact_sha256: None,
+        }
+    }
+
+    fn signed(key: &SigningKey, sha256: &str) -> HeaderMap {
+        let message = create_signing_message(sha256, "C1", "1.0.0");
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            PUBLISHER_SIGNATURE_HEADER,
+            HeaderValue::from_str(&BASE64.encode(key.sign(&message).to_bytes())).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn uploads_need_the_publishers_signature_over_the_bytes() {
+        let publisher = SigningKey::from_bytes(&[7; 32]);
+        let row = version(&publisher);
+        let sha256 = artifact_store::sha256_hex(b"source");
+
+        assert!(authorize_upload("C1", &row, &sha256, &signed(&publisher, &sha256)).is_ok());
+
+        let missing = authorize_upload("C1", &row, &sha256, &HeaderMap::new()).unwrap_err();
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+
+        // Someone else's key, or the publisher's signature over other bytes.
+        let stranger = SigningKey::from_bytes(&[8; 32]);
+        let forged = authorize_upload("C1", &row, &sha256, &signed(&stranger, &sha256));
+        assert_eq!(forged.unwrap_err().status(), StatusCode::FORBIDDEN);
+        let other = artifact_store::sha256_hex(b"other");
+        let reused = authorize_upload("C1", &row, &sha256, &signed(&publisher, &other));
+        assert_eq!(reused.unwrap_err().status(), StatusCode::FORBIDDEN);
+
+        let unowned = ContractVersion {
+            publisher_key: None,
+            ..row
+        };
+        let err = authorize_upload("C1", &unowned, &sha256, &signed(&publisher, &sha256));
+        assert_eq!(err.unwrap_err().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn download_names_include_contract_and_version() {
+        assert_eq!(file_name(ArtifactKind::Wasm, "C1", "1.0.0"), "C1-1.0.0.wasm");
+        assert_eq!(
+            file_name(ArtifactKind::Source, "C1", "1.0.0"),
+            "C1-1.0.0-source.tar.gz"
+        );
+    }
+}
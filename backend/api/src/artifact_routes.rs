@@ -0,0 +1,19 @@
+use axum::{extract::DefaultBodyLimit, routing::get, Router};
+
+use crate::{artifact_handlers, artifact_store::MAX_SOURCE_BYTES, state::AppState};
+
+pub fn artifact_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/contracts/:id/versions/:version/wasm",
+            get(artifact_handlers::get_version_wasm).put(artifact_handlers::put_version_wasm),
+        )
+        .route(
+            "/api/contracts/:id/versions/:version/source.tar.gz",
+            get(artifact_handlers::get_version_source)
+                .put(artifact_handlers::put_version_source),
+        )
+        .route("/api/artifacts/:sha256", get(artifact_handlers::get_artifact))
+        // Uploads are raw bodies, larger than the JSON default allows.
+        .layer(DefaultBodyLimit::max(MAX_SOURCE_BYTES))
+}
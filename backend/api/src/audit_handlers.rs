@@ -127,14 +127,15 @@ pub async fn create_security_audit(
 
         sqlx::query(
             r#"INSERT INTO audit_checks
-                   (audit_id, check_id, status, auto_detected, evidence)
-               VALUES ($1, $2, $3, $4, $5)"#,
+                   (audit_id, check_id, status, auto_detected, evidence, severity)
+               VALUES ($1, $2, $3, $4, $5, $6)"#,
         )
         .bind(audit.id)
         .bind(&item.id)
         .bind(&status)
         .bind(auto_detected)
         .bind(&evidence)
+        .bind(item.severity.to_string().to_lowercase())
         .execute(&state.db)
         .await
         .map_err(|_| ApiError::db_error("Failed to seed audit check rows"))?;
@@ -0,0 +1,260 @@
+//! Appending to and verifying `contract_audit_log`
+//!
+//! Rows are hash-chained per contract in `seq` order (see
+//! [`shared::audit_chain`]) and each new row's hash is signed with the
+//! Ed25519 key in `AUDIT_LOG_SIGNING_KEY` (base64 32-byte seed). Keys that
+//! signed older rows stay trusted for verification when listed, as base64
+//! public keys, in `AUDIT_LOG_RETIRED_KEYS` (comma-separated).
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use serde_json::Value;
+use shared::audit_chain::{self, AuditBundle, ChainReport};
+use shared::transparency;
+use shared::{AuditActionType, ContractAuditLog};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// `changed_by` is a Stellar address or a service name.
+const MAX_ACTOR_LEN: usize = 56;
+
+const ENTRY_COLUMNS: &str = "id, contract_id, action_type, old_value, new_value, changed_by, \
+     timestamp, previous_hash, hash, signature, seq, hash_version, signing_key_id";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditLogError {
+    #[error("audit log signing key is not configured")]
+    SigningKeyMissing,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct AuditSigner {
+    key: SigningKey,
+    key_id: String,
+}
+
+impl AuditSigner {
+    pub fn from_env() -> Option<Self> {
+        let encoded = std::env::var("AUDIT_LOG_SIGNING_KEY").ok()?;
+        let seed: [u8; 32] = match BASE64.decode(encoded.trim()).map(<[u8; 32]>::try_from) {
+            Ok(Ok(seed)) => seed,
+            _ => {
+                tracing::error!("audit log: AUDIT_LOG_SIGNING_KEY is not a base64 32-byte seed");
+                return None;
+            }
+        };
+        Some(Self::new(SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn new(key: SigningKey) -> Self {
+        let key_id = transparency::key_id(key.verifying_key().as_bytes());
+        Self { key, key_id }
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.key.verifying_key()
+    }
+
+    pub fn public_key(&self) -> String {
+        BASE64.encode(self.key.verifying_key().as_bytes())
+    }
+
+    fn sign(&self, message: &[u8]) -> String {
+        BASE64.encode(self.key.sign(message).to_bytes())
+    }
+}
+
+/// Process-wide signer, read from the environment once.
+pub fn signer() -> Option<&'static AuditSigner> {
+    static SIGNER: OnceLock<Option<AuditSigner>> = OnceLock::new();
+    SIGNER.get_or_init(AuditSigner::from_env).as_ref()
+}
+
+/// Keys whose signatures verification accepts, by key id.
+pub fn trusted_keys() -> BTreeMap<String, VerifyingKey> {
+    let mut keys = BTreeMap::new();
+    if let Some(signer) = signer() {
+        keys.insert(signer.key_id.clone(), signer.verifying_key());
+    }
+    let retired = std::env::var("AUDIT_LOG_RETIRED_KEYS").unwrap_or_default();
+    for encoded in retired.split(',').filter(|s| !s.trim().is_empty()) {
+        match audit_chain::decode_public_key(encoded) {
+            Some(key) => {
+                keys.insert(transparency::key_id(key.as_bytes()), key);
+            }
+            None => tracing::warn!(key = encoded, "audit log: ignoring malformed retired key"),
+        }
+    }
+    keys
+}
+
+/// Append one entry inside the caller's transaction, so the audit row
+/// commits (or not) together with the change it describes.
+pub async fn append(
+    tx: &mut Transaction<'_, Postgres>,
+    contract_id: Uuid,
+    action_type: AuditActionType,
+    old_value: Option<Value>,
+    new_value: Option<Value>,
+    changed_by: &str,
+) -> Result<Uuid, sqlx::Error> {
+    let changed_by: String = changed_by.chars().take(MAX_ACTOR_LEN).collect();
+
+    // Serialise writers per contract so two appends cannot share a parent.
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1::text))")
+        .bind(contract_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let previous_hash: Option<String> = sqlx::query_scalar(
+        "SELECT hash FROM contract_audit_log WHERE contract_id = $1 ORDER BY seq DESC LIMIT 1",
+    )
+    .bind(contract_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    .flatten();
+
+    // Postgres keeps microseconds; hash exactly what will be stored.
+    let now = Utc::now();
+    let timestamp = DateTime::from_timestamp_micros(now.timestamp_micros()).unwrap_or(now);
+    let hash = audit_chain::entry_hash_v2(
+        previous_hash.as_deref(),
+        contract_id,
+        &action_type,
+        &changed_by,
+        old_value.as_ref(),
+        new_value.as_ref(),
+        timestamp,
+    );
+    let (signature, signing_key_id) = match signer() {
+        Some(signer) => (
+            Some(signer.sign(&audit_chain::signing_message(&hash))),
+            Some(signer.key_id.clone()),
+        ),
+        None => {
+            tracing::warn!(
+                contract_id = %contract_id,
+                "audit log: AUDIT_LOG_SIGNING_KEY is not set; entry will fail verification"
+            );
+            (None, None)
+        }
+    };
+
+    sqlx::query_scalar(
+        "INSERT INTO contract_audit_log
+               (contract_id, action_type, old_value, new_value, changed_by, timestamp,
+                previous_hash, hash, signature, hash_version, signing_key_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+         RETURNING id",
+    )
+    .bind(contract_id)
+    .bind(&action_type)
+    .bind(&old_value)
+    .bind(&new_value)
+    .bind(&changed_by)
+    .bind(timestamp)
+    .bind(&previous_hash)
+    .bind(&hash)
+    .bind(&signature)
+    .bind(audit_chain::HASH_VERSION)
+    .bind(&signing_key_id)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// A contract's whole chain, oldest first.
+pub async fn load_chain(
+    pool: &PgPool,
+    contract_id: Uuid,
+) -> Result<Vec<ContractAuditLog>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {} FROM contract_audit_log WHERE contract_id = $1 ORDER BY seq ASC",
+        ENTRY_COLUMNS
+    ))
+    .bind(contract_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Last `seq` backfilled when signing was introduced; no unsigned row is
+/// legitimately numbered above it.
+pub async fn legacy_cutoff(pool: &PgPool) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar("SELECT legacy_seq_cutoff FROM audit_chain_settings")
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn verify(pool: &PgPool, contract_id: Uuid) -> Result<ChainReport, sqlx::Error> {
+    let entries = load_chain(pool, contract_id).await?;
+    let cutoff = legacy_cutoff(pool).await?;
+    Ok(audit_chain::verify_chain(&entries, &trusted_keys(), cutoff))
+}
+
+/// Snapshot the chain and sign it as a whole, so the bundle can be checked
+/// offline against the registry's public key.
+pub async fn export_bundle(pool: &PgPool, contract_id: Uuid) -> Result<AuditBundle, AuditLogError> {
+    let signer = signer().ok_or(AuditLogError::SigningKeyMissing)?;
+    let entries = load_chain(pool, contract_id).await?;
+    let head_hash = entries.last().and_then(|e| e.hash.clone());
+    let now = Utc::now();
+    let exported_at = DateTime::from_timestamp_millis(now.timestamp_millis()).unwrap_or(now);
+    let message = AuditBundle::signing_message(
+        contract_id,
+        entries.len(),
+        head_hash.as_deref(),
+        exported_at,
+    );
+    Ok(AuditBundle {
+        contract_id,
+        exported_at,
+        entries,
+        head_hash,
+        key_id: signer.key_id.clone(),
+        signature: signer.sign(&message),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signer_signs_entry_hashes() {
+        let signer = AuditSigner::new(SigningKey::from_bytes(&[9u8; 32]));
+        let hash = audit_chain::entry_hash_v2(
+            None,
+            Uuid::nil(),
+            &AuditActionType::CanaryTransition,
+            "canary-controller",
+            None,
+            Some(&serde_json::json!({ "status": "active" })),
+            DateTime::from_timestamp_micros(1_700_000_000_000_001).unwrap(),
+        );
+        let entry = ContractAuditLog {
+            id: Uuid::new_v4(),
+            contract_id: Uuid::nil(),
+            action_type: AuditActionType::CanaryTransition,
+            old_value: None,
+            new_value: Some(serde_json::json!({ "status": "active" })),
+            changed_by: "canary-controller".into(),
+            timestamp: DateTime::from_timestamp_micros(1_700_000_000_000_001).unwrap(),
+            previous_hash: None,
+            signature: Some(signer.sign(&audit_chain::signing_message(&hash))),
+            hash: Some(hash),
+            seq: 1,
+            hash_version: audit_chain::HASH_VERSION,
+            signing_key_id: Some(signer.key_id().to_string()),
+        };
+        let keys = BTreeMap::from([(signer.key_id().to_string(), signer.verifying_key())]);
+        assert!(audit_chain::verify_chain(&[entry], &keys, Some(0)).valid);
+    }
+}
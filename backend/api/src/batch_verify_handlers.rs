@@ -0,0 +1,201 @@
+// batch_verify_handlers.rs
+// Re-verify several contracts at once (`soroban-registry batch-verify`).
+//
+// Each contract's most recent source submission is queued as a fresh
+// verification job; the response lists one job per contract so callers can
+// follow them through `/api/jobs/:id`.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::{db_internal_error, extract_ip_address};
+use crate::job_worker::VerifyPayload;
+use crate::jobs::{self, JobKind, NewJob};
+use crate::state::AppState;
+
+pub const MAX_BATCH_SIZE: usize = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct BatchVerifyRequest {
+    pub contracts: Vec<BatchContractEntry>,
+    #[serde(default)]
+    pub initiated_by: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchContractEntry {
+    pub contract_id: String,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchVerifyResponse {
+    pub batch_id: Uuid,
+    pub total: usize,
+    pub queued: usize,
+    pub skipped: usize,
+    pub skipped_duplicates: usize,
+    pub results: Vec<BatchEntryResult>,
+    pub initiated_by: String,
+    pub initiated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchEntryResult {
+    pub contract_id: String,
+    pub version: Option<String>,
+    /// `queued` or `skipped`
+    pub status: String,
+    pub job_id: Option<Uuid>,
+    pub verification_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+impl BatchEntryResult {
+    fn skipped(entry: &BatchContractEntry, error: impl Into<String>) -> Self {
+        Self {
+            contract_id: entry.contract_id.clone(),
+            version: entry.version.clone(),
+            status: "skipped".to_string(),
+            job_id: None,
+            verification_id: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// POST /api/contracts/batch-verify
+pub async fn batch_verify_contracts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<BatchVerifyRequest>,
+) -> ApiResult<(StatusCode, Json<BatchVerifyResponse>)> {
+    let mut seen = HashSet::new();
+    let entries: Vec<&BatchContractEntry> = req
+        .contracts
+        .iter()
+        .filter(|entry| !entry.contract_id.trim().is_empty())
+        .filter(|entry| seen.insert(entry.contract_id.trim().to_string()))
+        .collect();
+    let skipped_duplicates = req.contracts.len() - entries.len();
+
+    if entries.is_empty() {
+        return Err(ApiError::bad_request(
+            "EmptyBatch",
+            "Provide at least one contract_id",
+        ));
+    }
+    if entries.len() > MAX_BATCH_SIZE {
+        return Err(ApiError::bad_request(
+            "BatchTooLarge",
+            format!(
+                "Batch size {} exceeds the maximum of {}",
+                entries.len(),
+                MAX_BATCH_SIZE
+            ),
+        ));
+    }
+
+    let batch_id = Uuid::new_v4();
+    let ip_address = extract_ip_address(&headers);
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|err| db_internal_error("begin batch verification", err))?;
+
+    let mut results = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let contract: Option<(Uuid,)> = sqlx::query_as(
+            "SELECT id FROM contracts WHERE contract_id = $1 ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(entry.contract_id.trim())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|err| db_internal_error("fetch contract for batch verification", err))?;
+        let Some((contract_id,)) = contract else {
+            results.push(BatchEntryResult::skipped(entry, "Contract not found"));
+            continue;
+        };
+
+        // Resubmit the latest source the publisher provided.
+        let verification_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO verifications (contract_id, status, source_code, build_params, compiler_version)
+            SELECT contract_id, 'pending', source_code, build_params, compiler_version
+            FROM verifications
+            WHERE contract_id = $1 AND source_code IS NOT NULL
+            ORDER BY created_at DESC
+            LIMIT 1
+            RETURNING id
+            "#,
+        )
+        .bind(contract_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|err| db_internal_error("queue batch verification", err))?;
+        let Some(verification_id) = verification_id else {
+            results.push(BatchEntryResult::skipped(
+                entry,
+                "No source has been submitted for this contract",
+            ));
+            continue;
+        };
+
+        let mut payload = json!(VerifyPayload {
+            verification_id,
+            ip_address: ip_address.clone(),
+        });
+        payload["batch_id"] = json!(batch_id);
+        let job = jobs::enqueue(
+            &mut *tx,
+            NewJob::new(JobKind::VerifyContract, payload).contract(contract_id),
+        )
+        .await
+        .map_err(|err| db_internal_error("enqueue batch verification job", err))?;
+
+        results.push(BatchEntryResult {
+            contract_id: entry.contract_id.clone(),
+            version: entry.version.clone(),
+            status: "queued".to_string(),
+            job_id: Some(job.id),
+            verification_id: Some(verification_id),
+            error: None,
+        });
+    }
+
+    tx.commit()
+        .await
+        .map_err(|err| db_internal_error("commit batch verification", err))?;
+
+    let queued = results.iter().filter(|r| r.job_id.is_some()).count();
+    tracing::info!(
+        batch_id = %batch_id,
+        queued,
+        initiated_by = %req.initiated_by,
+        "batch verification queued"
+    );
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(BatchVerifyResponse {
+            batch_id,
+            total: results.len(),
+            queued,
+            skipped: results.len() - queued,
+            skipped_duplicates,
+            results,
+            initiated_by: req.initiated_by,
+            initiated_at: Utc::now(),
+        }),
+    ))
+}
@@ -184,18 +184,14 @@ pub fn format_cli_output(
         " high variance"
     };
     let mut out = String::new();
-    out.push_str(&format!(
-        "\n╔══ Soroban Registry Benchmark ══════════════════════════╗\n"
-    ));
+    out.push_str("\n╔══ Soroban Registry Benchmark ══════════════════════════╗\n");
     out.push_str(&format!("  Contract : {}\n", contract_id));
     out.push_str(&format!("  Method   : {}()\n", method));
     out.push_str(&format!(
         "  Runs     : {} iterations + warmup\n",
         iterations
     ));
-    out.push_str(&format!(
-        "╠══ Timing (ms) ══════════════════════════════════════════╣\n"
-    ));
+    out.push_str("╠══ Timing (ms) ══════════════════════════════════════════╣\n");
     out.push_str(&format!("  Min      : {:>8.3} ms\n", stats.min_ms));
     out.push_str(&format!("  Max      : {:>8.3} ms\n", stats.max_ms));
     out.push_str(&format!("  Avg      : {:>8.3} ms\n", stats.avg_ms));
@@ -206,14 +202,10 @@ pub fn format_cli_output(
         stats.stddev_ms, consistency
     ));
     if let Some(alert_msg) = alert {
-        out.push_str(&format!(
-            "╠══  REGRESSION ALERT ══════════════════════════════════╣\n"
-        ));
+        out.push_str("╠══  REGRESSION ALERT ══════════════════════════════════╣\n");
         out.push_str(&format!("  {}\n", alert_msg));
     }
-    out.push_str(&format!(
-        "╚═════════════════════════════════════════════════════════╝\n"
-    ));
+    out.push_str("╚═════════════════════════════════════════════════════════╝\n");
     out
 }
 
@@ -1,205 +1,239 @@
 // api/src/benchmark_handlers.rs
 // Axum handlers for contract benchmarking.
-// Follows the same patterns as audit_handlers.rs.
+//
+// POST only validates and queues the benchmark: the iterations run as a
+// `benchmark` job (see `benchmark_runner`) and the response is `202 Accepted`
+// with the queued record and its job_id.
 
 use axum::{
     extract::{Path, Query, State},
+    http::StatusCode,
     Json,
 };
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::FromRow;
 use uuid::Uuid;
 
 use crate::{
-    benchmark_engine::{check_regression, format_cli_output, BenchmarkRunner, BenchmarkStats},
+    benchmark_engine::{format_cli_output, BenchmarkStats},
     error::{ApiError, ApiResult},
+    job_worker::BenchmarkPayload,
+    jobs::{self, JobKind, NewJob},
     state::AppState,
 };
-use crate::models::{
-    BenchmarkComparison, BenchmarkRecord, BenchmarkResponse, BenchmarkRun, BenchmarkStatus,
-    BenchmarkTrendPoint, ContractBenchmarkSummary, PerformanceAlert, RunBenchmarkRequest,
-};
+
+pub const MAX_ITERATIONS: i32 = 1000;
+
+// ─────────────────────────────────────────────────────────
+// Types
+// ─────────────────────────────────────────────────────────
+
+/// Body for POST /api/contracts/:id/benchmarks
+#[derive(Debug, Deserialize)]
+pub struct RunBenchmarkRequest {
+    pub method: String,
+    pub iterations: i32,
+    pub version: Option<String>,
+    pub args_json: Option<serde_json::Value>,
+    #[serde(default = "default_alert_threshold")]
+    pub alert_threshold_pct: f64,
+}
+
+fn default_alert_threshold() -> f64 {
+    10.0
+}
+
+/// A queued or finished benchmark of one contract method.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BenchmarkRecord {
+    pub id: Uuid,
+    pub contract_id: Uuid,
+    pub contract_version: String,
+    pub method_name: String,
+    pub iterations: i32,
+    pub args_json: Option<serde_json::Value>,
+    pub alert_threshold_pct: f64,
+    /// queued, running, completed or failed
+    pub status: String,
+    pub min_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub stddev_ms: Option<f64>,
+    pub error_message: Option<String>,
+    /// The job executing this benchmark (see `/api/jobs/:id`)
+    pub job_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl BenchmarkRecord {
+    /// Statistics of a completed benchmark.
+    pub fn stats(&self) -> Option<BenchmarkStats> {
+        Some(BenchmarkStats {
+            min_ms: self.min_ms?,
+            max_ms: self.max_ms?,
+            avg_ms: self.avg_ms?,
+            p95_ms: self.p95_ms?,
+            p99_ms: self.p99_ms?,
+            stddev_ms: self.stddev_ms?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BenchmarkRun {
+    pub id: Uuid,
+    pub benchmark_id: Uuid,
+    pub iteration: i32,
+    pub execution_time_ms: f64,
+    pub cpu_instructions: Option<i64>,
+    pub memory_bytes: Option<i64>,
+}
+
+/// A p95 regression against the previous completed benchmark of a method.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BenchmarkAlert {
+    pub id: Uuid,
+    pub contract_id: Uuid,
+    pub method_name: String,
+    pub baseline_benchmark_id: Uuid,
+    pub current_benchmark_id: Uuid,
+    pub baseline_p95_ms: f64,
+    pub current_p95_ms: f64,
+    pub regression_pct: f64,
+    pub alert_threshold_pct: f64,
+    pub resolved: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchmarkResponse {
+    pub benchmark: BenchmarkRecord,
+    pub runs: Vec<BenchmarkRun>,
+    pub alert: Option<BenchmarkAlert>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct BenchmarkTrendPoint {
+    pub benchmark_id: Uuid,
+    pub version: String,
+    pub created_at: DateTime<Utc>,
+    pub p95_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub min_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContractBenchmarkSummary {
+    pub contract_id: Uuid,
+    pub total_benchmarks: i64,
+    pub methods_benchmarked: Vec<String>,
+    pub latest_benchmarks: Vec<BenchmarkRecord>,
+    pub active_alerts: Vec<BenchmarkAlert>,
+}
+
+fn validate_run(req: &RunBenchmarkRequest) -> ApiResult<()> {
+    let method = req.method.trim();
+    if method.is_empty() || method.len() > 64 {
+        return Err(ApiError::bad_request(
+            "InvalidMethod",
+            "method must be 1-64 characters",
+        ));
+    }
+    if !(1..=MAX_ITERATIONS).contains(&req.iterations) {
+        return Err(ApiError::bad_request(
+            "InvalidIterations",
+            format!("iterations must be between 1 and {}", MAX_ITERATIONS),
+        ));
+    }
+    if !req.alert_threshold_pct.is_finite() || req.alert_threshold_pct < 0.0 {
+        return Err(ApiError::bad_request(
+            "InvalidAlertThreshold",
+            "alert_threshold_pct must be a non-negative number",
+        ));
+    }
+    Ok(())
+}
 
 // ─────────────────────────────────────────────────────────
 // POST /api/contracts/:id/benchmarks
-// Runs N iterations of a method and persists results.
+// Queues N iterations of a method as a benchmark job.
 // ─────────────────────────────────────────────────────────
 pub async fn run_benchmark(
     State(state): State<AppState>,
     Path(contract_id): Path<Uuid>,
     Json(req): Json<RunBenchmarkRequest>,
-) -> ApiResult<Json<BenchmarkResponse>> {
-    // Validate contract exists
-    let (contract_name,): (String,) = sqlx::query_as("SELECT name FROM contracts WHERE id = $1")
+) -> ApiResult<(StatusCode, Json<BenchmarkRecord>)> {
+    validate_run(&req)?;
+
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM contracts WHERE id = $1)")
         .bind(contract_id)
         .fetch_one(&state.db)
         .await
-        .map_err(|_| ApiError::not_found("ContractNotFound", format!("No contract found with ID: {}", contract_id)))?;
+        .map_err(|_| ApiError::db_error("Failed to look up contract"))?;
+    if !exists {
+        return Err(ApiError::not_found(
+            "ContractNotFound",
+            format!("No contract found with ID: {}", contract_id),
+        ));
+    }
 
-    let iterations = req.iterations.clamp(1, 1000) as usize;
     let version = req.version.as_deref().unwrap_or("unknown");
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|_| ApiError::db_error("Failed to start transaction"))?;
 
-    // Create pending record
     let benchmark: BenchmarkRecord = sqlx::query_as(
         r#"INSERT INTO benchmark_records
-               (contract_id, contract_version, method_name, iterations, args_json, status)
-           VALUES ($1, $2, $3, $4, $5, 'pending')
+               (contract_id, contract_version, method_name, iterations, args_json,
+                alert_threshold_pct)
+           VALUES ($1, $2, $3, $4, $5, $6)
            RETURNING *"#,
     )
     .bind(contract_id)
     .bind(version)
-    .bind(&req.method)
+    .bind(req.method.trim())
     .bind(req.iterations)
     .bind(&req.args_json)
-    .fetch_one(&state.db)
+    .bind(req.alert_threshold_pct)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|_| ApiError::db_error("Failed to create benchmark record"))?;
 
-    // Mark as running
-    sqlx::query("UPDATE benchmark_records SET status = 'running' WHERE id = $1")
-        .bind(benchmark.id)
-        .execute(&state.db)
-        .await
-        .map_err(|_| ApiError::db_error("Failed to update benchmark status"))?;
-
-    // --- Run the benchmark (blocking; move to spawn_blocking in production) ---
-    let runner = BenchmarkRunner::new(req.method.clone(), iterations);
-    let (raw_results, stats) = runner.run();
-
-    // Persist individual runs
-    for (i, result) in raw_results.iter().enumerate() {
-        sqlx::query(
-            r#"INSERT INTO benchmark_runs
-                   (benchmark_id, iteration, execution_time_ms, cpu_instructions, memory_bytes)
-               VALUES ($1, $2, $3, $4, $5)"#,
+    let job = jobs::enqueue(
+        &mut *tx,
+        NewJob::new(
+            JobKind::Benchmark,
+            json!(BenchmarkPayload {
+                benchmark_id: benchmark.id,
+            }),
         )
-        .bind(benchmark.id)
-        .bind(i as i32 + 1)
-        .bind(result.execution_time_ms)
-        .bind(result.cpu_instructions)
-        .bind(result.memory_bytes)
-        .execute(&state.db)
-        .await
-        .map_err(|_| ApiError::db_error("Failed to persist benchmark run data"))?;
-    }
-
-    // Update record with computed stats
-    let benchmark: BenchmarkRecord = sqlx::query_as(
-        r#"UPDATE benchmark_records
-           SET status        = 'completed',
-               min_ms        = $1,
-               max_ms        = $2,
-               avg_ms        = $3,
-               p95_ms        = $4,
-               p99_ms        = $5,
-               stddev_ms     = $6,
-               completed_at  = NOW()
-           WHERE id = $7
-           RETURNING *"#,
+        .contract(contract_id),
     )
-    .bind(stats.min_ms)
-    .bind(stats.max_ms)
-    .bind(stats.avg_ms)
-    .bind(stats.p95_ms)
-    .bind(stats.p99_ms)
-    .bind(stats.stddev_ms)
-    .bind(benchmark.id)
-    .fetch_one(&state.db)
     .await
-    .map_err(|_| ApiError::db_error("Failed to update benchmark stats"))?;
+    .map_err(|_| ApiError::db_error("Failed to enqueue benchmark job"))?;
 
-    // Compare vs previous baseline for same method
-    let maybe_previous: Option<BenchmarkRecord> = sqlx::query_as(
-        r#"SELECT * FROM benchmark_records
-           WHERE contract_id = $1
-             AND method_name = $2
-             AND status = 'completed'
-             AND id != $3
-           ORDER BY created_at DESC
-           LIMIT 1"#,
-    )
-    .bind(contract_id)
-    .bind(&req.method)
-    .bind(benchmark.id)
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|_| ApiError::db_error("Failed to fetch previous benchmark for comparison"))?;
-
-    let (comparison, alert) = if let Some(prev) = &maybe_previous {
-        let comp = BenchmarkComparison {
-            previous_benchmark_id: prev.id,
-            previous_version: prev.contract_version.clone(),
-            previous_p95_ms: prev.p95_ms,
-            current_p95_ms: benchmark.p95_ms,
-            delta_ms: benchmark.p95_ms - prev.p95_ms,
-            delta_pct: ((benchmark.p95_ms - prev.p95_ms) / prev.p95_ms) * 100.0,
-            is_regression: false, // set below
-        };
-
-        let (is_regression, regression_pct) =
-            check_regression(prev.p95_ms, benchmark.p95_ms, req.alert_threshold_pct);
-
-        let comp = BenchmarkComparison {
-            is_regression,
-            ..comp
-        };
-
-        let maybe_alert = if is_regression {
-            let alert: PerformanceAlert = sqlx::query_as(
-                r#"INSERT INTO performance_alerts
-                       (contract_id, method_name, baseline_benchmark_id, current_benchmark_id,
-                        baseline_p95_ms, current_p95_ms, regression_pct, alert_threshold_pct)
-                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-                   RETURNING *"#,
-            )
-            .bind(contract_id)
-            .bind(&req.method)
-            .bind(prev.id)
-            .bind(benchmark.id)
-            .bind(prev.p95_ms)
-            .bind(benchmark.p95_ms)
-            .bind(regression_pct)
-            .bind(req.alert_threshold_pct)
-            .fetch_one(&state.db)
-            .await
-            .map_err(|_| ApiError::db_error("Failed to create performance alert"))?;
-
-            tracing::warn!(
-                contract_id = %contract_id,
-                method = %req.method,
-                regression_pct = %regression_pct,
-                "Performance regression detected"
-            );
-            Some(alert)
-        } else {
-            None
-        };
-
-        (Some(comp), maybe_alert)
-    } else {
-        (None, None)
-    };
-
-    // Fetch persisted runs for response
-    let runs: Vec<BenchmarkRun> =
-        sqlx::query_as("SELECT * FROM benchmark_runs WHERE benchmark_id = $1 ORDER BY iteration")
+    let benchmark: BenchmarkRecord =
+        sqlx::query_as("UPDATE benchmark_records SET job_id = $2 WHERE id = $1 RETURNING *")
             .bind(benchmark.id)
-            .fetch_all(&state.db)
+            .bind(job.id)
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|_| ApiError::db_error("Failed to fetch benchmark runs"))?;
+            .map_err(|_| ApiError::db_error("Failed to link benchmark job"))?;
 
-    tracing::info!(
-        benchmark_id = %benchmark.id,
-        method = %req.method,
-        p95_ms = %benchmark.p95_ms,
-        "Benchmark completed"
-    );
+    tx.commit()
+        .await
+        .map_err(|_| ApiError::db_error("Failed to commit benchmark"))?;
 
-    Ok(Json(BenchmarkResponse {
-        benchmark,
-        runs,
-        alert,
-        comparison,
-    }))
+    Ok((StatusCode::ACCEPTED, Json(benchmark)))
 }
 
 // ─────────────────────────────────────────────────────────
@@ -212,17 +246,16 @@ pub async fn list_benchmarks(
     Query(params): Query<ListBenchmarksParams>,
 ) -> ApiResult<Json<Vec<BenchmarkRecord>>> {
     let limit = params.limit.unwrap_or(20).clamp(1, 100);
-    let method_filter = params.method.as_deref().unwrap_or("%");
 
     let records: Vec<BenchmarkRecord> = sqlx::query_as(
         r#"SELECT * FROM benchmark_records
            WHERE contract_id = $1
-             AND method_name LIKE $2
+             AND ($2::text IS NULL OR method_name = $2)
            ORDER BY created_at DESC
            LIMIT $3"#,
     )
     .bind(contract_id)
-    .bind(method_filter)
+    .bind(params.method.as_deref())
     .bind(limit as i64)
     .fetch_all(&state.db)
     .await
@@ -245,13 +278,7 @@ pub async fn get_benchmark(
     State(state): State<AppState>,
     Path((contract_id, benchmark_id)): Path<(Uuid, Uuid)>,
 ) -> ApiResult<Json<BenchmarkResponse>> {
-    let benchmark: BenchmarkRecord =
-        sqlx::query_as("SELECT * FROM benchmark_records WHERE id = $1 AND contract_id = $2")
-            .bind(benchmark_id)
-            .bind(contract_id)
-            .fetch_one(&state.db)
-            .await
-            .map_err(|_| ApiError::not_found("BenchmarkNotFound", format!("No benchmark found with ID: {}", benchmark_id)))?;
+    let benchmark = fetch_benchmark(&state, contract_id, benchmark_id).await?;
 
     let runs: Vec<BenchmarkRun> =
         sqlx::query_as("SELECT * FROM benchmark_runs WHERE benchmark_id = $1 ORDER BY iteration")
@@ -260,21 +287,39 @@ pub async fn get_benchmark(
             .await
             .map_err(|_| ApiError::db_error("Failed to fetch benchmark runs"))?;
 
-    let alert: Option<PerformanceAlert> =
-        sqlx::query_as("SELECT * FROM performance_alerts WHERE current_benchmark_id = $1 LIMIT 1")
+    let alert: Option<BenchmarkAlert> =
+        sqlx::query_as("SELECT * FROM benchmark_alerts WHERE current_benchmark_id = $1")
             .bind(benchmark_id)
             .fetch_optional(&state.db)
             .await
-            .map_err(|_| ApiError::db_error("Failed to fetch performance alerts"))?;
+            .map_err(|_| ApiError::db_error("Failed to fetch benchmark alerts"))?;
 
     Ok(Json(BenchmarkResponse {
         benchmark,
         runs,
         alert,
-        comparison: None,
     }))
 }
 
+async fn fetch_benchmark(
+    state: &AppState,
+    contract_id: Uuid,
+    benchmark_id: Uuid,
+) -> ApiResult<BenchmarkRecord> {
+    sqlx::query_as("SELECT * FROM benchmark_records WHERE id = $1 AND contract_id = $2")
+        .bind(benchmark_id)
+        .bind(contract_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|_| ApiError::db_error("Failed to fetch benchmark"))?
+        .ok_or_else(|| {
+            ApiError::not_found(
+                "BenchmarkNotFound",
+                format!("No benchmark found with ID: {}", benchmark_id),
+            )
+        })
+}
+
 // ─────────────────────────────────────────────────────────
 // GET /api/contracts/:id/benchmarks/trend?method=transfer
 // Returns time-series data for the dashboard chart.
@@ -284,8 +329,6 @@ pub async fn get_benchmark_trend(
     Path(contract_id): Path<Uuid>,
     Query(params): Query<TrendParams>,
 ) -> ApiResult<Json<Vec<BenchmarkTrendPoint>>> {
-    let method = params.method.as_deref().unwrap_or("%");
-
     let trend: Vec<BenchmarkTrendPoint> = sqlx::query_as(
         r#"SELECT
                id AS benchmark_id,
@@ -297,13 +340,13 @@ pub async fn get_benchmark_trend(
                max_ms
            FROM benchmark_records
            WHERE contract_id = $1
-             AND method_name LIKE $2
+             AND ($2::text IS NULL OR method_name = $2)
              AND status = 'completed'
            ORDER BY created_at ASC
            LIMIT 200"#,
     )
     .bind(contract_id)
-    .bind(method)
+    .bind(params.method.as_deref())
     .fetch_all(&state.db)
     .await
     .map_err(|_| ApiError::db_error("Failed to fetch benchmark trend data"))?;
@@ -324,22 +367,6 @@ pub async fn get_benchmark_summary(
     State(state): State<AppState>,
     Path(contract_id): Path<Uuid>,
 ) -> ApiResult<Json<ContractBenchmarkSummary>> {
-    let (total_benchmarks,): (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM benchmark_records WHERE contract_id = $1 AND status = 'completed'",
-    )
-    .bind(contract_id)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|_| ApiError::db_error("Failed to count benchmark records"))?;
-
-    let methods: Vec<(String,)> = sqlx::query_as(
-        "SELECT DISTINCT method_name FROM benchmark_records WHERE contract_id = $1 AND status = 'completed' ORDER BY method_name",
-    )
-    .bind(contract_id)
-    .fetch_all(&state.db)
-    .await
-    .map_err(|_| ApiError::db_error("Failed to fetch benchmarked methods"))?;
-
     let latest_benchmarks: Vec<BenchmarkRecord> = sqlx::query_as(
         r#"SELECT DISTINCT ON (method_name) *
            FROM benchmark_records
@@ -351,18 +378,29 @@ pub async fn get_benchmark_summary(
     .await
     .map_err(|_| ApiError::db_error("Failed to fetch latest benchmarks"))?;
 
-    let active_alerts: Vec<PerformanceAlert> = sqlx::query_as(
-        "SELECT * FROM performance_alerts WHERE contract_id = $1 AND resolved = false ORDER BY created_at DESC",
+    let (total_benchmarks,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM benchmark_records WHERE contract_id = $1 AND status = 'completed'",
+    )
+    .bind(contract_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|_| ApiError::db_error("Failed to count benchmark records"))?;
+
+    let active_alerts: Vec<BenchmarkAlert> = sqlx::query_as(
+        "SELECT * FROM benchmark_alerts WHERE contract_id = $1 AND NOT resolved ORDER BY created_at DESC",
     )
     .bind(contract_id)
     .fetch_all(&state.db)
     .await
-    .map_err(|_| ApiError::db_error("Failed to fetch active performance alerts"))?;
+    .map_err(|_| ApiError::db_error("Failed to fetch active benchmark alerts"))?;
 
     Ok(Json(ContractBenchmarkSummary {
         contract_id,
         total_benchmarks,
-        methods_benchmarked: methods.into_iter().map(|(m,)| m).collect(),
+        methods_benchmarked: latest_benchmarks
+            .iter()
+            .map(|b| b.method_name.clone())
+            .collect(),
         latest_benchmarks,
         active_alerts,
     }))
@@ -376,68 +414,60 @@ pub async fn resolve_alert(
     Path((contract_id, alert_id)): Path<(Uuid, Uuid)>,
 ) -> ApiResult<Json<serde_json::Value>> {
     let rows = sqlx::query(
-        "UPDATE performance_alerts SET resolved = true WHERE id = $1 AND contract_id = $2",
+        "UPDATE benchmark_alerts SET resolved = true WHERE id = $1 AND contract_id = $2",
     )
     .bind(alert_id)
     .bind(contract_id)
     .execute(&state.db)
     .await
-    .map_err(|_| ApiError::db_error("Failed to resolve performance alert"))?
+    .map_err(|_| ApiError::db_error("Failed to resolve benchmark alert"))?
     .rows_affected();
 
     if rows == 0 {
         return Err(ApiError::not_found(
             "AlertNotFound",
-            format!("No performance alert found with ID: {}", alert_id),
+            format!("No benchmark alert found with ID: {}", alert_id),
         ));
     }
 
-    Ok(Json(serde_json::json!({ "status": "resolved", "alert_id": alert_id.to_string() })))
+    Ok(Json(json!({ "status": "resolved", "alert_id": alert_id })))
 }
 
 // ─────────────────────────────────────────────────────────
-// GET /api/contracts/:id/benchmarks/cli-output/:benchmark_id
+// GET /api/contracts/:id/benchmarks/:benchmark_id/cli-output
 // Returns the CLI-style formatted output for a completed benchmark.
 // ─────────────────────────────────────────────────────────
 pub async fn get_cli_output(
     State(state): State<AppState>,
     Path((contract_id, benchmark_id)): Path<(Uuid, Uuid)>,
 ) -> ApiResult<String> {
-    let benchmark: BenchmarkRecord =
-        sqlx::query_as("SELECT * FROM benchmark_records WHERE id = $1 AND contract_id = $2")
-            .bind(benchmark_id)
-            .bind(contract_id)
-            .fetch_one(&state.db)
-            .await
-            .map_err(|_| ApiError::not_found("BenchmarkNotFound", format!("No benchmark found with ID: {}", benchmark_id)))?;
+    let benchmark = fetch_benchmark(&state, contract_id, benchmark_id).await?;
 
-    if benchmark.status != BenchmarkStatus::Completed {
-        return Err(ApiError::unprocessable(
+    let Some(stats) = benchmark
+        .stats()
+        .filter(|_| benchmark.status == "completed")
+    else {
+        return Err(ApiError::conflict(
             "BenchmarkNotCompleted",
-            format!("Benchmark {} has status {:?} and cannot produce CLI output", benchmark_id, benchmark.status),
+            format!(
+                "Benchmark {} is {} and has no results yet",
+                benchmark_id, benchmark.status
+            ),
         ));
-    }
-
-    let stats = BenchmarkStats {
-        min_ms: benchmark.min_ms,
-        max_ms: benchmark.max_ms,
-        avg_ms: benchmark.avg_ms,
-        p95_ms: benchmark.p95_ms,
-        p99_ms: benchmark.p99_ms,
-        stddev_ms: benchmark.stddev_ms,
     };
 
-    let alert_msg: Option<String> = sqlx::query_scalar(
-        r#"SELECT CONCAT('p95 increased ', ROUND(regression_pct::numeric, 1), '% (', 
-                         ROUND(baseline_p95_ms::numeric, 2), 'ms → ',
-                         ROUND(current_p95_ms::numeric, 2), 'ms)')
-           FROM performance_alerts WHERE current_benchmark_id = $1 LIMIT 1"#,
-    )
-    .bind(benchmark_id)
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|_| ApiError::db_error("Failed to fetch performance alert message"))?
-    .flatten();
+    let alert: Option<BenchmarkAlert> =
+        sqlx::query_as("SELECT * FROM benchmark_alerts WHERE current_benchmark_id = $1")
+            .bind(benchmark_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|_| ApiError::db_error("Failed to fetch benchmark alert"))?;
+    let alert_msg = alert.map(|a| {
+        format!(
+            "p95 increased {:.1}% ({:.2}ms → {:.2}ms)",
+            a.regression_pct, a.baseline_p95_ms, a.current_p95_ms
+        )
+    });
 
     Ok(format_cli_output(
         &contract_id.to_string(),
@@ -447,3 +477,39 @@ pub async fn get_cli_output(
         alert_msg.as_deref(),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, iterations: i32) -> RunBenchmarkRequest {
+        RunBenchmarkRequest {
+            method: method.to_string(),
+            iterations,
+            version: None,
+            args_json: None,
+            alert_threshold_pct: default_alert_threshold(),
+        }
+    }
+
+    #[test]
+    fn run_requests_are_validated_before_queueing() {
+        assert!(validate_run(&request("transfer", 100)).is_ok());
+        assert!(validate_run(&request("transfer", MAX_ITERATIONS)).is_ok());
+
+        for bad in [
+            request("  ", 100),
+            request("transfer", 0),
+            request("transfer", MAX_ITERATIONS + 1),
+            RunBenchmarkRequest {
+                alert_threshold_pct: f64::NAN,
+                ..request("transfer", 10)
+            },
+        ] {
+            assert_eq!(
+                validate_run(&bad).unwrap_err().status(),
+                StatusCode::BAD_REQUEST
+            );
+        }
+    }
+}
@@ -1,6 +1,5 @@
 // api/src/benchmark_routes.rs
 // Benchmark route definitions.
-// Merge into the main Axum router alongside existing routes.
 
 use axum::{
     routing::{get, post},
@@ -10,15 +9,9 @@ use axum::{
 use crate::{benchmark_handlers, state::AppState};
 
 /// All contract benchmarking routes.
-///
-/// Add to main.rs router setup:
-///
-/// ```rust
-/// .merge(benchmark_routes::benchmark_routes())
-/// ```
 pub fn benchmark_routes() -> Router<AppState> {
     Router::new()
-        // ── Run a new benchmark ────────────────────────────────────────────
+        // ── Queue a new benchmark (202 + job_id) ───────────────────────────
         // CLI equivalent: soroban-registry benchmark {id} --method=transfer --iterations=100
         .route(
             "/api/contracts/:id/benchmarks",
@@ -0,0 +1,194 @@
+// benchmark_runner.rs
+// Job executor for contract benchmarks.
+//
+// `POST /api/contracts/:id/benchmarks` queues a `benchmark_records` row and a
+// `benchmark` job (see `job_worker`). The job runs the iterations off the
+// async runtime, stores per-iteration timings and statistics in one
+// transaction, and raises a `benchmark_alerts` row when p95 regressed past
+// the requested threshold against the previous completed benchmark of the
+// same method.
+
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::benchmark_engine::{check_regression, BenchmarkRunner};
+use crate::benchmark_handlers::BenchmarkRecord;
+use crate::jobs::{JobContext, JobError};
+
+/// Execute one queued benchmark. Returns its statistics as the job result.
+pub async fn execute(ctx: &JobContext, benchmark_id: Uuid) -> Result<serde_json::Value, JobError> {
+    let pool = &ctx.pool;
+    let benchmark: Option<BenchmarkRecord> =
+        sqlx::query_as("SELECT * FROM benchmark_records WHERE id = $1")
+            .bind(benchmark_id)
+            .fetch_optional(pool)
+            .await?;
+    let Some(benchmark) = benchmark else {
+        return Err(JobError::permanent(format!(
+            "Benchmark {} no longer exists",
+            benchmark_id
+        )));
+    };
+    if benchmark.status == "completed" || benchmark.status == "failed" {
+        // Already settled by an earlier attempt.
+        return Ok(summary(&benchmark, None));
+    }
+
+    sqlx::query(
+        "UPDATE benchmark_records SET status = 'running', started_at = NOW() WHERE id = $1",
+    )
+    .bind(benchmark.id)
+    .execute(pool)
+    .await?;
+
+    ctx.progress(
+        10,
+        format!(
+            "Running {} iteration(s) of {}()",
+            benchmark.iterations, benchmark.method_name
+        ),
+    )
+    .await;
+
+    // The runner sleeps between iterations; keep it off the async workers.
+    // A cancelled job stops waiting for it but cannot interrupt it.
+    let runner = BenchmarkRunner::new(
+        benchmark.method_name.clone(),
+        benchmark.iterations.max(1) as usize,
+    );
+    let (results, stats) = tokio::task::spawn_blocking(move || runner.run())
+        .await
+        .map_err(|e| JobError::retryable(format!("Benchmark runner stopped: {}", e)))?;
+
+    ctx.progress(90, "Recording results").await;
+    let mut tx = pool.begin().await?;
+
+    let iterations: Vec<i32> = (1..=results.len() as i32).collect();
+    let timings: Vec<f64> = results.iter().map(|r| r.execution_time_ms).collect();
+    let cpu: Vec<Option<i64>> = results.iter().map(|r| r.cpu_instructions).collect();
+    let memory: Vec<Option<i64>> = results.iter().map(|r| r.memory_bytes).collect();
+    sqlx::query(
+        r#"
+        INSERT INTO benchmark_runs
+            (benchmark_id, iteration, execution_time_ms, cpu_instructions, memory_bytes)
+        SELECT $1, i, t, c, m
+        FROM UNNEST($2::int[], $3::float8[], $4::int8[], $5::int8[]) AS r(i, t, c, m)
+        "#,
+    )
+    .bind(benchmark.id)
+    .bind(&iterations)
+    .bind(&timings)
+    .bind(&cpu)
+    .bind(&memory)
+    .execute(&mut *tx)
+    .await?;
+
+    let benchmark: BenchmarkRecord = sqlx::query_as(
+        r#"
+        UPDATE benchmark_records
+        SET status = 'completed', min_ms = $2, max_ms = $3, avg_ms = $4, p95_ms = $5,
+            p99_ms = $6, stddev_ms = $7, error_message = NULL, completed_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(benchmark.id)
+    .bind(stats.min_ms)
+    .bind(stats.max_ms)
+    .bind(stats.avg_ms)
+    .bind(stats.p95_ms)
+    .bind(stats.p99_ms)
+    .bind(stats.stddev_ms)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let baseline: Option<(Uuid, f64)> = sqlx::query_as(
+        r#"
+        SELECT id, p95_ms FROM benchmark_records
+        WHERE contract_id = $1 AND method_name = $2 AND status = 'completed'
+          AND p95_ms IS NOT NULL AND id <> $3
+        ORDER BY completed_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(benchmark.contract_id)
+    .bind(&benchmark.method_name)
+    .bind(benchmark.id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let mut regression_pct = None;
+    if let Some((baseline_id, baseline_p95)) = baseline {
+        let (is_regression, pct) =
+            check_regression(baseline_p95, stats.p95_ms, benchmark.alert_threshold_pct);
+        if is_regression {
+            sqlx::query(
+                r#"
+                INSERT INTO benchmark_alerts
+                    (contract_id, method_name, baseline_benchmark_id, current_benchmark_id,
+                     baseline_p95_ms, current_p95_ms, regression_pct, alert_threshold_pct)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (current_benchmark_id) DO NOTHING
+                "#,
+            )
+            .bind(benchmark.contract_id)
+            .bind(&benchmark.method_name)
+            .bind(baseline_id)
+            .bind(benchmark.id)
+            .bind(baseline_p95)
+            .bind(stats.p95_ms)
+            .bind(pct)
+            .bind(benchmark.alert_threshold_pct)
+            .execute(&mut *tx)
+            .await?;
+            regression_pct = Some(pct);
+        }
+    }
+
+    tx.commit().await?;
+
+    if let Some(pct) = regression_pct {
+        tracing::warn!(
+            benchmark_id = %benchmark.id,
+            contract_id = %benchmark.contract_id,
+            method = %benchmark.method_name,
+            regression_pct = pct,
+            "benchmark: performance regression detected"
+        );
+        ctx.warn(format!("p95 regressed by {:.1}%", pct)).await;
+    }
+
+    Ok(summary(&benchmark, regression_pct))
+}
+
+fn summary(benchmark: &BenchmarkRecord, regression_pct: Option<f64>) -> serde_json::Value {
+    json!({
+        "benchmark_id": benchmark.id,
+        "status": benchmark.status,
+        "avg_ms": benchmark.avg_ms,
+        "p95_ms": benchmark.p95_ms,
+        "regression_pct": regression_pct,
+        "error_message": benchmark.error_message,
+    })
+}
+
+/// Settle a benchmark whose job failed for good or was cancelled.
+pub async fn abandon_benchmark(
+    pool: &PgPool,
+    benchmark_id: Uuid,
+    message: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE benchmark_records
+        SET status = 'failed', error_message = $2, completed_at = NOW()
+        WHERE id = $1 AND status IN ('queued', 'running')
+        "#,
+    )
+    .bind(benchmark_id)
+    .bind(message)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,714 @@
+//! Blue/green deployment controller
+//!
+//! Each contract has two slots, `blue` and `green`. A new release is written
+//! into whichever slot is idle and stays `testing` while health checks run:
+//! once `required_health_checks` consecutive checks pass it is switched in
+//! (if `auto_switch` is set), and `max_failed_health_checks` consecutive
+//! failures mark it `failed` while the live slot keeps serving.
+//!
+//! A switch flips both slots in one transaction and is recorded in
+//! `deployment_switches`. The newly live slot keeps being checked until
+//! `monitor_until`; if it fails in that window, traffic is rolled back to the
+//! previous slot.
+//!
+//! Checks come from the RPC probe (the same `getHealth` test as the load
+//! balancer's `HealthChecker`, plus a lookup that the WASM is installed on
+//! the network) or are reported by an external checker.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::{ContractDeployment, DeploymentEnvironment, DeploymentStatus, DeploymentSwitch};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use stellar_xdr::curr::{Hash, LedgerKey, LedgerKeyContractCode, Limits, WriteXdr};
+use uuid::Uuid;
+
+pub const CONTROLLER_ACTOR: &str = "deployment-controller";
+const DEFAULT_CHECK_INTERVAL_SECS: u64 = 30;
+const DEFAULT_MONITOR_SECS: i64 = 600;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub passed: bool,
+    pub detail: String,
+    pub latency_ms: Option<i32>,
+}
+
+#[async_trait]
+pub trait HealthProbe: Send + Sync {
+    async fn check(&self, deployment: &ContractDeployment) -> CheckResult;
+}
+
+/// Checks the Soroban RPC node and that the deployment's WASM is installed.
+pub struct RpcHealthProbe {
+    client: reqwest::Client,
+    rpc_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcEnvelope {
+    result: Option<serde_json::Value>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+impl RpcHealthProbe {
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(PROBE_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            rpc_url: rpc_url.into(),
+        }
+    }
+
+    /// Uses `SOROBAN_RPC_URL`; without it only reported checks are counted.
+    pub fn from_env() -> Option<Self> {
+        std::env::var("SOROBAN_RPC_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .map(Self::new)
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let response: RpcEnvelope = self
+            .client
+            .post(&self.rpc_url)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("{} request failed: {}", method, e))?
+            .error_for_status()
+            .map_err(|e| format!("{} returned {}", method, e))?
+            .json()
+            .await
+            .map_err(|e| format!("{} returned invalid JSON: {}", method, e))?;
+        if let Some(error) = response.error {
+            return Err(format!("{} failed: {}", method, error.message));
+        }
+        response
+            .result
+            .ok_or_else(|| format!("{} returned no result", method))
+    }
+
+    async fn probe(&self, wasm_hash: &str) -> Result<(), String> {
+        let health = self.call("getHealth", serde_json::json!({})).await?;
+        let status = health.get("status").and_then(|s| s.as_str()).unwrap_or("");
+        if status != "healthy" {
+            return Err(format!("RPC node reports status '{}'", status));
+        }
+
+        let key = contract_code_key(wasm_hash)?;
+        let entries = self
+            .call("getLedgerEntries", serde_json::json!({ "keys": [key] }))
+            .await?;
+        let installed = entries
+            .get("entries")
+            .and_then(|e| e.as_array())
+            .is_some_and(|e| !e.is_empty());
+        if !installed {
+            return Err(format!(
+                "WASM {} is not installed on the network",
+                wasm_hash
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Base64 XDR of the ledger key holding the contract code for `wasm_hash`.
+pub fn contract_code_key(wasm_hash: &str) -> Result<String, String> {
+    let bytes: [u8; 32] = hex::decode(wasm_hash)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| format!("'{}' is not a 32-byte hex hash", wasm_hash))?;
+    LedgerKey::ContractCode(LedgerKeyContractCode { hash: Hash(bytes) })
+        .to_xdr_base64(Limits::none())
+        .map_err(|e| e.to_string())
+}
+
+#[async_trait]
+impl HealthProbe for RpcHealthProbe {
+    async fn check(&self, deployment: &ContractDeployment) -> CheckResult {
+        let start = Instant::now();
+        let outcome = self.probe(&deployment.wasm_hash).await;
+        let latency_ms = Some(start.elapsed().as_millis().min(i32::MAX as u128) as i32);
+        match outcome {
+            Ok(()) => CheckResult {
+                passed: true,
+                detail: "RPC healthy and WASM installed".to_string(),
+                latency_ms,
+            },
+            Err(detail) => CheckResult {
+                passed: false,
+                detail,
+                latency_ms,
+            },
+        }
+    }
+}
+
+/// What a deployment's check counters call for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Wait,
+    /// A testing slot has passed enough checks to go live
+    Promote,
+    /// A testing slot has failed; the live slot is untouched
+    Reject,
+    /// A live slot failed while monitored
+    RollBack,
+}
+
+pub fn verdict(deployment: &ContractDeployment, now: DateTime<Utc>) -> Verdict {
+    let failed = deployment.consecutive_failures >= deployment.max_failed_health_checks;
+    match deployment.status {
+        DeploymentStatus::Testing if failed => Verdict::Reject,
+        DeploymentStatus::Testing
+            if deployment.auto_switch
+                && deployment.consecutive_passes >= deployment.required_health_checks =>
+        {
+            Verdict::Promote
+        }
+        DeploymentStatus::Active
+            if failed && deployment.monitor_until.is_some_and(|until| until > now) =>
+        {
+            Verdict::RollBack
+        }
+        _ => Verdict::Wait,
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeploymentError {
+    #[error("no deployment is waiting to be switched in")]
+    NoCandidate,
+    #[error("candidate has passed {passes} of {required} required health checks")]
+    NotHealthy { passes: i32, required: i32 },
+    #[error("no previous deployment to roll back to")]
+    NoPrevious,
+    #[error("no deployment is live")]
+    NothingLive,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+fn monitor_window() -> chrono::Duration {
+    let secs = std::env::var("DEPLOYMENT_MONITOR_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_MONITOR_SECS);
+    chrono::Duration::seconds(secs)
+}
+
+pub async fn deployments(
+    pool: &PgPool,
+    contract_id: Uuid,
+) -> Result<Vec<ContractDeployment>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM contract_deployments WHERE contract_id = $1 ORDER BY environment")
+        .bind(contract_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Register `wasm_hash` as the candidate in the idle slot, replacing
+/// whatever that slot held before.
+pub async fn register_candidate(
+    pool: &PgPool,
+    contract_id: Uuid,
+    wasm_hash: &str,
+    required_health_checks: i32,
+    max_failed_health_checks: i32,
+    auto_switch: bool,
+    deployed_by: Option<&str>,
+) -> Result<ContractDeployment, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let live: Option<DeploymentEnvironment> = sqlx::query_scalar(
+        "SELECT environment FROM contract_deployments
+         WHERE contract_id = $1 AND status = 'active'
+         FOR UPDATE",
+    )
+    .bind(contract_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let slot = live.map_or(DeploymentEnvironment::Blue, DeploymentEnvironment::other);
+
+    let deployment = sqlx::query_as(
+        "INSERT INTO contract_deployments
+            (contract_id, environment, status, wasm_hash, required_health_checks,
+             max_failed_health_checks, auto_switch, deployed_by)
+         VALUES ($1, $2, 'testing', $3, $4, $5, $6, $7)
+         ON CONFLICT (contract_id, environment) DO UPDATE SET
+            status = 'testing',
+            wasm_hash = EXCLUDED.wasm_hash,
+            deployed_at = NOW(),
+            activated_at = NULL,
+            health_checks_passed = 0,
+            health_checks_failed = 0,
+            consecutive_passes = 0,
+            consecutive_failures = 0,
+            last_health_check_at = NULL,
+            error_message = NULL,
+            monitor_until = NULL,
+            required_health_checks = EXCLUDED.required_health_checks,
+            max_failed_health_checks = EXCLUDED.max_failed_health_checks,
+            auto_switch = EXCLUDED.auto_switch,
+            deployed_by = EXCLUDED.deployed_by
+         RETURNING *",
+    )
+    .bind(contract_id)
+    .bind(slot)
+    .bind(wasm_hash)
+    .bind(required_health_checks)
+    .bind(max_failed_health_checks)
+    .bind(auto_switch)
+    .bind(deployed_by)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(deployment)
+}
+
+/// Count one check against a deployment.
+pub async fn record_check(
+    pool: &PgPool,
+    deployment: &ContractDeployment,
+    result: &CheckResult,
+    source: &str,
+) -> Result<ContractDeployment, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO deployment_health_checks
+            (deployment_id, wasm_hash, passed, source, detail, latency_ms)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(deployment.id)
+    .bind(&deployment.wasm_hash)
+    .bind(result.passed)
+    .bind(source)
+    .bind(&result.detail)
+    .bind(result.latency_ms)
+    .execute(&mut *tx)
+    .await?;
+    let updated = sqlx::query_as(
+        "UPDATE contract_deployments SET
+            health_checks_passed = health_checks_passed + CASE WHEN $2 THEN 1 ELSE 0 END,
+            health_checks_failed = health_checks_failed + CASE WHEN $2 THEN 0 ELSE 1 END,
+            consecutive_passes = CASE WHEN $2 THEN consecutive_passes + 1 ELSE 0 END,
+            consecutive_failures = CASE WHEN $2 THEN 0 ELSE consecutive_failures + 1 END,
+            last_health_check_at = NOW(),
+            error_message = CASE WHEN $2 THEN error_message ELSE $3 END
+         WHERE id = $1
+         RETURNING *",
+    )
+    .bind(deployment.id)
+    .bind(result.passed)
+    .bind(&result.detail)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(updated)
+}
+
+async fn lock_slots(
+    tx: &mut Transaction<'_, Postgres>,
+    contract_id: Uuid,
+) -> Result<Vec<ContractDeployment>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM contract_deployments WHERE contract_id = $1 FOR UPDATE")
+        .bind(contract_id)
+        .fetch_all(&mut **tx)
+        .await
+}
+
+async fn insert_switch(
+    tx: &mut Transaction<'_, Postgres>,
+    contract_id: Uuid,
+    from: Option<&ContractDeployment>,
+    to: &ContractDeployment,
+    actor: &str,
+    rollback: bool,
+    reason: Option<&str>,
+) -> Result<DeploymentSwitch, sqlx::Error> {
+    sqlx::query_as(
+        "INSERT INTO deployment_switches
+            (contract_id, from_environment, to_environment, switched_by, rollback,
+             from_deployment_id, to_deployment_id, from_wasm_hash, to_wasm_hash, reason)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+         RETURNING *",
+    )
+    .bind(contract_id)
+    .bind(from.map(|d| d.environment))
+    .bind(to.environment)
+    .bind(actor)
+    .bind(rollback)
+    .bind(from.map(|d| d.id))
+    .bind(to.id)
+    .bind(from.map(|d| d.wasm_hash.as_str()))
+    .bind(&to.wasm_hash)
+    .bind(reason)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Make `to` live and take `from` out of service in one transaction.
+async fn swap(
+    tx: &mut Transaction<'_, Postgres>,
+    from: Option<&ContractDeployment>,
+    from_status: DeploymentStatus,
+    from_error: Option<&str>,
+    to: &ContractDeployment,
+    monitor_until: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    if let Some(from) = from {
+        sqlx::query(
+            "UPDATE contract_deployments
+             SET status = $2, monitor_until = NULL, error_message = COALESCE($3, error_message)
+             WHERE id = $1",
+        )
+        .bind(from.id)
+        .bind(from_status)
+        .bind(from_error)
+        .execute(&mut **tx)
+        .await?;
+    }
+    sqlx::query(
+        "UPDATE contract_deployments
+         SET status = 'active', activated_at = NOW(), monitor_until = $2,
+             consecutive_passes = 0, consecutive_failures = 0
+         WHERE id = $1",
+    )
+    .bind(to.id)
+    .bind(monitor_until)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Switch the testing slot in. Without `force` it must have passed its
+/// required checks.
+pub async fn switch(
+    pool: &PgPool,
+    contract_id: Uuid,
+    force: bool,
+    actor: &str,
+) -> Result<DeploymentSwitch, DeploymentError> {
+    let mut tx = pool.begin().await?;
+    let slots = lock_slots(&mut tx, contract_id).await?;
+    let candidate = slots
+        .iter()
+        .find(|d| d.status == DeploymentStatus::Testing)
+        .ok_or(DeploymentError::NoCandidate)?;
+    if !force && candidate.consecutive_passes < candidate.required_health_checks {
+        return Err(DeploymentError::NotHealthy {
+            passes: candidate.consecutive_passes,
+            required: candidate.required_health_checks,
+        });
+    }
+    let live = slots.iter().find(|d| d.status == DeploymentStatus::Active);
+
+    let monitor_until = Utc::now() + monitor_window();
+    swap(
+        &mut tx,
+        live,
+        DeploymentStatus::Inactive,
+        None,
+        candidate,
+        Some(monitor_until),
+    )
+    .await?;
+    let reason = if force {
+        "forced switch"
+    } else {
+        "health checks passed"
+    };
+    let record = insert_switch(
+        &mut tx,
+        contract_id,
+        live,
+        candidate,
+        actor,
+        false,
+        Some(reason),
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        contract_id = %contract_id,
+        to = %candidate.environment,
+        wasm_hash = %candidate.wasm_hash,
+        actor,
+        "deployment switched"
+    );
+    Ok(record)
+}
+
+/// Return traffic to the previous slot and mark the live one failed.
+pub async fn rollback(
+    pool: &PgPool,
+    contract_id: Uuid,
+    actor: &str,
+    reason: &str,
+) -> Result<DeploymentSwitch, DeploymentError> {
+    let mut tx = pool.begin().await?;
+    let slots = lock_slots(&mut tx, contract_id).await?;
+    let live = slots
+        .iter()
+        .find(|d| d.status == DeploymentStatus::Active)
+        .ok_or(DeploymentError::NothingLive)?;
+    // The other slot only qualifies if it has served traffic before.
+    let previous = slots
+        .iter()
+        .find(|d| {
+            d.id != live.id && d.status == DeploymentStatus::Inactive && d.activated_at.is_some()
+        })
+        .ok_or(DeploymentError::NoPrevious)?;
+
+    swap(
+        &mut tx,
+        Some(live),
+        DeploymentStatus::Failed,
+        Some(reason),
+        previous,
+        None,
+    )
+    .await?;
+    let record = insert_switch(
+        &mut tx,
+        contract_id,
+        Some(live),
+        previous,
+        actor,
+        true,
+        Some(reason),
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::warn!(
+        contract_id = %contract_id,
+        to = %previous.environment,
+        reason,
+        actor,
+        "deployment rolled back"
+    );
+    Ok(record)
+}
+
+async fn reject(pool: &PgPool, deployment: &ContractDeployment) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE contract_deployments SET status = 'failed' WHERE id = $1 AND status = 'testing'",
+    )
+    .bind(deployment.id)
+    .execute(pool)
+    .await?;
+    tracing::warn!(
+        contract_id = %deployment.contract_id,
+        environment = %deployment.environment,
+        error = deployment.error_message.as_deref().unwrap_or(""),
+        "candidate deployment failed health checks"
+    );
+    Ok(())
+}
+
+/// Act on a deployment's counters after a check.
+pub async fn reconcile(
+    pool: &PgPool,
+    deployment: &ContractDeployment,
+) -> Result<Verdict, DeploymentError> {
+    let verdict = verdict(deployment, Utc::now());
+    match verdict {
+        Verdict::Wait => {}
+        Verdict::Reject => reject(pool, deployment).await?,
+        Verdict::Promote => {
+            switch(pool, deployment.contract_id, false, CONTROLLER_ACTOR).await?;
+        }
+        Verdict::RollBack => {
+            let reason = format!(
+                "{} consecutive health checks failed after switch: {}",
+                deployment.consecutive_failures,
+                deployment
+                    .error_message
+                    .as_deref()
+                    .unwrap_or("unknown error")
+            );
+            rollback(pool, deployment.contract_id, CONTROLLER_ACTOR, &reason).await?;
+        }
+    }
+    Ok(verdict)
+}
+
+/// Probe every testing slot and every live slot still being monitored.
+pub async fn check_watched(pool: &PgPool, probe: &dyn HealthProbe) -> Result<usize, sqlx::Error> {
+    let watched: Vec<ContractDeployment> = sqlx::query_as(
+        "SELECT * FROM contract_deployments
+         WHERE status = 'testing' OR (status = 'active' AND monitor_until > NOW())",
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut acted = 0;
+    for deployment in &watched {
+        let result = probe.check(deployment).await;
+        let updated = record_check(pool, deployment, &result, "probe").await?;
+        match reconcile(pool, &updated).await {
+            Ok(Verdict::Wait) => {}
+            Ok(_) => acted += 1,
+            Err(err) => tracing::warn!(
+                deployment_id = %deployment.id,
+                error = %err,
+                "deployment reconcile failed"
+            ),
+        }
+    }
+    Ok(acted)
+}
+
+/// Periodically health-check watched deployments. Without a probe the
+/// controller still acts on reported checks as they arrive.
+pub fn spawn_deployment_monitor(pool: PgPool) {
+    let Some(probe) = RpcHealthProbe::from_env() else {
+        tracing::info!("SOROBAN_RPC_URL not set; deployments rely on reported health checks");
+        return;
+    };
+    let probe: Arc<dyn HealthProbe> = Arc::new(probe);
+    let interval_secs = std::env::var("DEPLOYMENT_HEALTH_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_CHECK_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match check_watched(&pool, probe.as_ref()).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(count = n, "deployments: applied transitions"),
+                Err(err) => tracing::error!(error = ?err, "deployments: health checks failed"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use tokio::net::TcpListener;
+
+    fn deployment(status: DeploymentStatus, passes: i32, failures: i32) -> ContractDeployment {
+        ContractDeployment {
+            id: Uuid::new_v4(),
+            contract_id: Uuid::new_v4(),
+            environment: DeploymentEnvironment::Green,
+            status,
+            wasm_hash: "ab".repeat(32),
+            deployed_at: Utc::now(),
+            activated_at: None,
+            health_checks_passed: passes,
+            health_checks_failed: failures,
+            last_health_check_at: None,
+            error_message: None,
+            required_health_checks: 3,
+            max_failed_health_checks: 2,
+            consecutive_passes: passes,
+            consecutive_failures: failures,
+            auto_switch: true,
+            monitor_until: None,
+            deployed_by: None,
+        }
+    }
+
+    #[test]
+    fn testing_slot_promotes_or_rejects_on_thresholds() {
+        let now = Utc::now();
+        let d = deployment(DeploymentStatus::Testing, 2, 0);
+        assert_eq!(verdict(&d, now), Verdict::Wait);
+        let d = deployment(DeploymentStatus::Testing, 3, 0);
+        assert_eq!(verdict(&d, now), Verdict::Promote);
+        let manual = ContractDeployment {
+            auto_switch: false,
+            ..d
+        };
+        assert_eq!(verdict(&manual, now), Verdict::Wait);
+        let d = deployment(DeploymentStatus::Testing, 0, 2);
+        assert_eq!(verdict(&d, now), Verdict::Reject);
+    }
+
+    #[test]
+    fn live_slot_rolls_back_only_while_monitored() {
+        let now = Utc::now();
+        let mut d = deployment(DeploymentStatus::Active, 0, 2);
+        assert_eq!(verdict(&d, now), Verdict::Wait);
+        d.monitor_until = Some(now + chrono::Duration::minutes(5));
+        assert_eq!(verdict(&d, now), Verdict::RollBack);
+        d.monitor_until = Some(now - chrono::Duration::minutes(5));
+        assert_eq!(verdict(&d, now), Verdict::Wait);
+    }
+
+    /// Local stand-in Soroban RPC node.
+    async fn rpc_stand_in(status: &'static str, installed: bool) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(req): Json<serde_json::Value>| async move {
+                let result = match req["method"].as_str() {
+                    Some("getHealth") => serde_json::json!({ "status": status }),
+                    Some("getLedgerEntries") => {
+                        let entries: Vec<serde_json::Value> = if installed {
+                            vec![serde_json::json!({ "key": req["params"]["keys"][0] })]
+                        } else {
+                            vec![]
+                        };
+                        serde_json::json!({ "entries": entries, "latestLedger": 1 })
+                    }
+                    _ => serde_json::Value::Null,
+                };
+                Json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn rpc_probe_requires_healthy_node_and_installed_wasm() {
+        let d = deployment(DeploymentStatus::Testing, 0, 0);
+
+        let ok = RpcHealthProbe::new(rpc_stand_in("healthy", true).await);
+        assert!(ok.check(&d).await.passed);
+
+        let missing = RpcHealthProbe::new(rpc_stand_in("healthy", false).await);
+        let result = missing.check(&d).await;
+        assert!(!result.passed);
+        assert!(result.detail.contains("not installed"), "{}", result.detail);
+
+        let degraded = RpcHealthProbe::new(rpc_stand_in("catching_up", true).await);
+        assert!(!degraded.check(&d).await.passed);
+    }
+
+    #[test]
+    fn contract_code_key_rejects_bad_hashes() {
+        assert!(contract_code_key(&"ab".repeat(32)).is_ok());
+        assert!(contract_code_key("abcd").is_err());
+    }
+}
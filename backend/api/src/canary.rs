@@ -0,0 +1,630 @@
+//! Canary release controller
+//!
+//! A canary shifts traffic from one `contract_deployments` row to another in
+//! stages (1% → 10% → 50% → 100%, capped at the canary's target). Callers are
+//! bucketed by a hash of the canary and caller address, so a caller's
+//! assignment never flips back as the percentage grows.
+//!
+//! Each stage is judged on the metrics recorded since it started. A stage
+//! whose error rate crosses `error_rate_threshold` (once it has seen enough
+//! requests) rolls the canary back; a healthy stage that has run long enough
+//! is promoted when `auto_advance` is set. Every transition is written to
+//! `canary_stage_history` and `contract_audit_log` in one transaction.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use shared::{AuditActionType, CanaryRelease, CanaryStatus, RolloutStage};
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::audit_log;
+use crate::metrics::{CANARY_ACTIVE, CANARY_PROMOTIONS, CANARY_ROLLBACKS};
+
+pub const CONTROLLER_ACTOR: &str = "canary-controller";
+const DEFAULT_EVALUATION_INTERVAL_SECS: u64 = 30;
+/// Buckets per percent; callers are placed in 0..10_000.
+const BUCKETS_PER_PERCENT: u64 = 100;
+
+/// Share of traffic each stage sends to the new deployment.
+pub fn stage_percentage(stage: RolloutStage) -> i32 {
+    match stage {
+        RolloutStage::Stage1 => 1,
+        RolloutStage::Stage2 => 10,
+        RolloutStage::Stage3 => 50,
+        RolloutStage::Stage4 | RolloutStage::Complete => 100,
+    }
+}
+
+pub fn next_stage(stage: RolloutStage) -> RolloutStage {
+    match stage {
+        RolloutStage::Stage1 => RolloutStage::Stage2,
+        RolloutStage::Stage2 => RolloutStage::Stage3,
+        RolloutStage::Stage3 => RolloutStage::Stage4,
+        RolloutStage::Stage4 | RolloutStage::Complete => RolloutStage::Complete,
+    }
+}
+
+/// Stable position of `caller` in 0..10_000 for this canary.
+pub fn bucket(canary_id: Uuid, caller: &str) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(canary_id.as_bytes())
+        .chain_update(b":")
+        .chain_update(caller.trim().as_bytes())
+        .finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(prefix) % (100 * BUCKETS_PER_PERCENT)
+}
+
+/// The first stage whose traffic share covers `bucket`.
+pub fn entry_stage(bucket: u64) -> RolloutStage {
+    [
+        RolloutStage::Stage1,
+        RolloutStage::Stage2,
+        RolloutStage::Stage3,
+    ]
+    .into_iter()
+    .find(|stage| bucket < stage_percentage(*stage) as u64 * BUCKETS_PER_PERCENT)
+    .unwrap_or(RolloutStage::Stage4)
+}
+
+/// Traffic counted since the current stage started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct StageWindow {
+    pub requests: i64,
+    pub errors: i64,
+}
+
+impl StageWindow {
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 * 100.0 / self.requests as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    Hold,
+    Advance,
+    Rollback { reason: String },
+}
+
+/// Promotion policy for one canary, decoupled from the row for testing.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    pub error_rate_threshold: f64,
+    pub min_requests: i64,
+    pub min_stage_duration: chrono::Duration,
+    pub auto_advance: bool,
+}
+
+impl Policy {
+    pub fn of(canary: &CanaryRelease) -> Self {
+        Self {
+            error_rate_threshold: canary.error_rate_threshold.to_f64().unwrap_or(5.0),
+            min_requests: canary.min_requests_per_stage as i64,
+            min_stage_duration: chrono::Duration::seconds(canary.min_stage_duration_secs as i64),
+            auto_advance: canary.auto_advance,
+        }
+    }
+
+    /// Nothing is decided before the stage has seen `min_requests`; a stage
+    /// over the threshold is rolled back, a healthy one promoted once it has
+    /// also run for `min_stage_duration`.
+    pub fn decide(&self, window: StageWindow, stage_age: chrono::Duration) -> Decision {
+        if window.requests < self.min_requests.max(1) {
+            return Decision::Hold;
+        }
+        let rate = window.error_rate();
+        if rate > self.error_rate_threshold {
+            return Decision::Rollback {
+                reason: format!(
+                    "error rate {:.2}% over {} requests exceeded the {:.2}% threshold",
+                    rate, window.requests, self.error_rate_threshold
+                ),
+            };
+        }
+        if self.auto_advance && stage_age >= self.min_stage_duration {
+            Decision::Advance
+        } else {
+            Decision::Hold
+        }
+    }
+}
+
+fn decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(2)
+}
+
+fn snapshot(canary: &CanaryRelease) -> Value {
+    json!({
+        "canary_id": canary.id,
+        "status": canary.status,
+        "stage": canary.current_stage,
+        "percentage": canary.current_percentage,
+        "from_deployment_id": canary.from_deployment_id,
+        "to_deployment_id": canary.to_deployment_id,
+    })
+}
+
+pub async fn fetch(pool: &PgPool, canary_id: Uuid) -> Result<Option<CanaryRelease>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM canary_releases WHERE id = $1")
+        .bind(canary_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn stage_window(
+    pool: &PgPool,
+    canary: &CanaryRelease,
+) -> Result<StageWindow, sqlx::Error> {
+    let (requests, errors): (i64, i64) = sqlx::query_as(
+        "SELECT COALESCE(SUM(requests), 0)::BIGINT, COALESCE(SUM(errors), 0)::BIGINT
+         FROM canary_metrics WHERE canary_id = $1 AND timestamp >= $2",
+    )
+    .bind(canary.id)
+    .bind(canary.stage_started_at)
+    .fetch_one(pool)
+    .await?;
+    Ok(StageWindow { requests, errors })
+}
+
+/// A state change to apply to a canary.
+#[derive(Debug, Clone)]
+pub enum Transition {
+    Start,
+    /// Move to the next stage, or straight to `target_percentage` if given.
+    Advance {
+        target_percentage: Option<i32>,
+    },
+    Pause,
+    Resume,
+    Rollback {
+        reason: String,
+    },
+}
+
+impl Transition {
+    fn name(&self) -> &'static str {
+        match self {
+            Transition::Start => "started",
+            Transition::Advance { .. } => "advanced",
+            Transition::Pause => "paused",
+            Transition::Resume => "resumed",
+            Transition::Rollback { .. } => "rolled_back",
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CanaryError {
+    #[error("canary is {0:?}; {1}")]
+    InvalidState(CanaryStatus, &'static str),
+    #[error("target percentage must be between 1 and 100")]
+    InvalidTarget,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// The status, stage and percentage a transition leads to.
+fn plan(
+    canary: &CanaryRelease,
+    transition: &Transition,
+) -> Result<(CanaryStatus, RolloutStage, i32), CanaryError> {
+    use CanaryStatus::*;
+    let live = matches!(canary.status, Active | Paused);
+    match transition {
+        Transition::Start if canary.status == Pending => {
+            let pct = stage_percentage(RolloutStage::Stage1).min(canary.target_percentage);
+            Ok((Active, RolloutStage::Stage1, pct))
+        }
+        Transition::Start => Err(CanaryError::InvalidState(
+            canary.status,
+            "only pending canaries start",
+        )),
+        Transition::Advance { target_percentage } if live => {
+            let target = canary.target_percentage;
+            let pct = match target_percentage {
+                Some(p) if !(1..=100).contains(p) => return Err(CanaryError::InvalidTarget),
+                Some(p) => (*p).max(canary.current_percentage).min(target),
+                None => stage_percentage(next_stage(canary.current_stage)).min(target),
+            };
+            if pct >= target || canary.current_stage == RolloutStage::Complete {
+                return Ok((Completed, RolloutStage::Complete, target));
+            }
+            // The stage is the first whose share covers the new percentage.
+            let stage = [
+                RolloutStage::Stage1,
+                RolloutStage::Stage2,
+                RolloutStage::Stage3,
+            ]
+            .into_iter()
+            .find(|s| pct <= stage_percentage(*s))
+            .unwrap_or(RolloutStage::Stage4);
+            Ok((canary.status, stage, pct))
+        }
+        Transition::Pause if canary.status == Active => {
+            Ok((Paused, canary.current_stage, canary.current_percentage))
+        }
+        Transition::Resume if canary.status == Paused => {
+            Ok((Active, canary.current_stage, canary.current_percentage))
+        }
+        Transition::Rollback { .. } if live || canary.status == Pending => {
+            Ok((RolledBack, canary.current_stage, 0))
+        }
+        Transition::Advance { .. } | Transition::Rollback { .. } => Err(CanaryError::InvalidState(
+            canary.status,
+            "only active or paused canaries can change",
+        )),
+        Transition::Pause => Err(CanaryError::InvalidState(
+            canary.status,
+            "only active canaries pause",
+        )),
+        Transition::Resume => Err(CanaryError::InvalidState(
+            canary.status,
+            "only paused canaries resume",
+        )),
+    }
+}
+
+/// Apply `transition`, recording stage history and an audit entry.
+pub async fn apply(
+    pool: &PgPool,
+    canary_id: Uuid,
+    transition: Transition,
+    actor: &str,
+    window: Option<StageWindow>,
+) -> Result<CanaryRelease, CanaryError> {
+    let mut tx = pool.begin().await?;
+    let canary: CanaryRelease =
+        sqlx::query_as("SELECT * FROM canary_releases WHERE id = $1 FOR UPDATE")
+            .bind(canary_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    let (status, stage, percentage) = plan(&canary, &transition)?;
+    let reason = match &transition {
+        Transition::Rollback { reason } => Some(reason.clone()),
+        _ => None,
+    };
+    let finished = matches!(status, CanaryStatus::Completed | CanaryStatus::RolledBack);
+    let stage_changed = stage != canary.current_stage || percentage != canary.current_percentage;
+
+    let updated: CanaryRelease = sqlx::query_as(
+        "UPDATE canary_releases
+         SET status = $2, current_stage = $3, current_percentage = $4,
+             stage_started_at = CASE WHEN $5 THEN NOW() ELSE stage_started_at END,
+             completed_at = CASE WHEN $6 THEN NOW() ELSE completed_at END,
+             rollback_reason = COALESCE($7, rollback_reason)
+         WHERE id = $1
+         RETURNING *",
+    )
+    .bind(canary.id)
+    .bind(status)
+    .bind(stage)
+    .bind(percentage)
+    .bind(stage_changed)
+    .bind(finished)
+    .bind(&reason)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let metrics = window.map(|w| {
+        json!({
+            "requests": w.requests,
+            "errors": w.errors,
+            "error_rate": w.error_rate(),
+        })
+    });
+    if stage_changed {
+        sqlx::query(
+            "INSERT INTO canary_stage_history
+                (canary_id, from_stage, to_stage, from_percentage, to_percentage,
+                 transitioned_by, metrics_at_transition)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(canary.id)
+        .bind(canary.current_stage)
+        .bind(stage)
+        .bind(canary.current_percentage)
+        .bind(percentage)
+        .bind(actor)
+        .bind(&metrics)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let mut new_value = snapshot(&updated);
+    new_value["transition"] = json!(transition.name());
+    if let Some(reason) = &reason {
+        new_value["reason"] = json!(reason);
+    }
+    if let Some(metrics) = metrics {
+        new_value["metrics"] = metrics;
+    }
+    audit_log::append(
+        &mut tx,
+        canary.contract_id,
+        AuditActionType::CanaryTransition,
+        Some(snapshot(&canary)),
+        Some(new_value),
+        actor,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    match status {
+        CanaryStatus::RolledBack => CANARY_ROLLBACKS.inc(),
+        CanaryStatus::Completed => CANARY_PROMOTIONS.inc(),
+        _ => {}
+    }
+    refresh_active_gauge(pool).await;
+    tracing::info!(
+        canary_id = %canary.id,
+        transition = transition.name(),
+        stage = ?stage,
+        percentage,
+        actor,
+        "canary transition"
+    );
+    Ok(updated)
+}
+
+async fn refresh_active_gauge(pool: &PgPool) {
+    if let Ok(count) =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM canary_releases WHERE status = 'active'")
+            .fetch_one(pool)
+            .await
+    {
+        CANARY_ACTIVE.set(count);
+    }
+}
+
+/// A batch of traffic observed on the new deployment.
+#[derive(Debug, Clone, Default)]
+pub struct MetricSample {
+    pub requests: i32,
+    pub errors: i32,
+    pub avg_response_time_ms: Option<f64>,
+    pub p95_response_time_ms: Option<f64>,
+    pub p99_response_time_ms: Option<f64>,
+}
+
+/// Record a metric sample and update the canary's running totals.
+pub async fn record_metric(
+    pool: &PgPool,
+    canary: &CanaryRelease,
+    sample: &MetricSample,
+) -> Result<(), sqlx::Error> {
+    let rate = StageWindow {
+        requests: sample.requests as i64,
+        errors: sample.errors as i64,
+    }
+    .error_rate();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO canary_metrics
+            (canary_id, requests, errors, error_rate,
+             avg_response_time_ms, p95_response_time_ms, p99_response_time_ms)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(canary.id)
+    .bind(sample.requests)
+    .bind(sample.errors)
+    .bind(decimal(rate))
+    .bind(sample.avg_response_time_ms.map(decimal))
+    .bind(sample.p95_response_time_ms.map(decimal))
+    .bind(sample.p99_response_time_ms.map(decimal))
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE canary_releases
+         SET total_requests = total_requests + $2,
+             error_count = error_count + $3,
+             current_error_rate = ROUND(
+                 (error_count + $3)::NUMERIC * 100 / NULLIF(total_requests + $2, 0), 2)
+         WHERE id = $1",
+    )
+    .bind(canary.id)
+    .bind(sample.requests)
+    .bind(sample.errors)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+/// Judge an active canary's current stage and apply the resulting
+/// transition, if any.
+pub async fn evaluate(
+    pool: &PgPool,
+    canary_id: Uuid,
+) -> Result<Option<CanaryRelease>, CanaryError> {
+    let Some(canary) = fetch(pool, canary_id).await? else {
+        return Ok(None);
+    };
+    if canary.status != CanaryStatus::Active {
+        return Ok(None);
+    }
+    let window = stage_window(pool, &canary).await?;
+    let age = Utc::now() - canary.stage_started_at;
+    let transition = match Policy::of(&canary).decide(window, age) {
+        Decision::Hold => return Ok(None),
+        Decision::Advance => Transition::Advance {
+            target_percentage: None,
+        },
+        Decision::Rollback { reason } => Transition::Rollback { reason },
+    };
+    apply(pool, canary.id, transition, CONTROLLER_ACTOR, Some(window))
+        .await
+        .map(Some)
+}
+
+/// Evaluate all active canaries.
+pub async fn evaluate_active(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let ids: Vec<Uuid> =
+        sqlx::query_scalar("SELECT id FROM canary_releases WHERE status = 'active'")
+            .fetch_all(pool)
+            .await?;
+    let mut changed = 0;
+    for id in ids {
+        match evaluate(pool, id).await {
+            Ok(Some(_)) => changed += 1,
+            Ok(None) => {}
+            Err(err) => tracing::warn!(canary_id = %id, error = %err, "canary evaluation failed"),
+        }
+    }
+    Ok(changed)
+}
+
+/// Periodically promote or roll back active canaries, so a stage that has
+/// met its duration is promoted even when no new metrics arrive.
+pub fn spawn_canary_controller(pool: PgPool) {
+    let interval_secs = std::env::var("CANARY_EVALUATION_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_EVALUATION_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match evaluate_active(&pool).await {
+                Ok(0) => {}
+                Ok(n) => tracing::debug!(count = n, "canary: applied transitions"),
+                Err(err) => tracing::error!(error = ?err, "canary: evaluation failed"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canary(status: CanaryStatus, stage: RolloutStage, pct: i32) -> CanaryRelease {
+        CanaryRelease {
+            id: Uuid::new_v4(),
+            contract_id: Uuid::new_v4(),
+            from_deployment_id: Some(Uuid::new_v4()),
+            to_deployment_id: Uuid::new_v4(),
+            status,
+            current_stage: stage,
+            current_percentage: pct,
+            target_percentage: 100,
+            error_rate_threshold: Decimal::new(500, 2),
+            current_error_rate: None,
+            total_requests: 0,
+            error_count: 0,
+            started_at: Utc::now(),
+            completed_at: None,
+            created_by: None,
+            min_requests_per_stage: 100,
+            min_stage_duration_secs: 300,
+            auto_advance: true,
+            stage_started_at: Utc::now(),
+            rollback_reason: None,
+        }
+    }
+
+    #[test]
+    fn assignments_are_stable_and_monotonic() {
+        let id = Uuid::new_v4();
+        assert_eq!(bucket(id, "GABC"), bucket(id, "GABC"));
+        assert!(bucket(id, "GABC") < 10_000);
+
+        // Roughly 10% of callers land in the 10% stage or earlier.
+        let early = (0..10_000)
+            .filter(|i| entry_stage(bucket(id, &format!("G{}", i))) <= RolloutStage::Stage2)
+            .count();
+        assert!((800..1200).contains(&early), "{}", early);
+    }
+
+    #[test]
+    fn entry_stage_matches_stage_shares() {
+        assert_eq!(entry_stage(0), RolloutStage::Stage1);
+        assert_eq!(entry_stage(99), RolloutStage::Stage1);
+        assert_eq!(entry_stage(100), RolloutStage::Stage2);
+        assert_eq!(entry_stage(4_999), RolloutStage::Stage3);
+        assert_eq!(entry_stage(5_000), RolloutStage::Stage4);
+    }
+
+    #[test]
+    fn policy_waits_for_enough_traffic() {
+        let policy = Policy::of(&canary(CanaryStatus::Active, RolloutStage::Stage1, 1));
+        let long_ago = chrono::Duration::hours(1);
+        let few = StageWindow {
+            requests: 10,
+            errors: 10,
+        };
+        assert_eq!(policy.decide(few, long_ago), Decision::Hold);
+
+        let healthy = StageWindow {
+            requests: 1000,
+            errors: 10,
+        };
+        assert_eq!(policy.decide(healthy, long_ago), Decision::Advance);
+        assert_eq!(
+            policy.decide(healthy, chrono::Duration::seconds(10)),
+            Decision::Hold
+        );
+
+        let failing = StageWindow {
+            requests: 1000,
+            errors: 60,
+        };
+        assert!(matches!(
+            policy.decide(failing, chrono::Duration::seconds(10)),
+            Decision::Rollback { .. }
+        ));
+    }
+
+    #[test]
+    fn advance_walks_stages_and_completes_at_target() {
+        let c = canary(CanaryStatus::Active, RolloutStage::Stage1, 1);
+        let step = Transition::Advance {
+            target_percentage: None,
+        };
+        assert_eq!(
+            plan(&c, &step).unwrap(),
+            (CanaryStatus::Active, RolloutStage::Stage2, 10)
+        );
+
+        let mut capped = canary(CanaryStatus::Active, RolloutStage::Stage2, 10);
+        capped.target_percentage = 25;
+        assert_eq!(
+            plan(&capped, &step).unwrap(),
+            (CanaryStatus::Completed, RolloutStage::Complete, 25)
+        );
+
+        let jump = Transition::Advance {
+            target_percentage: Some(30),
+        };
+        assert_eq!(
+            plan(&c, &jump).unwrap(),
+            (CanaryStatus::Active, RolloutStage::Stage3, 30)
+        );
+    }
+
+    #[test]
+    fn finished_canaries_do_not_change() {
+        let done = canary(CanaryStatus::RolledBack, RolloutStage::Stage2, 0);
+        assert!(plan(
+            &done,
+            &Transition::Advance {
+                target_percentage: None
+            }
+        )
+        .is_err());
+        assert!(plan(&done, &Transition::Rollback { reason: "x".into() }).is_err());
+        assert!(plan(&done, &Transition::Resume).is_err());
+    }
+}
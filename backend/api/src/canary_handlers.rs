@@ -0,0 +1,469 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{
+    AdvanceCanaryRequest, CanaryMetric, CanaryRelease, CanaryStatus, CreateCanaryRequest,
+    RecordCanaryMetricRequest, RolloutStage,
+};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::canary::{self, CanaryError, MetricSample, Transition};
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::{db_internal_error, fetch_contract_identity};
+use crate::state::AppState;
+
+const RECENT_METRICS: i64 = 50;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CanaryListQuery {
+    pub contract_id: Option<String>,
+    pub status: Option<CanaryStatus>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignmentQuery {
+    pub caller: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CanaryActionRequest {
+    pub reason: Option<String>,
+    pub actor: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CanaryStageTransition {
+    pub from_stage: RolloutStage,
+    pub to_stage: RolloutStage,
+    pub from_percentage: i32,
+    pub to_percentage: i32,
+    pub transitioned_at: chrono::DateTime<chrono::Utc>,
+    pub transitioned_by: Option<String>,
+    pub metrics_at_transition: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CanaryDetail {
+    #[serde(flatten)]
+    pub canary: CanaryRelease,
+    pub stage_window: canary::StageWindow,
+    pub history: Vec<CanaryStageTransition>,
+    pub recent_metrics: Vec<CanaryMetric>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CanaryAssignment {
+    pub canary_id: Uuid,
+    pub caller: String,
+    /// Deployment the caller should be routed to
+    pub deployment_id: Option<Uuid>,
+    pub in_canary: bool,
+    /// Stage at which the caller reaches the new deployment
+    pub stage: RolloutStage,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricRecorded {
+    pub canary: CanaryRelease,
+    /// The transition the new sample triggered, if any
+    pub transitioned: bool,
+}
+
+fn parse_canary_id(raw: &str) -> ApiResult<Uuid> {
+    Uuid::parse_str(raw).map_err(|_| {
+        ApiError::bad_request("InvalidCanaryId", format!("Invalid canary ID: {}", raw))
+    })
+}
+
+fn canary_error(err: CanaryError) -> ApiError {
+    match err {
+        err @ CanaryError::InvalidState(..) => {
+            ApiError::conflict("InvalidCanaryTransition", err.to_string())
+        }
+        err @ CanaryError::InvalidTarget => {
+            ApiError::bad_request("InvalidTargetPercentage", err.to_string())
+        }
+        CanaryError::Database(err) => db_internal_error("canary transition", err),
+    }
+}
+
+async fn fetch_canary(state: &AppState, id: &str) -> ApiResult<CanaryRelease> {
+    let canary_id = parse_canary_id(id)?;
+    canary::fetch(&state.db, canary_id)
+        .await
+        .map_err(|err| db_internal_error("fetch canary", err))?
+        .ok_or_else(|| {
+            ApiError::not_found("CanaryNotFound", format!("No canary found with ID: {}", id))
+        })
+}
+
+/// The deployment must exist and belong to the canary's contract.
+pub(crate) async fn deployment_of_contract(
+    state: &AppState,
+    contract_uuid: Uuid,
+    raw: &str,
+) -> ApiResult<Uuid> {
+    let id = Uuid::parse_str(raw).map_err(|_| {
+        ApiError::bad_request(
+            "InvalidDeploymentId",
+            format!("Invalid deployment ID: {}", raw),
+        )
+    })?;
+    let owner: Option<Uuid> =
+        sqlx::query_scalar("SELECT contract_id FROM contract_deployments WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|err| db_internal_error("fetch deployment", err))?;
+    match owner {
+        Some(owner) if owner == contract_uuid => Ok(id),
+        Some(_) => Err(ApiError::unprocessable(
+            "DeploymentContractMismatch",
+            format!("Deployment {} belongs to a different contract", id),
+        )),
+        None => Err(ApiError::not_found(
+            "DeploymentNotFound",
+            format!("No deployment found with ID: {}", id),
+        )),
+    }
+}
+
+/// POST /api/canaries — start shifting traffic to `to_deployment_id`.
+pub async fn create_canary(
+    State(state): State<AppState>,
+    Json(req): Json<CreateCanaryRequest>,
+) -> ApiResult<(StatusCode, Json<CanaryRelease>)> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &req.contract_id).await?;
+    let to_id = deployment_of_contract(&state, contract_uuid, &req.to_deployment_id).await?;
+    let from_id = match req.from_deployment_id.as_deref() {
+        Some(raw) => Some(deployment_of_contract(&state, contract_uuid, raw).await?),
+        None => sqlx::query_scalar(
+            "SELECT id FROM contract_deployments WHERE contract_id = $1 AND status = 'active'",
+        )
+        .bind(contract_uuid)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|err| db_internal_error("fetch active deployment", err))?,
+    };
+    if from_id == Some(to_id) {
+        return Err(ApiError::unprocessable(
+            "SameDeployment",
+            "A canary needs two different deployments",
+        ));
+    }
+
+    let threshold = req.error_rate_threshold.unwrap_or(5.0);
+    if !(0.0..=100.0).contains(&threshold) {
+        return Err(ApiError::bad_request(
+            "InvalidErrorRateThreshold",
+            "error_rate_threshold must be between 0 and 100",
+        ));
+    }
+    let target = req.target_percentage.unwrap_or(100);
+    if !(1..=100).contains(&target) {
+        return Err(ApiError::bad_request(
+            "InvalidTargetPercentage",
+            "target_percentage must be between 1 and 100",
+        ));
+    }
+    if req.min_requests_per_stage.is_some_and(|n| n < 0)
+        || req.min_stage_duration_secs.is_some_and(|n| n < 0)
+    {
+        return Err(ApiError::bad_request(
+            "InvalidStagePolicy",
+            "min_requests_per_stage and min_stage_duration_secs must not be negative",
+        ));
+    }
+
+    let inserted: Result<Uuid, sqlx::Error> = sqlx::query_scalar(
+        "INSERT INTO canary_releases
+            (contract_id, from_deployment_id, to_deployment_id, status, current_stage,
+             current_percentage, target_percentage, error_rate_threshold,
+             min_requests_per_stage, min_stage_duration_secs, auto_advance, created_by)
+         VALUES ($1, $2, $3, 'pending', 'stage_1', 0, $4, $5,
+                 COALESCE($6, 100), COALESCE($7, 300), COALESCE($8, TRUE), $9)
+         RETURNING id",
+    )
+    .bind(contract_uuid)
+    .bind(from_id)
+    .bind(to_id)
+    .bind(target)
+    .bind(Decimal::from_f64(threshold).unwrap_or_default().round_dp(2))
+    .bind(req.min_requests_per_stage)
+    .bind(req.min_stage_duration_secs)
+    .bind(req.auto_advance)
+    .bind(&req.created_by)
+    .fetch_one(&state.db)
+    .await;
+    let canary_id = match inserted {
+        Ok(id) => id,
+        Err(sqlx::Error::Database(db)) if db.is_unique_violation() => {
+            return Err(ApiError::conflict(
+                "CanaryAlreadyActive",
+                "This contract already has a pending or active canary",
+            ))
+        }
+        Err(err) => return Err(db_internal_error("create canary", err)),
+    };
+
+    let actor = req
+        .created_by
+        .as_deref()
+        .unwrap_or(canary::CONTROLLER_ACTOR);
+    let canary = canary::apply(&state.db, canary_id, Transition::Start, actor, None)
+        .await
+        .map_err(canary_error)?;
+    Ok((StatusCode::CREATED, Json(canary)))
+}
+
+/// GET /api/canaries
+pub async fn list_canaries(
+    State(state): State<AppState>,
+    Query(query): Query<CanaryListQuery>,
+) -> ApiResult<Json<Vec<CanaryRelease>>> {
+    let contract_uuid = match query.contract_id.as_deref() {
+        Some(id) => Some(fetch_contract_identity(&state, id).await?.0),
+        None => None,
+    };
+    let canaries: Vec<CanaryRelease> = sqlx::query_as(
+        "SELECT * FROM canary_releases
+         WHERE ($1::uuid IS NULL OR contract_id = $1)
+           AND ($2::canary_status IS NULL OR status = $2)
+         ORDER BY started_at DESC
+         LIMIT $3",
+    )
+    .bind(contract_uuid)
+    .bind(query.status)
+    .bind(query.limit.unwrap_or(50).clamp(1, 200))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list canaries", err))?;
+    Ok(Json(canaries))
+}
+
+/// GET /api/canaries/:id — the canary with its stage history and latest metrics.
+pub async fn get_canary(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CanaryDetail>> {
+    let canary = fetch_canary(&state, &id).await?;
+    let stage_window = canary::stage_window(&state.db, &canary)
+        .await
+        .map_err(|err| db_internal_error("canary stage window", err))?;
+    let history: Vec<CanaryStageTransition> = sqlx::query_as(
+        "SELECT from_stage, to_stage, from_percentage, to_percentage,
+                transitioned_at, transitioned_by, metrics_at_transition
+         FROM canary_stage_history WHERE canary_id = $1
+         ORDER BY transitioned_at",
+    )
+    .bind(canary.id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("canary stage history", err))?;
+    let recent_metrics: Vec<CanaryMetric> = sqlx::query_as(
+        "SELECT * FROM canary_metrics WHERE canary_id = $1 ORDER BY timestamp DESC LIMIT $2",
+    )
+    .bind(canary.id)
+    .bind(RECENT_METRICS)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("canary metrics", err))?;
+
+    Ok(Json(CanaryDetail {
+        canary,
+        stage_window,
+        history,
+        recent_metrics,
+    }))
+}
+
+/// POST /api/canaries/:id/advance — promote now, regardless of policy.
+pub async fn advance_canary(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<AdvanceCanaryRequest>,
+) -> ApiResult<Json<CanaryRelease>> {
+    let canary = fetch_canary(&state, &id).await?;
+    let window = canary::stage_window(&state.db, &canary)
+        .await
+        .map_err(|err| db_internal_error("canary stage window", err))?;
+    let actor = req
+        .advanced_by
+        .as_deref()
+        .unwrap_or(canary::CONTROLLER_ACTOR);
+    let transition = Transition::Advance {
+        target_percentage: req.target_percentage,
+    };
+    canary::apply(&state.db, canary.id, transition, actor, Some(window))
+        .await
+        .map(Json)
+        .map_err(canary_error)
+}
+
+async fn manual_transition(
+    state: &AppState,
+    id: &str,
+    transition: Transition,
+    actor: Option<&str>,
+) -> ApiResult<Json<CanaryRelease>> {
+    let canary = fetch_canary(state, id).await?;
+    canary::apply(
+        &state.db,
+        canary.id,
+        transition,
+        actor.unwrap_or(canary::CONTROLLER_ACTOR),
+        None,
+    )
+    .await
+    .map(Json)
+    .map_err(canary_error)
+}
+
+/// POST /api/canaries/:id/rollback
+pub async fn rollback_canary(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<CanaryActionRequest>,
+) -> ApiResult<Json<CanaryRelease>> {
+    let reason = req
+        .reason
+        .unwrap_or_else(|| "rolled back manually".to_string());
+    manual_transition(
+        &state,
+        &id,
+        Transition::Rollback { reason },
+        req.actor.as_deref(),
+    )
+    .await
+}
+
+/// POST /api/canaries/:id/pause
+pub async fn pause_canary(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<CanaryActionRequest>,
+) -> ApiResult<Json<CanaryRelease>> {
+    manual_transition(&state, &id, Transition::Pause, req.actor.as_deref()).await
+}
+
+/// POST /api/canaries/:id/resume
+pub async fn resume_canary(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<CanaryActionRequest>,
+) -> ApiResult<Json<CanaryRelease>> {
+    manual_transition(&state, &id, Transition::Resume, req.actor.as_deref()).await
+}
+
+/// POST /api/canaries/:id/metrics — record traffic on the new deployment
+/// and re-evaluate the current stage.
+pub async fn record_canary_metric(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<RecordCanaryMetricRequest>,
+) -> ApiResult<Json<MetricRecorded>> {
+    if !req.canary_id.is_empty() && req.canary_id != id {
+        return Err(ApiError::bad_request(
+            "CanaryIdMismatch",
+            "canary_id in the body does not match the path",
+        ));
+    }
+    if req.requests < 0 || req.errors < 0 || req.errors > req.requests {
+        return Err(ApiError::bad_request(
+            "InvalidCanaryMetric",
+            "requests and errors must be non-negative and errors cannot exceed requests",
+        ));
+    }
+    let canary = fetch_canary(&state, &id).await?;
+    if canary.status != CanaryStatus::Active {
+        return Err(ApiError::conflict(
+            "CanaryNotActive",
+            format!(
+                "Canary is {:?}; metrics are only recorded while active",
+                canary.status
+            ),
+        ));
+    }
+
+    let sample = MetricSample {
+        requests: req.requests,
+        errors: req.errors,
+        avg_response_time_ms: req.avg_response_time_ms,
+        p95_response_time_ms: req.p95_response_time_ms,
+        p99_response_time_ms: req.p99_response_time_ms,
+    };
+    canary::record_metric(&state.db, &canary, &sample)
+        .await
+        .map_err(|err| db_internal_error("record canary metric", err))?;
+
+    let transitioned = canary::evaluate(&state.db, canary.id)
+        .await
+        .map_err(canary_error)?;
+    let transitioned_now = transitioned.is_some();
+    let canary = match transitioned {
+        Some(canary) => canary,
+        None => fetch_canary(&state, &id).await?,
+    };
+    Ok(Json(MetricRecorded {
+        canary,
+        transitioned: transitioned_now,
+    }))
+}
+
+/// GET /api/canaries/:id/assignment?caller= — which deployment serves a caller.
+pub async fn get_canary_assignment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<AssignmentQuery>,
+) -> ApiResult<Json<CanaryAssignment>> {
+    let caller = query.caller.trim();
+    if caller.is_empty() || caller.len() > 56 {
+        return Err(ApiError::bad_request(
+            "InvalidCaller",
+            "caller must be a Stellar address of at most 56 characters",
+        ));
+    }
+    let canary = fetch_canary(&state, &id).await?;
+    let bucket = canary::bucket(canary.id, caller);
+    let stage = canary::entry_stage(bucket);
+
+    let in_canary = match canary.status {
+        CanaryStatus::Completed => true,
+        CanaryStatus::Active | CanaryStatus::Paused => {
+            bucket < canary.current_percentage as u64 * 100
+        }
+        _ => false,
+    };
+    if in_canary && canary.status != CanaryStatus::Completed {
+        sqlx::query(
+            "INSERT INTO canary_user_assignments (canary_id, user_address, stage)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (canary_id, user_address) DO NOTHING",
+        )
+        .bind(canary.id)
+        .bind(caller)
+        .bind(stage)
+        .execute(&state.db)
+        .await
+        .map_err(|err| db_internal_error("record canary assignment", err))?;
+    }
+
+    Ok(Json(CanaryAssignment {
+        canary_id: canary.id,
+        caller: caller.to_string(),
+        deployment_id: if in_canary {
+            Some(canary.to_deployment_id)
+        } else {
+            canary.from_deployment_id
+        },
+        in_canary,
+        stage,
+    }))
+}
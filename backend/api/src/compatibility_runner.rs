@@ -0,0 +1,419 @@
+// compatibility_runner.rs
+// Job executor for compatibility test runs.
+//
+// Each run rebuilds the contract's latest verified source against the
+// requested soroban-sdk version and replays its recorded fixtures on that
+// SDK's host (see `verifier::fixtures`). Runs are executed as
+// `compatibility_run` jobs (see `job_worker`). The outcome is stored on the
+// run and mirrored into `contract_compatibility`, with history and publisher
+// notifications when the status changes.
+
+use chrono::Utc;
+use serde_json::json;
+use shared::RegistryError;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::time::Instant;
+use uuid::Uuid;
+use verifier::fixtures::{self, CompatibilityReport, Fixture};
+
+use crate::compatibility_testing_handlers::{
+    CompatibilityStatus, CompatibilityTestRun, ContractTestFixture,
+};
+use crate::jobs::{JobContext, JobError};
+
+const MAX_ERROR_LEN: usize = 2_000;
+
+/// `COMPATIBILITY_TARGET_DIR` optionally names a directory for per-SDK cargo
+/// target caches so repeat runs skip rebuilding the SDK.
+fn target_root() -> Option<PathBuf> {
+    std::env::var("COMPATIBILITY_TARGET_DIR")
+        .ok()
+        .map(PathBuf::from)
+}
+
+/// Execute one queued run. Returns the run's summary as the job result.
+pub async fn execute(ctx: &JobContext, run_id: Uuid) -> Result<serde_json::Value, JobError> {
+    let pool = &ctx.pool;
+    let run: Option<CompatibilityTestRun> =
+        sqlx::query_as("SELECT * FROM compatibility_test_runs WHERE id = $1")
+            .bind(run_id)
+            .fetch_optional(pool)
+            .await?;
+    let Some(run) = run else {
+        return Err(JobError::permanent(format!(
+            "Compatibility run {} no longer exists",
+            run_id
+        )));
+    };
+    if run.status == "completed" || run.status == "failed" {
+        // Already settled by an earlier attempt.
+        return Ok(summary(
+            &run.id,
+            run.result.as_ref(),
+            run.error_message.as_deref(),
+        ));
+    }
+
+    sqlx::query(
+        "UPDATE compatibility_test_runs SET status = 'running', started_at = NOW() WHERE id = $1",
+    )
+    .bind(run.id)
+    .execute(pool)
+    .await?;
+
+    tracing::info!(
+        run_id = %run.id,
+        contract_id = %run.contract_id,
+        sdk_version = %run.sdk_version,
+        "compatibility: running"
+    );
+
+    let source: Option<(String, Option<serde_json::Value>)> = sqlx::query_as(
+        r#"
+        SELECT source_code, build_params FROM verifications
+        WHERE contract_id = $1 AND status = 'verified' AND source_code IS NOT NULL
+        ORDER BY verified_at DESC NULLS LAST, created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(run.contract_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some((source_code, build_params)) = source else {
+        return Err(fail_run(pool, run.id, "Contract has no verified source to rebuild").await);
+    };
+
+    let rows: Vec<ContractTestFixture> = sqlx::query_as(
+        "SELECT * FROM contract_test_fixtures WHERE contract_id = $1 ORDER BY created_at, name",
+    )
+    .bind(run.contract_id)
+    .fetch_all(pool)
+    .await?;
+    let fixtures = match rows
+        .iter()
+        .map(ContractTestFixture::to_fixture)
+        .collect::<Result<Vec<Fixture>, String>>()
+    {
+        Ok(fixtures) => fixtures,
+        Err(message) => return Err(fail_run(pool, run.id, &message).await),
+    };
+
+    ctx.progress(
+        10,
+        format!(
+            "Building against soroban-sdk {} and replaying {} fixture(s)",
+            run.sdk_version,
+            fixtures.len()
+        ),
+    )
+    .await;
+
+    let target_dir = target_root().map(|root| root.join(&run.sdk_version));
+    let start = Instant::now();
+    let result = fixtures::run_compatibility(
+        &source_code,
+        build_params.as_ref(),
+        &run.sdk_version,
+        &fixtures,
+        target_dir.as_deref(),
+    )
+    .await;
+    let duration_ms = start.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let report = match result {
+        Ok(report) => report,
+        // Could not start cargo, write the harness, ...: worth another try.
+        Err(RegistryError::Internal(message)) if !ctx.is_last_attempt() => {
+            return Err(JobError::retryable(message));
+        }
+        Err(err) => return Err(fail_run(pool, run.id, &err.to_string()).await),
+    };
+
+    ctx.progress(90, "Recording results").await;
+    let (status, error_message) = classify(&report, &run.sdk_version);
+    sqlx::query(
+        r#"
+        UPDATE compatibility_test_runs
+        SET status = 'completed', result = $2, fixtures_total = $3, fixtures_passed = $4,
+            fixture_results = $5, duration_ms = $6, output = $7, error_message = $8,
+            finished_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(run.id)
+    .bind(&status)
+    .bind(report.fixtures.len() as i32)
+    .bind(report.passed() as i32)
+    .bind(serde_json::to_value(&report.fixtures).unwrap_or_default())
+    .bind(duration_ms)
+    .bind(&report.output)
+    .bind(&error_message)
+    .execute(pool)
+    .await?;
+
+    record_result(
+        pool,
+        &run,
+        status.clone(),
+        duration_ms,
+        &report.output,
+        error_message.as_deref(),
+    )
+    .await?;
+
+    let mut summary = summary(&run.id, Some(&status), error_message.as_deref());
+    summary["fixtures_total"] = json!(report.fixtures.len());
+    summary["fixtures_passed"] = json!(report.passed());
+    Ok(summary)
+}
+
+fn summary(
+    run_id: &Uuid,
+    result: Option<&CompatibilityStatus>,
+    error_message: Option<&str>,
+) -> serde_json::Value {
+    json!({
+        "run_id": run_id,
+        "result": result,
+        "error_message": error_message,
+    })
+}
+
+/// Settle a run whose job failed for good or was cancelled.
+pub async fn abandon_run(pool: &PgPool, run_id: Uuid, message: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE compatibility_test_runs
+        SET status = 'failed', error_message = $2, finished_at = NOW()
+        WHERE id = $1 AND status IN ('queued', 'running')
+        "#,
+    )
+    .bind(run_id)
+    .bind(truncate(message))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The run could not be carried out; the matrix is left untouched. Returns
+/// the (permanent) job error to report.
+async fn fail_run(pool: &PgPool, run_id: Uuid, message: &str) -> JobError {
+    tracing::warn!(run_id = %run_id, error = message, "compatibility: run failed");
+    if let Err(err) = abandon_run(pool, run_id, message).await {
+        return err.into();
+    }
+    JobError::permanent(truncate(message))
+}
+
+fn truncate(value: &str) -> String {
+    if value.len() <= MAX_ERROR_LEN {
+        return value.to_string();
+    }
+    let mut end = MAX_ERROR_LEN;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...[truncated]", &value[..end])
+}
+
+/// Map a report to the matrix status. A contract without fixtures that
+/// builds and loads is only a warning: nothing about its behaviour was checked.
+pub fn classify(
+    report: &CompatibilityReport,
+    sdk_version: &str,
+) -> (CompatibilityStatus, Option<String>) {
+    if let Some(build_error) = &report.build_error {
+        return (
+            CompatibilityStatus::Incompatible,
+            Some(truncate(&format!(
+                "Does not build against soroban-sdk {}: {}",
+                sdk_version, build_error
+            ))),
+        );
+    }
+    if let Some(load_error) = &report.load_error {
+        return (
+            CompatibilityStatus::Incompatible,
+            Some(truncate(&format!(
+                "The soroban-sdk {} host rejected the contract: {}",
+                sdk_version, load_error
+            ))),
+        );
+    }
+
+    let failed: Vec<&str> = report
+        .fixtures
+        .iter()
+        .filter(|f| !f.passed)
+        .map(|f| f.name.as_str())
+        .collect();
+    if !failed.is_empty() {
+        return (
+            CompatibilityStatus::Incompatible,
+            Some(truncate(&format!(
+                "{} of {} fixtures failed: {}",
+                failed.len(),
+                report.fixtures.len(),
+                failed.join(", ")
+            ))),
+        );
+    }
+    if report.fixtures.is_empty() {
+        return (
+            CompatibilityStatus::Warning,
+            Some("Builds and loads, but the contract has no recorded fixtures".to_string()),
+        );
+    }
+    (CompatibilityStatus::Compatible, None)
+}
+
+/// Upsert the matrix cell and record history/notifications on a change.
+async fn record_result(
+    pool: &PgPool,
+    run: &CompatibilityTestRun,
+    status: CompatibilityStatus,
+    duration_ms: i32,
+    output: &str,
+    error_message: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+
+    let previous_status: Option<CompatibilityStatus> = sqlx::query_scalar(
+        r#"
+        SELECT compatible FROM contract_compatibility
+        WHERE contract_id = $1 AND sdk_version = $2 AND wasm_runtime = $3 AND network = $4
+        "#,
+    )
+    .bind(run.contract_id)
+    .bind(&run.sdk_version)
+    .bind(&run.wasm_runtime)
+    .bind(&run.network)
+    .fetch_optional(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO contract_compatibility
+            (contract_id, sdk_version, wasm_runtime, network, compatible, tested_at,
+             test_duration_ms, test_output, error_message)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (contract_id, sdk_version, wasm_runtime, network)
+        DO UPDATE SET
+            compatible = EXCLUDED.compatible,
+            tested_at = EXCLUDED.tested_at,
+            test_duration_ms = EXCLUDED.test_duration_ms,
+            test_output = EXCLUDED.test_output,
+            error_message = EXCLUDED.error_message,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(run.contract_id)
+    .bind(&run.sdk_version)
+    .bind(&run.wasm_runtime)
+    .bind(&run.network)
+    .bind(&status)
+    .bind(now)
+    .bind(duration_ms)
+    .bind(output)
+    .bind(error_message)
+    .execute(pool)
+    .await?;
+
+    if previous_status.as_ref() == Some(&status) {
+        return Ok(());
+    }
+
+    let change_reason = match &previous_status {
+        None => "Initial test".to_string(),
+        Some(old) => format!("Status changed from {} to {}", old, status),
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO contract_compatibility_history
+            (contract_id, sdk_version, wasm_runtime, network, previous_status, new_status, changed_at, change_reason)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(run.contract_id)
+    .bind(&run.sdk_version)
+    .bind(&run.wasm_runtime)
+    .bind(&run.network)
+    .bind(&previous_status)
+    .bind(&status)
+    .bind(now)
+    .bind(&change_reason)
+    .execute(pool)
+    .await?;
+
+    // Notify the publisher when the result is no longer fully compatible.
+    if status != CompatibilityStatus::Compatible {
+        let message = format!(
+            "Contract compatibility changed to '{}' for SDK {} / Runtime {} / Network {}",
+            status, run.sdk_version, run.wasm_runtime, run.network
+        );
+        sqlx::query(
+            r#"
+            INSERT INTO compatibility_notifications (contract_id, sdk_version, message)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(run.contract_id)
+        .bind(&run.sdk_version)
+        .bind(&message)
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use verifier::fixtures::FixtureOutcome;
+
+    fn report(fixtures: Vec<(&str, bool)>) -> CompatibilityReport {
+        CompatibilityReport {
+            build_error: None,
+            load_error: None,
+            fixtures: fixtures
+                .into_iter()
+                .map(|(name, passed)| FixtureOutcome {
+                    name: name.to_string(),
+                    passed,
+                    message: None,
+                })
+                .collect(),
+            output: String::new(),
+        }
+    }
+
+    #[test]
+    fn classifies_reports() {
+        let (status, error) = classify(&report(vec![("a", true), ("b", true)]), "21.7.7");
+        assert_eq!(status, CompatibilityStatus::Compatible);
+        assert!(error.is_none());
+
+        let (status, error) = classify(&report(vec![("a", true), ("b", false)]), "21.7.7");
+        assert_eq!(status, CompatibilityStatus::Incompatible);
+        assert_eq!(error.unwrap(), "1 of 2 fixtures failed: b");
+
+        let (status, _) = classify(&report(vec![]), "21.7.7");
+        assert_eq!(status, CompatibilityStatus::Warning);
+
+        let mut rejected = report(vec![]);
+        rejected.load_error = Some("HostError: Error(WasmVm, InvalidInput)".into());
+        let (status, error) = classify(&rejected, "21.7.7");
+        assert_eq!(status, CompatibilityStatus::Incompatible);
+        assert!(error.unwrap().contains("host rejected"));
+
+        let mut broken = report(vec![("a", true)]);
+        broken.build_error = Some("error[E0433]".into());
+        let (status, error) = classify(&broken, "22.0.0");
+        assert_eq!(status, CompatibilityStatus::Incompatible);
+        assert!(error
+            .unwrap()
+            .starts_with("Does not build against soroban-sdk 22.0.0"));
+    }
+}
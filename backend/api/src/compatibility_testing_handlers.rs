@@ -3,15 +3,21 @@
 
 use axum::{
     extract::{Path, Query, State},
+    http::StatusCode,
     Json,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::SemVer;
 use sqlx::FromRow;
 use uuid::Uuid;
+use verifier::fixtures::Fixture;
 
 use crate::{
     error::{ApiError, ApiResult},
+    job_worker::CompatibilityRunPayload,
+    jobs::{self, JobKind, NewJob},
     state::AppState,
 };
 
@@ -21,6 +27,7 @@ use crate::{
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::Type)]
 #[sqlx(type_name = "compatibility_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum CompatibilityStatus {
     Compatible,
     Warning,
@@ -85,9 +92,76 @@ pub struct CompatibilityTestSummary {
 
 #[derive(Debug, Deserialize)]
 pub struct RunCompatibilityTestRequest {
+    /// A single soroban-sdk version, e.g. `21.7.7`
+    #[serde(default)]
+    pub sdk_version: Option<String>,
+    /// Several soroban-sdk versions to test in one request
+    #[serde(default)]
+    pub sdk_versions: Vec<String>,
+    pub wasm_runtime: String,
+    pub network: String,
+}
+
+/// A queued or finished rebuild of a contract against one SDK version.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CompatibilityTestRun {
+    pub id: Uuid,
+    pub contract_id: Uuid,
     pub sdk_version: String,
     pub wasm_runtime: String,
     pub network: String,
+    /// queued, running, completed or failed (the run itself could not happen)
+    pub status: String,
+    pub result: Option<CompatibilityStatus>,
+    pub fixtures_total: Option<i32>,
+    pub fixtures_passed: Option<i32>,
+    pub fixture_results: Option<serde_json::Value>,
+    pub duration_ms: Option<i32>,
+    pub output: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// The job executing this run (see `/api/jobs/:id`)
+    pub job_id: Option<Uuid>,
+}
+
+/// A recorded contract call replayed by every compatibility run.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ContractTestFixture {
+    pub id: Uuid,
+    pub contract_id: Uuid,
+    pub name: String,
+    pub function_name: String,
+    pub args: serde_json::Value,
+    pub expected: Option<serde_json::Value>,
+    pub expect_error: bool,
+    pub error_code: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ContractTestFixture {
+    pub fn to_fixture(&self) -> Result<Fixture, String> {
+        let invalid = |e: serde_json::Error| format!("Fixture '{}' is invalid: {}", self.name, e);
+        Ok(Fixture {
+            name: self.name.clone(),
+            function: self.function_name.clone(),
+            args: serde_json::from_value(self.args.clone()).map_err(invalid)?,
+            expected: self
+                .expected
+                .clone()
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(invalid)?,
+            expect_error: self.expect_error,
+            error_code: self.error_code.map(|code| code as u32),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunListQuery {
+    pub limit: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -153,8 +227,7 @@ pub async fn get_compatibility_matrix(
 
     let rows: Vec<ContractCompatibilityRow> = sqlx::query_as(
         r#"
-        SELECT id, contract_id, sdk_version, wasm_runtime, network,
-               compatible AS "compatible: CompatibilityStatus",
+        SELECT id, contract_id, sdk_version, wasm_runtime, network, compatible,
                tested_at, test_duration_ms, test_output, error_message,
                created_at, updated_at
         FROM contract_compatibility
@@ -230,148 +303,276 @@ pub async fn get_compatibility_matrix(
 
 /// POST /api/contracts/:id/compatibility-matrix/test
 ///
-/// Run a compatibility test for a contract against a specific SDK version,
-/// Wasm runtime, and network. Attempts to invoke the contract with synthetic
-/// operations and records the result.
+/// Queue compatibility runs for one or more soroban-sdk versions. The
+/// compatibility worker rebuilds the contract's verified source against each
+/// version and executes its recorded fixtures; the returned runs can be
+/// polled for results, which also land in the matrix.
 pub async fn run_compatibility_test(
     State(state): State<AppState>,
     Path(contract_id): Path<Uuid>,
     Json(body): Json<RunCompatibilityTestRequest>,
-) -> ApiResult<Json<CompatibilityTestEntry>> {
-    // Verify contract exists
-    let exists: bool = sqlx::query_scalar(
-        "SELECT COUNT(*) > 0 FROM contracts WHERE id = $1",
+) -> ApiResult<(StatusCode, Json<Vec<CompatibilityTestRun>>)> {
+    let mut versions: Vec<String> = body
+        .sdk_version
+        .iter()
+        .chain(body.sdk_versions.iter())
+        .map(|v| v.trim().to_string())
+        .collect();
+    versions.sort();
+    versions.dedup();
+
+    if versions.is_empty() {
+        return Err(ApiError::bad_request(
+            "MissingSdkVersion",
+            "Provide sdk_version or sdk_versions",
+        ));
+    }
+    if let Some(invalid) = versions.iter().find(|v| !is_sdk_version(v)) {
+        return Err(ApiError::bad_request(
+            "InvalidSdkVersion",
+            format!("'{}' is not a soroban-sdk version (e.g. 21.7.7)", invalid),
+        ));
+    }
+    if body.wasm_runtime.trim().is_empty() || body.network.trim().is_empty() {
+        return Err(ApiError::bad_request(
+            "InvalidRequest",
+            "wasm_runtime and network are required",
+        ));
+    }
+
+    ensure_contract_exists(&state, contract_id).await?;
+
+    let has_source: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM verifications
+            WHERE contract_id = $1 AND status = 'verified' AND source_code IS NOT NULL
+        )
+        "#,
     )
     .bind(contract_id)
     .fetch_one(&state.db)
     .await
     .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
 
-    if !exists {
-        return Err(ApiError::not_found("NotFound", "Contract not found"));
+    if !has_source {
+        return Err(ApiError::unprocessable(
+            "NoVerifiedSource",
+            "Compatibility runs rebuild the contract's verified source; verify the contract first",
+        ));
     }
 
-    // Simulate compatibility test execution
-    // In production, this would invoke the contract with synthetic operations
-    // against the specified SDK/runtime/network combination.
-    let start = std::time::Instant::now();
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
+
+    let mut runs: Vec<CompatibilityTestRun> = sqlx::query_as(
+        r#"
+        INSERT INTO compatibility_test_runs (contract_id, sdk_version, wasm_runtime, network)
+        SELECT $1, v, $3, $4 FROM UNNEST($2::text[]) AS v
+        RETURNING *
+        "#,
+    )
+    .bind(contract_id)
+    .bind(&versions)
+    .bind(body.wasm_runtime.trim())
+    .bind(body.network.trim())
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
+
+    for run in &mut runs {
+        let job = jobs::enqueue(
+            &mut *tx,
+            NewJob::new(
+                JobKind::CompatibilityRun,
+                json!(CompatibilityRunPayload { run_id: run.id }),
+            )
+            .contract(contract_id),
+        )
+        .await
+        .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
+
+        sqlx::query("UPDATE compatibility_test_runs SET job_id = $2 WHERE id = $1")
+            .bind(run.id)
+            .bind(job.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
+        run.job_id = Some(job.id);
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
+
+    Ok((StatusCode::ACCEPTED, Json(runs)))
+}
+
+/// A plain version such as `21.7.7` or `22.0.0-rc.3`; it ends up in a
+/// generated Cargo.toml, so nothing else is accepted.
+fn is_sdk_version(version: &str) -> bool {
+    SemVer::parse(version).is_some()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+}
+
+async fn ensure_contract_exists(state: &AppState, contract_id: Uuid) -> ApiResult<()> {
+    let exists: bool = sqlx::query_scalar("SELECT COUNT(*) > 0 FROM contracts WHERE id = $1")
+        .bind(contract_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
 
-    let (status, error_message) = simulate_compatibility_test(
-        &body.sdk_version,
-        &body.wasm_runtime,
-        &body.network,
-    );
+    if !exists {
+        return Err(ApiError::not_found("NotFound", "Contract not found"));
+    }
+    Ok(())
+}
 
-    let duration_ms = start.elapsed().as_millis() as i32;
-    let now = Utc::now();
+/// GET /api/contracts/:id/compatibility-matrix/runs
+///
+/// Recent compatibility runs for a contract, newest first.
+pub async fn list_compatibility_runs(
+    State(state): State<AppState>,
+    Path(contract_id): Path<Uuid>,
+    Query(params): Query<RunListQuery>,
+) -> ApiResult<Json<Vec<CompatibilityTestRun>>> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
 
-    // Check if there's an existing entry to detect status changes
-    let previous: Option<ContractCompatibilityRow> = sqlx::query_as(
+    let runs: Vec<CompatibilityTestRun> = sqlx::query_as(
         r#"
-        SELECT id, contract_id, sdk_version, wasm_runtime, network,
-               compatible AS "compatible: CompatibilityStatus",
-               tested_at, test_duration_ms, test_output, error_message,
-               created_at, updated_at
-        FROM contract_compatibility
-        WHERE contract_id = $1 AND sdk_version = $2 AND wasm_runtime = $3 AND network = $4
+        SELECT * FROM compatibility_test_runs
+        WHERE contract_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
         "#,
     )
     .bind(contract_id)
-    .bind(&body.sdk_version)
-    .bind(&body.wasm_runtime)
-    .bind(&body.network)
-    .fetch_optional(&state.db)
+    .bind(limit)
+    .fetch_all(&state.db)
     .await
     .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
 
-    let previous_status = previous.as_ref().map(|p| p.compatible.clone());
+    Ok(Json(runs))
+}
 
-    // Upsert the compatibility result
-    sqlx::query(
+/// GET /api/contracts/:id/compatibility-matrix/runs/:run_id
+///
+/// A single run including per-fixture results and the harness output.
+pub async fn get_compatibility_run(
+    State(state): State<AppState>,
+    Path((contract_id, run_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<Json<CompatibilityTestRun>> {
+    let run: Option<CompatibilityTestRun> =
+        sqlx::query_as("SELECT * FROM compatibility_test_runs WHERE id = $1 AND contract_id = $2")
+            .bind(run_id)
+            .bind(contract_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
+
+    run.map(Json)
+        .ok_or_else(|| ApiError::not_found("RunNotFound", "Compatibility run not found"))
+}
+
+/// GET /api/contracts/:id/compatibility-matrix/fixtures
+pub async fn list_test_fixtures(
+    State(state): State<AppState>,
+    Path(contract_id): Path<Uuid>,
+) -> ApiResult<Json<Vec<ContractTestFixture>>> {
+    let fixtures: Vec<ContractTestFixture> = sqlx::query_as(
+        "SELECT * FROM contract_test_fixtures WHERE contract_id = $1 ORDER BY created_at, name",
+    )
+    .bind(contract_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
+
+    Ok(Json(fixtures))
+}
+
+/// POST /api/contracts/:id/compatibility-matrix/fixtures
+///
+/// Record a contract call, e.g.
+/// `{"name": "adds", "function": "add", "args": [{"type": "u32", "value": 2}],
+/// "expected": {"type": "u32", "value": 2}}`. Replaces a fixture of the same name.
+pub async fn upsert_test_fixture(
+    State(state): State<AppState>,
+    Path(contract_id): Path<Uuid>,
+    Json(fixture): Json<Fixture>,
+) -> ApiResult<(StatusCode, Json<ContractTestFixture>)> {
+    fixture
+        .validate()
+        .map_err(|msg| ApiError::bad_request("InvalidFixture", msg))?;
+    let error_code = fixture
+        .error_code
+        .map(i32::try_from)
+        .transpose()
+        .map_err(|_| ApiError::bad_request("InvalidFixture", "error_code is out of range"))?;
+
+    ensure_contract_exists(&state, contract_id).await?;
+
+    let args = serde_json::to_value(&fixture.args)
+        .map_err(|e| ApiError::internal(format!("Failed to encode fixture: {e}")))?;
+    let expected = fixture
+        .expected
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| ApiError::internal(format!("Failed to encode fixture: {e}")))?;
+
+    let row: ContractTestFixture = sqlx::query_as(
         r#"
-        INSERT INTO contract_compatibility
-            (contract_id, sdk_version, wasm_runtime, network, compatible, tested_at, test_duration_ms, error_message)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        ON CONFLICT (contract_id, sdk_version, wasm_runtime, network)
-        DO UPDATE SET
-            compatible = EXCLUDED.compatible,
-            tested_at = EXCLUDED.tested_at,
-            test_duration_ms = EXCLUDED.test_duration_ms,
-            error_message = EXCLUDED.error_message,
-            updated_at = NOW()
+        INSERT INTO contract_test_fixtures
+            (contract_id, name, function_name, args, expected, expect_error, error_code)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (contract_id, name) DO UPDATE SET
+            function_name = EXCLUDED.function_name,
+            args = EXCLUDED.args,
+            expected = EXCLUDED.expected,
+            expect_error = EXCLUDED.expect_error,
+            error_code = EXCLUDED.error_code
+        RETURNING *
         "#,
     )
     .bind(contract_id)
-    .bind(&body.sdk_version)
-    .bind(&body.wasm_runtime)
-    .bind(&body.network)
-    .bind(&status)
-    .bind(now)
-    .bind(duration_ms)
-    .bind(&error_message)
-    .execute(&state.db)
+    .bind(fixture.name.trim())
+    .bind(&fixture.function)
+    .bind(args)
+    .bind(expected)
+    .bind(fixture.expect_error)
+    .bind(error_code)
+    .fetch_one(&state.db)
     .await
     .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
 
-    // Record history if status changed
-    if previous_status.as_ref() != Some(&status) {
-        let change_reason = match (&previous_status, &status) {
-            (None, _) => Some("Initial test".to_string()),
-            (Some(old), new) => Some(format!("Status changed from {} to {}", old, new)),
-        };
-
-        sqlx::query(
-            r#"
-            INSERT INTO contract_compatibility_history
-                (contract_id, sdk_version, wasm_runtime, network, previous_status, new_status, changed_at, change_reason)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            "#,
-        )
-        .bind(contract_id)
-        .bind(&body.sdk_version)
-        .bind(&body.wasm_runtime)
-        .bind(&body.network)
-        .bind(&previous_status)
-        .bind(&status)
-        .bind(now)
-        .bind(&change_reason)
-        .execute(&state.db)
-        .await
-        .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
+    Ok((StatusCode::CREATED, Json(row)))
+}
 
-        // Notify publisher if status degraded
-        if status == CompatibilityStatus::Incompatible || status == CompatibilityStatus::Warning {
-            let message = format!(
-                "Contract compatibility changed to '{}' for SDK {} / Runtime {} / Network {}",
-                status, body.sdk_version, body.wasm_runtime, body.network
-            );
-
-            sqlx::query(
-                r#"
-                INSERT INTO compatibility_notifications (contract_id, sdk_version, message)
-                VALUES ($1, $2, $3)
-                "#,
-            )
+/// DELETE /api/contracts/:id/compatibility-matrix/fixtures/:fixture_id
+pub async fn delete_test_fixture(
+    State(state): State<AppState>,
+    Path((contract_id, fixture_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<StatusCode> {
+    let result =
+        sqlx::query("DELETE FROM contract_test_fixtures WHERE id = $1 AND contract_id = $2")
+            .bind(fixture_id)
             .bind(contract_id)
-            .bind(&body.sdk_version)
-            .bind(&message)
             .execute(&state.db)
             .await
             .map_err(|e| ApiError::internal(format!("DB error: {e}")))?;
-        }
-    }
 
-    let entry = CompatibilityTestEntry {
-        sdk_version: body.sdk_version,
-        wasm_runtime: body.wasm_runtime,
-        network: body.network,
-        status,
-        tested_at: now,
-        test_duration_ms: Some(duration_ms),
-        error_message,
-    };
-
-    Ok(Json(entry))
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(
+            "FixtureNotFound",
+            "Test fixture not found",
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// GET /api/contracts/:id/compatibility-matrix/history
@@ -394,9 +595,7 @@ pub async fn get_compatibility_history(
     let rows: Vec<CompatibilityHistoryRow> = sqlx::query_as(
         r#"
         SELECT id, contract_id, sdk_version, wasm_runtime, network,
-               previous_status AS "previous_status: CompatibilityStatus",
-               new_status AS "new_status: CompatibilityStatus",
-               changed_at, change_reason
+               previous_status, new_status, changed_at, change_reason
         FROM contract_compatibility_history
         WHERE contract_id = $1
         ORDER BY changed_at DESC
@@ -508,9 +707,7 @@ pub async fn get_compatibility_dashboard(
     let recent_changes: Vec<CompatibilityHistoryRow> = sqlx::query_as(
         r#"
         SELECT id, contract_id, sdk_version, wasm_runtime, network,
-               previous_status AS "previous_status: CompatibilityStatus",
-               new_status AS "new_status: CompatibilityStatus",
-               changed_at, change_reason
+               previous_status, new_status, changed_at, change_reason
         FROM contract_compatibility_history
         ORDER BY changed_at DESC
         LIMIT 20
@@ -529,35 +726,3 @@ pub async fn get_compatibility_dashboard(
         recent_changes,
     }))
 }
-
-/// Simulates a compatibility test by attempting synthetic contract invocation.
-/// In production, this would use the Soroban SDK to actually invoke the contract.
-fn simulate_compatibility_test(
-    sdk_version: &str,
-    _wasm_runtime: &str,
-    _network: &str,
-) -> (CompatibilityStatus, Option<String>) {
-    // Parse SDK version to determine compatibility heuristic
-    let parts: Vec<&str> = sdk_version.split('.').collect();
-    let major: u32 = parts.first().and_then(|p| p.parse().ok()).unwrap_or(0);
-
-    if major < 20 {
-        (
-            CompatibilityStatus::Incompatible,
-            Some(format!(
-                "SDK version {} is below minimum supported version 20.0.0",
-                sdk_version
-            )),
-        )
-    } else if major < 21 {
-        (
-            CompatibilityStatus::Warning,
-            Some(format!(
-                "SDK version {} has known deprecations; upgrade recommended",
-                sdk_version
-            )),
-        )
-    } else {
-        (CompatibilityStatus::Compatible, None)
-    }
-}
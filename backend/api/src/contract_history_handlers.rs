@@ -6,7 +6,9 @@
 //   GET  /api/contracts/:id/history              – last 10 log entries (sidebar)
 //   GET  /api/contracts/:id/history/all          – paginated full history
 //   GET  /api/contracts/:id/history/export       – CSV download
-//   GET  /api/contracts/:id/versions/:v1/diff/:v2 – field-level diff
+//   GET  /api/contracts/:id/history/verify       – hash-chain and signature check
+//   GET  /api/contracts/:id/history/bundle       – signed audit bundle
+//   GET  /api/contracts/:id/versions/:version/diff/:other – field-level diff
 //   POST /api/contracts/:id/rollback/:snapshot_id – admin rollback
 
 use axum::{
@@ -19,9 +21,11 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
+    audit_log::{self, AuditLogError},
     error::{ApiError, ApiResult},
     state::AppState,
 };
+use shared::audit_chain::{AuditBundle, ChainReport};
 use shared::{
     AuditActionType, AuditLogPage, ContractAuditLog, ContractSnapshot, FieldChange,
     RollbackRequest, VersionDiff,
@@ -38,10 +42,11 @@ pub async fn get_contract_history(
     verify_contract_exists(&state, contract_id).await?;
 
     let entries: Vec<ContractAuditLog> = sqlx::query_as(
-        "SELECT id, contract_id, action_type, old_value, new_value, changed_by, timestamp, previous_hash, hash, signature
+        "SELECT id, contract_id, action_type, old_value, new_value, changed_by, timestamp,
+                previous_hash, hash, signature, seq, hash_version, signing_key_id
            FROM contract_audit_log
           WHERE contract_id = $1
-          ORDER BY timestamp DESC
+          ORDER BY seq DESC
           LIMIT 10",
     )
     .bind(contract_id)
@@ -94,10 +99,11 @@ pub async fn get_full_history(
             .map_err(|e| db_err("count audit log", e))?;
 
     let items: Vec<ContractAuditLog> = sqlx::query_as(
-        "SELECT id, contract_id, action_type, old_value, new_value, changed_by, timestamp, previous_hash, hash, signature
+        "SELECT id, contract_id, action_type, old_value, new_value, changed_by, timestamp,
+                previous_hash, hash, signature, seq, hash_version, signing_key_id
            FROM contract_audit_log
           WHERE contract_id = $1
-          ORDER BY timestamp DESC
+          ORDER BY seq DESC
           LIMIT $2 OFFSET $3",
     )
     .bind(contract_id)
@@ -133,20 +139,13 @@ pub async fn export_history_csv(
         .await
         .map_err(|_| StatusCode::NOT_FOUND)?;
 
-    let entries: Vec<ContractAuditLog> = sqlx::query_as(
-        "SELECT id, contract_id, action_type, old_value, new_value, changed_by, timestamp, previous_hash, hash, signature
-           FROM contract_audit_log
-          WHERE contract_id = $1
-          ORDER BY timestamp ASC",
-    )
-    .bind(contract_id)
-    .fetch_all(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let entries = audit_log::load_chain(&state.db, contract_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let mut csv =
-        String::from("id,contract_id,action_type,old_value,new_value,changed_by,timestamp\n");
-    let mut csv = String::from("id,contract_id,action_type,old_value,new_value,changed_by,timestamp,previous_hash,hash,signature\n");
+    let mut csv = String::from(
+        "id,contract_id,action_type,old_value,new_value,changed_by,timestamp,previous_hash,hash,signature,seq,hash_version,signing_key_id\n",
+    );
 
     for entry in &entries {
         let old = entry
@@ -165,9 +164,10 @@ pub async fn export_history_csv(
         let ph = entry.previous_hash.as_deref().unwrap_or("");
         let h = entry.hash.as_deref().unwrap_or("");
         let sig = entry.signature.as_deref().unwrap_or("");
+        let key_id = entry.signing_key_id.as_deref().unwrap_or("");
 
         csv.push_str(&format!(
-            "{},{},{},\"{}\",\"{}\",{},{},{},{},{}\n",
+            "{},{},{},\"{}\",\"{}\",{},{},{},{},{},{},{},{}\n",
             entry.id,
             entry.contract_id,
             entry.action_type,
@@ -175,7 +175,12 @@ pub async fn export_history_csv(
             new,
             entry.changed_by,
             entry.timestamp.to_rfc3339(),
-            ph, h, sig
+            ph,
+            h,
+            sig,
+            entry.seq,
+            entry.hash_version,
+            key_id
         ));
     }
 
@@ -197,76 +202,72 @@ pub async fn export_history_csv(
 
 // ─────────────────────────────────────────────────────────────────────────────
 // GET /api/contracts/:id/history/verify
-// Iterates the history to verify the cryptographic hash-chain.
+// Walks the whole hash chain, checking every link and signature, and reports
+// the first broken entry.
 // ─────────────────────────────────────────────────────────────────────────────
+#[derive(Debug, serde::Serialize)]
+pub struct ChainVerificationResponse {
+    pub contract_id: Uuid,
+    #[serde(flatten)]
+    pub report: ChainReport,
+    /// Base64 key new entries are signed with. Pin it out of band before
+    /// verifying exported bundles.
+    pub public_key: Option<String>,
+}
+
 pub async fn verify_contract_history(
     State(state): State<AppState>,
     Path(contract_id): Path<Uuid>,
-) -> ApiResult<Json<serde_json::Value>> {
+) -> ApiResult<Json<ChainVerificationResponse>> {
     verify_contract_exists(&state, contract_id).await?;
 
-    let entries: Vec<ContractAuditLog> = sqlx::query_as(
-        "SELECT id, contract_id, action_type, old_value, new_value, changed_by, timestamp, previous_hash, hash, signature
-           FROM contract_audit_log
-          WHERE contract_id = $1
-          ORDER BY timestamp ASC",
-    )
-    .bind(contract_id)
-    .fetch_all(&state.db)
-    .await
-    .map_err(|e| db_err("fetch entire audit log", e))?;
-
-    use sha2::{Sha256, Digest};
-    let mut expected_prev: Option<String> = None;
-
-    for entry in &entries {
-        if entry.previous_hash != expected_prev {
-            return Ok(Json(serde_json::json!({
-                "valid": false,
-                "error": format!("Hash chain broken at log {}. Expected previous {}, got {:?}", entry.id, expected_prev.unwrap_or_default(), entry.previous_hash)
-            })));
-        }
-
-        let mut hasher = Sha256::new();
-        if let Some(ph) = &entry.previous_hash {
-            hasher.update(ph.as_bytes());
-        }
-        hasher.update(entry.contract_id.as_bytes());
-        hasher.update(entry.action_type.to_string().as_bytes());
-        hasher.update(entry.changed_by.as_bytes());
-        if let Some(nv) = &entry.new_value {
-            hasher.update(nv.to_string().as_bytes());
-        }
-        let computed_hash = hex::encode(hasher.finalize());
-        
-        if Some(computed_hash.clone()) != entry.hash {
-            return Ok(Json(serde_json::json!({
-                "valid": false,
-                "error": format!("Hash mismatch at log {}. Computed {}, got {:?}", entry.id, computed_hash, entry.hash)
-            })));
-        }
+    let report = audit_log::verify(&state.db, contract_id)
+        .await
+        .map_err(|e| db_err("verify audit log chain", e))?;
+    if let Some(first_break) = &report.first_break {
+        tracing::warn!(
+            contract_id = %contract_id,
+            entry_id = %first_break.entry_id,
+            position = first_break.position,
+            reason = %first_break.reason,
+            "audit log chain is broken"
+        );
+    }
 
-        // Dummy signature validation
-        let expected_sig = format!("sig_{}", hex::encode(&computed_hash[0..16]));
-        if Some(expected_sig.clone()) != entry.signature {
-            return Ok(Json(serde_json::json!({
-                "valid": false,
-                "error": format!("Signature mismatch at log {}. Expected {}, got {:?}", entry.id, expected_sig, entry.signature)
-            })));
-        }
+    Ok(Json(ChainVerificationResponse {
+        contract_id,
+        report,
+        public_key: audit_log::signer().map(|signer| signer.public_key()),
+    }))
+}
 
-        expected_prev = Some(computed_hash);
-    }
+// ─────────────────────────────────────────────────────────────────────────────
+// GET /api/contracts/:id/history/bundle
+// Exports the full chain signed as a whole, for offline verification with
+// `soroban-registry audit verify-bundle`.
+// ─────────────────────────────────────────────────────────────────────────────
+pub async fn export_audit_bundle(
+    State(state): State<AppState>,
+    Path(contract_id): Path<Uuid>,
+) -> ApiResult<Json<AuditBundle>> {
+    verify_contract_exists(&state, contract_id).await?;
 
-    Ok(Json(serde_json::json!({
-        "valid": true,
-        "verified_entries_count": entries.len()
-    })))
+    let bundle = audit_log::export_bundle(&state.db, contract_id)
+        .await
+        .map_err(|err| match err {
+            err @ AuditLogError::SigningKeyMissing => ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "AuditSigningUnavailable",
+                err.to_string(),
+            ),
+            AuditLogError::Database(e) => db_err("export audit bundle", e),
+        })?;
 
+    Ok(Json(bundle))
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// GET /api/contracts/:id/versions/:v1/diff/:v2
+// GET /api/contracts/:id/versions/:version/diff/:other
 // Computes a field-level diff between two snapshots.
 // ─────────────────────────────────────────────────────────────────────────────
 pub async fn diff_versions(
@@ -392,18 +393,14 @@ pub async fn rollback_contract(
     .map_err(|e| db_err("apply rollback to contract", e))?;
 
     // 5. Write audit log entry
-    let log_entry: ContractAuditLog = sqlx::query_as(
-        "INSERT INTO contract_audit_log
-               (contract_id, action_type, old_value, new_value, changed_by)
-         VALUES ($1, $2, $3, $4, $5)
-         RETURNING id, contract_id, action_type, old_value, new_value, changed_by, timestamp",
+    let log_id = audit_log::append(
+        &mut tx,
+        contract_id,
+        AuditActionType::Rollback,
+        Some(current_data),
+        Some(snapshot.snapshot_data.clone()),
+        &req.changed_by,
     )
-    .bind(contract_id)
-    .bind(AuditActionType::Rollback)
-    .bind(&current_data)
-    .bind(&snapshot.snapshot_data)
-    .bind(&req.changed_by)
-    .fetch_one(&mut *tx)
     .await
     .map_err(|e| db_err("insert rollback audit log", e))?;
 
@@ -422,7 +419,7 @@ pub async fn rollback_contract(
     .bind(contract_id)
     .bind(next_ver)
     .bind(&snapshot.snapshot_data)
-    .bind(log_entry.id)
+    .bind(log_id)
     .execute(&mut *tx)
     .await
     .map_err(|e| db_err("insert post-rollback snapshot", e))?;
@@ -445,7 +442,7 @@ pub async fn rollback_contract(
         "contract_id": contract_id,
         "rolled_back_to_version": snapshot.version_number,
         "new_version": next_ver,
-        "audit_log_id": log_entry.id,
+        "audit_log_id": log_id,
     })))
 }
 
@@ -463,50 +460,18 @@ pub async fn log_contract_change(
     new_value: Option<serde_json::Value>,
     changed_by: &str,
 ) -> Result<Uuid, sqlx::Error> {
-    use sha2::{Sha256, Digest};
     let mut tx = db.begin().await?;
 
-    // 1. Fetch the latest hash to use as previous_hash
-    let prev_hash: Option<String> = sqlx::query_scalar(
-        "SELECT hash FROM contract_audit_log WHERE contract_id = $1 ORDER BY timestamp DESC LIMIT 1"
-    )
-    .bind(contract_id)
-    .fetch_optional(&mut *tx)
-    .await?;
-
-    // 2. Compute new hash
-    let mut hasher = Sha256::new();
-    if let Some(ph) = &prev_hash {
-        hasher.update(ph.as_bytes());
-    }
-    hasher.update(contract_id.as_bytes());
-    hasher.update(action_type.to_string().as_bytes());
-    hasher.update(changed_by.as_bytes());
-    if let Some(nv) = &new_value {
-        hasher.update(nv.to_string().as_bytes());
-    }
-    let new_hash = hex::encode(hasher.finalize());
-    let dummy_signature = format!("sig_{}", hex::encode(&new_hash[0..16])); // dummy implemented signature per plan
-
-    // Insert audit log row
-    let (log_id,): (Uuid,) = sqlx::query_as(
-        "INSERT INTO contract_audit_log
-               (contract_id, action_type, old_value, new_value, changed_by, previous_hash, hash, signature)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-         RETURNING id",
+    let log_id = audit_log::append(
+        &mut tx,
+        contract_id,
+        action_type,
+        old_value,
+        new_value.clone(),
+        changed_by,
     )
-    .bind(contract_id)
-    .bind(&action_type)
-    .bind(&old_value)
-    .bind(&new_value)
-    .bind(changed_by)
-    .bind(&prev_hash)
-    .bind(&new_hash)
-    .bind(&dummy_signature)
-    .fetch_one(&mut *tx)
     .await?;
 
-
     // If we have a new_value, persist a snapshot
     if let Some(ref snap_data) = new_value {
         let next_ver: i32 = sqlx::query_scalar("SELECT next_contract_version($1)")
@@ -35,7 +35,7 @@ pub fn contract_history_routes() -> Router<AppState> {
         )
         // Version diff: compare any two snapshot versions
         .route(
-            "/api/contracts/:id/versions/:v1/diff/:v2",
+            "/api/contracts/:id/versions/:version/diff/:other",
             get(contract_history_handlers::diff_versions),
         )
         // Admin rollback to a specific snapshot
@@ -48,4 +48,9 @@ pub fn contract_history_routes() -> Router<AppState> {
             "/api/contracts/:id/history/verify",
             get(contract_history_handlers::verify_contract_history),
         )
+        // Signed export of the whole chain for offline verification
+        .route(
+            "/api/contracts/:id/history/bundle",
+            get(contract_history_handlers::export_audit_bundle),
+        )
 }
@@ -1,14 +1,24 @@
 use axum::{
     extract::{Path, State},
+    http::StatusCode,
     Json,
 };
+use serde::Deserialize;
+use shared::fees::{FeeEstimate, FeeSchedule, RentChange, ResourceUsage, StoredEntry};
 use shared::models::{
     BatchCostEstimate, CostEstimate, CostEstimateRequest, CostForecast, CostOptimization,
+    CostSource,
+};
+use stellar_xdr::curr::{
+    ContractDataDurability, ContractEventType, DiagnosticEvent, FeeBumpTransactionInnerTx,
+    LedgerEntry, LedgerEntryData, Limits, ScVal, SorobanTransactionData, TransactionEnvelope,
+    TransactionExt, WriteXdr,
 };
 use uuid::Uuid;
 
 use crate::{
     error::{ApiError, ApiResult},
+    request_xdr,
     state::AppState,
 };
 
@@ -28,11 +38,15 @@ fn safe_checked_add(lhs: i64, rhs: i64, context: &str) -> ApiResult<i64> {
     ))
 }
 
+/// Subtract cost amounts, which are never negative: a result below zero is
+/// reported as underflow rather than returned.
 fn safe_checked_sub(lhs: i64, rhs: i64, context: &str) -> ApiResult<i64> {
-    lhs.checked_sub(rhs).ok_or_else(|| ApiError::unprocessable(
-        "ArithmeticOverflow",
-        format!("Underflow during {}", context),
-    ))
+    lhs.checked_sub(rhs)
+        .filter(|diff| *diff >= 0)
+        .ok_or_else(|| ApiError::unprocessable(
+            "ArithmeticUnderflow",
+            format!("Underflow during {}", context),
+        ))
 }
 
 fn saturating_apply_discount(amount: i64, discount_bps: i64) -> ApiResult<i64> {
@@ -62,51 +76,242 @@ fn ensure_reasonable_cost(value: i64, context: &str) -> ApiResult<i64> {
     Ok(value)
 }
 
-// Stellar network constants (approximate)
 const STROOPS_PER_XLM: i64 = 10_000_000;
-const BASE_GAS_COST: i64 = 100_000; // stroops
-const STORAGE_COST_PER_KB: i64 = 50_000; // stroops
-const BANDWIDTH_COST_PER_KB: i64 = 10_000; // stroops
+/// Roughly 31 days of 5-second ledgers.
+const DEFAULT_TTL_HORIZON_LEDGERS: u32 = 535_680;
+const LEDGERS_PER_DAY: u32 = 17_280;
+/// Encoded size of one decorated ed25519 signature, added to unsigned envelopes.
+const SIGNATURE_SIZE_BYTES: u32 = 72;
+
+fn to_xlm(stroops: i64) -> f64 {
+    stroops as f64 / STROOPS_PER_XLM as f64
+}
 
-pub async fn estimate_cost(
-    State(state): State<AppState>,
-    Path(contract_id): Path<Uuid>,
-    Json(req): Json<CostEstimateRequest>,
-) -> ApiResult<Json<CostEstimate>> {
-    let invocations = req.invocations.unwrap_or(1);
-    let storage_kb = req.storage_growth_kb.unwrap_or(0);
+/// Fee schedule for a request: the caller's override, else `COST_FEE_SCHEDULE`
+/// (JSON), else the built-in mainnet approximation.
+fn fee_schedule(req: &CostEstimateRequest) -> ApiResult<FeeSchedule> {
+    if let Some(schedule) = &req.fee_schedule {
+        return Ok(schedule.clone());
+    }
+    match std::env::var("COST_FEE_SCHEDULE") {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map_err(|e| ApiError::internal(format!("Invalid COST_FEE_SCHEDULE: {}", e))),
+        Err(_) => Ok(FeeSchedule::default()),
+    }
+}
 
-    // Try to get historical data
-    let historical_gas = sqlx::query_scalar::<_, i64>(
-        "SELECT avg_gas_cost FROM cost_estimates WHERE contract_id = $1 AND method_name = $2",
-    )
+/// Resource figures for one call, with the entries it leaves behind.
+#[derive(Debug, Clone)]
+struct Measurement {
+    source: CostSource,
+    resources: ResourceUsage,
+    rent_changes: Vec<RentChange>,
+    live_entries: Vec<StoredEntry>,
+    current_ledger: u32,
+}
+
+async fn measure(
+    state: &AppState,
+    contract_id: Uuid,
+    req: &CostEstimateRequest,
+    schedule: &FeeSchedule,
+) -> ApiResult<Measurement> {
+    if let Some(resources) = req.resources {
+        return Ok(Measurement {
+            source: CostSource::Provided,
+            resources,
+            rent_changes: req.rent_changes.clone(),
+            live_entries: req.live_entries.clone(),
+            current_ledger: req.current_ledger.unwrap_or_default(),
+        });
+    }
+    if let Some(transaction_xdr) = &req.transaction_xdr {
+        return simulate(transaction_xdr, schedule).await;
+    }
+    historical(state, contract_id, &req.method_name)
+        .await?
+        .ok_or_else(|| {
+            ApiError::unprocessable(
+                "NoResourceData",
+                format!(
+                    "No recorded usage for '{}'; submit measured resources or a transaction to simulate",
+                    req.method_name
+                ),
+            )
+        })
+}
+
+const RESOURCE_COLUMNS: [&str; 7] = [
+    "avg_instructions",
+    "avg_read_entries",
+    "avg_write_entries",
+    "avg_read_bytes",
+    "avg_write_bytes",
+    "avg_events_bytes",
+    "avg_tx_size_bytes",
+];
+
+#[derive(Debug, sqlx::FromRow)]
+struct ResourceAverages {
+    avg_instructions: i64,
+    avg_read_entries: i64,
+    avg_write_entries: i64,
+    avg_read_bytes: i64,
+    avg_write_bytes: i64,
+    avg_events_bytes: i64,
+    avg_tx_size_bytes: i64,
+    avg_storage_bytes: i64,
+}
+
+async fn historical(
+    state: &AppState,
+    contract_id: Uuid,
+    method_name: &str,
+) -> ApiResult<Option<Measurement>> {
+    let row: Option<ResourceAverages> = sqlx::query_as(&format!(
+        "SELECT {}, avg_storage_bytes FROM cost_estimates \
+         WHERE contract_id = $1 AND method_name = $2 AND avg_instructions > 0",
+        RESOURCE_COLUMNS.join(", ")
+    ))
     .bind(contract_id)
-    .bind(&req.method_name)
+    .bind(method_name)
     .fetch_optional(&state.db)
     .await
-    .unwrap_or(None);
+    .map_err(|e| ApiError::db_error(e.to_string()))?;
+
+    let clamp = |value: i64| u32::try_from(value.max(0)).unwrap_or(u32::MAX);
+    Ok(row.map(|avg| Measurement {
+        source: CostSource::Historical,
+        resources: ResourceUsage {
+            instructions: clamp(avg.avg_instructions),
+            read_entries: clamp(avg.avg_read_entries),
+            write_entries: clamp(avg.avg_write_entries),
+            read_bytes: clamp(avg.avg_read_bytes),
+            write_bytes: clamp(avg.avg_write_bytes),
+            contract_events_size_bytes: clamp(avg.avg_events_bytes),
+            transaction_size_bytes: clamp(avg.avg_tx_size_bytes),
+        },
+        rent_changes: Vec::new(),
+        live_entries: if avg.avg_storage_bytes > 0 {
+            vec![StoredEntry {
+                is_persistent: true,
+                size_bytes: clamp(avg.avg_storage_bytes),
+            }]
+        } else {
+            Vec::new()
+        },
+        current_ledger: 0,
+    }))
+}
 
-    let gas_cost = safe_checked_mul(historical_gas.unwrap_or(BASE_GAS_COST), invocations, "gas cost")?;
-    let storage_cost = safe_checked_mul(storage_kb, STORAGE_COST_PER_KB, "storage cost")?;
-    let bandwidth_cost = safe_checked_mul(storage_kb / 4, BANDWIDTH_COST_PER_KB, "bandwidth cost")?;
+/// Folds a measured call into the running per-method averages.
+async fn record_sample(
+    state: &AppState,
+    contract_id: Uuid,
+    method_name: &str,
+    measurement: &Measurement,
+    per_call: &FeeEstimate,
+) {
+    let storage_bytes: i64 = measurement
+        .live_entries
+        .iter()
+        .map(|entry| i64::from(entry.size_bytes))
+        .sum();
+    let r = &measurement.resources;
+    let updates: Vec<String> = std::iter::once("avg_gas_cost")
+        .chain(std::iter::once("avg_storage_bytes"))
+        .chain(RESOURCE_COLUMNS)
+        .map(|column| {
+            format!(
+                "{column} = (cost_estimates.{column} * cost_estimates.sample_count + EXCLUDED.{column}) \
+                 / (cost_estimates.sample_count + 1)"
+            )
+        })
+        .collect();
+    let sql = format!(
+        "INSERT INTO cost_estimates (contract_id, method_name, avg_gas_cost, avg_storage_bytes, {}) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+         ON CONFLICT (contract_id, method_name) DO UPDATE SET {}, \
+         sample_count = cost_estimates.sample_count + 1, last_updated = NOW()",
+        RESOURCE_COLUMNS.join(", "),
+        updates.join(", ")
+    );
+
+    let result = sqlx::query(&sql)
+        .bind(contract_id)
+        .bind(method_name)
+        .bind(per_call.total_fee)
+        .bind(storage_bytes)
+        .bind(i64::from(r.instructions))
+        .bind(i64::from(r.read_entries))
+        .bind(i64::from(r.write_entries))
+        .bind(i64::from(r.read_bytes))
+        .bind(i64::from(r.write_bytes))
+        .bind(i64::from(r.contract_events_size_bytes))
+        .bind(i64::from(r.transaction_size_bytes))
+        .execute(&state.db)
+        .await;
+    if let Err(e) = result {
+        tracing::warn!(error = ?e, contract_id = %contract_id, "failed to record cost sample");
+    }
+}
 
-    let total_stroops = safe_checked_add(
-        safe_checked_add(gas_cost, storage_cost, "total cost add 1")?,
-        bandwidth_cost,
-        "total cost add 2",
-    )?;
+fn price(
+    method_name: &str,
+    invocations: u32,
+    measurement: &Measurement,
+    schedule: &FeeSchedule,
+    horizon_ledgers: u32,
+) -> ApiResult<CostEstimate> {
+    let per_call = schedule.estimate(
+        &measurement.resources,
+        &measurement.rent_changes,
+        measurement.current_ledger,
+        &measurement.live_entries,
+        horizon_ledgers,
+    );
+    let total = per_call.repeated(invocations);
+    let total_stroops = safe_checked_add(total.total_fee, total.ttl_extension_fee, "total cost")?;
     let total_stroops = ensure_reasonable_cost(total_stroops, "total cost")?;
-    let total_xlm = total_stroops as f64 / STROOPS_PER_XLM as f64;
-
-    Ok(Json(CostEstimate {
-        method_name: req.method_name,
-        gas_cost,
-        storage_cost,
-        bandwidth_cost,
-        total_stroops,
-        total_xlm,
+
+    Ok(CostEstimate {
+        method_name: method_name.to_string(),
         invocations,
-    }))
+        source: measurement.source,
+        resources: measurement.resources,
+        live_entries: measurement.live_entries.clone(),
+        per_call,
+        total,
+        total_xlm: to_xlm(total_stroops),
+    })
+}
+
+async fn estimate_one(
+    state: &AppState,
+    contract_id: Uuid,
+    req: &CostEstimateRequest,
+) -> ApiResult<CostEstimate> {
+    let schedule = fee_schedule(req)?;
+    let measurement = measure(state, contract_id, req, &schedule).await?;
+    let estimate = price(
+        &req.method_name,
+        req.invocations.unwrap_or(1),
+        &measurement,
+        &schedule,
+        req.ttl_horizon_ledgers.unwrap_or(DEFAULT_TTL_HORIZON_LEDGERS),
+    )?;
+    if measurement.source != CostSource::Historical {
+        record_sample(state, contract_id, &req.method_name, &measurement, &estimate.per_call).await;
+    }
+    Ok(estimate)
+}
+
+pub async fn estimate_cost(
+    State(state): State<AppState>,
+    Path(contract_id): Path<Uuid>,
+    Json(req): Json<CostEstimateRequest>,
+) -> ApiResult<Json<CostEstimate>> {
+    Ok(Json(estimate_one(&state, contract_id, &req).await?))
 }
 
 pub async fn batch_estimate(
@@ -114,50 +319,27 @@ pub async fn batch_estimate(
     Path(contract_id): Path<Uuid>,
     Json(requests): Json<Vec<CostEstimateRequest>>,
 ) -> ApiResult<Json<BatchCostEstimate>> {
-    let mut estimates = Vec::new();
+    let mut estimates = Vec::with_capacity(requests.len());
+    let mut total = FeeEstimate::default();
     let mut total_stroops = 0i64;
 
-    for req in requests {
-        let invocations = req.invocations.unwrap_or(1);
-        let storage_kb = req.storage_growth_kb.unwrap_or(0);
-
-        let historical_gas = sqlx::query_scalar::<_, i64>(
-            "SELECT avg_gas_cost FROM cost_estimates WHERE contract_id = $1 AND method_name = $2",
-        )
-        .bind(contract_id)
-        .bind(&req.method_name)
-        .fetch_optional(&state.db)
-        .await
-        .unwrap_or(None);
-
-        let gas_cost = safe_checked_mul(historical_gas.unwrap_or(BASE_GAS_COST), invocations, "gas cost")?;
-        let storage_cost = safe_checked_mul(storage_kb, STORAGE_COST_PER_KB, "storage cost")?;
-        let bandwidth_cost = safe_checked_mul(storage_kb / 4, BANDWIDTH_COST_PER_KB, "bandwidth cost")?;
-
-        let estimate_total = safe_checked_add(
-            safe_checked_add(gas_cost, storage_cost, "total cost add 1")?,
-            bandwidth_cost,
-            "total cost add 2",
+    for req in &requests {
+        let estimate = estimate_one(&state, contract_id, req).await?;
+        total = total.saturating_add(&estimate.total);
+        total_stroops = safe_checked_add(total_stroops, estimate.total.total_fee, "batch total add")?;
+        total_stroops = safe_checked_add(
+            total_stroops,
+            estimate.total.ttl_extension_fee,
+            "batch total add",
         )?;
-        total_stroops = safe_checked_add(total_stroops, estimate_total, "batch total add")?;
-        let estimate_total = ensure_reasonable_cost(estimate_total, "estimate total")?;
         total_stroops = ensure_reasonable_cost(total_stroops, "batch total")?;
-
-        estimates.push(CostEstimate {
-            method_name: req.method_name,
-            gas_cost,
-            storage_cost,
-            bandwidth_cost,
-            total_stroops: estimate_total,
-            total_xlm: estimate_total as f64 / STROOPS_PER_XLM as f64,
-            invocations,
-        });
+        estimates.push(estimate);
     }
 
     Ok(Json(BatchCostEstimate {
         estimates,
-        total_stroops,
-        total_xlm: total_stroops as f64 / STROOPS_PER_XLM as f64,
+        total,
+        total_xlm: to_xlm(total_stroops),
     }))
 }
 
@@ -167,25 +349,51 @@ pub async fn optimize_costs(
     Json(estimate): Json<CostEstimate>,
 ) -> ApiResult<Json<CostOptimization>> {
     let mut suggestions = Vec::new();
-    let current_cost = estimate.total_stroops;
+    if estimate.total.total_fee < 0 || estimate.total.ttl_extension_fee < 0 {
+        return Err(ApiError::unprocessable(
+            "InvalidAmount",
+            "Cost values must be non-negative",
+        ));
+    }
+    let current_cost = safe_checked_add(
+        estimate.total.total_fee,
+        estimate.total.ttl_extension_fee,
+        "current cost",
+    )?;
     let mut optimized_cost = current_cost;
-
-    // Suggest batching if multiple invocations
-    if estimate.invocations > 1 {
+    let fee = &estimate.per_call.resource_fee;
+    let per_call_total = estimate.per_call.total_fee.max(1);
+    let share = |part: i64| part * 100 / per_call_total;
+
+    // Inclusion and bandwidth are paid per transaction, so batching amortises them.
+    if estimate.invocations > 1
+        && share(estimate.per_call.inclusion_fee + fee.bandwidth + fee.historical) >= 20
+    {
         suggestions.push("Batch multiple operations into single transaction".to_string());
-        optimized_cost = saturating_apply_discount(optimized_cost, 1500)?; // 15% savings
+        optimized_cost = saturating_apply_discount(optimized_cost, 1500)?;
+    }
+
+    if share(fee.write_bytes + fee.write_entries + estimate.per_call.rent_fee) >= 40 {
+        suggestions
+            .push("Optimize data structures to reduce storage footprint".to_string());
+        optimized_cost = saturating_apply_discount(optimized_cost, 1000)?;
     }
 
-    // Suggest storage optimization
-    if estimate.storage_cost > estimate.gas_cost {
-        suggestions.push("Optimize data structures to reduce storage footprint".to_string());
-        optimized_cost = saturating_apply_discount(optimized_cost, 1000)?; // 10% savings
+    if estimate.total.ttl_extension_fee > estimate.total.total_fee {
+        suggestions.push(
+            "Move short-lived data to temporary storage to cut TTL extension rent".to_string(),
+        );
+        optimized_cost = saturating_apply_discount(optimized_cost, 1000)?;
     }
 
-    // Suggest caching
-    if estimate.gas_cost > 500_000 {
+    if share(fee.compute) >= 40 {
         suggestions.push("Implement caching to reduce redundant computations".to_string());
-        optimized_cost = saturating_apply_discount(optimized_cost, 800)?; // 8% savings
+        optimized_cost = saturating_apply_discount(optimized_cost, 800)?;
+    }
+
+    if share(fee.events) >= 20 {
+        suggestions.push("Emit fewer or smaller contract events".to_string());
+        optimized_cost = saturating_apply_discount(optimized_cost, 500)?;
     }
 
     let savings_raw = safe_checked_sub(current_cost, optimized_cost, "savings computation")?;
@@ -209,40 +417,255 @@ pub async fn forecast_costs(
     Json(req): Json<CostEstimateRequest>,
 ) -> ApiResult<Json<CostForecast>> {
     let daily_invocations = req.invocations.unwrap_or(100);
-    let storage_kb = req.storage_growth_kb.unwrap_or(1);
-
-    let historical_gas = sqlx::query_scalar::<_, i64>(
-        "SELECT avg_gas_cost FROM cost_estimates WHERE contract_id = $1 AND method_name = $2",
-    )
-    .bind(contract_id)
-    .bind(&req.method_name)
-    .fetch_optional(&state.db)
-    .await
-    .unwrap_or(None);
-
-    let gas_per_call = historical_gas.unwrap_or(BASE_GAS_COST);
-    let storage_cost = safe_checked_mul(storage_kb, STORAGE_COST_PER_KB, "storage cost")?;
-    let bandwidth_cost = safe_checked_mul(storage_kb / 4, BANDWIDTH_COST_PER_KB, "bandwidth cost")?;
-    let gas_total = safe_checked_mul(gas_per_call, daily_invocations, "gas cost")?;
-    let daily_cost_stroops = safe_checked_add(
-        safe_checked_add(gas_total, storage_cost, "daily cost add 1")?,
-        bandwidth_cost,
-        "daily cost add 2",
+    let schedule = fee_schedule(&req)?;
+    let measurement = measure(&state, contract_id, &req, &schedule).await?;
+    let estimate = price(
+        &req.method_name,
+        daily_invocations,
+        &measurement,
+        &schedule,
+        LEDGERS_PER_DAY,
     )?;
+
+    let daily_rent = estimate.total.ttl_extension_fee;
+    let daily_cost_stroops = safe_checked_add(estimate.total.total_fee, daily_rent, "daily cost")?;
     let daily_cost_stroops = ensure_reasonable_cost(daily_cost_stroops, "daily cost")?;
-    let daily_cost_xlm = daily_cost_stroops as f64 / STROOPS_PER_XLM as f64;
+    let daily_cost_xlm = to_xlm(daily_cost_stroops);
 
     Ok(Json(CostForecast {
         daily_cost_xlm,
         monthly_cost_xlm: daily_cost_xlm * 30.0,
         yearly_cost_xlm: daily_cost_xlm * 365.0,
-        usage_pattern: format!("{} invocations/day, {} KB storage/day", daily_invocations, storage_kb),
+        daily_rent_xlm: to_xlm(daily_rent),
+        usage_pattern: format!(
+            "{} invocations/day, {} bytes of state kept alive",
+            daily_invocations,
+            measurement
+                .live_entries
+                .iter()
+                .map(|entry| u64::from(entry.size_bytes))
+                .sum::<u64>()
+        ),
     }))
 }
 
+// ─── Soroban RPC simulation ─────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<SimulateResult>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulateResult {
+    transaction_data: Option<String>,
+    #[serde(default)]
+    events: Vec<String>,
+    #[serde(default)]
+    results: Vec<SimulateHostFunctionResult>,
+    latest_ledger: u32,
+    #[serde(default)]
+    state_changes: Vec<StateChange>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimulateHostFunctionResult {
+    xdr: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateChange {
+    before: Option<String>,
+    after: Option<String>,
+}
+
+fn simulation_failed(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::BAD_GATEWAY, "SimulationFailed", message)
+}
+
+/// Decodes a client-supplied envelope; oversize or over-deep XDR is a bad request.
+fn decode_transaction(transaction_xdr: &str) -> ApiResult<TransactionEnvelope> {
+    request_xdr::from_base64(transaction_xdr)
+        .map_err(|e| ApiError::bad_request("InvalidTransaction", e.to_string()))
+}
+
+/// Simulates `transaction_xdr` with the node at `SOROBAN_RPC_URL`.
+async fn simulate(transaction_xdr: &str, schedule: &FeeSchedule) -> ApiResult<Measurement> {
+    let envelope = decode_transaction(transaction_xdr)?;
+    let rpc_url = std::env::var("SOROBAN_RPC_URL").map_err(|_| {
+        ApiError::unprocessable(
+            "SimulationUnavailable",
+            "SOROBAN_RPC_URL is not configured; submit measured resources instead",
+        )
+    })?;
+
+    let response: RpcResponse = reqwest::Client::new()
+        .post(&rpc_url)
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "simulateTransaction",
+            "params": { "transaction": transaction_xdr },
+        }))
+        .send()
+        .await
+        .map_err(|e| simulation_failed(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| simulation_failed(e.to_string()))?;
+
+    if let Some(error) = response.error {
+        return Err(simulation_failed(error.message));
+    }
+    let result = response
+        .result
+        .ok_or_else(|| simulation_failed("RPC returned neither result nor error"))?;
+    measurement_from_simulation(&envelope, &result, schedule)
+}
+
+fn measurement_from_simulation(
+    envelope: &TransactionEnvelope,
+    result: &SimulateResult,
+    schedule: &FeeSchedule,
+) -> ApiResult<Measurement> {
+    if let Some(error) = &result.error {
+        return Err(ApiError::unprocessable("SimulationFailed", error.clone()));
+    }
+    let decode_err = |e: stellar_xdr::curr::Error| simulation_failed(e.to_string());
+    let data = request_xdr::from_base64::<SorobanTransactionData>(
+        result
+            .transaction_data
+            .as_deref()
+            .ok_or_else(|| simulation_failed("simulation returned no transaction data"))?,
+    )
+    .map_err(decode_err)?;
+
+    let mut events_size = 0u32;
+    for encoded in &result.events {
+        let event = request_xdr::from_base64::<DiagnosticEvent>(encoded).map_err(decode_err)?;
+        if event.in_successful_contract_call && event.event.type_ == ContractEventType::Contract {
+            events_size += event.event.to_xdr(Limits::none()).map_err(decode_err)?.len() as u32;
+        }
+    }
+    for returned in &result.results {
+        events_size += request_xdr::from_base64::<ScVal>(&returned.xdr)
+            .map_err(decode_err)?
+            .to_xdr(Limits::none())
+            .map_err(decode_err)?
+            .len() as u32;
+    }
+
+    let mut transaction_size = envelope.to_xdr(Limits::none()).map_err(decode_err)?.len() as u32;
+    let (unsigned, has_soroban_data) = match envelope {
+        TransactionEnvelope::TxV0(env) => (env.signatures.is_empty(), false),
+        TransactionEnvelope::Tx(env) => (
+            env.signatures.is_empty(),
+            matches!(env.tx.ext, TransactionExt::V1(_)),
+        ),
+        TransactionEnvelope::TxFeeBump(env) => {
+            let FeeBumpTransactionInnerTx::Tx(inner) = &env.tx.inner_tx;
+            (
+                env.signatures.is_empty(),
+                matches!(inner.tx.ext, TransactionExt::V1(_)),
+            )
+        }
+    };
+    if unsigned {
+        transaction_size += SIGNATURE_SIZE_BYTES;
+    }
+    if !has_soroban_data {
+        // The simulated footprint and fee are attached before submission.
+        transaction_size += data.to_xdr(Limits::none()).map_err(decode_err)?.len() as u32;
+    }
+
+    let current_ledger = result.latest_ledger + 1;
+    let (rent_changes, live_entries) =
+        rent_from_state_changes(&result.state_changes, current_ledger, schedule)?;
+    let footprint = &data.resources.footprint;
+
+    Ok(Measurement {
+        source: CostSource::Simulation,
+        resources: ResourceUsage {
+            instructions: data.resources.instructions,
+            read_entries: footprint.read_only.len() as u32,
+            write_entries: footprint.read_write.len() as u32,
+            read_bytes: data.resources.read_bytes,
+            write_bytes: data.resources.write_bytes,
+            contract_events_size_bytes: events_size,
+            transaction_size_bytes: transaction_size,
+        },
+        rent_changes,
+        live_entries,
+        current_ledger,
+    })
+}
+
+/// Derives rent changes from simulated state changes.
+///
+/// RPC reports entry values but not their TTLs, so created entries are given
+/// the network minimum lifetime and growth of existing entries is not topped
+/// up for its prepaid period.
+fn rent_from_state_changes(
+    changes: &[StateChange],
+    current_ledger: u32,
+    schedule: &FeeSchedule,
+) -> ApiResult<(Vec<RentChange>, Vec<StoredEntry>)> {
+    let mut rent_changes = Vec::new();
+    let mut live_entries = Vec::new();
+    for change in changes {
+        let Some(after) = &change.after else {
+            continue;
+        };
+        let entry = request_xdr::from_base64::<LedgerEntry>(after)
+            .map_err(|e| simulation_failed(e.to_string()))?;
+        let is_persistent = match &entry.data {
+            LedgerEntryData::ContractData(data) => {
+                data.durability == ContractDataDurability::Persistent
+            }
+            LedgerEntryData::ContractCode(_) => true,
+            _ => continue,
+        };
+        let size_bytes = entry
+            .to_xdr(Limits::none())
+            .map_err(|e| simulation_failed(e.to_string()))?
+            .len() as u32;
+        live_entries.push(StoredEntry {
+            is_persistent,
+            size_bytes,
+        });
+        if change.before.is_none() {
+            let min_ttl = if is_persistent {
+                schedule.min_persistent_ttl
+            } else {
+                schedule.min_temporary_ttl
+            };
+            rent_changes.push(RentChange {
+                is_persistent,
+                old_size_bytes: 0,
+                new_size_bytes: size_bytes,
+                old_live_until_ledger: 0,
+                new_live_until_ledger: current_ledger + min_ttl.saturating_sub(1),
+            });
+        }
+    }
+    Ok((rent_changes, live_entries))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::request_xdr::test_support::{deep_envelope, large_envelope};
+    use stellar_xdr::curr::{
+        ContractDataEntry, ExtensionPoint, Hash, LedgerEntryExt, ScAddress, ScSymbol,
+    };
 
     #[test]
     fn safe_checked_mul_overflow() {
@@ -254,6 +677,8 @@ mod tests {
     fn safe_checked_sub_underflow() {
         let result = safe_checked_sub(1, 2, "sub");
         assert!(result.is_err());
+        assert!(safe_checked_sub(i64::MIN, 1, "sub").is_err());
+        assert_eq!(safe_checked_sub(2, 2, "sub").unwrap(), 0);
     }
 
     #[test]
@@ -267,4 +692,79 @@ mod tests {
         let result = saturating_apply_discount(1000, 1000).unwrap();
         assert_eq!(result, 900);
     }
+
+    fn measurement() -> Measurement {
+        Measurement {
+            source: CostSource::Provided,
+            resources: ResourceUsage {
+                instructions: 2_000_000,
+                read_entries: 2,
+                write_entries: 1,
+                read_bytes: 3_000,
+                write_bytes: 200,
+                contract_events_size_bytes: 120,
+                transaction_size_bytes: 400,
+            },
+            rent_changes: Vec::new(),
+            live_entries: vec![StoredEntry {
+                is_persistent: true,
+                size_bytes: 200,
+            }],
+            current_ledger: 1_000,
+        }
+    }
+
+    #[test]
+    fn price_scales_calls_but_not_ttl_extension() {
+        let schedule = FeeSchedule::default();
+        let single = price("transfer", 1, &measurement(), &schedule, LEDGERS_PER_DAY).unwrap();
+        let batch = price("transfer", 10, &measurement(), &schedule, LEDGERS_PER_DAY).unwrap();
+        assert_eq!(batch.total.total_fee, 10 * single.per_call.total_fee);
+        assert_eq!(batch.total.ttl_extension_fee, single.total.ttl_extension_fee);
+        assert!(single.per_call.ttl_extension_fee > 0);
+    }
+
+    #[test]
+    fn created_entries_pay_minimum_ttl_rent() {
+        let entry = LedgerEntry {
+            last_modified_ledger_seq: 0,
+            data: LedgerEntryData::ContractData(ContractDataEntry {
+                ext: ExtensionPoint::V0,
+                contract: ScAddress::Contract(Hash([7; 32])),
+                key: ScVal::Symbol(ScSymbol("balance".try_into().unwrap())),
+                durability: ContractDataDurability::Persistent,
+                val: ScVal::U64(5),
+            }),
+            ext: LedgerEntryExt::V0,
+        };
+        let encoded = entry.to_xdr_base64(Limits::none()).unwrap();
+        let changes = vec![
+            StateChange {
+                before: None,
+                after: Some(encoded.clone()),
+            },
+            StateChange {
+                before: Some(encoded.clone()),
+                after: Some(encoded),
+            },
+        ];
+        let schedule = FeeSchedule::default();
+        let (rent, live) = rent_from_state_changes(&changes, 50, &schedule).unwrap();
+        assert_eq!(live.len(), 2);
+        assert_eq!(rent.len(), 1);
+        assert_eq!(rent[0].new_live_until_ledger, 50 + 120_960 - 1);
+        assert!(rent[0].is_persistent);
+    }
+
+    #[test]
+    fn oversize_or_over_deep_transactions_are_bad_requests() {
+        assert!(decode_transaction(&deep_envelope(16)).is_ok());
+
+        let err =
+            decode_transaction(&deep_envelope(request_xdr::MAX_DEPTH as usize + 10)).unwrap_err();
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+
+        let err = decode_transaction(&large_envelope(request_xdr::MAX_LEN)).unwrap_err();
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    }
 }
@@ -1,8 +1,8 @@
 use crate::error::ApiError;
 use anyhow::Result;
 use shared::{
-    ContractDependency, DependencyDeclaration, DependencyTreeNode, GraphEdge, GraphNode,
-    GraphResponse,
+    ContractDependency, ContractVersion, DependencyDeclaration, DependencyTreeNode, GraphEdge,
+    GraphNode, GraphResponse, VersionConstraint,
 };
 use sqlx::PgPool;
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -160,6 +160,24 @@ pub async fn resolve_contract_id(pool: &PgPool, identifier: &str) -> Result<Opti
     Ok(id)
 }
 
+/// Highest published version of a contract that satisfies `constraint`,
+/// the way Cargo and npm pick a dependency version.
+pub async fn resolve_version(
+    pool: &PgPool,
+    contract_id: Uuid,
+    constraint: &VersionConstraint,
+) -> Result<Option<ContractVersion>> {
+    let versions: Vec<ContractVersion> =
+        sqlx::query_as("SELECT * FROM contract_versions WHERE contract_id = $1")
+            .bind(contract_id)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(constraint
+        .best_match(&versions, |v| v.version.as_str())
+        .cloned())
+}
+
 /// Save dependencies for a contract, resolving them if possible
 pub async fn save_dependencies(
     pool: &PgPool,
@@ -0,0 +1,276 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use shared::{
+    ContractDeployment, DeployGreenRequest, DeploymentHealthCheck, DeploymentStatus,
+    DeploymentSwitch, HealthCheckRequest, RollbackDeploymentRequest, SwitchDeploymentRequest,
+};
+use uuid::Uuid;
+
+use crate::blue_green::{self, CheckResult, DeploymentError, Verdict};
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::{db_internal_error, fetch_contract_identity};
+use crate::state::AppState;
+
+const RECENT_CHECKS: i64 = 20;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SwitchHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeploymentStatusResponse {
+    pub contract_id: Uuid,
+    /// Slot currently serving traffic
+    pub active: Option<ContractDeployment>,
+    /// Slot being health-checked before a switch
+    pub candidate: Option<ContractDeployment>,
+    pub deployments: Vec<ContractDeployment>,
+    pub recent_checks: Vec<DeploymentHealthCheck>,
+    pub last_switch: Option<DeploymentSwitch>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthCheckRecorded {
+    pub deployment: ContractDeployment,
+    /// What the controller did in response to the check
+    pub verdict: Verdict,
+}
+
+fn deployment_error(err: DeploymentError) -> ApiError {
+    match err {
+        err @ DeploymentError::NoCandidate => {
+            ApiError::conflict("NoCandidateDeployment", err.to_string())
+        }
+        err @ DeploymentError::NotHealthy { .. } => {
+            ApiError::conflict("CandidateNotHealthy", err.to_string())
+        }
+        err @ DeploymentError::NoPrevious => {
+            ApiError::conflict("NoPreviousDeployment", err.to_string())
+        }
+        err @ DeploymentError::NothingLive => {
+            ApiError::conflict("NoActiveDeployment", err.to_string())
+        }
+        DeploymentError::Database(err) => db_internal_error("deployment switch", err),
+    }
+}
+
+/// POST /api/deployments/green — register a release in the idle slot.
+pub async fn deploy_green(
+    State(state): State<AppState>,
+    Json(req): Json<DeployGreenRequest>,
+) -> ApiResult<(StatusCode, Json<ContractDeployment>)> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &req.contract_id).await?;
+    let wasm_hash = req.wasm_hash.trim().to_ascii_lowercase();
+    blue_green::contract_code_key(&wasm_hash)
+        .map_err(|msg| ApiError::bad_request("InvalidWasmHash", msg))?;
+
+    let required = req.required_health_checks.unwrap_or(3);
+    let max_failed = req.max_failed_health_checks.unwrap_or(2);
+    if required < 1 || max_failed < 1 {
+        return Err(ApiError::bad_request(
+            "InvalidHealthCheckPolicy",
+            "required_health_checks and max_failed_health_checks must be at least 1",
+        ));
+    }
+
+    let deployments = blue_green::deployments(&state.db, contract_uuid)
+        .await
+        .map_err(|err| db_internal_error("fetch deployments", err))?;
+    if deployments
+        .iter()
+        .any(|d| d.status == DeploymentStatus::Active && d.wasm_hash == wasm_hash)
+    {
+        return Err(ApiError::conflict(
+            "AlreadyActive",
+            format!("WASM {} is already live for this contract", wasm_hash),
+        ));
+    }
+
+    let deployment = blue_green::register_candidate(
+        &state.db,
+        contract_uuid,
+        &wasm_hash,
+        required,
+        max_failed,
+        req.auto_switch.unwrap_or(true),
+        req.deployed_by.as_deref(),
+    )
+    .await
+    .map_err(|err| db_internal_error("register deployment", err))?;
+
+    tracing::info!(
+        contract_id = %contract_uuid,
+        environment = %deployment.environment,
+        wasm_hash = %wasm_hash,
+        "candidate deployment registered"
+    );
+    Ok((StatusCode::CREATED, Json(deployment)))
+}
+
+/// GET /api/contracts/:id/deployments/status
+pub async fn get_deployment_status(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<DeploymentStatusResponse>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let deployments = blue_green::deployments(&state.db, contract_uuid)
+        .await
+        .map_err(|err| db_internal_error("fetch deployments", err))?;
+
+    let recent_checks: Vec<DeploymentHealthCheck> = sqlx::query_as(
+        "SELECT h.* FROM deployment_health_checks h
+         JOIN contract_deployments d ON d.id = h.deployment_id
+         WHERE d.contract_id = $1
+         ORDER BY h.checked_at DESC
+         LIMIT $2",
+    )
+    .bind(contract_uuid)
+    .bind(RECENT_CHECKS)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("fetch deployment health checks", err))?;
+
+    let last_switch: Option<DeploymentSwitch> = sqlx::query_as(
+        "SELECT * FROM deployment_switches WHERE contract_id = $1
+         ORDER BY switched_at DESC LIMIT 1",
+    )
+    .bind(contract_uuid)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|err| db_internal_error("fetch last deployment switch", err))?;
+
+    let find = |status| deployments.iter().find(|d| d.status == status).cloned();
+    let active = find(DeploymentStatus::Active);
+    let candidate = find(DeploymentStatus::Testing);
+    Ok(Json(DeploymentStatusResponse {
+        contract_id: contract_uuid,
+        active,
+        candidate,
+        deployments,
+        recent_checks,
+        last_switch,
+    }))
+}
+
+/// GET /api/contracts/:id/deployments/switches
+pub async fn list_deployment_switches(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<SwitchHistoryQuery>,
+) -> ApiResult<Json<Vec<DeploymentSwitch>>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let switches = sqlx::query_as(
+        "SELECT * FROM deployment_switches WHERE contract_id = $1
+         ORDER BY switched_at DESC LIMIT $2",
+    )
+    .bind(contract_uuid)
+    .bind(query.limit.unwrap_or(50).clamp(1, 500))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("fetch deployment switches", err))?;
+    Ok(Json(switches))
+}
+
+/// POST /api/deployments/switch — put the candidate live.
+pub async fn switch_deployment(
+    State(state): State<AppState>,
+    Json(req): Json<SwitchDeploymentRequest>,
+) -> ApiResult<Json<DeploymentSwitch>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &req.contract_id).await?;
+    let actor = req.switched_by.as_deref().unwrap_or("api");
+    blue_green::switch(&state.db, contract_uuid, req.force.unwrap_or(false), actor)
+        .await
+        .map(Json)
+        .map_err(deployment_error)
+}
+
+/// POST /api/deployments/rollback — return traffic to the previous slot.
+pub async fn rollback_deployment(
+    State(state): State<AppState>,
+    Json(req): Json<RollbackDeploymentRequest>,
+) -> ApiResult<Json<DeploymentSwitch>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &req.contract_id).await?;
+    let actor = req.rolled_back_by.as_deref().unwrap_or("api");
+    let reason = req.reason.as_deref().unwrap_or("manual rollback");
+    blue_green::rollback(&state.db, contract_uuid, actor, reason)
+        .await
+        .map(Json)
+        .map_err(deployment_error)
+}
+
+/// POST /api/deployments/health-check — record a check from an external
+/// checker and act on it.
+pub async fn report_health_check(
+    State(state): State<AppState>,
+    Json(req): Json<HealthCheckRequest>,
+) -> ApiResult<Json<HealthCheckRecorded>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &req.contract_id).await?;
+    let deployment: ContractDeployment = sqlx::query_as(
+        "SELECT * FROM contract_deployments WHERE contract_id = $1 AND environment = $2",
+    )
+    .bind(contract_uuid)
+    .bind(req.environment)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|err| db_internal_error("fetch deployment", err))?
+    .ok_or_else(|| {
+        ApiError::not_found(
+            "DeploymentNotFound",
+            format!("No {} deployment for this contract", req.environment),
+        )
+    })?;
+    if matches!(
+        deployment.status,
+        DeploymentStatus::Inactive | DeploymentStatus::Failed
+    ) {
+        return Err(ApiError::conflict(
+            "DeploymentNotWatched",
+            format!(
+                "The {} deployment is not being health-checked",
+                req.environment
+            ),
+        ));
+    }
+
+    let result = CheckResult {
+        passed: req.passed,
+        detail: req.detail.unwrap_or_else(|| {
+            if req.passed {
+                "reported healthy"
+            } else {
+                "reported unhealthy"
+            }
+            .to_string()
+        }),
+        latency_ms: None,
+    };
+    let updated = blue_green::record_check(&state.db, &deployment, &result, "reported")
+        .await
+        .map_err(|err| db_internal_error("record health check", err))?;
+    let verdict = blue_green::reconcile(&state.db, &updated)
+        .await
+        .map_err(deployment_error)?;
+
+    let deployment = if verdict == Verdict::Wait {
+        updated
+    } else {
+        fetch_deployment(&state, updated.id).await?
+    };
+    Ok(Json(HealthCheckRecorded {
+        deployment,
+        verdict,
+    }))
+}
+
+async fn fetch_deployment(state: &AppState, id: Uuid) -> ApiResult<ContractDeployment> {
+    sqlx::query_as("SELECT * FROM contract_deployments WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|err| db_internal_error("fetch deployment", err))
+}
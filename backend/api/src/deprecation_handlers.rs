@@ -8,6 +8,7 @@ use uuid::Uuid;
 
 use crate::error::{ApiError, ApiResult};
 use crate::state::AppState;
+use crate::webhooks::{self, WebhookEvent};
 
 pub async fn get_deprecation_info(
     State(state): State<AppState>,
@@ -130,10 +131,24 @@ pub async fn deprecate_contract(
 
     notify_dependents(&state, contract_uuid, &contract_id, req.retirement_at).await?;
 
+    webhooks::emit(
+        &state.db,
+        WebhookEvent::ContractDeprecated,
+        contract_uuid,
+        serde_json::json!({
+            "contract_id": contract_id,
+            "retirement_at": req.retirement_at,
+            "replacement_contract_id": replacement_uuid,
+            "migration_guide_url": req.migration_guide_url,
+            "notes": req.notes,
+        }),
+    )
+    .await;
+
     get_deprecation_info(State(state), Path(contract_id)).await
 }
 
-async fn notify_dependents(
+pub(crate) async fn notify_dependents(
     state: &AppState,
     deprecated_id: Uuid,
     contract_id: &str,
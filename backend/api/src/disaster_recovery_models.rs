@@ -155,10 +155,16 @@ pub struct UserNotificationPreference {
     pub user_id: Uuid,
     pub contract_id: Option<Uuid>, // If null, applies to all contracts
     pub notification_types: Vec<String>, // ['recovery_started', 'recovery_completed', 'incident_detected']
-    pub channels: Vec<String>,           // ['email', 'sms', 'push']
+    pub channels: Vec<String>,           // ['email', 'webhook', 'slack', 'matrix']
     pub enabled: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub email_address: Option<String>,
+    pub webhook_url: Option<String>,
+    #[serde(skip_serializing)]
+    pub webhook_secret: Option<String>,
+    pub slack_webhook_url: Option<String>,
+    pub matrix_webhook_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -168,6 +174,12 @@ pub struct CreateUserNotificationPreferenceRequest {
     pub notification_types: Vec<String>,
     pub channels: Vec<String>,
     pub enabled: bool,
+    pub email_address: Option<String>,
+    pub webhook_url: Option<String>,
+    /// HMAC-SHA256 key used to sign generic webhook deliveries
+    pub webhook_secret: Option<String>,
+    pub slack_webhook_url: Option<String>,
+    pub matrix_webhook_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,3 +190,68 @@ pub struct SendNotificationRequest {
     pub recipients: Vec<String>,  // User IDs or addresses
     pub priority: Option<String>, // 'low', 'normal', 'high', 'critical'
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendNotificationResponse {
+    pub notification_log_id: Uuid,
+    pub queued: Vec<Uuid>,
+    /// Recipients that produced no delivery, with the reason
+    pub skipped: Vec<SkippedRecipient>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedRecipient {
+    pub recipient: String,
+    pub reason: String,
+}
+
+/// One queued delivery of a rendered notification to a single destination.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationOutboxEntry {
+    pub id: Uuid,
+    pub notification_log_id: Option<Uuid>,
+    pub contract_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub notification_type: String,
+    pub channel: String, // 'email', 'webhook', 'slack', 'matrix'
+    pub recipient: String,
+    #[serde(skip_serializing)]
+    pub signing_secret: Option<String>,
+    pub subject: String,
+    pub body: String,
+    pub payload: serde_json::Value,
+    pub status: String, // 'pending', 'delivered', 'dead_letter'
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationDeliveryAttempt {
+    pub id: Uuid,
+    pub outbox_id: Uuid,
+    pub attempt: i32,
+    pub outcome: String, // 'delivered', 'retry', 'rejected'
+    pub response_code: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+    pub attempted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationOutboxDetail {
+    #[serde(flatten)]
+    pub entry: NotificationOutboxEntry,
+    pub delivery_attempts: Vec<NotificationDeliveryAttempt>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationOutboxQuery {
+    pub status: Option<String>,
+    pub channel: Option<String>,
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,376 @@
+//! Real-time registry event stream
+//!
+//! Database triggers (migration 052) append activity-feed entries,
+//! verification results, indexer deployments and contract events to
+//! `registry_stream_events` and announce each row with
+//! `pg_notify('registry_stream', id)`. Every API replica runs one listener
+//! that loads announced rows and broadcasts them in-process; SSE and
+//! WebSocket clients subscribe to that broadcast with their own filters.
+//!
+//! The row id is the stream cursor: a client that reconnects with
+//! `Last-Event-ID` (or `?cursor=`) first gets the rows it missed, then live
+//! events.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch};
+use uuid::Uuid;
+
+pub const NOTIFY_CHANNEL: &str = "registry_stream";
+
+const BROADCAST_CAPACITY: usize = 1024;
+const CLIENT_BUFFER: usize = 256;
+const REPLAY_PAGE: i64 = 500;
+const DEFAULT_RETENTION_DAYS: i64 = 7;
+
+pub const KINDS: [&str; 4] = ["activity", "verification", "deployment", "contract_event"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StreamEvent {
+    pub id: i64,
+    /// activity, verification, deployment or contract_event
+    pub kind: String,
+    /// Analytics event type, verification status or contract event topic
+    pub event_type: String,
+    pub contract_id: Option<Uuid>,
+    pub contract_stellar_id: Option<String>,
+    pub publisher_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub network: Option<String>,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Which events a client wants. Empty lists match everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamFilter {
+    /// Registry UUIDs or Stellar contract IDs
+    #[serde(default)]
+    pub contract_ids: Vec<String>,
+    pub publisher_id: Option<Uuid>,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub kinds: Vec<String>,
+}
+
+impl StreamFilter {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.kinds.iter().find(|k| !KINDS.contains(&k.as_str())) {
+            Some(kind) => Err(format!(
+                "Unknown kind '{}'. Valid: {}",
+                kind,
+                KINDS.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn matches(&self, event: &StreamEvent) -> bool {
+        let contract_ok = self.contract_ids.is_empty()
+            || self.contract_ids.iter().any(|id| {
+                event.contract_id.is_some_and(|c| c.to_string() == *id)
+                    || event.contract_stellar_id.as_deref() == Some(id.as_str())
+            });
+        let publisher_ok = self
+            .publisher_id
+            .is_none_or(|p| event.publisher_id == Some(p));
+        let category_ok = self.category.as_deref().is_none_or(|c| {
+            event
+                .category
+                .as_deref()
+                .is_some_and(|ec| ec.eq_ignore_ascii_case(c))
+        });
+        let type_ok = self.event_types.is_empty() || self.event_types.contains(&event.event_type);
+        let kind_ok = self.kinds.is_empty() || self.kinds.contains(&event.kind);
+
+        contract_ok && publisher_ok && category_ok && type_ok && kind_ok
+    }
+}
+
+/// What a subscriber receives.
+#[derive(Debug, Clone)]
+pub enum StreamMessage {
+    Event(Arc<StreamEvent>),
+    /// The client fell behind and `skipped` live events were dropped; it can
+    /// reconnect with the last id it saw to replay them.
+    Lagged {
+        skipped: u64,
+    },
+}
+
+/// In-process fan-out of the rows announced over LISTEN/NOTIFY.
+pub struct EventHub {
+    sender: broadcast::Sender<Arc<StreamEvent>>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(BROADCAST_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: StreamEvent) {
+        // No receivers just means nobody is connected.
+        let _ = self.sender.send(Arc::new(event));
+    }
+}
+
+/// A live subscription. Dropping it stops the feeding task.
+pub struct Subscription {
+    pub messages: mpsc::Receiver<StreamMessage>,
+    filter: watch::Sender<StreamFilter>,
+}
+
+impl Subscription {
+    /// Replace the filter; applies to live events from now on.
+    pub fn set_filter(&self, filter: StreamFilter) {
+        let _ = self.filter.send(filter);
+    }
+}
+
+/// Subscribe to live events, first replaying rows after `cursor` if given.
+pub fn subscribe(
+    pool: PgPool,
+    hub: &EventHub,
+    filter: StreamFilter,
+    cursor: Option<i64>,
+) -> Subscription {
+    // Subscribe before replaying so nothing committed in between is missed.
+    let mut live = hub.sender.subscribe();
+    let (tx, messages) = mpsc::channel(CLIENT_BUFFER);
+    let (filter_tx, mut filter_rx) = watch::channel(filter);
+
+    tokio::spawn(async move {
+        let mut replayed_up_to = 0;
+        if let Some(cursor) = cursor {
+            let filter = filter_rx.borrow().clone();
+            match replay(&pool, cursor, &filter, &tx).await {
+                Ok(Some(last)) => replayed_up_to = last,
+                Ok(None) => return,
+                Err(err) => {
+                    tracing::warn!(error = ?err, cursor, "event stream: replay failed");
+                }
+            }
+        }
+
+        loop {
+            tokio::select! {
+                received = live.recv() => {
+                    let message = match received {
+                        Ok(event) if event.id <= replayed_up_to => continue,
+                        Ok(event) if filter_rx.borrow().matches(&event) => StreamMessage::Event(event),
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            StreamMessage::Lagged { skipped }
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    if tx.send(message).await.is_err() {
+                        break;
+                    }
+                }
+                changed = filter_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+                _ = tx.closed() => break,
+            }
+        }
+    });
+
+    Subscription {
+        messages,
+        filter: filter_tx,
+    }
+}
+
+/// Send every retained row after `cursor` that matches the filter. Returns
+/// the last id read, or `None` if the subscriber went away.
+async fn replay(
+    pool: &PgPool,
+    cursor: i64,
+    filter: &StreamFilter,
+    tx: &mpsc::Sender<StreamMessage>,
+) -> Result<Option<i64>, sqlx::Error> {
+    let mut last = cursor;
+    loop {
+        let page: Vec<StreamEvent> = sqlx::query_as(
+            "SELECT * FROM registry_stream_events WHERE id > $1 ORDER BY id LIMIT $2",
+        )
+        .bind(last)
+        .bind(REPLAY_PAGE)
+        .fetch_all(pool)
+        .await?;
+
+        let done = (page.len() as i64) < REPLAY_PAGE;
+        for event in page {
+            last = event.id;
+            if filter.matches(&event)
+                && tx
+                    .send(StreamMessage::Event(Arc::new(event)))
+                    .await
+                    .is_err()
+            {
+                return Ok(None);
+            }
+        }
+        if done {
+            return Ok(Some(last));
+        }
+    }
+}
+
+/// Spawn the LISTEN loop that feeds `hub`, plus hourly retention cleanup
+/// (`EVENT_STREAM_RETENTION_DAYS`, default 7).
+pub fn spawn_stream_listener(pool: PgPool, hub: Arc<EventHub>) {
+    let listen_pool = pool.clone();
+    tokio::spawn(async move {
+        let mut last_seen: Option<i64> = None;
+        loop {
+            if let Err(err) = listen(&listen_pool, &hub, &mut last_seen).await {
+                tracing::error!(error = ?err, "event stream: listener failed; reconnecting");
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    });
+
+    let retention_days = std::env::var("EVENT_STREAM_RETENTION_DAYS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            if let Err(err) = sqlx::query(
+                "DELETE FROM registry_stream_events WHERE created_at < NOW() - make_interval(days => $1)",
+            )
+            .bind(retention_days as i32)
+            .execute(&pool)
+            .await
+            {
+                tracing::error!(error = ?err, "event stream: retention cleanup failed");
+            }
+        }
+    });
+}
+
+async fn listen(
+    pool: &PgPool,
+    hub: &EventHub,
+    last_seen: &mut Option<i64>,
+) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(NOTIFY_CHANNEL).await?;
+
+    // Rows committed while we were disconnected were never announced to us.
+    if let Some(last) = *last_seen {
+        let missed: Vec<StreamEvent> =
+            sqlx::query_as("SELECT * FROM registry_stream_events WHERE id > $1 ORDER BY id")
+                .bind(last)
+                .fetch_all(pool)
+                .await?;
+        for event in missed {
+            *last_seen = Some(event.id);
+            hub.publish(event);
+        }
+    }
+
+    loop {
+        // `try_recv` returns None when the connection drops, so the caller
+        // reconnects and catches up instead of silently missing rows.
+        let Some(notification) = listener.try_recv().await? else {
+            return Ok(());
+        };
+        let Ok(id) = notification.payload().parse::<i64>() else {
+            continue;
+        };
+        let event: Option<StreamEvent> =
+            sqlx::query_as("SELECT * FROM registry_stream_events WHERE id = $1")
+                .bind(id)
+                .fetch_optional(pool)
+                .await?;
+        if let Some(event) = event {
+            *last_seen = Some(last_seen.map_or(event.id, |l| l.max(event.id)));
+            hub.publish(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: &str, event_type: &str) -> StreamEvent {
+        StreamEvent {
+            id: 1,
+            kind: kind.to_string(),
+            event_type: event_type.to_string(),
+            contract_id: Some(Uuid::nil()),
+            contract_stellar_id: Some("CABC".to_string()),
+            publisher_id: None,
+            category: Some("DeFi".to_string()),
+            network: Some("testnet".to_string()),
+            payload: serde_json::json!({}),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn filter_matches_each_dimension() {
+        let ev = event("activity", "contract_published");
+        assert!(StreamFilter::default().matches(&ev));
+
+        let by_stellar_id = StreamFilter {
+            contract_ids: vec!["CXYZ".into(), "CABC".into()],
+            ..Default::default()
+        };
+        assert!(by_stellar_id.matches(&ev));
+        let by_uuid = StreamFilter {
+            contract_ids: vec![Uuid::nil().to_string()],
+            ..Default::default()
+        };
+        assert!(by_uuid.matches(&ev));
+
+        let category = StreamFilter {
+            category: Some("defi".into()),
+            kinds: vec!["activity".into()],
+            ..Default::default()
+        };
+        assert!(category.matches(&ev));
+
+        let publisher = StreamFilter {
+            publisher_id: Some(Uuid::nil()),
+            ..Default::default()
+        };
+        assert!(!publisher.matches(&ev));
+        let types = StreamFilter {
+            event_types: vec!["verified".into()],
+            ..Default::default()
+        };
+        assert!(!types.matches(&ev));
+        assert!(types.matches(&event("verification", "verified")));
+    }
+
+    #[test]
+    fn rejects_unknown_kinds() {
+        let filter = StreamFilter {
+            kinds: vec!["deployment".into(), "gossip".into()],
+            ..Default::default()
+        };
+        assert!(filter.validate().unwrap_err().contains("gossip"));
+    }
+}
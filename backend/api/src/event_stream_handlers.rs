@@ -0,0 +1,212 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+};
+use futures::{stream::Stream, SinkExt, StreamExt};
+use serde::Deserialize;
+use std::convert::Infallible;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::event_stream::{self, StreamFilter, StreamMessage};
+use crate::state::AppState;
+
+/// Query string shared by the SSE and WebSocket endpoints. List values are
+/// comma-separated, e.g. `?kinds=activity,verification&contract_ids=C...`.
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamQuery {
+    pub contract_ids: Option<String>,
+    pub publisher_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub event_types: Option<String>,
+    pub kinds: Option<String>,
+    /// Resume after this event id (for clients that cannot send
+    /// `Last-Event-ID`, such as browser WebSockets)
+    pub cursor: Option<i64>,
+}
+
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl StreamQuery {
+    fn filter(&self) -> ApiResult<StreamFilter> {
+        let filter = StreamFilter {
+            contract_ids: split_list(self.contract_ids.as_deref()),
+            publisher_id: self.publisher_id,
+            category: self.category.clone(),
+            event_types: split_list(self.event_types.as_deref()),
+            kinds: split_list(self.kinds.as_deref()),
+        };
+        filter
+            .validate()
+            .map_err(|msg| ApiError::bad_request("InvalidStreamFilter", msg))?;
+        Ok(filter)
+    }
+
+    /// `Last-Event-ID` wins over `?cursor=` so EventSource reconnects resume
+    /// from what the browser actually received.
+    fn cursor(&self, headers: &HeaderMap) -> ApiResult<Option<i64>> {
+        match headers.get("last-event-id") {
+            Some(value) => value
+                .to_str()
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .map(Some)
+                .ok_or_else(|| {
+                    ApiError::bad_request("InvalidCursor", "Last-Event-ID must be an event id")
+                }),
+            None => Ok(self.cursor),
+        }
+    }
+}
+
+/// GET /api/stream — Server-Sent Events. Each event carries its stream id,
+/// so EventSource resumes automatically after a disconnect.
+pub async fn stream_sse(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let filter = query.filter()?;
+    let cursor = query.cursor(&headers)?;
+    let subscription = event_stream::subscribe(state.db.clone(), &state.event_hub, filter, cursor);
+
+    let events = futures::stream::unfold(subscription, |mut subscription| async move {
+        let message = subscription.messages.recv().await?;
+        let event = match message {
+            StreamMessage::Event(event) => Event::default()
+                .id(event.id.to_string())
+                .json_data(event.as_ref())
+                .unwrap_or_else(|_| Event::default().comment("unserializable event")),
+            StreamMessage::Lagged { skipped } => {
+                Event::default().event("lagged").data(skipped.to_string())
+            }
+        };
+        Some((Ok(event), subscription))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Messages a WebSocket client may send.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    /// Replace the subscription filter
+    Subscribe {
+        #[serde(flatten)]
+        filter: StreamFilter,
+    },
+}
+
+/// GET /api/stream/ws — the same stream over a WebSocket. Server frames are
+/// `{"type":"event","event":{...}}`, `{"type":"lagged","skipped":n}`,
+/// `{"type":"subscribed","filter":{...}}` and `{"type":"error","message":..}`.
+pub async fn stream_ws(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    let filter = query.filter()?;
+    let cursor = query.cursor(&headers)?;
+    Ok(ws.on_upgrade(move |socket| serve_socket(socket, state, filter, cursor)))
+}
+
+async fn serve_socket(
+    socket: WebSocket,
+    state: AppState,
+    filter: StreamFilter,
+    cursor: Option<i64>,
+) {
+    let mut subscription =
+        event_stream::subscribe(state.db.clone(), &state.event_hub, filter, cursor);
+    let (mut sink, mut incoming) = socket.split();
+
+    loop {
+        let frame = tokio::select! {
+            message = subscription.messages.recv() => match message {
+                Some(StreamMessage::Event(event)) => {
+                    serde_json::json!({ "type": "event", "event": event.as_ref() })
+                }
+                Some(StreamMessage::Lagged { skipped }) => {
+                    serde_json::json!({ "type": "lagged", "skipped": skipped })
+                }
+                None => break,
+            },
+            received = incoming.next() => match received {
+                Some(Ok(Message::Text(text))) => client_message(&subscription, &text),
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                // Pings are answered by the WebSocket layer.
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        if sink.send(Message::Text(frame.to_string())).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn client_message(subscription: &event_stream::Subscription, text: &str) -> serde_json::Value {
+    match serde_json::from_str::<ClientMessage>(text) {
+        Ok(ClientMessage::Subscribe { filter }) => match filter.validate() {
+            Ok(()) => {
+                let reply = serde_json::json!({ "type": "subscribed", "filter": &filter });
+                subscription.set_filter(filter);
+                reply
+            }
+            Err(message) => serde_json::json!({ "type": "error", "message": message }),
+        },
+        Err(e) => serde_json::json!({
+            "type": "error",
+            "message": format!("Unrecognised message: {}", e),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_lists_are_comma_separated() {
+        let query = StreamQuery {
+            contract_ids: Some("CA, CB,,".into()),
+            kinds: Some("activity,deployment".into()),
+            ..Default::default()
+        };
+        let filter = query.filter().unwrap();
+        assert_eq!(filter.contract_ids, vec!["CA", "CB"]);
+        assert_eq!(filter.kinds, vec!["activity", "deployment"]);
+        assert!(filter.event_types.is_empty());
+    }
+
+    #[test]
+    fn last_event_id_overrides_cursor() {
+        let query = StreamQuery {
+            cursor: Some(5),
+            ..Default::default()
+        };
+        let mut headers = HeaderMap::new();
+        assert_eq!(query.cursor(&headers).unwrap(), Some(5));
+        headers.insert("last-event-id", "42".parse().unwrap());
+        assert_eq!(query.cursor(&headers).unwrap(), Some(42));
+        headers.insert("last-event-id", "abc".parse().unwrap());
+        assert!(query.cursor(&headers).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+use axum::{routing::get, Router};
+
+use crate::{event_stream_handlers, state::AppState};
+
+pub fn event_stream_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/stream", get(event_stream_handlers::stream_sse))
+        .route("/api/stream/ws", get(event_stream_handlers::stream_ws))
+}
@@ -0,0 +1,496 @@
+//! Statistics for A/B experiments
+//!
+//! Conversion metrics (0/1 outcomes) are compared with a two-proportion
+//! z-test and Wilson intervals; continuous metrics with Welch's t-test, which
+//! does not assume equal variances. Sample sizes are planned for a minimum
+//! detectable effect at a target power, and interim looks use an
+//! O'Brien–Fleming boundary so checking results early does not inflate the
+//! false-positive rate.
+
+use serde::Serialize;
+
+/// Count, mean and sample variance of one variant's observations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub n: u64,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+impl Summary {
+    pub fn from_values(values: &[f64]) -> Self {
+        let n = values.len() as u64;
+        if n == 0 {
+            return Self::default();
+        }
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        Self { n, mean, variance }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.max(0.0).sqrt()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestMethod {
+    TwoProportionZTest,
+    WelchTTest,
+}
+
+impl TestMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TestMethod::TwoProportionZTest => "two_proportion_z_test",
+            TestMethod::WelchTTest => "welch_t_test",
+        }
+    }
+}
+
+/// Treatment compared against control.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Comparison {
+    pub method: TestMethod,
+    /// treatment − control
+    pub difference: f64,
+    pub difference_ci: (f64, f64),
+    /// z or t statistic
+    pub statistic: f64,
+    /// Two-sided
+    pub p_value: f64,
+    /// Welch–Satterthwaite degrees of freedom (t-test only)
+    pub degrees_of_freedom: Option<f64>,
+}
+
+impl Comparison {
+    /// The standard-normal statistic with the same two-sided p-value, so
+    /// z- and t-tests can be held against one boundary.
+    pub fn z_equivalent(&self) -> f64 {
+        normal_quantile(1.0 - self.p_value / 2.0).min(40.0)
+    }
+}
+
+// ── Distributions ───────────────────────────────────────────────────────────
+
+/// Complementary error function (Numerical Recipes `erfcc`, |ε| < 1.2e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.265_512_23
+            + t * (1.000_023_68
+                + t * (0.374_091_96
+                    + t * (0.096_784_18
+                        + t * (-0.186_288_06
+                            + t * (0.278_868_07
+                                + t * (-1.135_203_98
+                                    + t * (1.488_515_87
+                                        + t * (-0.822_152_23 + t * 0.170_872_77)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+/// Inverse standard-normal CDF (Acklam's rational approximation).
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.024_25;
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    if p < LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -normal_quantile(1.0 - p)
+    }
+}
+
+/// ln Γ(x) (Lanczos, g = 7).
+fn ln_gamma(x: f64) -> f64 {
+    const COEF: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEF[1..]
+        .iter()
+        .enumerate()
+        .fold(COEF[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Continued fraction for the incomplete beta function (Numerical Recipes).
+fn beta_cf(a: f64, b: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+    let (qab, qap, qam) = (a + b, a + 1.0, a - 1.0);
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..=300 {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPS {
+            break;
+        }
+    }
+    h
+}
+
+/// Regularised incomplete beta function I_x(a, b).
+fn inc_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_cf(a, b, x) / a
+    } else {
+        1.0 - front * beta_cf(b, a, 1.0 - x) / b
+    }
+}
+
+/// Two-sided tail probability P(|T| ≥ |t|) for Student's t.
+pub fn student_t_two_sided(t: f64, df: f64) -> f64 {
+    if !t.is_finite() {
+        return 0.0;
+    }
+    inc_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// The t value whose two-sided tail probability is `alpha`.
+pub fn student_t_critical(alpha: f64, df: f64) -> f64 {
+    let (mut lo, mut hi) = (0.0, 1e4);
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if student_t_two_sided(mid, df) > alpha {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────────
+
+/// Two-proportion z-test; `mean` of each summary is its conversion rate.
+pub fn two_proportion_z_test(control: &Summary, treatment: &Summary, alpha: f64) -> Comparison {
+    let (n1, n2) = (control.n as f64, treatment.n as f64);
+    let (p1, p2) = (control.mean, treatment.mean);
+    let difference = p2 - p1;
+
+    let pooled = (p1 * n1 + p2 * n2) / (n1 + n2);
+    let se_pooled = (pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2)).sqrt();
+    let statistic = if se_pooled > 0.0 {
+        difference / se_pooled
+    } else {
+        0.0
+    };
+    let p_value = 2.0 * (1.0 - normal_cdf(statistic.abs()));
+
+    // The interval uses the unpooled standard error.
+    let se = (p1 * (1.0 - p1) / n1 + p2 * (1.0 - p2) / n2).sqrt();
+    let z = normal_quantile(1.0 - alpha / 2.0);
+    Comparison {
+        method: TestMethod::TwoProportionZTest,
+        difference,
+        difference_ci: (difference - z * se, difference + z * se),
+        statistic,
+        p_value: p_value.clamp(0.0, 1.0),
+        degrees_of_freedom: None,
+    }
+}
+
+/// Welch's unequal-variances t-test.
+pub fn welch_t_test(control: &Summary, treatment: &Summary, alpha: f64) -> Comparison {
+    let (n1, n2) = (control.n as f64, treatment.n as f64);
+    let (v1, v2) = (control.variance / n1, treatment.variance / n2);
+    let se = (v1 + v2).sqrt();
+    let difference = treatment.mean - control.mean;
+
+    if se == 0.0 {
+        let p_value = if difference == 0.0 { 1.0 } else { 0.0 };
+        return Comparison {
+            method: TestMethod::WelchTTest,
+            difference,
+            difference_ci: (difference, difference),
+            statistic: if difference == 0.0 {
+                0.0
+            } else {
+                f64::INFINITY.copysign(difference)
+            },
+            p_value,
+            degrees_of_freedom: None,
+        };
+    }
+
+    let df = (v1 + v2).powi(2) / (v1.powi(2) / (n1 - 1.0) + v2.powi(2) / (n2 - 1.0));
+    let statistic = difference / se;
+    let p_value = student_t_two_sided(statistic, df);
+    let t = student_t_critical(alpha, df);
+    Comparison {
+        method: TestMethod::WelchTTest,
+        difference,
+        difference_ci: (difference - t * se, difference + t * se),
+        statistic,
+        p_value: p_value.clamp(0.0, 1.0),
+        degrees_of_freedom: Some(df),
+    }
+}
+
+/// Wilson score interval for a conversion rate.
+pub fn wilson_interval(summary: &Summary, alpha: f64) -> (f64, f64) {
+    if summary.n == 0 {
+        return (0.0, 1.0);
+    }
+    let n = summary.n as f64;
+    let p = summary.mean;
+    let z = normal_quantile(1.0 - alpha / 2.0);
+    let z2 = z * z;
+    let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+    ((centre - half).max(0.0), (centre + half).min(1.0))
+}
+
+/// t interval for a mean.
+pub fn mean_interval(summary: &Summary, alpha: f64) -> (f64, f64) {
+    if summary.n < 2 {
+        return (summary.mean, summary.mean);
+    }
+    let n = summary.n as f64;
+    let half = student_t_critical(alpha, n - 1.0) * summary.std_dev() / n.sqrt();
+    (summary.mean - half, summary.mean + half)
+}
+
+// ── Planning ────────────────────────────────────────────────────────────────
+
+/// Per-variant sample size to detect a change of `mde` from a baseline
+/// conversion rate with a two-sided test.
+pub fn sample_size_proportions(baseline: f64, mde: f64, alpha: f64, power: f64) -> Option<u64> {
+    let p1 = baseline;
+    let p2 = baseline + mde;
+    if mde == 0.0 || !(0.0..=1.0).contains(&p1) || !(0.0..=1.0).contains(&p2) {
+        return None;
+    }
+    let pooled = (p1 + p2) / 2.0;
+    let z_a = normal_quantile(1.0 - alpha / 2.0);
+    let z_b = normal_quantile(power);
+    let numerator = z_a * (2.0 * pooled * (1.0 - pooled)).sqrt()
+        + z_b * (p1 * (1.0 - p1) + p2 * (1.0 - p2)).sqrt();
+    Some((numerator.powi(2) / mde.powi(2)).ceil() as u64)
+}
+
+/// Per-variant sample size to detect a shift of `mde` in a mean with
+/// standard deviation `std_dev`.
+pub fn sample_size_means(std_dev: f64, mde: f64, alpha: f64, power: f64) -> Option<u64> {
+    if mde == 0.0 || std_dev <= 0.0 {
+        return None;
+    }
+    let z = normal_quantile(1.0 - alpha / 2.0) + normal_quantile(power);
+    Some((2.0 * (std_dev * z / mde).powi(2)).ceil() as u64)
+}
+
+/// Power of the current samples to detect a difference of `mde`.
+pub fn achieved_power(control: &Summary, treatment: &Summary, mde: f64, alpha: f64) -> f64 {
+    let se = (control.variance / control.n as f64 + treatment.variance / treatment.n as f64).sqrt();
+    if se == 0.0 || !se.is_finite() {
+        return 0.0;
+    }
+    let z_a = normal_quantile(1.0 - alpha / 2.0);
+    normal_cdf(mde.abs() / se - z_a)
+}
+
+/// O'Brien–Fleming boundary on the z scale at information fraction `t`:
+/// very strict early, relaxing to the fixed-sample critical value at t = 1.
+pub fn efficacy_boundary(alpha: f64, information_fraction: f64) -> f64 {
+    normal_quantile(1.0 - alpha / 2.0) / information_fraction.clamp(1e-6, 1.0).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tol: f64) -> bool {
+        (a - b).abs() < tol
+    }
+
+    #[test]
+    fn distributions_match_reference_values() {
+        assert!(close(normal_cdf(1.96), 0.975, 1e-4));
+        assert!(close(normal_quantile(0.975), 1.959_964, 1e-5));
+        assert!(close(normal_quantile(0.8), 0.841_621, 1e-5));
+        // t(10): P(|T| > 2.228) = 0.05
+        assert!(close(student_t_two_sided(2.228_139, 10.0), 0.05, 1e-5));
+        assert!(close(student_t_critical(0.05, 10.0), 2.228_139, 1e-4));
+        assert!(close(student_t_critical(0.05, 1e6), 1.959_964, 1e-3));
+    }
+
+    #[test]
+    fn two_proportion_z_test_matches_hand_computation() {
+        // 200/1000 vs 250/1000: pooled 0.225, z ≈ 2.677, p ≈ 0.0074
+        let control = Summary {
+            n: 1000,
+            mean: 0.20,
+            variance: 0.16,
+        };
+        let treatment = Summary {
+            n: 1000,
+            mean: 0.25,
+            variance: 0.1875,
+        };
+        let result = two_proportion_z_test(&control, &treatment, 0.05);
+        assert!(close(result.statistic, 2.677, 1e-3), "{}", result.statistic);
+        assert!(close(result.p_value, 0.0074, 2e-4), "{}", result.p_value);
+        assert!(result.difference_ci.0 > 0.0);
+    }
+
+    #[test]
+    fn welch_t_test_handles_unequal_variances() {
+        let control =
+            Summary::from_values(&[19.8, 20.4, 19.6, 17.8, 18.5, 18.9, 18.3, 18.9, 19.5, 22.0]);
+        let treatment =
+            Summary::from_values(&[28.2, 26.6, 20.1, 23.3, 25.2, 22.1, 17.7, 27.6, 20.6, 13.7]);
+        let result = welch_t_test(&control, &treatment, 0.05);
+        // t = 2.074 on 10.21 Welch–Satterthwaite degrees of freedom; the
+        // 5% critical value near 10 df is 2.228, so this is not significant.
+        assert!(close(result.statistic, 2.074, 1e-3), "{}", result.statistic);
+        assert!(close(result.degrees_of_freedom.unwrap(), 10.209, 1e-3));
+        assert!(
+            result.p_value > 0.06 && result.p_value < 0.07,
+            "{}",
+            result.p_value
+        );
+        assert!(result.difference_ci.0 < 0.0 && result.difference_ci.1 > 0.0);
+    }
+
+    #[test]
+    fn sample_size_and_power_agree() {
+        let n = sample_size_means(10.0, 2.0, 0.05, 0.8).unwrap();
+        assert_eq!(n, 393);
+        let s = Summary {
+            n,
+            mean: 0.0,
+            variance: 100.0,
+        };
+        assert!(close(achieved_power(&s, &s, 2.0, 0.05), 0.8, 0.01));
+
+        let n = sample_size_proportions(0.10, 0.02, 0.05, 0.8).unwrap();
+        assert!((3800..3900).contains(&n), "{}", n);
+    }
+
+    #[test]
+    fn boundary_relaxes_to_fixed_sample_value() {
+        assert!(close(efficacy_boundary(0.05, 1.0), 1.96, 1e-2));
+        assert!(efficacy_boundary(0.05, 0.25) > 3.9);
+    }
+
+    #[test]
+    fn wilson_interval_stays_in_unit_range() {
+        let (lo, hi) = wilson_interval(
+            &Summary {
+                n: 10,
+                mean: 0.0,
+                variance: 0.0,
+            },
+            0.05,
+        );
+        assert_eq!(lo, 0.0);
+        assert!(hi > 0.2 && hi < 0.35);
+    }
+}
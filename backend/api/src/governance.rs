@@ -0,0 +1,1125 @@
+//! Governance proposals: electorate, tallying and execution
+//!
+//! A contract's electorate is its `governance_members` (or its publisher
+//! alone), snapshotted into `governance_proposal_voters` when a proposal is
+//! created. Member weight becomes voting power according to the proposal's
+//! model. A member who does not vote passes their power along their
+//! delegation chain to the first delegate who did; chains that loop count
+//! for nobody. Delegations are taken as they stood when voting closed.
+//!
+//! An approved proposal becomes executable once its timelock (voting end
+//! plus `execution_delay_hours`) has passed. Execution applies the proposal's
+//! [`GovernanceAction`], marks it executed and appends a
+//! `governance_executed` entry to `contract_audit_log` in one transaction.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use shared::{
+    AuditActionType, CastVoteRequest, CreateGovernanceProposalRequest, DelegateVoteRequest,
+    GovernanceAction, GovernanceMember, GovernanceModel, GovernanceProposal,
+    GovernanceProposalStatus, GovernanceTally, GovernanceVote, ProposalResults,
+    SetGovernanceMemberRequest, VoteChoice, VoteDelegation,
+};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::audit_log;
+
+pub const EXECUTOR_ACTOR: &str = "governance";
+pub const DEFAULT_QUORUM_PERCENT: i32 = 50;
+pub const DEFAULT_APPROVAL_PERCENT: i32 = 50;
+/// Shortest execution delay a `timelock` proposal may have
+pub const MIN_TIMELOCK_DELAY_HOURS: i32 = 24;
+const MAX_VOTING_HOURS: i32 = 24 * 30;
+const MAX_EXECUTION_DELAY_HOURS: i32 = 24 * 30;
+/// Values of the `maturity_level` enum
+pub const MATURITY_LEVELS: [&str; 5] = ["alpha", "beta", "stable", "mature", "legacy"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum GovernanceError {
+    #[error("proposal not found")]
+    NotFound,
+    #[error("{0}")]
+    InvalidProposal(String),
+    #[error("invalid action: {0}")]
+    InvalidAction(String),
+    #[error("publisher {0} has no voting power on this contract")]
+    NotInElectorate(Uuid),
+    #[error("voting is not open for this proposal")]
+    VotingClosed,
+    #[error("voting is open until {0}")]
+    VotingOpen(DateTime<Utc>),
+    #[error("proposal is timelocked until {0}")]
+    Timelocked(DateTime<Utc>),
+    #[error("proposal is {0:?}")]
+    InvalidState(GovernanceProposalStatus),
+    #[error("proposal was rejected: {0}")]
+    Rejected(&'static str),
+    #[error("delegating to {0} would create a delegation cycle")]
+    DelegationCycle(Uuid),
+    #[error("action failed: {0}")]
+    ActionFailed(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+fn isqrt(n: i64) -> i64 {
+    if n <= 0 {
+        return 0;
+    }
+    let mut x = (n as f64).sqrt() as i64;
+    while x * x > n {
+        x -= 1;
+    }
+    while (x + 1) * (x + 1) <= n {
+        x += 1;
+    }
+    x
+}
+
+/// Voting power a member of the given weight holds under `model`.
+pub fn voting_power(model: GovernanceModel, weight: i64) -> i64 {
+    match model {
+        GovernanceModel::TokenWeighted | GovernanceModel::Timelock => weight.max(0),
+        GovernanceModel::Quadratic => isqrt(weight),
+        GovernanceModel::Multisig => i64::from(weight > 0),
+    }
+}
+
+/// The voter whose ballot `member`'s power follows: the member if they voted,
+/// else the first delegate along their chain who did.
+pub fn resolve_voter(
+    member: Uuid,
+    ballots: &HashMap<Uuid, VoteChoice>,
+    delegations: &HashMap<Uuid, Uuid>,
+) -> Option<Uuid> {
+    let mut current = member;
+    let mut seen = HashSet::new();
+    loop {
+        if ballots.contains_key(&current) {
+            return Some(current);
+        }
+        if !seen.insert(current) {
+            return None;
+        }
+        current = *delegations.get(&current)?;
+    }
+}
+
+/// Whether `delegator → delegate` would close a loop.
+pub fn creates_cycle(delegator: Uuid, delegate: Uuid, delegations: &HashMap<Uuid, Uuid>) -> bool {
+    let mut current = delegate;
+    let mut seen = HashSet::new();
+    while seen.insert(current) {
+        if current == delegator {
+            return true;
+        }
+        match delegations.get(&current) {
+            Some(next) => current = *next,
+            None => return false,
+        }
+    }
+    false
+}
+
+pub fn tally(
+    model: GovernanceModel,
+    electorate: &[(Uuid, i64)],
+    ballots: &HashMap<Uuid, VoteChoice>,
+    delegations: &HashMap<Uuid, Uuid>,
+) -> GovernanceTally {
+    let mut result = GovernanceTally::default();
+    for &(member, weight) in electorate {
+        let power = voting_power(model, weight);
+        result.eligible_power += power;
+        let Some(voter) = resolve_voter(member, ballots, delegations) else {
+            continue;
+        };
+        match ballots[&voter] {
+            VoteChoice::For => result.votes_for += power,
+            VoteChoice::Against => result.votes_against += power,
+            VoteChoice::Abstain => result.votes_abstain += power,
+        }
+        if voter != member {
+            result.delegated_power += power;
+        }
+    }
+    result
+}
+
+/// `(quorum_met, approved)`. Abstentions count towards quorum only.
+pub fn outcome(
+    tally: &GovernanceTally,
+    quorum_percent: i32,
+    approval_percent: i32,
+) -> (bool, bool) {
+    let quorum_met = tally.eligible_power > 0
+        && tally.total_votes() * 100 >= i64::from(quorum_percent) * tally.eligible_power;
+    let decided = tally.votes_for + tally.votes_against;
+    let approved = quorum_met
+        && tally.votes_for > 0
+        && tally.votes_for * 100 >= i64::from(approval_percent) * decided;
+    (quorum_met, approved)
+}
+
+pub fn validate_action(
+    contract_id: Uuid,
+    action: &GovernanceAction,
+    now: DateTime<Utc>,
+) -> Result<(), GovernanceError> {
+    let invalid = |msg: &str| Err(GovernanceError::InvalidAction(msg.to_string()));
+    match action {
+        GovernanceAction::DeprecateContract {
+            retirement_at,
+            replacement_contract_id,
+            migration_guide_url,
+            ..
+        } => {
+            if *retirement_at <= now {
+                return invalid("retirement_at must be in the future");
+            }
+            if replacement_contract_id.is_none() && migration_guide_url.is_none() {
+                return invalid("provide replacement_contract_id or migration_guide_url");
+            }
+            if *replacement_contract_id == Some(contract_id) {
+                return invalid("a contract cannot replace itself");
+            }
+        }
+        GovernanceAction::ChangePublisher { .. } => {}
+        GovernanceAction::UpdateMetadata {
+            name,
+            description,
+            category,
+            tags,
+        } => {
+            if name.is_none() && description.is_none() && category.is_none() && tags.is_none() {
+                return invalid("update_metadata must change at least one field");
+            }
+            if name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+                return invalid("name cannot be empty");
+            }
+        }
+        GovernanceAction::SetMaturity { maturity, .. } => {
+            if !MATURITY_LEVELS.contains(&maturity.as_str()) {
+                return invalid("maturity must be one of alpha, beta, stable, mature, legacy");
+            }
+        }
+        GovernanceAction::ApproveVersion { version } => {
+            if version.trim().is_empty() {
+                return invalid("version cannot be empty");
+            }
+        }
+        GovernanceAction::ChangeMultisigPolicy {
+            threshold,
+            signer_addresses,
+            expiry_seconds,
+            ..
+        } => {
+            if threshold.is_none() && signer_addresses.is_none() && expiry_seconds.is_none() {
+                return invalid("change_multisig_policy must change at least one field");
+            }
+            if threshold.is_some_and(|t| t < 1) {
+                return invalid("threshold must be at least 1");
+            }
+            if let Some(signers) = signer_addresses {
+                let unique: HashSet<&String> = signers.iter().collect();
+                if signers.is_empty() || unique.len() != signers.len() {
+                    return invalid("signer_addresses must be non-empty and distinct");
+                }
+                if threshold.is_some_and(|t| t as usize > signers.len()) {
+                    return invalid("threshold cannot exceed the number of signers");
+                }
+            }
+            if expiry_seconds.is_some_and(|e| e < 60) {
+                return invalid("expiry_seconds must be at least 60");
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_request(
+    contract_id: Uuid,
+    req: &CreateGovernanceProposalRequest,
+    now: DateTime<Utc>,
+) -> Result<(), GovernanceError> {
+    let invalid = |msg: String| Err(GovernanceError::InvalidProposal(msg));
+    if req.title.trim().is_empty() {
+        return invalid("title cannot be empty".into());
+    }
+    if !(1..=MAX_VOTING_HOURS).contains(&req.voting_duration_hours) {
+        return invalid(format!(
+            "voting_duration_hours must be between 1 and {}",
+            MAX_VOTING_HOURS
+        ));
+    }
+    let delay = req.execution_delay_hours.unwrap_or(0);
+    if !(0..=MAX_EXECUTION_DELAY_HOURS).contains(&delay) {
+        return invalid(format!(
+            "execution_delay_hours must be between 0 and {}",
+            MAX_EXECUTION_DELAY_HOURS
+        ));
+    }
+    if req.governance_model == GovernanceModel::Timelock && delay < MIN_TIMELOCK_DELAY_HOURS {
+        return invalid(format!(
+            "timelock proposals need execution_delay_hours of at least {}",
+            MIN_TIMELOCK_DELAY_HOURS
+        ));
+    }
+    for (field, value) in [
+        ("quorum_required", req.quorum_required),
+        ("approval_threshold", req.approval_threshold),
+    ] {
+        if value.is_some_and(|v| !(1..=100).contains(&v)) {
+            return invalid(format!("{} must be a percentage between 1 and 100", field));
+        }
+    }
+    validate_action(contract_id, &req.action, now)
+}
+
+/// Current electorate: members and weights, or the publisher alone.
+pub async fn electorate(
+    conn: &mut PgConnection,
+    contract_id: Uuid,
+) -> Result<Vec<(Uuid, i64)>, sqlx::Error> {
+    let members: Vec<(Uuid, i64)> = sqlx::query_as(
+        "SELECT publisher_id, weight FROM governance_members WHERE contract_id = $1",
+    )
+    .bind(contract_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    if !members.is_empty() {
+        return Ok(members);
+    }
+    let publisher: Option<Uuid> =
+        sqlx::query_scalar("SELECT publisher_id FROM contracts WHERE id = $1")
+            .bind(contract_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    Ok(publisher.map(|p| vec![(p, 1)]).unwrap_or_default())
+}
+
+/// Delegations in force at `at`. A contract-specific delegation overrides a
+/// delegator's registry-wide one.
+pub async fn delegations_at(
+    conn: &mut PgConnection,
+    contract_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<HashMap<Uuid, Uuid>, sqlx::Error> {
+    let rows: Vec<(Uuid, Uuid)> = sqlx::query_as(
+        "SELECT delegator, delegate FROM vote_delegations
+          WHERE (contract_id = $1 OR contract_id IS NULL)
+            AND created_at <= $2
+            AND (revoked_at IS NULL OR revoked_at > $2)
+          ORDER BY contract_id NULLS FIRST, created_at",
+    )
+    .bind(contract_id)
+    .bind(at)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+pub async fn fetch(
+    pool: &PgPool,
+    proposal_id: Uuid,
+) -> Result<Option<GovernanceProposal>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM governance_proposals WHERE id = $1")
+        .bind(proposal_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn list(
+    pool: &PgPool,
+    contract_id: Uuid,
+) -> Result<Vec<GovernanceProposal>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT * FROM governance_proposals WHERE contract_id = $1 ORDER BY created_at DESC",
+    )
+    .bind(contract_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn create(
+    pool: &PgPool,
+    contract_id: Uuid,
+    req: &CreateGovernanceProposalRequest,
+    now: DateTime<Utc>,
+) -> Result<GovernanceProposal, GovernanceError> {
+    validate_request(contract_id, req, now)?;
+
+    let mut tx = pool.begin().await?;
+    let voters = electorate(&mut tx, contract_id).await?;
+    if !voters.iter().any(|(voter, _)| *voter == req.proposer) {
+        return Err(GovernanceError::NotInElectorate(req.proposer));
+    }
+
+    let proposal: GovernanceProposal = sqlx::query_as(
+        "INSERT INTO governance_proposals
+               (contract_id, title, description, governance_model, proposer, status,
+                voting_starts_at, voting_ends_at, execution_delay_hours, quorum_required,
+                approval_threshold, action)
+         VALUES ($1, $2, $3, $4, $5, 'active', $6, $7, $8, $9, $10, $11)
+         RETURNING *",
+    )
+    .bind(contract_id)
+    .bind(req.title.trim())
+    .bind(&req.description)
+    .bind(req.governance_model)
+    .bind(req.proposer)
+    .bind(now)
+    .bind(now + chrono::Duration::hours(i64::from(req.voting_duration_hours)))
+    .bind(req.execution_delay_hours.unwrap_or(0))
+    .bind(req.quorum_required.unwrap_or(DEFAULT_QUORUM_PERCENT))
+    .bind(req.approval_threshold.unwrap_or(DEFAULT_APPROVAL_PERCENT))
+    .bind(sqlx::types::Json(&req.action))
+    .fetch_one(&mut *tx)
+    .await?;
+
+    for (voter, weight) in &voters {
+        sqlx::query(
+            "INSERT INTO governance_proposal_voters (proposal_id, voter, weight) VALUES ($1, $2, $3)",
+        )
+        .bind(proposal.id)
+        .bind(voter)
+        .bind(weight)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(proposal)
+}
+
+async fn proposal_voters(
+    conn: &mut PgConnection,
+    proposal_id: Uuid,
+) -> Result<Vec<(Uuid, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT voter, weight FROM governance_proposal_voters WHERE proposal_id = $1")
+        .bind(proposal_id)
+        .fetch_all(&mut *conn)
+        .await
+}
+
+pub async fn cast_vote(
+    pool: &PgPool,
+    proposal_id: Uuid,
+    req: &CastVoteRequest,
+    now: DateTime<Utc>,
+) -> Result<GovernanceVote, GovernanceError> {
+    let proposal = fetch(pool, proposal_id)
+        .await?
+        .ok_or(GovernanceError::NotFound)?;
+    let open = matches!(
+        proposal.status,
+        GovernanceProposalStatus::Pending | GovernanceProposalStatus::Active
+    );
+    if !open || now < proposal.voting_starts_at || now >= proposal.voting_ends_at {
+        return Err(GovernanceError::VotingClosed);
+    }
+
+    let mut conn = pool.acquire().await?;
+    let voters = proposal_voters(&mut conn, proposal_id).await?;
+    let own_weight = voters
+        .iter()
+        .find(|(voter, _)| *voter == req.voter)
+        .map(|(_, weight)| *weight);
+    if own_weight.is_none() {
+        // Delegates outside the electorate vote with the power routed to them.
+        let delegations = delegations_at(&mut conn, proposal.contract_id, now).await?;
+        if !delegations.values().any(|delegate| *delegate == req.voter) {
+            return Err(GovernanceError::NotInElectorate(req.voter));
+        }
+    }
+    let power = own_weight
+        .map(|w| voting_power(proposal.governance_model, w))
+        .unwrap_or(0);
+
+    let vote = sqlx::query_as(
+        "INSERT INTO governance_votes (proposal_id, voter, vote_choice, voting_power)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (proposal_id, voter) DO UPDATE
+            SET vote_choice = EXCLUDED.vote_choice, voting_power = EXCLUDED.voting_power,
+                created_at = NOW()
+         RETURNING *",
+    )
+    .bind(proposal_id)
+    .bind(req.voter)
+    .bind(req.vote_choice)
+    .bind(power)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(vote)
+}
+
+async fn tally_proposal(
+    conn: &mut PgConnection,
+    proposal: &GovernanceProposal,
+    now: DateTime<Utc>,
+) -> Result<GovernanceTally, sqlx::Error> {
+    let voters = proposal_voters(conn, proposal.id).await?;
+    let ballots: HashMap<Uuid, VoteChoice> = sqlx::query_as::<_, (Uuid, VoteChoice)>(
+        "SELECT voter, vote_choice FROM governance_votes WHERE proposal_id = $1",
+    )
+    .bind(proposal.id)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .collect();
+    let delegations =
+        delegations_at(conn, proposal.contract_id, now.min(proposal.voting_ends_at)).await?;
+    Ok(tally(
+        proposal.governance_model,
+        &voters,
+        &ballots,
+        &delegations,
+    ))
+}
+
+pub async fn results(
+    pool: &PgPool,
+    proposal: GovernanceProposal,
+    now: DateTime<Utc>,
+) -> Result<ProposalResults, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let tally = tally_proposal(&mut conn, &proposal, now).await?;
+    let (quorum_met, approved) = outcome(
+        &tally,
+        proposal.quorum_required,
+        proposal.approval_threshold,
+    );
+    Ok(ProposalResults {
+        total_votes: tally.total_votes(),
+        tally,
+        quorum_met,
+        approved,
+        voting_closed: now >= proposal.voting_ends_at,
+        executable_at: proposal.executable_at(),
+        proposal,
+    })
+}
+
+async fn set_status(
+    conn: &mut PgConnection,
+    proposal_id: Uuid,
+    status: GovernanceProposalStatus,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE governance_proposals SET status = $2 WHERE id = $1")
+        .bind(proposal_id)
+        .bind(status)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Tally a closed proposal and, if it passed and its timelock has expired,
+/// apply its action. Rejection and passing are persisted even though they
+/// are reported as errors.
+pub async fn execute(
+    pool: &PgPool,
+    proposal_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<GovernanceProposal, GovernanceError> {
+    let mut tx = pool.begin().await?;
+    let proposal: GovernanceProposal =
+        sqlx::query_as("SELECT * FROM governance_proposals WHERE id = $1 FOR UPDATE")
+            .bind(proposal_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(GovernanceError::NotFound)?;
+
+    use GovernanceProposalStatus::*;
+    if matches!(proposal.status, Executed | Rejected | Cancelled) {
+        return Err(GovernanceError::InvalidState(proposal.status));
+    }
+    if now < proposal.voting_ends_at {
+        return Err(GovernanceError::VotingOpen(proposal.voting_ends_at));
+    }
+
+    let tally = tally_proposal(&mut tx, &proposal, now).await?;
+    let (quorum_met, approved) = outcome(
+        &tally,
+        proposal.quorum_required,
+        proposal.approval_threshold,
+    );
+    if !approved {
+        set_status(&mut tx, proposal_id, Rejected).await?;
+        tx.commit().await?;
+        return Err(GovernanceError::Rejected(if quorum_met {
+            "approval threshold not met"
+        } else {
+            "quorum not met"
+        }));
+    }
+    if now < proposal.executable_at() {
+        if proposal.status != Passed {
+            set_status(&mut tx, proposal_id, Passed).await?;
+            tx.commit().await?;
+        }
+        return Err(GovernanceError::Timelocked(proposal.executable_at()));
+    }
+
+    let action = proposal.action.as_ref().map(|a| &a.0);
+    let result = match action {
+        Some(action) => match apply(&mut tx, &proposal, action, now).await {
+            Ok(result) => result,
+            Err(err) => {
+                drop(tx);
+                record_failure(pool, proposal_id, &err).await;
+                return Err(err);
+            }
+        },
+        None => Value::Null,
+    };
+
+    let executed: GovernanceProposal = sqlx::query_as(
+        "UPDATE governance_proposals
+            SET status = 'executed', executed_at = $2, execution_result = $3,
+                execution_error = NULL
+          WHERE id = $1
+          RETURNING *",
+    )
+    .bind(proposal_id)
+    .bind(now)
+    .bind(&result)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    audit_log::append(
+        &mut tx,
+        proposal.contract_id,
+        AuditActionType::GovernanceExecuted,
+        None,
+        Some(json!({
+            "proposal_id": proposal_id,
+            "title": proposal.title,
+            "governance_model": proposal.governance_model,
+            "action": action,
+            "result": result,
+            "tally": tally,
+        })),
+        EXECUTOR_ACTOR,
+    )
+    .await?;
+
+    tx.commit().await?;
+    tracing::info!(
+        proposal_id = %proposal_id,
+        contract_id = %proposal.contract_id,
+        "governance proposal executed"
+    );
+    Ok(executed)
+}
+
+async fn record_failure(pool: &PgPool, proposal_id: Uuid, err: &GovernanceError) {
+    let recorded =
+        sqlx::query("UPDATE governance_proposals SET execution_error = $2 WHERE id = $1")
+            .bind(proposal_id)
+            .bind(err.to_string())
+            .execute(pool)
+            .await;
+    if let Err(db_err) = recorded {
+        tracing::warn!(proposal_id = %proposal_id, error = ?db_err, "governance: failed to record execution error");
+    }
+}
+
+fn action_failed(msg: impl Into<String>) -> GovernanceError {
+    GovernanceError::ActionFailed(msg.into())
+}
+
+/// Apply `action` inside the execution transaction; returns what changed.
+async fn apply(
+    conn: &mut PgConnection,
+    proposal: &GovernanceProposal,
+    action: &GovernanceAction,
+    now: DateTime<Utc>,
+) -> Result<Value, GovernanceError> {
+    let contract_id = proposal.contract_id;
+    validate_action(contract_id, action, now)?;
+
+    match action {
+        GovernanceAction::DeprecateContract {
+            retirement_at,
+            replacement_contract_id,
+            migration_guide_url,
+            notes,
+        } => {
+            if let Some(replacement) = replacement_contract_id {
+                let exists: bool =
+                    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM contracts WHERE id = $1)")
+                        .bind(replacement)
+                        .fetch_one(&mut *conn)
+                        .await?;
+                if !exists {
+                    return Err(action_failed("replacement contract does not exist"));
+                }
+            }
+            sqlx::query(
+                "INSERT INTO contract_deprecations
+                       (contract_id, retirement_at, replacement_contract_id, migration_guide_url, notes)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (contract_id) DO UPDATE SET
+                   retirement_at = EXCLUDED.retirement_at,
+                   replacement_contract_id = EXCLUDED.replacement_contract_id,
+                   migration_guide_url = EXCLUDED.migration_guide_url,
+                   notes = EXCLUDED.notes,
+                   updated_at = NOW()",
+            )
+            .bind(contract_id)
+            .bind(retirement_at)
+            .bind(replacement_contract_id)
+            .bind(migration_guide_url)
+            .bind(notes)
+            .execute(&mut *conn)
+            .await?;
+            Ok(json!({
+                "retirement_at": retirement_at,
+                "replacement_contract_id": replacement_contract_id,
+            }))
+        }
+        GovernanceAction::ChangePublisher { publisher_id } => {
+            let exists: bool =
+                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM publishers WHERE id = $1)")
+                    .bind(publisher_id)
+                    .fetch_one(&mut *conn)
+                    .await?;
+            if !exists {
+                return Err(action_failed("new publisher does not exist"));
+            }
+            let previous: Uuid =
+                sqlx::query_scalar("SELECT publisher_id FROM contracts WHERE id = $1 FOR UPDATE")
+                    .bind(contract_id)
+                    .fetch_one(&mut *conn)
+                    .await?;
+            sqlx::query("UPDATE contracts SET publisher_id = $2, updated_at = NOW() WHERE id = $1")
+                .bind(contract_id)
+                .bind(publisher_id)
+                .execute(&mut *conn)
+                .await?;
+            Ok(json!({ "from": previous, "to": publisher_id }))
+        }
+        GovernanceAction::UpdateMetadata {
+            name,
+            description,
+            category,
+            tags,
+        } => {
+            type Metadata = (String, Option<String>, Option<String>, Vec<String>);
+            let before: Metadata = sqlx::query_as(
+                "SELECT name, description, category, tags FROM contracts WHERE id = $1 FOR UPDATE",
+            )
+            .bind(contract_id)
+            .fetch_one(&mut *conn)
+            .await?;
+            let after: Metadata = sqlx::query_as(
+                "UPDATE contracts
+                    SET name = COALESCE($2, name),
+                        description = COALESCE($3, description),
+                        category = COALESCE($4, category),
+                        tags = COALESCE($5, tags),
+                        updated_at = NOW()
+                  WHERE id = $1
+                  RETURNING name, description, category, tags",
+            )
+            .bind(contract_id)
+            .bind(name.as_deref().map(str::trim))
+            .bind(description)
+            .bind(category)
+            .bind(tags)
+            .fetch_one(&mut *conn)
+            .await?;
+            let as_json = |(name, description, category, tags): Metadata| json!({ "name": name, "description": description, "category": category, "tags": tags });
+            Ok(json!({ "before": as_json(before), "after": as_json(after) }))
+        }
+        GovernanceAction::SetMaturity { maturity, reason } => {
+            let previous: String =
+                sqlx::query_scalar("SELECT maturity::text FROM contracts WHERE id = $1 FOR UPDATE")
+                    .bind(contract_id)
+                    .fetch_one(&mut *conn)
+                    .await?;
+            sqlx::query(
+                "UPDATE contracts SET maturity = $2::maturity_level, updated_at = NOW() WHERE id = $1",
+            )
+            .bind(contract_id)
+            .bind(maturity)
+            .execute(&mut *conn)
+            .await?;
+            sqlx::query(
+                "INSERT INTO maturity_changes (contract_id, from_level, to_level, reason, changed_by)
+                 VALUES ($1, $2::maturity_level, $3::maturity_level, $4, $5)",
+            )
+            .bind(contract_id)
+            .bind(&previous)
+            .bind(maturity)
+            .bind(
+                reason
+                    .clone()
+                    .unwrap_or_else(|| format!("governance proposal {}", proposal.id)),
+            )
+            .bind(proposal.proposer)
+            .execute(&mut *conn)
+            .await?;
+            Ok(json!({ "from": previous, "to": maturity }))
+        }
+        GovernanceAction::ApproveVersion { version } => {
+            let approved: Option<(Uuid, String)> = sqlx::query_as(
+                "UPDATE contract_versions
+                    SET approved_at = $3, approved_by_proposal = $4
+                  WHERE contract_id = $1 AND version = $2
+                  RETURNING id, wasm_hash",
+            )
+            .bind(contract_id)
+            .bind(version)
+            .bind(now)
+            .bind(proposal.id)
+            .fetch_optional(&mut *conn)
+            .await?;
+            let (version_id, wasm_hash) = approved
+                .ok_or_else(|| action_failed(format!("version {} does not exist", version)))?;
+            Ok(json!({ "version_id": version_id, "version": version, "wasm_hash": wasm_hash }))
+        }
+        GovernanceAction::ChangeMultisigPolicy {
+            policy_id,
+            threshold,
+            signer_addresses,
+            expiry_seconds,
+        } => {
+            type Policy = (i32, Vec<String>, Option<Vec<i32>>, i32);
+            let before: Option<Policy> = sqlx::query_as(
+                "SELECT threshold, signer_addresses, signer_weights, expiry_seconds
+                   FROM multisig_policies WHERE id = $1 FOR UPDATE",
+            )
+            .bind(policy_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+            let (old_threshold, old_signers, old_weights, old_expiry) =
+                before.ok_or_else(|| action_failed("multisig policy does not exist"))?;
+
+            let shared_elsewhere: bool = sqlx::query_scalar(
+                "SELECT EXISTS(
+                    SELECT 1 FROM deploy_proposals dp, contracts c
+                     WHERE c.id = $2 AND dp.policy_id = $1 AND dp.contract_id <> c.contract_id)",
+            )
+            .bind(policy_id)
+            .bind(contract_id)
+            .fetch_one(&mut *conn)
+            .await?;
+            if shared_elsewhere {
+                return Err(action_failed(
+                    "multisig policy also governs other contracts' deployments",
+                ));
+            }
+
+            let new_threshold = threshold.unwrap_or(old_threshold);
+            // A new signer set starts with equal weights.
+            let (new_signers, new_weights) = match signer_addresses {
+                Some(signers) => (signers.clone(), None),
+                None => (old_signers.clone(), old_weights.clone()),
+            };
+            let new_expiry = expiry_seconds.unwrap_or(old_expiry);
+            let total_weight: i64 = match &new_weights {
+                Some(weights) => weights.iter().map(|w| i64::from(*w)).sum(),
+                None => new_signers.len() as i64,
+            };
+            if i64::from(new_threshold) > total_weight {
+                return Err(action_failed(
+                    "threshold cannot exceed the total signer weight",
+                ));
+            }
+            sqlx::query(
+                "UPDATE multisig_policies
+                    SET threshold = $2, signer_addresses = $3, signer_weights = $4,
+                        expiry_seconds = $5
+                  WHERE id = $1",
+            )
+            .bind(policy_id)
+            .bind(new_threshold)
+            .bind(&new_signers)
+            .bind(&new_weights)
+            .bind(new_expiry)
+            .execute(&mut *conn)
+            .await?;
+            Ok(json!({
+                "policy_id": policy_id,
+                "before": {
+                    "threshold": old_threshold,
+                    "signer_addresses": old_signers,
+                    "signer_weights": old_weights,
+                    "expiry_seconds": old_expiry,
+                },
+                "after": {
+                    "threshold": new_threshold,
+                    "signer_addresses": new_signers,
+                    "signer_weights": new_weights,
+                    "expiry_seconds": new_expiry,
+                },
+            }))
+        }
+    }
+}
+
+/// Delegate `req.delegator`'s votes on this contract, replacing any earlier
+/// delegation for the contract.
+pub async fn delegate(
+    pool: &PgPool,
+    contract_id: Uuid,
+    req: &DelegateVoteRequest,
+    now: DateTime<Utc>,
+) -> Result<VoteDelegation, GovernanceError> {
+    if req.delegator == req.delegate {
+        return Err(GovernanceError::InvalidProposal(
+            "cannot delegate to yourself".into(),
+        ));
+    }
+    let mut tx = pool.begin().await?;
+    // Serialise delegation changes per contract so two cannot form a cycle.
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('governance:' || $1::text))")
+        .bind(contract_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut delegations = delegations_at(&mut tx, contract_id, now).await?;
+    delegations.remove(&req.delegator);
+    if creates_cycle(req.delegator, req.delegate, &delegations) {
+        return Err(GovernanceError::DelegationCycle(req.delegate));
+    }
+
+    sqlx::query(
+        "UPDATE vote_delegations SET active = false, revoked_at = $3
+          WHERE delegator = $1 AND contract_id = $2 AND active",
+    )
+    .bind(req.delegator)
+    .bind(contract_id)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    let delegation = sqlx::query_as(
+        "INSERT INTO vote_delegations (delegator, delegate, contract_id, created_at)
+         VALUES ($1, $2, $3, $4)
+         RETURNING *",
+    )
+    .bind(req.delegator)
+    .bind(req.delegate)
+    .bind(contract_id)
+    .bind(now)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(delegation)
+}
+
+/// Returns false when the delegation does not exist or is already revoked.
+pub async fn revoke_delegation(
+    pool: &PgPool,
+    delegation_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let revoked = sqlx::query(
+        "UPDATE vote_delegations SET active = false, revoked_at = $2 WHERE id = $1 AND active",
+    )
+    .bind(delegation_id)
+    .bind(now)
+    .execute(pool)
+    .await?;
+    Ok(revoked.rows_affected() > 0)
+}
+
+pub async fn members(
+    pool: &PgPool,
+    contract_id: Uuid,
+) -> Result<Vec<GovernanceMember>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT * FROM governance_members WHERE contract_id = $1 ORDER BY weight DESC, added_at",
+    )
+    .bind(contract_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Add, reweight or (with weight 0) remove a member. Running proposals keep
+/// the electorate they were created with.
+pub async fn set_member(
+    pool: &PgPool,
+    contract_id: Uuid,
+    req: &SetGovernanceMemberRequest,
+) -> Result<Option<GovernanceMember>, GovernanceError> {
+    if req.weight < 0 {
+        return Err(GovernanceError::InvalidProposal(
+            "weight cannot be negative".into(),
+        ));
+    }
+    if req.weight == 0 {
+        sqlx::query("DELETE FROM governance_members WHERE contract_id = $1 AND publisher_id = $2")
+            .bind(contract_id)
+            .bind(req.publisher_id)
+            .execute(pool)
+            .await?;
+        return Ok(None);
+    }
+    let member = sqlx::query_as(
+        "INSERT INTO governance_members (contract_id, publisher_id, weight)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (contract_id, publisher_id) DO UPDATE SET weight = EXCLUDED.weight
+         RETURNING *",
+    )
+    .bind(contract_id)
+    .bind(req.publisher_id)
+    .bind(req.weight)
+    .fetch_one(pool)
+    .await?;
+    Ok(Some(member))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(n: u128) -> Vec<Uuid> {
+        (1..=n).map(Uuid::from_u128).collect()
+    }
+
+    #[test]
+    fn weighting_by_model() {
+        assert_eq!(voting_power(GovernanceModel::TokenWeighted, 90), 90);
+        assert_eq!(voting_power(GovernanceModel::Quadratic, 90), 9);
+        assert_eq!(voting_power(GovernanceModel::Quadratic, 100), 10);
+        assert_eq!(voting_power(GovernanceModel::Multisig, 90), 1);
+        assert_eq!(voting_power(GovernanceModel::Timelock, 90), 90);
+    }
+
+    #[test]
+    fn delegation_chains_follow_to_the_first_voter() {
+        let v = ids(4);
+        let electorate: Vec<(Uuid, i64)> = v.iter().map(|id| (*id, 10)).collect();
+        // 1 → 2 → 3, and 3 votes; 4 votes against directly.
+        let delegations = HashMap::from([(v[0], v[1]), (v[1], v[2])]);
+        let ballots = HashMap::from([(v[2], VoteChoice::For), (v[3], VoteChoice::Against)]);
+        let result = tally(
+            GovernanceModel::TokenWeighted,
+            &electorate,
+            &ballots,
+            &delegations,
+        );
+        assert_eq!(result.votes_for, 30);
+        assert_eq!(result.votes_against, 10);
+        assert_eq!(result.delegated_power, 20);
+        assert_eq!(result.eligible_power, 40);
+
+        // A direct vote overrides the member's delegation.
+        let ballots = HashMap::from([(v[1], VoteChoice::Against), (v[2], VoteChoice::For)]);
+        let result = tally(
+            GovernanceModel::TokenWeighted,
+            &electorate,
+            &ballots,
+            &delegations,
+        );
+        assert_eq!(result.votes_against, 20);
+        assert_eq!(result.votes_for, 10);
+    }
+
+    #[test]
+    fn cyclic_delegations_count_for_nobody() {
+        let v = ids(3);
+        let electorate: Vec<(Uuid, i64)> = v.iter().map(|id| (*id, 1)).collect();
+        let delegations = HashMap::from([(v[0], v[1]), (v[1], v[0])]);
+        let ballots = HashMap::from([(v[2], VoteChoice::For)]);
+        let result = tally(
+            GovernanceModel::Multisig,
+            &electorate,
+            &ballots,
+            &delegations,
+        );
+        assert_eq!(result.total_votes(), 1);
+        assert!(creates_cycle(v[2], v[0], &HashMap::from([(v[0], v[2])])));
+        assert!(!creates_cycle(v[2], v[0], &HashMap::from([(v[0], v[1])])));
+    }
+
+    #[test]
+    fn quorum_and_threshold() {
+        let tally = GovernanceTally {
+            votes_for: 30,
+            votes_against: 10,
+            votes_abstain: 20,
+            eligible_power: 100,
+            delegated_power: 0,
+        };
+        // 60% turnout; 75% of decided votes are for.
+        assert_eq!(outcome(&tally, 50, 50), (true, true));
+        assert_eq!(outcome(&tally, 61, 50), (false, false));
+        assert_eq!(outcome(&tally, 50, 80), (true, false));
+        assert_eq!(outcome(&GovernanceTally::default(), 1, 1), (false, false));
+    }
+
+    #[test]
+    fn requests_are_validated() {
+        let now = Utc::now();
+        let contract = Uuid::from_u128(9);
+        let mut req = CreateGovernanceProposalRequest {
+            title: "Mark stable".into(),
+            description: String::new(),
+            governance_model: GovernanceModel::Timelock,
+            proposer: Uuid::from_u128(1),
+            action: GovernanceAction::SetMaturity {
+                maturity: "stable".into(),
+                reason: None,
+            },
+            voting_duration_hours: 72,
+            execution_delay_hours: Some(MIN_TIMELOCK_DELAY_HOURS),
+            quorum_required: None,
+            approval_threshold: Some(66),
+        };
+        assert!(validate_request(contract, &req, now).is_ok());
+
+        req.execution_delay_hours = Some(1);
+        assert!(validate_request(contract, &req, now).is_err());
+        req.execution_delay_hours = Some(MIN_TIMELOCK_DELAY_HOURS);
+
+        req.action = GovernanceAction::SetMaturity {
+            maturity: "production".into(),
+            reason: None,
+        };
+        assert!(matches!(
+            validate_request(contract, &req, now),
+            Err(GovernanceError::InvalidAction(_))
+        ));
+
+        req.action = GovernanceAction::ChangeMultisigPolicy {
+            policy_id: Uuid::nil(),
+            threshold: Some(3),
+            signer_addresses: Some(vec!["GA".into(), "GB".into()]),
+            expiry_seconds: None,
+        };
+        assert!(validate_request(contract, &req, now).is_err());
+
+        req.action = GovernanceAction::DeprecateContract {
+            retirement_at: now + chrono::Duration::days(30),
+            replacement_contract_id: Some(contract),
+            migration_guide_url: None,
+            notes: None,
+        };
+        assert!(validate_request(contract, &req, now).is_err());
+    }
+
+    #[test]
+    fn actions_round_trip_as_tagged_json() {
+        let action: GovernanceAction = serde_json::from_value(json!({
+            "type": "approve_version",
+            "version": "1.2.0",
+        }))
+        .unwrap();
+        assert_eq!(
+            action,
+            GovernanceAction::ApproveVersion {
+                version: "1.2.0".into()
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&action).unwrap()["type"],
+            "approve_version"
+        );
+    }
+}
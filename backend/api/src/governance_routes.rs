@@ -24,6 +24,10 @@ pub fn governance_routes() -> Router<AppState> {
             "/api/governance/proposals/:id/execute",
             post(governance_handlers::execute_proposal),
         )
+        .route(
+            "/api/contracts/:id/governance/members",
+            get(governance_handlers::list_members).put(governance_handlers::set_member),
+        )
         .route(
             "/api/contracts/:id/governance/delegate",
             post(governance_handlers::delegate_vote),
@@ -18,8 +18,8 @@ use shared::{
     CreateInteractionBatchRequest, CreateInteractionRequest, DeploymentStats,
     InteractionTimeSeriesPoint, InteractionTimeSeriesResponse, InteractionsListResponse,
     InteractionsQueryParams, InteractorStats, Network, NetworkConfig, PaginatedResponse,
-    PublishRequest, Publisher, SemVer, TimelineEntry, TopUser, TrendingParams,
-    UpdateContractMetadataRequest, UpdateContractStatusRequest, VerifyRequest,
+    PublishRequest, Publisher, RegistryError, SemVer, TimelineEntry, TopUser, TrendingParams,
+    UpdateContractMetadataRequest, UpdateContractStatusRequest, VerifyRequest, VersionConstraint,
 };
 use std::time::Duration;
 use uuid::Uuid;
@@ -32,12 +32,18 @@ pub struct GetContractQuery {
 
 use crate::{
     analytics,
+    artifact_handlers::{decode_upload, ingest_upload},
+    artifact_store::{self, ArtifactKind},
     breaking_changes::{diff_abi, has_breaking_changes, resolve_abi},
     dependency,
     error::{ApiError, ApiResult},
+    job_worker::VerifyPayload,
+    jobs::{self, JobContext, JobKind, NewJob},
+    search,
     state::AppState,
     type_safety::parser::parse_json_spec,
     type_safety::{generate_openapi, to_json, to_yaml},
+    webhooks::{self, WebhookEvent},
 };
 
 pub(crate) fn db_internal_error(operation: &str, err: sqlx::Error) -> ApiError {
@@ -92,7 +98,7 @@ fn default_audit_limit() -> i64 {
     100
 }
 
-fn extract_ip_address(headers: &HeaderMap) -> String {
+pub(crate) fn extract_ip_address(headers: &HeaderMap) -> String {
     if let Some(forwarded_for) = headers
         .get("x-forwarded-for")
         .and_then(|value| value.to_str().ok())
@@ -331,6 +337,13 @@ pub async fn list_contracts(
         Err(err) => return map_query_rejection(err).into_response(),
     };
 
+    if search::is_search(&params) {
+        return match search::search_contracts(&state.db, &params).await {
+            Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+            Err(err) => err.into_response(),
+        };
+    }
+
     let limit = params.limit.unwrap_or(20).clamp(1, 100);
 
     // Cursor logic
@@ -343,13 +356,7 @@ pub async fn list_contracts(
         (p, (p - 1).max(0) * limit)
     };
 
-    let sort_by = params.sort_by.clone().unwrap_or_else(|| {
-        if params.query.is_some() {
-            shared::SortBy::Relevance
-        } else {
-            shared::SortBy::CreatedAt
-        }
-    });
+    let sort_by = params.sort_by.clone().unwrap_or(shared::SortBy::CreatedAt);
     let sort_order = params.sort_order.clone().unwrap_or(shared::SortOrder::Desc);
 
     let is_timestamp_sort = matches!(sort_by, shared::SortBy::CreatedAt);
@@ -364,15 +371,6 @@ pub async fn list_contracts(
     );
     let mut count_query = String::from("SELECT COUNT(*) FROM contracts WHERE 1=1");
 
-    if let Some(ref q) = params.query {
-        let search_clause = format!(
-            " AND (c.name ILIKE '%{}%' OR c.description ILIKE '%{}%')",
-            q, q
-        );
-        query.push_str(&search_clause);
-        count_query.push_str(&search_clause);
-    }
-
     if let Some(verified) = params.verified_only {
         if verified {
             query.push_str(" AND c.is_verified = true");
@@ -435,18 +433,8 @@ pub async fn list_contracts(
             "COUNT(DISTINCT ci.id)".to_string()
         }
         shared::SortBy::Deployments => "COUNT(DISTINCT cv.id)".to_string(),
-        shared::SortBy::Relevance => {
-            if let Some(ref q) = params.query {
-                format!(
-                    "CASE WHEN c.name ILIKE '{}' THEN 0 
-                          WHEN c.name ILIKE '%{}%' THEN 1 
-                          ELSE 2 END",
-                    q, q
-                )
-            } else {
-                "c.created_at".to_string()
-            }
-        }
+        // Relevance needs a query, which is answered by the search engine.
+        shared::SortBy::Relevance => "c.created_at".to_string(),
     };
 
     let direction = if sort_order == shared::SortOrder::Asc {
@@ -564,6 +552,44 @@ pub async fn get_contract_versions(
     Ok(Json(versions))
 }
 
+#[derive(Debug, serde::Deserialize)]
+pub struct ResolveVersionQuery {
+    /// Version range, e.g. `^1.2`, `>=1.2, <2` or `1.* || 2.0.0-rc.1`
+    pub constraint: String,
+}
+
+/// GET /api/contracts/:id/versions/resolve?constraint=... — the highest
+/// published version satisfying a semver range.
+pub async fn resolve_contract_version(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ResolveVersionQuery>,
+) -> ApiResult<Json<ContractVersion>> {
+    let contract_uuid = Uuid::parse_str(&id).map_err(|_| {
+        ApiError::bad_request(
+            "InvalidContractId",
+            format!("Invalid contract ID format: {}", id),
+        )
+    })?;
+    let constraint = VersionConstraint::parse(&query.constraint).ok_or_else(|| {
+        ApiError::bad_request(
+            "InvalidVersionConstraint",
+            format!("Invalid version constraint: {}", query.constraint),
+        )
+    })?;
+
+    dependency::resolve_version(&state.db, contract_uuid, &constraint)
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to resolve version: {}", e)))?
+        .map(Json)
+        .ok_or_else(|| {
+            ApiError::not_found(
+                "NoMatchingVersion",
+                format!("No published version satisfies '{}'", constraint),
+            )
+        })
+}
+
 /// GET /api/contracts/:id/changelog (and /contracts/:id/changelog) — release history with breaking-change markers.
 pub async fn get_contract_changelog(
     State(state): State<AppState>,
@@ -790,6 +816,30 @@ pub async fn create_contract_version(
         }
     }
 
+    // Uploaded artifacts are stored before the version row is written; a
+    // WASM already stored under this hash (e.g. at publish) is linked as is.
+    let wasm_artifact = match req.wasm_base64.as_deref() {
+        Some(encoded) => {
+            let bytes = decode_upload("wasm_base64", encoded)?;
+            let (artifact, _) =
+                ingest_upload(&state, ArtifactKind::Wasm, &bytes, Some(&req.wasm_hash)).await?;
+            Some(artifact.sha256)
+        }
+        None => artifact_store::find(&state.db, &req.wasm_hash.to_ascii_lowercase())
+            .await
+            .map_err(crate::artifact_handlers::artifact_error)?
+            .filter(|artifact| artifact.kind == ArtifactKind::Wasm.as_str())
+            .map(|artifact| artifact.sha256),
+    };
+    let source_artifact = match req.source_base64.as_deref() {
+        Some(encoded) => {
+            let bytes = decode_upload("source_base64", encoded)?;
+            let (artifact, _) = ingest_upload(&state, ArtifactKind::Source, &bytes, None).await?;
+            Some(artifact.sha256)
+        }
+        None => None,
+    };
+
     let mut tx = state
         .db
         .begin()
@@ -798,8 +848,9 @@ pub async fn create_contract_version(
 
     let version_row: ContractVersion = sqlx::query_as(
         "INSERT INTO contract_versions \
-            (contract_id, version, wasm_hash, source_url, commit_hash, release_notes, signature, publisher_key, signature_algorithm) \
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+            (contract_id, version, wasm_hash, source_url, commit_hash, release_notes, signature, publisher_key, signature_algorithm, \
+             wasm_artifact_sha256, source_artifact_sha256) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
          RETURNING *",
     )
     .bind(contract_uuid)
@@ -811,6 +862,8 @@ pub async fn create_contract_version(
     .bind(&version_signature)
     .bind(&version_publisher_key)
     .bind(&version_algorithm)
+    .bind(&wasm_artifact)
+    .bind(&source_artifact)
     .fetch_one(&mut *tx)
     .await
     .map_err(|err| match err {
@@ -877,10 +930,24 @@ pub async fn create_contract_version(
     )
     .await;
 
+    webhooks::emit(
+        &state.db,
+        WebhookEvent::VersionCreated,
+        contract_uuid,
+        json!({
+            "contract_id": contract_id,
+            "version": version_row.version,
+            "wasm_hash": version_row.wasm_hash,
+            "source_url": version_row.source_url,
+            "commit_hash": version_row.commit_hash,
+        }),
+    )
+    .await;
+
     Ok(Json(version_row))
 }
 
-async fn fetch_contract_identity(state: &AppState, id: &str) -> ApiResult<(Uuid, String)> {
+pub(crate) async fn fetch_contract_identity(state: &AppState, id: &str) -> ApiResult<(Uuid, String)> {
     if let Ok(uuid) = Uuid::parse_str(id) {
         let row = sqlx::query_as::<_, (Uuid, String)>(
             "SELECT id, contract_id FROM contracts WHERE id = $1",
@@ -931,7 +998,15 @@ pub async fn publish_contract(
     .await
     .map_err(|err| db_internal_error("upsert publisher", err))?;
 
-    let wasm_hash = "placeholder_hash".to_string();
+    // Without an uploaded binary the hash is filled in by later versions.
+    let wasm_hash = match req.wasm_base64.as_deref() {
+        Some(encoded) => {
+            let bytes = decode_upload("wasm_base64", encoded)?;
+            let (artifact, _) = ingest_upload(&state, ArtifactKind::Wasm, &bytes, None).await?;
+            artifact.sha256
+        }
+        None => "placeholder_hash".to_string(),
+    };
     let network_key = req.network.to_string();
     let mut config_map = serde_json::Map::new();
     config_map.insert(
@@ -1056,6 +1131,20 @@ pub async fn publish_contract(
     )
     .await;
 
+    webhooks::emit(
+        &state.db,
+        WebhookEvent::ContractPublished,
+        contract.id,
+        json!({
+            "contract_id": contract.contract_id,
+            "name": contract.name,
+            "network": contract.network,
+            "publisher_id": contract.publisher_id,
+            "publisher_address": publisher.stellar_address,
+        }),
+    )
+    .await;
+
     Ok(Json(contract))
 }
 
@@ -1305,10 +1394,6 @@ pub async fn get_contract_analytics(
     }))
 }
 
-pub async fn get_trust_score() -> impl IntoResponse {
-    Json(json!({"score": 0}))
-}
-
 pub async fn get_contract_dependencies(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -1323,7 +1408,27 @@ pub async fn get_contract_dependencies(
             .await
             .map_err(|e| db_internal_error("get_contract_dependencies", e))?;
 
-    Ok(Json(json!({ "dependencies": deps })))
+    // Report the version each registry dependency currently resolves to.
+    let mut resolved = Vec::with_capacity(deps.len());
+    for dep in deps {
+        let resolved_version = match (
+            dep.dependency_contract_id,
+            VersionConstraint::parse(&dep.version_constraint),
+        ) {
+            (Some(dep_id), Some(constraint)) => {
+                dependency::resolve_version(&state.db, dep_id, &constraint)
+                    .await
+                    .map_err(|e| ApiError::internal(format!("Failed to resolve version: {}", e)))?
+                    .map(|v| v.version)
+            }
+            _ => None,
+        };
+        let mut value = json!(dep);
+        value["resolved_version"] = json!(resolved_version);
+        resolved.push(value);
+    }
+
+    Ok(Json(json!({ "dependencies": resolved })))
 }
 
 pub async fn get_contract_dependents(
@@ -1518,11 +1623,14 @@ pub async fn get_trending_contracts(
     })))
 }
 
+/// POST /api/contracts/verify — record the submission and queue the build.
+/// Responds `202 Accepted`; the outcome is reported by `/api/jobs/:id` and on
+/// the verification record.
 pub async fn verify_contract(
     State(state): State<AppState>,
     headers: HeaderMap,
     ValidatedJson(req): ValidatedJson<VerifyRequest>,
-) -> ApiResult<Json<Value>> {
+) -> ApiResult<(StatusCode, Json<Value>)> {
     let contract: Contract = sqlx::query_as(
         "SELECT * FROM contracts WHERE contract_id = $1 ORDER BY created_at DESC LIMIT 1",
     )
@@ -1537,13 +1645,11 @@ pub async fn verify_contract(
         _ => db_internal_error("fetch contract for verification", err),
     })?;
 
-    let previous_status: Option<String> = sqlx::query_scalar(
-        "SELECT status::text FROM verifications WHERE contract_id = $1 ORDER BY created_at DESC LIMIT 1",
-    )
-    .bind(contract.id)
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|err| db_internal_error("fetch previous verification status", err))?;
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|err| db_internal_error("begin verification transaction", err))?;
 
     let verification_id: Uuid = sqlx::query_scalar(
         "INSERT INTO verifications (contract_id, status, source_code, build_params, compiler_version, verified_at, error_message)
@@ -1554,19 +1660,138 @@ pub async fn verify_contract(
     .bind(&req.source_code)
     .bind(&req.build_params)
     .bind(&req.compiler_version)
-    .fetch_one(&state.db)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|err| db_internal_error("insert verification record", err))?;
 
+    let job = jobs::enqueue(
+        &mut *tx,
+        NewJob::new(
+            JobKind::VerifyContract,
+            json!(VerifyPayload {
+                verification_id,
+                ip_address: extract_ip_address(&headers),
+            }),
+        )
+        .contract(contract.id),
+    )
+    .await
+    .map_err(|err| db_internal_error("enqueue verification job", err))?;
+
+    tx.commit()
+        .await
+        .map_err(|err| db_internal_error("commit verification", err))?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "status": "pending",
+            "verification_id": verification_id,
+            "contract_id": contract.id,
+            "job_id": job.id,
+            "job_url": format!("/api/jobs/{}", job.id),
+        })),
+    ))
+}
+
+/// Executor for `verify_contract` jobs: compile the submitted source, compare
+/// it with the deployed WASM and record the outcome (audit log, analytics,
+/// webhooks). A verification that already left `pending` is not rebuilt, so
+/// retried attempts are harmless.
+pub async fn run_verification(
+    ctx: &JobContext,
+    verification_id: Uuid,
+    ip_address: &str,
+) -> ApiResult<Value> {
+    let db = &ctx.pool;
+    let (status, error_message, source_code, build_params, compiler_version, created_at): (
+        String,
+        Option<String>,
+        Option<String>,
+        Option<Value>,
+        Option<String>,
+        chrono::DateTime<chrono::Utc>,
+    ) = sqlx::query_as(
+        "SELECT status::text, error_message, source_code, build_params, compiler_version, created_at
+         FROM verifications WHERE id = $1",
+    )
+    .bind(verification_id)
+    .fetch_one(db)
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::RowNotFound => ApiError::not_found(
+            "VerificationNotFound",
+            format!("No verification found with ID: {}", verification_id),
+        ),
+        _ => db_internal_error("fetch verification", err),
+    })?;
+
+    // Settled by an earlier attempt; report the same outcome again.
+    match status.as_str() {
+        "pending" => {}
+        "verified" => {
+            return Ok(json!({
+                "verified": true,
+                "status": status,
+                "verification_id": verification_id,
+            }))
+        }
+        _ => {
+            return Err(ApiError::unprocessable(
+                "VerificationFailed",
+                error_message.unwrap_or_else(|| "Verification failed".to_string()),
+            ))
+        }
+    }
+
+    let contract: Contract = sqlx::query_as(
+        "SELECT c.* FROM contracts c JOIN verifications v ON v.contract_id = c.id WHERE v.id = $1",
+    )
+    .bind(verification_id)
+    .fetch_one(db)
+    .await
+    .map_err(|err| db_internal_error("fetch contract for verification", err))?;
+
+    let previous_status: Option<String> = sqlx::query_scalar(
+        "SELECT status::text FROM verifications
+         WHERE contract_id = $1 AND id <> $2 AND created_at <= $3
+         ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(contract.id)
+    .bind(verification_id)
+    .bind(created_at)
+    .fetch_optional(db)
+    .await
+    .map_err(|err| db_internal_error("fetch previous verification status", err))?;
+
+    let source_code = source_code.unwrap_or_default();
+    let build_params = build_params.unwrap_or(Value::Null);
+    let compiler_version = compiler_version.unwrap_or_default();
+
+    ctx.progress(
+        10,
+        format!("Compiling contract source with {}", compiler_version),
+    )
+    .await;
+
     let verification_result = verifier::verify_contract(
-        &req.source_code,
+        &source_code,
         &contract.wasm_hash,
-        Some(&req.compiler_version),
-        Some(&req.build_params),
+        Some(&compiler_version),
+        Some(&build_params),
     )
     .await;
 
-    let ip_address = extract_ip_address(&headers);
+    // A build that could not even be started (temp dir, cargo missing) is
+    // retried before the verification is marked failed.
+    if let Err(RegistryError::Internal(message)) = &verification_result {
+        if !ctx.is_last_attempt() {
+            return Err(ApiError::internal(message.clone()));
+        }
+    }
+
+    ctx.progress(90, "Recording verification result").await;
+
     let before_status = previous_status.unwrap_or_else(|| "pending".to_string());
 
     match verification_result {
@@ -1577,7 +1802,7 @@ pub async fn verify_contract(
                  WHERE id = $1",
             )
             .bind(verification_id)
-            .execute(&state.db)
+            .execute(db)
             .await
             .map_err(|err| db_internal_error("mark verification as verified", err))?;
 
@@ -1585,26 +1810,26 @@ pub async fn verify_contract(
                 "UPDATE contracts SET is_verified = true, updated_at = NOW() WHERE id = $1",
             )
             .bind(contract.id)
-            .execute(&state.db)
+            .execute(db)
             .await
             .map_err(|err| db_internal_error("mark contract verified", err))?;
 
             let verification_changes = json!({
                 "verification_id": { "before": Value::Null, "after": verification_id },
                 "status": { "before": Value::Null, "after": "verified" },
-                "compiler_version": { "before": Value::Null, "after": req.compiler_version },
+                "compiler_version": { "before": Value::Null, "after": compiler_version },
                 "verified_at": { "before": Value::Null, "after": chrono::Utc::now() },
                 "compiled_wasm_hash": { "before": Value::Null, "after": result.compiled_wasm_hash },
                 "deployed_wasm_hash": { "before": Value::Null, "after": result.deployed_wasm_hash }
             });
 
             write_contract_audit_log(
-                &state.db,
+                db,
                 ContractAuditEventType::VerificationAdded,
                 contract.id,
                 contract.publisher_id,
                 verification_changes,
-                &ip_address,
+                ip_address,
             )
             .await
             .map_err(|err| db_internal_error("write verification_added audit log", err))?;
@@ -1615,19 +1840,19 @@ pub async fn verify_contract(
                     "is_verified": { "before": contract.is_verified, "after": true }
                 });
                 write_contract_audit_log(
-                    &state.db,
+                    db,
                     ContractAuditEventType::StatusChanged,
                     contract.id,
                     contract.publisher_id,
                     status_changes,
-                    &ip_address,
+                    ip_address,
                 )
                 .await
                 .map_err(|err| db_internal_error("write status_changed audit log", err))?;
             }
 
     record_contract_interaction(
-        &state.db,
+        db,
         contract.id,
         None,
         "publish_success",
@@ -1642,7 +1867,7 @@ pub async fn verify_contract(
     .map_err(|err| db_internal_error("record verification interaction", err))?;
 
     let _ = analytics::record_event(
-        &state.db,
+        db,
         AnalyticsEventType::ContractVerified,
         Some(contract.id),
         Some(contract.publisher_id),
@@ -1652,7 +1877,7 @@ pub async fn verify_contract(
     )
     .await;
             let _ = analytics::record_event(
-                &state.db,
+                db,
                 AnalyticsEventType::ContractVerified,
                 Some(contract.id),
                 Some(contract.publisher_id),
@@ -1662,14 +1887,28 @@ pub async fn verify_contract(
             )
             .await;
 
-            Ok(Json(json!({
+            webhooks::emit(
+                db,
+                WebhookEvent::ContractVerified,
+                contract.id,
+                json!({
+                    "contract_id": contract.contract_id,
+                    "verification_id": verification_id,
+                    "compiler_version": compiler_version,
+                    "compiled_wasm_hash": result.compiled_wasm_hash,
+                    "deployed_wasm_hash": result.deployed_wasm_hash
+                }),
+            )
+            .await;
+
+            Ok(json!({
                 "verified": true,
                 "status": "verified",
                 "verification_id": verification_id,
                 "contract_id": contract.id,
                 "compiled_wasm_hash": result.compiled_wasm_hash,
                 "deployed_wasm_hash": result.deployed_wasm_hash
-            })))
+            }))
         }
         Ok(result) => {
             let failure_message = result
@@ -1683,25 +1922,25 @@ pub async fn verify_contract(
             )
             .bind(verification_id)
             .bind(&failure_message)
-            .execute(&state.db)
+            .execute(db)
             .await
             .map_err(|err| db_internal_error("mark verification as failed", err))?;
 
             let verification_changes = json!({
                 "verification_id": { "before": Value::Null, "after": verification_id },
                 "status": { "before": Value::Null, "after": "failed" },
-                "compiler_version": { "before": Value::Null, "after": req.compiler_version },
+                "compiler_version": { "before": Value::Null, "after": compiler_version },
                 "error_message": { "before": Value::Null, "after": failure_message },
                 "compiled_wasm_hash": { "before": Value::Null, "after": result.compiled_wasm_hash },
                 "deployed_wasm_hash": { "before": Value::Null, "after": result.deployed_wasm_hash }
             });
             write_contract_audit_log(
-                &state.db,
+                db,
                 ContractAuditEventType::VerificationAdded,
                 contract.id,
                 contract.publisher_id,
                 verification_changes,
-                &ip_address,
+                ip_address,
             )
             .await
             .map_err(|err| db_internal_error("write failed verification audit log", err))?;
@@ -1712,17 +1951,30 @@ pub async fn verify_contract(
                     "is_verified": { "before": contract.is_verified, "after": contract.is_verified }
                 });
                 write_contract_audit_log(
-                    &state.db,
+                    db,
                     ContractAuditEventType::StatusChanged,
                     contract.id,
                     contract.publisher_id,
                     status_changes,
-                    &ip_address,
+                    ip_address,
                 )
                 .await
                 .map_err(|err| db_internal_error("write failed status audit log", err))?;
             }
 
+            webhooks::emit(
+                db,
+                WebhookEvent::ContractFailedVerification,
+                contract.id,
+                json!({
+                    "contract_id": contract.contract_id,
+                    "verification_id": verification_id,
+                    "compiler_version": compiler_version,
+                    "error": failure_message
+                }),
+            )
+            .await;
+
             Err(ApiError::unprocessable(
                 "VerificationFailed",
                 failure_message,
@@ -1738,23 +1990,23 @@ pub async fn verify_contract(
             )
             .bind(verification_id)
             .bind(&failure_message)
-            .execute(&state.db)
+            .execute(db)
             .await
             .map_err(|db_err| db_internal_error("persist verifier error", db_err))?;
 
             let verification_changes = json!({
                 "verification_id": { "before": Value::Null, "after": verification_id },
                 "status": { "before": Value::Null, "after": "failed" },
-                "compiler_version": { "before": Value::Null, "after": req.compiler_version },
+                "compiler_version": { "before": Value::Null, "after": compiler_version },
                 "error_message": { "before": Value::Null, "after": failure_message }
             });
             write_contract_audit_log(
-                &state.db,
+                db,
                 ContractAuditEventType::VerificationAdded,
                 contract.id,
                 contract.publisher_id,
                 verification_changes,
-                &ip_address,
+                ip_address,
             )
             .await
             .map_err(|db_err| db_internal_error("write verifier error audit log", db_err))?;
@@ -1765,12 +2017,12 @@ pub async fn verify_contract(
                     "is_verified": { "before": contract.is_verified, "after": contract.is_verified }
                 });
                 write_contract_audit_log(
-                    &state.db,
+                    db,
                     ContractAuditEventType::StatusChanged,
                     contract.id,
                     contract.publisher_id,
                     status_changes,
-                    &ip_address,
+                    ip_address,
                 )
                 .await
                 .map_err(|db_err| {
@@ -1778,6 +2030,19 @@ pub async fn verify_contract(
                 })?;
             }
 
+            webhooks::emit(
+                db,
+                WebhookEvent::ContractFailedVerification,
+                contract.id,
+                json!({
+                    "contract_id": contract.contract_id,
+                    "verification_id": verification_id,
+                    "compiler_version": compiler_version,
+                    "error": failure_message
+                }),
+            )
+            .await;
+
             Err(ApiError::unprocessable(
                 "VerificationFailed",
                 failure_message,
@@ -2167,18 +2432,6 @@ pub async fn get_all_audit_logs(
     Ok(Json(logs))
 }
 
-pub async fn get_deployment_status() -> impl IntoResponse {
-    Json(json!({"status": "pending"}))
-}
-
-pub async fn deploy_green() -> impl IntoResponse {
-    Json(json!({"deployment_id": ""}))
-}
-
-pub async fn get_contract_performance() -> impl IntoResponse {
-    Json(json!({"performance": {}}))
-}
-
 // ─── Contract interaction history (Issue #46) ─────────────────────────────────
 
 /// GET /api/contracts/:id/interactions — list with optional filters (account, method, date range).
@@ -76,7 +76,7 @@ pub async fn calculate_health_score(pool: &PgPool, contract_id: Uuid) -> ApiResu
             END
         ), 0)
         FROM contract_scan_results sr
-        JOIN cve_vulnerabilities v ON sr.cve_id = v.cve_id
+        JOIN cve_vulnerabilities v ON sr.cve_id = v.cve_id AND v.withdrawn_at IS NULL
         WHERE sr.contract_id = $1 AND sr.is_false_positive = false
         "#,
     )
@@ -0,0 +1,313 @@
+//! API Handlers for interface-conformance search
+//!
+//! Finds contracts whose latest ABI implements a set of function signatures
+//! or a named interface such as SEP-41, and reports how closely each one
+//! conforms.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use contract_abi::{
+    check_conformance, parse_json_spec, ConformanceLevel, ConformanceReport, ContractABI,
+    InterfaceDefinition,
+};
+use serde::{Deserialize, Serialize};
+use shared::Network;
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::db_internal_error;
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+const MAX_SIGNATURES: usize = 64;
+
+#[derive(Debug, Deserialize)]
+pub struct InterfaceSearchRequest {
+    /// Built-in interface id (`sep41`, `nft`, `sep40`, ...)
+    pub interface: Option<String>,
+    /// Additional required signatures, e.g. `swap(Address, i128)`
+    #[serde(default)]
+    pub functions: Vec<String>,
+    #[serde(default)]
+    pub verified_only: bool,
+    pub network: Option<Network>,
+    /// Lowest conformance level to return (default: `full`)
+    pub min_level: Option<ConformanceLevel>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InterfaceMatch {
+    pub id: Uuid,
+    pub contract_id: String,
+    pub name: String,
+    pub network: Network,
+    pub is_verified: bool,
+    pub abi_version: String,
+    pub conformance: ConformanceReport,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InterfaceSearchResponse {
+    pub interface: String,
+    /// The signatures contracts were checked against
+    pub required: Vec<String>,
+    pub min_level: ConformanceLevel,
+    pub total: usize,
+    pub matches: Vec<InterfaceMatch>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct CandidateRow {
+    id: Uuid,
+    contract_id: String,
+    name: String,
+    network: Network,
+    is_verified: bool,
+    version: String,
+    abi: serde_json::Value,
+}
+
+/// GET /api/interfaces
+pub async fn list_interfaces() -> Json<Vec<InterfaceDefinition>> {
+    Json(InterfaceDefinition::builtins())
+}
+
+/// GET /api/interfaces/:id
+pub async fn get_interface(Path(id): Path<String>) -> ApiResult<Json<InterfaceDefinition>> {
+    InterfaceDefinition::builtin(&id)
+        .map(Json)
+        .ok_or_else(|| unknown_interface(&id))
+}
+
+/// POST /api/contracts/interface-search
+pub async fn search_by_interface(
+    State(state): State<AppState>,
+    Json(req): Json<InterfaceSearchRequest>,
+) -> ApiResult<Json<InterfaceSearchResponse>> {
+    let interface = resolve_interface(&req)?;
+    let min_level = req.min_level.unwrap_or(ConformanceLevel::Full);
+    if min_level == ConformanceLevel::None {
+        return Err(ApiError::bad_request(
+            "InvalidMinLevel",
+            "min_level must be 'partial' or 'full'",
+        ));
+    }
+    let limit = req.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let offset = req.offset.unwrap_or(0);
+
+    let candidates = fetch_candidates(&state, &req, &interface, min_level).await?;
+
+    let mut matches: Vec<InterfaceMatch> = candidates
+        .into_iter()
+        .filter_map(|row| {
+            let abi = parse_stored_abi(&row.abi, &row.contract_id)?;
+            let conformance = check_conformance(&abi, &interface);
+            (conformance.level >= min_level).then(|| InterfaceMatch {
+                id: row.id,
+                contract_id: row.contract_id,
+                name: row.name,
+                network: row.network,
+                is_verified: row.is_verified,
+                abi_version: row.version,
+                conformance,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.conformance
+            .level
+            .cmp(&a.conformance.level)
+            .then(b.conformance.score.total_cmp(&a.conformance.score))
+            .then(b.is_verified.cmp(&a.is_verified))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let total = matches.len();
+    let matches = matches.into_iter().skip(offset).take(limit).collect();
+
+    Ok(Json(InterfaceSearchResponse {
+        interface: interface.id.clone(),
+        required: interface.functions.iter().map(|f| f.to_string()).collect(),
+        min_level,
+        total,
+        matches,
+    }))
+}
+
+/// Combine the named interface, if any, with the ad-hoc signatures.
+fn resolve_interface(req: &InterfaceSearchRequest) -> ApiResult<InterfaceDefinition> {
+    if req.functions.len() > MAX_SIGNATURES {
+        return Err(ApiError::bad_request(
+            "TooManySignatures",
+            format!("At most {} function signatures are allowed", MAX_SIGNATURES),
+        ));
+    }
+
+    let custom = InterfaceDefinition::from_signatures("custom", &req.functions)
+        .map_err(|e| ApiError::bad_request("InvalidSignature", e.to_string()))?;
+
+    let mut interface = match req.interface.as_deref() {
+        Some(id) => InterfaceDefinition::builtin(id).ok_or_else(|| unknown_interface(id))?,
+        None if custom.functions.is_empty() => {
+            return Err(ApiError::bad_request(
+                "EmptyInterface",
+                "Provide an interface id, function signatures, or both",
+            ))
+        }
+        None => return Ok(custom),
+    };
+
+    for function in custom.functions {
+        if let Some(existing) = interface
+            .functions
+            .iter_mut()
+            .find(|f| f.name == function.name)
+        {
+            *existing = function;
+        } else {
+            interface.functions.push(function);
+        }
+    }
+    Ok(interface)
+}
+
+fn unknown_interface(id: &str) -> ApiError {
+    let known: Vec<String> = InterfaceDefinition::builtins()
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+    ApiError::not_found(
+        "InterfaceNotFound",
+        format!(
+            "Unknown interface '{}'. Known interfaces: {}",
+            id,
+            known.join(", ")
+        ),
+    )
+}
+
+/// Latest ABI of every contract that defines the required function names:
+/// all of them for full conformance, any of them for partial. Signatures are
+/// checked afterwards, in Rust.
+async fn fetch_candidates(
+    state: &AppState,
+    req: &InterfaceSearchRequest,
+    interface: &InterfaceDefinition,
+    min_level: ConformanceLevel,
+) -> ApiResult<Vec<CandidateRow>> {
+    let names: Vec<String> = interface.functions.iter().map(|f| f.name.clone()).collect();
+
+    let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+        "SELECT DISTINCT ON (c.id) \
+           c.id, c.contract_id, c.name, c.network, c.is_verified, a.version, a.abi \
+         FROM contracts c \
+         JOIN contract_search_documents d ON d.contract_id = c.id \
+         JOIN contract_abis a ON a.contract_id = c.id \
+         WHERE d.function_names ",
+    );
+    query.push(if min_level == ConformanceLevel::Full {
+        "@> "
+    } else {
+        "&& "
+    });
+    query.push_bind(names);
+
+    if req.verified_only {
+        query.push(" AND c.is_verified = true");
+    }
+    if let Some(network) = &req.network {
+        query.push(" AND c.network = ").push_bind(network.clone());
+    }
+    query.push(" ORDER BY c.id, a.created_at DESC");
+
+    query
+        .build_query_as::<CandidateRow>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(|err| db_internal_error("interface search candidates", err))
+}
+
+/// Stored ABIs are either raw contract specs or an already-parsed
+/// `ContractABI`; anything else is skipped.
+fn parse_stored_abi(abi: &serde_json::Value, contract_id: &str) -> Option<ContractABI> {
+    if abi.is_array() {
+        return parse_json_spec(&abi.to_string(), contract_id)
+            .map_err(|e| tracing::debug!(contract_id, error = %e, "unparseable contract spec"))
+            .ok();
+    }
+    serde_json::from_value(abi.clone())
+        .map_err(|e| tracing::debug!(contract_id, error = %e, "unparseable stored ABI"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(interface: Option<&str>, functions: &[&str]) -> InterfaceSearchRequest {
+        InterfaceSearchRequest {
+            interface: interface.map(str::to_string),
+            functions: functions.iter().map(|f| f.to_string()).collect(),
+            verified_only: false,
+            network: None,
+            min_level: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    #[test]
+    fn signatures_extend_and_override_named_interfaces() {
+        let interface = resolve_interface(&request(
+            Some("sep41"),
+            &["mint(Address, i128)", "decimals() -> u64"],
+        ))
+        .unwrap();
+        assert_eq!(interface.functions.len(), 11);
+        let decimals = interface
+            .functions
+            .iter()
+            .find(|f| f.name == "decimals")
+            .unwrap();
+        assert_eq!(decimals.to_string(), "decimals() -> u64");
+    }
+
+    #[test]
+    fn rejects_empty_unknown_and_invalid_queries() {
+        assert!(resolve_interface(&request(None, &[])).is_err());
+        assert!(resolve_interface(&request(Some("erc20"), &[])).is_err());
+        assert!(resolve_interface(&request(None, &["swap(Address"])).is_err());
+        assert_eq!(
+            resolve_interface(&request(None, &["swap(Address, i128)"]))
+                .unwrap()
+                .id,
+            "custom"
+        );
+    }
+
+    #[test]
+    fn parses_both_stored_abi_formats() {
+        let spec = serde_json::json!([{
+            "type": "function",
+            "name": "balance",
+            "inputs": [{ "name": "id", "value": { "type": "address" } }],
+            "outputs": [{ "type": "i128" }]
+        }]);
+        let abi = parse_stored_abi(&spec, "C1").unwrap();
+        assert!(abi.has_function("balance"));
+
+        let parsed = serde_json::to_value(&abi).unwrap();
+        assert!(parse_stored_abi(&parsed, "C1")
+            .unwrap()
+            .has_function("balance"));
+
+        assert!(parse_stored_abi(&serde_json::json!({ "functions": 1 }), "C1").is_none());
+    }
+}
@@ -0,0 +1,19 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{interface_handlers, state::AppState};
+
+pub fn interface_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/interfaces", get(interface_handlers::list_interfaces))
+        .route(
+            "/api/interfaces/:id",
+            get(interface_handlers::get_interface),
+        )
+        .route(
+            "/api/contracts/interface-search",
+            post(interface_handlers::search_by_interface),
+        )
+}
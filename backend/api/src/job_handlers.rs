@@ -0,0 +1,256 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures::stream::Stream;
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::db_internal_error;
+use crate::job_worker;
+use crate::jobs::{self, Job, JobKind, JobLog, JobStatus};
+use crate::state::AppState;
+
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const STREAM_LOG_BATCH: i64 = 200;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct JobListQuery {
+    pub status: Option<String>,
+    pub kind: Option<String>,
+    pub contract_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct JobLogQuery {
+    /// Only lines after this log id
+    pub after: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+fn job_not_found(job_id: Uuid) -> ApiError {
+    ApiError::not_found("JobNotFound", format!("No job found with ID: {}", job_id))
+}
+
+async fn fetch_job(pool: &PgPool, job_id: Uuid) -> ApiResult<Job> {
+    jobs::get(pool, job_id)
+        .await
+        .map_err(|e| db_internal_error("fetch job", e))?
+        .ok_or_else(|| job_not_found(job_id))
+}
+
+/// GET /api/jobs — most recent jobs, optionally filtered.
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    Query(query): Query<JobListQuery>,
+) -> ApiResult<Json<Vec<Job>>> {
+    if let Some(status) = query.status.as_deref() {
+        if JobStatus::parse(status).is_none() {
+            return Err(ApiError::bad_request(
+                "InvalidJobStatus",
+                format!("Unknown job status '{}'", status),
+            ));
+        }
+    }
+    if let Some(kind) = query.kind.as_deref() {
+        if JobKind::parse(kind).is_none() {
+            return Err(ApiError::bad_request(
+                "InvalidJobKind",
+                format!("Unknown job kind '{}'", kind),
+            ));
+        }
+    }
+
+    let jobs: Vec<Job> = sqlx::query_as(
+        r#"
+        SELECT * FROM jobs
+        WHERE ($1::text IS NULL OR status = $1)
+          AND ($2::text IS NULL OR kind = $2)
+          AND ($3::uuid IS NULL OR contract_id = $3)
+        ORDER BY created_at DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(&query.status)
+    .bind(&query.kind)
+    .bind(query.contract_id)
+    .bind(query.limit.unwrap_or(50).clamp(1, 200))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| db_internal_error("list jobs", e))?;
+
+    Ok(Json(jobs))
+}
+
+/// GET /api/jobs/:id — status, progress and result.
+pub async fn get_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+) -> ApiResult<Json<Job>> {
+    fetch_job(&state.db, job_id).await.map(Json)
+}
+
+/// GET /api/jobs/:id/logs
+pub async fn get_job_logs(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+    Query(query): Query<JobLogQuery>,
+) -> ApiResult<Json<Vec<JobLog>>> {
+    fetch_job(&state.db, job_id).await?;
+    let logs = jobs::logs_after(
+        &state.db,
+        job_id,
+        query.after.unwrap_or(0),
+        query.limit.unwrap_or(500).clamp(1, 1000),
+    )
+    .await
+    .map_err(|e| db_internal_error("fetch job logs", e))?;
+    Ok(Json(logs))
+}
+
+/// POST /api/jobs/:id/cancel — a queued job is cancelled immediately
+/// (`200`); a running one is stopped by its worker within a few seconds
+/// (`202`).
+pub async fn cancel_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+) -> ApiResult<(StatusCode, Json<Job>)> {
+    let job = jobs::request_cancel(&state.db, job_id)
+        .await
+        .map_err(|e| db_internal_error("cancel job", e))?;
+
+    let Some(job) = job else {
+        let job = fetch_job(&state.db, job_id).await?;
+        return Err(ApiError::conflict(
+            "JobFinished",
+            format!("Job {} has already finished ({})", job.id, job.status),
+        ));
+    };
+
+    if job.job_status() == Some(JobStatus::Cancelled) {
+        let _ = jobs::append_log(&state.db, job.id, jobs::LogLevel::Warn, "Job cancelled").await;
+        job_worker::abandon(&state.db, &job, "Job cancelled").await;
+        Ok((StatusCode::OK, Json(job)))
+    } else {
+        let _ = jobs::append_log(
+            &state.db,
+            job.id,
+            jobs::LogLevel::Warn,
+            "Cancellation requested",
+        )
+        .await;
+        Ok((StatusCode::ACCEPTED, Json(job)))
+    }
+}
+
+struct JobStream {
+    pool: PgPool,
+    job_id: Uuid,
+    last_log_id: i64,
+    last_seen: Option<(String, i16, Option<String>)>,
+    pending: VecDeque<Event>,
+    /// The job finished; one more pass picks up its final log lines.
+    finishing: bool,
+    done: bool,
+}
+
+impl JobStream {
+    async fn poll(&mut self) {
+        let job = match jobs::get(&self.pool, self.job_id).await {
+            Ok(Some(job)) => job,
+            Ok(None) => {
+                self.done = true;
+                return;
+            }
+            Err(err) => {
+                tracing::warn!(job_id = %self.job_id, error = ?err, "jobs: stream poll failed");
+                return;
+            }
+        };
+
+        if let Ok(logs) =
+            jobs::logs_after(&self.pool, self.job_id, self.last_log_id, STREAM_LOG_BATCH).await
+        {
+            for log in logs {
+                self.last_log_id = log.id;
+                if let Ok(event) = Event::default()
+                    .event("log")
+                    .id(log.id.to_string())
+                    .json_data(&log)
+                {
+                    self.pending.push_back(event);
+                }
+            }
+        }
+
+        let seen = (
+            job.status.clone(),
+            job.progress,
+            job.progress_message.clone(),
+        );
+        if self.last_seen.as_ref() != Some(&seen) {
+            self.last_seen = Some(seen);
+            if let Ok(event) = Event::default().event("job").json_data(&job) {
+                self.pending.push_back(event);
+            }
+        }
+
+        if self.finishing {
+            self.done = true;
+        } else if job.is_terminal() {
+            self.finishing = true;
+        }
+    }
+}
+
+/// GET /api/jobs/:id/stream — Server-Sent Events: `job` events carry the job
+/// whenever its status or progress changes, `log` events carry log lines
+/// (with their id, so reconnecting with `Last-Event-ID` skips lines already
+/// seen). The stream ends once the job has finished.
+pub async fn stream_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    fetch_job(&state.db, job_id).await?;
+    let last_log_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0);
+
+    let stream = JobStream {
+        pool: state.db.clone(),
+        job_id,
+        last_log_id,
+        last_seen: None,
+        pending: VecDeque::new(),
+        finishing: false,
+        done: false,
+    };
+
+    let events = futures::stream::unfold(stream, |mut stream| async move {
+        loop {
+            if let Some(event) = stream.pending.pop_front() {
+                return Some((Ok(event), stream));
+            }
+            if stream.done {
+                return None;
+            }
+            if stream.last_seen.is_some() {
+                tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+            }
+            stream.poll().await;
+        }
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
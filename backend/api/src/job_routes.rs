@@ -0,0 +1,15 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{job_handlers, state::AppState};
+
+pub fn job_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/jobs", get(job_handlers::list_jobs))
+        .route("/api/jobs/:id", get(job_handlers::get_job))
+        .route("/api/jobs/:id/logs", get(job_handlers::get_job_logs))
+        .route("/api/jobs/:id/stream", get(job_handlers::stream_job))
+        .route("/api/jobs/:id/cancel", post(job_handlers::cancel_job))
+}
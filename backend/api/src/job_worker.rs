@@ -0,0 +1,361 @@
+//! Job workers and executors
+//!
+//! `api worker` runs [`run_worker`] as its own process so compiler-heavy
+//! jobs scale apart from the HTTP tier; unless `JOB_WORKER_EMBEDDED=false`
+//! the API process also runs a few workers via [`spawn_job_workers`], which
+//! keeps single-process deployments working.
+//!
+//! Each worker slot claims one job at a time, renews its lease every
+//! [`HEARTBEAT_INTERVAL`] and stops the job as soon as a cancellation is
+//! seen there. Dropping the executor's future kills any cargo process it
+//! started.
+//!
+//! Configuration: `JOB_WORKER_CONCURRENCY` (slots, default 2),
+//! `JOB_POLL_INTERVAL_MS` (default 1000), `JOB_LEASE_SECS` (default 60) and
+//! `JOB_WORKER_KINDS` (comma-separated job kinds to take; default all).
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::benchmark_runner;
+use crate::compatibility_runner;
+use crate::handlers;
+use crate::jobs::{self, FailOutcome, Job, JobContext, JobError, JobKind};
+use crate::scanner_service::{self, ScanRequest};
+
+const DEFAULT_CONCURRENCY: usize = 2;
+const DEFAULT_POLL_INTERVAL_MS: u64 = 1_000;
+const DEFAULT_LEASE_SECS: u64 = 60;
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Payload of a [`JobKind::VerifyContract`] job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyPayload {
+    pub verification_id: Uuid,
+    pub ip_address: String,
+}
+
+/// Payload of a [`JobKind::ScanContract`] job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanPayload {
+    pub contract_id: Uuid,
+    pub request: ScanRequest,
+}
+
+/// Payload of a [`JobKind::CompatibilityRun`] job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatibilityRunPayload {
+    pub run_id: Uuid,
+}
+
+/// Payload of a [`JobKind::Benchmark`] job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkPayload {
+    pub benchmark_id: Uuid,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkerConfig {
+    pub worker_id: String,
+    pub concurrency: usize,
+    pub poll_interval: Duration,
+    pub lease: Duration,
+    pub kinds: Option<Vec<String>>,
+}
+
+impl WorkerConfig {
+    pub fn from_env() -> Self {
+        let env_u64 = |name: &str| std::env::var(name).ok().and_then(|s| s.parse::<u64>().ok());
+
+        let kinds = std::env::var("JOB_WORKER_KINDS").ok().map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|kind| !kind.is_empty())
+                .filter(|kind| {
+                    let known = JobKind::parse(kind).is_some();
+                    if !known {
+                        tracing::warn!(kind = %kind, "jobs: ignoring unknown kind in JOB_WORKER_KINDS");
+                    }
+                    known
+                })
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        });
+
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "worker".to_string());
+        Self {
+            worker_id: format!("{}:{}", host, std::process::id()),
+            concurrency: env_u64("JOB_WORKER_CONCURRENCY")
+                .map(|n| n.max(1) as usize)
+                .unwrap_or(DEFAULT_CONCURRENCY),
+            poll_interval: Duration::from_millis(
+                env_u64("JOB_POLL_INTERVAL_MS").unwrap_or(DEFAULT_POLL_INTERVAL_MS),
+            ),
+            lease: Duration::from_secs(
+                env_u64("JOB_LEASE_SECS")
+                    .unwrap_or(DEFAULT_LEASE_SECS)
+                    .max(3 * HEARTBEAT_INTERVAL.as_secs()),
+            ),
+            kinds,
+        }
+    }
+}
+
+/// Whether the API process should run workers itself.
+pub fn embedded_enabled() -> bool {
+    std::env::var("JOB_WORKER_EMBEDDED")
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "0" | "false" | "no"))
+        .unwrap_or(true)
+}
+
+/// Start `config.concurrency` worker slots in the background.
+pub fn spawn_job_workers(pool: PgPool, config: WorkerConfig) {
+    let config = Arc::new(config);
+    for _ in 0..config.concurrency {
+        tokio::spawn(work_loop(pool.clone(), config.clone()));
+    }
+}
+
+/// Run workers until SIGINT/SIGTERM, then hand in-flight jobs back to the
+/// queue.
+pub async fn run_worker(pool: PgPool, config: WorkerConfig) {
+    tracing::info!(
+        worker_id = %config.worker_id,
+        concurrency = config.concurrency,
+        kinds = ?config.kinds,
+        "jobs: worker started"
+    );
+    let worker_id = config.worker_id.clone();
+    let config = Arc::new(config);
+    let slots: Vec<_> = (0..config.concurrency)
+        .map(|_| tokio::spawn(work_loop(pool.clone(), config.clone())))
+        .collect();
+
+    shutdown_signal().await;
+    tracing::info!(worker_id = %worker_id, "jobs: shutting down worker");
+    for slot in &slots {
+        slot.abort();
+    }
+    for slot in slots {
+        let _ = slot.await;
+    }
+    match jobs::release(&pool, &worker_id).await {
+        Ok(released) => tracing::info!(released, "jobs: returned in-flight jobs to the queue"),
+        Err(err) => tracing::error!(error = ?err, "jobs: failed to release in-flight jobs"),
+    }
+    pool.close().await;
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+async fn work_loop(pool: PgPool, config: Arc<WorkerConfig>) {
+    let mut last_reap: Option<Instant> = None;
+    loop {
+        if last_reap.is_none_or(|at| at.elapsed() >= config.lease) {
+            reap(&pool).await;
+            last_reap = Some(Instant::now());
+        }
+        match run_next(&pool, &config).await {
+            Ok(true) => {}
+            Ok(false) => tokio::time::sleep(config.poll_interval).await,
+            Err(err) => {
+                tracing::error!(error = ?err, "jobs: worker failed");
+                tokio::time::sleep(config.poll_interval).await;
+            }
+        }
+    }
+}
+
+async fn reap(pool: &PgPool) {
+    match jobs::reap_expired(pool).await {
+        Ok(settled) => {
+            for job in settled {
+                let reason = job
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| "Job cancelled".to_string());
+                abandon(pool, &job, &reason).await;
+            }
+        }
+        Err(err) => tracing::error!(error = ?err, "jobs: failed to reap expired leases"),
+    }
+}
+
+/// Claim and execute one job; returns false when nothing was due.
+pub async fn run_next(pool: &PgPool, config: &WorkerConfig) -> Result<bool, sqlx::Error> {
+    let Some(job) = jobs::claim(
+        pool,
+        &config.worker_id,
+        config.kinds.as_deref(),
+        config.lease,
+    )
+    .await?
+    else {
+        return Ok(false);
+    };
+
+    tracing::info!(job_id = %job.id, kind = %job.kind, attempt = job.attempts, "jobs: running");
+    let ctx = JobContext::new(pool.clone(), &job);
+    ctx.info(format!(
+        "Attempt {}/{} started on {}",
+        job.attempts, job.max_attempts, config.worker_id
+    ))
+    .await;
+
+    let mut execution = std::pin::pin!(execute(&ctx, &job));
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    heartbeat.tick().await;
+
+    let outcome = loop {
+        tokio::select! {
+            outcome = &mut execution => break Some(outcome),
+            _ = heartbeat.tick() => {
+                match jobs::heartbeat(pool, job.id, &config.worker_id, config.lease).await {
+                    Ok(Some(false)) => {}
+                    Ok(Some(true)) => break None,
+                    Ok(None) => {
+                        tracing::warn!(job_id = %job.id, "jobs: lease lost, abandoning attempt");
+                        return Ok(true);
+                    }
+                    Err(err) => tracing::warn!(job_id = %job.id, error = ?err, "jobs: heartbeat failed"),
+                }
+            }
+        }
+    };
+
+    match outcome {
+        Some(Ok(result)) => {
+            if jobs::complete(pool, job.id, &config.worker_id, &result).await? {
+                ctx.info("Job succeeded").await;
+            } else {
+                tracing::warn!(job_id = %job.id, "jobs: lease lost, discarding result");
+            }
+        }
+        Some(Err(err)) => {
+            tracing::warn!(job_id = %job.id, kind = %job.kind, error = %err, "jobs: attempt failed");
+            match jobs::fail(pool, &job, &config.worker_id, &err).await? {
+                FailOutcome::Failed(failed) => {
+                    ctx.log(jobs::LogLevel::Error, format!("Job failed: {}", err))
+                        .await;
+                    abandon(pool, &failed, &err.message).await;
+                }
+                FailOutcome::Retrying => {
+                    ctx.warn(format!(
+                        "Attempt {} failed, will retry: {}",
+                        job.attempts, err
+                    ))
+                    .await;
+                }
+                FailOutcome::LeaseLost => {
+                    tracing::warn!(job_id = %job.id, "jobs: lease lost, discarding failure");
+                }
+            }
+        }
+        None => {
+            if let Some(cancelled) = jobs::mark_cancelled(pool, job.id).await? {
+                ctx.warn("Job cancelled").await;
+                abandon(pool, &cancelled, "Job cancelled").await;
+            }
+        }
+    }
+    Ok(true)
+}
+
+async fn execute(ctx: &JobContext, job: &Job) -> Result<serde_json::Value, JobError> {
+    let kind = JobKind::parse(&job.kind)
+        .ok_or_else(|| JobError::permanent(format!("Unknown job kind '{}'", job.kind)))?;
+
+    match kind {
+        JobKind::VerifyContract => {
+            let payload: VerifyPayload = parse_payload(job)?;
+            Ok(
+                handlers::run_verification(ctx, payload.verification_id, &payload.ip_address)
+                    .await?,
+            )
+        }
+        JobKind::ScanContract => {
+            let payload: ScanPayload = parse_payload(job)?;
+            ctx.progress(10, "Matching dependencies against known advisories")
+                .await;
+            let report =
+                scanner_service::perform_scan(&ctx.pool, payload.contract_id, payload.request)
+                    .await
+                    .map_err(|err| match err {
+                        shared::RegistryError::Database(e) => JobError::from(e),
+                        other => JobError::permanent(other.to_string()),
+                    })?;
+            serde_json::to_value(report).map_err(|e| JobError::permanent(e.to_string()))
+        }
+        JobKind::CompatibilityRun => {
+            let payload: CompatibilityRunPayload = parse_payload(job)?;
+            compatibility_runner::execute(ctx, payload.run_id).await
+        }
+        JobKind::Benchmark => {
+            let payload: BenchmarkPayload = parse_payload(job)?;
+            benchmark_runner::execute(ctx, payload.benchmark_id).await
+        }
+    }
+}
+
+fn parse_payload<T: serde::de::DeserializeOwned>(job: &Job) -> Result<T, JobError> {
+    serde_json::from_value(job.payload.clone())
+        .map_err(|e| JobError::permanent(format!("Invalid {} payload: {}", job.kind, e)))
+}
+
+/// Settle the record a job was working on after it failed for good or was
+/// cancelled, so nothing is left pending forever. Safe to call repeatedly.
+pub async fn abandon(pool: &PgPool, job: &Job, reason: &str) {
+    let result = match JobKind::parse(&job.kind) {
+        Some(JobKind::VerifyContract) => match parse_payload::<VerifyPayload>(job) {
+            Ok(payload) => sqlx::query(
+                "UPDATE verifications SET status = 'failed', error_message = $2
+                 WHERE id = $1 AND status = 'pending'",
+            )
+            .bind(payload.verification_id)
+            .bind(reason)
+            .execute(pool)
+            .await
+            .map(|_| ()),
+            Err(_) => Ok(()),
+        },
+        Some(JobKind::CompatibilityRun) => match parse_payload::<CompatibilityRunPayload>(job) {
+            Ok(payload) => compatibility_runner::abandon_run(pool, payload.run_id, reason).await,
+            Err(_) => Ok(()),
+        },
+        Some(JobKind::Benchmark) => match parse_payload::<BenchmarkPayload>(job) {
+            Ok(payload) => {
+                benchmark_runner::abandon_benchmark(pool, payload.benchmark_id, reason).await
+            }
+            Err(_) => Ok(()),
+        },
+        Some(JobKind::ScanContract) | None => Ok(()),
+    };
+    if let Err(err) = result {
+        tracing::error!(job_id = %job.id, error = ?err, "jobs: failed to clean up after job");
+    }
+}
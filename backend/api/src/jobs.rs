@@ -0,0 +1,640 @@
+//! Durable job queue
+//!
+//! Handlers that would otherwise block on a compiler or a long database pass
+//! [`enqueue`] a job (usually in the same transaction as the record it
+//! works on) and answer `202 Accepted` with the job id. Workers (see
+//! `job_worker`) [`claim`] the highest-priority due job with
+//! `FOR UPDATE SKIP LOCKED`, renew their lease while it runs, and record the
+//! outcome: success, a retry with backoff, a permanent failure or a
+//! cancellation. Progress and log lines are written as the job runs so
+//! `/api/jobs/:id` and its stream can report them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::notification_dispatcher::backoff_delay;
+
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 3;
+const MAX_ERROR_LEN: usize = 4_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    VerifyContract,
+    ScanContract,
+    CompatibilityRun,
+    Benchmark,
+}
+
+impl JobKind {
+    pub const ALL: [JobKind; 4] = [
+        JobKind::VerifyContract,
+        JobKind::ScanContract,
+        JobKind::CompatibilityRun,
+        JobKind::Benchmark,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::VerifyContract => "verify_contract",
+            JobKind::ScanContract => "scan_contract",
+            JobKind::CompatibilityRun => "compatibility_run",
+            JobKind::Benchmark => "benchmark",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == s)
+    }
+
+    /// Someone is usually waiting on a verification; compatibility sweeps
+    /// and benchmarks can queue behind it.
+    pub fn default_priority(&self) -> i16 {
+        match self {
+            JobKind::VerifyContract => 10,
+            JobKind::ScanContract => 5,
+            JobKind::CompatibilityRun | JobKind::Benchmark => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for JobKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub const ALL: [JobStatus; 5] = [
+        JobStatus::Queued,
+        JobStatus::Running,
+        JobStatus::Succeeded,
+        JobStatus::Failed,
+        JobStatus::Cancelled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == s)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub priority: i16,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+    pub locked_by: Option<String>,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub cancel_requested: bool,
+    pub progress: i16,
+    pub progress_message: Option<String>,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub contract_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Job {
+    pub fn job_status(&self) -> Option<JobStatus> {
+        JobStatus::parse(&self.status)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.job_status().is_some_and(|s| s.is_terminal())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct JobLog {
+    pub id: i64,
+    pub job_id: Uuid,
+    pub level: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+/// A job to be queued; see [`enqueue`].
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    pub kind: JobKind,
+    pub payload: serde_json::Value,
+    pub priority: i16,
+    pub max_attempts: i32,
+    pub contract_id: Option<Uuid>,
+}
+
+impl NewJob {
+    pub fn new(kind: JobKind, payload: serde_json::Value) -> Self {
+        Self {
+            kind,
+            payload,
+            priority: kind.default_priority(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            contract_id: None,
+        }
+    }
+
+    pub fn contract(mut self, contract_id: Uuid) -> Self {
+        self.contract_id = Some(contract_id);
+        self
+    }
+
+    pub fn priority(mut self, priority: i16) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: i32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+}
+
+/// Why an attempt did not produce a result. Retryable errors (database
+/// hiccups, a compiler that could not be started) are run again after a
+/// backoff until `max_attempts`; permanent ones fail the job immediately.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobError {
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl JobError {
+    pub fn retryable(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            retryable: true,
+        }
+    }
+
+    pub fn permanent(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            retryable: false,
+        }
+    }
+}
+
+impl std::fmt::Display for JobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<sqlx::Error> for JobError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::retryable(format!("Database error: {}", err))
+    }
+}
+
+/// Executors reuse handler code that reports through `ApiError`: client
+/// errors (e.g. a failed verification) are final, server errors are retried.
+impl From<ApiError> for JobError {
+    fn from(err: ApiError) -> Self {
+        if err.status().is_server_error() {
+            Self::retryable(err.to_string())
+        } else {
+            Self::permanent(err.to_string())
+        }
+    }
+}
+
+/// Handle given to an executor for reporting on the job it runs.
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    pub pool: PgPool,
+    pub job_id: Uuid,
+    pub attempt: i32,
+    pub max_attempts: i32,
+}
+
+impl JobContext {
+    pub fn new(pool: PgPool, job: &Job) -> Self {
+        Self {
+            pool,
+            job_id: job.id,
+            attempt: job.attempts,
+            max_attempts: job.max_attempts,
+        }
+    }
+
+    pub fn is_last_attempt(&self) -> bool {
+        self.attempt >= self.max_attempts
+    }
+
+    /// Append a log line. Logging is best effort and never fails the job.
+    pub async fn log(&self, level: LogLevel, message: impl AsRef<str>) {
+        if let Err(err) = append_log(&self.pool, self.job_id, level, message.as_ref()).await {
+            tracing::warn!(job_id = %self.job_id, error = ?err, "jobs: failed to write log line");
+        }
+    }
+
+    pub async fn info(&self, message: impl AsRef<str>) {
+        self.log(LogLevel::Info, message).await
+    }
+
+    pub async fn warn(&self, message: impl AsRef<str>) {
+        self.log(LogLevel::Warn, message).await
+    }
+
+    /// Record progress (0-100) and log the step.
+    pub async fn progress(&self, percent: i16, message: impl AsRef<str>) {
+        let message = message.as_ref();
+        let result = sqlx::query(
+            "UPDATE jobs SET progress = $2, progress_message = $3, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(self.job_id)
+        .bind(percent.clamp(0, 100))
+        .bind(message)
+        .execute(&self.pool)
+        .await;
+        if let Err(err) = result {
+            tracing::warn!(job_id = %self.job_id, error = ?err, "jobs: failed to record progress");
+        }
+        self.info(message).await;
+    }
+}
+
+/// Queue a job. Takes any executor so callers can enqueue inside the
+/// transaction that creates the record the job works on.
+pub async fn enqueue<'e, E: PgExecutor<'e>>(executor: E, job: NewJob) -> Result<Job, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        INSERT INTO jobs (kind, payload, priority, max_attempts, contract_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(job.kind.as_str())
+    .bind(&job.payload)
+    .bind(job.priority)
+    .bind(job.max_attempts)
+    .bind(job.contract_id)
+    .fetch_one(executor)
+    .await
+}
+
+pub async fn get(pool: &PgPool, job_id: Uuid) -> Result<Option<Job>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn append_log(
+    pool: &PgPool,
+    job_id: Uuid,
+    level: LogLevel,
+    message: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO job_logs (job_id, level, message) VALUES ($1, $2, $3)")
+        .bind(job_id)
+        .bind(level.as_str())
+        .bind(truncate(message))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Log lines with an id greater than `after`, oldest first.
+pub async fn logs_after(
+    pool: &PgPool,
+    job_id: Uuid,
+    after: i64,
+    limit: i64,
+) -> Result<Vec<JobLog>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM job_logs WHERE job_id = $1 AND id > $2 ORDER BY id LIMIT $3")
+        .bind(job_id)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+/// Claim the highest-priority due job, optionally restricted to `kinds`,
+/// and lease it to `worker_id`.
+pub async fn claim(
+    pool: &PgPool,
+    worker_id: &str,
+    kinds: Option<&[String]>,
+    lease: Duration,
+) -> Result<Option<Job>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        UPDATE jobs
+        SET status = 'running', attempts = attempts + 1, locked_by = $1,
+            locked_until = NOW() + make_interval(secs => $2),
+            started_at = COALESCE(started_at, NOW()), updated_at = NOW()
+        WHERE id = (
+            SELECT id FROM jobs
+            WHERE status = 'queued' AND run_at <= NOW()
+              AND ($3::text[] IS NULL OR kind = ANY($3))
+            ORDER BY priority DESC, run_at
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(worker_id)
+    .bind(lease.as_secs_f64())
+    .bind(kinds)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Extend the lease on a running job. Returns `Some(true)` when someone
+/// asked for the job to be cancelled, `None` when the lease was lost.
+pub async fn heartbeat(
+    pool: &PgPool,
+    job_id: Uuid,
+    worker_id: &str,
+    lease: Duration,
+) -> Result<Option<bool>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        UPDATE jobs
+        SET locked_until = NOW() + make_interval(secs => $3), updated_at = NOW()
+        WHERE id = $1 AND locked_by = $2 AND status = 'running'
+        RETURNING cancel_requested
+        "#,
+    )
+    .bind(job_id)
+    .bind(worker_id)
+    .bind(lease.as_secs_f64())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record a successful attempt. Returns false, recording nothing, when
+/// `worker_id` no longer holds the lease.
+pub async fn complete(
+    pool: &PgPool,
+    job_id: Uuid,
+    worker_id: &str,
+    result: &serde_json::Value,
+) -> Result<bool, sqlx::Error> {
+    let done = sqlx::query(
+        r#"
+        UPDATE jobs
+        SET status = 'succeeded', result = $3, error = NULL, progress = 100,
+            locked_by = NULL, locked_until = NULL, finished_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND locked_by = $2 AND status = 'running'
+        "#,
+    )
+    .bind(job_id)
+    .bind(worker_id)
+    .bind(result)
+    .execute(pool)
+    .await?;
+    Ok(done.rows_affected() > 0)
+}
+
+/// What [`fail`] did with a failed attempt.
+#[derive(Debug)]
+pub enum FailOutcome {
+    /// Requeued for another attempt after [`backoff_delay`].
+    Retrying,
+    /// Failed for good; the caller cleans up after it.
+    Failed(Job),
+    /// `worker_id` no longer holds the lease; nothing was recorded.
+    LeaseLost,
+}
+
+/// Record a failed attempt. Retryable errors with attempts left are
+/// requeued; others fail the job.
+pub async fn fail(
+    pool: &PgPool,
+    job: &Job,
+    worker_id: &str,
+    err: &JobError,
+) -> Result<FailOutcome, sqlx::Error> {
+    let message = truncate(&err.message);
+    if err.retryable && job.attempts < job.max_attempts {
+        let delay = backoff_delay(job.attempts);
+        let requeued = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = 'queued', error = $3, run_at = NOW() + make_interval(secs => $4),
+                locked_by = NULL, locked_until = NULL, updated_at = NOW()
+            WHERE id = $1 AND locked_by = $2 AND status = 'running'
+            "#,
+        )
+        .bind(job.id)
+        .bind(worker_id)
+        .bind(&message)
+        .bind(delay.as_secs_f64())
+        .execute(pool)
+        .await?;
+        return Ok(if requeued.rows_affected() > 0 {
+            FailOutcome::Retrying
+        } else {
+            FailOutcome::LeaseLost
+        });
+    }
+
+    let failed: Option<Job> = sqlx::query_as(
+        r#"
+        UPDATE jobs
+        SET status = 'failed', error = $3, locked_by = NULL, locked_until = NULL,
+            finished_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND locked_by = $2 AND status = 'running'
+        RETURNING *
+        "#,
+    )
+    .bind(job.id)
+    .bind(worker_id)
+    .bind(&message)
+    .fetch_optional(pool)
+    .await?;
+    Ok(failed.map_or(FailOutcome::LeaseLost, FailOutcome::Failed))
+}
+
+pub async fn mark_cancelled(pool: &PgPool, job_id: Uuid) -> Result<Option<Job>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        UPDATE jobs
+        SET status = 'cancelled', locked_by = NULL, locked_until = NULL,
+            finished_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND status IN ('queued', 'running')
+        RETURNING *
+        "#,
+    )
+    .bind(job_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Ask for a job to stop. A queued job is cancelled on the spot; a running
+/// one is flagged and its worker stops it at the next heartbeat. Returns
+/// `None` when the job does not exist or has already finished.
+pub async fn request_cancel(pool: &PgPool, job_id: Uuid) -> Result<Option<Job>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        UPDATE jobs
+        SET cancel_requested = TRUE,
+            status = CASE WHEN status = 'queued' THEN 'cancelled' ELSE status END,
+            finished_at = CASE WHEN status = 'queued' THEN NOW() ELSE finished_at END,
+            updated_at = NOW()
+        WHERE id = $1 AND status IN ('queued', 'running')
+        RETURNING *
+        "#,
+    )
+    .bind(job_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Settle running jobs whose worker stopped renewing the lease (it crashed
+/// or lost the database). They are requeued unless they were cancelled or
+/// have used all their attempts; those are returned for cleanup.
+pub async fn reap_expired(pool: &PgPool) -> Result<Vec<Job>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        UPDATE jobs
+        SET status = CASE
+                WHEN cancel_requested THEN 'cancelled'
+                WHEN attempts >= max_attempts THEN 'failed'
+                ELSE 'queued'
+            END,
+            error = CASE
+                WHEN cancel_requested THEN error
+                ELSE 'Worker stopped responding (lease expired)'
+            END,
+            finished_at = CASE
+                WHEN cancel_requested OR attempts >= max_attempts THEN NOW()
+                ELSE NULL
+            END,
+            locked_by = NULL, locked_until = NULL, updated_at = NOW()
+        WHERE id IN (
+            SELECT id FROM jobs
+            WHERE status = 'running' AND locked_until < NOW()
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map(|jobs: Vec<Job>| jobs.into_iter().filter(Job::is_terminal).collect())
+}
+
+/// Hand a shutting-down worker's jobs back to the queue without counting
+/// the interrupted attempt.
+pub async fn release(pool: &PgPool, worker_id: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE jobs
+        SET status = 'queued', attempts = GREATEST(attempts - 1, 0),
+            locked_by = NULL, locked_until = NULL, updated_at = NOW()
+        WHERE locked_by = $1 AND status = 'running'
+        "#,
+    )
+    .bind(worker_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub(crate) fn truncate(value: &str) -> String {
+    if value.len() <= MAX_ERROR_LEN {
+        return value.to_string();
+    }
+    let mut end = MAX_ERROR_LEN;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...[truncated]", &value[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    #[test]
+    fn kinds_and_statuses_round_trip() {
+        for kind in JobKind::ALL {
+            assert_eq!(JobKind::parse(kind.as_str()), Some(kind));
+            assert_eq!(
+                serde_json::to_value(kind).unwrap(),
+                serde_json::json!(kind.as_str())
+            );
+        }
+        for status in JobStatus::ALL {
+            assert_eq!(JobStatus::parse(status.as_str()), Some(status));
+        }
+        assert!(JobKind::parse("deploy").is_none());
+        assert!(!JobStatus::Running.is_terminal());
+        assert!(JobStatus::Cancelled.is_terminal());
+    }
+
+    #[test]
+    fn api_errors_retry_only_on_server_errors() {
+        let failed: JobError =
+            ApiError::unprocessable("VerificationFailed", "bytecode mismatch").into();
+        assert!(!failed.retryable);
+        assert_eq!(failed.message, "VerificationFailed: bytecode mismatch");
+
+        let db: JobError = ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "DbDown", "x").into();
+        assert!(db.retryable);
+    }
+}
@@ -1,13 +1,30 @@
 #![allow(dead_code, unused)]
 
+pub mod ab_testing;
+pub mod artifact_store;
+pub mod audit_log;
 pub mod backup_handlers;
 pub mod backup_routes;
+pub mod blue_green;
 pub mod cache;
+pub mod canary;
 pub mod disaster_recovery_models;
 pub mod error;
+pub mod event_stream;
+pub mod experiment_stats;
+pub mod governance;
+pub mod notification_dispatcher;
 pub mod notification_handlers;
 pub mod notification_routes;
+pub mod notification_transport;
+pub mod performance_monitor;
 pub mod post_incident_handlers;
 pub mod post_incident_routes;
+pub mod request_xdr;
 pub mod state;
+pub mod transparency_log;
+pub mod trust;
+pub mod trust_service;
+pub mod webhooks;
 pub mod metrics;
+pub mod multisig;
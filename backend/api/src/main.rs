@@ -1,35 +1,91 @@
 #![allow(dead_code, unused)]
 
+mod ab_test_handlers;
+mod ab_testing;
 mod aggregation;
 mod analytics;
+mod artifact_handlers;
+mod artifact_routes;
+mod artifact_store;
+mod audit_log;
+mod batch_verify_handlers;
+mod benchmark_engine;
+mod benchmark_handlers;
+mod benchmark_routes;
+mod benchmark_runner;
+mod blue_green;
 mod breaking_changes;
 mod cache;
+mod canary;
+mod canary_handlers;
+mod compatibility_runner;
 mod compatibility_testing_handlers;
+mod contract_history_handlers;
+mod contract_history_routes;
+mod cost_handlers;
+mod cost_routes;
 mod db_monitoring;
 
 mod activity_feed_handlers;
 mod activity_feed_routes;
 mod custom_metrics_handlers;
 mod dependency;
+mod deployment_handlers;
 mod deprecation_handlers;
+mod disaster_recovery_models;
 mod error;
+mod event_stream;
+mod event_stream_handlers;
+mod event_stream_routes;
+mod experiment_stats;
+mod governance;
+mod governance_handlers;
+mod governance_routes;
 mod handlers;
 mod health;
 pub mod health_monitor;
 #[cfg(test)]
 mod health_tests;
+mod interface_handlers;
+mod interface_routes;
+mod job_handlers;
+mod job_routes;
+mod job_worker;
+mod jobs;
 mod metrics;
 mod metrics_handler;
 mod migration_handlers;
+mod multisig;
+mod multisig_handlers;
+mod multisig_routes;
+mod notification_dispatcher;
+mod notification_handlers;
+mod notification_routes;
+mod notification_transport;
+mod performance_handlers;
+mod performance_monitor;
 mod rate_limit;
 mod release_notes_handlers;
 mod release_notes_routes;
+mod request_xdr;
 pub mod request_tracing;
 mod routes;
+mod scan_handlers;
+mod scan_routes;
+mod scanner_service;
+mod search;
 pub mod signing_handlers;
+mod signing_routes;
 mod state;
+mod transparency_log;
+mod trust;
+mod trust_handlers;
+mod trust_service;
 mod type_safety;
 mod validation;
+mod webhook_handlers;
+mod webhook_routes;
+mod webhooks;
 // mod auth;
 // mod auth_handlers;
 // mod resource_handlers;
@@ -110,9 +166,48 @@ async fn main() -> Result<()> {
     // Check migration versioning state on startup (Issue #252)
     migration_handlers::check_migrations_on_startup(&pool).await;
 
+    // `api worker` only executes queued jobs, so builds and scans can be
+    // scaled separately from the HTTP tier.
+    if std::env::args().nth(1).as_deref() == Some("worker") {
+        job_worker::run_worker(pool, job_worker::WorkerConfig::from_env()).await;
+        return Ok(());
+    }
+
     // Spawn the hourly analytics aggregation background task
     aggregation::spawn_aggregation_task(pool.clone());
 
+    // Deliver queued notifications (email, webhook, Slack, Matrix)
+    notification_dispatcher::spawn_notification_dispatcher(
+        pool.clone(),
+        Arc::new(notification_transport::TransportRegistry::from_env()),
+    );
+
+    // Deliver queued webhook subscription events
+    webhooks::spawn_webhook_dispatcher(pool.clone());
+
+    // Health-check blue/green candidates and switch or roll back on the result
+    blue_green::spawn_deployment_monitor(pool.clone());
+
+    // Detect performance anomalies, evaluate alert configs and roll up trends
+    performance_monitor::spawn_performance_monitor(pool.clone());
+
+    // Sequence new transparency log entries and sign tree heads
+    transparency_log::spawn_transparency_sequencer(pool.clone());
+
+    // Rescore every contract so trust history has regular points
+    trust_service::spawn_trust_score_refresher(pool.clone());
+
+    // Promote or roll back active canaries on their stage metrics
+    canary::spawn_canary_controller(pool.clone());
+
+    // Re-analyse running A/B tests and stop them when a result is reached
+    ab_testing::spawn_ab_test_evaluator(pool.clone());
+
+    // Execute queued jobs in-process unless dedicated workers are deployed
+    if job_worker::embedded_enabled() {
+        job_worker::spawn_job_workers(pool.clone(), job_worker::WorkerConfig::from_env());
+    }
+
     // Create prometheus registry for metrics
     let registry = Registry::new();
     if let Err(e) = crate::metrics::register_all(&registry) {
@@ -126,6 +221,9 @@ async fn main() -> Result<()> {
     // Spawn the background DB and cache monitoring task
     db_monitoring::spawn_db_monitoring_task(pool.clone(), state.cache.clone());
 
+    // Fan registry events out to SSE / WebSocket clients via LISTEN/NOTIFY
+    event_stream::spawn_stream_listener(pool.clone(), state.event_hub.clone());
+
     // Warm up the cache
     state.cache.clone().warm_up(pool.clone());
 
@@ -150,7 +248,7 @@ async fn main() -> Result<()> {
 
     let cors = CorsLayer::new()
         .allow_origin(origins)
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::OPTIONS])
         .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
 
     // Build router
@@ -160,7 +258,25 @@ async fn main() -> Result<()> {
         .merge(routes::health_routes())
         .merge(routes::migration_routes())
         .merge(routes::compatibility_dashboard_routes())
+        .merge(routes::deployment_routes())
+        .merge(routes::canary_routes())
+        .merge(routes::ab_test_routes())
+        .merge(routes::performance_routes())
+        .merge(routes::trust_routes())
+        .merge(signing_routes::signing_routes())
+        .merge(contract_history_routes::contract_history_routes())
+        .merge(governance_routes::governance_routes())
+        .merge(multisig_routes::multisig_routes())
         .merge(release_notes_routes::release_notes_routes())
+        .merge(cost_routes::cost_routes())
+        .merge(interface_routes::interface_routes())
+        .merge(scan_routes::scan_routes())
+        .merge(benchmark_routes::benchmark_routes())
+        .merge(notification_routes::notification_routes())
+        .merge(webhook_routes::webhook_routes())
+        .merge(event_stream_routes::event_stream_routes())
+        .merge(job_routes::job_routes())
+        .merge(artifact_routes::artifact_routes())
         .nest("/api", activity_feed_routes::routes())
         .fallback(handlers::route_not_found)
         .layer(middleware::from_fn(request_tracing::tracing_middleware))
@@ -199,6 +199,15 @@ pub static AB_TEST_CONVERSIONS: Lazy<IntCounterVec> = counter_vec!(
     &["test_id", "variant"]
 );
 
+// ── Performance monitoring ──────────────────────────────────────────────────
+pub static PERFORMANCE_ANOMALIES_OPEN: Lazy<IntGauge> =
+    gauge!("performance_anomalies_open", "Unresolved performance anomalies");
+pub static PERFORMANCE_ALERTS_FIRED: Lazy<IntCounterVec> = counter_vec!(
+    "performance_alerts_fired_total",
+    "Performance alerts raised",
+    &["severity"]
+);
+
 // ── Multisig ────────────────────────────────────────────────────────────────
 pub static MULTISIG_PROPOSALS: Lazy<IntCounter> =
     counter!("multisig_proposals_total", "Multisig proposals created");
@@ -294,6 +303,8 @@ pub fn register_all(r: &Registry) -> prometheus::Result<()> {
     r.register(Box::new(AB_TESTS_ACTIVE.clone()))?;
     r.register(Box::new(AB_TEST_IMPRESSIONS.clone()))?;
     r.register(Box::new(AB_TEST_CONVERSIONS.clone()))?;
+    r.register(Box::new(PERFORMANCE_ANOMALIES_OPEN.clone()))?;
+    r.register(Box::new(PERFORMANCE_ALERTS_FIRED.clone()))?;
     r.register(Box::new(MULTISIG_PROPOSALS.clone()))?;
     r.register(Box::new(MULTISIG_SIGNATURES.clone()))?;
     r.register(Box::new(MULTISIG_EXECUTIONS.clone()))?;
@@ -36,6 +36,10 @@ mod tests {
             cache: Arc::new(CacheLayer::new(CacheConfig::default())),
             registry,
             is_shutting_down: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            event_hub: Arc::new(crate::event_stream::EventHub::new()),
+            artifacts: Arc::new(crate::artifact_store::LocalArtifactStore::new(
+                std::env::temp_dir().join("registry-test-artifacts"),
+            )),
         }
     }
 
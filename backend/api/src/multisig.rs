@@ -0,0 +1,607 @@
+//! Stellar transaction envelopes behind multisig deploy proposals
+//!
+//! A deploy proposal carries an unsigned `TransactionEnvelope` whose single
+//! `InvokeHostFunction` operation uploads the proposal's WASM, creates the
+//! proposed contract from it, or calls that contract's `upgrade` with it.
+//! Signers sign the transaction hash for the proposal's network and submit
+//! the resulting `DecoratedSignature`; each is checked against the signer's
+//! Ed25519 key before it counts towards the policy threshold. Execution
+//! re-checks the collected signatures and threshold under the current policy,
+//! then attaches them to produce the envelope to submit.
+
+use std::str::FromStr;
+
+use ed25519_dalek::{Signature as Ed25519Signature, Verifier, VerifyingKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use shared::{DeployOperation, MultisigPolicy, Network};
+use stellar_xdr::curr::{
+    ContractExecutable, DecoratedSignature, Hash, HashIdPreimage, HashIdPreimageContractId,
+    HostFunction, Limits, OperationBody, PublicKey, ScAddress, ScVal, Transaction,
+    TransactionEnvelope, TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
+    TransactionV1Envelope, WriteXdr,
+};
+
+use crate::request_xdr;
+
+pub const MAINNET_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+pub const TESTNET_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+pub const FUTURENET_PASSPHRASE: &str = "Test SDF Future Network ; October 2022";
+/// Most signatures a Stellar transaction envelope can carry
+pub const MAX_SIGNATURES: usize = 20;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum EnvelopeError {
+    #[error("transaction_xdr is not a base64 TransactionEnvelope: {0}")]
+    Decode(String),
+    #[error("only unsigned v1 transaction envelopes can be proposed")]
+    NotUnsignedV1,
+    #[error("the transaction must contain exactly one InvokeHostFunction operation")]
+    NotHostFunction,
+    #[error("a {operation:?} proposal needs a {expected} host function")]
+    OperationMismatch {
+        operation: DeployOperation,
+        expected: &'static str,
+    },
+    #[error("the transaction installs or references WASM {found}, not the proposed {expected}")]
+    WasmHashMismatch { expected: String, found: String },
+    #[error("the transaction targets contract {found}, not the proposed {expected}")]
+    ContractMismatch { expected: String, found: String },
+    #[error("'{0}' is not a Stellar account address")]
+    InvalidSigner(String),
+    #[error("signature_data is not a base64 DecoratedSignature: {0}")]
+    InvalidSignatureEncoding(String),
+    #[error("the signature hint does not belong to {0}")]
+    HintMismatch(String),
+    #[error("the signature does not verify for {0} over this transaction")]
+    InvalidSignature(String),
+    #[error("at most {MAX_SIGNATURES} signatures fit in a transaction envelope")]
+    TooManySignatures,
+}
+
+pub fn network_passphrase(network: &Network) -> &'static str {
+    match network {
+        Network::Mainnet => MAINNET_PASSPHRASE,
+        Network::Testnet => TESTNET_PASSPHRASE,
+        Network::Futurenet => FUTURENET_PASSPHRASE,
+    }
+}
+
+pub fn network_id(network: &Network) -> [u8; 32] {
+    Sha256::digest(network_passphrase(network).as_bytes()).into()
+}
+
+/// The hash signers sign, which is also the transaction's on-chain id.
+pub fn transaction_hash(tx: &Transaction, network_id: &[u8; 32]) -> [u8; 32] {
+    let payload = TransactionSignaturePayload {
+        network_id: Hash(*network_id),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+    };
+    let bytes = payload
+        .to_xdr(Limits::none())
+        .expect("a decoded transaction re-encodes");
+    Sha256::digest(bytes).into()
+}
+
+/// Address of the contract a `CreateContract` preimage produces on a network.
+fn created_contract_address(host_function: &HostFunction, network_id: &[u8; 32]) -> Option<String> {
+    let HostFunction::CreateContract(args) = host_function else {
+        return None;
+    };
+    let preimage = HashIdPreimage::ContractId(HashIdPreimageContractId {
+        network_id: Hash(*network_id),
+        contract_id_preimage: args.contract_id_preimage.clone(),
+    });
+    let bytes = preimage.to_xdr(Limits::none()).ok()?;
+    let id: [u8; 32] = Sha256::digest(bytes).into();
+    Some(ScAddress::Contract(Hash(id)).to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct UnsignedEnvelope {
+    pub tx: Transaction,
+    pub hash: [u8; 32],
+}
+
+impl UnsignedEnvelope {
+    pub fn hash_hex(&self) -> String {
+        hex::encode(self.hash)
+    }
+}
+
+/// Decode a proposal's envelope and check that it does what the proposal
+/// says: `operation` on `wasm_hash` for `contract_id`.
+pub fn parse_unsigned(
+    transaction_xdr: &str,
+    network: &Network,
+    operation: DeployOperation,
+    wasm_hash: &str,
+    contract_id: &str,
+) -> Result<UnsignedEnvelope, EnvelopeError> {
+    let envelope = request_xdr::from_base64::<TransactionEnvelope>(transaction_xdr.trim())
+        .map_err(|e| EnvelopeError::Decode(e.to_string()))?;
+    let TransactionEnvelope::Tx(TransactionV1Envelope { tx, signatures }) = envelope else {
+        return Err(EnvelopeError::NotUnsignedV1);
+    };
+    if !signatures.is_empty() {
+        return Err(EnvelopeError::NotUnsignedV1);
+    }
+    let host_function = match tx.operations.as_slice() {
+        [op] => match &op.body {
+            OperationBody::InvokeHostFunction(invoke) => &invoke.host_function,
+            _ => return Err(EnvelopeError::NotHostFunction),
+        },
+        _ => return Err(EnvelopeError::NotHostFunction),
+    };
+
+    let expected_hash = wasm_hash.trim().to_ascii_lowercase();
+    let wasm_mismatch = |found: String| EnvelopeError::WasmHashMismatch {
+        expected: expected_hash.clone(),
+        found,
+    };
+    let contract_mismatch = |found: String| EnvelopeError::ContractMismatch {
+        expected: contract_id.to_string(),
+        found,
+    };
+    let network_id = network_id(network);
+
+    match (operation, host_function) {
+        (DeployOperation::Upload, HostFunction::UploadContractWasm(wasm)) => {
+            let found = hex::encode(Sha256::digest(wasm.as_slice()));
+            if found != expected_hash {
+                return Err(wasm_mismatch(found));
+            }
+        }
+        (DeployOperation::Create, HostFunction::CreateContract(args)) => {
+            let ContractExecutable::Wasm(Hash(found)) = &args.executable else {
+                return Err(wasm_mismatch("a built-in token".into()));
+            };
+            if hex::encode(found) != expected_hash {
+                return Err(wasm_mismatch(hex::encode(found)));
+            }
+            let address = created_contract_address(host_function, &network_id)
+                .ok_or_else(|| contract_mismatch("an unknown address".into()))?;
+            if address != contract_id {
+                return Err(contract_mismatch(address));
+            }
+        }
+        (DeployOperation::Upgrade, HostFunction::InvokeContract(args)) => {
+            let target = args.contract_address.to_string();
+            if target != contract_id {
+                return Err(contract_mismatch(target));
+            }
+            let upgrade_to = match (args.function_name.0.as_slice(), args.args.as_slice()) {
+                (b"upgrade", [ScVal::Bytes(bytes)]) => hex::encode(bytes.as_slice()),
+                _ => {
+                    return Err(EnvelopeError::OperationMismatch {
+                        operation,
+                        expected: "upgrade(wasm_hash) contract call",
+                    })
+                }
+            };
+            if upgrade_to != expected_hash {
+                return Err(wasm_mismatch(upgrade_to));
+            }
+        }
+        (operation, _) => {
+            return Err(EnvelopeError::OperationMismatch {
+                operation,
+                expected: match operation {
+                    DeployOperation::Upload => "UploadContractWasm",
+                    DeployOperation::Create => "CreateContract",
+                    DeployOperation::Upgrade => "InvokeContract",
+                },
+            })
+        }
+    }
+
+    let hash = transaction_hash(&tx, &network_id);
+    Ok(UnsignedEnvelope { tx, hash })
+}
+
+/// Decode `signature_data` and check it is `signer`'s signature over `hash`.
+pub fn verify_signature(
+    hash: &[u8; 32],
+    signer: &str,
+    signature_data: &str,
+) -> Result<DecoratedSignature, EnvelopeError> {
+    let PublicKey::PublicKeyTypeEd25519(key) = PublicKey::from_str(signer.trim())
+        .map_err(|_| EnvelopeError::InvalidSigner(signer.to_string()))?;
+    let verifying_key = VerifyingKey::from_bytes(&key.0)
+        .map_err(|_| EnvelopeError::InvalidSigner(signer.into()))?;
+
+    let decorated = request_xdr::from_base64::<DecoratedSignature>(signature_data.trim())
+        .map_err(|e| EnvelopeError::InvalidSignatureEncoding(e.to_string()))?;
+    if decorated.hint.0 != key.0[28..] {
+        return Err(EnvelopeError::HintMismatch(signer.to_string()));
+    }
+    let signature = Ed25519Signature::from_slice(decorated.signature.0.as_slice())
+        .map_err(|_| EnvelopeError::InvalidSignature(signer.to_string()))?;
+    verifying_key
+        .verify(hash, &signature)
+        .map_err(|_| EnvelopeError::InvalidSignature(signer.to_string()))?;
+    Ok(decorated)
+}
+
+/// Re-check stored `(signer, signature_data)` pairs against `hash` under the
+/// policy as it stands now, returning the signatures that still count and
+/// their total weight. Signers since removed from the policy are dropped; a
+/// stored signature that no longer verifies is an error.
+pub fn recheck_signatures<'a>(
+    hash: &[u8; 32],
+    policy: &MultisigPolicy,
+    stored: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<(Vec<DecoratedSignature>, i64), EnvelopeError> {
+    let mut signatures = Vec::new();
+    let mut weight = 0i64;
+    for (signer, signature_data) in stored {
+        let Some(signer_weight) = policy.weight_of(signer) else {
+            continue;
+        };
+        signatures.push(verify_signature(hash, signer, signature_data)?);
+        weight += i64::from(signer_weight);
+    }
+    Ok((signatures, weight))
+}
+
+/// The envelope with `signatures` attached, as base64 XDR.
+pub fn assemble(
+    tx: Transaction,
+    signatures: Vec<DecoratedSignature>,
+) -> Result<String, EnvelopeError> {
+    let signatures = signatures
+        .try_into()
+        .map_err(|_| EnvelopeError::TooManySignatures)?;
+    TransactionEnvelope::Tx(TransactionV1Envelope { tx, signatures })
+        .to_xdr_base64(Limits::none())
+        .map_err(|e| EnvelopeError::Decode(e.to_string()))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTransactionResult {
+    pub status: String,
+    pub hash: String,
+    #[serde(default)]
+    pub error_result_xdr: Option<String>,
+}
+
+impl SendTransactionResult {
+    /// `PENDING` and `DUPLICATE` both mean the network has the transaction.
+    pub fn accepted(&self) -> bool {
+        matches!(self.status.as_str(), "PENDING" | "DUPLICATE")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<SendTransactionResult>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+/// Environment variable holding the RPC endpoint for `network`; the same
+/// names the indexer reads.
+pub fn rpc_url_var(network: &Network) -> &'static str {
+    match network {
+        Network::Mainnet => "STELLAR_RPC_MAINNET",
+        Network::Testnet => "STELLAR_RPC_TESTNET",
+        Network::Futurenet => "STELLAR_RPC_FUTURENET",
+    }
+}
+
+/// The RPC endpoint configured for `network`, if any. There is no shared
+/// fallback: an envelope is only ever sent to a node of its own network.
+pub fn rpc_url(network: &Network) -> Option<String> {
+    std::env::var(rpc_url_var(network))
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+}
+
+/// Submit a signed envelope with `sendTransaction`.
+pub async fn submit(rpc_url: &str, envelope_xdr: &str) -> Result<SendTransactionResult, String> {
+    let response: RpcResponse = reqwest::Client::new()
+        .post(rpc_url)
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendTransaction",
+            "params": { "transaction": envelope_xdr },
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("sendTransaction request failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("sendTransaction returned invalid JSON: {}", e))?;
+    if let Some(error) = response.error {
+        return Err(format!("sendTransaction failed: {}", error.message));
+    }
+    response
+        .result
+        .ok_or_else(|| "sendTransaction returned no result".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use stellar_xdr::curr::{
+        ContractIdPreimage, ContractIdPreimageFromAddress, CreateContractArgs, InvokeContractArgs,
+        InvokeHostFunctionOp, Memo, MuxedAccount, Operation, Preconditions, ReadXdr, ScBytes,
+        ScSymbol, SequenceNumber, SignatureHint, TransactionExt, Uint256, VecM,
+    };
+
+    const WASM: &[u8] = b"\0asm\x01\0\0\0";
+
+    fn account(key: &SigningKey) -> String {
+        PublicKey::PublicKeyTypeEd25519(Uint256(key.verifying_key().to_bytes())).to_string()
+    }
+
+    fn envelope(source: &SigningKey, host_function: HostFunction) -> (Transaction, String) {
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(source.verifying_key().to_bytes())),
+            fee: 100,
+            seq_num: SequenceNumber(7),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                    host_function,
+                    auth: VecM::default(),
+                }),
+            }]
+            .try_into()
+            .unwrap(),
+            ext: TransactionExt::V0,
+        };
+        let xdr = TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx: tx.clone(),
+            signatures: VecM::default(),
+        })
+        .to_xdr_base64(Limits::none())
+        .unwrap();
+        (tx, xdr)
+    }
+
+    fn decorated(key: &SigningKey, hash: &[u8; 32]) -> String {
+        let public = key.verifying_key().to_bytes();
+        DecoratedSignature {
+            hint: SignatureHint(public[28..].try_into().unwrap()),
+            signature: stellar_xdr::curr::Signature(
+                key.sign(hash).to_bytes().to_vec().try_into().unwrap(),
+            ),
+        }
+        .to_xdr_base64(Limits::none())
+        .unwrap()
+    }
+
+    #[test]
+    fn upload_envelopes_must_carry_the_proposed_wasm() {
+        let source = SigningKey::from_bytes(&[1; 32]);
+        let wasm_hash = hex::encode(Sha256::digest(WASM));
+        let (tx, xdr) = envelope(
+            &source,
+            HostFunction::UploadContractWasm(WASM.to_vec().try_into().unwrap()),
+        );
+        let parsed = parse_unsigned(
+            &xdr,
+            &Network::Testnet,
+            DeployOperation::Upload,
+            &wasm_hash,
+            "",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.hash,
+            transaction_hash(&tx, &network_id(&Network::Testnet))
+        );
+        assert_ne!(
+            parsed.hash,
+            transaction_hash(&tx, &network_id(&Network::Mainnet))
+        );
+
+        let err = parse_unsigned(
+            &xdr,
+            &Network::Testnet,
+            DeployOperation::Upload,
+            &"00".repeat(32),
+            "",
+        )
+        .unwrap_err();
+        assert!(matches!(err, EnvelopeError::WasmHashMismatch { .. }));
+        let err = parse_unsigned(
+            &xdr,
+            &Network::Testnet,
+            DeployOperation::Create,
+            &wasm_hash,
+            "",
+        )
+        .unwrap_err();
+        assert!(matches!(err, EnvelopeError::OperationMismatch { .. }));
+    }
+
+    #[test]
+    fn create_and_upgrade_envelopes_must_target_the_proposed_contract() {
+        let source = SigningKey::from_bytes(&[1; 32]);
+        let wasm_hash = [5u8; 32];
+        let create = HostFunction::CreateContract(CreateContractArgs {
+            contract_id_preimage: ContractIdPreimage::Address(ContractIdPreimageFromAddress {
+                address: ScAddress::from_str(&account(&source)).unwrap(),
+                salt: Uint256([9; 32]),
+            }),
+            executable: ContractExecutable::Wasm(Hash(wasm_hash)),
+        });
+        let testnet_address =
+            created_contract_address(&create, &network_id(&Network::Testnet)).unwrap();
+        let (_, xdr) = envelope(&source, create);
+        assert!(parse_unsigned(
+            &xdr,
+            &Network::Testnet,
+            DeployOperation::Create,
+            &hex::encode(wasm_hash),
+            &testnet_address,
+        )
+        .is_ok());
+        // The same preimage yields a different address on another network.
+        assert!(matches!(
+            parse_unsigned(
+                &xdr,
+                &Network::Mainnet,
+                DeployOperation::Create,
+                &hex::encode(wasm_hash),
+                &testnet_address,
+            ),
+            Err(EnvelopeError::ContractMismatch { .. })
+        ));
+
+        let upgrade = |function: &str| {
+            HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address: ScAddress::from_str(&testnet_address).unwrap(),
+                function_name: ScSymbol(function.try_into().unwrap()),
+                args: vec![ScVal::Bytes(ScBytes(
+                    wasm_hash.to_vec().try_into().unwrap(),
+                ))]
+                .try_into()
+                .unwrap(),
+            })
+        };
+        let (_, xdr) = envelope(&source, upgrade("upgrade"));
+        assert!(parse_unsigned(
+            &xdr,
+            &Network::Testnet,
+            DeployOperation::Upgrade,
+            &hex::encode(wasm_hash),
+            &testnet_address,
+        )
+        .is_ok());
+        let (_, xdr) = envelope(&source, upgrade("transfer"));
+        assert!(matches!(
+            parse_unsigned(
+                &xdr,
+                &Network::Testnet,
+                DeployOperation::Upgrade,
+                &hex::encode(wasm_hash),
+                &testnet_address,
+            ),
+            Err(EnvelopeError::OperationMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn signatures_are_checked_against_the_signer_and_hash() {
+        let signer = SigningKey::from_bytes(&[2; 32]);
+        let other = SigningKey::from_bytes(&[3; 32]);
+        let (tx, xdr) = envelope(
+            &signer,
+            HostFunction::UploadContractWasm(WASM.to_vec().try_into().unwrap()),
+        );
+        let parsed = parse_unsigned(
+            &xdr,
+            &Network::Futurenet,
+            DeployOperation::Upload,
+            &hex::encode(Sha256::digest(WASM)),
+            "",
+        )
+        .unwrap();
+
+        let good = verify_signature(
+            &parsed.hash,
+            &account(&signer),
+            &decorated(&signer, &parsed.hash),
+        )
+        .unwrap();
+        assert_eq!(
+            verify_signature(
+                &parsed.hash,
+                &account(&signer),
+                &decorated(&other, &parsed.hash)
+            ),
+            Err(EnvelopeError::HintMismatch(account(&signer)))
+        );
+        assert_eq!(
+            verify_signature(
+                &[0; 32],
+                &account(&signer),
+                &decorated(&signer, &parsed.hash)
+            ),
+            Err(EnvelopeError::InvalidSignature(account(&signer)))
+        );
+
+        let signed = assemble(tx, vec![good]).unwrap();
+        let TransactionEnvelope::Tx(envelope) =
+            TransactionEnvelope::from_xdr_base64(&signed, Limits::none()).unwrap()
+        else {
+            panic!("expected a v1 envelope");
+        };
+        assert_eq!(envelope.signatures.len(), 1);
+        // A signed envelope cannot be proposed again.
+        assert_eq!(
+            parse_unsigned(
+                &signed,
+                &Network::Futurenet,
+                DeployOperation::Upload,
+                &hex::encode(Sha256::digest(WASM)),
+                "",
+            )
+            .unwrap_err(),
+            EnvelopeError::NotUnsignedV1
+        );
+    }
+
+    #[test]
+    fn rechecked_signatures_follow_the_current_policy() {
+        let first = SigningKey::from_bytes(&[2; 32]);
+        let second = SigningKey::from_bytes(&[3; 32]);
+        let hash = [7u8; 32];
+        let mut policy = MultisigPolicy {
+            id: uuid::Uuid::nil(),
+            name: "deployers".into(),
+            threshold: 3,
+            signer_addresses: vec![account(&first), account(&second)],
+            signer_weights: Some(vec![2, 1]),
+            expiry_seconds: 3600,
+            created_by: "admin".into(),
+            created_at: chrono::Utc::now(),
+        };
+        let stored = [
+            (account(&first), decorated(&first, &hash)),
+            (account(&second), decorated(&second, &hash)),
+        ];
+        let pairs = || stored.iter().map(|(s, d)| (s.as_str(), d.as_str()));
+
+        let (signatures, weight) = recheck_signatures(&hash, &policy, pairs()).unwrap();
+        assert_eq!((signatures.len(), weight), (2, 3));
+
+        // A signer dropped from the policy no longer counts.
+        policy.signer_addresses.truncate(1);
+        policy.signer_weights = Some(vec![2]);
+        let (signatures, weight) = recheck_signatures(&hash, &policy, pairs()).unwrap();
+        assert_eq!((signatures.len(), weight), (1, 2));
+
+        // A stored signature over anything else is rejected outright.
+        assert_eq!(
+            recheck_signatures(&[0; 32], &policy, pairs()).unwrap_err(),
+            EnvelopeError::InvalidSignature(account(&first))
+        );
+    }
+
+    #[test]
+    fn rpc_url_is_chosen_per_network() {
+        std::env::set_var("STELLAR_RPC_FUTURENET", "https://rpc-futurenet.example");
+        std::env::set_var("STELLAR_RPC_TESTNET", "  ");
+        assert_eq!(
+            rpc_url(&Network::Futurenet).as_deref(),
+            Some("https://rpc-futurenet.example")
+        );
+        // A blank endpoint counts as unconfigured, and another network's
+        // endpoint is never borrowed.
+        assert_eq!(rpc_url(&Network::Testnet), None);
+        assert_eq!(rpc_url_var(&Network::Mainnet), "STELLAR_RPC_MAINNET");
+    }
+}
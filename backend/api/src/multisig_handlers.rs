@@ -7,19 +7,23 @@ use axum::{
     response::IntoResponse,
     Json,
 };
+use base64::Engine as _;
 use chrono::Utc;
+use ed25519_dalek::Verifier as _;
 use serde::Deserialize;
 use shared::{
-    CreatePolicyRequest, CreateProposalRequest, DeployProposal, MultisigPolicy, ProposalSignature,
-    ProposalStatus, ProposalWithSignatures, SignProposalRequest,
+    AuditActionType, CreatePolicyRequest, CreateProposalRequest, DeployProposal,
+    ExecuteProposalRequest, MultisigPolicy, ProposalSignature, ProposalStatus,
+    ProposalWithSignatures, SignProposalRequest,
 };
 use uuid::Uuid;
 
 use crate::{
+    audit_log,
     error::{ApiError, ApiResult},
     handlers::db_internal_error,
+    multisig::{self, EnvelopeError, UnsignedEnvelope},
     signing_handlers::create_signing_message,
-    resource_tracking::ResourceUsage,
     state::AppState,
 };
 
@@ -49,6 +53,73 @@ async fn fetch_proposal(state: &AppState, id: Uuid) -> ApiResult<DeployProposal>
         })
 }
 
+async fn fetch_policy(state: &AppState, id: Uuid) -> ApiResult<MultisigPolicy> {
+    sqlx::query_as("SELECT * FROM multisig_policies WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => {
+                ApiError::not_found("PolicyNotFound", format!("No policy found with ID: {}", id))
+            }
+            _ => db_internal_error("fetch policy", err),
+        })
+}
+
+fn envelope_error(err: EnvelopeError) -> ApiError {
+    match err {
+        err @ (EnvelopeError::HintMismatch(_) | EnvelopeError::InvalidSignature(_)) => {
+            ApiError::bad_request("InvalidSignature", err.to_string())
+        }
+        err @ EnvelopeError::TooManySignatures => {
+            ApiError::unprocessable("TooManySignatures", err.to_string())
+        }
+        err => ApiError::bad_request("InvalidTransactionEnvelope", err.to_string()),
+    }
+}
+
+/// 409 for an execute that lost the race to another call.
+fn already_settled(status: ProposalStatus) -> ApiError {
+    ApiError::conflict(
+        "ProposalAlreadySettled",
+        format!(
+            "Proposal is no longer 'approved' (now '{}'); it was settled by another request",
+            status
+        ),
+    )
+}
+
+/// Decode and re-check the envelope stored on a proposal.
+fn proposal_envelope(proposal: &DeployProposal) -> ApiResult<UnsignedEnvelope> {
+    let (Some(operation), Some(transaction_xdr)) =
+        (proposal.operation, proposal.transaction_xdr.as_deref())
+    else {
+        return Err(ApiError::unprocessable(
+            "MissingTransactionEnvelope",
+            "This proposal predates transaction envelopes; create a new proposal",
+        ));
+    };
+    multisig::parse_unsigned(
+        transaction_xdr,
+        &proposal.network,
+        operation,
+        &proposal.wasm_hash,
+        &proposal.contract_id,
+    )
+    .map_err(envelope_error)
+}
+
+/// Sum of the weights recorded with a proposal's signatures.
+async fn collected_weight(state: &AppState, proposal_id: Uuid) -> ApiResult<i64> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(weight), 0)::BIGINT FROM proposal_signatures WHERE proposal_id = $1",
+    )
+    .bind(proposal_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|err| db_internal_error("sum signature weights", err))
+}
+
 /// Transition an expired proposal to `expired` status in the DB.
 async fn expire_proposal(state: &AppState, id: Uuid) -> ApiResult<()> {
     sqlx::query("UPDATE deploy_proposals SET status = 'expired', updated_at = NOW() WHERE id = $1")
@@ -83,13 +154,34 @@ pub async fn create_policy(
             "signer_addresses must not be empty",
         ));
     }
-    if req.threshold as usize > req.signer_addresses.len() {
+    if let Some(signer) = req
+        .signer_addresses
+        .iter()
+        .find(|s| s.parse::<stellar_xdr::curr::PublicKey>().is_err())
+    {
+        return Err(ApiError::bad_request(
+            "InvalidSigners",
+            format!("'{}' is not a Stellar account address", signer),
+        ));
+    }
+    let total_weight: i64 = match &req.signer_weights {
+        Some(weights) => {
+            if weights.len() != req.signer_addresses.len() || weights.iter().any(|w| *w < 1) {
+                return Err(ApiError::bad_request(
+                    "InvalidSignerWeights",
+                    "signer_weights must give a positive weight for each signer",
+                ));
+            }
+            weights.iter().map(|w| i64::from(*w)).sum()
+        }
+        None => req.signer_addresses.len() as i64,
+    };
+    if i64::from(req.threshold) > total_weight {
         return Err(ApiError::bad_request(
             "ThresholdExceedsSigners",
             format!(
-                "threshold ({}) cannot exceed the number of signers ({})",
-                req.threshold,
-                req.signer_addresses.len()
+                "threshold ({}) cannot exceed the total signer weight ({})",
+                req.threshold, total_weight
             ),
         ));
     }
@@ -103,13 +195,15 @@ pub async fn create_policy(
     let expiry_seconds = req.expiry_seconds.unwrap_or(86_400);
 
     let policy: MultisigPolicy = sqlx::query_as(
-        "INSERT INTO multisig_policies (name, threshold, signer_addresses, expiry_seconds, created_by)
-         VALUES ($1, $2, $3, $4, $5)
+        "INSERT INTO multisig_policies
+            (name, threshold, signer_addresses, signer_weights, expiry_seconds, created_by)
+         VALUES ($1, $2, $3, $4, $5, $6)
          RETURNING *",
     )
     .bind(&req.name)
     .bind(req.threshold)
     .bind(&req.signer_addresses)
+    .bind(&req.signer_weights)
     .bind(expiry_seconds)
     .bind(&req.created_by)
     .fetch_one(&state.db)
@@ -125,8 +219,10 @@ pub async fn create_policy(
 // POST /api/contracts/deploy-proposal
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Create an unsigned deployment proposal. The proposal will stay `pending`
-/// until enough signers have signed it (threshold reached → `approved`).
+/// Create a deployment proposal around an unsigned transaction envelope. The
+/// envelope must perform `operation` on `wasm_hash` / `contract_id` on
+/// `network`. The proposal stays `pending` until the signatures collected
+/// reach the policy's threshold weight (→ `approved`).
 pub async fn create_proposal(
     State(state): State<AppState>,
     payload: Result<Json<CreateProposalRequest>, axum::extract::rejection::JsonRejection>,
@@ -153,35 +249,38 @@ pub async fn create_proposal(
         ));
     }
 
+    let envelope = multisig::parse_unsigned(
+        &req.transaction_xdr,
+        &req.network,
+        req.operation,
+        &req.wasm_hash,
+        &req.contract_id,
+    )
+    .map_err(envelope_error)?;
+
     // Look up the policy to compute expires_at
-    let policy: MultisigPolicy = sqlx::query_as("SELECT * FROM multisig_policies WHERE id = $1")
-        .bind(req.policy_id)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|err| match err {
-            sqlx::Error::RowNotFound => ApiError::not_found(
-                "PolicyNotFound",
-                format!("No policy found with ID: {}", req.policy_id),
-            ),
-            _ => db_internal_error("fetch policy for proposal", err),
-        })?;
+    let policy = fetch_policy(&state, req.policy_id).await?;
 
     let expires_at = Utc::now() + chrono::Duration::seconds(policy.expiry_seconds as i64);
 
     let proposal: DeployProposal = sqlx::query_as(
         "INSERT INTO deploy_proposals
-            (contract_name, contract_id, wasm_hash, network, description, policy_id, expires_at, proposer)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            (contract_name, contract_id, wasm_hash, network, description, policy_id, expires_at,
+             proposer, operation, transaction_xdr, transaction_hash)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
          RETURNING *",
     )
     .bind(&req.contract_name)
     .bind(&req.contract_id)
-    .bind(&req.wasm_hash)
+    .bind(req.wasm_hash.trim().to_ascii_lowercase())
     .bind(&req.network)
     .bind(&req.description)
     .bind(req.policy_id)
     .bind(expires_at)
     .bind(&req.proposer)
+    .bind(req.operation)
+    .bind(req.transaction_xdr.trim())
+    .bind(envelope.hash_hex())
     .fetch_one(&state.db)
     .await
     .map_err(|err| db_internal_error("create deploy proposal", err))?;
@@ -189,6 +288,7 @@ pub async fn create_proposal(
     tracing::info!(
         proposal_id = %proposal.id,
         contract_id = %proposal.contract_id,
+        tx_hash     = %envelope.hash_hex(),
         threshold   = policy.threshold,
         expires_at  = %proposal.expires_at,
         "deployment proposal created"
@@ -205,9 +305,11 @@ pub async fn create_proposal(
 /// - Proposal exists and is still `pending`
 /// - Proposal has not expired
 /// - Signer is in the policy's signer list
+/// - `signature_data` is the signer's decorated signature over the
+///   proposal's transaction hash
 /// - Signer has not already signed
 ///
-/// If the threshold is met after this signature the proposal moves to `approved`.
+/// If the collected weight reaches the threshold the proposal moves to `approved`.
 pub async fn sign_proposal(
     State(state): State<AppState>,
     Path(proposal_id): Path<Uuid>,
@@ -241,31 +343,32 @@ pub async fn sign_proposal(
     }
 
     // Fetch the policy to validate the signer
-    let policy: MultisigPolicy = sqlx::query_as("SELECT * FROM multisig_policies WHERE id = $1")
-        .bind(proposal.policy_id)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|err| db_internal_error("fetch policy for signing", err))?;
+    let policy = fetch_policy(&state, proposal.policy_id).await?;
 
-    if !policy.signer_addresses.contains(&req.signer_address) {
-        return Err(ApiError::bad_request(
+    let weight = policy.weight_of(&req.signer_address).ok_or_else(|| {
+        ApiError::bad_request(
             "UnauthorizedSigner",
             format!(
                 "'{}' is not an authorized signer for this proposal",
                 req.signer_address
             ),
-        ));
-    }
+        )
+    })?;
+
+    let envelope = proposal_envelope(&proposal)?;
+    multisig::verify_signature(&envelope.hash, &req.signer_address, &req.signature_data)
+        .map_err(envelope_error)?;
 
     // Insert signature (UNIQUE constraint on (proposal_id, signer_address) handles duplicates)
     let signature: ProposalSignature = sqlx::query_as(
-        "INSERT INTO proposal_signatures (proposal_id, signer_address, signature_data)
-         VALUES ($1, $2, $3)
+        "INSERT INTO proposal_signatures (proposal_id, signer_address, signature_data, weight)
+         VALUES ($1, $2, $3, $4)
          RETURNING *",
     )
     .bind(proposal_id)
     .bind(&req.signer_address)
-    .bind(&req.signature_data)
+    .bind(req.signature_data.trim())
+    .bind(weight)
     .fetch_one(&state.db)
     .await
     .map_err(|err| match err {
@@ -281,16 +384,11 @@ pub async fn sign_proposal(
         _ => db_internal_error("insert proposal signature", err),
     })?;
 
-    // Count total signatures so far
-    let sig_count: i64 =
-        sqlx::query_scalar("SELECT COUNT(*) FROM proposal_signatures WHERE proposal_id = $1")
-            .bind(proposal_id)
-            .fetch_one(&state.db)
-            .await
-            .map_err(|err| db_internal_error("count signatures", err))?;
+    // Weight collected so far
+    let weight_collected = collected_weight(&state, proposal_id).await?;
 
     // Promote to approved if threshold met
-    if sig_count >= policy.threshold as i64 {
+    if weight_collected >= policy.threshold as i64 {
         sqlx::query(
             "UPDATE deploy_proposals SET status = 'approved', updated_at = NOW() WHERE id = $1",
         )
@@ -302,20 +400,20 @@ pub async fn sign_proposal(
 
         tracing::info!(
             proposal_id = %proposal_id,
-            sig_count   = sig_count,
+            weight      = weight_collected,
             threshold   = policy.threshold,
             "proposal threshold reached — status: approved"
         );
     }
 
-    let signatures_needed = (policy.threshold as i64 - sig_count).max(0) as i32;
+    let signatures_needed = (policy.threshold as i64 - weight_collected).max(0) as i32;
 
     Ok((
         StatusCode::CREATED,
         Json(serde_json::json!({
             "signature": signature,
             "proposal_status": proposal.status.to_string(),
-            "signatures_collected": sig_count,
+            "signatures_collected": weight_collected,
             "signatures_needed": signatures_needed,
             "threshold_met": signatures_needed == 0,
         })),
@@ -327,11 +425,20 @@ pub async fn sign_proposal(
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Execute an approved deployment proposal. The proposal must be in `approved`
-/// status and not expired. Once executed the status transitions to `executed`.
+/// status and not expired. Its collected signatures are re-verified, their
+/// weight re-checked against the current policy threshold, and attached to the
+/// envelope, which is returned ready for submission or, with
+/// `{"submit": true}`, submitted to the RPC endpoint configured for the
+/// proposal's network (`STELLAR_RPC_<NETWORK>`). The proposal row is locked
+/// from submission until it is marked `executed`, so concurrent calls cannot
+/// both submit; the loser gets 409. The transaction hash is kept on the
+/// proposal and in the contract's audit log.
 pub async fn execute_proposal(
     State(state): State<AppState>,
     Path(proposal_id): Path<Uuid>,
+    payload: Option<Json<ExecuteProposalRequest>>,
 ) -> ApiResult<Json<serde_json::Value>> {
+    let req = payload.map(|Json(req)| req).unwrap_or_default();
     let proposal = fetch_proposal(&state, proposal_id).await?;
 
     // Check expiry even for approved proposals
@@ -346,6 +453,9 @@ pub async fn execute_proposal(
         ));
     }
 
+    if proposal.status == ProposalStatus::Executed {
+        return Err(already_settled(proposal.status));
+    }
     if proposal.status != ProposalStatus::Approved {
         return Err(ApiError::bad_request(
             "ProposalNotApproved",
@@ -359,29 +469,34 @@ pub async fn execute_proposal(
     // Enforce that the target contract wasm_hash has a valid Ed25519 signature
     // recorded in contract_versions before allowing deployment to proceed.
     // This protects against deploying unsigned or tampered binaries.
-    let (contract_id_str, version, signature, publisher_key): (String, String, Option<String>, Option<String>) =
-        sqlx::query_as(
-            r#"
-            SELECT c.contract_id, cv.version, cv.signature, cv.publisher_key
+    let (contract_uuid, contract_id_str, version, signature, publisher_key): (
+        Uuid,
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+    ) = sqlx::query_as(
+        r#"
+            SELECT c.id, c.contract_id, cv.version, cv.signature, cv.publisher_key
             FROM contracts c
             JOIN contract_versions cv ON cv.contract_id = c.id
-            WHERE c.id = $1
+            WHERE c.contract_id = $1
               AND cv.wasm_hash = $2
             ORDER BY cv.created_at DESC
             LIMIT 1
             "#,
+    )
+    .bind(&proposal.contract_id)
+    .bind(&proposal.wasm_hash)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|err| db_internal_error("lookup contract version for deployment", err))?
+    .ok_or_else(|| {
+        ApiError::bad_request(
+            "UnsignedDeployment",
+            "No signed contract version found for this deployment's wasm_hash",
         )
-        .bind(proposal.contract_id)
-        .bind(&proposal.wasm_hash)
-        .fetch_optional(&state.db)
-        .await
-        .map_err(|err| db_internal_error("lookup contract version for deployment", err))?
-        .ok_or_else(|| {
-            ApiError::bad_request(
-                "UnsignedDeployment",
-                "No signed contract version found for this deployment's wasm_hash",
-            )
-        })?;
+    })?;
 
     let signature = signature.ok_or_else(|| {
         ApiError::bad_request(
@@ -420,8 +535,7 @@ pub async fn execute_proposal(
         .map_err(|_| ApiError::internal("Stored signature must decode to 64 bytes"))?;
     let signature = ed25519_dalek::Signature::from_bytes(&sig_array);
 
-    let message =
-        create_signing_message(&proposal.wasm_hash, &contract_id_str, &version);
+    let message = create_signing_message(&proposal.wasm_hash, &contract_id_str, &version);
 
     if verifying_key.verify(&message, &signature).is_err() {
         tracing::warn!(
@@ -444,45 +558,192 @@ pub async fn execute_proposal(
         "deployment signature verified"
     );
 
-    // Mark as executed
-    sqlx::query(
+    // Attach every collected signature, re-checked against the envelope and
+    // the policy as it stands now: rows or weights may have changed since the
+    // proposal was approved.
+    let envelope = proposal_envelope(&proposal)?;
+    let policy = fetch_policy(&state, proposal.policy_id).await?;
+    let signatures: Vec<ProposalSignature> = sqlx::query_as(
+        "SELECT * FROM proposal_signatures WHERE proposal_id = $1 ORDER BY signed_at ASC",
+    )
+    .bind(proposal_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list proposal signatures", err))?;
+    let (decorated, weight) = multisig::recheck_signatures(
+        &envelope.hash,
+        &policy,
+        signatures.iter().map(|sig| {
+            (
+                sig.signer_address.as_str(),
+                sig.signature_data.as_deref().unwrap_or_default(),
+            )
+        }),
+    )
+    .map_err(|err| {
+        tracing::warn!(proposal_id = %proposal_id, error = %err, "stored proposal signature failed re-verification");
+        ApiError::new(
+            StatusCode::CONFLICT,
+            "StoredSignatureInvalid",
+            format!("A collected signature no longer verifies: {}", err),
+        )
+    })?;
+    if weight < i64::from(policy.threshold) {
+        return Err(ApiError::unprocessable(
+            "ThresholdNotMet",
+            format!(
+                "Collected signatures carry weight {} under the current policy; {} is required",
+                weight, policy.threshold
+            ),
+        ));
+    }
+    let tx_hash = envelope.hash_hex();
+    let signed_envelope = multisig::assemble(envelope.tx, decorated).map_err(envelope_error)?;
+
+    let rpc_url = if req.submit {
+        Some(multisig::rpc_url(&proposal.network).ok_or_else(|| {
+            ApiError::unprocessable(
+                "SubmissionUnavailable",
+                format!(
+                    "{} is not configured for {}; submit the signed envelope yourself",
+                    multisig::rpc_url_var(&proposal.network),
+                    proposal.network
+                ),
+            )
+        })?)
+    } else {
+        None
+    };
+
+    // Claim the proposal: the row stays locked until it is marked executed or
+    // the submission error is recorded, and a concurrent execute that waited
+    // on the lock finds it no longer approved.
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|err| db_internal_error("begin execute proposal", err))?;
+    let status: ProposalStatus =
+        sqlx::query_scalar("SELECT status FROM deploy_proposals WHERE id = $1 FOR UPDATE")
+            .bind(proposal_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|err| db_internal_error("lock proposal for execution", err))?;
+    if status != ProposalStatus::Approved {
+        return Err(already_settled(status));
+    }
+
+    let mut submission = None;
+    if let Some(rpc_url) = rpc_url {
+        let failure = match multisig::submit(&rpc_url, &signed_envelope).await {
+            Ok(result) if result.accepted() => {
+                submission = Some(result);
+                None
+            }
+            Ok(result) => Some(format!(
+                "sendTransaction returned {}{}",
+                result.status,
+                result
+                    .error_result_xdr
+                    .map(|xdr| format!(" ({})", xdr))
+                    .unwrap_or_default()
+            )),
+            Err(err) => Some(err),
+        };
+        if let Some(error) = failure {
+            // Keep the proposal approved so submission can be retried.
+            sqlx::query(
+                "UPDATE deploy_proposals SET signed_envelope_xdr = $2, submission_error = $3
+                 WHERE id = $1",
+            )
+            .bind(proposal_id)
+            .bind(&signed_envelope)
+            .bind(&error)
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| db_internal_error("record submission error", err))?;
+            tx.commit()
+                .await
+                .map_err(|err| db_internal_error("commit submission error", err))?;
+            tracing::warn!(proposal_id = %proposal_id, tx_hash = %tx_hash, error = %error, "deployment submission failed");
+            return Err(ApiError::new(
+                StatusCode::BAD_GATEWAY,
+                "SubmissionFailed",
+                error,
+            ));
+        }
+    }
+    let submitted = submission.is_some();
+
+    // Mark as executed and record it in the contract's signed audit chain
+    let executed_at: Option<chrono::DateTime<Utc>> = sqlx::query_scalar(
         "UPDATE deploy_proposals
-         SET status = 'executed', executed_at = NOW(), updated_at = NOW()
-         WHERE id = $1",
+         SET status = 'executed', executed_at = NOW(), updated_at = NOW(),
+             transaction_hash = $2, signed_envelope_xdr = $3,
+             submitted_at = CASE WHEN $4 THEN NOW() ELSE submitted_at END,
+             submission_error = NULL
+         WHERE id = $1 AND status = 'approved'
+         RETURNING executed_at",
     )
     .bind(proposal_id)
-    .execute(&state.db)
+    .bind(&tx_hash)
+    .bind(&signed_envelope)
+    .bind(submitted)
+    .fetch_optional(&mut *tx)
     .await
     .map_err(|err| db_internal_error("execute proposal", err))?;
+    let Some(executed_at) = executed_at else {
+        return Err(already_settled(ProposalStatus::Executed));
+    };
+
+    audit_log::append(
+        &mut tx,
+        contract_uuid,
+        AuditActionType::MultisigExecuted,
+        None,
+        Some(serde_json::json!({
+            "proposal_id": proposal_id,
+            "operation": proposal.operation,
+            "wasm_hash": proposal.wasm_hash,
+            "network": proposal.network,
+            "version": version,
+            "transaction_hash": tx_hash,
+            "signers": signatures.iter().map(|s| &s.signer_address).collect::<Vec<_>>(),
+            "submitted": submitted,
+        })),
+        &proposal.proposer,
+    )
+    .await
+    .map_err(|err| db_internal_error("audit proposal execution", err))?;
+
+    tx.commit()
+        .await
+        .map_err(|err| db_internal_error("commit execute proposal", err))?;
 
     tracing::info!(
         proposal_id  = %proposal_id,
         contract_id  = %proposal.contract_id,
         wasm_hash    = %proposal.wasm_hash,
+        tx_hash      = %tx_hash,
+        submitted    = submitted,
         "deployment proposal executed"
     );
-    let wasm_len = proposal.wasm_hash.len() as u64;
-    let cpu = 2_400_000 + wasm_len.saturating_mul(140);
-    let mem = 4_000_000 + wasm_len.saturating_mul(96);
-    let contract_id = proposal.contract_id.to_string();
-    let mut mgr = state.resource_mgr.write().unwrap();
-    let _ = mgr.record_usage(
-        &contract_id,
-        ResourceUsage {
-            cpu_instructions: cpu,
-            mem_bytes: mem,
-            storage_bytes: wasm_len,
-            timestamp: Utc::now(),
-        },
-    );
 
     Ok(Json(serde_json::json!({
         "success": true,
         "proposal_id": proposal_id,
         "contract_id": proposal.contract_id,
         "wasm_hash": proposal.wasm_hash,
-        "executed_at": Utc::now().to_rfc3339(),
-        "message": "Deployment proposal executed successfully"
+        "transaction_hash": tx_hash,
+        "signed_envelope_xdr": signed_envelope,
+        "submitted": submitted,
+        "submission_status": submission.map(|s| s.status),
+        "executed_at": executed_at.to_rfc3339(),
+        "message": if submitted {
+            "Deployment transaction submitted"
+        } else {
+            "Deployment transaction signed and ready for submission"
+        }
     })))
 }
 
@@ -497,11 +758,7 @@ pub async fn get_proposal(
 ) -> ApiResult<Json<ProposalWithSignatures>> {
     let proposal = fetch_proposal(&state, proposal_id).await?;
 
-    let policy: MultisigPolicy = sqlx::query_as("SELECT * FROM multisig_policies WHERE id = $1")
-        .bind(proposal.policy_id)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|err| db_internal_error("fetch policy for proposal info", err))?;
+    let policy = fetch_policy(&state, proposal.policy_id).await?;
 
     let signatures: Vec<ProposalSignature> = sqlx::query_as(
         "SELECT * FROM proposal_signatures WHERE proposal_id = $1 ORDER BY signed_at ASC",
@@ -511,13 +768,14 @@ pub async fn get_proposal(
     .await
     .map_err(|err| db_internal_error("list proposal signatures", err))?;
 
-    let collected = signatures.len() as i32;
-    let signatures_needed = (policy.threshold - collected).max(0);
+    let collected_weight: i64 = signatures.iter().map(|s| i64::from(s.weight)).sum();
+    let signatures_needed = (i64::from(policy.threshold) - collected_weight).max(0) as i32;
 
     Ok(Json(ProposalWithSignatures {
         proposal,
         policy,
         signatures,
+        collected_weight,
         signatures_needed,
     }))
 }
@@ -548,7 +806,7 @@ pub async fn list_proposals(
     let mut arg_idx = 1usize;
 
     if let Some(ref s) = params.status {
-        where_clauses.push(format!("status = ${}", arg_idx));
+        where_clauses.push(format!("status = ${}::proposal_status", arg_idx));
         arg_idx += 1;
     }
     if params.policy_id.is_some() {
@@ -604,3 +862,23 @@ pub async fn list_proposals(
         "pages": total_pages,
     })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_xdr::{test_support::deep_envelope, MAX_DEPTH};
+
+    #[test]
+    fn over_deep_envelopes_are_bad_requests() {
+        let err = multisig::parse_unsigned(
+            &deep_envelope(MAX_DEPTH as usize + 10),
+            &shared::Network::Testnet,
+            shared::DeployOperation::Upgrade,
+            &"00".repeat(32),
+            "",
+        )
+        .unwrap_err();
+        assert!(matches!(err, EnvelopeError::Decode(_)));
+        assert_eq!(envelope_error(err).status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,235 @@
+//! Background delivery of the notification outbox
+//!
+//! Every few seconds the dispatcher claims due outbox rows with
+//! `FOR UPDATE SKIP LOCKED` (so several API replicas can run it), hands
+//! each to its channel transport, records the attempt, and either marks the
+//! row delivered, schedules a retry with exponential backoff, or moves it to
+//! the dead-letter state. As with webhook deliveries, a claimed row stays
+//! 'pending' with `next_attempt_at` pushed out by a lease, so rows held by a
+//! dispatcher that died become due again on their own.
+
+use chrono::Utc;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::disaster_recovery_models::NotificationOutboxEntry;
+use crate::notification_transport::{DeliveryOutcome, OutboundMessage, TransportRegistry};
+
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+const BATCH_SIZE: i64 = 25;
+/// First retry delay; each later retry waits four times as long.
+const BASE_BACKOFF_SECS: u64 = 30;
+const MAX_BACKOFF_SECS: u64 = 6 * 3600;
+/// How long a claimed row is hidden from other dispatchers.
+const CLAIM_LEASE_MINUTES: i32 = 10;
+
+/// Spawn the outbox dispatcher. The poll interval can be changed with
+/// `NOTIFICATION_DISPATCH_INTERVAL_SECS`.
+pub fn spawn_notification_dispatcher(pool: PgPool, transports: Arc<TransportRegistry>) {
+    let interval_secs = std::env::var("NOTIFICATION_DISPATCH_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match dispatch_due(&pool, &transports).await {
+                Ok(0) => {}
+                Ok(n) => tracing::debug!(count = n, "notifications: dispatched batch"),
+                Err(err) => tracing::error!(error = ?err, "notifications: dispatch failed"),
+            }
+        }
+    });
+}
+
+/// Delay before retry number `attempt` (1-based): 30s, 2m, 8m, 32m, ...
+pub fn backoff_delay(attempt: i32) -> Duration {
+    let exponent = attempt.saturating_sub(1).clamp(0, 16) as u32;
+    Duration::from_secs(
+        BASE_BACKOFF_SECS
+            .saturating_mul(4u64.saturating_pow(exponent))
+            .min(MAX_BACKOFF_SECS),
+    )
+}
+
+/// Deliver one batch of due notifications; returns how many were attempted.
+pub async fn dispatch_due(
+    pool: &PgPool,
+    transports: &TransportRegistry,
+) -> Result<usize, sqlx::Error> {
+    let claimed: Vec<NotificationOutboxEntry> = sqlx::query_as(
+        r#"
+        UPDATE notification_outbox
+        SET attempts = attempts + 1,
+            next_attempt_at = NOW() + make_interval(mins => $2),
+            updated_at = NOW()
+        WHERE id IN (
+            SELECT id FROM notification_outbox
+            WHERE status = 'pending' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .bind(CLAIM_LEASE_MINUTES)
+    .fetch_all(pool)
+    .await?;
+
+    for entry in &claimed {
+        let started = Instant::now();
+        let outcome = match transports.get(&entry.channel) {
+            Some(transport) => transport.deliver(&outbound_message(entry)).await,
+            None => DeliveryOutcome::Rejected {
+                response_code: None,
+                error: format!("No transport configured for channel '{}'", entry.channel),
+            },
+        };
+        let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+        record_outcome(pool, entry, &outcome, duration_ms).await?;
+    }
+
+    Ok(claimed.len())
+}
+
+fn outbound_message(entry: &NotificationOutboxEntry) -> OutboundMessage {
+    OutboundMessage {
+        id: entry.id,
+        notification_type: entry.notification_type.clone(),
+        recipient: entry.recipient.clone(),
+        subject: entry.subject.clone(),
+        body: entry.body.clone(),
+        payload: entry.payload.clone(),
+        signing_secret: entry.signing_secret.clone(),
+    }
+}
+
+async fn record_outcome(
+    pool: &PgPool,
+    entry: &NotificationOutboxEntry,
+    outcome: &DeliveryOutcome,
+    duration_ms: i32,
+) -> Result<(), sqlx::Error> {
+    let (label, response_code, error) = match outcome {
+        DeliveryOutcome::Delivered { response_code } => ("delivered", *response_code, None),
+        DeliveryOutcome::Retry {
+            response_code,
+            error,
+        } => ("retry", *response_code, Some(error.as_str())),
+        DeliveryOutcome::Rejected {
+            response_code,
+            error,
+        } => ("rejected", *response_code, Some(error.as_str())),
+    };
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO notification_delivery_attempts
+            (outbox_id, attempt, outcome, response_code, error, duration_ms)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(entry.id)
+    .bind(entry.attempts)
+    .bind(label)
+    .bind(response_code)
+    .bind(error)
+    .bind(duration_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    let retry =
+        matches!(outcome, DeliveryOutcome::Retry { .. }) && entry.attempts < entry.max_attempts;
+    if let DeliveryOutcome::Delivered { .. } = outcome {
+        sqlx::query(
+            "UPDATE notification_outbox \
+             SET status = 'delivered', delivered_at = NOW(), last_error = NULL, updated_at = NOW() \
+             WHERE id = $1",
+        )
+        .bind(entry.id)
+        .execute(&mut *tx)
+        .await?;
+    } else if retry {
+        let next_attempt_at = Utc::now()
+            + chrono::Duration::from_std(backoff_delay(entry.attempts))
+                .unwrap_or_else(|_| chrono::Duration::hours(6));
+        sqlx::query(
+            "UPDATE notification_outbox \
+             SET status = 'pending', next_attempt_at = $2, last_error = $3, updated_at = NOW() \
+             WHERE id = $1",
+        )
+        .bind(entry.id)
+        .bind(next_attempt_at)
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+    } else {
+        tracing::warn!(
+            outbox_id = %entry.id,
+            channel = %entry.channel,
+            attempts = entry.attempts,
+            error = error.unwrap_or_default(),
+            "notifications: delivery dead-lettered"
+        );
+        sqlx::query(
+            "UPDATE notification_outbox \
+             SET status = 'dead_letter', last_error = $2, updated_at = NOW() \
+             WHERE id = $1",
+        )
+        .bind(entry.id)
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if let Some(log_id) = entry.notification_log_id {
+        settle_log(&mut tx, log_id).await?;
+    }
+
+    tx.commit().await
+}
+
+/// Once every delivery for a send request has settled, mark its log row
+/// 'delivered' (all succeeded) or 'failed' (any dead-lettered).
+async fn settle_log(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    log_id: uuid::Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE notification_logs l
+        SET status = CASE WHEN s.dead > 0 THEN 'failed' ELSE 'delivered' END
+        FROM (
+            SELECT COUNT(*) FILTER (WHERE status = 'pending') AS open,
+                   COUNT(*) FILTER (WHERE status = 'dead_letter') AS dead
+            FROM notification_outbox
+            WHERE notification_log_id = $1
+        ) s
+        WHERE l.id = $1 AND s.open = 0
+        "#,
+    )
+    .bind(log_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_exponentially_and_is_capped() {
+        let delays: Vec<u64> = (1..=5).map(|a| backoff_delay(a).as_secs()).collect();
+        assert_eq!(delays, vec![30, 120, 480, 1920, 7680]);
+        assert_eq!(backoff_delay(12).as_secs(), MAX_BACKOFF_SECS);
+        assert_eq!(backoff_delay(0).as_secs(), 30);
+    }
+}
@@ -1,20 +1,37 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
 use chrono::Utc;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{
     disaster_recovery_models::{
         CreateNotificationTemplateRequest, CreateUserNotificationPreferenceRequest,
-        NotificationTemplate, SendNotificationRequest, UserNotificationPreference,
+        NotificationDeliveryAttempt, NotificationOutboxDetail, NotificationOutboxEntry,
+        NotificationOutboxQuery, NotificationTemplate, SendNotificationRequest,
+        SendNotificationResponse, SkippedRecipient, UserNotificationPreference,
     },
     error::{ApiError, ApiResult},
+    notification_transport::CHANNELS,
     state::AppState,
+    webhooks,
 };
 
+const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+const MAX_OUTBOX_PAGE: i64 = 200;
+
+/// A single delivery to enqueue.
+#[derive(Debug, Clone, PartialEq)]
+struct Destination {
+    user_id: Option<Uuid>,
+    channel: String,
+    recipient: String,
+    signing_secret: Option<String>,
+}
+
 pub async fn create_notification_template(
     State(state): State<AppState>,
     Json(req): Json<CreateNotificationTemplateRequest>,
@@ -60,11 +77,32 @@ pub async fn create_user_notification_preference(
     State(state): State<AppState>,
     Json(req): Json<CreateUserNotificationPreferenceRequest>,
 ) -> ApiResult<Json<UserNotificationPreference>> {
+    for channel in &req.channels {
+        if !CHANNELS.contains(&channel.as_str()) {
+            return Err(ApiError::bad_request(
+                "InvalidChannel",
+                format!(
+                    "Unknown channel '{}'. Supported channels: {}",
+                    channel,
+                    CHANNELS.join(", ")
+                ),
+            ));
+        }
+        if preference_destination_missing(&req, channel) {
+            return Err(ApiError::bad_request(
+                "MissingDestination",
+                format!("Channel '{}' needs a destination address or URL", channel),
+            ));
+        }
+    }
+    check_preference_urls(&req, webhooks::allow_private_targets()).await?;
+
     let preference = sqlx::query_as::<_, UserNotificationPreference>(
         r#"
         INSERT INTO user_notification_preferences 
-        (user_id, contract_id, notification_types, channels, enabled)
-        VALUES ($1, $2, $3, $4, $5)
+        (user_id, contract_id, notification_types, channels, enabled,
+         email_address, webhook_url, webhook_secret, slack_webhook_url, matrix_webhook_url)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING *
         "#,
     )
@@ -73,6 +111,11 @@ pub async fn create_user_notification_preference(
     .bind(&req.notification_types)
     .bind(&req.channels)
     .bind(req.enabled)
+    .bind(&req.email_address)
+    .bind(&req.webhook_url)
+    .bind(&req.webhook_secret)
+    .bind(&req.slack_webhook_url)
+    .bind(&req.matrix_webhook_url)
     .fetch_one(&state.db)
     .await
     .map_err(|e| ApiError::internal(format!("Failed to create notification preference: {}", e)))?;
@@ -95,11 +138,142 @@ pub async fn get_user_notification_preferences(
     Ok(Json(preferences))
 }
 
+fn preference_destination_missing(
+    req: &CreateUserNotificationPreferenceRequest,
+    channel: &str,
+) -> bool {
+    let destination = match channel {
+        "email" => &req.email_address,
+        "webhook" => &req.webhook_url,
+        "slack" => &req.slack_webhook_url,
+        "matrix" => &req.matrix_webhook_url,
+        _ => return true,
+    };
+    destination.as_deref().is_none_or(|d| d.trim().is_empty())
+}
+
+/// Refuse preference URLs that are not public http(s) endpoints (see
+/// `webhooks::check_target`); the transport checks again before each delivery.
+async fn check_preference_urls(
+    req: &CreateUserNotificationPreferenceRequest,
+    allow_private: bool,
+) -> ApiResult<()> {
+    for (field, url) in [
+        ("webhook_url", &req.webhook_url),
+        ("slack_webhook_url", &req.slack_webhook_url),
+        ("matrix_webhook_url", &req.matrix_webhook_url),
+    ] {
+        let Some(url) = url.as_deref().filter(|u| !u.trim().is_empty()) else {
+            continue;
+        };
+        webhooks::check_target(url, allow_private)
+            .await
+            .map_err(|err| {
+                ApiError::bad_request("InvalidDestinationUrl", format!("{}: {}", field, err))
+            })?;
+    }
+    Ok(())
+}
+
+/// Replace every `{{name}}` placeholder with its value.
+fn render_template(template: &str, variables: &HashMap<String, String>) -> String {
+    let mut rendered = template.to_string();
+    for (key, value) in variables {
+        let placeholder = format!("{{{{{}}}}}", key); // {{variable}}
+        rendered = rendered.replace(&placeholder, value);
+    }
+    rendered
+}
+
+/// Destinations a user has opted in to for this notification, one per
+/// enabled channel.
+fn destinations_for_user(
+    user_id: Uuid,
+    preferences: &[UserNotificationPreference],
+) -> Vec<Destination> {
+    let mut destinations: Vec<Destination> = Vec::new();
+    for pref in preferences {
+        for channel in &pref.channels {
+            let (recipient, signing_secret) = match channel.as_str() {
+                "email" => (pref.email_address.clone(), None),
+                "webhook" => (pref.webhook_url.clone(), pref.webhook_secret.clone()),
+                "slack" => (pref.slack_webhook_url.clone(), None),
+                "matrix" => (pref.matrix_webhook_url.clone(), None),
+                _ => (None, None),
+            };
+            let Some(recipient) = recipient.filter(|r| !r.trim().is_empty()) else {
+                continue;
+            };
+            let destination = Destination {
+                user_id: Some(user_id),
+                channel: channel.clone(),
+                recipient,
+                signing_secret,
+            };
+            if !destinations
+                .iter()
+                .any(|d| d.channel == destination.channel && d.recipient == destination.recipient)
+            {
+                destinations.push(destination);
+            }
+        }
+    }
+    destinations
+}
+
+/// A raw address recipient: email addresses go by email; URLs use the
+/// template's channel when it is URL-based, else a generic webhook.
+fn destination_for_address(address: &str, template_channel: &str) -> Option<Destination> {
+    let channel = if address.starts_with("https://") || address.starts_with("http://") {
+        match template_channel {
+            "slack" | "matrix" | "webhook" => template_channel,
+            _ => "webhook",
+        }
+    } else if address.contains('@') {
+        "email"
+    } else {
+        return None;
+    };
+    Some(Destination {
+        user_id: None,
+        channel: channel.to_string(),
+        recipient: address.to_string(),
+        signing_secret: None,
+    })
+}
+
+/// [`destination_for_address`], refusing URLs that are not public endpoints.
+/// The error is the reason the recipient was skipped.
+async fn checked_address_destination(
+    address: &str,
+    template_channel: &str,
+    allow_private: bool,
+) -> Result<Destination, String> {
+    let destination = destination_for_address(address, template_channel)
+        .ok_or_else(|| "not an email address or http(s) URL".to_string())?;
+    if destination.channel != "email" {
+        webhooks::check_target(&destination.recipient, allow_private)
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(destination)
+}
+
+/// Render the template and queue one outbox row per destination. Recipients
+/// are publisher IDs (resolved through their notification preferences) or
+/// literal email addresses / webhook URLs. Delivery happens in the
+/// background; see `notification_dispatcher`.
 pub async fn send_notification(
     State(state): State<AppState>,
     Json(req): Json<SendNotificationRequest>,
-) -> ApiResult<StatusCode> {
-    // First, get the notification template
+) -> ApiResult<(StatusCode, Json<SendNotificationResponse>)> {
+    if req.recipients.is_empty() {
+        return Err(ApiError::bad_request(
+            "NoRecipients",
+            "At least one recipient is required",
+        ));
+    }
+
     let template = sqlx::query_as::<_, NotificationTemplate>(
         "SELECT * FROM notification_templates WHERE name = $1",
     )
@@ -111,28 +285,79 @@ pub async fn send_notification(
         ApiError::not_found("notification_template", "Notification template not found")
     })?;
 
-    // Process template with variables
-    let mut message = template.message_template.clone();
-    for (key, value) in &req.template_variables {
-        let placeholder = format!("{{{{{}}}}}", key); // {{variable}}
-        message = message.replace(&placeholder, value);
-    }
+    let subject = render_template(&template.subject, &req.template_variables);
+    let message = render_template(&template.message_template, &req.template_variables);
 
-    // For now, just log the notification - in a real implementation this would send via email/SMS/etc.
-    println!(
-        "Notification sent to {:?}: {} - {}",
-        req.recipients, template.subject, message
-    );
+    let allow_private = webhooks::allow_private_targets();
+    let mut destinations: Vec<Destination> = Vec::new();
+    let mut skipped = Vec::new();
+    for recipient in &req.recipients {
+        let resolved = match Uuid::parse_str(recipient) {
+            Ok(user_id) => {
+                let preferences = sqlx::query_as::<_, UserNotificationPreference>(
+                    r#"
+                    SELECT * FROM user_notification_preferences
+                    WHERE user_id = $1 AND enabled = true
+                      AND (contract_id IS NULL OR contract_id = $2)
+                      AND (cardinality(notification_types) = 0 OR $3 = ANY(notification_types))
+                    ORDER BY created_at
+                    "#,
+                )
+                .bind(user_id)
+                .bind(req.contract_id)
+                .bind(&req.notification_type)
+                .fetch_all(&state.db)
+                .await
+                .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+                destinations_for_user(user_id, &preferences)
+            }
+            Err(_) => {
+                match checked_address_destination(recipient, &template.channel, allow_private).await
+                {
+                    Ok(destination) => vec![destination],
+                    Err(reason) => {
+                        skipped.push(SkippedRecipient {
+                            recipient: recipient.clone(),
+                            reason,
+                        });
+                        continue;
+                    }
+                }
+            }
+        };
 
-    // In a real system, we'd store the notification in a queue table for processing
-    // and track delivery status
+        if resolved.is_empty() {
+            skipped.push(SkippedRecipient {
+                recipient: recipient.clone(),
+                reason: "no enabled preference with a destination for this notification"
+                    .to_string(),
+            });
+        }
+        for destination in resolved {
+            if !destinations.contains(&destination) {
+                destinations.push(destination);
+            }
+        }
+    }
+
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
 
-    // Log the notification for audit purposes
-    sqlx::query(
+    // One log row per request, for auditing; the dispatcher settles its status.
+    let log_status = if destinations.is_empty() {
+        "skipped"
+    } else {
+        "queued"
+    };
+    let notification_log_id: Uuid = sqlx::query_scalar(
         r#"
         INSERT INTO notification_logs 
         (contract_id, notification_type, recipients, message, sent_at, status)
-        VALUES ($1, $2, $3, $4, $5, 'sent')
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
         "#,
     )
     .bind(req.contract_id)
@@ -140,23 +365,278 @@ pub async fn send_notification(
     .bind(&req.recipients)
     .bind(&message)
     .bind(Utc::now())
-    .execute(&state.db)
+    .bind(log_status)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| ApiError::internal(format!("Failed to log notification: {}", e)))?;
 
-    Ok(StatusCode::OK)
+    let created_at = Utc::now();
+    let mut queued = Vec::with_capacity(destinations.len());
+    for destination in destinations {
+        let id = Uuid::new_v4();
+        let payload = serde_json::json!({
+            "id": id,
+            "type": req.notification_type,
+            "contract_id": req.contract_id,
+            "subject": subject,
+            "message": message,
+            "data": req.template_variables,
+            "priority": req.priority.as_deref().unwrap_or("normal"),
+            "created_at": created_at,
+        });
+        sqlx::query(
+            r#"
+            INSERT INTO notification_outbox
+            (id, notification_log_id, contract_id, user_id, notification_type, channel,
+             recipient, signing_secret, subject, body, payload, max_attempts)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+        )
+        .bind(id)
+        .bind(notification_log_id)
+        .bind(req.contract_id)
+        .bind(destination.user_id)
+        .bind(&req.notification_type)
+        .bind(&destination.channel)
+        .bind(&destination.recipient)
+        .bind(&destination.signing_secret)
+        .bind(&subject)
+        .bind(&message)
+        .bind(payload)
+        .bind(DEFAULT_MAX_ATTEMPTS)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to queue notification: {}", e)))?;
+        queued.push(id);
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(SendNotificationResponse {
+            notification_log_id,
+            queued,
+            skipped,
+        }),
+    ))
+}
+
+/// GET /api/notifications/outbox?status=dead_letter&channel=email&limit=50
+pub async fn list_outbox(
+    State(state): State<AppState>,
+    Query(query): Query<NotificationOutboxQuery>,
+) -> ApiResult<Json<Vec<NotificationOutboxEntry>>> {
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_OUTBOX_PAGE);
+    let entries = sqlx::query_as::<_, NotificationOutboxEntry>(
+        r#"
+        SELECT * FROM notification_outbox
+        WHERE ($1::text IS NULL OR status = $1)
+          AND ($2::text IS NULL OR channel = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(&query.status)
+    .bind(&query.channel)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+
+    Ok(Json(entries))
+}
+
+/// GET /api/notifications/outbox/:id — the entry and every delivery attempt.
+pub async fn get_outbox_entry(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<NotificationOutboxDetail>> {
+    let entry = sqlx::query_as::<_, NotificationOutboxEntry>(
+        "SELECT * FROM notification_outbox WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?
+    .ok_or_else(|| ApiError::not_found("notification", "Outbox entry not found"))?;
+
+    let delivery_attempts = sqlx::query_as::<_, NotificationDeliveryAttempt>(
+        "SELECT * FROM notification_delivery_attempts WHERE outbox_id = $1 ORDER BY attempt",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+
+    Ok(Json(NotificationOutboxDetail {
+        entry,
+        delivery_attempts,
+    }))
+}
+
+/// POST /api/notifications/outbox/:id/retry — re-queue a dead-lettered
+/// delivery with a fresh attempt budget.
+pub async fn retry_outbox_entry(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<NotificationOutboxEntry>> {
+    let entry = sqlx::query_as::<_, NotificationOutboxEntry>(
+        r#"
+        UPDATE notification_outbox
+        SET status = 'pending', next_attempt_at = NOW(),
+            max_attempts = attempts + $2, updated_at = NOW()
+        WHERE id = $1 AND status = 'dead_letter'
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(DEFAULT_MAX_ATTEMPTS)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?
+    .ok_or_else(|| {
+        ApiError::conflict(
+            "NotDeadLettered",
+            "Only dead-lettered notifications can be retried",
+        )
+    })?;
+
+    if let Some(log_id) = entry.notification_log_id {
+        sqlx::query("UPDATE notification_logs SET status = 'queued' WHERE id = $1")
+            .bind(log_id)
+            .execute(&state.db)
+            .await
+            .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+    }
+
+    Ok(Json(entry))
 }
 
 pub async fn get_user_notifications(
     State(state): State<AppState>,
     Path(user_id): Path<Uuid>,
 ) -> ApiResult<Json<serde_json::Value>> {
-    // In a real system, this would return user-specific notifications
-    // For now, return a placeholder response
+    let notifications = sqlx::query_as::<_, NotificationOutboxEntry>(
+        "SELECT * FROM notification_outbox WHERE user_id = $1 ORDER BY created_at DESC LIMIT 50",
+    )
+    .bind(user_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+
+    let pending_count = notifications
+        .iter()
+        .filter(|n| n.status == "pending")
+        .count();
+
     Ok(Json(serde_json::json!({
         "user_id": user_id,
-        "notifications": [],
-        "unread_count": 0,
+        "notifications": notifications,
+        "pending_count": pending_count,
         "last_checked": Utc::now()
     })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preference(channels: &[&str]) -> UserNotificationPreference {
+        UserNotificationPreference {
+            id: Uuid::new_v4(),
+            user_id: Uuid::nil(),
+            contract_id: None,
+            notification_types: vec![],
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            email_address: Some("ops@example.org".to_string()),
+            webhook_url: Some("https://hooks.example.org/in".to_string()),
+            webhook_secret: Some("s3cret".to_string()),
+            slack_webhook_url: None,
+            matrix_webhook_url: Some(" ".to_string()),
+        }
+    }
+
+    #[test]
+    fn renders_subject_and_body_placeholders() {
+        let vars = HashMap::from([
+            ("contract_id".to_string(), "C1".to_string()),
+            ("status".to_string(), "running".to_string()),
+        ]);
+        assert_eq!(
+            render_template(
+                "Recovery for {{contract_id}}: {{status}} {{missing}}",
+                &vars
+            ),
+            "Recovery for C1: running {{missing}}"
+        );
+    }
+
+    #[test]
+    fn user_destinations_follow_enabled_channels() {
+        let prefs = vec![
+            preference(&["email", "webhook", "slack", "matrix"]),
+            preference(&["email"]),
+        ];
+        let destinations = destinations_for_user(Uuid::nil(), &prefs);
+        let summary: Vec<(&str, bool)> = destinations
+            .iter()
+            .map(|d| (d.channel.as_str(), d.signing_secret.is_some()))
+            .collect();
+        // Slack has no URL and Matrix a blank one; the duplicate email is merged.
+        assert_eq!(summary, vec![("email", false), ("webhook", true)]);
+    }
+
+    #[test]
+    fn address_recipients_pick_a_channel() {
+        let email = destination_for_address("ops@example.org", "webhook").unwrap();
+        assert_eq!(email.channel, "email");
+        let slack = destination_for_address("https://hooks.slack.com/x", "slack").unwrap();
+        assert_eq!(slack.channel, "slack");
+        let hook = destination_for_address("https://example.org/hook", "email").unwrap();
+        assert_eq!(hook.channel, "webhook");
+        assert!(destination_for_address("GABC", "email").is_none());
+    }
+
+    #[tokio::test]
+    async fn internal_notification_urls_are_refused() {
+        let skipped = checked_address_destination("http://169.254.169.254/hook", "slack", false)
+            .await
+            .unwrap_err();
+        assert!(skipped.contains("not a public address"), "{}", skipped);
+        assert!(
+            checked_address_destination("https://203.0.113.7/hook", "slack", false)
+                .await
+                .is_ok()
+        );
+        assert!(
+            checked_address_destination("ops@example.org", "email", false)
+                .await
+                .is_ok()
+        );
+
+        let mut req = CreateUserNotificationPreferenceRequest {
+            user_id: Uuid::nil(),
+            contract_id: None,
+            notification_types: vec![],
+            channels: vec!["matrix".to_string()],
+            enabled: true,
+            email_address: None,
+            webhook_url: Some(" ".to_string()),
+            webhook_secret: None,
+            slack_webhook_url: None,
+            matrix_webhook_url: Some("http://10.0.0.8/_matrix/hook".to_string()),
+        };
+        let err = check_preference_urls(&req, false).await.unwrap_err();
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert!(check_preference_urls(&req, true).await.is_ok());
+
+        req.matrix_webhook_url = Some("https://203.0.113.9/_matrix/hook".to_string());
+        assert!(check_preference_urls(&req, false).await.is_ok());
+    }
+}
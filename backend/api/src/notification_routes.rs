@@ -21,6 +21,18 @@ pub fn notification_routes() -> Router<AppState> {
             "/api/notifications/send",
             post(notification_handlers::send_notification),
         )
+        .route(
+            "/api/notifications/outbox",
+            get(notification_handlers::list_outbox),
+        )
+        .route(
+            "/api/notifications/outbox/:id",
+            get(notification_handlers::get_outbox_entry),
+        )
+        .route(
+            "/api/notifications/outbox/:id/retry",
+            post(notification_handlers::retry_outbox_entry),
+        )
         .route(
             "/api/users/:id/notifications",
             get(notification_handlers::get_user_notifications),
@@ -0,0 +1,845 @@
+//! Channel transports for the notification outbox
+//!
+//! Each channel (`email`, `webhook`, `slack`, `matrix`) has a transport that
+//! delivers one rendered notification to one destination and classifies the
+//! result, so the dispatcher knows whether to retry.
+//!
+//! HTTP transports only reach public addresses (see
+//! `webhooks::check_target`) unless built with `allow_private(true)`, and
+//! never follow redirects: a 3xx answer is a rejected delivery.
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+use crate::webhooks::{self, PublicResolver};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const CHANNELS: [&str; 4] = ["email", "webhook", "slack", "matrix"];
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A rendered notification addressed to a single destination.
+#[derive(Debug, Clone)]
+pub struct OutboundMessage {
+    pub id: Uuid,
+    pub notification_type: String,
+    /// Email address or endpoint URL
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+    /// Structured event body for generic webhooks
+    pub payload: serde_json::Value,
+    pub signing_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliveryOutcome {
+    Delivered {
+        response_code: Option<i32>,
+    },
+    /// Transient failure (timeout, 5xx, SMTP 4xx): try again later.
+    Retry {
+        response_code: Option<i32>,
+        error: String,
+    },
+    /// The destination refused the message; retrying won't help.
+    Rejected {
+        response_code: Option<i32>,
+        error: String,
+    },
+}
+
+#[async_trait]
+pub trait NotificationTransport: Send + Sync {
+    async fn deliver(&self, message: &OutboundMessage) -> DeliveryOutcome;
+}
+
+/// Transports keyed by channel name.
+#[derive(Clone, Default)]
+pub struct TransportRegistry {
+    transports: HashMap<String, Arc<dyn NotificationTransport>>,
+}
+
+impl TransportRegistry {
+    /// Webhook, Slack and Matrix transports are always available; email is
+    /// enabled when `SMTP_HOST` and `SMTP_FROM` are set. HTTP destinations
+    /// follow `WEBHOOK_ALLOW_PRIVATE_TARGETS`.
+    pub fn from_env() -> Self {
+        let allow_private = webhooks::allow_private_targets();
+        let mut registry = TransportRegistry::default()
+            .with(
+                "webhook",
+                SignedWebhookTransport::new().allow_private(allow_private),
+            )
+            .with(
+                "slack",
+                ChatWebhookTransport::new(ChatFlavor::Slack).allow_private(allow_private),
+            )
+            .with(
+                "matrix",
+                ChatWebhookTransport::new(ChatFlavor::Matrix).allow_private(allow_private),
+            );
+
+        match SmtpTransport::from_env() {
+            Some(smtp) => registry = registry.with("email", smtp),
+            None => tracing::warn!("SMTP_HOST/SMTP_FROM not set; email notifications will fail"),
+        }
+        registry
+    }
+
+    pub fn with(mut self, channel: &str, transport: impl NotificationTransport + 'static) -> Self {
+        self.transports
+            .insert(channel.to_string(), Arc::new(transport));
+        self
+    }
+
+    pub fn get(&self, channel: &str) -> Option<Arc<dyn NotificationTransport>> {
+        self.transports.get(channel).cloned()
+    }
+}
+
+/// Hex-encoded HMAC-SHA256 of the payload, the same scheme the CLI uses to
+/// verify `X-Soroban-Signature` on received webhooks.
+pub fn sign_payload(secret: &str, payload: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Delivery client: no redirects and, unless `allow_private`, names resolved
+/// through [`PublicResolver`].
+fn http_client(allow_private: bool) -> reqwest::Client {
+    let builder = reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none());
+    let builder = if allow_private {
+        builder
+    } else {
+        builder.dns_resolver(Arc::new(PublicResolver))
+    };
+    // A default client would follow redirects to internal hosts.
+    builder.build().expect("delivery HTTP client")
+}
+
+/// Refuse destinations that are not public http(s) URLs before sending.
+async fn check_recipient(recipient: &str, allow_private: bool) -> Result<(), DeliveryOutcome> {
+    webhooks::check_target(recipient, allow_private)
+        .await
+        .map_err(|err| DeliveryOutcome::Rejected {
+            response_code: None,
+            error: err.to_string(),
+        })
+}
+
+/// 408 and 429 are worth retrying; other 4xx responses and redirects, which
+/// are never followed, are not.
+fn classify_http(result: Result<reqwest::Response, reqwest::Error>) -> DeliveryOutcome {
+    match result {
+        Ok(resp) => {
+            let status = resp.status();
+            let response_code = Some(status.as_u16() as i32);
+            if status.is_success() {
+                DeliveryOutcome::Delivered { response_code }
+            } else if status.is_redirection() {
+                DeliveryOutcome::Rejected {
+                    response_code,
+                    error: format!("HTTP {}: redirects are not followed", status.as_u16()),
+                }
+            } else if status.is_client_error()
+                && status != reqwest::StatusCode::REQUEST_TIMEOUT
+                && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+            {
+                DeliveryOutcome::Rejected {
+                    response_code,
+                    error: format!("HTTP {}", status.as_u16()),
+                }
+            } else {
+                DeliveryOutcome::Retry {
+                    response_code,
+                    error: format!("HTTP {}", status.as_u16()),
+                }
+            }
+        }
+        Err(e) => DeliveryOutcome::Retry {
+            response_code: None,
+            error: e.to_string(),
+        },
+    }
+}
+
+// ── Generic webhooks ─────────────────────────────────────────────────────────
+
+/// POSTs the JSON payload, signed with the subscriber's secret when it has
+/// one.
+pub struct SignedWebhookTransport {
+    client: reqwest::Client,
+    allow_private: bool,
+}
+
+impl SignedWebhookTransport {
+    pub fn new() -> Self {
+        Self {
+            client: http_client(false),
+            allow_private: false,
+        }
+    }
+
+    /// Also deliver to loopback, private and link-local addresses.
+    pub fn allow_private(self, allow_private: bool) -> Self {
+        Self {
+            client: http_client(allow_private),
+            allow_private,
+        }
+    }
+}
+
+/// `X-Soroban-*` headers sent with `body`, the serialized payload of
+/// `message`. The signature is present only when the message has a secret.
+pub fn webhook_headers(message: &OutboundMessage, body: &[u8]) -> Vec<(&'static str, String)> {
+    let mut headers = vec![
+        ("X-Soroban-Event", message.notification_type.clone()),
+        ("X-Soroban-Delivery-Id", message.id.to_string()),
+    ];
+    if let Some(secret) = &message.signing_secret {
+        headers.push((
+            "X-Soroban-Signature",
+            format!("sha256={}", sign_payload(secret, body)),
+        ));
+    }
+    headers
+}
+
+#[async_trait]
+impl NotificationTransport for SignedWebhookTransport {
+    async fn deliver(&self, message: &OutboundMessage) -> DeliveryOutcome {
+        if let Err(refused) = check_recipient(&message.recipient, self.allow_private).await {
+            return refused;
+        }
+        let body = match serde_json::to_vec(&message.payload) {
+            Ok(body) => body,
+            Err(e) => {
+                return DeliveryOutcome::Rejected {
+                    response_code: None,
+                    error: format!("Unserializable payload: {}", e),
+                }
+            }
+        };
+
+        let mut request = self
+            .client
+            .post(&message.recipient)
+            .header("Content-Type", "application/json");
+        for (name, value) in webhook_headers(message, &body) {
+            request = request.header(name, value);
+        }
+
+        classify_http(request.body(body).send().await)
+    }
+}
+
+// ── Slack / Matrix incoming webhooks ─────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy)]
+pub enum ChatFlavor {
+    /// Slack incoming webhooks (and Mattermost, Rocket.Chat, ...)
+    Slack,
+    /// Matrix hookshot generic webhooks
+    Matrix,
+}
+
+pub struct ChatWebhookTransport {
+    client: reqwest::Client,
+    flavor: ChatFlavor,
+    allow_private: bool,
+}
+
+impl ChatWebhookTransport {
+    pub fn new(flavor: ChatFlavor) -> Self {
+        Self {
+            client: http_client(false),
+            flavor,
+            allow_private: false,
+        }
+    }
+
+    /// Also deliver to loopback, private and link-local addresses.
+    pub fn allow_private(self, allow_private: bool) -> Self {
+        Self {
+            client: http_client(allow_private),
+            allow_private,
+            ..self
+        }
+    }
+}
+
+pub fn chat_payload(flavor: ChatFlavor, subject: &str, body: &str) -> serde_json::Value {
+    match flavor {
+        ChatFlavor::Slack => serde_json::json!({
+            "text": format!("*{}*\n{}", subject, body),
+        }),
+        ChatFlavor::Matrix => serde_json::json!({
+            "text": format!("{}\n\n{}", subject, body),
+            "html": format!(
+                "<strong>{}</strong><br>{}",
+                escape_html(subject),
+                escape_html(body).replace('\n', "<br>")
+            ),
+        }),
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[async_trait]
+impl NotificationTransport for ChatWebhookTransport {
+    async fn deliver(&self, message: &OutboundMessage) -> DeliveryOutcome {
+        if let Err(refused) = check_recipient(&message.recipient, self.allow_private).await {
+            return refused;
+        }
+        let payload = chat_payload(self.flavor, &message.subject, &message.body);
+        classify_http(
+            self.client
+                .post(&message.recipient)
+                .json(&payload)
+                .send()
+                .await,
+        )
+    }
+}
+
+// ── SMTP email ───────────────────────────────────────────────────────────────
+
+/// Minimal SMTP submission client (EHLO, optional AUTH PLAIN, one
+/// recipient per message). Connections are plaintext, so point it at a
+/// local relay or an MTA on a trusted network.
+pub struct SmtpTransport {
+    host: String,
+    port: u16,
+    from: String,
+    credentials: Option<(String, String)>,
+    hello_name: String,
+    timeout: Duration,
+}
+
+#[derive(Debug)]
+struct SmtpReply {
+    code: u16,
+    text: String,
+}
+
+#[derive(Debug)]
+enum SmtpFailure {
+    /// The server answered with an unexpected reply code.
+    Reply(SmtpReply),
+    Io(String),
+    InvalidAddress(String),
+}
+
+impl From<std::io::Error> for SmtpFailure {
+    fn from(e: std::io::Error) -> Self {
+        SmtpFailure::Io(e.to_string())
+    }
+}
+
+impl SmtpTransport {
+    pub fn new(host: impl Into<String>, port: u16, from: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            from: from.into(),
+            credentials: None,
+            hello_name: "soroban-registry".to_string(),
+            timeout: HTTP_TIMEOUT,
+        }
+    }
+
+    pub fn with_credentials(mut self, username: String, password: String) -> Self {
+        self.credentials = Some((username, password));
+        self
+    }
+
+    /// `SMTP_HOST`, `SMTP_PORT` (default 25), `SMTP_FROM`, and optionally
+    /// `SMTP_USERNAME`/`SMTP_PASSWORD`.
+    pub fn from_env() -> Option<Self> {
+        let host = std::env::var("SMTP_HOST").ok()?;
+        let from = std::env::var("SMTP_FROM").ok()?;
+        let port = std::env::var("SMTP_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(25);
+        let mut transport = SmtpTransport::new(host, port, from);
+        if let (Ok(user), Ok(pass)) = (
+            std::env::var("SMTP_USERNAME"),
+            std::env::var("SMTP_PASSWORD"),
+        ) {
+            transport = transport.with_credentials(user, pass);
+        }
+        Some(transport)
+    }
+
+    async fn send(&self, message: &OutboundMessage) -> Result<SmtpReply, SmtpFailure> {
+        let to = sanitize_header(&message.recipient);
+        if !is_plausible_address(&to) {
+            return Err(SmtpFailure::InvalidAddress(to));
+        }
+
+        let stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+
+        expect(read_reply(&mut reader).await?, &[220])?;
+        command(
+            &mut writer,
+            &mut reader,
+            &format!("EHLO {}", self.hello_name),
+            &[250],
+        )
+        .await?;
+
+        if let Some((user, pass)) = &self.credentials {
+            let token = BASE64.encode(format!("\0{}\0{}", user, pass));
+            command(
+                &mut writer,
+                &mut reader,
+                &format!("AUTH PLAIN {}", token),
+                &[235],
+            )
+            .await?;
+        }
+
+        command(
+            &mut writer,
+            &mut reader,
+            &format!("MAIL FROM:<{}>", self.from),
+            &[250],
+        )
+        .await?;
+        command(
+            &mut writer,
+            &mut reader,
+            &format!("RCPT TO:<{}>", to),
+            &[250, 251],
+        )
+        .await?;
+        command(&mut writer, &mut reader, "DATA", &[354]).await?;
+
+        let data = format_email(&self.from, &to, message);
+        writer.write_all(data.as_bytes()).await?;
+        let accepted = expect(read_reply(&mut reader).await?, &[250])?;
+
+        // The message is accepted at this point; a failed QUIT doesn't matter.
+        let _ = writer.write_all(b"QUIT\r\n").await;
+        Ok(accepted)
+    }
+}
+
+#[async_trait]
+impl NotificationTransport for SmtpTransport {
+    async fn deliver(&self, message: &OutboundMessage) -> DeliveryOutcome {
+        match tokio::time::timeout(self.timeout, self.send(message)).await {
+            Ok(Ok(reply)) => DeliveryOutcome::Delivered {
+                response_code: Some(reply.code as i32),
+            },
+            // Permanent negative completion (5yz) vs. transient (4yz).
+            Ok(Err(SmtpFailure::Reply(reply))) => {
+                let response_code = Some(reply.code as i32);
+                let error = format!("SMTP {} {}", reply.code, reply.text);
+                if reply.code >= 500 {
+                    DeliveryOutcome::Rejected {
+                        response_code,
+                        error,
+                    }
+                } else {
+                    DeliveryOutcome::Retry {
+                        response_code,
+                        error,
+                    }
+                }
+            }
+            Ok(Err(SmtpFailure::InvalidAddress(address))) => DeliveryOutcome::Rejected {
+                response_code: None,
+                error: format!("Invalid email address: {}", address),
+            },
+            Ok(Err(SmtpFailure::Io(error))) => DeliveryOutcome::Retry {
+                response_code: None,
+                error,
+            },
+            Err(_) => DeliveryOutcome::Retry {
+                response_code: None,
+                error: "SMTP session timed out".to_string(),
+            },
+        }
+    }
+}
+
+async fn command<R, W>(
+    writer: &mut W,
+    reader: &mut BufReader<R>,
+    line: &str,
+    accepted: &[u16],
+) -> Result<SmtpReply, SmtpFailure>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    writer.write_all(format!("{}\r\n", line).as_bytes()).await?;
+    expect(read_reply(reader).await?, accepted)
+}
+
+fn expect(reply: SmtpReply, accepted: &[u16]) -> Result<SmtpReply, SmtpFailure> {
+    if accepted.contains(&reply.code) {
+        Ok(reply)
+    } else {
+        Err(SmtpFailure::Reply(reply))
+    }
+}
+
+/// Read a possibly multi-line reply (`250-...` continues, `250 ...` ends).
+async fn read_reply<R>(reader: &mut BufReader<R>) -> Result<SmtpReply, SmtpFailure>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    let mut text = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(SmtpFailure::Io(
+                "SMTP server closed the connection".to_string(),
+            ));
+        }
+        let line = line.trim_end();
+        let code = line
+            .get(..3)
+            .and_then(|c| c.parse::<u16>().ok())
+            .ok_or_else(|| SmtpFailure::Io(format!("Malformed SMTP reply: {}", line)))?;
+        text.push(line.get(4..).unwrap_or("").to_string());
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(SmtpReply {
+                code,
+                text: text.join(" "),
+            });
+        }
+    }
+}
+
+fn sanitize_header(value: &str) -> String {
+    value.replace(['\r', '\n'], " ").trim().to_string()
+}
+
+fn is_plausible_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && domain.contains('.') && !address.contains(['<', '>', ' ', ','])
+        }
+        None => false,
+    }
+}
+
+/// RFC 2047 encoding for non-ASCII header values.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", BASE64.encode(value))
+    }
+}
+
+/// Headers and dot-stuffed body, terminated by `<CRLF>.<CRLF>`.
+pub fn format_email(from: &str, to: &str, message: &OutboundMessage) -> String {
+    let mut data = String::new();
+    data.push_str(&format!("From: <{}>\r\n", from));
+    data.push_str(&format!("To: <{}>\r\n", to));
+    data.push_str(&format!(
+        "Subject: {}\r\n",
+        encode_header(&sanitize_header(&message.subject))
+    ));
+    data.push_str(&format!("Date: {}\r\n", chrono::Utc::now().to_rfc2822()));
+    data.push_str(&format!(
+        "Message-ID: <{}@soroban-registry>\r\n",
+        message.id
+    ));
+    data.push_str(&format!(
+        "X-Soroban-Event: {}\r\n",
+        sanitize_header(&message.notification_type)
+    ));
+    data.push_str("MIME-Version: 1.0\r\n");
+    data.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+    data.push_str("Content-Transfer-Encoding: 8bit\r\n\r\n");
+
+    for line in message.body.lines() {
+        if line.starts_with('.') {
+            data.push('.');
+        }
+        data.push_str(line);
+        data.push_str("\r\n");
+    }
+    data.push_str(".\r\n");
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::HeaderMap, routing::post, Router};
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    fn message(recipient: &str) -> OutboundMessage {
+        OutboundMessage {
+            id: Uuid::new_v4(),
+            notification_type: "recovery_started".to_string(),
+            recipient: recipient.to_string(),
+            subject: "Recovery started".to_string(),
+            body: "Contract C1 is recovering.\n.leading dot".to_string(),
+            payload: serde_json::json!({ "contract_id": "C1" }),
+            signing_secret: Some("s3cret".to_string()),
+        }
+    }
+
+    type Captured = Arc<Mutex<Vec<(HeaderMap, Vec<u8>)>>>;
+
+    /// Local stand-in for a webhook receiver answering with `status`.
+    async fn http_stand_in(status: u16) -> (String, Captured) {
+        let captured: Captured = Arc::default();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(c): State<Captured>,
+                          headers: HeaderMap,
+                          body: axum::body::Bytes| async move {
+                        c.lock().unwrap().push((headers, body.to_vec()));
+                        axum::http::StatusCode::from_u16(status).unwrap()
+                    },
+                ),
+            )
+            .with_state(captured.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, captured)
+    }
+
+    /// Local stand-in answering every POST with a 302 to `location`.
+    async fn redirect_stand_in(location: String) -> String {
+        let app = Router::new().route(
+            "/hook",
+            post(move || async move {
+                (
+                    axum::http::StatusCode::FOUND,
+                    [(axum::http::header::LOCATION, location)],
+                )
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    /// Local stand-in SMTP server that accepts one message, or refuses the
+    /// recipient with `rcpt_reply`.
+    async fn smtp_stand_in(rcpt_reply: &'static str) -> (u16, Arc<Mutex<String>>) {
+        let transcript: Arc<Mutex<String>> = Arc::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let log = transcript.clone();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer.write_all(b"220 stand-in ESMTP\r\n").await.unwrap();
+            let mut in_data = false;
+            while let Ok(Some(line)) = lines.next_line().await {
+                log.lock().unwrap().push_str(&format!("{}\n", line));
+                let reply: &[u8] = if in_data {
+                    if line == "." {
+                        in_data = false;
+                        b"250 2.0.0 queued\r\n"
+                    } else {
+                        continue;
+                    }
+                } else if line.starts_with("EHLO") {
+                    b"250-stand-in\r\n250-AUTH PLAIN\r\n250 8BITMIME\r\n"
+                } else if line.starts_with("AUTH PLAIN") {
+                    b"235 2.7.0 ok\r\n"
+                } else if line.starts_with("RCPT") {
+                    rcpt_reply.as_bytes()
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    writer.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+        });
+        (port, transcript)
+    }
+
+    #[tokio::test]
+    async fn webhook_deliveries_are_signed() {
+        let (url, captured) = http_stand_in(200).await;
+        let outcome = SignedWebhookTransport::new()
+            .allow_private(true)
+            .deliver(&message(&url))
+            .await;
+        assert_eq!(
+            outcome,
+            DeliveryOutcome::Delivered {
+                response_code: Some(200)
+            }
+        );
+
+        let captured = captured.lock().unwrap();
+        let (headers, body) = &captured[0];
+        let expected = format!("sha256={}", sign_payload("s3cret", body));
+        assert_eq!(headers["x-soroban-signature"], expected.as_str());
+        assert_eq!(headers["x-soroban-event"], "recovery_started");
+    }
+
+    #[tokio::test]
+    async fn http_failures_are_classified() {
+        let (url, _) = http_stand_in(410).await;
+        let outcome = SignedWebhookTransport::new()
+            .allow_private(true)
+            .deliver(&message(&url))
+            .await;
+        assert!(matches!(outcome, DeliveryOutcome::Rejected { .. }));
+
+        let (url, _) = http_stand_in(503).await;
+        let outcome = ChatWebhookTransport::new(ChatFlavor::Slack)
+            .allow_private(true)
+            .deliver(&message(&url))
+            .await;
+        assert!(matches!(outcome, DeliveryOutcome::Retry { .. }));
+
+        let (url, _) = http_stand_in(429).await;
+        let outcome = ChatWebhookTransport::new(ChatFlavor::Matrix)
+            .allow_private(true)
+            .deliver(&message(&url))
+            .await;
+        assert!(matches!(outcome, DeliveryOutcome::Retry { .. }));
+    }
+
+    #[tokio::test]
+    async fn redirects_to_loopback_are_not_followed() {
+        let (internal, captured) = http_stand_in(200).await;
+        let url = redirect_stand_in(internal).await;
+
+        // The redirecting stand-in is itself on loopback, so private targets
+        // must be allowed to reach it at all.
+        let outcome = SignedWebhookTransport::new()
+            .allow_private(true)
+            .deliver(&message(&url))
+            .await;
+        assert!(matches!(
+            outcome,
+            DeliveryOutcome::Rejected {
+                response_code: Some(302),
+                ..
+            }
+        ));
+
+        let outcome = ChatWebhookTransport::new(ChatFlavor::Slack)
+            .allow_private(true)
+            .deliver(&message(&url))
+            .await;
+        assert!(matches!(outcome, DeliveryOutcome::Rejected { .. }));
+        assert!(captured.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn private_recipients_are_refused_before_sending() {
+        let (url, captured) = http_stand_in(200).await;
+        let outcome = SignedWebhookTransport::new().deliver(&message(&url)).await;
+        assert!(matches!(
+            outcome,
+            DeliveryOutcome::Rejected {
+                response_code: None,
+                ..
+            }
+        ));
+
+        let localhost = url.replace("127.0.0.1", "localhost");
+        let outcome = ChatWebhookTransport::new(ChatFlavor::Matrix)
+            .deliver(&message(&localhost))
+            .await;
+        assert!(matches!(outcome, DeliveryOutcome::Rejected { .. }));
+        assert!(captured.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn smtp_delivers_and_dot_stuffs() {
+        let (port, transcript) = smtp_stand_in("250 2.1.5 ok\r\n").await;
+        let smtp = SmtpTransport::new("127.0.0.1", port, "registry@example.org")
+            .with_credentials("user".to_string(), "pass".to_string());
+        let outcome = smtp.deliver(&message("ops@example.org")).await;
+        assert_eq!(
+            outcome,
+            DeliveryOutcome::Delivered {
+                response_code: Some(250)
+            }
+        );
+
+        let transcript = transcript.lock().unwrap();
+        assert!(transcript.contains("RCPT TO:<ops@example.org>"));
+        assert!(transcript.contains("Subject: Recovery started"));
+        assert!(transcript.contains("\n..leading dot\n"));
+    }
+
+    #[tokio::test]
+    async fn smtp_permanent_and_transient_failures() {
+        let (port, _) = smtp_stand_in("550 5.1.1 no such user\r\n").await;
+        let outcome = SmtpTransport::new("127.0.0.1", port, "registry@example.org")
+            .deliver(&message("nobody@example.org"))
+            .await;
+        assert!(matches!(
+            outcome,
+            DeliveryOutcome::Rejected {
+                response_code: Some(550),
+                ..
+            }
+        ));
+
+        let (port, _) = smtp_stand_in("451 4.3.0 try later\r\n").await;
+        let outcome = SmtpTransport::new("127.0.0.1", port, "registry@example.org")
+            .deliver(&message("ops@example.org"))
+            .await;
+        assert!(matches!(outcome, DeliveryOutcome::Retry { .. }));
+    }
+
+    #[test]
+    fn chat_payloads_and_headers_are_escaped() {
+        let payload = chat_payload(ChatFlavor::Matrix, "<b>", "a & b\nc");
+        assert_eq!(
+            payload["html"],
+            "<strong>&lt;b&gt;</strong><br>a &amp; b<br>c"
+        );
+        assert_eq!(chat_payload(ChatFlavor::Slack, "S", "B")["text"], "*S*\nB");
+
+        let mut msg = message("ops@example.org");
+        msg.subject = "Räumung\r\nBcc: evil@example.org".to_string();
+        let data = format_email("from@example.org", "ops@example.org", &msg);
+        assert!(!data.contains("\r\nBcc:"));
+        assert!(data.contains("Subject: =?UTF-8?B?"));
+    }
+}
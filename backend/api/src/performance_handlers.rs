@@ -0,0 +1,484 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{
+    AlertSeverity, CreateAlertConfigRequest, MetricType, PerformanceAlert, PerformanceAlertConfig,
+    PerformanceAnomaly, PerformanceMetric, PerformanceTrend, RecordPerformanceMetricRequest,
+};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::{db_internal_error, fetch_contract_identity};
+use crate::performance_monitor::ThresholdType;
+use crate::state::AppState;
+
+const MAX_BATCH: usize = 500;
+const SUMMARY_HOURS: i32 = 24;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MetricsQuery {
+    pub metric_type: Option<MetricType>,
+    pub function_name: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TrendQuery {
+    pub metric_type: Option<MetricType>,
+    pub function_name: Option<String>,
+    /// `hour` (default) or `day`
+    pub granularity: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct IssueQuery {
+    pub resolved: Option<bool>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AcknowledgeAlertRequest {
+    pub acknowledged_by: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchRecorded {
+    pub recorded: usize,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct SeriesSummary {
+    pub metric_type: MetricType,
+    pub function_name: Option<String>,
+    pub samples: i64,
+    pub avg_value: Option<f64>,
+    pub p95_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub last_recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContractPerformance {
+    pub contract_id: Uuid,
+    /// Per-series statistics over the last 24 hours
+    pub series: Vec<SeriesSummary>,
+    pub open_anomalies: Vec<PerformanceAnomaly>,
+    pub open_alerts: Vec<PerformanceAlert>,
+}
+
+fn to_decimal(field: &str, value: f64) -> ApiResult<Decimal> {
+    Decimal::from_f64(value)
+        .filter(|_| value.is_finite())
+        .map(|d| d.round_dp(4))
+        .ok_or_else(|| {
+            ApiError::bad_request(
+                "InvalidMetricValue",
+                format!("{} must be a finite number", field),
+            )
+        })
+}
+
+fn optional_decimal(field: &str, value: Option<f64>) -> ApiResult<Option<Decimal>> {
+    value.map(|v| to_decimal(field, v)).transpose()
+}
+
+fn limit(raw: Option<i64>, default: i64) -> i64 {
+    raw.unwrap_or(default).clamp(1, 1000)
+}
+
+/// A validated sample, ready to insert.
+struct NewMetric {
+    contract_id: Uuid,
+    metric_type: MetricType,
+    function_name: Option<String>,
+    value: Decimal,
+    p50: Option<Decimal>,
+    p95: Option<Decimal>,
+    p99: Option<Decimal>,
+    metadata: Option<serde_json::Value>,
+}
+
+async fn validate_metric(
+    state: &AppState,
+    req: RecordPerformanceMetricRequest,
+) -> ApiResult<NewMetric> {
+    let (contract_id, _) = fetch_contract_identity(state, &req.contract_id).await?;
+    if req.function_name.as_ref().is_some_and(|f| f.len() > 255) {
+        return Err(ApiError::bad_request(
+            "InvalidFunctionName",
+            "function_name must be at most 255 characters",
+        ));
+    }
+    Ok(NewMetric {
+        contract_id,
+        metric_type: req.metric_type,
+        function_name: req.function_name,
+        value: to_decimal("value", req.value)?,
+        p50: optional_decimal("p50", req.p50)?,
+        p95: optional_decimal("p95", req.p95)?,
+        p99: optional_decimal("p99", req.p99)?,
+        metadata: req.metadata,
+    })
+}
+
+async fn insert_metric<'e, E>(executor: E, metric: &NewMetric) -> ApiResult<PerformanceMetric>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as(
+        "INSERT INTO performance_metrics
+            (contract_id, metric_type, function_name, value, p50, p95, p99, metadata)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         RETURNING *",
+    )
+    .bind(metric.contract_id)
+    .bind(metric.metric_type)
+    .bind(&metric.function_name)
+    .bind(metric.value)
+    .bind(metric.p50)
+    .bind(metric.p95)
+    .bind(metric.p99)
+    .bind(&metric.metadata)
+    .fetch_one(executor)
+    .await
+    .map_err(|err| db_internal_error("record performance metric", err))
+}
+
+/// POST /api/performance/metrics
+pub async fn record_metric(
+    State(state): State<AppState>,
+    Json(req): Json<RecordPerformanceMetricRequest>,
+) -> ApiResult<(StatusCode, Json<PerformanceMetric>)> {
+    let metric = validate_metric(&state, req).await?;
+    let metric = insert_metric(&state.db, &metric).await?;
+    Ok((StatusCode::CREATED, Json(metric)))
+}
+
+/// POST /api/performance/metrics/batch
+pub async fn record_metrics_batch(
+    State(state): State<AppState>,
+    Json(reqs): Json<Vec<RecordPerformanceMetricRequest>>,
+) -> ApiResult<(StatusCode, Json<BatchRecorded>)> {
+    if reqs.len() > MAX_BATCH {
+        return Err(ApiError::bad_request(
+            "BatchTooLarge",
+            format!("At most {} metrics can be recorded per request", MAX_BATCH),
+        ));
+    }
+    let mut metrics = Vec::with_capacity(reqs.len());
+    for req in reqs {
+        metrics.push(validate_metric(&state, req).await?);
+    }
+
+    // All or nothing, so a client can safely retry a failed batch.
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|err| db_internal_error("begin transaction", err))?;
+    for metric in &metrics {
+        insert_metric(&mut *tx, metric).await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|err| db_internal_error("commit transaction", err))?;
+    Ok((
+        StatusCode::CREATED,
+        Json(BatchRecorded {
+            recorded: metrics.len(),
+        }),
+    ))
+}
+
+/// GET /api/contracts/:id/performance
+pub async fn get_contract_performance(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ContractPerformance>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+
+    let series: Vec<SeriesSummary> = sqlx::query_as(
+        "SELECT metric_type, function_name, COUNT(*) AS samples,
+                AVG(value)::float8 AS avg_value,
+                percentile_cont(0.95) WITHIN GROUP (ORDER BY value) AS p95_value,
+                MAX(value)::float8 AS max_value,
+                MAX(timestamp) AS last_recorded_at
+         FROM performance_metrics
+         WHERE contract_id = $1 AND timestamp > NOW() - make_interval(hours => $2)
+         GROUP BY metric_type, function_name
+         ORDER BY metric_type, function_name NULLS FIRST",
+    )
+    .bind(contract_uuid)
+    .bind(SUMMARY_HOURS)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("summarise performance metrics", err))?;
+
+    let open_anomalies = sqlx::query_as(
+        "SELECT * FROM performance_anomalies
+         WHERE contract_id = $1 AND NOT resolved ORDER BY detected_at DESC",
+    )
+    .bind(contract_uuid)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("fetch open anomalies", err))?;
+
+    let open_alerts = sqlx::query_as(
+        "SELECT * FROM performance_alerts
+         WHERE contract_id = $1 AND NOT resolved ORDER BY triggered_at DESC",
+    )
+    .bind(contract_uuid)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("fetch open alerts", err))?;
+
+    Ok(Json(ContractPerformance {
+        contract_id: contract_uuid,
+        series,
+        open_anomalies,
+        open_alerts,
+    }))
+}
+
+/// GET /api/contracts/:id/performance/metrics
+pub async fn list_metrics(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<MetricsQuery>,
+) -> ApiResult<Json<Vec<PerformanceMetric>>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let metrics = sqlx::query_as(
+        "SELECT * FROM performance_metrics
+         WHERE contract_id = $1
+           AND ($2::metric_type IS NULL OR metric_type = $2)
+           AND ($3::text IS NULL OR function_name = $3)
+           AND ($4::timestamptz IS NULL OR timestamp >= $4)
+         ORDER BY timestamp DESC
+         LIMIT $5",
+    )
+    .bind(contract_uuid)
+    .bind(query.metric_type)
+    .bind(&query.function_name)
+    .bind(query.since)
+    .bind(limit(query.limit, 200))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list performance metrics", err))?;
+    Ok(Json(metrics))
+}
+
+/// GET /api/contracts/:id/performance/trends
+pub async fn list_trends(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TrendQuery>,
+) -> ApiResult<Json<Vec<PerformanceTrend>>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let granularity = query.granularity.as_deref().unwrap_or("hour");
+    if !matches!(granularity, "hour" | "day") {
+        return Err(ApiError::bad_request(
+            "InvalidGranularity",
+            "granularity must be 'hour' or 'day'",
+        ));
+    }
+    let trends = sqlx::query_as(
+        "SELECT * FROM performance_trends
+         WHERE contract_id = $1 AND granularity = $2
+           AND ($3::metric_type IS NULL OR metric_type = $3)
+           AND ($4::text IS NULL OR function_name = $4)
+           AND ($5::timestamptz IS NULL OR timeframe_start >= $5)
+         ORDER BY timeframe_start DESC
+         LIMIT $6",
+    )
+    .bind(contract_uuid)
+    .bind(granularity)
+    .bind(query.metric_type)
+    .bind(&query.function_name)
+    .bind(query.since)
+    .bind(limit(query.limit, 168))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list performance trends", err))?;
+    Ok(Json(trends))
+}
+
+/// GET /api/contracts/:id/performance/anomalies
+pub async fn list_anomalies(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<IssueQuery>,
+) -> ApiResult<Json<Vec<PerformanceAnomaly>>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let anomalies = sqlx::query_as(
+        "SELECT * FROM performance_anomalies
+         WHERE contract_id = $1 AND ($2::bool IS NULL OR resolved = $2)
+         ORDER BY detected_at DESC
+         LIMIT $3",
+    )
+    .bind(contract_uuid)
+    .bind(query.resolved)
+    .bind(limit(query.limit, 100))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list performance anomalies", err))?;
+    Ok(Json(anomalies))
+}
+
+/// GET /api/contracts/:id/performance/alerts
+pub async fn list_alerts(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<IssueQuery>,
+) -> ApiResult<Json<Vec<PerformanceAlert>>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let alerts = sqlx::query_as(
+        "SELECT * FROM performance_alerts
+         WHERE contract_id = $1 AND ($2::bool IS NULL OR resolved = $2)
+         ORDER BY triggered_at DESC
+         LIMIT $3",
+    )
+    .bind(contract_uuid)
+    .bind(query.resolved)
+    .bind(limit(query.limit, 100))
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list performance alerts", err))?;
+    Ok(Json(alerts))
+}
+
+/// POST /api/performance/alerts/:id/acknowledge
+pub async fn acknowledge_alert(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<AcknowledgeAlertRequest>,
+) -> ApiResult<Json<PerformanceAlert>> {
+    let alert_id = Uuid::parse_str(&id).map_err(|_| {
+        ApiError::bad_request("InvalidAlertId", format!("Invalid alert ID: {}", id))
+    })?;
+    sqlx::query_as(
+        "UPDATE performance_alerts
+         SET acknowledged = TRUE,
+             acknowledged_at = COALESCE(acknowledged_at, NOW()),
+             acknowledged_by = COALESCE($2, acknowledged_by)
+         WHERE id = $1
+         RETURNING *",
+    )
+    .bind(alert_id)
+    .bind(&req.acknowledged_by)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|err| db_internal_error("acknowledge performance alert", err))?
+    .map(Json)
+    .ok_or_else(|| {
+        ApiError::not_found(
+            "AlertNotFound",
+            format!("No performance alert found with ID: {}", id),
+        )
+    })
+}
+
+/// GET /api/contracts/:id/performance/alert-configs
+pub async fn list_alert_configs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Vec<PerformanceAlertConfig>>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let configs = sqlx::query_as(
+        "SELECT * FROM performance_alert_configs
+         WHERE contract_id = $1 ORDER BY metric_type, threshold_type",
+    )
+    .bind(contract_uuid)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list alert configs", err))?;
+    Ok(Json(configs))
+}
+
+/// POST /api/performance/alert-configs — create or replace the config for a
+/// (contract, metric type, threshold type).
+pub async fn upsert_alert_config(
+    State(state): State<AppState>,
+    Json(req): Json<CreateAlertConfigRequest>,
+) -> ApiResult<(StatusCode, Json<PerformanceAlertConfig>)> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &req.contract_id).await?;
+    let threshold_type = ThresholdType::parse(&req.threshold_type).ok_or_else(|| {
+        let valid: Vec<&str> = ThresholdType::ALL.iter().map(|t| t.as_str()).collect();
+        ApiError::bad_request(
+            "InvalidThresholdType",
+            format!("threshold_type must be one of: {}", valid.join(", ")),
+        )
+    })?;
+    let threshold_value = to_decimal("threshold_value", req.threshold_value)?;
+
+    let config = sqlx::query_as(
+        "INSERT INTO performance_alert_configs
+            (contract_id, metric_type, threshold_type, threshold_value, severity)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (contract_id, metric_type, threshold_type) DO UPDATE SET
+            threshold_value = EXCLUDED.threshold_value,
+            severity = EXCLUDED.severity,
+            enabled = TRUE,
+            updated_at = NOW()
+         RETURNING *",
+    )
+    .bind(contract_uuid)
+    .bind(req.metric_type)
+    .bind(threshold_type.as_str())
+    .bind(threshold_value)
+    .bind(req.severity.unwrap_or(AlertSeverity::Warning))
+    .fetch_one(&state.db)
+    .await
+    .map_err(|err| db_internal_error("save alert config", err))?;
+    Ok((StatusCode::CREATED, Json(config)))
+}
+
+/// DELETE /api/performance/alert-configs/:id — also resolves its open alert.
+pub async fn delete_alert_config(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let config_id = Uuid::parse_str(&id).map_err(|_| {
+        ApiError::bad_request(
+            "InvalidAlertConfigId",
+            format!("Invalid alert config ID: {}", id),
+        )
+    })?;
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|err| db_internal_error("begin transaction", err))?;
+    sqlx::query(
+        "UPDATE performance_alerts SET resolved = TRUE, resolved_at = NOW()
+         WHERE alert_config_id = $1 AND NOT resolved",
+    )
+    .bind(config_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|err| db_internal_error("resolve config alerts", err))?;
+    let deleted = sqlx::query("DELETE FROM performance_alert_configs WHERE id = $1")
+        .bind(config_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| db_internal_error("delete alert config", err))?;
+    if deleted.rows_affected() == 0 {
+        return Err(ApiError::not_found(
+            "AlertConfigNotFound",
+            format!("No alert config found with ID: {}", id),
+        ));
+    }
+    tx.commit()
+        .await
+        .map_err(|err| db_internal_error("commit transaction", err))?;
+    Ok(StatusCode::NO_CONTENT)
+}
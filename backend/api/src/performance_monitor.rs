@@ -0,0 +1,767 @@
+//! Performance anomaly detection
+//!
+//! Samples land in `performance_metrics` as they are reported. Every
+//! `PERFORMANCE_DETECTION_INTERVAL_SECS` the monitor takes each series
+//! (contract, metric type, function) with samples in the detection window
+//! and compares the window's mean against a baseline:
+//!
+//! * a rolling z-score and an EWMA over the preceding hour, until the series
+//!   has history for at least `MIN_SEASONS` days;
+//! * after that, a seasonal baseline: the same time of day on each of the
+//!   previous seven days, scored with median and MAD so that daily cycles do
+//!   not look anomalous.
+//!
+//! All tracked metric types are costs or error rates, so only increases are
+//! treated as anomalies. A series opens an anomaly at `OPEN_SCORE` and keeps
+//! it open, escalating severity if needed, until its score falls below
+//! `RESOLVE_SCORE`. The same pass evaluates every enabled
+//! `performance_alert_configs` row against the window, raising or resolving
+//! one alert per config, and rolls samples up into hourly and daily
+//! `performance_trends`.
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use shared::{AlertSeverity, MetricType};
+use sqlx::{FromRow, PgPool};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::metrics::{PERFORMANCE_ALERTS_FIRED, PERFORMANCE_ANOMALIES_OPEN};
+use crate::webhooks::{self, WebhookEvent};
+
+const DEFAULT_DETECTION_INTERVAL_SECS: u64 = 60;
+const DEFAULT_DETECTION_WINDOW_SECS: i64 = 300;
+/// Rolling baseline length, ending where the detection window starts.
+const BASELINE_SECS: i64 = 3600;
+/// Most recent samples loaded per series for the rolling baseline.
+const MAX_SERIES_SAMPLES: i64 = 5000;
+const MIN_BASELINE_SAMPLES: usize = 10;
+const SEASONAL_DAYS: i32 = 7;
+const MIN_SEASONS: usize = 3;
+const EWMA_ALPHA: f64 = 0.3;
+pub const OPEN_SCORE: f64 = 3.0;
+pub const RESOLVE_SCORE: f64 = 2.0;
+/// Spread never counts as less than this share of the baseline, so a flat
+/// baseline does not turn tiny changes into huge scores.
+const NOISE_FLOOR: f64 = 0.05;
+/// Change between trend buckets below which a trend is `stable`.
+const STABLE_CHANGE_PERCENT: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detector {
+    ZScore,
+    Ewma,
+    Seasonal,
+}
+
+impl Detector {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Detector::ZScore => "zscore",
+            Detector::Ewma => "ewma",
+            Detector::Seasonal => "seasonal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Assessment {
+    pub detector: Detector,
+    pub baseline: f64,
+    pub current: f64,
+    pub score: f64,
+}
+
+impl Assessment {
+    pub fn severity(&self) -> Option<AlertSeverity> {
+        severity(self.score)
+    }
+
+    pub fn deviation_percent(&self) -> Option<f64> {
+        (self.baseline != 0.0).then(|| (self.current - self.baseline) / self.baseline.abs() * 100.0)
+    }
+}
+
+pub fn severity(score: f64) -> Option<AlertSeverity> {
+    if score >= 6.0 {
+        Some(AlertSeverity::Critical)
+    } else if score >= 4.0 {
+        Some(AlertSeverity::Warning)
+    } else if score >= OPEN_SCORE {
+        Some(AlertSeverity::Info)
+    } else {
+        None
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn spread(center: f64, raw: f64) -> f64 {
+    raw.max(NOISE_FLOOR * center.abs()).max(f64::EPSILON)
+}
+
+/// Distance of `current` from the mean of `baseline`, in standard deviations.
+pub fn zscore(baseline: &[f64], current: f64) -> Option<Assessment> {
+    if baseline.len() < MIN_BASELINE_SAMPLES {
+        return None;
+    }
+    let m = mean(baseline);
+    let var = baseline.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (baseline.len() - 1) as f64;
+    Some(Assessment {
+        detector: Detector::ZScore,
+        baseline: m,
+        current,
+        score: (current - m) / spread(m, var.sqrt()),
+    })
+}
+
+/// Distance of `current` from an exponentially weighted mean of `baseline`
+/// (oldest first), scaled by the matching weighted deviation.
+pub fn ewma(baseline: &[f64], current: f64) -> Option<Assessment> {
+    if baseline.len() < MIN_BASELINE_SAMPLES {
+        return None;
+    }
+    let mut m = baseline[0];
+    let mut var = 0.0;
+    for x in &baseline[1..] {
+        let d = x - m;
+        m += EWMA_ALPHA * d;
+        var = (1.0 - EWMA_ALPHA) * (var + EWMA_ALPHA * d * d);
+    }
+    Some(Assessment {
+        detector: Detector::Ewma,
+        baseline: m,
+        current,
+        score: (current - m) / spread(m, var.sqrt()),
+    })
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    if n % 2 == 1 {
+        sorted[n / 2]
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    }
+}
+
+/// Robust score of `current` against the same window on previous days.
+pub fn seasonal(seasons: &[f64], current: f64) -> Option<Assessment> {
+    if seasons.len() < MIN_SEASONS {
+        return None;
+    }
+    let mut sorted = seasons.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let med = median(&sorted);
+    let mut deviations: Vec<f64> = sorted.iter().map(|x| (x - med).abs()).collect();
+    deviations.sort_by(f64::total_cmp);
+    // 1.4826 · MAD estimates the standard deviation for normal data.
+    let mad = 1.4826 * median(&deviations);
+    Some(Assessment {
+        detector: Detector::Seasonal,
+        baseline: med,
+        current,
+        score: (current - med) / spread(med, mad),
+    })
+}
+
+/// Score a detection window. The seasonal baseline decides once there is
+/// enough history for it; before that the higher rolling score is used.
+pub fn assess(window: &[f64], baseline: &[f64], seasons: &[f64]) -> Option<Assessment> {
+    if window.is_empty() {
+        return None;
+    }
+    let current = mean(window);
+    if let Some(a) = seasonal(seasons, current) {
+        return Some(a);
+    }
+    match (zscore(baseline, current), ewma(baseline, current)) {
+        (Some(z), Some(e)) => Some(if e.score > z.score { e } else { z }),
+        (z, e) => z.or(e),
+    }
+}
+
+/// Threshold kinds accepted in `performance_alert_configs.threshold_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdType {
+    ValueExceeds,
+    ValueBelow,
+    P95Exceeds,
+    P99Exceeds,
+}
+
+impl ThresholdType {
+    pub const ALL: [ThresholdType; 4] = [
+        ThresholdType::ValueExceeds,
+        ThresholdType::ValueBelow,
+        ThresholdType::P95Exceeds,
+        ThresholdType::P99Exceeds,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThresholdType::ValueExceeds => "value_exceeds",
+            ThresholdType::ValueBelow => "value_below",
+            ThresholdType::P95Exceeds => "p95_exceeds",
+            ThresholdType::P99Exceeds => "p99_exceeds",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == s)
+    }
+
+    /// The window statistic this threshold is compared with.
+    pub fn observed(self, window: &WindowStats) -> f64 {
+        match self {
+            ThresholdType::ValueExceeds | ThresholdType::ValueBelow => window.avg_value,
+            ThresholdType::P95Exceeds => window.p95_value,
+            ThresholdType::P99Exceeds => window.p99_value,
+        }
+    }
+
+    pub fn breached(self, observed: f64, threshold: f64) -> bool {
+        match self {
+            ThresholdType::ValueBelow => observed < threshold,
+            _ => observed > threshold,
+        }
+    }
+}
+
+/// A contract's samples of one metric type in the detection window, across
+/// functions. Percentiles use reported p95/p99 where available and fall back
+/// to the distribution of values.
+#[derive(Debug, Clone, FromRow)]
+pub struct WindowStats {
+    pub avg_value: f64,
+    pub p95_value: f64,
+    pub p99_value: f64,
+    pub samples: i64,
+}
+
+#[derive(Debug, FromRow)]
+struct Series {
+    contract_id: Uuid,
+    metric_type: MetricType,
+    function_name: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct ConfigWindow {
+    id: Uuid,
+    contract_id: Uuid,
+    metric_type: MetricType,
+    threshold_type: String,
+    threshold_value: f64,
+    severity: AlertSeverity,
+    #[sqlx(flatten)]
+    window: WindowStats,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PassSummary {
+    pub anomalies_opened: usize,
+    pub anomalies_resolved: usize,
+    pub alerts_raised: usize,
+    pub alerts_resolved: usize,
+}
+
+pub fn detection_window() -> chrono::Duration {
+    let secs = std::env::var("PERFORMANCE_DETECTION_WINDOW_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_DETECTION_WINDOW_SECS);
+    chrono::Duration::seconds(secs.max(1))
+}
+
+async fn assess_series(
+    pool: &PgPool,
+    series: &Series,
+    now: DateTime<Utc>,
+    window: chrono::Duration,
+) -> Result<Option<Assessment>, sqlx::Error> {
+    let window_start = now - window;
+    let mut samples: Vec<(DateTime<Utc>, f64)> = sqlx::query_as(
+        "SELECT timestamp, value::float8 FROM performance_metrics
+         WHERE contract_id = $1 AND metric_type = $2 AND function_name IS NOT DISTINCT FROM $3
+           AND timestamp > $4 AND timestamp <= $5
+         ORDER BY timestamp DESC
+         LIMIT $6",
+    )
+    .bind(series.contract_id)
+    .bind(series.metric_type)
+    .bind(&series.function_name)
+    .bind(window_start - chrono::Duration::seconds(BASELINE_SECS))
+    .bind(now)
+    .bind(MAX_SERIES_SAMPLES)
+    .fetch_all(pool)
+    .await?;
+    samples.reverse();
+    let (baseline, current): (Vec<_>, Vec<_>) =
+        samples.into_iter().partition(|(ts, _)| *ts <= window_start);
+    let baseline: Vec<f64> = baseline.into_iter().map(|(_, v)| v).collect();
+    let current: Vec<f64> = current.into_iter().map(|(_, v)| v).collect();
+
+    let seasons: Vec<f64> = sqlx::query_scalar(
+        "SELECT AVG(m.value)::float8
+         FROM generate_series(1, $6) AS d
+         JOIN performance_metrics m
+           ON m.contract_id = $1 AND m.metric_type = $2
+          AND m.function_name IS NOT DISTINCT FROM $3
+          AND m.timestamp > $4 - make_interval(days => d) - make_interval(secs => $5)
+          AND m.timestamp <= $4 - make_interval(days => d)
+         GROUP BY d",
+    )
+    .bind(series.contract_id)
+    .bind(series.metric_type)
+    .bind(&series.function_name)
+    .bind(now)
+    .bind(window.num_seconds() as f64)
+    .bind(SEASONAL_DAYS)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(assess(&current, &baseline, &seasons))
+}
+
+fn describe(series: &Series, a: &Assessment) -> String {
+    let deviation = a
+        .deviation_percent()
+        .map(|d| format!(" ({:+.1}%)", d))
+        .unwrap_or_default();
+    format!(
+        "{} for {} is {:.4} against a {} baseline of {:.4}{}, score {:.2}",
+        metric_type_name(series.metric_type),
+        series.function_name.as_deref().unwrap_or("contract"),
+        a.current,
+        a.detector.as_str(),
+        a.baseline,
+        deviation,
+        a.score
+    )
+}
+
+pub fn metric_type_name(metric_type: MetricType) -> &'static str {
+    match metric_type {
+        MetricType::ExecutionTime => "execution_time",
+        MetricType::MemoryUsage => "memory_usage",
+        MetricType::StorageIo => "storage_io",
+        MetricType::GasConsumption => "gas_consumption",
+        MetricType::ErrorRate => "error_rate",
+    }
+}
+
+/// DECIMAL(10,2) columns cannot hold larger magnitudes.
+fn clamp_percent(value: f64) -> f64 {
+    value.clamp(-99_999_999.99, 99_999_999.99)
+}
+
+/// Open, update or resolve the series' anomaly. Returns (opened, resolved).
+async fn apply_assessment(
+    pool: &PgPool,
+    series: &Series,
+    a: &Assessment,
+) -> Result<(bool, bool), sqlx::Error> {
+    if let Some(severity) = a.severity() {
+        let description = describe(series, a);
+        let opened: Option<(Uuid, bool)> = sqlx::query_as(
+            "INSERT INTO performance_anomalies
+                (contract_id, metric_type, function_name, baseline_value, current_value,
+                 deviation_percent, severity, description, detector, score)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (contract_id, metric_type, (COALESCE(function_name, '')))
+                WHERE NOT resolved
+             DO UPDATE SET
+                current_value = EXCLUDED.current_value,
+                baseline_value = EXCLUDED.baseline_value,
+                deviation_percent = EXCLUDED.deviation_percent,
+                severity = GREATEST(performance_anomalies.severity, EXCLUDED.severity),
+                description = EXCLUDED.description,
+                detector = EXCLUDED.detector,
+                score = EXCLUDED.score,
+                last_evaluated_at = NOW()
+             RETURNING id, (xmax = 0) AS inserted",
+        )
+        .bind(series.contract_id)
+        .bind(series.metric_type)
+        .bind(&series.function_name)
+        .bind(a.baseline)
+        .bind(a.current)
+        .bind(a.deviation_percent().map(clamp_percent))
+        .bind(severity)
+        .bind(&description)
+        .bind(a.detector.as_str())
+        .bind(a.score)
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some((id, true)) = opened {
+            tracing::warn!(
+                contract_id = %series.contract_id,
+                anomaly_id = %id,
+                %description,
+                "performance anomaly detected"
+            );
+            webhooks::emit(
+                pool,
+                WebhookEvent::PerformanceAnomalyDetected,
+                series.contract_id,
+                json!({
+                    "anomaly_id": id,
+                    "metric_type": series.metric_type,
+                    "function_name": series.function_name,
+                    "severity": severity,
+                    "detector": a.detector.as_str(),
+                    "baseline_value": a.baseline,
+                    "current_value": a.current,
+                    "score": a.score,
+                }),
+            )
+            .await;
+            return Ok((true, false));
+        }
+        return Ok((false, false));
+    }
+
+    let resolve = a.score < RESOLVE_SCORE;
+    let updated = sqlx::query(
+        "UPDATE performance_anomalies
+         SET last_evaluated_at = NOW(),
+             score = $4,
+             current_value = $5,
+             resolved = $6,
+             resolved_at = CASE WHEN $6 THEN NOW() END
+         WHERE contract_id = $1 AND metric_type = $2
+           AND function_name IS NOT DISTINCT FROM $3 AND NOT resolved",
+    )
+    .bind(series.contract_id)
+    .bind(series.metric_type)
+    .bind(&series.function_name)
+    .bind(a.score)
+    .bind(a.current)
+    .bind(resolve)
+    .execute(pool)
+    .await?;
+    Ok((false, resolve && updated.rows_affected() > 0))
+}
+
+/// Raise or resolve each enabled config's alert from the detection window.
+async fn evaluate_alert_configs(
+    pool: &PgPool,
+    window_start: DateTime<Utc>,
+    summary: &mut PassSummary,
+) -> Result<(), sqlx::Error> {
+    let configs: Vec<ConfigWindow> = sqlx::query_as(
+        "SELECT c.id, c.contract_id, c.metric_type, c.threshold_type,
+                c.threshold_value::float8 AS threshold_value, c.severity,
+                w.avg_value, w.p95_value, w.p99_value, w.samples
+         FROM performance_alert_configs c
+         JOIN LATERAL (
+             SELECT AVG(m.value)::float8 AS avg_value,
+                    COALESCE(MAX(m.p95)::float8,
+                             percentile_cont(0.95) WITHIN GROUP (ORDER BY m.value)) AS p95_value,
+                    COALESCE(MAX(m.p99)::float8,
+                             percentile_cont(0.99) WITHIN GROUP (ORDER BY m.value)) AS p99_value,
+                    COUNT(*) AS samples
+             FROM performance_metrics m
+             WHERE m.contract_id = c.contract_id
+               AND m.metric_type = c.metric_type
+               AND m.timestamp > $1
+         ) w ON w.samples > 0
+         WHERE c.enabled",
+    )
+    .bind(window_start)
+    .fetch_all(pool)
+    .await?;
+
+    for config in configs {
+        let Some(threshold) = ThresholdType::parse(&config.threshold_type) else {
+            tracing::debug!(
+                config_id = %config.id,
+                threshold_type = %config.threshold_type,
+                "performance: skipping unknown threshold type"
+            );
+            continue;
+        };
+        let observed = threshold.observed(&config.window);
+        if !threshold.breached(observed, config.threshold_value) {
+            let resolved = sqlx::query(
+                "UPDATE performance_alerts SET resolved = TRUE, resolved_at = NOW()
+                 WHERE alert_config_id = $1 AND NOT resolved",
+            )
+            .bind(config.id)
+            .execute(pool)
+            .await?;
+            summary.alerts_resolved += resolved.rows_affected() as usize;
+            continue;
+        }
+
+        let message = format!(
+            "{} {} {:.4}: observed {:.4} over {} samples",
+            metric_type_name(config.metric_type),
+            threshold.as_str(),
+            config.threshold_value,
+            observed,
+            config.window.samples
+        );
+        let (alert_id, inserted): (Uuid, bool) = sqlx::query_as(
+            "INSERT INTO performance_alerts
+                (contract_id, metric_type, threshold_type, threshold_value, current_value,
+                 severity, message, alert_config_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT (alert_config_id) WHERE NOT resolved
+             DO UPDATE SET current_value = EXCLUDED.current_value, message = EXCLUDED.message
+             RETURNING id, (xmax = 0) AS inserted",
+        )
+        .bind(config.contract_id)
+        .bind(config.metric_type)
+        .bind(threshold.as_str())
+        .bind(config.threshold_value)
+        .bind(observed)
+        .bind(config.severity)
+        .bind(&message)
+        .bind(config.id)
+        .fetch_one(pool)
+        .await?;
+
+        if inserted {
+            summary.alerts_raised += 1;
+            PERFORMANCE_ALERTS_FIRED
+                .with_label_values(&[severity_name(config.severity)])
+                .inc();
+            webhooks::emit(
+                pool,
+                WebhookEvent::PerformanceAlertTriggered,
+                config.contract_id,
+                json!({
+                    "alert_id": alert_id,
+                    "alert_config_id": config.id,
+                    "metric_type": config.metric_type,
+                    "threshold_type": threshold.as_str(),
+                    "threshold_value": config.threshold_value,
+                    "current_value": observed,
+                    "severity": config.severity,
+                    "message": message,
+                }),
+            )
+            .await;
+        }
+    }
+    Ok(())
+}
+
+fn severity_name(severity: AlertSeverity) -> &'static str {
+    match severity {
+        AlertSeverity::Info => "info",
+        AlertSeverity::Warning => "warning",
+        AlertSeverity::Critical => "critical",
+    }
+}
+
+/// Recompute the current and previous `granularity` buckets (`hour` or
+/// `day`) for every series, so late samples are folded in.
+pub async fn roll_up_trends(pool: &PgPool, granularity: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "WITH agg AS (
+             SELECT contract_id, metric_type, function_name,
+                    date_trunc($1, timestamp) AS timeframe_start,
+                    AVG(value) AS avg_value, MIN(value) AS min_value, MAX(value) AS max_value,
+                    percentile_cont(0.5) WITHIN GROUP (ORDER BY value) AS p50_value,
+                    percentile_cont(0.95) WITHIN GROUP (ORDER BY value) AS p95_value,
+                    percentile_cont(0.99) WITHIN GROUP (ORDER BY value) AS p99_value,
+                    COUNT(*)::int AS sample_count
+             FROM performance_metrics
+             WHERE timestamp >= date_trunc($1, NOW()) - 2 * ('1 ' || $1)::interval
+             GROUP BY 1, 2, 3, 4
+         ),
+         changes AS (
+             SELECT agg.*,
+                    ROUND((avg_value - prev) / NULLIF(ABS(prev), 0) * 100, 2) AS change_percent
+             FROM (
+                 SELECT agg.*, LAG(avg_value) OVER (
+                     PARTITION BY contract_id, metric_type, function_name
+                     ORDER BY timeframe_start
+                 ) AS prev
+                 FROM agg
+             ) agg
+         )
+         INSERT INTO performance_trends
+             (contract_id, function_name, metric_type, granularity, timeframe_start,
+              timeframe_end, avg_value, min_value, max_value, p50_value, p95_value,
+              p99_value, sample_count, trend_direction, change_percent, calculated_at)
+         SELECT contract_id, function_name, metric_type, $1, timeframe_start,
+                timeframe_start + ('1 ' || $1)::interval, avg_value, min_value, max_value,
+                p50_value, p95_value, p99_value, sample_count,
+                CASE
+                    WHEN change_percent IS NULL THEN NULL
+                    WHEN change_percent > $2 THEN 'increasing'
+                    WHEN change_percent < -$2 THEN 'decreasing'
+                    ELSE 'stable'
+                END,
+                LEAST(GREATEST(change_percent, -99999999.99), 99999999.99),
+                NOW()
+         FROM changes
+         WHERE timeframe_start >= date_trunc($1, NOW()) - ('1 ' || $1)::interval
+         ON CONFLICT (contract_id, metric_type, (COALESCE(function_name, '')), granularity,
+                      timeframe_start)
+         DO UPDATE SET
+             avg_value = EXCLUDED.avg_value,
+             min_value = EXCLUDED.min_value,
+             max_value = EXCLUDED.max_value,
+             p50_value = EXCLUDED.p50_value,
+             p95_value = EXCLUDED.p95_value,
+             p99_value = EXCLUDED.p99_value,
+             sample_count = EXCLUDED.sample_count,
+             trend_direction = EXCLUDED.trend_direction,
+             change_percent = EXCLUDED.change_percent,
+             calculated_at = NOW()",
+    )
+    .bind(granularity)
+    .bind(STABLE_CHANGE_PERCENT)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// One detection pass over every series with samples in the window.
+pub async fn run_detection(pool: &PgPool) -> Result<PassSummary, sqlx::Error> {
+    let now = Utc::now();
+    let window = detection_window();
+    let mut summary = PassSummary::default();
+
+    let series: Vec<Series> = sqlx::query_as(
+        "SELECT DISTINCT contract_id, metric_type, function_name
+         FROM performance_metrics WHERE timestamp > $1",
+    )
+    .bind(now - window)
+    .fetch_all(pool)
+    .await?;
+
+    for s in &series {
+        let assessment = match assess_series(pool, s, now, window).await {
+            Ok(Some(a)) => a,
+            Ok(None) => continue,
+            Err(err) => {
+                tracing::warn!(contract_id = %s.contract_id, error = ?err, "performance: series assessment failed");
+                continue;
+            }
+        };
+        let (opened, resolved) = apply_assessment(pool, s, &assessment).await?;
+        summary.anomalies_opened += opened as usize;
+        summary.anomalies_resolved += resolved as usize;
+    }
+
+    evaluate_alert_configs(pool, now - window, &mut summary).await?;
+    roll_up_trends(pool, "hour").await?;
+    roll_up_trends(pool, "day").await?;
+
+    let open: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM performance_anomalies WHERE NOT resolved")
+            .fetch_one(pool)
+            .await?;
+    PERFORMANCE_ANOMALIES_OPEN.set(open);
+    Ok(summary)
+}
+
+pub fn spawn_performance_monitor(pool: PgPool) {
+    let interval_secs = std::env::var("PERFORMANCE_DETECTION_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_DETECTION_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match run_detection(&pool).await {
+                Ok(summary) if summary == PassSummary::default() => {}
+                Ok(summary) => tracing::info!(?summary, "performance: detection pass"),
+                Err(err) => tracing::error!(error = ?err, "performance: detection pass failed"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noisy(center: f64, n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| center + if i % 2 == 0 { 1.0 } else { -1.0 })
+            .collect()
+    }
+
+    #[test]
+    fn rolling_detectors_flag_increases_only() {
+        let baseline = noisy(100.0, 30);
+        let spike = assess(&[134.0, 136.0], &baseline, &[]).unwrap();
+        assert!(spike.score >= OPEN_SCORE, "{:?}", spike);
+        assert_eq!(spike.severity(), Some(AlertSeverity::Critical));
+
+        let drop = assess(&[80.0], &baseline, &[]).unwrap();
+        assert!(drop.severity().is_none());
+        let normal = assess(&[100.5], &baseline, &[]).unwrap();
+        assert!(normal.score < RESOLVE_SCORE);
+    }
+
+    #[test]
+    fn flat_baseline_uses_noise_floor() {
+        let baseline = vec![50.0; 20];
+        let a = zscore(&baseline, 51.0).unwrap();
+        // σ floors at 5% of 50 = 2.5
+        assert!((a.score - 0.4).abs() < 1e-9);
+        assert!(zscore(&baseline[..5], 51.0).is_none());
+    }
+
+    #[test]
+    fn ewma_tracks_level_shifts() {
+        let mut history = noisy(10.0, 20);
+        history.extend(noisy(20.0, 20));
+        let e = ewma(&history, 20.0).unwrap();
+        assert!((e.baseline - 20.0).abs() < 1.0, "{:?}", e);
+        assert!(e.score.abs() < OPEN_SCORE);
+        // The plain mean is still dragged down by the old level.
+        let z = zscore(&history, 20.0).unwrap();
+        assert!((z.baseline - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn seasonal_baseline_overrides_rolling_once_available() {
+        // Last hour is quiet, but this time of day is always busy.
+        let baseline = noisy(100.0, 30);
+        let seasons = [195.0, 200.0, 205.0, 198.0, 202.0];
+        let a = assess(&[200.0], &baseline, &seasons).unwrap();
+        assert_eq!(a.detector, Detector::Seasonal);
+        assert!(a.severity().is_none(), "{:?}", a);
+
+        let a = assess(&[200.0], &baseline, &seasons[..2]).unwrap();
+        assert_ne!(a.detector, Detector::Seasonal);
+        assert!(a.severity().is_some());
+    }
+
+    #[test]
+    fn threshold_types_round_trip_and_compare() {
+        for t in ThresholdType::ALL {
+            assert_eq!(ThresholdType::parse(t.as_str()), Some(t));
+        }
+        assert_eq!(ThresholdType::parse("p50_exceeds"), None);
+        let window = WindowStats {
+            avg_value: 10.0,
+            p95_value: 40.0,
+            p99_value: 90.0,
+            samples: 12,
+        };
+        assert!(
+            ThresholdType::P99Exceeds.breached(ThresholdType::P99Exceeds.observed(&window), 80.0)
+        );
+        assert!(
+            !ThresholdType::P95Exceeds.breached(ThresholdType::P95Exceeds.observed(&window), 80.0)
+        );
+        assert!(
+            ThresholdType::ValueBelow.breached(ThresholdType::ValueBelow.observed(&window), 20.0)
+        );
+    }
+}
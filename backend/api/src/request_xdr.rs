@@ -0,0 +1,107 @@
+//! Bounded decoding of XDR taken from request bodies
+//!
+//! Decoding recurses once per nested XDR value, so `Limits::none()` lets a
+//! client choose how deep the API's stack goes. Anything decoded from a
+//! request body or an RPC reply uses [`limits`] instead.
+
+use stellar_xdr::curr::{Limits, ReadXdr};
+
+/// Deepest XDR nesting accepted.
+pub const MAX_DEPTH: u32 = 500;
+/// Most decoded bytes accepted, matching axum's default 2 MiB body limit.
+pub const MAX_LEN: usize = 2 * 1024 * 1024;
+
+pub fn limits() -> Limits {
+    Limits {
+        depth: MAX_DEPTH,
+        len: MAX_LEN,
+    }
+}
+
+/// Decode base64 XDR within [`limits`].
+pub fn from_base64<T: ReadXdr>(encoded: &str) -> Result<T, stellar_xdr::curr::Error> {
+    T::from_xdr_base64(encoded, limits())
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use stellar_xdr::curr::{
+        Hash, HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Limits, Memo, MuxedAccount,
+        Operation, OperationBody, Preconditions, ScAddress, ScSymbol, ScVal, ScVec, SequenceNumber,
+        Transaction, TransactionEnvelope, TransactionExt, TransactionV1Envelope, Uint256, VecM,
+        WriteXdr,
+    };
+
+    /// An unsigned envelope whose `upgrade` call argument nests `depth` vectors.
+    pub fn deep_envelope(depth: usize) -> String {
+        let mut value = ScVal::Void;
+        for _ in 0..depth {
+            value = ScVal::Vec(Some(ScVec(vec![value].try_into().unwrap())));
+        }
+        envelope(HostFunction::InvokeContract(InvokeContractArgs {
+            contract_address: ScAddress::Contract(Hash([2; 32])),
+            function_name: ScSymbol("upgrade".try_into().unwrap()),
+            args: vec![value].try_into().unwrap(),
+        }))
+    }
+
+    /// An unsigned envelope uploading `len` bytes of WASM.
+    pub fn large_envelope(len: usize) -> String {
+        envelope(HostFunction::UploadContractWasm(
+            vec![0; len].try_into().unwrap(),
+        ))
+    }
+
+    fn envelope(host_function: HostFunction) -> String {
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([1; 32])),
+            fee: 100,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                    host_function,
+                    auth: VecM::default(),
+                }),
+            }]
+            .try_into()
+            .unwrap(),
+            ext: TransactionExt::V0,
+        };
+        TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx,
+            signatures: VecM::default(),
+        })
+        .to_xdr_base64(Limits::none())
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stellar_xdr::curr::TransactionEnvelope;
+
+    #[test]
+    fn nesting_beyond_the_depth_limit_is_rejected() {
+        let shallow = test_support::deep_envelope(16);
+        assert!(from_base64::<TransactionEnvelope>(&shallow).is_ok());
+
+        let deep = test_support::deep_envelope(MAX_DEPTH as usize + 10);
+        assert!(matches!(
+            from_base64::<TransactionEnvelope>(&deep),
+            Err(stellar_xdr::curr::Error::DepthLimitExceeded)
+        ));
+    }
+
+    #[test]
+    fn input_beyond_the_length_limit_is_rejected() {
+        let large = test_support::large_envelope(MAX_LEN);
+        assert!(matches!(
+            from_base64::<TransactionEnvelope>(&large),
+            Err(stellar_xdr::curr::Error::LengthLimitExceeded)
+        ));
+    }
+}
@@ -1,10 +1,15 @@
 use axum::{
-    routing::{get, patch, post},
+    routing::{delete, get, patch, post},
     Router,
 };
 
 use crate::{
+    ab_test_handlers,
     batch_verify_handlers,
+    canary_handlers,
+    deployment_handlers,
+    performance_handlers,
+    trust_handlers,
     handlers,
     metrics_handler,
     breaking_changes,
@@ -64,6 +69,10 @@ pub fn contract_routes() -> Router<AppState> {
             "/api/contracts/:id/versions",
             get(handlers::get_contract_versions).post(handlers::create_contract_version),
         )
+        .route(
+            "/api/contracts/:id/versions/resolve",
+            get(handlers::resolve_contract_version),
+        )
         .route(
             "/api/contracts/:id/changelog",
             get(handlers::get_contract_changelog),
@@ -105,10 +114,6 @@ pub fn contract_routes() -> Router<AppState> {
             "/api/contracts/:id/analytics",
             get(handlers::get_contract_analytics),
         )
-        .route(
-            "/api/contracts/:id/trust-score",
-            get(handlers::get_trust_score),
-        )
         .route(
             "/api/contracts/:id/dependencies",
             get(handlers::get_contract_dependencies),
@@ -125,7 +130,6 @@ pub fn contract_routes() -> Router<AppState> {
         .route("/api/contracts/:id/deprecate", post(deprecation_handlers::deprecate_contract))
         .route("/api/contracts/:id/state/:key", get(handlers::get_contract_state).post(handlers::update_contract_state))
         .route("/api/contracts/:id/analytics", get(handlers::get_contract_analytics))
-        .route("/api/contracts/:id/trust-score", get(handlers::get_trust_score))
         .route("/api/contracts/:id/dependencies", get(handlers::get_contract_dependencies))
         .route("/api/contracts/:id/dependents", get(handlers::get_contract_dependents))
         .route("/api/contracts/:id/impact", get(handlers::get_impact_analysis))
@@ -135,10 +139,6 @@ pub fn contract_routes() -> Router<AppState> {
             "/api/contracts/batch-verify",
             post(batch_verify_handlers::batch_verify_contracts),
         )
-        .route(
-            "/api/contracts/:id/performance",
-            get(handlers::get_contract_performance),
-        )
         .route(
             "/api/contracts/:id/metrics",
             get(custom_metrics_handlers::get_contract_metrics)
@@ -161,6 +161,23 @@ pub fn contract_routes() -> Router<AppState> {
             "/api/contracts/:id/compatibility-matrix/test",
             post(compatibility_testing_handlers::run_compatibility_test),
         )
+        .route(
+            "/api/contracts/:id/compatibility-matrix/runs",
+            get(compatibility_testing_handlers::list_compatibility_runs),
+        )
+        .route(
+            "/api/contracts/:id/compatibility-matrix/runs/:run_id",
+            get(compatibility_testing_handlers::get_compatibility_run),
+        )
+        .route(
+            "/api/contracts/:id/compatibility-matrix/fixtures",
+            get(compatibility_testing_handlers::list_test_fixtures)
+                .post(compatibility_testing_handlers::upsert_test_fixture),
+        )
+        .route(
+            "/api/contracts/:id/compatibility-matrix/fixtures/:fixture_id",
+            delete(compatibility_testing_handlers::delete_test_fixture),
+        )
         .route(
             "/api/contracts/:id/compatibility-matrix/history",
             get(compatibility_testing_handlers::get_compatibility_history),
@@ -173,11 +190,6 @@ pub fn contract_routes() -> Router<AppState> {
             "/api/contracts/:id/compatibility-matrix/notifications/read",
             post(compatibility_testing_handlers::mark_notifications_read),
         )
-        .route(
-            "/api/contracts/:id/deployments/status",
-            get(handlers::get_deployment_status),
-        )
-        .route("/api/deployments/green", post(handlers::deploy_green))
     // TODO: backup_routes, notification_routes, and post_incident_routes
     // are available in the api library crate but need architectural refactoring
     // to be integrated with the main AppState
@@ -236,12 +248,146 @@ pub fn compatibility_dashboard_routes() -> Router<AppState> {
         )
 }
 
+pub fn deployment_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/contracts/:id/deployments/status",
+            get(deployment_handlers::get_deployment_status),
+        )
+        .route(
+            "/api/contracts/:id/deployments/switches",
+            get(deployment_handlers::list_deployment_switches),
+        )
+        .route(
+            "/api/deployments/green",
+            post(deployment_handlers::deploy_green),
+        )
+        .route(
+            "/api/deployments/switch",
+            post(deployment_handlers::switch_deployment),
+        )
+        .route(
+            "/api/deployments/rollback",
+            post(deployment_handlers::rollback_deployment),
+        )
+        .route(
+            "/api/deployments/health-check",
+            post(deployment_handlers::report_health_check),
+        )
+}
+
 pub fn canary_routes() -> Router<AppState> {
     Router::new()
+        .route(
+            "/api/canaries",
+            get(canary_handlers::list_canaries).post(canary_handlers::create_canary),
+        )
+        .route("/api/canaries/:id", get(canary_handlers::get_canary))
+        .route(
+            "/api/canaries/:id/advance",
+            post(canary_handlers::advance_canary),
+        )
+        .route(
+            "/api/canaries/:id/rollback",
+            post(canary_handlers::rollback_canary),
+        )
+        .route("/api/canaries/:id/pause", post(canary_handlers::pause_canary))
+        .route(
+            "/api/canaries/:id/resume",
+            post(canary_handlers::resume_canary),
+        )
+        .route(
+            "/api/canaries/:id/metrics",
+            post(canary_handlers::record_canary_metric),
+        )
+        .route(
+            "/api/canaries/:id/assignment",
+            get(canary_handlers::get_canary_assignment),
+        )
 }
 pub fn ab_test_routes() -> Router<AppState> {
     Router::new()
+        .route(
+            "/api/ab-tests",
+            get(ab_test_handlers::list_ab_tests).post(ab_test_handlers::create_ab_test),
+        )
+        .route("/api/ab-tests/:id", get(ab_test_handlers::get_ab_test))
+        .route("/api/ab-tests/:id/start", post(ab_test_handlers::start_ab_test))
+        .route("/api/ab-tests/:id/pause", post(ab_test_handlers::pause_ab_test))
+        .route(
+            "/api/ab-tests/:id/cancel",
+            post(ab_test_handlers::cancel_ab_test),
+        )
+        .route("/api/ab-tests/:id/variant", get(ab_test_handlers::get_variant))
+        .route(
+            "/api/ab-tests/:id/metrics",
+            post(ab_test_handlers::record_metric),
+        )
+        .route(
+            "/api/ab-tests/:id/results",
+            get(ab_test_handlers::get_results),
+        )
 }
 pub fn performance_routes() -> Router<AppState> {
     Router::new()
+        .route(
+            "/api/performance/metrics",
+            post(performance_handlers::record_metric),
+        )
+        .route(
+            "/api/performance/metrics/batch",
+            post(performance_handlers::record_metrics_batch),
+        )
+        .route(
+            "/api/performance/alerts/:id/acknowledge",
+            post(performance_handlers::acknowledge_alert),
+        )
+        .route(
+            "/api/performance/alert-configs",
+            post(performance_handlers::upsert_alert_config),
+        )
+        .route(
+            "/api/performance/alert-configs/:id",
+            delete(performance_handlers::delete_alert_config),
+        )
+        .route(
+            "/api/contracts/:id/performance",
+            get(performance_handlers::get_contract_performance),
+        )
+        .route(
+            "/api/contracts/:id/performance/metrics",
+            get(performance_handlers::list_metrics),
+        )
+        .route(
+            "/api/contracts/:id/performance/trends",
+            get(performance_handlers::list_trends),
+        )
+        .route(
+            "/api/contracts/:id/performance/anomalies",
+            get(performance_handlers::list_anomalies),
+        )
+        .route(
+            "/api/contracts/:id/performance/alerts",
+            get(performance_handlers::list_alerts),
+        )
+        .route(
+            "/api/contracts/:id/performance/alert-configs",
+            get(performance_handlers::list_alert_configs),
+        )
+}
+
+pub fn trust_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/contracts/:id/trust-score",
+            get(trust_handlers::get_trust_score),
+        )
+        .route(
+            "/api/contracts/:id/trust-score/history",
+            get(trust_handlers::get_trust_score_history),
+        )
+        .route(
+            "/api/contracts/:id/trust-badge.svg",
+            get(trust_handlers::get_trust_badge),
+        )
 }
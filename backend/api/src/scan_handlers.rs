@@ -1,53 +1,90 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
     Json,
 };
+use serde_json::{json, Value};
+use shared::RegistryError;
 use uuid::Uuid;
 
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::db_internal_error;
+use crate::job_worker::ScanPayload;
+use crate::jobs::{self, JobKind, NewJob};
+use crate::scanner_service::{self, ScanReport, ScanRequest, VulnerabilityPayload};
 use crate::state::AppState;
-use crate::scanner_service::{self, VulnerabilityPayload, ScanRequest};
+
+fn scan_error(operation: &str, err: RegistryError) -> ApiError {
+    match err {
+        RegistryError::InvalidInput(msg) => ApiError::bad_request("InvalidScanInput", msg),
+        RegistryError::Database(e) => db_internal_error(operation, e),
+        other => ApiError::internal(other.to_string()),
+    }
+}
 
 pub async fn ingest_cves(
     State(state): State<AppState>,
     Json(payload): Json<Vec<VulnerabilityPayload>>,
-) -> impl IntoResponse {
-    match scanner_service::sync_cves(&state.pool, payload).await {
-        Ok(count) => {
-            let msg = format!("Ingested {} CVEs successfully", count);
-            (StatusCode::OK, Json(msg)).into_response()
-        }
-        Err(e) => {
-            let err = format!("Database error ingesting CVEs: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response()
-        }
-    }
+) -> ApiResult<Json<String>> {
+    let count = scanner_service::sync_cves(&state.db, payload)
+        .await
+        .map_err(|e| scan_error("ingest CVEs", e))?;
+    Ok(Json(format!("Ingested {} CVEs successfully", count)))
 }
 
+/// POST /api/contracts/:id/scan — validate the dependency set and queue the
+/// scan. Responds `202 Accepted`; the job result is the `ScanReport`.
 pub async fn scan_contract(
     State(state): State<AppState>,
     Path(contract_id): Path<Uuid>,
     Json(payload): Json<ScanRequest>,
-) -> impl IntoResponse {
-    match scanner_service::perform_scan(&state.pool, contract_id, payload).await {
-        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
-        Err(e) => {
-            let err = format!("Failed to run contract scan: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response()
-        }
+) -> ApiResult<(StatusCode, Json<Value>)> {
+    // Reject malformed lock files now rather than in a failed job.
+    scanner_service::collect_dependencies(payload.clone())
+        .map_err(|e| scan_error("validate scan request", e))?;
+
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM contracts WHERE id = $1)")
+        .bind(contract_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| db_internal_error("check contract for scan", e))?;
+    if !exists {
+        return Err(ApiError::not_found(
+            "ContractNotFound",
+            format!("No contract found with ID: {}", contract_id),
+        ));
     }
+
+    let job = jobs::enqueue(
+        &state.db,
+        NewJob::new(
+            JobKind::ScanContract,
+            json!(ScanPayload {
+                contract_id,
+                request: payload,
+            }),
+        )
+        .contract(contract_id),
+    )
+    .await
+    .map_err(|e| db_internal_error("enqueue contract scan", e))?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "status": job.status,
+            "job_id": job.id,
+            "job_url": format!("/api/jobs/{}", job.id),
+        })),
+    ))
 }
 
 pub async fn get_scan_report(
     State(state): State<AppState>,
     Path(contract_id): Path<Uuid>,
-) -> impl IntoResponse {
-    match scanner_service::get_history(&state.pool, contract_id).await {
-        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
-        Err(e) => {
-            let err = format!("Failed to retrieve scan history: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response()
-        }
-    }
+) -> ApiResult<Json<ScanReport>> {
+    scanner_service::get_history(&state.db, contract_id)
+        .await
+        .map(Json)
+        .map_err(|e| db_internal_error("retrieve scan history", e))
 }
@@ -1,26 +1,57 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use shared::advisory::AffectedRanges;
+use shared::lockfile::parse_cargo_lock;
+use shared::{RegistryError, SemVer};
 use sqlx::{FromRow, PgPool, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::webhooks::{self, WebhookEvent};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VulnerabilityPayload {
     pub cve_id: String,
     pub description: Option<String>,
     pub severity: String,
     pub package_name: String,
+    /// Version requirements that contain the fix, e.g. `>= 1.2.3`
     pub patched_versions: Vec<String>,
+    /// Version requirements that never had the bug, e.g. `< 1.0.0`
+    #[serde(default)]
+    pub unaffected_versions: Vec<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    #[serde(default = "default_source")]
+    pub source: String,
+}
+
+fn default_source() -> String {
+    "manual".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DependencyDescriptor {
     pub package_name: String,
     pub version: String,
+    #[serde(default = "default_direct")]
+    pub is_direct: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+fn default_direct() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ScanRequest {
+    #[serde(default)]
     pub dependencies: Vec<DependencyDescriptor>,
+    /// Contents of the contract's `Cargo.lock`; every locked crate,
+    /// including transitive ones, is scanned.
+    pub cargo_lock: Option<String>,
 }
 
 #[derive(Debug, Serialize, FromRow)]
@@ -31,6 +62,16 @@ pub struct ScanResultRow {
     pub recommended_version: Option<String>,
     pub severity: String,
     pub is_false_positive: bool,
+    pub aliases: Vec<String>,
+}
+
+/// A dependency that couldn't be checked, e.g. a git revision without a
+/// semver version.
+#[derive(Debug, Serialize)]
+pub struct SkippedDependency {
+    pub package_name: String,
+    pub version: String,
+    pub reason: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -38,20 +79,53 @@ pub struct ScanReport {
     pub contract_id: Uuid,
     pub findings: Vec<ScanResultRow>,
     pub scanned_dependencies_count: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedDependency>,
+}
+
+struct AdvisoryRow {
+    cve_id: String,
+    severity: String,
+    aliases: Vec<String>,
+    ranges: AffectedRanges,
 }
 
-pub async fn sync_cves(pool: &PgPool, payloads: Vec<VulnerabilityPayload>) -> Result<usize, sqlx::Error> {
-    let mut count = 0;
-    for payload in payloads {
+pub async fn sync_cves(
+    pool: &PgPool,
+    payloads: Vec<VulnerabilityPayload>,
+) -> shared::Result<usize> {
+    // Reject the whole batch rather than store requirements the scanner
+    // would have to ignore.
+    for payload in &payloads {
+        AffectedRanges::parse(&payload.patched_versions, &payload.unaffected_versions).map_err(
+            |e| match e {
+                RegistryError::InvalidInput(msg) => {
+                    RegistryError::InvalidInput(format!("{}: {}", payload.cve_id, msg))
+                }
+                other => other,
+            },
+        )?;
+    }
+
+    let mut tx = pool.begin().await?;
+    for payload in &payloads {
         sqlx::query(
             r#"
-            INSERT INTO cve_vulnerabilities (cve_id, description, severity, package_name, patched_versions, updated_at)
-            VALUES ($1, $2, $3, $4, $5, NOW())
+            INSERT INTO cve_vulnerabilities
+                (cve_id, description, severity, package_name, patched_versions,
+                 unaffected_versions, aliases, title, url, source, published_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())
             ON CONFLICT (cve_id) DO UPDATE SET
                 description = EXCLUDED.description,
                 severity = EXCLUDED.severity,
                 package_name = EXCLUDED.package_name,
                 patched_versions = EXCLUDED.patched_versions,
+                unaffected_versions = EXCLUDED.unaffected_versions,
+                aliases = EXCLUDED.aliases,
+                title = EXCLUDED.title,
+                url = EXCLUDED.url,
+                source = EXCLUDED.source,
+                published_at = EXCLUDED.published_at,
                 updated_at = NOW()
             "#,
         )
@@ -60,97 +134,223 @@ pub async fn sync_cves(pool: &PgPool, payloads: Vec<VulnerabilityPayload>) -> Re
         .bind(&payload.severity)
         .bind(&payload.package_name)
         .bind(&payload.patched_versions)
-        .execute(pool)
+        .bind(&payload.unaffected_versions)
+        .bind(&payload.aliases)
+        .bind(&payload.title)
+        .bind(&payload.url)
+        .bind(&payload.source)
+        .bind(payload.published_at)
+        .execute(&mut *tx)
         .await?;
-        count += 1;
     }
-    Ok(count)
+    tx.commit().await?;
+    Ok(payloads.len())
 }
 
-pub async fn perform_scan(pool: &PgPool, contract_id: Uuid, request: ScanRequest) -> Result<ScanReport, sqlx::Error> {
-    let mut findings = Vec::new();
+/// Explicit dependencies plus everything locked in `Cargo.lock`, one entry
+/// per (package, version).
+pub(crate) fn collect_dependencies(
+    request: ScanRequest,
+) -> shared::Result<Vec<(DependencyDescriptor, Option<String>)>> {
+    let mut dependencies: Vec<(DependencyDescriptor, Option<String>)> = Vec::new();
+    if let Some(lockfile) = request.cargo_lock.as_deref() {
+        for package in parse_cargo_lock(lockfile)? {
+            dependencies.push((
+                DependencyDescriptor {
+                    package_name: package.name,
+                    version: package.version,
+                    is_direct: package.is_direct,
+                },
+                Some(package.source),
+            ));
+        }
+    }
+    for dep in request.dependencies {
+        let existing = dependencies
+            .iter()
+            .any(|(d, _)| d.package_name == dep.package_name && d.version == dep.version);
+        if !existing {
+            dependencies.push((dep, None));
+        }
+    }
+    Ok(dependencies)
+}
+
+pub async fn perform_scan(
+    pool: &PgPool,
+    contract_id: Uuid,
+    request: ScanRequest,
+) -> shared::Result<ScanReport> {
+    let dependencies = collect_dependencies(request)?;
+
+    let package_names: Vec<String> = dependencies
+        .iter()
+        .map(|(d, _)| d.package_name.clone())
+        .collect();
+    let mut advisories: HashMap<String, Vec<AdvisoryRow>> = HashMap::new();
+    let rows = sqlx::query(
+        r#"
+        SELECT cve_id, severity, package_name, patched_versions, unaffected_versions, aliases
+        FROM cve_vulnerabilities
+        WHERE package_name = ANY($1) AND withdrawn_at IS NULL
+        "#,
+    )
+    .bind(&package_names)
+    .fetch_all(pool)
+    .await?;
+    for row in rows {
+        let cve_id: String = row.get("cve_id");
+        let patched: Vec<String> = row.get("patched_versions");
+        let unaffected: Vec<String> = row.get("unaffected_versions");
+        // Entries stored before requirements were validated may not parse;
+        // skipping them avoids flagging every version of the package.
+        let ranges = match AffectedRanges::parse(&patched, &unaffected) {
+            Ok(ranges) => ranges,
+            Err(e) => {
+                tracing::warn!(cve_id = %cve_id, error = %e, "skipping advisory with invalid version ranges");
+                continue;
+            }
+        };
+        advisories
+            .entry(row.get("package_name"))
+            .or_default()
+            .push(AdvisoryRow {
+                cve_id,
+                severity: row.get("severity"),
+                aliases: row.get("aliases"),
+                ranges,
+            });
+    }
+
+    let mut tx = pool.begin().await?;
+
+    // A scan describes the contract's current dependency set.
+    sqlx::query("DELETE FROM contract_package_dependencies WHERE contract_id = $1")
+        .bind(contract_id)
+        .execute(&mut *tx)
+        .await?;
 
-    // Insert dependencies first
-    for dep in &request.dependencies {
+    let mut findings = Vec::new();
+    // Indexes into `findings` that this scan recorded for the first time.
+    let mut new_findings = Vec::new();
+    let mut skipped = Vec::new();
+    for (dep, source) in &dependencies {
         sqlx::query(
             r#"
-            INSERT INTO contract_dependencies (contract_id, package_name, version)
-            VALUES ($1, $2, $3)
-            ON CONFLICT (contract_id, package_name) DO UPDATE SET
-                version = EXCLUDED.version
+            INSERT INTO contract_package_dependencies (contract_id, package_name, version, is_direct, source)
+            VALUES ($1, $2, $3, $4, $5)
             "#,
         )
         .bind(contract_id)
         .bind(&dep.package_name)
         .bind(&dep.version)
-        .execute(pool)
-        .await?;
-        
-        // Match against vulnerabilities strictly to minimize false positives (<1%)
-        // This query matches if the vulnerability exists for the package and version is not in patched_versions
-        let cves = sqlx::query(
-            r#"
-            SELECT cve_id, severity, patched_versions
-            FROM cve_vulnerabilities
-            WHERE package_name = $1
-            "#,
-        )
-        .bind(&dep.package_name)
-        .fetch_all(pool)
+        .bind(dep.is_direct)
+        .bind(source)
+        .execute(&mut *tx)
         .await?;
 
-        for cve in cves {
-            // Very simple semantic version match placeholder - if it's not patched, it's vulnerable.
-            // A rigid check to maintain <1% false positive. If no patch versions matching exactly, assume vulnerable
-            let patched_versions: Vec<String> = cve.get("patched_versions");
-            let is_patched = patched_versions.contains(&dep.version);
-            if !is_patched {
-                let rec_version = patched_versions.first().cloned();
-                let cve_id: String = cve.get("cve_id");
-                let severity: String = cve.get("severity");
-                
-                sqlx::query(
-                    r#"
-                    INSERT INTO contract_scan_results (contract_id, cve_id, package_name, current_version, recommended_version)
-                    VALUES ($1, $2, $3, $4, $5)
-                    ON CONFLICT (contract_id, cve_id) DO UPDATE SET
-                        current_version = EXCLUDED.current_version,
-                        recommended_version = EXCLUDED.recommended_version
-                    "#,
-                )
-                .bind(contract_id)
-                .bind(&cve_id)
-                .bind(&dep.package_name)
-                .bind(&dep.version)
-                .bind(&rec_version)
-                .execute(pool)
-                .await?;
-
-                findings.push(ScanResultRow {
-                    cve_id: cve_id,
-                    severity: severity,
-                    package_name: dep.package_name.clone(),
-                    current_version: dep.version.clone(),
-                    recommended_version: rec_version,
-                    is_false_positive: false,
-                });
+        let Some(candidates) = advisories.get(&dep.package_name) else {
+            continue;
+        };
+        let Some(version) = SemVer::parse(&dep.version) else {
+            skipped.push(SkippedDependency {
+                package_name: dep.package_name.clone(),
+                version: dep.version.clone(),
+                reason: "version is not valid semver".to_string(),
+            });
+            continue;
+        };
+
+        for advisory in candidates.iter().filter(|a| a.ranges.is_affected(&version)) {
+            let rec_version = advisory
+                .ranges
+                .recommended_upgrade(&version)
+                .map(|v| v.to_string());
+
+            // xmax = 0 only for rows this statement inserted.
+            let (is_false_positive, inserted): (bool, bool) = sqlx::query_as(
+                r#"
+                INSERT INTO contract_scan_results (contract_id, cve_id, package_name, current_version, recommended_version)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (contract_id, cve_id, package_name, current_version) DO UPDATE SET
+                    recommended_version = EXCLUDED.recommended_version
+                RETURNING is_false_positive, (xmax = 0) AS inserted
+                "#,
+            )
+            .bind(contract_id)
+            .bind(&advisory.cve_id)
+            .bind(&dep.package_name)
+            .bind(&dep.version)
+            .bind(&rec_version)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if inserted && !is_false_positive {
+                new_findings.push(findings.len());
             }
+            findings.push(ScanResultRow {
+                cve_id: advisory.cve_id.clone(),
+                severity: advisory.severity.clone(),
+                package_name: dep.package_name.clone(),
+                current_version: dep.version.clone(),
+                recommended_version: rec_version,
+                is_false_positive,
+                aliases: advisory.aliases.clone(),
+            });
         }
     }
 
+    // Drop findings for dependencies that were upgraded or removed.
+    let cve_ids: Vec<&str> = findings.iter().map(|f| f.cve_id.as_str()).collect();
+    let packages: Vec<&str> = findings.iter().map(|f| f.package_name.as_str()).collect();
+    let versions: Vec<&str> = findings
+        .iter()
+        .map(|f| f.current_version.as_str())
+        .collect();
+    sqlx::query(
+        r#"
+        DELETE FROM contract_scan_results s
+        WHERE s.contract_id = $1
+          AND (s.cve_id, s.package_name, s.current_version) NOT IN (
+              SELECT * FROM UNNEST($2::text[], $3::text[], $4::text[])
+          )
+        "#,
+    )
+    .bind(contract_id)
+    .bind(&cve_ids)
+    .bind(&packages)
+    .bind(&versions)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    if !new_findings.is_empty() {
+        let detected: Vec<&ScanResultRow> = new_findings.iter().map(|&i| &findings[i]).collect();
+        webhooks::emit(
+            pool,
+            WebhookEvent::VulnerabilityDetected,
+            contract_id,
+            serde_json::json!({ "findings": detected }),
+        )
+        .await;
+    }
+
     Ok(ScanReport {
         contract_id,
         findings,
-        scanned_dependencies_count: request.dependencies.len(),
+        scanned_dependencies_count: dependencies.len(),
+        skipped,
     })
 }
 
 pub async fn get_history(pool: &PgPool, contract_id: Uuid) -> Result<ScanReport, sqlx::Error> {
     let rows = sqlx::query_as::<_, ScanResultRow>(
         r#"
-        SELECT s.cve_id, s.package_name, s.current_version, s.recommended_version, c.severity, s.is_false_positive
+        SELECT s.cve_id, s.package_name, s.current_version, s.recommended_version, c.severity,
+               s.is_false_positive, c.aliases
         FROM contract_scan_results s
-        JOIN cve_vulnerabilities c ON s.cve_id = c.cve_id
+        JOIN cve_vulnerabilities c ON s.cve_id = c.cve_id AND c.withdrawn_at IS NULL
         WHERE s.contract_id = $1
         ORDER BY s.created_at DESC
         "#,
@@ -160,7 +360,7 @@ pub async fn get_history(pool: &PgPool, contract_id: Uuid) -> Result<ScanReport,
     .await?;
 
     let dep_count_row = sqlx::query(
-        r#"SELECT COUNT(*) as count FROM contract_dependencies WHERE contract_id = $1"#,
+        r#"SELECT COUNT(*) as count FROM contract_package_dependencies WHERE contract_id = $1"#,
     )
     .bind(contract_id)
     .fetch_one(pool)
@@ -171,5 +371,77 @@ pub async fn get_history(pool: &PgPool, contract_id: Uuid) -> Result<ScanReport,
         contract_id,
         findings: rows,
         scanned_dependencies_count: dep_count as usize,
+        skipped: Vec::new(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lockfile_and_explicit_dependencies_are_merged() {
+        let request = ScanRequest {
+            dependencies: vec![
+                DependencyDescriptor {
+                    package_name: "soroban-sdk".to_string(),
+                    version: "21.7.7".to_string(),
+                    is_direct: true,
+                },
+                DependencyDescriptor {
+                    package_name: "ed25519-dalek".to_string(),
+                    version: "1.0.1".to_string(),
+                    is_direct: true,
+                },
+            ],
+            cargo_lock: Some(
+                r#"
+version = 3
+
+[[package]]
+name = "contract"
+version = "0.1.0"
+dependencies = ["soroban-sdk"]
+
+[[package]]
+name = "soroban-sdk"
+version = "21.7.7"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["curve25519-dalek"]
+
+[[package]]
+name = "curve25519-dalek"
+version = "4.1.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#
+                .to_string(),
+            ),
+        };
+
+        let deps = collect_dependencies(request).unwrap();
+        let summary: Vec<(&str, bool, bool)> = deps
+            .iter()
+            .map(|(d, source)| (d.package_name.as_str(), d.is_direct, source.is_some()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("curve25519-dalek", false, true),
+                ("soroban-sdk", true, true),
+                ("ed25519-dalek", true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_lockfiles_are_rejected() {
+        let request = ScanRequest {
+            cargo_lock: Some("not toml [".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            collect_dependencies(request),
+            Err(RegistryError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,443 @@
+// api/src/search.rs
+// Contract search engine: weighted full-text ranking, fuzzy matching, facet
+// counts and highlighted snippets over `contract_search_documents`
+// (migration 047).
+
+use shared::{
+    Contract, ContractSearchHit, ContractSearchParams, ContractSearchResponse, FacetCount,
+    PaginatedResponse, SearchFacets, SearchHighlights, SortBy, SortOrder,
+};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::db_internal_error;
+
+/// Longest query accepted, in characters.
+pub const MAX_QUERY_LEN: usize = 256;
+
+/// Minimum pg_trgm `word_similarity` for a fuzzy match.
+const FUZZY_THRESHOLD: f32 = 0.4;
+
+/// Shorter queries produce too few trigrams for fuzzy matching to mean much.
+const FUZZY_MIN_LEN: usize = 3;
+
+const TAG_FACET_LIMIT: i64 = 20;
+
+/// `ts_rank_cd` weights for D, C, B, A (publisher, description, tags and
+/// functions, name).
+const RANK_WEIGHTS: &str = "{0.1, 0.2, 0.4, 1.0}";
+
+const NAME_HIGHLIGHT: &str = "StartSel=<mark>, StopSel=</mark>, HighlightAll=true";
+const SNIPPET_HIGHLIGHT: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=24, MinWords=8, FragmentDelimiter=\" … \"";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Facet {
+    Category,
+    Network,
+    Verified,
+}
+
+/// A validated search request.
+#[derive(Debug, Clone)]
+struct SearchRequest {
+    text: Option<String>,
+    fuzzy: bool,
+    facets: bool,
+    category: Option<String>,
+    networks: Vec<String>,
+    verified_only: bool,
+    tags: Vec<String>,
+    sort_by: SortBy,
+    sort_order: SortOrder,
+    page: i64,
+    limit: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct HitRow {
+    #[sqlx(flatten)]
+    contract: Contract,
+    relevance: f32,
+    name_highlight: Option<String>,
+    description_highlight: Option<String>,
+    matched_functions: Vec<String>,
+    matched_events: Vec<String>,
+}
+
+/// Whether `GET /api/contracts` should be answered by the search engine
+/// rather than the plain listing.
+pub fn is_search(params: &ContractSearchParams) -> bool {
+    params
+        .query
+        .as_deref()
+        .is_some_and(|q| !q.trim().is_empty())
+        || params.facets == Some(true)
+        || params.tags.as_ref().is_some_and(|t| !t.is_empty())
+}
+
+/// Trim the raw query, treating blank queries as absent.
+fn normalize_query(raw: Option<&str>) -> ApiResult<Option<String>> {
+    let Some(text) = raw.map(str::trim).filter(|q| !q.is_empty()) else {
+        return Ok(None);
+    };
+    if text.chars().count() > MAX_QUERY_LEN {
+        return Err(ApiError::bad_request(
+            "QueryTooLong",
+            format!("Search query must be at most {} characters", MAX_QUERY_LEN),
+        ));
+    }
+    Ok(Some(text.to_string()))
+}
+
+fn fuzzy_applies(text: &str, requested: bool) -> bool {
+    requested && text.chars().count() >= FUZZY_MIN_LEN
+}
+
+impl SearchRequest {
+    fn from_params(params: &ContractSearchParams) -> ApiResult<Self> {
+        let text = normalize_query(params.query.as_deref())?;
+        let fuzzy = text
+            .as_deref()
+            .is_some_and(|t| fuzzy_applies(t, params.fuzzy.unwrap_or(true)));
+
+        let networks = match (&params.networks, &params.network) {
+            (Some(nets), _) if !nets.is_empty() => nets.iter().map(|n| n.to_string()).collect(),
+            (_, Some(net)) => vec![net.to_string()],
+            _ => Vec::new(),
+        };
+
+        let sort_by = params.sort_by.clone().unwrap_or(if text.is_some() {
+            SortBy::Relevance
+        } else {
+            SortBy::CreatedAt
+        });
+
+        let limit = params.limit.unwrap_or(20).clamp(1, 100);
+        Ok(Self {
+            facets: params.facets.unwrap_or(true),
+            fuzzy,
+            category: params.category.clone().filter(|c| !c.is_empty()),
+            networks,
+            verified_only: params.verified_only.unwrap_or(false),
+            tags: params.tags.clone().unwrap_or_default(),
+            sort_by,
+            sort_order: params.sort_order.clone().unwrap_or(SortOrder::Desc),
+            page: params.page.unwrap_or(1).max(1),
+            limit,
+            text,
+        })
+    }
+
+    fn offset(&self) -> i64 {
+        (self.page - 1) * self.limit
+    }
+
+    /// FROM/WHERE shared by every search query. `exclude` drops one facet's
+    /// own filter so its counts show the alternatives; `join` adds a lateral
+    /// join before the WHERE clause.
+    fn push_from(&self, qb: &mut QueryBuilder<'_, Postgres>, exclude: Option<Facet>, join: &str) {
+        qb.push(
+            " FROM contracts c \
+             LEFT JOIN contract_search_documents d ON d.contract_id = c.id",
+        );
+        if let Some(text) = &self.text {
+            qb.push(" CROSS JOIN LATERAL (SELECT contracts_build_tsquery(");
+            qb.push_bind(text.clone());
+            qb.push(") AS tsq) q");
+        }
+        qb.push(join);
+        qb.push(" WHERE 1=1");
+
+        if let Some(text) = &self.text {
+            qb.push(" AND (d.document @@ q.tsq");
+            if self.fuzzy {
+                qb.push(" OR word_similarity(");
+                qb.push_bind(text.clone());
+                qb.push(", d.terms) >= ");
+                qb.push_bind(FUZZY_THRESHOLD);
+            }
+            qb.push(")");
+        }
+        if exclude != Some(Facet::Category) {
+            if let Some(category) = &self.category {
+                qb.push(" AND lower(c.category) = lower(");
+                qb.push_bind(category.clone());
+                qb.push(")");
+            }
+        }
+        if exclude != Some(Facet::Network) && !self.networks.is_empty() {
+            qb.push(" AND c.network::text = ANY(");
+            qb.push_bind(self.networks.clone());
+            qb.push(")");
+        }
+        if exclude != Some(Facet::Verified) && self.verified_only {
+            qb.push(" AND c.is_verified = true");
+        }
+        if !self.tags.is_empty() {
+            qb.push(" AND c.tags @> ");
+            qb.push_bind(self.tags.clone());
+        }
+    }
+
+    fn hits_query(&self) -> QueryBuilder<'static, Postgres> {
+        let mut qb = QueryBuilder::new("SELECT c.*, ");
+        match &self.text {
+            Some(text) => {
+                qb.push("(COALESCE(ts_rank_cd(");
+                qb.push_bind(RANK_WEIGHTS);
+                qb.push("::real[], d.document, q.tsq, 1), 0) + CASE WHEN lower(c.name) = lower(");
+                qb.push_bind(text.clone());
+                qb.push(") THEN 1 ELSE 0 END");
+                if self.fuzzy {
+                    qb.push(" + 0.5 * COALESCE(word_similarity(");
+                    qb.push_bind(text.clone());
+                    qb.push(", d.terms), 0)");
+                }
+                qb.push(")::real AS relevance, ");
+
+                qb.push("ts_headline('english', c.name, q.tsq, ");
+                qb.push_bind(NAME_HIGHLIGHT);
+                qb.push(") AS name_highlight, ");
+                qb.push("ts_headline('english', c.description, q.tsq, ");
+                qb.push_bind(SNIPPET_HIGHLIGHT);
+                qb.push(") AS description_highlight, ");
+
+                for (column, alias) in [
+                    ("function_names", "matched_functions"),
+                    ("event_names", "matched_events"),
+                ] {
+                    qb.push(format!(
+                        "ARRAY(SELECT n FROM unnest(COALESCE(d.{}, '{{}}')) AS n \
+                         WHERE to_tsvector('english', replace(n, '_', ' ')) @@ q.tsq",
+                        column
+                    ));
+                    if self.fuzzy {
+                        qb.push(" OR word_similarity(");
+                        qb.push_bind(text.clone());
+                        qb.push(", lower(replace(n, '_', ' '))) >= ");
+                        qb.push_bind(FUZZY_THRESHOLD);
+                    }
+                    qb.push(format!(" ORDER BY n) AS {}", alias));
+                    if alias == "matched_functions" {
+                        qb.push(", ");
+                    }
+                }
+            }
+            None => {
+                qb.push(
+                    "0::real AS relevance, NULL::text AS name_highlight, \
+                     NULL::text AS description_highlight, \
+                     '{}'::text[] AS matched_functions, '{}'::text[] AS matched_events",
+                );
+            }
+        }
+
+        self.push_from(&mut qb, None, "");
+
+        let direction = match self.sort_order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        qb.push(format!(
+            " ORDER BY {} {}, c.id DESC LIMIT ",
+            self.order_column(),
+            direction
+        ));
+        qb.push_bind(self.limit);
+        qb.push(" OFFSET ");
+        qb.push_bind(self.offset());
+        qb
+    }
+
+    fn order_column(&self) -> &'static str {
+        match self.sort_by {
+            SortBy::Relevance if self.text.is_some() => "relevance",
+            SortBy::Relevance | SortBy::CreatedAt => "c.created_at",
+            SortBy::UpdatedAt => "c.updated_at",
+            SortBy::Popularity | SortBy::Interactions => {
+                "(SELECT COUNT(*) FROM contract_interactions ci WHERE ci.contract_id = c.id)"
+            }
+            SortBy::Deployments => {
+                "(SELECT COUNT(*) FROM contract_versions cv WHERE cv.contract_id = c.id)"
+            }
+        }
+    }
+
+    fn count_query(&self) -> QueryBuilder<'static, Postgres> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(*)");
+        self.push_from(&mut qb, None, "");
+        qb
+    }
+
+    fn facet_query(&self, facet: Facet) -> QueryBuilder<'static, Postgres> {
+        let value = match facet {
+            Facet::Category => "c.category",
+            Facet::Network => "c.network::text",
+            Facet::Verified => "c.is_verified::text",
+        };
+        let mut qb = QueryBuilder::new(format!("SELECT {} AS value, COUNT(*) AS count", value));
+        self.push_from(&mut qb, Some(facet), "");
+        qb.push(format!(
+            " AND {} IS NOT NULL GROUP BY 1 ORDER BY count DESC, value",
+            value
+        ));
+        qb
+    }
+
+    fn tag_facet_query(&self) -> QueryBuilder<'static, Postgres> {
+        let mut qb = QueryBuilder::new("SELECT t AS value, COUNT(*) AS count");
+        self.push_from(&mut qb, None, " CROSS JOIN LATERAL unnest(c.tags) AS t");
+        qb.push(" GROUP BY t ORDER BY count DESC, value LIMIT ");
+        qb.push_bind(TAG_FACET_LIMIT);
+        qb
+    }
+}
+
+async fn facets(db: &PgPool, request: &SearchRequest) -> Result<SearchFacets, sqlx::Error> {
+    let mut category = request.facet_query(Facet::Category);
+    let mut network = request.facet_query(Facet::Network);
+    let mut verified = request.facet_query(Facet::Verified);
+    let mut tags = request.tag_facet_query();
+
+    let (category, network, verified, tags) = tokio::try_join!(
+        category.build_query_as::<FacetCount>().fetch_all(db),
+        network.build_query_as::<FacetCount>().fetch_all(db),
+        verified.build_query_as::<FacetCount>().fetch_all(db),
+        tags.build_query_as::<FacetCount>().fetch_all(db),
+    )?;
+
+    Ok(SearchFacets {
+        category,
+        network,
+        verified,
+        tags,
+    })
+}
+
+/// Run a contract search for `GET /api/contracts`.
+pub async fn search_contracts(
+    db: &PgPool,
+    params: &ContractSearchParams,
+) -> ApiResult<ContractSearchResponse> {
+    let request = SearchRequest::from_params(params)?;
+
+    let mut hits = request.hits_query();
+    let mut count = request.count_query();
+
+    let (rows, total, facets) = tokio::try_join!(
+        hits.build_query_as::<HitRow>().fetch_all(db),
+        count.build_query_scalar::<i64>().fetch_one(db),
+        async {
+            if request.facets {
+                facets(db, &request).await.map(Some)
+            } else {
+                Ok(None)
+            }
+        },
+    )
+    .map_err(|err| db_internal_error("search contracts", err))?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| ContractSearchHit {
+            contract: row.contract,
+            relevance: row.relevance,
+            highlights: SearchHighlights {
+                name: row.name_highlight,
+                description: row.description_highlight,
+            },
+            matched_functions: row.matched_functions,
+            matched_events: row.matched_events,
+        })
+        .collect();
+
+    Ok(ContractSearchResponse {
+        page: PaginatedResponse::new(items, total, request.page, request.limit),
+        facets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(query: Option<&str>) -> ContractSearchParams {
+        serde_json::from_value(serde_json::json!({ "query": query })).unwrap()
+    }
+
+    #[test]
+    fn normalizes_queries() {
+        assert_eq!(normalize_query(None).unwrap(), None);
+        assert_eq!(normalize_query(Some("   ")).unwrap(), None);
+        assert_eq!(
+            normalize_query(Some("  token swap ")).unwrap().as_deref(),
+            Some("token swap")
+        );
+        assert!(normalize_query(Some(&"a".repeat(MAX_QUERY_LEN + 1))).is_err());
+    }
+
+    #[test]
+    fn fuzzy_needs_enough_characters() {
+        assert!(fuzzy_applies("tokn", true));
+        assert!(!fuzzy_applies("tk", true));
+        assert!(!fuzzy_applies("token", false));
+    }
+
+    #[test]
+    fn routes_only_searches_to_the_engine() {
+        assert!(is_search(&params(Some("dex"))));
+        assert!(!is_search(&params(Some("  "))));
+        assert!(!is_search(&params(None)));
+
+        let mut tagged = params(None);
+        tagged.tags = Some(vec!["defi".into()]);
+        assert!(is_search(&tagged));
+    }
+
+    #[test]
+    fn binds_user_input_instead_of_interpolating_it() {
+        let mut p = params(Some("x'); DROP TABLE contracts; --"));
+        p.category = Some("dex' OR '1'='1".into());
+        p.tags = Some(vec!["defi'--".into()]);
+        let request = SearchRequest::from_params(&p).unwrap();
+
+        for sql in [
+            request.hits_query().sql().to_string(),
+            request.count_query().sql().to_string(),
+            request.facet_query(Facet::Category).sql().to_string(),
+            request.tag_facet_query().sql().to_string(),
+        ] {
+            assert!(!sql.contains("DROP TABLE"), "{}", sql);
+            assert!(!sql.contains("'1'='1"), "{}", sql);
+            assert!(!sql.contains("defi'"), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn facets_drop_their_own_filter() {
+        let mut p = params(Some("token"));
+        p.category = Some("dex".into());
+        p.verified_only = Some(true);
+        let request = SearchRequest::from_params(&p).unwrap();
+
+        let category = request.facet_query(Facet::Category).sql().to_string();
+        assert!(!category.contains("lower(c.category)"));
+        assert!(category.contains("c.is_verified = true"));
+
+        let verified = request.facet_query(Facet::Verified).sql().to_string();
+        assert!(verified.contains("lower(c.category)"));
+        assert!(!verified.contains("c.is_verified = true"));
+    }
+
+    #[test]
+    fn relevance_sort_needs_a_query() {
+        let request = SearchRequest::from_params(&params(Some("dex"))).unwrap();
+        assert_eq!(request.order_column(), "relevance");
+
+        let mut p = params(None);
+        p.sort_by = Some(SortBy::Relevance);
+        let request = SearchRequest::from_params(&p).unwrap();
+        assert_eq!(request.order_column(), "c.created_at");
+    }
+}
@@ -7,6 +7,7 @@ use chrono::Utc;
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
+use shared::transparency::{ConsistencyProof, InclusionProof, TreeHead};
 use shared::{
     ChainOfCustodyEntry, ChainOfCustodyResponse, PackageSignature, RevokeSignatureRequest,
     SignPackageRequest, SignatureStatus, TransparencyEntryType, TransparencyLogEntry,
@@ -18,6 +19,7 @@ use crate::{
     error::{ApiError, ApiResult},
     handlers::db_internal_error,
     state::AppState,
+    transparency_log::{self, LogSigner, TransparencyError},
 };
 
 fn map_json_rejection(err: axum::extract::rejection::JsonRejection) -> ApiError {
@@ -571,6 +573,99 @@ pub async fn get_transparency_log(
     Ok(Json(TransparencyLogResponse { items, total }))
 }
 
+fn transparency_error(err: TransparencyError) -> ApiError {
+    match err {
+        err @ (TransparencyError::NoTreeHead | TransparencyError::UnknownTreeSize(_)) => {
+            ApiError::not_found("TreeHeadNotFound", err.to_string())
+        }
+        err @ TransparencyError::NotSequenced => {
+            ApiError::conflict("EntryNotSequenced", err.to_string())
+        }
+        err @ (TransparencyError::LeafOutOfRange { .. }
+        | TransparencyError::InvalidRange { .. }) => {
+            ApiError::bad_request("InvalidProofRange", err.to_string())
+        }
+        err @ TransparencyError::CorruptLeaf(_) => ApiError::internal(err.to_string()),
+        TransparencyError::Database(err) => db_internal_error("transparency log proof", err),
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct TreeHeadResponse {
+    #[serde(flatten)]
+    pub tree_head: TreeHead,
+    /// Base64 Ed25519 key the head is signed with. Clients should pin this
+    /// out of band rather than trust it from the same response.
+    pub public_key: Option<String>,
+}
+
+pub async fn get_tree_head(State(state): State<AppState>) -> ApiResult<Json<TreeHeadResponse>> {
+    let tree_head = transparency_log::latest_tree_head(&state.db)
+        .await
+        .map_err(transparency_error)?;
+    let public_key = LogSigner::from_env().map(|signer| signer.public_key());
+    Ok(Json(TreeHeadResponse {
+        tree_head,
+        public_key,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InclusionProofQuery {
+    pub signature_id: Option<Uuid>,
+    pub leaf_index: Option<i64>,
+    /// Defaults to the latest tree head
+    pub tree_size: Option<i64>,
+}
+
+pub async fn get_inclusion_proof(
+    State(state): State<AppState>,
+    Query(query): Query<InclusionProofQuery>,
+) -> ApiResult<Json<InclusionProof>> {
+    let leaf_index = match (query.leaf_index, query.signature_id) {
+        (Some(index), _) => index,
+        (None, Some(signature_id)) => {
+            transparency_log::signature_leaf_index(&state.db, signature_id)
+                .await
+                .map_err(transparency_error)?
+                .ok_or_else(|| {
+                    ApiError::not_found(
+                        "SignatureNotFound",
+                        format!("No transparency log entry for signature: {}", signature_id),
+                    )
+                })?
+        }
+        (None, None) => {
+            return Err(ApiError::bad_request(
+                "MissingLeaf",
+                "signature_id or leaf_index is required",
+            ))
+        }
+    };
+
+    let proof = transparency_log::inclusion_proof(&state.db, leaf_index, query.tree_size)
+        .await
+        .map_err(transparency_error)?;
+    Ok(Json(proof))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsistencyProofQuery {
+    pub first: i64,
+    /// Defaults to the latest tree head
+    pub second: Option<i64>,
+}
+
+pub async fn get_consistency_proof(
+    State(state): State<AppState>,
+    Query(query): Query<ConsistencyProofQuery>,
+) -> ApiResult<Json<ConsistencyProof>> {
+    let proof = transparency_log::consistency_proof(&state.db, query.first, query.second)
+        .await
+        .map_err(transparency_error)?;
+    Ok(Json(proof))
+}
+
 async fn parse_contract_uuid(state: &AppState, contract_id: &str) -> ApiResult<Uuid> {
     if let Ok(uuid) = Uuid::parse_str(contract_id) {
         return Ok(uuid);
@@ -28,4 +28,16 @@ pub fn signing_routes() -> Router<AppState> {
             "/api/signatures/transparency",
             get(signing_handlers::get_transparency_log),
         )
+        .route(
+            "/api/signatures/transparency/tree-head",
+            get(signing_handlers::get_tree_head),
+        )
+        .route(
+            "/api/signatures/transparency/proof/inclusion",
+            get(signing_handlers::get_inclusion_proof),
+        )
+        .route(
+            "/api/signatures/transparency/proof/consistency",
+            get(signing_handlers::get_consistency_proof),
+        )
 }
@@ -1,4 +1,6 @@
+use crate::artifact_store::{self, ArtifactStore};
 use crate::cache::{CacheConfig, CacheLayer};
+use crate::event_stream::EventHub;
 use prometheus::Registry;
 use sqlx::PgPool;
 use std::sync::atomic::AtomicBool;
@@ -13,6 +15,10 @@ pub struct AppState {
     pub cache: Arc<CacheLayer>,
     pub registry: Registry,
     pub is_shutting_down: Arc<AtomicBool>,
+    /// Fan-out point for the real-time event stream
+    pub event_hub: Arc<EventHub>,
+    /// Content-addressed WASM and source storage
+    pub artifacts: Arc<dyn ArtifactStore>,
 }
 
 impl AppState {
@@ -24,6 +30,8 @@ impl AppState {
             cache: Arc::new(CacheLayer::new(config)),
             registry,
             is_shutting_down,
+            event_hub: Arc::new(EventHub::new()),
+            artifacts: artifact_store::from_env(),
         }
     }
 }
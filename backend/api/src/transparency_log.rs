@@ -0,0 +1,405 @@
+//! Transparency log sequencer and proofs
+//!
+//! Handlers append entries to `transparency_log` without ordering them. Every
+//! `TRANSPARENCY_LOG_SEQUENCE_INTERVAL_SECS` (default 60) the sequencer gives
+//! new entries the next leaf indexes, then signs a tree head over the whole
+//! log with the Ed25519 key in `TRANSPARENCY_LOG_SIGNING_KEY` (base64 32-byte
+//! seed). Proofs are only served against published tree heads.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signer, SigningKey};
+use shared::transparency::{self, ConsistencyProof, Hash, InclusionProof, LogLeaf, TreeHead};
+use sqlx::{FromRow, PgPool};
+use std::time::Duration;
+use uuid::Uuid;
+
+const DEFAULT_SEQUENCE_INTERVAL_SECS: u64 = 60;
+const SEQUENCE_BATCH: i64 = 1000;
+/// Advisory lock key held while assigning leaf indexes
+const SEQUENCER_LOCK: i64 = 0x7472_616e_7370;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransparencyError {
+    #[error("no tree head has been published yet")]
+    NoTreeHead,
+    #[error("no tree head has been published for tree size {0}")]
+    UnknownTreeSize(i64),
+    #[error("log entry is not sequenced yet; retry after the next tree head")]
+    NotSequenced,
+    #[error("leaf {leaf_index} is not in the tree of size {tree_size}")]
+    LeafOutOfRange { leaf_index: i64, tree_size: i64 },
+    #[error("first tree size {first} is larger than second tree size {second}")]
+    InvalidRange { first: i64, second: i64 },
+    #[error("stored leaf hash at index {0} is malformed")]
+    CorruptLeaf(i64),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct LogSigner {
+    key: SigningKey,
+    key_id: String,
+}
+
+impl LogSigner {
+    pub fn from_env() -> Option<Self> {
+        let encoded = std::env::var("TRANSPARENCY_LOG_SIGNING_KEY").ok()?;
+        let seed: [u8; 32] = match BASE64.decode(encoded.trim()).map(<[u8; 32]>::try_from) {
+            Ok(Ok(seed)) => seed,
+            _ => {
+                tracing::error!(
+                    "transparency: TRANSPARENCY_LOG_SIGNING_KEY is not a base64 32-byte seed"
+                );
+                return None;
+            }
+        };
+        Some(Self::new(SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn new(key: SigningKey) -> Self {
+        let key_id = transparency::key_id(key.verifying_key().as_bytes());
+        Self { key, key_id }
+    }
+
+    pub fn public_key(&self) -> String {
+        BASE64.encode(self.key.verifying_key().as_bytes())
+    }
+
+    pub fn sign(&self, tree_size: u64, root: &Hash, timestamp: DateTime<Utc>) -> TreeHead {
+        // Stored heads keep microseconds; the signed message uses millis.
+        let timestamp =
+            DateTime::from_timestamp_millis(timestamp.timestamp_millis()).unwrap_or(timestamp);
+        let root_hash = transparency::encode_hash(root);
+        let message = TreeHead::signing_message(tree_size, &root_hash, timestamp);
+        TreeHead {
+            tree_size,
+            root_hash,
+            timestamp,
+            signature: BASE64.encode(self.key.sign(&message).to_bytes()),
+            key_id: self.key_id.clone(),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct PendingEntry {
+    id: Uuid,
+    entry_type: String,
+    contract_id: Option<Uuid>,
+    signature_id: Option<Uuid>,
+    actor_address: String,
+    entry_hash: String,
+    timestamp: DateTime<Utc>,
+    version: Option<String>,
+    wasm_hash: Option<String>,
+    signature: Option<String>,
+    public_key: Option<String>,
+}
+
+#[derive(FromRow)]
+struct TreeHeadRow {
+    tree_size: i64,
+    root_hash: String,
+    signature: String,
+    key_id: String,
+    timestamp: DateTime<Utc>,
+}
+
+impl From<TreeHeadRow> for TreeHead {
+    fn from(row: TreeHeadRow) -> Self {
+        TreeHead {
+            tree_size: row.tree_size as u64,
+            root_hash: row.root_hash,
+            timestamp: row.timestamp,
+            signature: row.signature,
+            key_id: row.key_id,
+        }
+    }
+}
+
+/// Assign leaf indexes to unsequenced entries. Returns how many were added.
+pub async fn sequence_pending(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut sequenced = 0u64;
+    loop {
+        let mut tx = pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(SEQUENCER_LOCK)
+            .execute(&mut *tx)
+            .await?;
+
+        let next: i64 =
+            sqlx::query_scalar("SELECT COALESCE(MAX(leaf_index) + 1, 0) FROM transparency_log")
+                .fetch_one(&mut *tx)
+                .await?;
+        let pending: Vec<PendingEntry> = sqlx::query_as(
+            "SELECT t.id, t.entry_type::text AS entry_type, t.contract_id, t.signature_id,
+                    t.actor_address, t.entry_hash, t.timestamp,
+                    s.version, s.wasm_hash, s.signature, s.public_key
+             FROM transparency_log t
+             LEFT JOIN package_signatures s
+                    ON s.id = t.signature_id AND t.entry_type = 'package_signed'
+             WHERE t.leaf_index IS NULL
+             ORDER BY t.timestamp, t.id
+             LIMIT $1
+             FOR UPDATE OF t",
+        )
+        .bind(SEQUENCE_BATCH)
+        .fetch_all(&mut *tx)
+        .await?;
+        let batch = pending.len() as u64;
+
+        for (offset, entry) in pending.into_iter().enumerate() {
+            let leaf = LogLeaf {
+                entry_id: entry.id,
+                entry_type: entry.entry_type,
+                contract_id: entry.contract_id,
+                signature_id: entry.signature_id,
+                actor_address: entry.actor_address,
+                entry_hash: entry.entry_hash,
+                version: entry.version,
+                wasm_hash: entry.wasm_hash,
+                signature: entry.signature,
+                public_key: entry.public_key,
+                timestamp: entry.timestamp,
+            };
+            let leaf_data = leaf.to_leaf_data();
+            let leaf_hash =
+                transparency::encode_hash(&transparency::leaf_hash(leaf_data.as_bytes()));
+            sqlx::query(
+                "UPDATE transparency_log SET leaf_index = $2, leaf_data = $3, leaf_hash = $4
+                 WHERE id = $1",
+            )
+            .bind(entry.id)
+            .bind(next + offset as i64)
+            .bind(&leaf_data)
+            .bind(&leaf_hash)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        sequenced += batch;
+        if batch < SEQUENCE_BATCH as u64 {
+            return Ok(sequenced);
+        }
+    }
+}
+
+async fn leaf_hashes(pool: &PgPool, tree_size: i64) -> Result<Vec<Hash>, TransparencyError> {
+    let rows: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT leaf_index, leaf_hash FROM transparency_log
+         WHERE leaf_index < $1 ORDER BY leaf_index",
+    )
+    .bind(tree_size)
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|(index, hash)| {
+            transparency::decode_hash(&hash).ok_or(TransparencyError::CorruptLeaf(index))
+        })
+        .collect()
+}
+
+/// Sign and store a head over every sequenced entry, if the log has grown
+/// since the last one.
+pub async fn publish_tree_head(
+    pool: &PgPool,
+    signer: &LogSigner,
+) -> Result<Option<TreeHead>, TransparencyError> {
+    let tree_size: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(leaf_index) + 1, 0) FROM transparency_log")
+            .fetch_one(pool)
+            .await?;
+    let published: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(tree_size), 0) FROM transparency_tree_heads")
+            .fetch_one(pool)
+            .await?;
+    if tree_size == 0 || tree_size <= published {
+        return Ok(None);
+    }
+
+    let leaves = leaf_hashes(pool, tree_size).await?;
+    let head = signer.sign(tree_size as u64, &transparency::root(&leaves), Utc::now());
+    sqlx::query(
+        "INSERT INTO transparency_tree_heads (tree_size, root_hash, signature, key_id, timestamp)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (tree_size) DO NOTHING",
+    )
+    .bind(tree_size)
+    .bind(&head.root_hash)
+    .bind(&head.signature)
+    .bind(&head.key_id)
+    .bind(head.timestamp)
+    .execute(pool)
+    .await?;
+    Ok(Some(head))
+}
+
+pub async fn latest_tree_head(pool: &PgPool) -> Result<TreeHead, TransparencyError> {
+    let row: Option<TreeHeadRow> = sqlx::query_as(
+        "SELECT tree_size, root_hash, signature, key_id, timestamp
+         FROM transparency_tree_heads ORDER BY tree_size DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+    row.map(TreeHead::from).ok_or(TransparencyError::NoTreeHead)
+}
+
+/// The published head of exactly `tree_size`, or the latest when `None`.
+pub async fn tree_head(
+    pool: &PgPool,
+    tree_size: Option<i64>,
+) -> Result<TreeHead, TransparencyError> {
+    let Some(size) = tree_size else {
+        return latest_tree_head(pool).await;
+    };
+    let row: Option<TreeHeadRow> = sqlx::query_as(
+        "SELECT tree_size, root_hash, signature, key_id, timestamp
+         FROM transparency_tree_heads WHERE tree_size = $1",
+    )
+    .bind(size)
+    .fetch_optional(pool)
+    .await?;
+    row.map(TreeHead::from)
+        .ok_or(TransparencyError::UnknownTreeSize(size))
+}
+
+/// Leaf index of the `package_signed` entry for a signature.
+pub async fn signature_leaf_index(
+    pool: &PgPool,
+    signature_id: Uuid,
+) -> Result<Option<i64>, TransparencyError> {
+    let found: Option<Option<i64>> = sqlx::query_scalar(
+        "SELECT leaf_index FROM transparency_log
+         WHERE signature_id = $1 AND entry_type = 'package_signed'
+         ORDER BY timestamp LIMIT 1",
+    )
+    .bind(signature_id)
+    .fetch_optional(pool)
+    .await?;
+    match found {
+        None => Ok(None),
+        Some(None) => Err(TransparencyError::NotSequenced),
+        Some(Some(index)) => Ok(Some(index)),
+    }
+}
+
+pub async fn inclusion_proof(
+    pool: &PgPool,
+    leaf_index: i64,
+    tree_size: Option<i64>,
+) -> Result<InclusionProof, TransparencyError> {
+    let head = tree_head(pool, tree_size).await?;
+    let size = head.tree_size as i64;
+    if leaf_index < 0 || leaf_index >= size {
+        return Err(TransparencyError::LeafOutOfRange {
+            leaf_index,
+            tree_size: size,
+        });
+    }
+
+    let leaf_data: String =
+        sqlx::query_scalar("SELECT leaf_data FROM transparency_log WHERE leaf_index = $1")
+            .bind(leaf_index)
+            .fetch_one(pool)
+            .await?;
+    let leaves = leaf_hashes(pool, size).await?;
+    let audit_path = transparency::inclusion_path(leaf_index as usize, &leaves)
+        .iter()
+        .map(transparency::encode_hash)
+        .collect();
+    Ok(InclusionProof {
+        leaf_index: leaf_index as u64,
+        leaf_data,
+        audit_path,
+        tree_head: head,
+    })
+}
+
+pub async fn consistency_proof(
+    pool: &PgPool,
+    first: i64,
+    second: Option<i64>,
+) -> Result<ConsistencyProof, TransparencyError> {
+    let second = tree_head(pool, second).await?;
+    let first = tree_head(pool, Some(first)).await?;
+    if first.tree_size > second.tree_size {
+        return Err(TransparencyError::InvalidRange {
+            first: first.tree_size as i64,
+            second: second.tree_size as i64,
+        });
+    }
+
+    let leaves = leaf_hashes(pool, second.tree_size as i64).await?;
+    let proof = transparency::consistency_proof(first.tree_size as usize, &leaves)
+        .iter()
+        .map(transparency::encode_hash)
+        .collect();
+    Ok(ConsistencyProof {
+        first,
+        second,
+        proof,
+    })
+}
+
+pub fn spawn_transparency_sequencer(pool: PgPool) {
+    let interval_secs = std::env::var("TRANSPARENCY_LOG_SEQUENCE_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_SEQUENCE_INTERVAL_SECS);
+    let signer = LogSigner::from_env();
+    if signer.is_none() {
+        tracing::warn!("transparency: TRANSPARENCY_LOG_SIGNING_KEY unset; entries are sequenced but no tree heads are published");
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match sequence_pending(&pool).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(count = n, "transparency: entries sequenced"),
+                Err(err) => {
+                    tracing::error!(error = ?err, "transparency: sequencing failed");
+                    continue;
+                }
+            }
+            let Some(signer) = signer.as_ref() else {
+                continue;
+            };
+            match publish_tree_head(&pool, signer).await {
+                Ok(Some(head)) => {
+                    tracing::info!(tree_size = head.tree_size, root = %head.root_hash, "transparency: tree head published")
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::error!(error = ?err, "transparency: publishing tree head failed")
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier};
+
+    #[test]
+    fn signed_head_verifies_against_the_public_key() {
+        let signer = LogSigner::new(SigningKey::from_bytes(&[7u8; 32]));
+        let root =
+            transparency::root(&[transparency::leaf_hash(b"a"), transparency::leaf_hash(b"b")]);
+        let head = signer.sign(2, &root, Utc::now());
+
+        let public_key = signer.key.verifying_key();
+        let signature: [u8; 64] = BASE64.decode(&head.signature).unwrap().try_into().unwrap();
+        let message = TreeHead::signing_message(head.tree_size, &head.root_hash, head.timestamp);
+        assert!(public_key
+            .verify(&message, &Signature::from_bytes(&signature))
+            .is_ok());
+        assert_eq!(head.key_id, transparency::key_id(public_key.as_bytes()));
+        assert_eq!(head.timestamp.timestamp_subsec_micros() % 1000, 0);
+    }
+}
@@ -6,11 +6,14 @@
 //
 //  Factor                  Weight   Description
 //  ──────────────────────  ──────   ────────────────────────────────────────
-//  Verification status       25 pt  +25 if is_verified = true
-//  Audit quality             35 pt  latest audit overall_score × 0.35
-//  Usage / adoption          20 pt  deployments + interactions, capped at 20
+//  Verification status       20 pt  +20 if is_verified = true
+//  Audit quality             25 pt  latest audit overall_score × 0.25
+//  Usage / adoption          15 pt  deployments + interactions, capped at 15
 //  Contract age              10 pt  days since created_at, capped at 10
-//  No critical vulns         10 pt  −10 per unresolved critical audit failure
+//  No critical vulns         10 pt  −5 per unresolved critical audit failure
+//  Dependency CVEs           10 pt  −5 per critical, −2 per high CVE finding
+//  Package signature          5 pt  +5 if the latest signature is valid
+//  Publisher reputation       5 pt  publisher's other verified contracts + age
 //
 // ── Trust tiers ─────────────────────────────────────────────────────────────
 //
@@ -24,18 +27,18 @@
 // All weights are defined as constants so they are easy to audit and adjust.
 
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 // ── Weight constants ──────────────────────────────────────────────────────────
 
 /// Maximum points awarded for on-chain verification
-pub const WEIGHT_VERIFIED: f64 = 25.0;
+pub const WEIGHT_VERIFIED: f64 = 20.0;
 
 /// Maximum points from audit quality (latest audit score × this fraction)
-pub const WEIGHT_AUDIT: f64 = 35.0;
+pub const WEIGHT_AUDIT: f64 = 25.0;
 
 /// Maximum points from usage/adoption signals
-pub const WEIGHT_USAGE: f64 = 20.0;
+pub const WEIGHT_USAGE: f64 = 15.0;
 
 /// Maximum points from contract age
 pub const WEIGHT_AGE: f64 = 10.0;
@@ -43,6 +46,15 @@ pub const WEIGHT_AGE: f64 = 10.0;
 /// Maximum points from having no critical vulnerabilities
 pub const WEIGHT_NO_VULNS: f64 = 10.0;
 
+/// Maximum points from a clean dependency CVE scan
+pub const WEIGHT_DEPENDENCIES: f64 = 10.0;
+
+/// Maximum points from a valid package signature
+pub const WEIGHT_SIGNATURE: f64 = 5.0;
+
+/// Maximum points from the publisher's track record
+pub const WEIGHT_PUBLISHER: f64 = 5.0;
+
 /// Number of deployments needed to earn full usage points
 const USAGE_DEPLOYMENT_CAP: f64 = 50.0;
 
@@ -52,9 +64,34 @@ const USAGE_INTERACTION_CAP: f64 = 500.0;
 /// Days of age needed to earn full age points
 const AGE_DAYS_CAP: f64 = 180.0;
 
+/// Days of publisher account age needed for full account-age credit
+const PUBLISHER_AGE_DAYS_CAP: f64 = 365.0;
+
 // ── Input data ────────────────────────────────────────────────────────────────
 
+/// State of the most recent package signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureState {
+    Valid,
+    Expired,
+    Revoked,
+    Unsigned,
+}
+
+/// The publisher's record outside the contract being scored
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublisherReputation {
+    /// Other contracts published by the same publisher
+    pub other_contracts: i64,
+    /// How many of those are verified
+    pub other_verified: i64,
+    /// Days since the publisher registered
+    pub account_age_days: i64,
+}
+
 /// Raw data collected from the DB before scoring
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrustInput {
     /// Whether the contract is verified on-chain
     pub is_verified: bool,
@@ -73,12 +110,27 @@ pub struct TrustInput {
 
     /// Number of unresolved critical-severity audit check failures
     pub unresolved_critical_vulns: i64,
+
+    /// Packages recorded by the dependency scanner (0 = never scanned)
+    pub scanned_packages: i64,
+
+    /// Open critical-severity CVE findings in dependencies
+    pub critical_cves: i64,
+
+    /// Open high-severity CVE findings in dependencies
+    pub high_cves: i64,
+
+    /// Status of the latest package signature
+    pub signature: SignatureState,
+
+    /// Publisher track record
+    pub publisher: PublisherReputation,
 }
 
 // ── Output types ──────────────────────────────────────────────────────────────
 
 /// One factor contributing to the overall trust score
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TrustFactor {
     /// Human-readable factor name
     pub name: &'static str,
@@ -91,7 +143,7 @@ pub struct TrustFactor {
 }
 
 /// Full trust score response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TrustScore {
     /// 0–100 composite trust score
     pub score: f64,
@@ -129,7 +181,7 @@ pub fn trust_badge(score: f64) -> (&'static str, &'static str) {
 ///
 /// Returns a fully-populated [`TrustScore`] with per-factor breakdown.
 pub fn compute_trust_score(input: &TrustInput) -> TrustScore {
-    let mut factors: Vec<TrustFactor> = Vec::with_capacity(5);
+    let mut factors: Vec<TrustFactor> = Vec::with_capacity(8);
     let mut total = 0.0f64;
 
     // ── Factor 1: Verification status ────────────────────────────────────────
@@ -161,7 +213,10 @@ pub fn compute_trust_score(input: &TrustInput) -> TrustScore {
                 "Latest security audit scored {:.1}/100. Audit score contributes up to {:.0} trust points.",
                 s, WEIGHT_AUDIT
             ),
-            None => "No security audit found. Complete an audit to earn up to 35 points.".into(),
+            None => format!(
+                "No security audit found. Complete an audit to earn up to {:.0} points.",
+                WEIGHT_AUDIT
+            ),
         },
     });
 
@@ -217,6 +272,70 @@ pub fn compute_trust_score(input: &TrustInput) -> TrustScore {
         },
     });
 
+    // ── Factor 6: Dependency CVEs ─────────────────────────────────────────────
+    let cve_penalty = input.critical_cves as f64 * 5.0 + input.high_cves as f64 * 2.0;
+    let cve_points = if input.scanned_packages == 0 {
+        0.0
+    } else {
+        (WEIGHT_DEPENDENCIES - cve_penalty).max(0.0)
+    };
+    total += cve_points;
+    factors.push(TrustFactor {
+        name: "Dependency Vulnerabilities",
+        points_earned: cve_points,
+        points_max: WEIGHT_DEPENDENCIES,
+        explanation: if input.scanned_packages == 0 {
+            "No dependency scan on record. Run a scan to earn these points.".into()
+        } else if input.critical_cves == 0 && input.high_cves == 0 {
+            format!(
+                "{} dependencies scanned with no open critical or high CVEs.",
+                input.scanned_packages
+            )
+        } else {
+            format!(
+                "{} critical and {} high CVE(s) open in dependencies. Critical deducts 5, high deducts 2.",
+                input.critical_cves, input.high_cves
+            )
+        },
+    });
+
+    // ── Factor 7: Package signature ───────────────────────────────────────────
+    let signature_points = if input.signature == SignatureState::Valid { WEIGHT_SIGNATURE } else { 0.0 };
+    total += signature_points;
+    factors.push(TrustFactor {
+        name: "Package Signature",
+        points_earned: signature_points,
+        points_max: WEIGHT_SIGNATURE,
+        explanation: match input.signature {
+            SignatureState::Valid    => "Latest package signature is valid.".into(),
+            SignatureState::Expired  => "Latest package signature has expired. Re-sign to earn these points.".into(),
+            SignatureState::Revoked  => "Latest package signature was revoked.".into(),
+            SignatureState::Unsigned => "Package is not signed. Sign a release to earn these points.".into(),
+        },
+    });
+
+    // ── Factor 8: Publisher reputation ────────────────────────────────────────
+    // Share of the publisher's other contracts that are verified (60%) and
+    // account age (40%)
+    let publisher = &input.publisher;
+    let verified_share = if publisher.other_contracts > 0 {
+        publisher.other_verified as f64 / publisher.other_contracts as f64
+    } else {
+        0.0
+    };
+    let account_ratio = (publisher.account_age_days.max(0) as f64 / PUBLISHER_AGE_DAYS_CAP).min(1.0);
+    let publisher_points = (verified_share * 0.6 + account_ratio * 0.4) * WEIGHT_PUBLISHER;
+    total += publisher_points;
+    factors.push(TrustFactor {
+        name: "Publisher Reputation",
+        points_earned: publisher_points,
+        points_max: WEIGHT_PUBLISHER,
+        explanation: format!(
+            "Publisher has {} of {} other contract(s) verified and registered {} days ago.",
+            publisher.other_verified, publisher.other_contracts, publisher.account_age_days,
+        ),
+    });
+
     // ── Assemble result ───────────────────────────────────────────────────────
     let score = total.clamp(0.0, 100.0);
     let (badge, badge_icon) = trust_badge(score);
@@ -237,6 +356,54 @@ pub fn compute_trust_score(input: &TrustInput) -> TrustScore {
     TrustScore { score, badge, badge_icon, factors, summary }
 }
 
+// ── Badge rendering ───────────────────────────────────────────────────────────
+
+/// Fill colour for each tier
+fn badge_color(badge: &str) -> &'static str {
+    match badge {
+        "Platinum" => "#5b7fa6",
+        "Gold"     => "#c9a100",
+        "Silver"   => "#8a8a8a",
+        _          => "#b0703c",
+    }
+}
+
+/// Rough text width in Verdana 11px, as used by shields.io-style badges
+fn text_width(text: &str) -> u32 {
+    text.chars().map(|c| if c.is_ascii_uppercase() { 8 } else { 7 }).sum::<u32>()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Embeddable flat SVG badge: `trust | Gold 82`.
+pub fn badge_svg(score: f64, badge: &str) -> String {
+    let label = "trust";
+    let value = format!("{} {:.0}", badge, score.clamp(0.0, 100.0));
+    let label_w = text_width(label) + 12;
+    let value_w = text_width(&value) + 12;
+    let width = label_w + value_w;
+    let (label, value) = (xml_escape(label), xml_escape(&value));
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {value}">
+<title>{label}: {value}</title>
+<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+<g clip-path="url(#r)"><rect width="{label_w}" height="20" fill="#555"/><rect x="{label_w}" width="{value_w}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g>
+<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+<text x="{label_x}" y="14">{label}</text><text x="{value_x}" y="14">{value}</text>
+</g>
+</svg>"##,
+        color = badge_color(badge),
+        label_x = label_w / 2,
+        value_x = label_w + value_w / 2,
+    )
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -251,30 +418,35 @@ mod tests {
             total_interactions: 0,
             created_at: Utc::now(),
             unresolved_critical_vulns: 0,
+            scanned_packages: 0,
+            critical_cves: 0,
+            high_cves: 0,
+            signature: SignatureState::Unsigned,
+            publisher: PublisherReputation::default(),
         }
     }
 
     #[test]
-    fn zero_input_scores_zero_plus_age() {
+    fn zero_input_scores_only_vulnerability_status() {
         let score = compute_trust_score(&base_input());
-        // Only age can be > 0 when created_at is now — but it rounds to ~0
-        assert!(score.score < 5.0);
+        // No findings earns the vulnerability factor; everything else needs evidence
+        assert_eq!(score.score, WEIGHT_NO_VULNS);
     }
 
     #[test]
-    fn verified_adds_25_points() {
+    fn verified_adds_full_verification_weight() {
         let input = TrustInput { is_verified: true, ..base_input() };
         let score = compute_trust_score(&input);
         let v = score.factors.iter().find(|f| f.name == "Verification Status").unwrap();
-        assert_eq!(v.points_earned, 25.0);
+        assert_eq!(v.points_earned, WEIGHT_VERIFIED);
     }
 
     #[test]
-    fn perfect_audit_adds_35_points() {
+    fn perfect_audit_adds_full_audit_weight() {
         let input = TrustInput { latest_audit_score: Some(100.0), ..base_input() };
         let score = compute_trust_score(&input);
         let a = score.factors.iter().find(|f| f.name == "Audit Quality").unwrap();
-        assert!((a.points_earned - 35.0).abs() < 0.01);
+        assert!((a.points_earned - WEIGHT_AUDIT).abs() < 0.01);
     }
 
     #[test]
@@ -294,9 +466,81 @@ mod tests {
             total_interactions: 10000,
             created_at: Utc::now() - chrono::Duration::days(365),
             unresolved_critical_vulns: 0,
+            scanned_packages: 12,
+            critical_cves: 0,
+            high_cves: 0,
+            signature: SignatureState::Valid,
+            publisher: PublisherReputation {
+                other_contracts: 4,
+                other_verified: 4,
+                account_age_days: 800,
+            },
         };
         let score = compute_trust_score(&input);
         assert!(score.score <= 100.0);
+        assert!((score.score - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn weights_sum_to_100() {
+        let total = WEIGHT_VERIFIED + WEIGHT_AUDIT + WEIGHT_USAGE + WEIGHT_AGE
+            + WEIGHT_NO_VULNS + WEIGHT_DEPENDENCIES + WEIGHT_SIGNATURE + WEIGHT_PUBLISHER;
+        assert_eq!(total, 100.0);
+    }
+
+    #[test]
+    fn dependency_cves_need_a_scan_and_deduct_by_severity() {
+        let unscanned = compute_trust_score(&base_input());
+        let d = unscanned.factors.iter().find(|f| f.name == "Dependency Vulnerabilities").unwrap();
+        assert_eq!(d.points_earned, 0.0);
+
+        let input = TrustInput { scanned_packages: 8, high_cves: 1, ..base_input() };
+        let score = compute_trust_score(&input);
+        let d = score.factors.iter().find(|f| f.name == "Dependency Vulnerabilities").unwrap();
+        assert_eq!(d.points_earned, WEIGHT_DEPENDENCIES - 2.0);
+
+        let input = TrustInput { scanned_packages: 8, critical_cves: 2, ..base_input() };
+        let score = compute_trust_score(&input);
+        let d = score.factors.iter().find(|f| f.name == "Dependency Vulnerabilities").unwrap();
+        assert_eq!(d.points_earned, 0.0);
+    }
+
+    #[test]
+    fn only_valid_signatures_earn_points() {
+        for (state, expected) in [
+            (SignatureState::Valid, WEIGHT_SIGNATURE),
+            (SignatureState::Expired, 0.0),
+            (SignatureState::Revoked, 0.0),
+            (SignatureState::Unsigned, 0.0),
+        ] {
+            let score = compute_trust_score(&TrustInput { signature: state, ..base_input() });
+            let s = score.factors.iter().find(|f| f.name == "Package Signature").unwrap();
+            assert_eq!(s.points_earned, expected, "{:?}", state);
+        }
+    }
+
+    #[test]
+    fn publisher_reputation_blends_verified_share_and_age() {
+        let input = TrustInput {
+            publisher: PublisherReputation {
+                other_contracts: 4,
+                other_verified: 2,
+                account_age_days: 0,
+            },
+            ..base_input()
+        };
+        let score = compute_trust_score(&input);
+        let p = score.factors.iter().find(|f| f.name == "Publisher Reputation").unwrap();
+        assert!((p.points_earned - 0.5 * 0.6 * WEIGHT_PUBLISHER).abs() < 1e-9);
+    }
+
+    #[test]
+    fn badge_svg_shows_tier_and_score() {
+        let svg = badge_svg(82.4, "Gold");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">Gold 82</text>"));
+        assert!(svg.contains("#c9a100"));
+        assert!(svg.trim_end().ends_with("</svg>"));
     }
 
     #[test]
@@ -312,8 +556,8 @@ mod tests {
     }
 
     #[test]
-    fn factors_count_is_five() {
+    fn factors_count_is_eight() {
         let score = compute_trust_score(&base_input());
-        assert_eq!(score.factors.len(), 5);
+        assert_eq!(score.factors.len(), 8);
     }
 }
\ No newline at end of file
@@ -0,0 +1,134 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::{db_internal_error, fetch_contract_identity};
+use crate::state::AppState;
+use crate::trust::{self, TrustInput, TrustScore};
+use crate::trust_service::{self, TrustSnapshot};
+
+const DEFAULT_HISTORY_DAYS: i64 = 90;
+const MAX_HISTORY_DAYS: i64 = 730;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TrustHistoryQuery {
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrustScoreResponse {
+    pub contract_id: Uuid,
+    #[serde(flatten)]
+    pub score: TrustScore,
+    /// Raw signals the score was computed from
+    pub inputs: TrustInput,
+    pub computed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrustHistoryResponse {
+    pub contract_id: Uuid,
+    pub days: i64,
+    pub points: Vec<TrustSnapshot>,
+    /// Latest score minus the earliest score in the window
+    pub change: Option<f64>,
+}
+
+async fn current_score(
+    state: &AppState,
+    contract_uuid: Uuid,
+) -> ApiResult<(TrustScore, TrustInput)> {
+    trust_service::score_contract(&state.db, contract_uuid)
+        .await
+        .map_err(|err| db_internal_error("compute trust score", err))?
+        .ok_or_else(|| {
+            ApiError::not_found(
+                "ContractNotFound",
+                format!("No contract found with ID: {}", contract_uuid),
+            )
+        })
+}
+
+/// GET /api/contracts/:id/trust-score
+pub async fn get_trust_score(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<TrustScoreResponse>> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let (score, inputs) = current_score(&state, contract_uuid).await?;
+    Ok(Json(TrustScoreResponse {
+        contract_id: contract_uuid,
+        score,
+        inputs,
+        computed_at: Utc::now(),
+    }))
+}
+
+/// GET /api/contracts/:id/trust-score/history
+pub async fn get_trust_score_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TrustHistoryQuery>,
+) -> ApiResult<Json<TrustHistoryResponse>> {
+    let days = query.days.unwrap_or(DEFAULT_HISTORY_DAYS);
+    if !(1..=MAX_HISTORY_DAYS).contains(&days) {
+        return Err(ApiError::bad_request(
+            "InvalidDays",
+            format!("days must be between 1 and {}", MAX_HISTORY_DAYS),
+        ));
+    }
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let points =
+        trust_service::history(&state.db, contract_uuid, Utc::now() - Duration::days(days))
+            .await
+            .map_err(|err| db_internal_error("fetch trust score history", err))?;
+    let change = match (points.first(), points.last()) {
+        (Some(first), Some(last)) if points.len() > 1 => Some(last.score - first.score),
+        _ => None,
+    };
+    Ok(Json(TrustHistoryResponse {
+        contract_id: contract_uuid,
+        days,
+        points,
+        change,
+    }))
+}
+
+/// GET /api/contracts/:id/trust-badge.svg
+///
+/// Serves the latest snapshot when one is recent enough, so embedding the
+/// badge in a README does not rescore the contract on every page view.
+pub async fn get_trust_badge(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Response> {
+    let (contract_uuid, _) = fetch_contract_identity(&state, &id).await?;
+    let cached = trust_service::fresh_snapshot(&state.db, contract_uuid)
+        .await
+        .map_err(|err| db_internal_error("fetch trust snapshot", err))?;
+    let svg = match cached {
+        Some(snapshot) => trust::badge_svg(snapshot.score, &snapshot.badge),
+        None => {
+            let (score, _) = current_score(&state, contract_uuid).await?;
+            trust::badge_svg(score.score, score.badge)
+        }
+    };
+
+    let mut response = (
+        [(header::CONTENT_TYPE, "image/svg+xml; charset=utf-8")],
+        svg,
+    )
+        .into_response();
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=3600"),
+    );
+    Ok(response)
+}
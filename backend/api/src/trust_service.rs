@@ -0,0 +1,276 @@
+//! Trust score inputs and history
+//!
+//! Collects the signals `trust::compute_trust_score` needs from the registry
+//! tables and records each computed score in `contract_trust_scores`, at most
+//! once an hour unless the score changes. A background task
+//! rescores every contract every `TRUST_SCORE_REFRESH_INTERVAL_SECS` (default
+//! daily) so history has regular points even for contracts nobody views.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::trust::{self, PublisherReputation, SignatureState, TrustInput, TrustScore};
+
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 86_400;
+const REFRESH_PAGE_SIZE: i64 = 500;
+/// Minimum gap between stored snapshots with the same score.
+const SNAPSHOT_INTERVAL_SECS: i64 = 3600;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TrustSnapshot {
+    pub score: f64,
+    pub badge: String,
+    pub computed_at: DateTime<Utc>,
+}
+
+fn signature_state(status: Option<&str>) -> SignatureState {
+    match status {
+        Some("valid") => SignatureState::Valid,
+        Some("expired") => SignatureState::Expired,
+        Some("revoked") => SignatureState::Revoked,
+        _ => SignatureState::Unsigned,
+    }
+}
+
+/// Gather every scoring input for a contract. `None` if it does not exist.
+pub async fn gather_inputs(
+    pool: &PgPool,
+    contract_id: Uuid,
+) -> Result<Option<TrustInput>, sqlx::Error> {
+    let contract: Option<(bool, DateTime<Utc>)> =
+        sqlx::query_as("SELECT is_verified, created_at FROM contracts WHERE id = $1")
+            .bind(contract_id)
+            .fetch_optional(pool)
+            .await?;
+    let Some((is_verified, created_at)) = contract else {
+        return Ok(None);
+    };
+
+    let audit: Option<(Uuid, f64)> = sqlx::query_as(
+        "SELECT id, overall_score FROM security_audits
+         WHERE contract_id = $1 ORDER BY audit_date DESC LIMIT 1",
+    )
+    .bind(contract_id)
+    .fetch_optional(pool)
+    .await?;
+    let unresolved_critical_vulns: i64 = match audit {
+        Some((audit_id, _)) => {
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM audit_checks
+                 WHERE audit_id = $1 AND status = 'failed' AND severity = 'critical'",
+            )
+            .bind(audit_id)
+            .fetch_one(pool)
+            .await?
+        }
+        None => 0,
+    };
+
+    // Daily aggregates cover history; raw events fill in days not yet
+    // aggregated.
+    let total_deployments: i64 = sqlx::query_scalar(
+        "SELECT
+            COALESCE((SELECT SUM(deployment_count) FROM analytics_daily_aggregates
+                      WHERE contract_id = $1), 0)::bigint
+          + (SELECT COUNT(*) FROM analytics_events
+             WHERE contract_id = $1 AND event_type = 'contract_deployed'
+               AND created_at >= COALESCE(
+                   (SELECT MAX(date) + 1 FROM analytics_daily_aggregates WHERE contract_id = $1),
+                   '-infinity'::date))",
+    )
+    .bind(contract_id)
+    .fetch_one(pool)
+    .await?;
+    let total_interactions: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM contract_interactions WHERE contract_id = $1")
+            .bind(contract_id)
+            .fetch_one(pool)
+            .await?;
+
+    let scanned_packages: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM contract_package_dependencies WHERE contract_id = $1",
+    )
+    .bind(contract_id)
+    .fetch_one(pool)
+    .await?;
+    // One advisory can match several dependency versions; count it once.
+    let (critical_cves, high_cves): (i64, i64) = sqlx::query_as(
+        "SELECT COUNT(DISTINCT s.cve_id) FILTER (WHERE LOWER(v.severity) = 'critical'),
+                COUNT(DISTINCT s.cve_id) FILTER (WHERE LOWER(v.severity) = 'high')
+         FROM contract_scan_results s
+         JOIN cve_vulnerabilities v ON v.cve_id = s.cve_id AND v.withdrawn_at IS NULL
+         WHERE s.contract_id = $1 AND NOT s.is_false_positive",
+    )
+    .bind(contract_id)
+    .fetch_one(pool)
+    .await?;
+
+    let signature: Option<String> = sqlx::query_scalar(
+        "SELECT CASE
+                    WHEN status = 'valid' AND expires_at < NOW() THEN 'expired'
+                    ELSE status::text
+                END
+         FROM package_signatures
+         WHERE contract_id = $1
+         ORDER BY signed_at DESC
+         LIMIT 1",
+    )
+    .bind(contract_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let publisher: (i64, i64, i64) = sqlx::query_as(
+        "SELECT COUNT(other.id),
+                COUNT(other.id) FILTER (WHERE other.is_verified),
+                GREATEST(EXTRACT(DAY FROM NOW() - p.created_at), 0)::bigint
+         FROM contracts c
+         JOIN publishers p ON p.id = c.publisher_id
+         LEFT JOIN contracts other ON other.publisher_id = p.id AND other.id <> c.id
+         WHERE c.id = $1
+         GROUP BY p.created_at",
+    )
+    .bind(contract_id)
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or_default();
+
+    Ok(Some(TrustInput {
+        is_verified,
+        latest_audit_score: audit.map(|(_, score)| score),
+        total_deployments,
+        total_interactions,
+        created_at,
+        unresolved_critical_vulns,
+        scanned_packages,
+        critical_cves,
+        high_cves,
+        signature: signature_state(signature.as_deref()),
+        publisher: PublisherReputation {
+            other_contracts: publisher.0,
+            other_verified: publisher.1,
+            account_age_days: publisher.2,
+        },
+    }))
+}
+
+/// Store `score` unless an identical one was stored within the snapshot
+/// interval.
+pub async fn record(
+    pool: &PgPool,
+    contract_id: Uuid,
+    score: &TrustScore,
+    input: &TrustInput,
+) -> Result<bool, sqlx::Error> {
+    let rounded = (score.score * 100.0).round() / 100.0;
+    let inserted = sqlx::query(
+        "INSERT INTO contract_trust_scores (contract_id, score, badge, factors, inputs)
+         SELECT $1, $2, $3, $4, $5
+         WHERE NOT EXISTS (
+             SELECT 1 FROM contract_trust_scores
+             WHERE contract_id = $1 AND score = $2::numeric(5,2)
+               AND computed_at > NOW() - make_interval(secs => $6)
+         )",
+    )
+    .bind(contract_id)
+    .bind(rounded)
+    .bind(score.badge)
+    .bind(serde_json::to_value(&score.factors).unwrap_or_default())
+    .bind(serde_json::to_value(input).unwrap_or_default())
+    .bind(SNAPSHOT_INTERVAL_SECS as f64)
+    .execute(pool)
+    .await?;
+    Ok(inserted.rows_affected() > 0)
+}
+
+/// Compute a contract's current score and record it.
+pub async fn score_contract(
+    pool: &PgPool,
+    contract_id: Uuid,
+) -> Result<Option<(TrustScore, TrustInput)>, sqlx::Error> {
+    let Some(input) = gather_inputs(pool, contract_id).await? else {
+        return Ok(None);
+    };
+    let score = trust::compute_trust_score(&input);
+    record(pool, contract_id, &score, &input).await?;
+    Ok(Some((score, input)))
+}
+
+/// Most recent stored score, if it is younger than the snapshot interval.
+pub async fn fresh_snapshot(
+    pool: &PgPool,
+    contract_id: Uuid,
+) -> Result<Option<TrustSnapshot>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT score::float8 AS score, badge, computed_at FROM contract_trust_scores
+         WHERE contract_id = $1 AND computed_at > NOW() - make_interval(secs => $2)
+         ORDER BY computed_at DESC LIMIT 1",
+    )
+    .bind(contract_id)
+    .bind(SNAPSHOT_INTERVAL_SECS as f64)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn history(
+    pool: &PgPool,
+    contract_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<Vec<TrustSnapshot>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT score::float8 AS score, badge, computed_at FROM contract_trust_scores
+         WHERE contract_id = $1 AND computed_at >= $2
+         ORDER BY computed_at",
+    )
+    .bind(contract_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
+/// Rescore every contract, a page at a time.
+pub async fn refresh_all(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let mut after = Uuid::nil();
+    let mut recorded = 0;
+    loop {
+        let ids: Vec<Uuid> =
+            sqlx::query_scalar("SELECT id FROM contracts WHERE id > $1 ORDER BY id LIMIT $2")
+                .bind(after)
+                .bind(REFRESH_PAGE_SIZE)
+                .fetch_all(pool)
+                .await?;
+        let Some(last) = ids.last() else {
+            return Ok(recorded);
+        };
+        after = *last;
+        for id in ids {
+            match score_contract(pool, id).await {
+                Ok(Some(_)) => recorded += 1,
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::warn!(contract_id = %id, error = ?err, "trust: rescoring failed")
+                }
+            }
+        }
+    }
+}
+
+pub fn spawn_trust_score_refresher(pool: PgPool) {
+    let interval_secs = std::env::var("TRUST_SCORE_REFRESH_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_REFRESH_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match refresh_all(&pool).await {
+                Ok(n) => tracing::info!(count = n, "trust: contracts rescored"),
+                Err(err) => tracing::error!(error = ?err, "trust: refresh failed"),
+            }
+        }
+    });
+}
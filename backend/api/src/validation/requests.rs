@@ -489,7 +489,14 @@ impl Validatable for DependencyDeclaration {
             if self.version_constraint.is_empty() {
                 return Err("version_constraint is required".to_string());
             }
-            validate_length(&self.version_constraint, 1, MAX_VERSION_CONSTRAINT_LENGTH)
+            validate_length(&self.version_constraint, 1, MAX_VERSION_CONSTRAINT_LENGTH)?;
+            if shared::VersionConstraint::parse(&self.version_constraint).is_none() {
+                return Err(format!(
+                    "'{}' is not a valid version constraint (e.g. ^1.2, >=1.0, <2, 1.*)",
+                    self.version_constraint
+                ));
+            }
+            Ok(())
         });
 
         builder.build()
@@ -521,6 +528,7 @@ mod tests {
             source_url: Some("https://github.com/user/repo".to_string()),
             publisher_address: valid_stellar_address(),
             dependencies: vec![],
+            wasm_base64: None,
         };
 
         assert!(req.validate().is_ok());
@@ -538,6 +546,7 @@ mod tests {
             source_url: None,
             publisher_address: valid_stellar_address(),
             dependencies: vec![],
+            wasm_base64: None,
         };
 
         let result = req.validate();
@@ -559,6 +568,7 @@ mod tests {
             publisher_address: "  gdlzfc3syjydzt7k67vz75hpjvieuvnixf47zg2fb2rmqqvu2hhgcysc  "
                 .to_string(),
             dependencies: vec![],
+            wasm_base64: None,
         };
 
         req.sanitize();
@@ -0,0 +1,288 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::db_internal_error;
+use crate::state::AppState;
+use crate::webhooks::{self, WebhookDelivery, WebhookEvent, WebhookSubscription};
+
+const MAX_DELIVERY_PAGE: i64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+    /// Generated when omitted
+    pub secret_key: Option<String>,
+    /// Only deliver events for this publisher's contracts
+    pub publisher_id: Option<Uuid>,
+    /// Only deliver events for this contract
+    pub contract_id: Option<Uuid>,
+}
+
+/// Creation response; the only time the signing secret is returned.
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    pub secret_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookListQuery {
+    pub publisher_id: Option<Uuid>,
+    pub contract_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryListQuery {
+    pub limit: Option<i64>,
+    pub status: Option<String>,
+}
+
+async fn validate_create(req: &CreateWebhookRequest) -> ApiResult<()> {
+    if req.events.is_empty() {
+        return Err(ApiError::bad_request(
+            "NoEvents",
+            "Subscribe to at least one event",
+        ));
+    }
+    if let Some(unknown) = req
+        .events
+        .iter()
+        .find(|e| WebhookEvent::parse_subscribable(e).is_none())
+    {
+        let valid: Vec<&str> = WebhookEvent::SUBSCRIBABLE
+            .iter()
+            .map(|e| e.as_str())
+            .collect();
+        return Err(ApiError::bad_request(
+            "UnknownEvent",
+            format!(
+                "Unknown event type: {}. Valid: {}",
+                unknown,
+                valid.join(", ")
+            ),
+        ));
+    }
+    if req.secret_key.as_deref().is_some_and(|s| s.len() < 16) {
+        return Err(ApiError::bad_request(
+            "WeakSecret",
+            "secret_key must be at least 16 characters",
+        ));
+    }
+    // Last, since it may need a DNS lookup.
+    webhooks::check_target(&req.url, webhooks::allow_private_targets())
+        .await
+        .map_err(|err| ApiError::bad_request("InvalidWebhookUrl", err.to_string()))?;
+    Ok(())
+}
+
+fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    hex::encode(bytes)
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> ApiResult<(StatusCode, Json<CreatedWebhook>)> {
+    validate_create(&req).await?;
+
+    let mut events = req.events.clone();
+    events.sort();
+    events.dedup();
+    let secret_key = req.secret_key.clone().unwrap_or_else(generate_secret);
+
+    let subscription: WebhookSubscription = sqlx::query_as(
+        r#"
+        INSERT INTO webhook_subscriptions (publisher_id, contract_id, url, events, secret_key)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(req.publisher_id)
+    .bind(req.contract_id)
+    .bind(req.url.trim())
+    .bind(&events)
+    .bind(&secret_key)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::Database(ref e) if e.is_foreign_key_violation() => ApiError::not_found(
+            "ScopeNotFound",
+            "publisher_id or contract_id does not exist",
+        ),
+        _ => db_internal_error("create webhook", err),
+    })?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWebhook {
+            subscription,
+            secret_key,
+        }),
+    ))
+}
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Query(query): Query<WebhookListQuery>,
+) -> ApiResult<Json<Vec<WebhookSubscription>>> {
+    let webhooks: Vec<WebhookSubscription> = sqlx::query_as(
+        r#"
+        SELECT * FROM webhook_subscriptions
+        WHERE ($1::uuid IS NULL OR publisher_id = $1)
+          AND ($2::uuid IS NULL OR contract_id = $2)
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(query.publisher_id)
+    .bind(query.contract_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list webhooks", err))?;
+
+    Ok(Json(webhooks))
+}
+
+async fn fetch_webhook(state: &AppState, id: Uuid) -> ApiResult<WebhookSubscription> {
+    sqlx::query_as("SELECT * FROM webhook_subscriptions WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|err| db_internal_error("fetch webhook", err))?
+        .ok_or_else(|| ApiError::not_found("WebhookNotFound", format!("No webhook with id {}", id)))
+}
+
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<WebhookSubscription>> {
+    fetch_webhook(&state, id).await.map(Json)
+}
+
+/// Deleting a subscription also drops its delivery log.
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(|err| db_internal_error("delete webhook", err))?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(
+            "WebhookNotFound",
+            format!("No webhook with id {}", id),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Queue a `webhook.test` delivery for this subscription regardless of the
+/// events it lists.
+pub async fn test_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<(StatusCode, Json<WebhookDelivery>)> {
+    let webhook = fetch_webhook(&state, id).await?;
+    let payload = webhooks::event_payload(
+        WebhookEvent::Test,
+        webhook.contract_id,
+        serde_json::json!({ "webhook_id": webhook.id }),
+    );
+
+    let delivery: WebhookDelivery = sqlx::query_as(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event, payload, max_attempts)
+        VALUES ($1, $2, $3, 1)
+        RETURNING *
+        "#,
+    )
+    .bind(webhook.id)
+    .bind(WebhookEvent::Test.as_str())
+    .bind(payload)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|err| db_internal_error("queue test delivery", err))?;
+
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
+
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeliveryListQuery>,
+) -> ApiResult<Json<Vec<WebhookDelivery>>> {
+    fetch_webhook(&state, id).await?;
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_DELIVERY_PAGE);
+
+    let deliveries: Vec<WebhookDelivery> = sqlx::query_as(
+        r#"
+        SELECT * FROM webhook_deliveries
+        WHERE webhook_id = $1 AND ($2::text IS NULL OR status = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(id)
+    .bind(&query.status)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|err| db_internal_error("list webhook deliveries", err))?;
+
+    Ok(Json(deliveries))
+}
+
+/// Re-queue a failed or dead-lettered delivery with a fresh attempt budget.
+pub async fn retry_delivery(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<WebhookDelivery>> {
+    let delivery: Option<WebhookDelivery> = sqlx::query_as(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = 'pending', next_retry_at = NOW(),
+            max_attempts = attempt + $2, updated_at = NOW()
+        WHERE id = $1 AND status IN ('failed', 'dead_letter')
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(webhooks::DEFAULT_MAX_ATTEMPTS)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|err| db_internal_error("retry webhook delivery", err))?;
+
+    if let Some(delivery) = delivery {
+        return Ok(Json(delivery));
+    }
+
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM webhook_deliveries WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(|err| db_internal_error("fetch webhook delivery", err))?;
+    Err(if exists {
+        ApiError::conflict(
+            "DeliveryNotRetryable",
+            "Only failed or dead-lettered deliveries can be retried",
+        )
+    } else {
+        ApiError::not_found(
+            "DeliveryNotFound",
+            format!("No webhook delivery with id {}", id),
+        )
+    })
+}
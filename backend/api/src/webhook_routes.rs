@@ -0,0 +1,30 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{state::AppState, webhook_handlers};
+
+pub fn webhook_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/webhooks",
+            post(webhook_handlers::create_webhook).get(webhook_handlers::list_webhooks),
+        )
+        .route(
+            "/api/webhooks/:id",
+            get(webhook_handlers::get_webhook).delete(webhook_handlers::delete_webhook),
+        )
+        .route(
+            "/api/webhooks/:id/test",
+            post(webhook_handlers::test_webhook),
+        )
+        .route(
+            "/api/webhooks/:id/deliveries",
+            get(webhook_handlers::list_deliveries),
+        )
+        .route(
+            "/api/webhook-deliveries/:id/retry",
+            post(webhook_handlers::retry_delivery),
+        )
+}
@@ -0,0 +1,692 @@
+//! Webhook subscriptions: event emission and signed delivery
+//!
+//! Handlers call [`emit`] after a lifecycle change has been committed. It
+//! queues one `webhook_deliveries` row per matching subscription; the
+//! dispatcher spawned by [`spawn_webhook_dispatcher`] then POSTs each payload
+//! with the same `X-Soroban-*` headers and HMAC signature the CLI verifies,
+//! retrying with the notification backoff schedule until the delivery
+//! succeeds or is dead-lettered.
+//!
+//! Subscription URLs must resolve to public addresses, unless
+//! `WEBHOOK_ALLOW_PRIVATE_TARGETS=true` (for local development). They are
+//! checked when the subscription is created and again by the transport before
+//! every delivery; delivery clients resolve names through [`PublicResolver`]
+//! and never follow redirects, so neither DNS rebinding nor a 3xx can move a
+//! delivery onto an internal address.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::notification_dispatcher::backoff_delay;
+use crate::notification_transport::{
+    DeliveryOutcome, NotificationTransport, OutboundMessage, SignedWebhookTransport,
+};
+
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+const BATCH_SIZE: i64 = 25;
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+/// How long a claimed delivery is hidden from other dispatchers.
+const CLAIM_LEASE_MINUTES: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    ContractPublished,
+    ContractVerified,
+    ContractFailedVerification,
+    VersionCreated,
+    ContractDeprecated,
+    VulnerabilityDetected,
+    GovernanceProposalExecuted,
+    PerformanceAnomalyDetected,
+    PerformanceAlertTriggered,
+    /// Sent by `POST /api/webhooks/:id/test`; cannot be subscribed to.
+    Test,
+}
+
+impl WebhookEvent {
+    /// Events a subscription may list.
+    pub const SUBSCRIBABLE: [WebhookEvent; 9] = [
+        WebhookEvent::ContractPublished,
+        WebhookEvent::ContractVerified,
+        WebhookEvent::ContractFailedVerification,
+        WebhookEvent::VersionCreated,
+        WebhookEvent::ContractDeprecated,
+        WebhookEvent::VulnerabilityDetected,
+        WebhookEvent::GovernanceProposalExecuted,
+        WebhookEvent::PerformanceAnomalyDetected,
+        WebhookEvent::PerformanceAlertTriggered,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::ContractPublished => "contract.published",
+            WebhookEvent::ContractVerified => "contract.verified",
+            WebhookEvent::ContractFailedVerification => "contract.failed_verification",
+            WebhookEvent::VersionCreated => "version.created",
+            WebhookEvent::ContractDeprecated => "contract.deprecated",
+            WebhookEvent::VulnerabilityDetected => "vulnerability.detected",
+            WebhookEvent::GovernanceProposalExecuted => "governance.proposal_executed",
+            WebhookEvent::PerformanceAnomalyDetected => "performance.anomaly_detected",
+            WebhookEvent::PerformanceAlertTriggered => "performance.alert_triggered",
+            WebhookEvent::Test => "webhook.test",
+        }
+    }
+
+    pub fn parse_subscribable(s: &str) -> Option<Self> {
+        Self::SUBSCRIBABLE.into_iter().find(|e| e.as_str() == s)
+    }
+}
+
+impl std::fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum TargetError {
+    #[error("url must be an http(s) URL")]
+    InvalidUrl,
+    #[error("{0} could not be resolved")]
+    Unresolvable(String),
+    #[error("{host} resolves to {addr}, which is not a public address")]
+    PrivateAddress { host: String, addr: IpAddr },
+}
+
+/// Whether `WEBHOOK_ALLOW_PRIVATE_TARGETS` lets subscriptions point at
+/// loopback, private and link-local addresses.
+pub fn allow_private_targets() -> bool {
+    std::env::var("WEBHOOK_ALLOW_PRIVATE_TARGETS")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Addresses a subscriber could use to reach the registry's own network.
+pub fn is_internal_address(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => is_internal_v4(v4),
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_internal_v4(v4);
+            }
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local, fc00::/7
+                || (first & 0xffc0) == 0xfe80 // link-local, fe80::/10
+        }
+    }
+}
+
+fn is_internal_v4(v4: Ipv4Addr) -> bool {
+    let [a, b, ..] = v4.octets();
+    v4.is_loopback()
+        || v4.is_private()
+        || v4.is_link_local()
+        || v4.is_unspecified()
+        || v4.is_broadcast()
+        || v4.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // carrier-grade NAT, 100.64.0.0/10
+}
+
+/// Check that `url` is http(s) and that every address its host resolves to
+/// is public, unless `allow_private` is set.
+pub async fn check_target(url: &str, allow_private: bool) -> Result<(), TargetError> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|_| TargetError::InvalidUrl)?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(TargetError::InvalidUrl);
+    }
+    let host = parsed.host_str().ok_or(TargetError::InvalidUrl)?;
+    if allow_private {
+        return Ok(());
+    }
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<IpAddr> = match literal.parse::<IpAddr>() {
+        Ok(addr) => vec![addr],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|_| TargetError::Unresolvable(host.to_string()))?
+            .map(|socket| socket.ip())
+            .collect(),
+    };
+    ensure_public(host, addrs)
+}
+
+fn ensure_public(host: &str, addrs: impl IntoIterator<Item = IpAddr>) -> Result<(), TargetError> {
+    let mut resolved = false;
+    for addr in addrs {
+        if is_internal_address(addr) {
+            return Err(TargetError::PrivateAddress {
+                host: host.to_string(),
+                addr,
+            });
+        }
+        resolved = true;
+    }
+    if resolved {
+        Ok(())
+    } else {
+        Err(TargetError::Unresolvable(host.to_string()))
+    }
+}
+
+/// DNS resolver for outbound delivery clients: the system resolver, failing
+/// any lookup with an internal result. The addresses a connection uses are
+/// therefore the ones that were checked, even if the name was rebound after
+/// [`check_target`] ran.
+#[derive(Debug, Default)]
+pub struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            ensure_public(&host, addrs.iter().map(|addr| addr.ip()))?;
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(addrs)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub publisher_id: Option<Uuid>,
+    pub contract_id: Option<Uuid>,
+    pub url: String,
+    pub events: Vec<String>,
+    /// Only returned once, when the subscription is created.
+    #[serde(skip_serializing)]
+    pub secret_key: String,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub attempt: i32,
+    pub max_attempts: i32,
+    /// pending, delivered, failed (retry scheduled) or dead_letter
+    pub status: String,
+    pub response_code: Option<i32>,
+    pub error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub next_retry_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body every subscriber receives. `id` identifies the event and is shared
+/// by all of its deliveries; `X-Soroban-Delivery-Id` identifies the delivery.
+pub fn event_payload(
+    event: WebhookEvent,
+    contract_id: Option<Uuid>,
+    data: serde_json::Value,
+) -> serde_json::Value {
+    serde_json::json!({
+        "id": Uuid::new_v4(),
+        "event": event.as_str(),
+        "created_at": Utc::now(),
+        "contract_id": contract_id,
+        "data": data,
+    })
+}
+
+/// Queue `event` for every active subscription that wants it and whose
+/// publisher/contract scope covers `contract_id`. Failures are logged, not
+/// returned: the change that triggered the event has already been committed.
+pub async fn emit(pool: &PgPool, event: WebhookEvent, contract_id: Uuid, data: serde_json::Value) {
+    let payload = event_payload(event, Some(contract_id), data);
+    let result = sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event, payload, max_attempts)
+        SELECT w.id, $1, $3, $4
+        FROM webhook_subscriptions w
+        WHERE w.active
+          AND $1 = ANY(w.events)
+          AND (w.contract_id IS NULL OR w.contract_id = $2)
+          AND (w.publisher_id IS NULL
+               OR w.publisher_id = (SELECT publisher_id FROM contracts WHERE id = $2))
+        "#,
+    )
+    .bind(event.as_str())
+    .bind(contract_id)
+    .bind(&payload)
+    .bind(DEFAULT_MAX_ATTEMPTS)
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(r) if r.rows_affected() > 0 => tracing::debug!(
+            event = event.as_str(),
+            %contract_id,
+            deliveries = r.rows_affected(),
+            "webhooks: event queued"
+        ),
+        Ok(_) => {}
+        Err(err) => tracing::warn!(
+            error = ?err,
+            event = event.as_str(),
+            %contract_id,
+            "webhooks: failed to queue event"
+        ),
+    }
+}
+
+/// Spawn the delivery loop. The poll interval can be changed with
+/// `WEBHOOK_DISPATCH_INTERVAL_SECS`.
+pub fn spawn_webhook_dispatcher(pool: PgPool) {
+    let interval_secs = std::env::var("WEBHOOK_DISPATCH_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let transport = SignedWebhookTransport::new().allow_private(allow_private_targets());
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match deliver_due(&pool, &transport).await {
+                Ok(0) => {}
+                Ok(n) => tracing::debug!(count = n, "webhooks: dispatched batch"),
+                Err(err) => tracing::error!(error = ?err, "webhooks: dispatch failed"),
+            }
+        }
+    });
+}
+
+/// Attempt one batch of due deliveries; returns how many were attempted.
+///
+/// Claimed rows keep their status and have `next_retry_at` pushed out by a
+/// lease, so a crashed dispatcher's rows are picked up again later.
+pub async fn deliver_due(
+    pool: &PgPool,
+    transport: &dyn NotificationTransport,
+) -> Result<usize, sqlx::Error> {
+    let claimed: Vec<ClaimedDelivery> = sqlx::query_as(
+        r#"
+        WITH claimed AS (
+            UPDATE webhook_deliveries
+            SET attempt = attempt + 1,
+                next_retry_at = NOW() + make_interval(mins => $2),
+                updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE status IN ('pending', 'failed') AND next_retry_at <= NOW()
+                ORDER BY next_retry_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+        )
+        SELECT c.*, w.url, w.secret_key
+        FROM claimed c
+        JOIN webhook_subscriptions w ON w.id = c.webhook_id
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .bind(CLAIM_LEASE_MINUTES)
+    .fetch_all(pool)
+    .await?;
+
+    for claim in &claimed {
+        let outcome = attempt(transport, claim).await;
+        record_outcome(pool, &claim.delivery, &outcome).await?;
+    }
+
+    Ok(claimed.len())
+}
+
+#[derive(sqlx::FromRow)]
+struct ClaimedDelivery {
+    #[sqlx(flatten)]
+    delivery: WebhookDelivery,
+    url: String,
+    secret_key: String,
+}
+
+/// Send one claimed delivery, signed with its subscription's secret. The
+/// transport re-checks the target: DNS may have changed since the
+/// subscription was created.
+async fn attempt(
+    transport: &dyn NotificationTransport,
+    claim: &ClaimedDelivery,
+) -> DeliveryOutcome {
+    transport
+        .deliver(&OutboundMessage {
+            id: claim.delivery.id,
+            notification_type: claim.delivery.event.clone(),
+            recipient: claim.url.clone(),
+            subject: String::new(),
+            body: String::new(),
+            payload: claim.delivery.payload.clone(),
+            signing_secret: Some(claim.secret_key.clone()),
+        })
+        .await
+}
+
+/// Row state after an attempt.
+#[derive(Debug, Clone, PartialEq)]
+struct DeliveryUpdate {
+    status: &'static str,
+    response_code: Option<i32>,
+    error: Option<String>,
+    delivered_at: Option<DateTime<Utc>>,
+    next_retry_at: Option<DateTime<Utc>>,
+}
+
+/// Delivered, retried with backoff while attempts remain, or dead-lettered.
+fn next_state(
+    delivery: &WebhookDelivery,
+    outcome: &DeliveryOutcome,
+    now: DateTime<Utc>,
+) -> DeliveryUpdate {
+    match outcome {
+        DeliveryOutcome::Delivered { response_code } => DeliveryUpdate {
+            status: "delivered",
+            response_code: *response_code,
+            error: None,
+            delivered_at: Some(now),
+            next_retry_at: None,
+        },
+        DeliveryOutcome::Retry {
+            response_code,
+            error,
+        } if delivery.attempt < delivery.max_attempts => DeliveryUpdate {
+            status: "failed",
+            response_code: *response_code,
+            error: Some(error.clone()),
+            delivered_at: None,
+            next_retry_at: Some(
+                now + chrono::Duration::from_std(backoff_delay(delivery.attempt))
+                    .unwrap_or_else(|_| chrono::Duration::hours(6)),
+            ),
+        },
+        DeliveryOutcome::Retry {
+            response_code,
+            error,
+        }
+        | DeliveryOutcome::Rejected {
+            response_code,
+            error,
+        } => DeliveryUpdate {
+            status: "dead_letter",
+            response_code: *response_code,
+            error: Some(error.clone()),
+            delivered_at: None,
+            next_retry_at: None,
+        },
+    }
+}
+
+async fn record_outcome(
+    pool: &PgPool,
+    delivery: &WebhookDelivery,
+    outcome: &DeliveryOutcome,
+) -> Result<(), sqlx::Error> {
+    let update = next_state(delivery, outcome, Utc::now());
+    if update.status == "dead_letter" {
+        tracing::warn!(
+            delivery_id = %delivery.id,
+            webhook_id = %delivery.webhook_id,
+            attempt = delivery.attempt,
+            error = update.error.as_deref().unwrap_or_default(),
+            "webhooks: delivery dead-lettered"
+        );
+    }
+    sqlx::query(
+        "UPDATE webhook_deliveries \
+         SET status = $2, response_code = $3, error = $4, delivered_at = $5, \
+             next_retry_at = $6, updated_at = NOW() \
+         WHERE id = $1",
+    )
+    .bind(delivery.id)
+    .bind(update.status)
+    .bind(update.response_code)
+    .bind(&update.error)
+    .bind(update.delivered_at)
+    .bind(update.next_retry_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification_transport::{sign_payload, webhook_headers};
+    use std::sync::Mutex;
+
+    fn delivery(attempt: i32, max_attempts: i32) -> WebhookDelivery {
+        let now = Utc::now();
+        WebhookDelivery {
+            id: Uuid::new_v4(),
+            webhook_id: Uuid::new_v4(),
+            event: WebhookEvent::ContractVerified.as_str().to_string(),
+            payload: event_payload(
+                WebhookEvent::ContractVerified,
+                Some(Uuid::nil()),
+                serde_json::json!({ "version": "1.2.0" }),
+            ),
+            attempt,
+            max_attempts,
+            status: "pending".to_string(),
+            response_code: None,
+            error: None,
+            delivered_at: None,
+            next_retry_at: Some(now),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn retry() -> DeliveryOutcome {
+        DeliveryOutcome::Retry {
+            response_code: Some(503),
+            error: "HTTP 503".to_string(),
+        }
+    }
+
+    /// Records what it was asked to send, with the headers the signed
+    /// webhook transport would attach, and reports success.
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Mutex<Vec<(OutboundMessage, Vec<(&'static str, String)>)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl NotificationTransport for RecordingTransport {
+        async fn deliver(&self, message: &OutboundMessage) -> DeliveryOutcome {
+            let body = serde_json::to_vec(&message.payload).unwrap();
+            let headers = webhook_headers(message, &body);
+            self.sent.lock().unwrap().push((message.clone(), headers));
+            DeliveryOutcome::Delivered {
+                response_code: Some(200),
+            }
+        }
+    }
+
+    #[test]
+    fn delivered_outcome_marks_the_delivery_done() {
+        let now = Utc::now();
+        let update = next_state(
+            &delivery(1, 5),
+            &DeliveryOutcome::Delivered {
+                response_code: Some(204),
+            },
+            now,
+        );
+        assert_eq!(
+            update,
+            DeliveryUpdate {
+                status: "delivered",
+                response_code: Some(204),
+                error: None,
+                delivered_at: Some(now),
+                next_retry_at: None,
+            }
+        );
+    }
+
+    #[test]
+    fn retries_back_off_until_attempts_run_out() {
+        let now = Utc::now();
+        let update = next_state(&delivery(2, 5), &retry(), now);
+        assert_eq!(update.status, "failed");
+        assert_eq!(update.error.as_deref(), Some("HTTP 503"));
+        assert_eq!(
+            update.next_retry_at,
+            Some(now + chrono::Duration::from_std(backoff_delay(2)).unwrap())
+        );
+
+        let update = next_state(&delivery(5, 5), &retry(), now);
+        assert_eq!(update.status, "dead_letter");
+        assert_eq!(update.response_code, Some(503));
+        assert_eq!(update.next_retry_at, None);
+    }
+
+    #[test]
+    fn rejected_deliveries_are_dead_lettered_at_once() {
+        let update = next_state(
+            &delivery(1, 5),
+            &DeliveryOutcome::Rejected {
+                response_code: Some(410),
+                error: "HTTP 410".to_string(),
+            },
+            Utc::now(),
+        );
+        assert_eq!(update.status, "dead_letter");
+        assert_eq!(update.response_code, Some(410));
+        assert_eq!(update.next_retry_at, None);
+        assert_eq!(update.delivered_at, None);
+    }
+
+    #[tokio::test]
+    async fn attempts_are_signed_with_the_subscription_secret() {
+        let transport = RecordingTransport::default();
+        let claim = ClaimedDelivery {
+            delivery: delivery(1, 5),
+            url: "https://203.0.113.7/hook".to_string(),
+            secret_key: "whsec-0123456789abcdef".to_string(),
+        };
+        let outcome = attempt(&transport, &claim).await;
+        assert!(matches!(outcome, DeliveryOutcome::Delivered { .. }));
+
+        let sent = transport.sent.lock().unwrap();
+        let (message, headers) = &sent[0];
+        assert_eq!(message.recipient, claim.url);
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.clone())
+        };
+        let body = serde_json::to_vec(&claim.delivery.payload).unwrap();
+        assert_eq!(
+            header("X-Soroban-Signature"),
+            Some(format!("sha256={}", sign_payload(&claim.secret_key, &body)))
+        );
+        assert_eq!(
+            header("X-Soroban-Event").as_deref(),
+            Some("contract.verified")
+        );
+        assert_eq!(
+            header("X-Soroban-Delivery-Id"),
+            Some(claim.delivery.id.to_string())
+        );
+    }
+
+    #[test]
+    fn subscribable_events_round_trip() {
+        for event in WebhookEvent::SUBSCRIBABLE {
+            assert_eq!(
+                WebhookEvent::parse_subscribable(event.as_str()),
+                Some(event)
+            );
+        }
+        assert_eq!(WebhookEvent::parse_subscribable("webhook.test"), None);
+        assert_eq!(WebhookEvent::parse_subscribable("contract.deleted"), None);
+    }
+
+    #[test]
+    fn internal_addresses_are_recognised() {
+        for internal in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            let addr: IpAddr = internal.parse().unwrap();
+            assert!(is_internal_address(addr), "{} should be internal", internal);
+        }
+        for public in ["8.8.8.8", "203.0.113.7", "2606:4700::1111"] {
+            let addr: IpAddr = public.parse().unwrap();
+            assert!(!is_internal_address(addr), "{} should be public", public);
+        }
+    }
+
+    #[tokio::test]
+    async fn private_targets_are_refused_unless_allowed() {
+        assert_eq!(
+            check_target("ftp://example.com/hook", false).await,
+            Err(TargetError::InvalidUrl)
+        );
+        assert!(matches!(
+            check_target("http://169.254.169.254/latest/meta-data", false).await,
+            Err(TargetError::PrivateAddress { .. })
+        ));
+        assert!(matches!(
+            check_target("http://[::1]:8080/hook", false).await,
+            Err(TargetError::PrivateAddress { .. })
+        ));
+        assert!(matches!(
+            check_target("http://localhost:3001/hook", false).await,
+            Err(TargetError::PrivateAddress { .. })
+        ));
+        assert_eq!(
+            check_target("https://203.0.113.7/hook", false).await,
+            Ok(())
+        );
+        assert_eq!(
+            check_target("http://127.0.0.1:9000/hook", true).await,
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn resolver_refuses_names_with_internal_addresses() {
+        use reqwest::dns::Resolve;
+
+        let err = PublicResolver
+            .resolve("localhost".parse().unwrap())
+            .await
+            .err()
+            .expect("localhost must not resolve");
+        assert!(matches!(
+            err.downcast_ref::<TargetError>(),
+            Some(TargetError::PrivateAddress { .. })
+        ));
+    }
+}
@@ -0,0 +1,702 @@
+//! Interface conformance: does a contract's ABI implement a set of function
+//! signatures, such as the SEP-41 token interface?
+//!
+//! Types are matched structurally. Parameter names are ignored, user-defined
+//! types are resolved through each side's type table and compared by shape
+//! (struct fields by name, enum variants by name and payload), so a contract
+//! that calls its price struct `Quote` still conforms to an oracle interface
+//! that calls it `PriceData`.
+
+use crate::parser::ParseError;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Limit on nested user-defined types compared at once.
+const MAX_TYPE_DEPTH: usize = 32;
+
+/// A function an interface requires, e.g. `balance(Address) -> i128`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionSignature {
+    pub name: String,
+    pub params: Vec<SorobanType>,
+    /// `None` accepts any return type
+    pub return_type: Option<SorobanType>,
+}
+
+impl FunctionSignature {
+    /// Parse `name(Type, ...)` with an optional `-> Type`. Parameters may be
+    /// named (`id: Address`); names are not part of the signature.
+    pub fn parse(signature: &str) -> Result<Self, ParseError> {
+        let invalid = |message: &str| ParseError {
+            message: message.to_string(),
+            context: Some(format!("signature `{}`", signature.trim())),
+        };
+
+        let (head, return_type) = match signature.rsplit_once("->") {
+            Some((head, ret)) if !ret.trim().is_empty() => {
+                (head, Some(SorobanType::from_type_string(ret)))
+            }
+            Some(_) => return Err(invalid("missing return type after `->`")),
+            None => (signature, None),
+        };
+
+        let head = head.trim();
+        let open = head.find('(').ok_or_else(|| invalid("expected `(`"))?;
+        let params = head[open + 1..]
+            .strip_suffix(')')
+            .ok_or_else(|| invalid("expected `)`"))?;
+        let name = head[..open].trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(invalid("invalid function name"));
+        }
+
+        let params = split_top_level(params)
+            .into_iter()
+            .map(|param| {
+                if param.is_empty() {
+                    return Err(invalid("empty parameter"));
+                }
+                // Drop a leading `name:`, but not the `::` of a path.
+                let ty = match param.split_once(':') {
+                    Some((_, ty)) if !ty.starts_with(':') => ty,
+                    _ => param,
+                };
+                Ok(SorobanType::from_type_string(ty))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name: name.to_string(),
+            params,
+            return_type,
+        })
+    }
+}
+
+impl FromStr for FunctionSignature {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for FunctionSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params: Vec<String> = self.params.iter().map(|p| p.display_name()).collect();
+        write!(f, "{}({})", self.name, params.join(", "))?;
+        if let Some(ret) = &self.return_type {
+            write!(f, " -> {}", ret.display_name())?;
+        }
+        Ok(())
+    }
+}
+
+/// A named set of required functions plus the user-defined types they use.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceDefinition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub functions: Vec<FunctionSignature>,
+    #[serde(default)]
+    pub types: HashMap<String, SorobanType>,
+}
+
+impl InterfaceDefinition {
+    /// An ad-hoc interface from signature strings.
+    pub fn from_signatures<S: AsRef<str>>(id: &str, signatures: &[S]) -> Result<Self, ParseError> {
+        Ok(Self {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            functions: signatures
+                .iter()
+                .map(|s| FunctionSignature::parse(s.as_ref()))
+                .collect::<Result<_, _>>()?,
+            types: HashMap::new(),
+        })
+    }
+
+    /// Look up a built-in interface by id (`sep41`, `token`, `nft`, `sep40`,
+    /// `oracle`).
+    pub fn builtin(id: &str) -> Option<Self> {
+        match id.to_ascii_lowercase().replace('-', "").as_str() {
+            "sep41" | "token" => Some(sep41_token()),
+            "nft" | "nonfungible" => Some(non_fungible_token()),
+            "sep40" | "oracle" => Some(sep40_oracle()),
+            _ => None,
+        }
+    }
+
+    pub fn builtins() -> Vec<Self> {
+        vec![sep41_token(), non_fungible_token(), sep40_oracle()]
+    }
+}
+
+fn builtin(
+    id: &str,
+    name: &str,
+    description: &str,
+    signatures: &[&str],
+    types: Vec<SorobanType>,
+) -> InterfaceDefinition {
+    let mut interface = InterfaceDefinition::from_signatures(id, signatures)
+        .expect("built-in interface signatures are valid");
+    interface.name = name.to_string();
+    interface.description = description.to_string();
+    interface.types = types
+        .into_iter()
+        .map(|ty| (ty.display_name(), ty))
+        .collect();
+    interface
+}
+
+fn sep41_token() -> InterfaceDefinition {
+    builtin(
+        "sep41",
+        "SEP-41 Token",
+        "Standard fungible token interface (CAP-46-6 / SEP-41)",
+        &[
+            "allowance(from: Address, spender: Address) -> i128",
+            "approve(from: Address, spender: Address, amount: i128, expiration_ledger: u32)",
+            "balance(id: Address) -> i128",
+            "transfer(from: Address, to: Address, amount: i128)",
+            "transfer_from(spender: Address, from: Address, to: Address, amount: i128)",
+            "burn(from: Address, amount: i128)",
+            "burn_from(spender: Address, from: Address, amount: i128)",
+            "decimals() -> u32",
+            "name() -> String",
+            "symbol() -> String",
+        ],
+        Vec::new(),
+    )
+}
+
+fn non_fungible_token() -> InterfaceDefinition {
+    builtin(
+        "nft",
+        "Non-Fungible Token",
+        "Non-fungible token interface with u32 token ids (SEP-50 draft)",
+        &[
+            "balance(owner: Address) -> u32",
+            "owner_of(token_id: u32) -> Address",
+            "transfer(from: Address, to: Address, token_id: u32)",
+            "transfer_from(spender: Address, from: Address, to: Address, token_id: u32)",
+            "approve(approver: Address, approved: Address, token_id: u32, live_until_ledger: u32)",
+            "approve_for_all(owner: Address, operator: Address, live_until_ledger: u32)",
+            "get_approved(token_id: u32) -> Option<Address>",
+            "is_approved_for_all(owner: Address, operator: Address) -> bool",
+            "name() -> String",
+            "symbol() -> String",
+            "token_uri(token_id: u32) -> String",
+        ],
+        Vec::new(),
+    )
+}
+
+fn sep40_oracle() -> InterfaceDefinition {
+    let field = |name: &str, field_type: SorobanType| StructField {
+        name: name.to_string(),
+        field_type,
+        doc: None,
+    };
+    let variant = |name: &str, payload: SorobanType| EnumVariant {
+        name: name.to_string(),
+        value: None,
+        fields: Some(vec![field("0", payload)]),
+        doc: None,
+    };
+
+    builtin(
+        "sep40",
+        "SEP-40 Price Oracle",
+        "Price feed oracle interface (SEP-40)",
+        &[
+            "base() -> Asset",
+            "assets() -> Vec<Asset>",
+            "decimals() -> u32",
+            "resolution() -> u32",
+            "price(asset: Asset, timestamp: u64) -> Option<PriceData>",
+            "prices(asset: Asset, records: u32) -> Option<Vec<PriceData>>",
+            "lastprice(asset: Asset) -> Option<PriceData>",
+        ],
+        vec![
+            SorobanType::Enum {
+                name: "Asset".to_string(),
+                variants: vec![
+                    variant("Stellar", SorobanType::Address),
+                    variant("Other", SorobanType::Symbol),
+                ],
+            },
+            SorobanType::Struct {
+                name: "PriceData".to_string(),
+                fields: vec![
+                    field("price", SorobanType::I128),
+                    field("timestamp", SorobanType::U64),
+                ],
+            },
+        ],
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConformanceLevel {
+    /// No required function is implemented
+    None,
+    /// Some required functions are implemented
+    Partial,
+    /// Every required function is implemented with a matching signature
+    Full,
+}
+
+/// A required function the contract defines with the wrong shape.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionMismatch {
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConformanceReport {
+    pub interface: String,
+    pub level: ConformanceLevel,
+    /// Fraction of required functions implemented, 0.0–1.0
+    pub score: f64,
+    pub matched: Vec<String>,
+    pub missing: Vec<String>,
+    pub mismatched: Vec<FunctionMismatch>,
+}
+
+/// Check `abi` against every function `interface` requires.
+pub fn check_conformance(abi: &ContractABI, interface: &InterfaceDefinition) -> ConformanceReport {
+    let matcher = TypeMatcher {
+        expected_types: &interface.types,
+        actual_types: &abi.types,
+    };
+
+    let mut matched = Vec::new();
+    let mut missing = Vec::new();
+    let mut mismatched = Vec::new();
+
+    for required in &interface.functions {
+        let Some(function) = abi.public_functions().find(|f| f.name == required.name) else {
+            missing.push(required.name.clone());
+            continue;
+        };
+        match matcher.function_mismatch(required, function) {
+            None => matched.push(required.name.clone()),
+            Some(reason) => mismatched.push(FunctionMismatch {
+                name: required.name.clone(),
+                expected: required.to_string(),
+                actual: actual_signature(function),
+                reason,
+            }),
+        }
+    }
+
+    let total = interface.functions.len();
+    let level = if matched.len() == total {
+        ConformanceLevel::Full
+    } else if matched.is_empty() {
+        ConformanceLevel::None
+    } else {
+        ConformanceLevel::Partial
+    };
+
+    ConformanceReport {
+        interface: interface.id.clone(),
+        level,
+        score: if total == 0 {
+            1.0
+        } else {
+            matched.len() as f64 / total as f64
+        },
+        matched,
+        missing,
+        mismatched,
+    }
+}
+
+fn actual_signature(function: &ContractFunction) -> String {
+    FunctionSignature {
+        name: function.name.clone(),
+        params: function
+            .params
+            .iter()
+            .map(|p| p.param_type.clone())
+            .collect(),
+        return_type: Some(function.return_type.clone()),
+    }
+    .to_string()
+}
+
+struct TypeMatcher<'a> {
+    expected_types: &'a HashMap<String, SorobanType>,
+    actual_types: &'a HashMap<String, SorobanType>,
+}
+
+impl TypeMatcher<'_> {
+    /// Why `function` doesn't implement `required`, or `None` if it does.
+    fn function_mismatch(
+        &self,
+        required: &FunctionSignature,
+        function: &ContractFunction,
+    ) -> Option<String> {
+        if required.params.len() != function.params.len() {
+            return Some(format!(
+                "expected {} parameter(s), found {}",
+                required.params.len(),
+                function.params.len()
+            ));
+        }
+
+        for (i, (expected, param)) in required.params.iter().zip(&function.params).enumerate() {
+            if !self.conforms(expected, &param.param_type) {
+                return Some(format!(
+                    "parameter {} (`{}`): expected {}, found {}",
+                    i + 1,
+                    param.name,
+                    expected.display_name(),
+                    param.param_type.display_name()
+                ));
+            }
+        }
+
+        match &required.return_type {
+            Some(expected) if !self.conforms(expected, &function.return_type) => Some(format!(
+                "returns {}, expected {}",
+                function.return_type.display_name(),
+                expected.display_name()
+            )),
+            _ => None,
+        }
+    }
+
+    fn conforms(&self, expected: &SorobanType, actual: &SorobanType) -> bool {
+        self.conforms_in(expected, actual, &mut Vec::new())
+    }
+
+    /// `assumed` holds the pairs of user-defined types being compared further
+    /// up the stack. Meeting one again means the types are recursive and have
+    /// matched so far, so the pair is assumed to conform.
+    fn conforms_in(
+        &self,
+        expected: &SorobanType,
+        actual: &SorobanType,
+        assumed: &mut Vec<(String, String)>,
+    ) -> bool {
+        use SorobanType as T;
+
+        if let (T::Custom { name: e }, T::Custom { name: a }) = (expected, actual) {
+            let pair = (e.clone(), a.clone());
+            if assumed.contains(&pair) {
+                return true;
+            }
+            if assumed.len() >= MAX_TYPE_DEPTH {
+                return false;
+            }
+            assumed.push(pair);
+            let result = self.conforms_resolved(expected, actual, assumed);
+            assumed.pop();
+            return result;
+        }
+        self.conforms_resolved(expected, actual, assumed)
+    }
+
+    fn conforms_resolved(
+        &self,
+        expected: &SorobanType,
+        actual: &SorobanType,
+        assumed: &mut Vec<(String, String)>,
+    ) -> bool {
+        use SorobanType as T;
+
+        let expected = resolve(expected, self.expected_types);
+        let actual = resolve(actual, self.actual_types);
+        let mut conforms = |e: &SorobanType, a: &SorobanType| self.conforms_in(e, a, assumed);
+
+        match (expected, actual) {
+            (T::Option { value_type: e }, T::Option { value_type: a }) => conforms(e, a),
+            (T::Vec { element_type: e }, T::Vec { element_type: a }) => conforms(e, a),
+            (
+                T::Map {
+                    key_type: ek,
+                    value_type: ev,
+                },
+                T::Map {
+                    key_type: ak,
+                    value_type: av,
+                },
+            ) => conforms(ek, ak) && conforms(ev, av),
+            (
+                T::Result {
+                    ok_type: eo,
+                    err_type: ee,
+                },
+                T::Result {
+                    ok_type: ao,
+                    err_type: ae,
+                },
+            ) => conforms(eo, ao) && conforms(ee, ae),
+            (T::Tuple { elements: e }, T::Tuple { elements: a }) => {
+                e.len() == a.len() && e.iter().zip(a).all(|(e, a)| conforms(e, a))
+            }
+            // Struct fields are encoded by name, so order doesn't matter.
+            (T::Struct { fields: e, .. }, T::Struct { fields: a, .. }) => {
+                e.len() == a.len()
+                    && e.iter().all(|ef| {
+                        a.iter().any(|af| {
+                            af.name == ef.name && conforms(&ef.field_type, &af.field_type)
+                        })
+                    })
+            }
+            (T::Enum { variants: e, .. }, T::Enum { variants: a, .. }) => {
+                e.len() == a.len()
+                    && e.iter().all(|ev| {
+                        a.iter()
+                            .any(|av| av.name == ev.name && variant_conforms(ev, av, &mut conforms))
+                    })
+            }
+            // An interface type without a definition matches by name only.
+            (T::Custom { name: e }, actual) => match actual {
+                T::Custom { name } | T::Struct { name, .. } | T::Enum { name, .. } => e == name,
+                _ => false,
+            },
+            (expected, actual) => expected == actual,
+        }
+    }
+}
+
+fn variant_conforms(
+    expected: &EnumVariant,
+    actual: &EnumVariant,
+    conforms: &mut impl FnMut(&SorobanType, &SorobanType) -> bool,
+) -> bool {
+    if let (Some(e), Some(a)) = (expected.value, actual.value) {
+        if e != a {
+            return false;
+        }
+    }
+    // Payloads are positional; their field names are not part of the type.
+    let expected = expected.fields.as_deref().unwrap_or_default();
+    let actual = actual.fields.as_deref().unwrap_or_default();
+    expected.len() == actual.len()
+        && expected
+            .iter()
+            .zip(actual)
+            .all(|(e, a)| conforms(&e.field_type, &a.field_type))
+}
+
+/// Replace a reference to a user-defined type with its definition.
+fn resolve<'a>(ty: &'a SorobanType, types: &'a HashMap<String, SorobanType>) -> &'a SorobanType {
+    match ty {
+        SorobanType::Custom { name } => types.get(name).unwrap_or(ty),
+        _ => ty,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(signature: &str) -> ContractFunction {
+        let sig = FunctionSignature::parse(signature).unwrap();
+        ContractFunction {
+            name: sig.name,
+            visibility: FunctionVisibility::Public,
+            params: sig
+                .params
+                .into_iter()
+                .enumerate()
+                .map(|(i, param_type)| FunctionParam {
+                    name: format!("arg{}", i),
+                    param_type,
+                    doc: None,
+                })
+                .collect(),
+            return_type: sig.return_type.unwrap_or(SorobanType::Void),
+            doc: None,
+            is_mutable: true,
+        }
+    }
+
+    fn abi_with(signatures: &[&str]) -> ContractABI {
+        let mut abi = ContractABI::new("Test".to_string());
+        abi.functions = signatures.iter().map(|s| function(s)).collect();
+        abi
+    }
+
+    fn token_abi() -> ContractABI {
+        abi_with(&[
+            "allowance(Address, Address) -> i128",
+            "approve(Address, Address, i128, u32)",
+            "balance(Address) -> i128",
+            "transfer(Address, Address, i128)",
+            "transfer_from(Address, Address, Address, i128)",
+            "burn(Address, i128)",
+            "burn_from(Address, Address, i128)",
+            "decimals() -> u32",
+            "name() -> String",
+            "symbol() -> String",
+            "mint(Address, i128)",
+        ])
+    }
+
+    #[test]
+    fn parses_signatures() {
+        let sig = FunctionSignature::parse(
+            "swap(to: Address, amounts: Map<Symbol, i128>) -> Result<i128, Error>",
+        )
+        .unwrap();
+        assert_eq!(sig.name, "swap");
+        assert_eq!(
+            sig.params[1],
+            SorobanType::Map {
+                key_type: Box::new(SorobanType::Symbol),
+                value_type: Box::new(SorobanType::I128),
+            }
+        );
+        assert_eq!(
+            sig.to_string(),
+            "swap(Address, Map<Symbol, i128>) -> Result<i128, Error>"
+        );
+
+        let sig = FunctionSignature::parse("swap(Address, i128)").unwrap();
+        assert_eq!(sig.params, vec![SorobanType::Address, SorobanType::I128]);
+        assert_eq!(sig.return_type, None);
+
+        assert!(FunctionSignature::parse("swap").is_err());
+        assert!(FunctionSignature::parse("swap(Address,)").is_err());
+        assert!(FunctionSignature::parse("swap() ->").is_err());
+    }
+
+    #[test]
+    fn full_token_conforms_to_sep41() {
+        let report = check_conformance(
+            &token_abi(),
+            &InterfaceDefinition::builtin("SEP-41").unwrap(),
+        );
+        assert_eq!(report.level, ConformanceLevel::Full);
+        assert_eq!(report.score, 1.0);
+        assert!(report.missing.is_empty() && report.mismatched.is_empty());
+    }
+
+    #[test]
+    fn reports_missing_and_mismatched_functions() {
+        let mut abi = token_abi();
+        abi.functions.retain(|f| f.name != "burn_from");
+        abi.functions
+            .iter_mut()
+            .find(|f| f.name == "balance")
+            .unwrap()
+            .return_type = SorobanType::U64;
+
+        let report = check_conformance(&abi, &InterfaceDefinition::builtin("token").unwrap());
+        assert_eq!(report.level, ConformanceLevel::Partial);
+        assert_eq!(report.missing, vec!["burn_from"]);
+        assert_eq!(report.mismatched.len(), 1);
+        assert_eq!(report.mismatched[0].name, "balance");
+        assert_eq!(report.mismatched[0].reason, "returns u64, expected i128");
+        assert!((report.score - 0.8).abs() < f64::EPSILON);
+
+        let report = check_conformance(&abi_with(&["swap(Address, i128)"]), &sep41_token());
+        assert_eq!(report.level, ConformanceLevel::None);
+    }
+
+    #[test]
+    fn matches_user_defined_types_by_structure() {
+        let mut abi = abi_with(&[
+            "base() -> Token",
+            "assets() -> Vec<Token>",
+            "decimals() -> u32",
+            "resolution() -> u32",
+            "price(Token, u64) -> Option<Quote>",
+            "prices(Token, u32) -> Option<Vec<Quote>>",
+            "lastprice(Token) -> Option<Quote>",
+        ]);
+        let oracle = sep40_oracle();
+        let mut token = oracle.types["Asset"].clone();
+        if let SorobanType::Enum { name, .. } = &mut token {
+            *name = "Token".to_string();
+        }
+        abi.types.insert("Token".to_string(), token);
+        // Same fields in a different order
+        abi.types.insert(
+            "Quote".to_string(),
+            SorobanType::Struct {
+                name: "Quote".to_string(),
+                fields: vec![
+                    StructField {
+                        name: "timestamp".to_string(),
+                        field_type: SorobanType::U64,
+                        doc: None,
+                    },
+                    StructField {
+                        name: "price".to_string(),
+                        field_type: SorobanType::I128,
+                        doc: None,
+                    },
+                ],
+            },
+        );
+        assert_eq!(
+            check_conformance(&abi, &oracle).level,
+            ConformanceLevel::Full
+        );
+
+        // A struct with a different field type no longer conforms.
+        if let Some(SorobanType::Struct { fields, .. }) = abi.types.get_mut("Quote") {
+            fields[1].field_type = SorobanType::I64;
+        }
+        let report = check_conformance(&abi, &oracle);
+        assert_eq!(report.level, ConformanceLevel::Partial);
+        assert_eq!(report.mismatched.len(), 3);
+    }
+
+    #[test]
+    fn tolerates_recursive_type_tables() {
+        let mut abi = abi_with(&["get() -> Node"]);
+        abi.types.insert(
+            "Node".to_string(),
+            SorobanType::Struct {
+                name: "Node".to_string(),
+                fields: vec![StructField {
+                    name: "next".to_string(),
+                    field_type: SorobanType::Option {
+                        value_type: Box::new(SorobanType::Custom {
+                            name: "Node".to_string(),
+                        }),
+                    },
+                    doc: None,
+                }],
+            },
+        );
+        let mut interface =
+            InterfaceDefinition::from_signatures("list", &["get() -> Node"]).unwrap();
+        interface.types = abi.types.clone();
+        assert_eq!(
+            check_conformance(&abi, &interface).level,
+            ConformanceLevel::Full
+        );
+
+        // Still structural: a recursive type with a different leaf fails.
+        if let Some(SorobanType::Struct { fields, .. }) = interface.types.get_mut("Node") {
+            fields.push(StructField {
+                name: "value".to_string(),
+                field_type: SorobanType::I128,
+                doc: None,
+            });
+        }
+        assert_eq!(
+            check_conformance(&abi, &interface).level,
+            ConformanceLevel::None
+        );
+    }
+}
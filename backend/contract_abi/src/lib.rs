@@ -1,9 +1,15 @@
-//! Parse Soroban contract ABI and generate OpenAPI 3.0 documentation.
+//! Parse Soroban contract ABI, generate OpenAPI 3.0 documentation and check
+//! interface conformance.
 
+pub mod interface;
 pub mod openapi;
 pub mod parser;
 pub mod types;
 
+pub use interface::{
+    check_conformance, ConformanceLevel, ConformanceReport, FunctionMismatch, FunctionSignature,
+    InterfaceDefinition,
+};
 pub use openapi::{generate_openapi, to_json, to_yaml, OpenApiDoc};
 pub use parser::{parse_contract_abi, parse_json_spec, ParseError, RawContractSpec};
 pub use types::*;
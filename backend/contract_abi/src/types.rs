@@ -87,6 +87,30 @@ impl SorobanType {
                         element_type: Box::new(Self::from_type_string(&inner)),
                     };
                 }
+                if let Some(inner) = Self::extract_generic(trimmed, "Map") {
+                    if let [key, value] = split_top_level(&inner)[..] {
+                        return SorobanType::Map {
+                            key_type: Box::new(Self::from_type_string(key)),
+                            value_type: Box::new(Self::from_type_string(value)),
+                        };
+                    }
+                }
+                if let Some(inner) = Self::extract_generic(trimmed, "Result") {
+                    if let [ok, err] = split_top_level(&inner)[..] {
+                        return SorobanType::Result {
+                            ok_type: Box::new(Self::from_type_string(ok)),
+                            err_type: Box::new(Self::from_type_string(err)),
+                        };
+                    }
+                }
+                if let Some(inner) = trimmed.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+                    return SorobanType::Tuple {
+                        elements: split_top_level(inner)
+                            .into_iter()
+                            .map(Self::from_type_string)
+                            .collect(),
+                    };
+                }
                 if let Some(n) = Self::extract_bytes_n(trimmed) {
                     return SorobanType::BytesN { n };
                 }
@@ -163,6 +187,30 @@ impl SorobanType {
     }
 }
 
+/// Split a comma-separated type list, ignoring commas nested inside `<>` or
+/// `()`: `"Address, Map<Symbol, i128>"` → `["Address", "Map<Symbol, i128>"]`.
+pub(crate) fn split_top_level(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = list[start..].trim();
+    if !last.is_empty() || !parts.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StructField {
     pub name: String,
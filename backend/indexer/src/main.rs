@@ -189,20 +189,13 @@ impl IndexerService {
         for i in 0..ledgers_to_process {
             let ledger_height = next_ledger + i;
 
-            // Fetch ledger details to get the hash
-            let ledger = self.rpc_client.get_ledger(ledger_height).await.map_err(|e| {
-                error!(
-                    network = network_name,
-                    ledger = ledger_height,
-                    error = %e,
-                    "Failed to fetch ledger details"
-                );
-                e
-            })?;
-
-            // Fetch ledger operations
-            match self.rpc_client.get_ledger_operations(ledger_height).await {
-                Ok(operations) => {
+            // Fetch the ledger's hash and operations in one request
+            match self
+                .rpc_client
+                .get_ledger_with_operations(ledger_height)
+                .await
+            {
+                Ok((ledger, operations)) => {
                     info!(
                         network = network_name,
                         ledger = ledger_height,
@@ -271,7 +264,7 @@ impl IndexerService {
                         network = network_name,
                         ledger = ledger_height,
                         error = %e,
-                        "Failed to fetch ledger operations"
+                        "Failed to fetch ledger"
                     );
                     return Err(e.into());
                 }
@@ -1,5 +1,6 @@
 /// RPC client for polling Stellar network ledgers
 /// Handles HTTP requests to Stellar RPC endpoints and deserializes ledger/operation data
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use thiserror::Error;
@@ -34,6 +35,14 @@ pub struct Ledger {
     pub timestamp: String,
 }
 
+/// Tip of the chain as reported by `getLatestLedger`, which returns no
+/// header: fetch the ledger itself for its parent hash and close time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestLedger {
+    pub sequence: u64,
+    pub hash: String,
+}
+
 /// Operation from ledger
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Operation {
@@ -56,36 +65,49 @@ pub struct ContractDeployment {
     pub ledger_sequence: u64,
 }
 
-/// RPC response for ledgers
+/// Operation type code the detector treats as a contract deployment.
+pub const CREATE_CONTRACT_TYPE_CODE: u32 = 110;
+
+/// JSON-RPC response envelope
 #[derive(Debug, Clone, Deserialize)]
-#[serde(untagged)]
-enum RpcResponse<T> {
-    Success(T),
-    Error { error: serde_json::Value },
+struct JsonRpcResponse<T> {
+    result: Option<T>,
+    error: Option<JsonRpcError>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct LedgerResponse {
-    sequence: u64,
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LatestLedgerResponse {
     id: String,
-    hash: String,
-    prev_hash: Option<String>,
-    closed_at: String,
+    sequence: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct OperationsResponse {
-    records: Vec<OperationRecord>,
+struct LedgersResponse {
+    ledgers: Vec<LedgerInfo>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct OperationRecord {
-    id: String,
-    transaction_hash: String,
-    type_code: u32,
-    type_name: String,
+#[serde(rename_all = "camelCase")]
+struct LedgerInfo {
+    hash: String,
+    sequence: u64,
+    ledger_close_time: String,
+    #[serde(default)]
+    header_json: Option<serde_json::Value>,
     #[serde(default)]
-    body: serde_json::Value,
+    metadata_json: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct HealthResponse {
+    status: String,
 }
 
 impl StellarRpcClient {
@@ -103,101 +125,31 @@ impl StellarRpcClient {
         }
     }
 
-    /// Fetch ledger by sequence number
-    pub async fn get_ledger(&self, sequence: u64) -> Result<Ledger, RpcError> {
-        let url = format!("{}/ledgers/{}", self.endpoint, sequence);
-        debug!("Fetching ledger from {}", url);
-
-        let response = self
-            .client
-            .get(&url)
-            .timeout(self.request_timeout)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    RpcError::Timeout
-                } else {
-                    RpcError::RequestFailed(e.to_string())
-                }
-            })?;
-
-        if !response.status().is_success() {
-            return Err(RpcError::RpcError(format!(
-                "HTTP {}: {}",
-                response.status(),
-                response.text().await.unwrap_or_default()
-            )));
-        }
-
-        let data: LedgerResponse = response.json().await.map_err(|e| {
-            RpcError::InvalidResponse(format!("Failed to parse ledger response: {}", e))
-        })?;
-
-        Ok(Ledger {
-            sequence: data.sequence,
-            id: data.id,
-            hash: data.hash,
-            prev_hash: data.prev_hash.unwrap_or_default(),
-            timestamp: data.closed_at,
-        })
-    }
-
-    /// Fetch operations for a ledger
-    pub async fn get_ledger_operations(&self, sequence: u64) -> Result<Vec<Operation>, RpcError> {
-        let url = format!(
-            "{}/ledgers/{}/operations?order=asc&limit=200",
-            self.endpoint, sequence
-        );
-        debug!("Fetching operations for ledger {} from {}", sequence, url);
-
-        let response = self
-            .client
-            .get(&url)
-            .timeout(self.request_timeout)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    RpcError::Timeout
-                } else {
-                    RpcError::RequestFailed(e.to_string())
-                }
-            })?;
-
-        if !response.status().is_success() {
-            return Err(RpcError::RpcError(format!(
-                "HTTP {}: {}",
-                response.status(),
-                response.text().await.unwrap_or_default()
-            )));
-        }
-
-        let data: OperationsResponse = response.json().await.map_err(|e| {
-            RpcError::InvalidResponse(format!("Failed to parse operations response: {}", e))
-        })?;
-
-        Ok(data
-            .records
-            .into_iter()
-            .map(|op| Operation {
-                id: op.id,
-                tx_id: op.transaction_hash,
-                type_code: op.type_code,
-                type_name: op.type_name,
-                body: op.body,
-            })
-            .collect())
+    /// Override the per-request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
     }
 
-    /// Get the latest ledger
-    pub async fn get_latest_ledger(&self) -> Result<Ledger, RpcError> {
-        let url = format!("{}/ledgers?order=desc&limit=1", self.endpoint);
-        debug!("Fetching latest ledger from {}", url);
+    /// Send a JSON-RPC request and return its result
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, RpcError> {
+        debug!("Calling {} on {}", method, self.endpoint);
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
 
         let response = self
             .client
-            .get(&url)
+            .post(&self.endpoint)
+            .json(&request)
             .timeout(self.request_timeout)
             .send()
             .await
@@ -217,107 +169,218 @@ impl StellarRpcClient {
             )));
         }
 
-        // Parse the response - it returns an array
         let response_text = response
             .text()
             .await
             .map_err(|e| RpcError::InvalidResponse(format!("Failed to read response: {}", e)))?;
 
-        let data: serde_json::Value = serde_json::from_str(&response_text).map_err(|e| {
-            error!("Invalid JSON in ledger response: {}", e);
+        let data: JsonRpcResponse<T> = serde_json::from_str(&response_text).map_err(|e| {
+            error!("Invalid JSON in {} response: {}", method, e);
             RpcError::InvalidResponse(format!("Invalid JSON: {}", e))
         })?;
 
-        // Extract first ledger from _embedded records
-        let ledgers = data
-            .get("_embedded")
-            .and_then(|e| e.get("records"))
-            .and_then(|r| r.as_array())
-            .ok_or_else(|| {
-                error!("No records found in latest ledger response");
-                RpcError::InvalidResponse("No records in response".to_string())
-            })?;
+        if let Some(err) = data.error {
+            return Err(RpcError::RpcError(format!(
+                "{} ({})",
+                err.message, err.code
+            )));
+        }
 
-        let ledger = ledgers.first().ok_or_else(|| {
-            error!("Empty records array in latest ledger response");
-            RpcError::InvalidResponse("Empty records array".to_string())
-        })?;
+        data.result
+            .ok_or_else(|| RpcError::InvalidResponse(format!("No result in {} response", method)))
+    }
 
-        let sequence = ledger
-            .get("sequence")
-            .and_then(|v| v.as_u64())
+    /// Fetch a single ledger through `getLedgers`
+    async fn get_ledger_info(&self, sequence: u64) -> Result<LedgerInfo, RpcError> {
+        let response: LedgersResponse = self
+            .call(
+                "getLedgers",
+                serde_json::json!({
+                    "startLedger": sequence,
+                    "pagination": { "limit": 1 },
+                    "xdrFormat": "json",
+                }),
+            )
+            .await?;
+
+        response
+            .ledgers
+            .into_iter()
+            .find(|l| l.sequence == sequence)
             .ok_or_else(|| {
-                error!("Missing or invalid sequence in ledger: {:?}", ledger);
-                RpcError::InvalidResponse("Missing sequence".to_string())
-            })?;
+                RpcError::InvalidResponse(format!("Ledger {} not returned by RPC", sequence))
+            })
+    }
 
-        let hash = ledger
-            .get("hash")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .ok_or_else(|| {
-                error!("Missing hash in ledger");
-                RpcError::InvalidResponse("Missing hash".to_string())
-            })?;
+    /// Fetch ledger by sequence number
+    pub async fn get_ledger(&self, sequence: u64) -> Result<Ledger, RpcError> {
+        let info = self.get_ledger_info(sequence).await?;
+        Ok(ledger_header(&info))
+    }
 
-        let prev_hash = ledger
-            .get("prev_hash")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .unwrap_or_default();
+    /// Fetch a ledger and its operations with a single `getLedgers` call
+    ///
+    /// Soroban RPC has no operations endpoint, so operations are read from
+    /// the ledger's close meta: every contract instance a transaction created
+    /// is reported as a createContract operation.
+    pub async fn get_ledger_with_operations(
+        &self,
+        sequence: u64,
+    ) -> Result<(Ledger, Vec<Operation>), RpcError> {
+        let info = self.get_ledger_info(sequence).await?;
+        let ledger = ledger_header(&info);
+
+        let operations = match &info.metadata_json {
+            Some(meta) => contract_creations(meta),
+            None => {
+                warn!(
+                    "Ledger {} has no metadata, assuming no operations",
+                    sequence
+                );
+                Vec::new()
+            }
+        };
+
+        Ok((ledger, operations))
+    }
 
-        let id = ledger
-            .get("id")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| hash.clone());
+    /// Get the latest ledger
+    pub async fn get_latest_ledger(&self) -> Result<LatestLedger, RpcError> {
+        let latest: LatestLedgerResponse =
+            self.call("getLatestLedger", serde_json::json!({})).await?;
 
-        let timestamp = ledger
-            .get("closed_at")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .unwrap_or_default();
-
-        Ok(Ledger {
-            sequence,
-            id,
-            hash,
-            prev_hash,
-            timestamp,
+        Ok(LatestLedger {
+            sequence: latest.sequence,
+            hash: latest.id,
         })
     }
 
     /// Check endpoint health
     pub async fn health_check(&self) -> Result<(), RpcError> {
-        let url = format!("{}/health", self.endpoint);
-        debug!("Checking RPC health at {}", url);
-
-        let response = self
-            .client
-            .get(&url)
-            .timeout(self.request_timeout)
-            .send()
+        let health: HealthResponse = self
+            .call("getHealth", serde_json::json!({}))
             .await
-            .map_err(|e| {
-                warn!("Health check failed: {}", e);
-                if e.is_timeout() {
-                    RpcError::Timeout
-                } else {
-                    RpcError::RequestFailed(e.to_string())
-                }
-            })?;
+            .inspect_err(|e| warn!("Health check failed: {}", e))?;
 
-        if response.status().is_success() {
+        if health.status == "healthy" {
             Ok(())
         } else {
             Err(RpcError::RpcError(format!(
-                "Health check failed with status {}",
-                response.status()
+                "Health check reported status {}",
+                health.status
             )))
         }
     }
 }
 
+/// Header fields of a `getLedgers` entry
+fn ledger_header(info: &LedgerInfo) -> Ledger {
+    let prev_hash = info
+        .header_json
+        .as_ref()
+        .and_then(|h| h.pointer("/header/previous_ledger_hash"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_default();
+
+    Ledger {
+        sequence: info.sequence,
+        id: info.hash.clone(),
+        hash: info.hash.clone(),
+        prev_hash,
+        timestamp: close_time_rfc3339(&info.ledger_close_time),
+    }
+}
+
+/// Convert RPC's unix-seconds close time to the RFC 3339 form Horizon used
+fn close_time_rfc3339(close_time: &str) -> String {
+    close_time
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| close_time.to_string())
+}
+
+/// Extract createContract operations from JSON-encoded `LedgerCloseMeta`
+fn contract_creations(meta: &serde_json::Value) -> Vec<Operation> {
+    let mut operations = Vec::new();
+
+    for tx in find_key(meta, "tx_processing")
+        .into_iter()
+        .filter_map(|v| v.as_array())
+        .flatten()
+    {
+        let tx_hash = tx
+            .pointer("/result/transaction_hash")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        // The fee source pays for the deployment; RPC meta doesn't carry the
+        // envelope, so it stands in for the operation source.
+        let deployer = tx
+            .get("fee_processing")
+            .and_then(|changes| find_key(changes, "account_id").into_iter().next())
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let Some(apply) = tx.get("tx_apply_processing") else {
+            continue;
+        };
+
+        for created in find_key(apply, "created") {
+            let Some(data) = created.pointer("/data/contract_data") else {
+                continue;
+            };
+            if data.get("key").and_then(|k| k.as_str()) != Some("ledger_key_contract_instance") {
+                continue;
+            }
+            let Some(contract) = data.get("contract").and_then(|c| c.as_str()) else {
+                continue;
+            };
+
+            let mut body = serde_json::json!({ "contract": contract });
+            if let Some(deployer) = &deployer {
+                body["source_account"] = serde_json::Value::String(deployer.clone());
+            }
+
+            operations.push(Operation {
+                id: format!("{}-{}", tx_hash, operations.len()),
+                tx_id: tx_hash.clone(),
+                type_code: CREATE_CONTRACT_TYPE_CODE,
+                type_name: "create_contract".to_string(),
+                body,
+            });
+        }
+    }
+
+    operations
+}
+
+/// Every value stored under `key`, at any depth
+fn find_key<'a>(value: &'a serde_json::Value, key: &str) -> Vec<&'a serde_json::Value> {
+    let mut found = Vec::new();
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                if k == key {
+                    found.push(v);
+                } else {
+                    found.extend(find_key(v, key));
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                found.extend(find_key(item, key));
+            }
+        }
+        _ => {}
+    }
+    found
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,164 @@
+//! RPC client tests against the mock Stellar RPC server
+//! These run the real client over HTTP without network access
+
+use indexer::detect_contract_deployments;
+use indexer::reorg::ReorgHandler;
+use indexer::rpc::{RpcError, StellarRpcClient};
+use indexer::state::IndexerState;
+use mock_rpc::{Fault, Fixture, LedgerFixture, MockRpcServer};
+use serde_json::json;
+use std::time::Duration;
+
+const CONTRACT_ID: &str = "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC";
+const DEPLOYER: &str = "GBZXN7PIRZGNMHGA7MUUUF4GWPY5AYPV6LY4UV2GL6VJGIQRXFDNMADI";
+
+/// Close meta for a ledger with one transaction that deploys `CONTRACT_ID`
+fn deployment_meta() -> serde_json::Value {
+    json!({
+        "v1": {
+            "tx_processing": [{
+                "result": { "transaction_hash": "ab".repeat(32) },
+                "fee_processing": [
+                    { "state": { "data": { "account": { "account_id": DEPLOYER } } } }
+                ],
+                "tx_apply_processing": {
+                    "v3": {
+                        "operations": [{
+                            "changes": [
+                                { "created": { "data": { "contract_data": {
+                                    "contract": CONTRACT_ID,
+                                    "key": "ledger_key_contract_instance",
+                                    "durability": "persistent"
+                                } } } },
+                                { "created": { "data": { "contract_data": {
+                                    "contract": CONTRACT_ID,
+                                    "key": { "symbol": "Admin" },
+                                    "durability": "persistent"
+                                } } } }
+                            ]
+                        }]
+                    }
+                }
+            }]
+        }
+    })
+}
+
+fn fixture() -> Fixture {
+    let mut fixture = Fixture::with_ledgers(100, 105);
+    fixture.ledgers[2] = LedgerFixture {
+        sequence: 102,
+        header_json: Some(json!({ "header": { "previous_ledger_hash": "00".repeat(32) } })),
+        metadata_json: Some(deployment_meta()),
+        ..Default::default()
+    };
+    fixture
+}
+
+fn state_at(server: &MockRpcServer, height: u64) -> IndexerState {
+    IndexerState {
+        network: shared::Network::Testnet,
+        last_indexed_ledger_height: height,
+        last_indexed_ledger_hash: server.ledger(height as u32).map(|l| l.hash),
+        last_checkpoint_ledger_height: 100,
+        consecutive_failures: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_latest_and_historical_ledgers() {
+    let server = MockRpcServer::start(fixture()).await.unwrap();
+    let client = StellarRpcClient::new(server.url());
+
+    client.health_check().await.unwrap();
+
+    let latest = client.get_latest_ledger().await.unwrap();
+    assert_eq!(latest.sequence, 105);
+    assert_eq!(latest.hash, server.latest_ledger().hash);
+
+    let ledger = client.get_ledger(102).await.unwrap();
+    assert_eq!(ledger.sequence, 102);
+    assert_eq!(ledger.hash, server.ledger(102).unwrap().hash);
+    assert_eq!(ledger.prev_hash, "00".repeat(32));
+    assert!(ledger.timestamp.starts_with("2023-11-14T"));
+
+    assert!(client.get_ledger(106).await.is_err());
+}
+
+#[tokio::test]
+async fn test_detects_deployments_from_ledger_meta() {
+    let server = MockRpcServer::start(fixture()).await.unwrap();
+    let client = StellarRpcClient::new(server.url());
+
+    let (ledger, operations) = client.get_ledger_with_operations(102).await.unwrap();
+    assert_eq!(ledger.hash, server.ledger(102).unwrap().hash);
+    assert_eq!(ledger.prev_hash, "00".repeat(32));
+    assert_eq!(operations.len(), 1);
+    // Header and operations come from the same getLedgers response
+    assert_eq!(server.call_count("getLedgers"), 1);
+
+    let deployments = detect_contract_deployments(&operations, 102);
+    assert_eq!(deployments.len(), 1);
+    assert_eq!(deployments[0].contract_id, CONTRACT_ID);
+    assert_eq!(deployments[0].deployer, DEPLOYER);
+    assert_eq!(deployments[0].tx_id, "ab".repeat(32));
+
+    // Ledgers without meta have nothing to index
+    let (_, operations) = client.get_ledger_with_operations(103).await.unwrap();
+    assert!(operations.is_empty());
+}
+
+#[tokio::test]
+async fn test_surfaces_rpc_failures() {
+    let server = MockRpcServer::start(fixture()).await.unwrap();
+    let client = StellarRpcClient::new(server.url()).with_timeout(Duration::from_millis(200));
+
+    server.fail_next("getHealth", 1, Fault::HttpStatus { status: 503 });
+    assert!(matches!(
+        client.health_check().await,
+        Err(RpcError::RpcError(_))
+    ));
+    client.health_check().await.unwrap();
+
+    server.fail_next(
+        "getLatestLedger",
+        1,
+        Fault::RpcError {
+            code: -32603,
+            message: "database locked".to_string(),
+        },
+    );
+    match client.get_latest_ledger().await {
+        Err(RpcError::RpcError(message)) => assert!(message.contains("database locked")),
+        other => panic!("expected RPC error, got {:?}", other),
+    }
+
+    server.fail_next("getLedgers", 1, Fault::Malformed);
+    assert!(matches!(
+        client.get_ledger(101).await,
+        Err(RpcError::InvalidResponse(_))
+    ));
+
+    server.fail_next("getLedgers", 1, Fault::Hang { ms: 1_000 });
+    assert!(matches!(
+        client.get_ledger(101).await,
+        Err(RpcError::Timeout)
+    ));
+}
+
+#[tokio::test]
+async fn test_reorg_detected_after_fork() {
+    let server = MockRpcServer::start(fixture()).await.unwrap();
+    let client = StellarRpcClient::new(server.url());
+    let handler = ReorgHandler::new(100);
+    let state = state_at(&server, 104);
+    let older = state_at(&server, 102);
+
+    assert!(!handler.check_for_reorg(&client, &state).await.unwrap());
+
+    server.reorg(3, vec![]);
+    assert!(handler.check_for_reorg(&client, &state).await.unwrap());
+
+    // Ledgers below the fork point are untouched
+    assert!(!handler.check_for_reorg(&client, &older).await.unwrap());
+}
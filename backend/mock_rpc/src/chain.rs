@@ -0,0 +1,375 @@
+//! The mock's view of the network: ledgers, ledger entries, events and
+//! submitted transactions, plus the scripted behaviour (faults, reorgs,
+//! automatic ledger closes) that mutates it.
+
+use crate::fixture::{
+    EventFixture, Fault, FaultFixture, Fixture, LatencyFixture, LedgerEntryFixture, LedgerFixture,
+    ReorgFixture, SimulationFixture, TransactionFixture, GENESIS_CLOSE_TIME, LEDGER_CLOSE_SECS,
+};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ledger {
+    pub sequence: u32,
+    pub hash: String,
+    pub close_time: i64,
+    pub header_xdr: Option<String>,
+    pub metadata_xdr: Option<String>,
+    pub header_json: Option<Value>,
+    pub metadata_json: Option<Value>,
+}
+
+impl Ledger {
+    /// Ledger with a hash derived from its sequence and fork, so generated
+    /// chains are identical across runs and differ after every reorg.
+    pub fn generated(sequence: u32, fork: u32) -> Self {
+        let hash = Sha256::digest(format!("mock-ledger:{}:{}", fork, sequence));
+        Self {
+            sequence,
+            hash: hex::encode(hash),
+            close_time: GENESIS_CLOSE_TIME + LEDGER_CLOSE_SECS * i64::from(sequence),
+            header_xdr: None,
+            metadata_xdr: None,
+            header_json: None,
+            metadata_json: None,
+        }
+    }
+
+    fn from_fixture(fixture: &LedgerFixture, fork: u32) -> Self {
+        let generated = Self::generated(fixture.sequence, fork);
+        Self {
+            sequence: fixture.sequence,
+            hash: fixture.hash.clone().unwrap_or(generated.hash),
+            close_time: fixture.close_time.unwrap_or(generated.close_time),
+            header_xdr: fixture.header_xdr.clone(),
+            metadata_xdr: fixture.metadata_xdr.clone(),
+            header_json: fixture.header_json.clone(),
+            metadata_json: fixture.metadata_json.clone(),
+        }
+    }
+}
+
+/// Position of an event in the chain, which is also its id and paging token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventPosition {
+    pub ledger: u32,
+    pub index: u32,
+}
+
+impl EventPosition {
+    /// Formats the position like RPC's TOID-based event ids.
+    pub fn id(&self) -> String {
+        format!("{:019}-{:010}", u64::from(self.ledger) << 32, self.index)
+    }
+
+    pub fn parse(id: &str) -> Option<Self> {
+        let (toid, index) = id.split_once('-')?;
+        let toid: u64 = toid.parse().ok()?;
+        Some(Self {
+            ledger: u32::try_from(toid >> 32).ok()?,
+            index: index.parse().ok()?,
+        })
+    }
+}
+
+/// A JSON-RPC request the server received, in arrival order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecordedCall {
+    pub method: String,
+    pub params: Value,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Submission {
+    pub fixture: TransactionFixture,
+    pub envelope: String,
+    pub polls_left: u32,
+    pub ledger: Option<u32>,
+}
+
+struct ActiveFault {
+    fixture: FaultFixture,
+    matched: u32,
+}
+
+pub struct Chain {
+    pub network_passphrase: String,
+    pub protocol_version: u32,
+    pub latency: LatencyFixture,
+    ledgers: BTreeMap<u32, Ledger>,
+    oldest: u32,
+    latest: u32,
+    fork: u32,
+    /// Sorted by ledger; order within a ledger is the event index.
+    events: Vec<EventFixture>,
+    pub(crate) ledger_entries: Vec<LedgerEntryFixture>,
+    pub(crate) simulations: Vec<SimulationFixture>,
+    pub(crate) transactions: Vec<TransactionFixture>,
+    pub(crate) submissions: HashMap<String, Submission>,
+    faults: Vec<ActiveFault>,
+    pending_reorgs: Vec<ReorgFixture>,
+    advance_every: Option<u32>,
+    latest_ledger_calls: u32,
+    calls: Vec<RecordedCall>,
+}
+
+impl Chain {
+    pub fn new(fixture: Fixture) -> Self {
+        let mut ledgers = BTreeMap::new();
+        for ledger in &fixture.ledgers {
+            ledgers.insert(ledger.sequence, Ledger::from_fixture(ledger, 0));
+        }
+        let oldest = fixture.ledgers.first().map_or(1, |l| l.sequence);
+        let last = fixture.ledgers.last().map_or(1, |l| l.sequence);
+        let latest = fixture.latest_ledger.unwrap_or(last);
+        for sequence in oldest..=latest.max(last) {
+            ledgers
+                .entry(sequence)
+                .or_insert_with(|| Ledger::generated(sequence, 0));
+        }
+        let mut events = fixture.events;
+        events.sort_by_key(|e| e.ledger);
+        let mut pending_reorgs = fixture.reorgs;
+        pending_reorgs.sort_by_key(|r| r.at_ledger);
+
+        Self {
+            network_passphrase: fixture.network_passphrase,
+            protocol_version: fixture.protocol_version,
+            latency: fixture.latency,
+            ledgers,
+            oldest,
+            latest,
+            fork: 0,
+            events,
+            ledger_entries: fixture.ledger_entries,
+            simulations: fixture.simulations,
+            transactions: fixture.transactions,
+            submissions: HashMap::new(),
+            faults: fixture
+                .faults
+                .into_iter()
+                .map(|fixture| ActiveFault {
+                    fixture,
+                    matched: 0,
+                })
+                .collect(),
+            pending_reorgs,
+            advance_every: fixture.advance_every,
+            latest_ledger_calls: 0,
+            calls: Vec::new(),
+        }
+    }
+
+    pub fn oldest(&self) -> &Ledger {
+        &self.ledgers[&self.oldest]
+    }
+
+    pub fn latest(&self) -> &Ledger {
+        &self.ledgers[&self.latest]
+    }
+
+    /// A ledger on the current chain, if it has closed.
+    pub fn ledger(&self, sequence: u32) -> Option<&Ledger> {
+        if sequence > self.latest {
+            return None;
+        }
+        self.ledgers.get(&sequence)
+    }
+
+    /// Closed ledgers from `start` onwards.
+    pub fn ledgers_from(&self, start: u32) -> impl Iterator<Item = &Ledger> {
+        self.ledgers.range(start..=self.latest).map(|(_, l)| l)
+    }
+
+    /// Events on closed ledgers, with their positions.
+    pub fn events(&self) -> impl Iterator<Item = (EventPosition, &EventFixture)> {
+        let latest = self.latest;
+        let mut previous_ledger = None;
+        let mut index = 0;
+        self.events
+            .iter()
+            .take_while(move |e| e.ledger <= latest)
+            .map(move |event| {
+                if previous_ledger == Some(event.ledger) {
+                    index += 1;
+                } else {
+                    previous_ledger = Some(event.ledger);
+                    index = 0;
+                }
+                (
+                    EventPosition {
+                        ledger: event.ledger,
+                        index,
+                    },
+                    event,
+                )
+            })
+    }
+
+    /// Closes `count` ledgers, applying any reorg scripted for them.
+    pub fn advance(&mut self, count: u32) {
+        for _ in 0..count {
+            self.latest += 1;
+            let sequence = self.latest;
+            let fork = self.fork;
+            self.ledgers
+                .entry(sequence)
+                .or_insert_with(|| Ledger::generated(sequence, fork));
+            while let Some(reorg) = self
+                .pending_reorgs
+                .first()
+                .filter(|r| r.at_ledger <= sequence)
+                .cloned()
+            {
+                self.pending_reorgs.remove(0);
+                let first = reorg.at_ledger.saturating_sub(reorg.depth).max(self.oldest);
+                self.replace(first, sequence, &reorg.ledgers, reorg.events);
+            }
+        }
+    }
+
+    /// Replaces the `depth` most recent ledgers with a new fork.
+    pub fn reorg(&mut self, depth: u32, ledgers: &[LedgerFixture], events: Vec<EventFixture>) {
+        let depth = depth.clamp(1, self.latest - self.oldest + 1);
+        self.replace(self.latest + 1 - depth, self.latest, ledgers, events);
+    }
+
+    fn replace(
+        &mut self,
+        first: u32,
+        last: u32,
+        ledgers: &[LedgerFixture],
+        events: Vec<EventFixture>,
+    ) {
+        self.fork += 1;
+        for sequence in first..=last {
+            let ledger = match ledgers.iter().find(|l| l.sequence == sequence) {
+                Some(fixture) => Ledger::from_fixture(fixture, self.fork),
+                None => Ledger::generated(sequence, self.fork),
+            };
+            self.ledgers.insert(sequence, ledger);
+        }
+        self.events.retain(|e| e.ledger < first || e.ledger > last);
+        self.events.extend(events);
+        self.events.sort_by_key(|e| e.ledger);
+        tracing::info!(first, last, fork = self.fork, "mock chain reorganised");
+    }
+
+    pub fn add_fault(&mut self, fixture: FaultFixture) {
+        self.faults.push(ActiveFault {
+            fixture,
+            matched: 0,
+        });
+    }
+
+    pub fn clear_faults(&mut self) {
+        self.faults.clear();
+    }
+
+    /// Counts the call against every fault for `method` and returns the
+    /// first fault that fires.
+    pub(crate) fn take_fault(&mut self, method: &str) -> Option<Fault> {
+        let mut fired = None;
+        for fault in &mut self.faults {
+            if fault.fixture.method.as_deref().is_some_and(|m| m != method) {
+                continue;
+            }
+            fault.matched += 1;
+            let after_skip = fault.matched > fault.fixture.skip;
+            let within_times = fault
+                .fixture
+                .times
+                .is_none_or(|times| fault.matched <= fault.fixture.skip + times);
+            if fired.is_none() && after_skip && within_times {
+                fired = Some(fault.fixture.fault.clone());
+            }
+        }
+        fired
+    }
+
+    pub(crate) fn record(&mut self, method: &str, params: &Value) {
+        self.calls.push(RecordedCall {
+            method: method.to_string(),
+            params: params.clone(),
+        });
+    }
+
+    pub fn calls(&self) -> &[RecordedCall] {
+        &self.calls
+    }
+
+    /// Called for every `getLatestLedger`; closes a ledger every
+    /// `advance_every` calls.
+    pub(crate) fn on_latest_ledger_call(&mut self) {
+        self.latest_ledger_calls += 1;
+        if let Some(every) = self.advance_every {
+            if self.latest_ledger_calls.is_multiple_of(every) {
+                self.advance(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_reorg_replaces_ledgers_and_events() {
+        let mut fixture = Fixture::with_ledgers(10, 12);
+        fixture.events = vec![EventFixture {
+            ledger: 11,
+            event_type: "contract".to_string(),
+            contract_id: None,
+            topic: vec![],
+            value: "AAAAAQ==".to_string(),
+            tx_hash: None,
+            in_successful_contract_call: true,
+        }];
+        fixture.reorgs = vec![ReorgFixture {
+            at_ledger: 13,
+            depth: 2,
+            ledgers: vec![],
+            events: vec![],
+        }];
+        let mut chain = Chain::new(fixture);
+        let before: Vec<String> = chain.ledgers_from(10).map(|l| l.hash.clone()).collect();
+        assert_eq!(chain.events().count(), 1);
+
+        chain.advance(1);
+        let after: Vec<String> = chain.ledgers_from(10).map(|l| l.hash.clone()).collect();
+        assert_eq!(after.len(), 4);
+        assert_eq!(before[0], after[0]);
+        assert_ne!(before[1], after[1]);
+        assert_ne!(before[2], after[2]);
+        assert_eq!(chain.events().count(), 0);
+    }
+
+    #[test]
+    fn faults_honour_skip_and_times() {
+        let mut chain = Chain::new(Fixture::with_ledgers(1, 1));
+        chain.add_fault(FaultFixture {
+            method: Some("getHealth".to_string()),
+            skip: 1,
+            times: Some(2),
+            fault: Fault::HttpStatus { status: 503 },
+        });
+        let fired: Vec<bool> = (0..4)
+            .map(|_| chain.take_fault("getHealth").is_some())
+            .collect();
+        assert_eq!(fired, vec![false, true, true, false]);
+        assert!(chain.take_fault("getLatestLedger").is_none());
+    }
+
+    #[test]
+    fn event_ids_round_trip() {
+        let position = EventPosition {
+            ledger: 1234,
+            index: 3,
+        };
+        assert_eq!(EventPosition::parse(&position.id()), Some(position));
+    }
+}
@@ -0,0 +1,318 @@
+//! Fixture file format.
+//!
+//! A fixture describes the chain the mock serves (ledgers, ledger entries and
+//! events) and scripts its behaviour: simulation and submission results,
+//! latency, injected failures and reorgs. Everything is keyed on ledger
+//! sequences and call counts, never on wall-clock time, so a fixture replays
+//! identically on every run.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const DEFAULT_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+pub const DEFAULT_PROTOCOL_VERSION: u32 = 21;
+/// Close time of ledger 0 for generated ledgers; each ledger adds 5 seconds.
+pub const GENESIS_CLOSE_TIME: i64 = 1_700_000_000;
+pub const LEDGER_CLOSE_SECS: i64 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    #[serde(default = "default_passphrase")]
+    pub network_passphrase: String,
+    #[serde(default = "default_protocol_version")]
+    pub protocol_version: u32,
+    /// Ledgers the server starts with, in sequence order. Gaps are filled
+    /// with generated ledgers.
+    #[serde(default)]
+    pub ledgers: Vec<LedgerFixture>,
+    /// Initial latest ledger. Defaults to the last fixture ledger.
+    #[serde(default)]
+    pub latest_ledger: Option<u32>,
+    #[serde(default)]
+    pub ledger_entries: Vec<LedgerEntryFixture>,
+    #[serde(default)]
+    pub events: Vec<EventFixture>,
+    #[serde(default)]
+    pub simulations: Vec<SimulationFixture>,
+    #[serde(default)]
+    pub transactions: Vec<TransactionFixture>,
+    #[serde(default)]
+    pub latency: LatencyFixture,
+    #[serde(default)]
+    pub faults: Vec<FaultFixture>,
+    #[serde(default)]
+    pub reorgs: Vec<ReorgFixture>,
+    /// Close one new ledger every N calls to `getLatestLedger`.
+    #[serde(default)]
+    pub advance_every: Option<u32>,
+}
+
+fn default_passphrase() -> String {
+    DEFAULT_PASSPHRASE.to_string()
+}
+
+fn default_protocol_version() -> u32 {
+    DEFAULT_PROTOCOL_VERSION
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_contract() -> String {
+    "contract".to_string()
+}
+
+fn default_pending() -> String {
+    "PENDING".to_string()
+}
+
+fn default_success() -> String {
+    "SUCCESS".to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LedgerFixture {
+    pub sequence: u32,
+    /// Defaults to a hash derived from the sequence and fork number.
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// Unix seconds. Defaults to `GENESIS_CLOSE_TIME + 5 * sequence`.
+    #[serde(default)]
+    pub close_time: Option<i64>,
+    #[serde(default)]
+    pub header_xdr: Option<String>,
+    #[serde(default)]
+    pub metadata_xdr: Option<String>,
+    /// Served instead of the XDR fields when a request asks for
+    /// `xdrFormat: "json"`.
+    #[serde(default)]
+    pub header_json: Option<Value>,
+    #[serde(default)]
+    pub metadata_json: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntryFixture {
+    /// Base64 `LedgerKey`, matched exactly against request keys.
+    pub key: String,
+    /// Base64 `LedgerEntryData`.
+    pub xdr: String,
+    #[serde(default)]
+    pub last_modified_ledger_seq: u32,
+    #[serde(default)]
+    pub live_until_ledger_seq: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventFixture {
+    pub ledger: u32,
+    /// `contract`, `system` or `diagnostic`.
+    #[serde(rename = "type", default = "default_contract")]
+    pub event_type: String,
+    #[serde(default)]
+    pub contract_id: Option<String>,
+    /// Base64 `ScVal` topics.
+    #[serde(default)]
+    pub topic: Vec<String>,
+    /// Base64 `ScVal` body.
+    pub value: String,
+    #[serde(default)]
+    pub tx_hash: Option<String>,
+    #[serde(default = "default_true")]
+    pub in_successful_contract_call: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationFixture {
+    /// Base64 envelope to answer for. Unset matches any transaction that no
+    /// other simulation matches.
+    #[serde(default)]
+    pub transaction: Option<String>,
+    /// The `simulateTransaction` result. `latestLedger` is filled in if absent.
+    pub response: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionFixture {
+    /// Base64 envelope to answer for. Unset matches any transaction that no
+    /// other fixture matches.
+    #[serde(default)]
+    pub transaction: Option<String>,
+    /// Defaults to the SHA-256 of the envelope bytes.
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// `sendTransaction` status: PENDING, DUPLICATE, TRY_AGAIN_LATER or ERROR.
+    #[serde(default = "default_pending")]
+    pub send_status: String,
+    #[serde(default)]
+    pub error_result_xdr: Option<String>,
+    /// Final `getTransaction` status: SUCCESS or FAILED.
+    #[serde(default = "default_success")]
+    pub status: String,
+    /// `getTransaction` polls answered with NOT_FOUND before the final status.
+    #[serde(default)]
+    pub pending_polls: u32,
+    #[serde(default)]
+    pub result_xdr: Option<String>,
+    #[serde(default)]
+    pub result_meta_xdr: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyFixture {
+    /// Added to every response, in milliseconds.
+    #[serde(default)]
+    pub default_ms: u64,
+    /// Per-method latency, replacing `default_ms`.
+    #[serde(default)]
+    pub methods: HashMap<String, u64>,
+}
+
+impl LatencyFixture {
+    pub fn for_method(&self, method: &str) -> u64 {
+        self.methods.get(method).copied().unwrap_or(self.default_ms)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultFixture {
+    /// Method to fail. Unset fails every method.
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Matching calls to let through before failing.
+    #[serde(default)]
+    pub skip: u32,
+    /// Matching calls to fail. Unset fails every call after `skip`.
+    #[serde(default)]
+    pub times: Option<u32>,
+    pub fault: Fault,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fault {
+    /// Plain HTTP error status with no JSON-RPC body.
+    HttpStatus { status: u16 },
+    /// JSON-RPC error object.
+    RpcError { code: i64, message: String },
+    /// A 200 response whose body is not JSON.
+    Malformed,
+    /// Holds the request for `ms` milliseconds before answering normally, so
+    /// clients with shorter timeouts see a timeout.
+    Hang { ms: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorgFixture {
+    /// The reorg happens when this ledger closes: the `depth` ledgers before
+    /// it are replaced by a new fork, and it closes on that fork.
+    pub at_ledger: u32,
+    pub depth: u32,
+    /// Replacement ledgers. Missing ones are generated with new hashes.
+    #[serde(default)]
+    pub ledgers: Vec<LedgerFixture>,
+    /// Events on the new fork. Events on the replaced ledgers are dropped.
+    #[serde(default)]
+    pub events: Vec<EventFixture>,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self {
+            network_passphrase: default_passphrase(),
+            protocol_version: default_protocol_version(),
+            ledgers: Vec::new(),
+            latest_ledger: None,
+            ledger_entries: Vec::new(),
+            events: Vec::new(),
+            simulations: Vec::new(),
+            transactions: Vec::new(),
+            latency: LatencyFixture::default(),
+            faults: Vec::new(),
+            reorgs: Vec::new(),
+            advance_every: None,
+        }
+    }
+}
+
+impl Fixture {
+    /// A chain of generated ledgers `first..=latest`.
+    pub fn with_ledgers(first: u32, latest: u32) -> Self {
+        Self {
+            ledgers: (first..=latest)
+                .map(|sequence| LedgerFixture {
+                    sequence,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+        let fixture: Self = serde_json::from_str(&raw)
+            .with_context(|| format!("Invalid fixture {}", path.display()))?;
+        fixture.validate()?;
+        Ok(fixture)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.ledgers.is_empty() {
+            bail!("fixture must contain at least one ledger");
+        }
+        if self
+            .ledgers
+            .windows(2)
+            .any(|pair| pair[0].sequence >= pair[1].sequence)
+        {
+            bail!("fixture ledgers must be in increasing sequence order");
+        }
+        let first = self.ledgers[0].sequence;
+        if let Some(latest) = self.latest_ledger {
+            if latest < first {
+                bail!(
+                    "latest_ledger {} is before the first ledger {}",
+                    latest,
+                    first
+                );
+            }
+        }
+        if let Some(event) = self.events.iter().find(|e| e.ledger < first) {
+            bail!(
+                "event at ledger {} is before the first ledger {}",
+                event.ledger,
+                first
+            );
+        }
+        let initial_latest = self
+            .latest_ledger
+            .unwrap_or(self.ledgers[self.ledgers.len() - 1].sequence);
+        for reorg in &self.reorgs {
+            if reorg.depth == 0 || reorg.at_ledger < first + reorg.depth {
+                bail!(
+                    "reorg at ledger {} with depth {} reaches before the first ledger {}",
+                    reorg.at_ledger,
+                    reorg.depth,
+                    first
+                );
+            }
+            if reorg.at_ledger <= initial_latest {
+                bail!(
+                    "reorg at ledger {} must come after the initial latest ledger {}",
+                    reorg.at_ledger,
+                    initial_latest
+                );
+            }
+        }
+        if self.advance_every == Some(0) {
+            bail!("advance_every must be at least 1");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+//! Deterministic mock of the Stellar (Soroban) RPC JSON-RPC API.
+//!
+//! Serves `getHealth`, `getNetwork`, `getLatestLedger`, `getLedgers`,
+//! `getLedgerEntries`, `getEvents`, `simulateTransaction`,
+//! `sendTransaction` and `getTransaction` from a [`Fixture`], with scripted
+//! latency, failures and reorgs. Tests start it in-process with
+//! [`MockRpcServer::start`]; the `mock-rpc` binary serves a fixture file for
+//! anything else.
+
+pub mod chain;
+pub mod fixture;
+pub mod methods;
+pub mod server;
+
+pub use chain::{Ledger, RecordedCall};
+pub use fixture::{
+    EventFixture, Fault, FaultFixture, Fixture, LatencyFixture, LedgerEntryFixture, LedgerFixture,
+    ReorgFixture, SimulationFixture, TransactionFixture,
+};
+pub use server::MockRpcServer;
@@ -0,0 +1,35 @@
+use anyhow::Result;
+use clap::Parser;
+use mock_rpc::{Fixture, MockRpcServer};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "mock-rpc")]
+#[command(about = "Deterministic mock Stellar RPC server backed by a fixture file")]
+struct Args {
+    /// Fixture file (JSON)
+    #[arg(long)]
+    fixture: PathBuf,
+
+    #[arg(long, default_value = "127.0.0.1:8000")]
+    listen: SocketAddr,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "mock_rpc=info".into()),
+        )
+        .init();
+
+    let args = Args::parse();
+    let fixture = Fixture::from_path(&args.fixture)?;
+    let server = MockRpcServer::bind(fixture, args.listen).await?;
+    tracing::info!("mock RPC listening on {}", server.url());
+
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
@@ -0,0 +1,482 @@
+//! JSON-RPC methods, answered from the chain state.
+
+use crate::chain::{Chain, EventPosition, Ledger, Submission};
+use crate::fixture::{EventFixture, TransactionFixture};
+use base64::Engine;
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+const DEFAULT_LEDGERS_LIMIT: usize = 5;
+const MAX_LEDGERS_LIMIT: usize = 200;
+const DEFAULT_EVENTS_LIMIT: usize = 100;
+const MAX_EVENTS_LIMIT: usize = 10_000;
+const MAX_EVENT_FILTERS: usize = 5;
+
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// A JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcFailure {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcFailure {
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+        }
+    }
+}
+
+type MethodResult = Result<Value, RpcFailure>;
+
+pub fn dispatch(chain: &mut Chain, method: &str, params: &Value) -> MethodResult {
+    match method {
+        "getHealth" => get_health(chain),
+        "getNetwork" => Ok(json!({
+            "passphrase": chain.network_passphrase,
+            "protocolVersion": chain.protocol_version,
+        })),
+        "getLatestLedger" => {
+            chain.on_latest_ledger_call();
+            let latest = chain.latest();
+            Ok(json!({
+                "id": latest.hash,
+                "protocolVersion": chain.protocol_version,
+                "sequence": latest.sequence,
+            }))
+        }
+        "getLedgers" => get_ledgers(chain, params),
+        "getLedgerEntries" => get_ledger_entries(chain, params),
+        "getEvents" => get_events(chain, params),
+        "simulateTransaction" => simulate_transaction(chain, params),
+        "sendTransaction" => send_transaction(chain, params),
+        "getTransaction" => get_transaction(chain, params),
+        other => Err(RpcFailure {
+            code: METHOD_NOT_FOUND,
+            message: format!("method '{}' not found", other),
+        }),
+    }
+}
+
+fn get_health(chain: &Chain) -> MethodResult {
+    let (oldest, latest) = (chain.oldest().sequence, chain.latest().sequence);
+    Ok(json!({
+        "status": "healthy",
+        "latestLedger": latest,
+        "oldestLedger": oldest,
+        "ledgerRetentionWindow": latest - oldest + 1,
+    }))
+}
+
+fn close_time_string(ledger: &Ledger) -> String {
+    ledger.close_time.to_string()
+}
+
+fn rfc3339(ledger: &Ledger) -> String {
+    Utc.timestamp_opt(ledger.close_time, 0)
+        .single()
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+fn param_u32(params: &Value, name: &str) -> Result<Option<u32>, RpcFailure> {
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .map(Some)
+            .ok_or_else(|| {
+                RpcFailure::invalid_params(format!("{} must be a ledger sequence", name))
+            }),
+    }
+}
+
+fn param_str<'a>(params: &'a Value, name: &str) -> Result<&'a str, RpcFailure> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcFailure::invalid_params(format!("missing '{}'", name)))
+}
+
+fn limit(params: &Value, default: usize, max: usize) -> Result<usize, RpcFailure> {
+    match params.pointer("/pagination/limit") {
+        None | Some(Value::Null) => Ok(default),
+        Some(v) => match v.as_u64() {
+            Some(n) if n as usize <= max => Ok((n as usize).max(1)),
+            _ => Err(RpcFailure::invalid_params(format!(
+                "limit must be between 1 and {}",
+                max
+            ))),
+        },
+    }
+}
+
+fn check_in_range(chain: &Chain, start: u32) -> Result<(), RpcFailure> {
+    let (oldest, latest) = (chain.oldest().sequence, chain.latest().sequence);
+    if start < oldest || start > latest {
+        return Err(RpcFailure::invalid_params(format!(
+            "start ledger must be between the oldest ledger: {} and the latest ledger: {}",
+            oldest, latest
+        )));
+    }
+    Ok(())
+}
+
+fn get_ledgers(chain: &Chain, params: &Value) -> MethodResult {
+    let limit = limit(params, DEFAULT_LEDGERS_LIMIT, MAX_LEDGERS_LIMIT)?;
+    let as_json = params.get("xdrFormat").and_then(Value::as_str) == Some("json");
+    let start = match params.pointer("/pagination/cursor").and_then(Value::as_str) {
+        Some(cursor) => cursor
+            .parse::<u32>()
+            .map_err(|_| RpcFailure::invalid_params("invalid cursor"))?
+            .saturating_add(1),
+        None => {
+            let start = param_u32(params, "startLedger")?
+                .ok_or_else(|| RpcFailure::invalid_params("startLedger or cursor is required"))?;
+            check_in_range(chain, start)?;
+            start
+        }
+    };
+
+    let ledgers: Vec<Value> = chain
+        .ledgers_from(start)
+        .take(limit)
+        .map(|ledger| {
+            let mut out = json!({
+                "hash": ledger.hash,
+                "sequence": ledger.sequence,
+                "ledgerCloseTime": close_time_string(ledger),
+            });
+            if as_json {
+                out["headerJson"] = ledger.header_json.clone().unwrap_or(Value::Null);
+                out["metadataJson"] = ledger.metadata_json.clone().unwrap_or(Value::Null);
+            } else {
+                out["headerXdr"] = json!(ledger.header_xdr.clone().unwrap_or_default());
+                out["metadataXdr"] = json!(ledger.metadata_xdr.clone().unwrap_or_default());
+            }
+            out
+        })
+        .collect();
+    let cursor = ledgers
+        .last()
+        .and_then(|l| l["sequence"].as_u64())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| start.saturating_sub(1).to_string());
+    let (oldest, latest) = (chain.oldest(), chain.latest());
+    Ok(json!({
+        "ledgers": ledgers,
+        "latestLedger": latest.sequence,
+        "latestLedgerCloseTime": close_time_string(latest),
+        "oldestLedger": oldest.sequence,
+        "oldestLedgerCloseTime": close_time_string(oldest),
+        "cursor": cursor,
+    }))
+}
+
+fn get_ledger_entries(chain: &Chain, params: &Value) -> MethodResult {
+    let keys = params
+        .get("keys")
+        .and_then(Value::as_array)
+        .ok_or_else(|| RpcFailure::invalid_params("missing 'keys'"))?;
+    let mut entries = Vec::new();
+    for key in keys {
+        let key = key
+            .as_str()
+            .ok_or_else(|| RpcFailure::invalid_params("keys must be base64 strings"))?;
+        if let Some(entry) = chain.ledger_entries.iter().find(|e| e.key == key) {
+            let mut out = json!({
+                "key": entry.key,
+                "xdr": entry.xdr,
+                "lastModifiedLedgerSeq": entry.last_modified_ledger_seq,
+            });
+            if let Some(live_until) = entry.live_until_ledger_seq {
+                out["liveUntilLedgerSeq"] = json!(live_until);
+            }
+            entries.push(out);
+        }
+    }
+    Ok(json!({
+        "entries": entries,
+        "latestLedger": chain.latest().sequence,
+    }))
+}
+
+/// A `getEvents` filter. Topic segments are base64 `ScVal`s, `*` matches any
+/// one segment and a trailing `**` matches any remaining segments.
+struct EventFilter {
+    event_type: Option<String>,
+    contract_ids: Vec<String>,
+    topics: Vec<Vec<String>>,
+}
+
+impl EventFilter {
+    fn parse(value: &Value) -> Result<Self, RpcFailure> {
+        let strings = |v: &Value| -> Result<Vec<String>, RpcFailure> {
+            v.as_array()
+                .ok_or_else(|| RpcFailure::invalid_params("filter fields must be arrays"))?
+                .iter()
+                .map(|s| {
+                    s.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| RpcFailure::invalid_params("filter values must be strings"))
+                })
+                .collect()
+        };
+        Ok(Self {
+            event_type: value
+                .get("type")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            contract_ids: match value.get("contractIds") {
+                Some(ids) => strings(ids)?,
+                None => Vec::new(),
+            },
+            topics: match value.get("topics") {
+                Some(Value::Array(patterns)) => {
+                    patterns.iter().map(strings).collect::<Result<_, _>>()?
+                }
+                Some(_) => return Err(RpcFailure::invalid_params("topics must be an array")),
+                None => Vec::new(),
+            },
+        })
+    }
+
+    fn matches(&self, event: &EventFixture) -> bool {
+        if self
+            .event_type
+            .as_ref()
+            .is_some_and(|t| *t != event.event_type)
+        {
+            return false;
+        }
+        if !self.contract_ids.is_empty()
+            && !event
+                .contract_id
+                .as_ref()
+                .is_some_and(|id| self.contract_ids.contains(id))
+        {
+            return false;
+        }
+        self.topics.is_empty()
+            || self
+                .topics
+                .iter()
+                .any(|pattern| topic_matches(pattern, &event.topic))
+    }
+}
+
+fn topic_matches(pattern: &[String], topic: &[String]) -> bool {
+    match (pattern.split_first(), topic.split_first()) {
+        (Some((segment, _)), _) if segment == "**" => true,
+        (None, None) => true,
+        (Some((segment, rest)), Some((value, values))) => {
+            (segment == "*" || segment == value) && topic_matches(rest, values)
+        }
+        _ => false,
+    }
+}
+
+fn get_events(chain: &Chain, params: &Value) -> MethodResult {
+    let limit = limit(params, DEFAULT_EVENTS_LIMIT, MAX_EVENTS_LIMIT)?;
+    let filters = match params.get("filters") {
+        Some(Value::Array(filters)) if filters.len() > MAX_EVENT_FILTERS => {
+            return Err(RpcFailure::invalid_params(format!(
+                "maximum {} filters per request",
+                MAX_EVENT_FILTERS
+            )))
+        }
+        Some(Value::Array(filters)) => filters
+            .iter()
+            .map(EventFilter::parse)
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(_) => return Err(RpcFailure::invalid_params("filters must be an array")),
+        None => Vec::new(),
+    };
+    let end_ledger = param_u32(params, "endLedger")?;
+    // Events strictly after this position are returned.
+    let after = match params.pointer("/pagination/cursor").and_then(Value::as_str) {
+        Some(cursor) => Some(
+            EventPosition::parse(cursor)
+                .ok_or_else(|| RpcFailure::invalid_params("invalid cursor"))?,
+        ),
+        None => {
+            let start = param_u32(params, "startLedger")?
+                .ok_or_else(|| RpcFailure::invalid_params("startLedger or cursor is required"))?;
+            check_in_range(chain, start)?;
+            if end_ledger.is_some_and(|end| end <= start) {
+                return Err(RpcFailure::invalid_params(
+                    "endLedger must be after startLedger",
+                ));
+            }
+            start.checked_sub(1).map(|ledger| EventPosition {
+                ledger,
+                index: u32::MAX,
+            })
+        }
+    };
+
+    let mut events = Vec::new();
+    let mut last_position = after;
+    for (position, event) in chain.events() {
+        if after.is_some_and(|after| position <= after) {
+            continue;
+        }
+        if end_ledger.is_some_and(|end| event.ledger >= end) || events.len() == limit {
+            break;
+        }
+        last_position = Some(position);
+        if !filters.is_empty() && !filters.iter().any(|f| f.matches(event)) {
+            continue;
+        }
+        let ledger = chain
+            .ledger(event.ledger)
+            .expect("events are only served for closed ledgers");
+        events.push(json!({
+            "type": event.event_type,
+            "ledger": event.ledger,
+            "ledgerClosedAt": rfc3339(ledger),
+            "contractId": event.contract_id.clone().unwrap_or_default(),
+            "id": position.id(),
+            "pagingToken": position.id(),
+            "topic": event.topic,
+            "value": event.value,
+            "inSuccessfulContractCall": event.in_successful_contract_call,
+            "txHash": event.tx_hash.clone().unwrap_or_default(),
+        }));
+    }
+    Ok(json!({
+        "events": events,
+        "latestLedger": chain.latest().sequence,
+        "cursor": last_position.map(|p| p.id()).unwrap_or_default(),
+    }))
+}
+
+/// Scripted entry for `transaction`: an exact match, else the catch-all.
+fn find_scripted<'a, T>(
+    scripted: &'a [T],
+    transaction: &str,
+    key: impl Fn(&T) -> Option<&str>,
+) -> Option<&'a T> {
+    scripted
+        .iter()
+        .find(|s| key(s) == Some(transaction))
+        .or_else(|| scripted.iter().find(|s| key(s).is_none()))
+}
+
+fn simulate_transaction(chain: &Chain, params: &Value) -> MethodResult {
+    let transaction = param_str(params, "transaction")?;
+    let latest = chain.latest().sequence;
+    let mut response = match find_scripted(&chain.simulations, transaction, |s| {
+        s.transaction.as_deref()
+    }) {
+        Some(simulation) => simulation.response.clone(),
+        None => json!({ "error": "no simulation scripted for this transaction" }),
+    };
+    if let Value::Object(map) = &mut response {
+        map.entry("latestLedger").or_insert(json!(latest));
+    }
+    Ok(response)
+}
+
+fn send_transaction(chain: &mut Chain, params: &Value) -> MethodResult {
+    let transaction = param_str(params, "transaction")?;
+    let envelope = base64::engine::general_purpose::STANDARD
+        .decode(transaction)
+        .map_err(|_| RpcFailure::invalid_params("transaction is not base64"))?;
+    let fixture = find_scripted(&chain.transactions, transaction, |t| {
+        t.transaction.as_deref()
+    })
+    .cloned()
+    .unwrap_or_else(|| TransactionFixture {
+        transaction: None,
+        hash: None,
+        send_status: "PENDING".to_string(),
+        error_result_xdr: None,
+        status: "SUCCESS".to_string(),
+        pending_polls: 0,
+        result_xdr: None,
+        result_meta_xdr: None,
+    });
+    let hash = fixture
+        .hash
+        .clone()
+        .unwrap_or_else(|| hex::encode(Sha256::digest(&envelope)));
+    let latest = chain.latest();
+    let mut response = json!({
+        "status": fixture.send_status,
+        "hash": hash,
+        "latestLedger": latest.sequence,
+        "latestLedgerCloseTime": close_time_string(latest),
+    });
+    if let Some(error) = &fixture.error_result_xdr {
+        response["errorResultXdr"] = json!(error);
+    }
+    if matches!(fixture.send_status.as_str(), "PENDING" | "DUPLICATE") {
+        chain.submissions.entry(hash).or_insert(Submission {
+            polls_left: fixture.pending_polls,
+            fixture,
+            envelope: transaction.to_string(),
+            ledger: None,
+        });
+    }
+    Ok(response)
+}
+
+fn get_transaction(chain: &mut Chain, params: &Value) -> MethodResult {
+    let hash = param_str(params, "hash")?;
+    let (oldest, latest) = (chain.oldest().clone(), chain.latest().clone());
+    let mut response = json!({
+        "status": "NOT_FOUND",
+        "latestLedger": latest.sequence,
+        "latestLedgerCloseTime": close_time_string(&latest),
+        "oldestLedger": oldest.sequence,
+        "oldestLedgerCloseTime": close_time_string(&oldest),
+    });
+    let Some(submission) = chain.submissions.get_mut(hash) else {
+        return Ok(response);
+    };
+    if submission.polls_left > 0 {
+        submission.polls_left -= 1;
+        return Ok(response);
+    }
+    // Included in the ledger that was latest when it was first found.
+    let ledger = *submission.ledger.get_or_insert(latest.sequence);
+    let submission = submission.clone();
+    let close_time = chain
+        .ledger(ledger)
+        .map(close_time_string)
+        .unwrap_or_default();
+    response["status"] = json!(submission.fixture.status);
+    response["ledger"] = json!(ledger);
+    response["createdAt"] = json!(close_time);
+    response["applicationOrder"] = json!(1);
+    response["envelopeXdr"] = json!(submission.envelope);
+    if let Some(result) = &submission.fixture.result_xdr {
+        response["resultXdr"] = json!(result);
+    }
+    if let Some(meta) = &submission.fixture.result_meta_xdr {
+        response["resultMetaXdr"] = json!(meta);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_patterns_support_wildcards() {
+        let topic = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let pattern = |p: &[&str]| p.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(topic_matches(&pattern(&["a", "*", "c"]), &topic));
+        assert!(topic_matches(&pattern(&["a", "**"]), &topic));
+        assert!(!topic_matches(&pattern(&["a", "*"]), &topic));
+        assert!(!topic_matches(&pattern(&["b", "**"]), &topic));
+    }
+}
@@ -0,0 +1,261 @@
+//! HTTP front end: JSON-RPC on `/`, plus `/mock/*` control endpoints for
+//! driving the chain from outside the process.
+
+use crate::chain::{Chain, Ledger, RecordedCall};
+use crate::fixture::{EventFixture, Fault, FaultFixture, Fixture, LedgerFixture};
+use crate::methods::{self, RpcFailure};
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+
+type SharedChain = Arc<Mutex<Chain>>;
+
+pub fn router(chain: SharedChain) -> Router {
+    Router::new()
+        .route("/", post(rpc))
+        .route("/mock/advance", post(advance))
+        .route("/mock/reorg", post(reorg))
+        .route("/mock/faults", post(add_fault).delete(clear_faults))
+        .route("/mock/calls", get(calls))
+        .with_state(chain)
+}
+
+fn lock(chain: &SharedChain) -> MutexGuard<'_, Chain> {
+    // A panicking handler can't leave the chain half-updated in a way later
+    // requests care about, so keep serving.
+    chain
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Response {
+    Json(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    }))
+    .into_response()
+}
+
+async fn rpc(State(chain): State<SharedChain>, body: Bytes) -> Response {
+    let request: Value = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return rpc_error(Value::Null, PARSE_ERROR, &e.to_string()),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return rpc_error(id, INVALID_REQUEST, "missing method");
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let (fault, latency) = {
+        let mut chain = lock(&chain);
+        chain.record(method, &params);
+        (chain.take_fault(method), chain.latency.for_method(method))
+    };
+    let hang = match &fault {
+        Some(Fault::Hang { ms }) => *ms,
+        _ => 0,
+    };
+    if latency + hang > 0 {
+        tokio::time::sleep(Duration::from_millis(latency + hang)).await;
+    }
+    match fault {
+        Some(Fault::HttpStatus { status }) => {
+            return StatusCode::from_u16(status)
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+                .into_response()
+        }
+        Some(Fault::RpcError { code, message }) => return rpc_error(id, code, &message),
+        Some(Fault::Malformed) => {
+            return (StatusCode::OK, "<html>upstream unavailable</html>").into_response()
+        }
+        Some(Fault::Hang { .. }) | None => {}
+    }
+
+    let result = methods::dispatch(&mut lock(&chain), method, &params);
+    match result {
+        Ok(result) => Json(json!({ "jsonrpc": "2.0", "id": id, "result": result })).into_response(),
+        Err(RpcFailure { code, message }) => rpc_error(id, code, &message),
+    }
+}
+
+#[derive(Deserialize)]
+struct AdvanceRequest {
+    #[serde(default = "one")]
+    ledgers: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+async fn advance(
+    State(chain): State<SharedChain>,
+    Json(request): Json<AdvanceRequest>,
+) -> Json<Value> {
+    let mut chain = lock(&chain);
+    chain.advance(request.ledgers);
+    Json(json!({ "latestLedger": chain.latest().sequence }))
+}
+
+#[derive(Deserialize)]
+struct ReorgRequest {
+    depth: u32,
+    #[serde(default)]
+    ledgers: Vec<LedgerFixture>,
+    #[serde(default)]
+    events: Vec<EventFixture>,
+}
+
+async fn reorg(State(chain): State<SharedChain>, Json(request): Json<ReorgRequest>) -> Json<Value> {
+    let mut chain = lock(&chain);
+    chain.reorg(request.depth, &request.ledgers, request.events);
+    Json(json!({ "latestLedger": chain.latest().sequence }))
+}
+
+async fn add_fault(
+    State(chain): State<SharedChain>,
+    Json(fault): Json<FaultFixture>,
+) -> StatusCode {
+    lock(&chain).add_fault(fault);
+    StatusCode::NO_CONTENT
+}
+
+async fn clear_faults(State(chain): State<SharedChain>) -> StatusCode {
+    lock(&chain).clear_faults();
+    StatusCode::NO_CONTENT
+}
+
+async fn calls(State(chain): State<SharedChain>) -> Json<Vec<RecordedCall>> {
+    Json(lock(&chain).calls().to_vec())
+}
+
+/// A mock RPC server running on the current Tokio runtime. It stops when
+/// dropped.
+pub struct MockRpcServer {
+    addr: SocketAddr,
+    chain: SharedChain,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockRpcServer {
+    /// Starts a server for `fixture` on an ephemeral localhost port.
+    pub async fn start(fixture: Fixture) -> anyhow::Result<Self> {
+        Self::bind(fixture, SocketAddr::from(([127, 0, 0, 1], 0))).await
+    }
+
+    pub async fn bind(fixture: Fixture, addr: SocketAddr) -> anyhow::Result<Self> {
+        fixture.validate()?;
+        let chain = Arc::new(Mutex::new(Chain::new(fixture)));
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let app = router(chain.clone());
+        tokio::spawn(async move {
+            let server = axum::serve(listener, app).with_graceful_shutdown(async {
+                let _ = stopped.await;
+            });
+            if let Err(e) = server.await {
+                tracing::warn!(error = ?e, "mock RPC server stopped");
+            }
+        });
+        Ok(Self {
+            addr,
+            chain,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// JSON-RPC endpoint URL.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn latest_ledger(&self) -> Ledger {
+        lock(&self.chain).latest().clone()
+    }
+
+    pub fn ledger(&self, sequence: u32) -> Option<Ledger> {
+        lock(&self.chain).ledger(sequence).cloned()
+    }
+
+    /// Closes `ledgers` new ledgers, applying any scripted reorgs.
+    pub fn advance(&self, ledgers: u32) {
+        lock(&self.chain).advance(ledgers);
+    }
+
+    /// Replaces the `depth` most recent ledgers with a new fork carrying
+    /// `events`.
+    pub fn reorg(&self, depth: u32, events: Vec<EventFixture>) {
+        lock(&self.chain).reorg(depth, &[], events);
+    }
+
+    pub fn inject_fault(&self, fault: FaultFixture) {
+        lock(&self.chain).add_fault(fault);
+    }
+
+    /// Fails the next `times` calls to `method`.
+    pub fn fail_next(&self, method: &str, times: u32, fault: Fault) {
+        self.inject_fault(FaultFixture {
+            method: Some(method.to_string()),
+            skip: 0,
+            times: Some(times),
+            fault,
+        });
+    }
+
+    pub fn clear_faults(&self) {
+        lock(&self.chain).clear_faults();
+    }
+
+    /// Sets the latency of `method`, or of every method without its own
+    /// latency when `method` is `None`.
+    pub fn set_latency(&self, method: Option<&str>, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let mut chain = lock(&self.chain);
+        match method {
+            Some(method) => {
+                chain.latency.methods.insert(method.to_string(), ms);
+            }
+            None => chain.latency.default_ms = ms,
+        }
+    }
+
+    /// Requests received so far, in order.
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        lock(&self.chain).calls().to_vec()
+    }
+
+    pub fn call_count(&self, method: &str) -> usize {
+        lock(&self.chain)
+            .calls()
+            .iter()
+            .filter(|c| c.method == method)
+            .count()
+    }
+}
+
+impl Drop for MockRpcServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
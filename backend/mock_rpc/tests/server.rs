@@ -0,0 +1,322 @@
+//! Drives the mock over HTTP the way RPC clients do.
+
+use mock_rpc::{EventFixture, Fault, Fixture, MockRpcServer, ReorgFixture, TransactionFixture};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+async fn call(server: &MockRpcServer, method: &str, params: Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(server.url())
+        .json(&json!({ "jsonrpc": "2.0", "id": 7, "method": method, "params": params }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn result(server: &MockRpcServer, method: &str, params: Value) -> Value {
+    let body: Value = call(server, method, params).await.json().await.unwrap();
+    assert_eq!(body["id"], 7);
+    assert!(body.get("error").is_none(), "{} failed: {}", method, body);
+    body["result"].clone()
+}
+
+fn event(ledger: u32, topic: &str) -> EventFixture {
+    EventFixture {
+        ledger,
+        event_type: "contract".to_string(),
+        contract_id: Some("CA".to_string()),
+        topic: vec![topic.to_string()],
+        value: "AAAAAQ==".to_string(),
+        tx_hash: None,
+        in_successful_contract_call: true,
+    }
+}
+
+fn sample() -> Fixture {
+    Fixture::from_path(&Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/sample.json")).unwrap()
+}
+
+#[tokio::test]
+async fn serves_ledgers_from_fixture() {
+    let server = MockRpcServer::start(sample()).await.unwrap();
+
+    let health = result(&server, "getHealth", Value::Null).await;
+    assert_eq!(health["status"], "healthy");
+    assert_eq!(health["oldestLedger"], 1000);
+    assert_eq!(health["latestLedger"], 1003);
+
+    let latest = result(&server, "getLatestLedger", Value::Null).await;
+    assert_eq!(latest["sequence"], 1003);
+    assert_eq!(latest["protocolVersion"], 21);
+
+    let page = result(
+        &server,
+        "getLedgers",
+        json!({ "startLedger": 1000, "pagination": { "limit": 2 } }),
+    )
+    .await;
+    let ledgers = page["ledgers"].as_array().unwrap();
+    assert_eq!(ledgers.len(), 2);
+    assert_eq!(
+        ledgers[0]["hash"],
+        "8f0e3bc6b9a0a1c7e4d2f6b5a3c1e0d9f8b7a6c5d4e3f2a1b0c9d8e7f6a5b4c3"
+    );
+    assert_eq!(page["cursor"], "1001");
+
+    let next = result(
+        &server,
+        "getLedgers",
+        json!({ "pagination": { "cursor": "1001", "limit": 10 } }),
+    )
+    .await;
+    let sequences: Vec<u64> = next["ledgers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l["sequence"].as_u64().unwrap())
+        .collect();
+    assert_eq!(sequences, vec![1002, 1003]);
+
+    let out_of_range: Value = call(&server, "getLedgers", json!({ "startLedger": 5 }))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(out_of_range["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn looks_up_ledger_entries_by_key() {
+    let server = MockRpcServer::start(sample()).await.unwrap();
+    let key = "AAAAAAAAAAC4fTa5VbaL7aTC8oHKvBBBMkbhaSeYPgDXoPDkZ5EoJQ==";
+    let found = result(
+        &server,
+        "getLedgerEntries",
+        json!({ "keys": [key, "AAAABg=="] }),
+    )
+    .await;
+    let entries = found["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["key"], key);
+    assert_eq!(entries[0]["lastModifiedLedgerSeq"], 990);
+}
+
+#[tokio::test]
+async fn pages_and_filters_events() {
+    let server = MockRpcServer::start(sample()).await.unwrap();
+
+    let all = result(&server, "getEvents", json!({ "startLedger": 1000 })).await;
+    let events = all["events"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["ledger"], 1001);
+    assert_eq!(events[0]["id"], format!("{:019}-{:010}", 1001u64 << 32, 0));
+    assert_eq!(events[0]["ledgerClosedAt"], "2023-11-14T23:36:45Z");
+
+    let transfers = result(
+        &server,
+        "getEvents",
+        json!({
+            "startLedger": 1000,
+            "filters": [{ "type": "contract", "topics": [["AAAADwAAAAh0cmFuc2Zlcg=="]] }],
+        }),
+    )
+    .await;
+    assert_eq!(transfers["events"].as_array().unwrap().len(), 1);
+
+    let bounded = result(
+        &server,
+        "getEvents",
+        json!({ "startLedger": 1000, "endLedger": 1002 }),
+    )
+    .await;
+    assert_eq!(bounded["events"].as_array().unwrap().len(), 1);
+
+    // The sample fixture fails the fourth getEvents call.
+    let failed = call(&server, "getEvents", json!({ "startLedger": 1000 })).await;
+    assert_eq!(failed.status(), 503);
+
+    let first_page = result(
+        &server,
+        "getEvents",
+        json!({ "startLedger": 1000, "pagination": { "limit": 1 } }),
+    )
+    .await;
+    let rest = result(
+        &server,
+        "getEvents",
+        json!({ "pagination": { "cursor": first_page["cursor"] } }),
+    )
+    .await;
+    assert_eq!(rest["events"][0]["ledger"], 1003);
+    assert_eq!(rest["events"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn scripted_reorg_changes_hashes_and_drops_orphaned_events() {
+    let mut fixture = Fixture::with_ledgers(100, 105);
+    fixture.events = vec![event(104, "old"), event(105, "old")];
+    fixture.reorgs = vec![ReorgFixture {
+        at_ledger: 106,
+        depth: 1,
+        ledgers: vec![],
+        events: vec![event(105, "new")],
+    }];
+    let server = MockRpcServer::start(fixture).await.unwrap();
+    let before = server.ledger(105).unwrap().hash;
+
+    server.advance(1);
+    assert_ne!(server.ledger(105).unwrap().hash, before);
+    let page = result(&server, "getEvents", json!({ "startLedger": 104 })).await;
+    let topics: Vec<&str> = page["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["topic"][0].as_str().unwrap())
+        .collect();
+    assert_eq!(topics, vec!["old", "new"]);
+
+    // Reorgs can also be triggered on demand.
+    let tip = server.latest_ledger().hash;
+    server.reorg(1, vec![]);
+    assert_ne!(server.latest_ledger().hash, tip);
+}
+
+#[tokio::test]
+async fn simulates_submits_and_confirms_transactions() {
+    let mut fixture = sample();
+    fixture.transactions.push(TransactionFixture {
+        transaction: Some("AAAAAw==".to_string()),
+        hash: Some("ab".repeat(32)),
+        send_status: "ERROR".to_string(),
+        error_result_xdr: Some("AAAAAAAAAGT////7AAAAAA==".to_string()),
+        status: "FAILED".to_string(),
+        pending_polls: 0,
+        result_xdr: None,
+        result_meta_xdr: None,
+    });
+    let server = MockRpcServer::start(fixture).await.unwrap();
+
+    let simulation = result(
+        &server,
+        "simulateTransaction",
+        json!({ "transaction": "AAAAAg==" }),
+    )
+    .await;
+    assert_eq!(simulation["minResourceFee"], "90000");
+    assert_eq!(simulation["latestLedger"], 1003);
+
+    let sent = result(
+        &server,
+        "sendTransaction",
+        json!({ "transaction": "AAAAAg==" }),
+    )
+    .await;
+    assert_eq!(sent["status"], "PENDING");
+    let hash = sent["hash"].as_str().unwrap().to_string();
+    assert_eq!(hash.len(), 64);
+
+    let first = result(&server, "getTransaction", json!({ "hash": hash })).await;
+    assert_eq!(first["status"], "NOT_FOUND");
+    let second = result(&server, "getTransaction", json!({ "hash": hash })).await;
+    assert_eq!(second["status"], "SUCCESS");
+    assert_eq!(second["ledger"], 1003);
+    assert_eq!(second["envelopeXdr"], "AAAAAg==");
+
+    let rejected = result(
+        &server,
+        "sendTransaction",
+        json!({ "transaction": "AAAAAw==" }),
+    )
+    .await;
+    assert_eq!(rejected["status"], "ERROR");
+    assert_eq!(rejected["errorResultXdr"], "AAAAAAAAAGT////7AAAAAA==");
+    let unknown = result(
+        &server,
+        "getTransaction",
+        json!({ "hash": "ab".repeat(32) }),
+    )
+    .await;
+    assert_eq!(unknown["status"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn injects_latency_and_failures() {
+    let server = MockRpcServer::start(Fixture::with_ledgers(1, 3))
+        .await
+        .unwrap();
+
+    server.fail_next(
+        "getLatestLedger",
+        1,
+        Fault::RpcError {
+            code: -32603,
+            message: "database locked".to_string(),
+        },
+    );
+    let body: Value = call(&server, "getLatestLedger", Value::Null)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["error"]["message"], "database locked");
+    assert_eq!(
+        result(&server, "getLatestLedger", Value::Null).await["sequence"],
+        3
+    );
+
+    server.fail_next("getHealth", 1, Fault::Malformed);
+    assert!(call(&server, "getHealth", Value::Null)
+        .await
+        .json::<Value>()
+        .await
+        .is_err());
+
+    server.set_latency(Some("getHealth"), Duration::from_millis(150));
+    let started = Instant::now();
+    result(&server, "getHealth", Value::Null).await;
+    assert!(started.elapsed() >= Duration::from_millis(150));
+
+    let unknown: Value = call(&server, "getFeeStats", Value::Null)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    let methods: Vec<String> = server.calls().into_iter().map(|c| c.method).collect();
+    assert_eq!(
+        methods,
+        vec![
+            "getLatestLedger",
+            "getLatestLedger",
+            "getHealth",
+            "getHealth",
+            "getFeeStats"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn advances_on_a_schedule_and_over_http() {
+    let mut fixture = Fixture::with_ledgers(10, 10);
+    fixture.advance_every = Some(2);
+    let server = MockRpcServer::start(fixture).await.unwrap();
+
+    let mut seen = Vec::new();
+    for _ in 0..4 {
+        seen.push(result(&server, "getLatestLedger", Value::Null).await["sequence"].clone());
+    }
+    assert_eq!(seen, vec![json!(10), json!(11), json!(11), json!(12)]);
+
+    let advanced: Value = reqwest::Client::new()
+        .post(format!("{}/mock/advance", server.url()))
+        .json(&json!({ "ledgers": 3 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(advanced["latestLedger"], 15);
+}
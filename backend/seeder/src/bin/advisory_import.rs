@@ -0,0 +1,205 @@
+//! Imports a local checkout of the RustSec advisory database
+//! (https://github.com/rustsec/advisory-db) into `cve_vulnerabilities`, so
+//! dependency scans work without network access. Advisories withdrawn since
+//! an earlier import are marked withdrawn, which scans and trust scores skip.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Parser;
+use colored::Colorize;
+use shared::advisory::{Advisory, ImportAction};
+use sqlx::postgres::PgPoolOptions;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+#[derive(Parser)]
+#[command(name = "advisory-import")]
+#[command(about = "Import RustSec advisories into the Soroban Registry")]
+struct Args {
+    /// Path to an advisory-db checkout
+    advisory_db: PathBuf,
+
+    #[arg(long, default_value = "postgresql://localhost/soroban_registry")]
+    database_url: String,
+
+    /// Also import unmaintained/unsound notices
+    #[arg(long)]
+    include_informational: bool,
+}
+
+#[derive(Default)]
+struct Summary {
+    imported: usize,
+    withdrawn: usize,
+    /// Withdrawn advisories that had been imported before
+    retracted: u64,
+    informational: usize,
+    invalid: usize,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let crates_dir = args.advisory_db.join("crates");
+    if !crates_dir.is_dir() {
+        anyhow::bail!(
+            "{} does not look like an advisory-db checkout (no crates/ directory)",
+            args.advisory_db.display()
+        );
+    }
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&args.database_url)
+        .await
+        .context("Failed to connect to database")?;
+
+    let start_time = Instant::now();
+    let mut summary = Summary::default();
+
+    for path in advisory_files(&crates_dir)? {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let advisory = match Advisory::parse_rustsec(&content).and_then(|a| {
+            a.ranges()?;
+            Ok(a)
+        }) {
+            Ok(advisory) => advisory,
+            Err(e) => {
+                println!("{} {}: {}", "⚠".yellow(), path.display(), e);
+                summary.invalid += 1;
+                continue;
+            }
+        };
+
+        match advisory.import_action(args.include_informational) {
+            ImportAction::Upsert => {
+                upsert_advisory(&pool, &advisory)
+                    .await
+                    .with_context(|| format!("Failed to import {}", advisory.id))?;
+                summary.imported += 1;
+            }
+            ImportAction::Withdraw => {
+                summary.retracted += withdraw_advisory(&pool, &advisory)
+                    .await
+                    .with_context(|| format!("Failed to withdraw {}", advisory.id))?;
+                summary.withdrawn += 1;
+            }
+            ImportAction::Skip => summary.informational += 1,
+        }
+    }
+
+    println!("{} Imported {} advisories", "✓".green(), summary.imported);
+    if summary.retracted > 0 {
+        println!(
+            "{} Marked {} previously imported advisories withdrawn",
+            "✓".green(),
+            summary.retracted
+        );
+    }
+    println!(
+        "{} Skipped {} withdrawn, {} informational, {} invalid",
+        "ℹ".blue(),
+        summary.withdrawn,
+        summary.informational,
+        summary.invalid
+    );
+    println!(
+        "{} Done in {:.2}s",
+        "✓".green().bold(),
+        start_time.elapsed().as_secs_f64()
+    );
+
+    Ok(())
+}
+
+/// `crates/<name>/RUSTSEC-*.md` (or `.toml` in older checkouts), sorted so
+/// imports are reproducible.
+fn advisory_files(crates_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(crates_dir)? {
+        let dir = entry?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        for file in fs::read_dir(&dir)? {
+            let path = file?.path();
+            let is_advisory = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("md") | Some("toml")
+            );
+            if is_advisory {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn parse_date(date: Option<&str>) -> Option<DateTime<Utc>> {
+    date.and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|d| d.and_utc())
+}
+
+/// Mark an earlier import of `advisory` withdrawn. Returns how many rows
+/// changed (0 when it was never imported or is already withdrawn).
+async fn withdraw_advisory(pool: &sqlx::PgPool, advisory: &Advisory) -> Result<u64> {
+    let withdrawn_at = parse_date(advisory.withdrawn.as_deref()).unwrap_or_else(Utc::now);
+    let result = sqlx::query(
+        r#"
+        UPDATE cve_vulnerabilities
+        SET withdrawn_at = $2, updated_at = NOW()
+        WHERE cve_id = $1 AND withdrawn_at IS NULL
+        "#,
+    )
+    .bind(&advisory.id)
+    .bind(withdrawn_at)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+async fn upsert_advisory(pool: &sqlx::PgPool, advisory: &Advisory) -> Result<()> {
+    let published_at = parse_date(advisory.date.as_deref());
+
+    sqlx::query(
+        r#"
+        INSERT INTO cve_vulnerabilities
+            (cve_id, description, severity, package_name, patched_versions,
+             unaffected_versions, aliases, title, url, source, published_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 'rustsec', $10, NOW())
+        ON CONFLICT (cve_id) DO UPDATE SET
+            description = EXCLUDED.description,
+            severity = EXCLUDED.severity,
+            package_name = EXCLUDED.package_name,
+            patched_versions = EXCLUDED.patched_versions,
+            unaffected_versions = EXCLUDED.unaffected_versions,
+            aliases = EXCLUDED.aliases,
+            title = EXCLUDED.title,
+            url = EXCLUDED.url,
+            source = EXCLUDED.source,
+            published_at = EXCLUDED.published_at,
+            withdrawn_at = NULL,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(&advisory.id)
+    .bind(&advisory.description)
+    .bind(advisory.severity())
+    .bind(&advisory.package)
+    .bind(&advisory.patched)
+    .bind(&advisory.unaffected)
+    .bind(&advisory.aliases)
+    .bind(&advisory.title)
+    .bind(&advisory.url)
+    .bind(published_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
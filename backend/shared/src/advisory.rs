@@ -0,0 +1,461 @@
+//! Security advisories in the RustSec format, and the version-range rules
+//! that decide whether a dependency is affected by one.
+//!
+//! An advisory lists `patched` and `unaffected` requirements; a version is
+//! vulnerable only if it satisfies none of them. This matches `cargo audit`.
+
+use crate::error::{RegistryError, Result};
+use crate::semver::{SemVer, VersionReq};
+use serde::{Deserialize, Serialize};
+
+/// An advisory as published in the RustSec advisory database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Advisory {
+    pub id: String,
+    pub package: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub date: Option<String>,
+    pub url: Option<String>,
+    pub aliases: Vec<String>,
+    /// CVSS v3 vector, e.g. `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`
+    pub cvss: Option<String>,
+    /// Set for non-vulnerability notices (`unmaintained`, `unsound`, `notice`)
+    pub informational: Option<String>,
+    /// Date the advisory was withdrawn, if it was
+    pub withdrawn: Option<String>,
+    pub patched: Vec<String>,
+    pub unaffected: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawAdvisoryFile {
+    advisory: RawAdvisory,
+    #[serde(default)]
+    versions: RawVersions,
+}
+
+#[derive(Deserialize)]
+struct RawAdvisory {
+    id: String,
+    package: String,
+    title: Option<String>,
+    description: Option<String>,
+    date: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    cvss: Option<String>,
+    informational: Option<String>,
+    withdrawn: Option<String>,
+    // Pre-2021 advisories kept their version lists here.
+    #[serde(default)]
+    patched_versions: Vec<String>,
+    #[serde(default)]
+    unaffected_versions: Vec<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct RawVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// What an import of the advisory database does with one advisory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportAction {
+    /// Insert it, or refresh and reactivate an earlier import.
+    Upsert,
+    /// Mark an earlier import withdrawn so scans stop matching it.
+    Withdraw,
+    /// Leave it out: an informational notice nobody asked for.
+    Skip,
+}
+
+impl Advisory {
+    /// Parse one advisory file: either the current Markdown format (a
+    /// ```` ```toml ```` front-matter block followed by a `# Title` and the
+    /// description) or the legacy pure-TOML format.
+    pub fn parse_rustsec(content: &str) -> Result<Self> {
+        let (toml_part, markdown) = split_front_matter(content);
+        let raw: RawAdvisoryFile = toml::from_str(toml_part)
+            .map_err(|e| RegistryError::InvalidInput(format!("Invalid advisory TOML: {}", e)))?;
+
+        let (title, description) = match markdown {
+            Some(markdown) => parse_markdown_body(markdown),
+            None => (None, None),
+        };
+
+        let RawAdvisory {
+            id,
+            package,
+            title: toml_title,
+            description: toml_description,
+            date,
+            url,
+            aliases,
+            cvss,
+            informational,
+            withdrawn,
+            patched_versions,
+            unaffected_versions,
+        } = raw.advisory;
+
+        let mut patched = raw.versions.patched;
+        patched.extend(patched_versions);
+        let mut unaffected = raw.versions.unaffected;
+        unaffected.extend(unaffected_versions);
+
+        let advisory = Advisory {
+            id,
+            package,
+            title: title.or(toml_title),
+            description: description.or(toml_description),
+            date,
+            url,
+            aliases,
+            cvss,
+            informational,
+            withdrawn,
+            patched,
+            unaffected,
+        };
+        advisory.ranges()?;
+        Ok(advisory)
+    }
+
+    pub fn ranges(&self) -> Result<AffectedRanges> {
+        AffectedRanges::parse(&self.patched, &self.unaffected)
+    }
+
+    /// `critical`, `high`, `medium` or `low`, from the CVSS score when there
+    /// is one. Informational notices are `low`; other advisories without a
+    /// score default to `medium`.
+    pub fn severity(&self) -> &'static str {
+        if let Some(score) = self.cvss.as_deref().and_then(cvss_base_score) {
+            return severity_for_score(score);
+        }
+        if self.informational.is_some() {
+            "low"
+        } else {
+            "medium"
+        }
+    }
+
+    pub fn import_action(&self, include_informational: bool) -> ImportAction {
+        if self.withdrawn.is_some() {
+            ImportAction::Withdraw
+        } else if self.informational.is_some() && !include_informational {
+            ImportAction::Skip
+        } else {
+            ImportAction::Upsert
+        }
+    }
+}
+
+fn split_front_matter(content: &str) -> (&str, Option<&str>) {
+    let trimmed = content.trim_start();
+    let Some(rest) = trimmed.strip_prefix("```toml") else {
+        return (content, None);
+    };
+    match rest.find("\n```") {
+        Some(end) => (&rest[..end], Some(&rest[end + 4..])),
+        None => (rest, None),
+    }
+}
+
+fn parse_markdown_body(markdown: &str) -> (Option<String>, Option<String>) {
+    let mut title = None;
+    let mut body = Vec::new();
+    for line in markdown.lines() {
+        match line.strip_prefix("# ") {
+            Some(heading) if title.is_none() => title = Some(heading.trim().to_string()),
+            _ if title.is_some() => body.push(line),
+            _ => {}
+        }
+    }
+    let description = body.join("\n").trim().to_string();
+    (title, (!description.is_empty()).then_some(description))
+}
+
+/// The `patched` and `unaffected` requirement lists of an advisory.
+#[derive(Debug, Clone)]
+pub struct AffectedRanges {
+    pub patched: Vec<VersionReq>,
+    pub unaffected: Vec<VersionReq>,
+}
+
+impl AffectedRanges {
+    pub fn parse(patched: &[String], unaffected: &[String]) -> Result<Self> {
+        let parse_all = |reqs: &[String]| {
+            reqs.iter()
+                .map(|req| {
+                    VersionReq::parse(req).ok_or_else(|| {
+                        RegistryError::InvalidInput(format!("Invalid version requirement: {}", req))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Self {
+            patched: parse_all(patched)?,
+            unaffected: parse_all(unaffected)?,
+        })
+    }
+
+    pub fn is_affected(&self, version: &SemVer) -> bool {
+        !self
+            .patched
+            .iter()
+            .chain(&self.unaffected)
+            .any(|req| req.matches(version))
+    }
+
+    /// The lowest patched version above `current`, if the requirements pin
+    /// one down.
+    pub fn recommended_upgrade(&self, current: &SemVer) -> Option<SemVer> {
+        self.patched
+            .iter()
+            .filter_map(|req| {
+                let floor = lower_bound(req)?;
+                req.matches(&floor).then_some(floor)
+            })
+            .filter(|floor| floor > current)
+            .min()
+    }
+}
+
+/// Smallest version a requirement could accept, from its lower bounds.
+fn lower_bound(req: &VersionReq) -> Option<SemVer> {
+    req.comparators.iter().filter_map(|c| c.lower_bound()).max()
+}
+
+fn severity_for_score(score: f64) -> &'static str {
+    if score >= 9.0 {
+        "critical"
+    } else if score >= 7.0 {
+        "high"
+    } else if score >= 4.0 {
+        "medium"
+    } else {
+        "low"
+    }
+}
+
+/// CVSS v3.0/v3.1 base score of a vector string.
+pub fn cvss_base_score(vector: &str) -> Option<f64> {
+    let mut metrics = std::collections::HashMap::new();
+    for part in vector.split('/').skip_while(|p| p.starts_with("CVSS:")) {
+        let (key, value) = part.split_once(':')?;
+        metrics.insert(key, value);
+    }
+    let metric = |key: &str| metrics.get(key).copied();
+
+    let scope_changed = match metric("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+    let av = match metric("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let ac = match metric("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let pr = match (metric("PR")?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let ui = match metric("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let cia = |key: &str| match metric(key)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+    let iss = 1.0 - (1.0 - cia("C")?) * (1.0 - cia("I")?) * (1.0 - cia("A")?);
+
+    let impact = if scope_changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02f64).powi(15)
+    } else {
+        6.42 * iss
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * av * ac * pr * ui;
+    let base = if scope_changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some(round_up(base.min(10.0)))
+}
+
+/// CVSS v3.1 "Roundup": smallest one-decimal number >= `value`, computed in
+/// integers to avoid floating-point artefacts.
+fn round_up(value: f64) -> f64 {
+    let int_input = (value * 100_000.0).round() as i64;
+    if int_input % 10_000 == 0 {
+        int_input as f64 / 100_000.0
+    } else {
+        ((int_input / 10_000) + 1) as f64 / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADVISORY_MD: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2023-0044"
+package = "openssl"
+date = "2023-06-20"
+url = "https://github.com/sfackler/rust-openssl/pull/1854"
+aliases = ["GHSA-xcf7-rvmh-g6q4"]
+cvss = "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+
+[versions]
+patched = [">= 0.10.55"]
+unaffected = ["< 0.10.0"]
+```
+
+# `openssl` `X509VerifyParamRef::set_host` buffer over-read
+
+When this function was passed an empty string, `openssl` would attempt to
+call `strlen` on it.
+"#;
+
+    const LEGACY_TOML: &str = r#"
+[advisory]
+id = "RUSTSEC-2018-0001"
+package = "untrusted"
+title = "Integer underflow"
+description = "Integer underflow in input handling."
+date = "2018-06-21"
+patched_versions = [">= 0.6.2"]
+"#;
+
+    fn v(s: &str) -> SemVer {
+        SemVer::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parses_markdown_advisories() {
+        let advisory = Advisory::parse_rustsec(ADVISORY_MD).unwrap();
+        assert_eq!(advisory.id, "RUSTSEC-2023-0044");
+        assert_eq!(advisory.package, "openssl");
+        assert_eq!(
+            advisory.title.as_deref(),
+            Some("`openssl` `X509VerifyParamRef::set_host` buffer over-read")
+        );
+        assert!(advisory
+            .description
+            .as_deref()
+            .unwrap()
+            .starts_with("When this function"));
+        assert_eq!(advisory.aliases, vec!["GHSA-xcf7-rvmh-g6q4"]);
+        assert_eq!(advisory.patched, vec![">= 0.10.55"]);
+        assert_eq!(advisory.severity(), "critical");
+    }
+
+    #[test]
+    fn reimporting_a_withdrawn_advisory_withdraws_it() {
+        let active = Advisory::parse_rustsec(ADVISORY_MD).unwrap();
+        assert_eq!(active.import_action(false), ImportAction::Upsert);
+
+        let withdrawn = Advisory::parse_rustsec(&ADVISORY_MD.replace(
+            "date = \"2023-06-20\"\n",
+            "date = \"2023-06-20\"\nwithdrawn = \"2023-07-01\"\n",
+        ))
+        .unwrap();
+        assert_eq!(withdrawn.id, active.id);
+        assert_eq!(withdrawn.withdrawn.as_deref(), Some("2023-07-01"));
+        // Withdrawal wins even when informational notices are imported.
+        assert_eq!(withdrawn.import_action(false), ImportAction::Withdraw);
+        assert_eq!(withdrawn.import_action(true), ImportAction::Withdraw);
+    }
+
+    #[test]
+    fn parses_legacy_toml_advisories() {
+        let advisory = Advisory::parse_rustsec(LEGACY_TOML).unwrap();
+        assert_eq!(advisory.title.as_deref(), Some("Integer underflow"));
+        assert_eq!(advisory.patched, vec![">= 0.6.2"]);
+        assert_eq!(advisory.severity(), "medium");
+    }
+
+    #[test]
+    fn rejects_invalid_requirements() {
+        let broken = LEGACY_TOML.replace(">= 0.6.2", ">= six");
+        assert!(Advisory::parse_rustsec(&broken).is_err());
+    }
+
+    #[test]
+    fn newer_releases_are_not_flagged() {
+        let ranges = Advisory::parse_rustsec(ADVISORY_MD)
+            .unwrap()
+            .ranges()
+            .unwrap();
+        assert!(ranges.is_affected(&v("0.10.54")));
+        assert!(ranges.is_affected(&v("0.10.0")));
+        assert!(!ranges.is_affected(&v("0.10.55")));
+        assert!(!ranges.is_affected(&v("0.10.60")));
+        assert!(!ranges.is_affected(&v("1.0.0")));
+        assert!(!ranges.is_affected(&v("0.9.24")));
+    }
+
+    #[test]
+    fn recommends_the_lowest_patched_release() {
+        let ranges = AffectedRanges::parse(
+            &[
+                "^0.9.30".to_string(),
+                ">= 0.10.4, < 0.11.0".to_string(),
+                ">= 0.11.2".to_string(),
+            ],
+            &[],
+        )
+        .unwrap();
+        assert!(ranges.is_affected(&v("0.10.3")));
+        assert!(!ranges.is_affected(&v("0.9.31")));
+        assert_eq!(ranges.recommended_upgrade(&v("0.10.3")), Some(v("0.10.4")));
+        assert_eq!(ranges.recommended_upgrade(&v("0.11.0")), Some(v("0.11.2")));
+        assert_eq!(ranges.recommended_upgrade(&v("0.12.0")), None);
+    }
+
+    #[test]
+    fn computes_cvss_scores() {
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            Some(9.8)
+        );
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"),
+            Some(10.0)
+        );
+        assert_eq!(
+            cvss_base_score("CVSS:3.0/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:N/A:H"),
+            Some(5.9)
+        );
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+        assert_eq!(cvss_base_score("CVSS:3.1/AV:X"), None);
+    }
+}
@@ -0,0 +1,577 @@
+//! Hash chain and signatures over `contract_audit_log`.
+//!
+//! Each row's `hash` commits to the previous row's hash and to the row itself,
+//! and its `signature` is the registry's Ed25519 signature over that hash.
+//! Version 1 hashes (unsigned, and not covering `old_value` or the timestamp)
+//! predate signing; they are accepted only before a contract's first signed
+//! entry and at or below the `seq` cutoff of the migration that introduced
+//! signing. [`verify_chain`] walks a contract's entries in `seq` order and
+//! reports the first broken link. A chain of legacy entries alone is reported
+//! as [`ChainStatus::LegacyOnly`], not valid: anyone able to rewrite the table
+//! can recompute unsigned hashes.
+
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::models::{AuditActionType, ContractAuditLog};
+
+/// Hash scheme written by current servers
+pub const HASH_VERSION: i16 = 2;
+
+const ENTRY_CONTEXT: &str = "soroban-registry-audit-log/v2";
+const BUNDLE_CONTEXT: &str = "soroban-registry-audit-bundle/v1";
+
+/// Legacy scheme: previous hash, contract, action, actor and new value,
+/// concatenated.
+pub fn entry_hash_v1(
+    previous_hash: Option<&str>,
+    contract_id: Uuid,
+    action_type: &AuditActionType,
+    changed_by: &str,
+    new_value: Option<&Value>,
+) -> String {
+    let mut hasher = Sha256::new();
+    if let Some(previous) = previous_hash {
+        hasher.update(previous.as_bytes());
+    }
+    hasher.update(contract_id.as_bytes());
+    hasher.update(action_type.to_string().as_bytes());
+    hasher.update(changed_by.as_bytes());
+    if let Some(value) = new_value {
+        hasher.update(value.to_string().as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Current scheme: SHA-256 of a JSON array of every stored field, so no two
+/// different entries share an encoding.
+#[allow(clippy::too_many_arguments)]
+pub fn entry_hash_v2(
+    previous_hash: Option<&str>,
+    contract_id: Uuid,
+    action_type: &AuditActionType,
+    changed_by: &str,
+    old_value: Option<&Value>,
+    new_value: Option<&Value>,
+    timestamp: DateTime<Utc>,
+) -> String {
+    let encoded = serde_json::json!([
+        ENTRY_CONTEXT,
+        previous_hash,
+        contract_id,
+        action_type.to_string(),
+        changed_by,
+        old_value,
+        new_value,
+        timestamp.timestamp_micros(),
+    ]);
+    hex::encode(Sha256::digest(encoded.to_string().as_bytes()))
+}
+
+/// Recompute a stored entry's hash with the scheme it was written under.
+pub fn compute_hash(entry: &ContractAuditLog) -> Option<String> {
+    match entry.hash_version {
+        1 => Some(entry_hash_v1(
+            entry.previous_hash.as_deref(),
+            entry.contract_id,
+            &entry.action_type,
+            &entry.changed_by,
+            entry.new_value.as_ref(),
+        )),
+        2 => Some(entry_hash_v2(
+            entry.previous_hash.as_deref(),
+            entry.contract_id,
+            &entry.action_type,
+            &entry.changed_by,
+            entry.old_value.as_ref(),
+            entry.new_value.as_ref(),
+            entry.timestamp,
+        )),
+        _ => None,
+    }
+}
+
+/// Bytes an entry signature covers.
+pub fn signing_message(hash: &str) -> Vec<u8> {
+    format!("{}\n{}\n", ENTRY_CONTEXT, hash).into_bytes()
+}
+
+pub fn decode_public_key(encoded: &str) -> Option<VerifyingKey> {
+    let bytes: [u8; 32] = BASE64.decode(encoded.trim()).ok()?.try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+fn verify_signature(key: &VerifyingKey, message: &[u8], signature: &str) -> bool {
+    let Some(bytes) = BASE64
+        .decode(signature)
+        .ok()
+        .and_then(|b| <[u8; 64]>::try_from(b).ok())
+    else {
+        return false;
+    };
+    key.verify(message, &Signature::from_bytes(&bytes)).is_ok()
+}
+
+/// Why the chain stops verifying at an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BreakReason {
+    MissingHash,
+    UnsupportedHashVersion {
+        version: i16,
+    },
+    /// An unsigned legacy entry after a signed one
+    LegacyAfterSigned,
+    /// An unsigned legacy entry numbered after the signing migration
+    LegacyAfterCutoff {
+        seq: i64,
+        cutoff: i64,
+    },
+    PreviousHashMismatch {
+        expected: Option<String>,
+        found: Option<String>,
+    },
+    HashMismatch {
+        computed: String,
+        stored: String,
+    },
+    Unsigned,
+    UnknownKey {
+        key_id: Option<String>,
+    },
+    InvalidSignature,
+}
+
+impl std::fmt::Display for BreakReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingHash => write!(f, "entry has no hash"),
+            Self::UnsupportedHashVersion { version } => {
+                write!(f, "unsupported hash version {}", version)
+            }
+            Self::LegacyAfterSigned => write!(f, "unsigned legacy entry follows a signed entry"),
+            Self::LegacyAfterCutoff { seq, cutoff } => write!(
+                f,
+                "unsigned legacy entry at seq {} was written after signing began (seq {})",
+                seq, cutoff
+            ),
+            Self::PreviousHashMismatch { expected, found } => write!(
+                f,
+                "previous_hash is {} but the preceding entry's hash is {}",
+                found.as_deref().unwrap_or("null"),
+                expected.as_deref().unwrap_or("null")
+            ),
+            Self::HashMismatch { computed, stored } => {
+                write!(
+                    f,
+                    "stored hash {} does not match computed {}",
+                    stored, computed
+                )
+            }
+            Self::Unsigned => write!(f, "entry is not signed"),
+            Self::UnknownKey { key_id } => write!(
+                f,
+                "signed by untrusted key {}",
+                key_id.as_deref().unwrap_or("(none)")
+            ),
+            Self::InvalidSignature => write!(f, "signature does not verify"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainBreak {
+    /// Zero-based position in the contract's chain
+    pub position: usize,
+    pub entry_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub reason: BreakReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainStatus {
+    /// Every entry checks out and the chain carries at least one signature
+    Verified,
+    /// Every entry checks out, but none is signed, so the chain proves nothing
+    /// against someone able to rewrite the table
+    LegacyOnly,
+    Broken,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainReport {
+    /// Whether `status` is [`ChainStatus::Verified`]
+    pub valid: bool,
+    pub status: ChainStatus,
+    pub total_entries: usize,
+    /// Entries before the first break
+    pub verified_entries: usize,
+    /// Of those, legacy entries checked by hash only
+    pub legacy_entries: usize,
+    /// Hash of the last verified entry
+    pub head_hash: Option<String>,
+    pub first_break: Option<ChainBreak>,
+}
+
+/// Walk `entries` (one contract, in `seq` order). Signed entries must be
+/// signed by one of `keys`, indexed by [`crate::transparency::key_id`].
+/// Legacy entries numbered above `legacy_cutoff`, the last `seq` the signing
+/// migration backfilled, break the chain.
+pub fn verify_chain(
+    entries: &[ContractAuditLog],
+    keys: &BTreeMap<String, VerifyingKey>,
+    legacy_cutoff: Option<i64>,
+) -> ChainReport {
+    let mut previous: Option<String> = None;
+    let mut legacy_entries = 0;
+    let mut seen_signed = false;
+
+    for (position, entry) in entries.iter().enumerate() {
+        let checked = check_entry(entry, previous.as_deref(), seen_signed, keys, legacy_cutoff);
+        if let Err(reason) = checked {
+            return ChainReport {
+                valid: false,
+                status: ChainStatus::Broken,
+                total_entries: entries.len(),
+                verified_entries: position,
+                legacy_entries,
+                head_hash: previous,
+                first_break: Some(ChainBreak {
+                    position,
+                    entry_id: entry.id,
+                    timestamp: entry.timestamp,
+                    reason,
+                }),
+            };
+        }
+        if entry.hash_version < HASH_VERSION {
+            legacy_entries += 1;
+        } else {
+            seen_signed = true;
+        }
+        previous = entry.hash.clone();
+    }
+
+    // An empty chain has nothing to prove; a non-empty one needs a signature.
+    let status = if entries.is_empty() || seen_signed {
+        ChainStatus::Verified
+    } else {
+        ChainStatus::LegacyOnly
+    };
+    ChainReport {
+        valid: status == ChainStatus::Verified,
+        status,
+        total_entries: entries.len(),
+        verified_entries: entries.len(),
+        legacy_entries,
+        head_hash: previous,
+        first_break: None,
+    }
+}
+
+fn check_entry(
+    entry: &ContractAuditLog,
+    previous: Option<&str>,
+    seen_signed: bool,
+    keys: &BTreeMap<String, VerifyingKey>,
+    legacy_cutoff: Option<i64>,
+) -> Result<(), BreakReason> {
+    let stored = entry.hash.as_deref().ok_or(BreakReason::MissingHash)?;
+    if entry.previous_hash.as_deref() != previous {
+        return Err(BreakReason::PreviousHashMismatch {
+            expected: previous.map(str::to_string),
+            found: entry.previous_hash.clone(),
+        });
+    }
+    let computed = compute_hash(entry).ok_or(BreakReason::UnsupportedHashVersion {
+        version: entry.hash_version,
+    })?;
+    if computed != stored {
+        return Err(BreakReason::HashMismatch {
+            computed,
+            stored: stored.to_string(),
+        });
+    }
+
+    if entry.hash_version < HASH_VERSION {
+        if seen_signed {
+            return Err(BreakReason::LegacyAfterSigned);
+        }
+        return match legacy_cutoff {
+            Some(cutoff) if entry.seq > cutoff => Err(BreakReason::LegacyAfterCutoff {
+                seq: entry.seq,
+                cutoff,
+            }),
+            _ => Ok(()),
+        };
+    }
+    let signature = entry.signature.as_deref().ok_or(BreakReason::Unsigned)?;
+    let key = entry
+        .signing_key_id
+        .as_ref()
+        .and_then(|id| keys.get(id))
+        .ok_or_else(|| BreakReason::UnknownKey {
+            key_id: entry.signing_key_id.clone(),
+        })?;
+    if !verify_signature(key, &signing_message(stored), signature) {
+        return Err(BreakReason::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// A contract's full audit chain, signed as a whole at export time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditBundle {
+    pub contract_id: Uuid,
+    pub exported_at: DateTime<Utc>,
+    pub entries: Vec<ContractAuditLog>,
+    pub head_hash: Option<String>,
+    pub key_id: String,
+    /// Base64 Ed25519 signature over [`AuditBundle::signing_message`]
+    pub signature: String,
+}
+
+impl AuditBundle {
+    pub fn signing_message(
+        contract_id: Uuid,
+        entries: usize,
+        head_hash: Option<&str>,
+        exported_at: DateTime<Utc>,
+    ) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n",
+            BUNDLE_CONTEXT,
+            contract_id,
+            entries,
+            head_hash.unwrap_or(""),
+            exported_at.timestamp_millis()
+        )
+        .into_bytes()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BundleError {
+    #[error("bundle is signed by key {0}, which is not trusted")]
+    UntrustedKey(String),
+    #[error("bundle signature does not verify")]
+    InvalidSignature,
+    #[error("bundle head hash does not match its last entry")]
+    HeadMismatch,
+    #[error("bundle contains entries for another contract")]
+    ForeignEntry,
+}
+
+/// Check a bundle's own signature, then walk its chain. Both use only the
+/// caller's trusted keys. Bundles do not carry the legacy cutoff, so an
+/// unsigned chain shows up only as [`ChainStatus::LegacyOnly`].
+pub fn verify_bundle(
+    bundle: &AuditBundle,
+    keys: &BTreeMap<String, VerifyingKey>,
+) -> Result<ChainReport, BundleError> {
+    let key = keys
+        .get(&bundle.key_id)
+        .ok_or_else(|| BundleError::UntrustedKey(bundle.key_id.clone()))?;
+    let message = AuditBundle::signing_message(
+        bundle.contract_id,
+        bundle.entries.len(),
+        bundle.head_hash.as_deref(),
+        bundle.exported_at,
+    );
+    if !verify_signature(key, &message, &bundle.signature) {
+        return Err(BundleError::InvalidSignature);
+    }
+    if bundle
+        .entries
+        .iter()
+        .any(|e| e.contract_id != bundle.contract_id)
+    {
+        return Err(BundleError::ForeignEntry);
+    }
+    if bundle.entries.last().and_then(|e| e.hash.as_ref()) != bundle.head_hash.as_ref() {
+        return Err(BundleError::HeadMismatch);
+    }
+    Ok(verify_chain(&bundle.entries, keys, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transparency;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[5u8; 32])
+    }
+
+    fn trusted(key: &SigningKey) -> BTreeMap<String, VerifyingKey> {
+        let public = key.verifying_key();
+        BTreeMap::from([(transparency::key_id(public.as_bytes()), public)])
+    }
+
+    fn entry(
+        previous: Option<&str>,
+        version: i16,
+        signer: Option<&SigningKey>,
+    ) -> ContractAuditLog {
+        let mut entry = ContractAuditLog {
+            id: Uuid::new_v4(),
+            contract_id: Uuid::nil(),
+            action_type: AuditActionType::MetadataUpdated,
+            old_value: Some(serde_json::json!({ "name": "a" })),
+            new_value: Some(serde_json::json!({ "name": "b" })),
+            changed_by: "GABC".into(),
+            timestamp: DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap(),
+            previous_hash: previous.map(str::to_string),
+            hash: None,
+            signature: None,
+            seq: 0,
+            hash_version: version,
+            signing_key_id: None,
+        };
+        let hash = compute_hash(&entry).unwrap();
+        if let Some(signer) = signer {
+            let signature = signer.sign(&signing_message(&hash));
+            entry.signature = Some(BASE64.encode(signature.to_bytes()));
+            entry.signing_key_id = Some(transparency::key_id(signer.verifying_key().as_bytes()));
+        }
+        entry.hash = Some(hash);
+        entry
+    }
+
+    fn chain(len: usize, signer: &SigningKey) -> Vec<ContractAuditLog> {
+        let mut entries: Vec<ContractAuditLog> = Vec::new();
+        for _ in 0..len {
+            let previous = entries.last().and_then(|e| e.hash.clone());
+            entries.push(entry(previous.as_deref(), HASH_VERSION, Some(signer)));
+        }
+        entries
+    }
+
+    #[test]
+    fn signed_chain_verifies() {
+        let signer = key();
+        let entries = chain(4, &signer);
+        let report = verify_chain(&entries, &trusted(&signer), None);
+        assert!(report.valid);
+        assert_eq!(report.verified_entries, 4);
+        assert_eq!(report.head_hash, entries[3].hash);
+    }
+
+    #[test]
+    fn v2_hash_covers_old_value_and_timestamp() {
+        let signer = key();
+        let mut entries = chain(3, &signer);
+        entries[1].old_value = Some(serde_json::json!({ "name": "z" }));
+        let report = verify_chain(&entries, &trusted(&signer), None);
+        assert_eq!(report.verified_entries, 1);
+        assert!(matches!(
+            report.first_break.unwrap().reason,
+            BreakReason::HashMismatch { .. }
+        ));
+
+        let mut entries = chain(2, &signer);
+        entries[0].timestamp += chrono::Duration::microseconds(1);
+        assert!(!verify_chain(&entries, &trusted(&signer), None).valid);
+    }
+
+    #[test]
+    fn removed_entry_breaks_the_next_link() {
+        let signer = key();
+        let mut entries = chain(4, &signer);
+        entries.remove(1);
+        let report = verify_chain(&entries, &trusted(&signer), None);
+        let first_break = report.first_break.unwrap();
+        assert_eq!(first_break.position, 1);
+        assert!(matches!(
+            first_break.reason,
+            BreakReason::PreviousHashMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn signatures_need_a_trusted_key() {
+        let signer = key();
+        let other = SigningKey::from_bytes(&[6u8; 32]);
+        let entries = chain(2, &signer);
+        let report = verify_chain(&entries, &trusted(&other), None);
+        assert!(matches!(
+            report.first_break.unwrap().reason,
+            BreakReason::UnknownKey { .. }
+        ));
+    }
+
+    #[test]
+    fn legacy_entries_only_before_signed_ones() {
+        let signer = key();
+        let legacy = entry(None, 1, None);
+        let signed = entry(legacy.hash.as_deref(), HASH_VERSION, Some(&signer));
+        let report = verify_chain(&[legacy, signed], &trusted(&signer), Some(0));
+        assert!(report.valid);
+        assert_eq!(report.legacy_entries, 1);
+
+        let signed = entry(None, HASH_VERSION, Some(&signer));
+        let late = entry(signed.hash.as_deref(), 1, None);
+        let report = verify_chain(&[signed, late], &trusted(&signer), None);
+        assert_eq!(
+            report.first_break.unwrap().reason,
+            BreakReason::LegacyAfterSigned
+        );
+    }
+
+    #[test]
+    fn all_legacy_chain_is_not_valid() {
+        let signer = key();
+        let first = entry(None, 1, None);
+        let mut second = entry(first.hash.as_deref(), 1, None);
+        second.seq = 1;
+        let entries = [first, second];
+
+        let report = verify_chain(&entries, &trusted(&signer), None);
+        assert!(!report.valid);
+        assert_eq!(report.status, ChainStatus::LegacyOnly);
+        assert_eq!(report.verified_entries, 2);
+        assert!(report.first_break.is_none());
+
+        // Unsigned rows numbered after the signing migration are forgeries.
+        let report = verify_chain(&entries, &trusted(&signer), Some(0));
+        assert_eq!(report.status, ChainStatus::Broken);
+        assert_eq!(
+            report.first_break.unwrap().reason,
+            BreakReason::LegacyAfterCutoff { seq: 1, cutoff: 0 }
+        );
+    }
+
+    #[test]
+    fn bundle_signature_and_head_are_checked() {
+        let signer = key();
+        let entries = chain(3, &signer);
+        let exported_at = DateTime::from_timestamp_millis(1_700_000_100_000).unwrap();
+        let head_hash = entries.last().and_then(|e| e.hash.clone());
+        let message =
+            AuditBundle::signing_message(Uuid::nil(), 3, head_hash.as_deref(), exported_at);
+        let mut bundle = AuditBundle {
+            contract_id: Uuid::nil(),
+            exported_at,
+            entries,
+            head_hash,
+            key_id: transparency::key_id(signer.verifying_key().as_bytes()),
+            signature: BASE64.encode(signer.sign(&message).to_bytes()),
+        };
+        assert!(verify_bundle(&bundle, &trusted(&signer)).unwrap().valid);
+
+        bundle.entries.pop();
+        assert_eq!(
+            verify_bundle(&bundle, &trusted(&signer)).unwrap_err(),
+            BundleError::InvalidSignature
+        );
+    }
+}
@@ -0,0 +1,491 @@
+//! Soroban fee model.
+//!
+//! Mirrors the fee formulas applied by the network (`soroban-env-host::fees`)
+//! so that resource usage measured by simulation can be priced without a
+//! round-trip to a validator. All amounts are in stroops.
+
+use serde::{Deserialize, Serialize};
+
+/// Transaction result bytes charged to the historical storage fee.
+pub const TX_BASE_RESULT_SIZE: u32 = 300;
+/// Size of the TTL entry written when an entry's lifetime is extended.
+pub const TTL_ENTRY_SIZE: u32 = 48;
+
+const INSTRUCTIONS_INCREMENT: i64 = 10_000;
+const DATA_SIZE_1KB_INCREMENT: i64 = 1024;
+
+/// Network fee settings. Defaults approximate the current mainnet values;
+/// load a different schedule to price against another network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeSchedule {
+    /// Inclusion (base) fee bid per operation.
+    pub inclusion_fee: i64,
+    /// Fee per 10,000 CPU instructions.
+    pub fee_per_instruction_increment: i64,
+    pub fee_per_read_entry: i64,
+    pub fee_per_write_entry: i64,
+    pub fee_per_read_1kb: i64,
+    pub fee_per_write_1kb: i64,
+    pub fee_per_historical_1kb: i64,
+    pub fee_per_contract_event_1kb: i64,
+    pub fee_per_transaction_size_1kb: i64,
+    pub persistent_rent_rate_denominator: i64,
+    pub temporary_rent_rate_denominator: i64,
+    /// Lifetime, in ledgers, granted to newly created persistent entries.
+    pub min_persistent_ttl: u32,
+    /// Lifetime, in ledgers, granted to newly created temporary entries.
+    pub min_temporary_ttl: u32,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            inclusion_fee: 100,
+            fee_per_instruction_increment: 25,
+            fee_per_read_entry: 6_250,
+            fee_per_write_entry: 10_000,
+            fee_per_read_1kb: 1_786,
+            fee_per_write_1kb: 11_800,
+            fee_per_historical_1kb: 16_235,
+            fee_per_contract_event_1kb: 10_000,
+            fee_per_transaction_size_1kb: 1_624,
+            persistent_rent_rate_denominator: 2_103,
+            temporary_rent_rate_denominator: 4_206,
+            min_persistent_ttl: 120_960,
+            min_temporary_ttl: 17_280,
+        }
+    }
+}
+
+/// Resources consumed by one invocation, as reported by simulation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceUsage {
+    pub instructions: u32,
+    pub read_entries: u32,
+    pub write_entries: u32,
+    pub read_bytes: u32,
+    pub write_bytes: u32,
+    pub contract_events_size_bytes: u32,
+    pub transaction_size_bytes: u32,
+}
+
+impl ResourceUsage {
+    /// Component-wise sum, used to total a batch of calls.
+    pub fn saturating_add(&self, other: &Self) -> Self {
+        Self {
+            instructions: self.instructions.saturating_add(other.instructions),
+            read_entries: self.read_entries.saturating_add(other.read_entries),
+            write_entries: self.write_entries.saturating_add(other.write_entries),
+            read_bytes: self.read_bytes.saturating_add(other.read_bytes),
+            write_bytes: self.write_bytes.saturating_add(other.write_bytes),
+            contract_events_size_bytes: self
+                .contract_events_size_bytes
+                .saturating_add(other.contract_events_size_bytes),
+            transaction_size_bytes: self
+                .transaction_size_bytes
+                .saturating_add(other.transaction_size_bytes),
+        }
+    }
+}
+
+/// Size and lifetime change of a ledger entry written by an invocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RentChange {
+    pub is_persistent: bool,
+    /// Zero for entries created by the invocation.
+    pub old_size_bytes: u32,
+    pub new_size_bytes: u32,
+    /// Zero for entries created by the invocation.
+    pub old_live_until_ledger: u32,
+    pub new_live_until_ledger: u32,
+}
+
+impl RentChange {
+    fn entry_is_new(&self) -> bool {
+        self.old_size_bytes == 0 && self.old_live_until_ledger == 0
+    }
+
+    fn extension_ledgers(&self, current_ledger: u32) -> Option<u32> {
+        let before = if self.entry_is_new() {
+            current_ledger.saturating_sub(1)
+        } else {
+            self.old_live_until_ledger
+        };
+        self.new_live_until_ledger.checked_sub(before)
+    }
+
+    fn prepaid_ledgers(&self, current_ledger: u32) -> Option<u32> {
+        if self.entry_is_new() {
+            return None;
+        }
+        self.old_live_until_ledger
+            .checked_sub(current_ledger)
+            .map(|diff| diff.saturating_add(1))
+    }
+}
+
+/// A stored entry whose lifetime may be extended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredEntry {
+    pub is_persistent: bool,
+    pub size_bytes: u32,
+}
+
+/// Itemised resource fee for one transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceFee {
+    pub compute: i64,
+    pub read_entries: i64,
+    pub write_entries: i64,
+    pub read_bytes: i64,
+    pub write_bytes: i64,
+    pub historical: i64,
+    pub bandwidth: i64,
+    pub events: i64,
+}
+
+impl ResourceFee {
+    /// Portion charged regardless of execution outcome.
+    pub fn non_refundable(&self) -> i64 {
+        self.compute
+            .saturating_add(self.read_entries)
+            .saturating_add(self.write_entries)
+            .saturating_add(self.read_bytes)
+            .saturating_add(self.write_bytes)
+            .saturating_add(self.historical)
+            .saturating_add(self.bandwidth)
+    }
+
+    /// Portion refunded when unused (events; rent is added on top).
+    pub fn refundable(&self) -> i64 {
+        self.events
+    }
+}
+
+/// Complete price of a call: what a submitter should expect to pay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    pub inclusion_fee: i64,
+    pub resource_fee: ResourceFee,
+    pub non_refundable_fee: i64,
+    pub refundable_fee: i64,
+    pub rent_fee: i64,
+    /// Inclusion + resource + rent.
+    pub total_fee: i64,
+    /// Rent for keeping the touched entries alive over `horizon_ledgers`.
+    pub ttl_extension_fee: i64,
+    pub horizon_ledgers: u32,
+}
+
+impl FeeEstimate {
+    /// Component-wise sum, used to total a batch of calls. TTL extension
+    /// fees are summed too, which overcounts entries shared between calls.
+    pub fn saturating_add(&self, other: &Self) -> Self {
+        let r = &self.resource_fee;
+        let o = &other.resource_fee;
+        Self {
+            inclusion_fee: self.inclusion_fee.saturating_add(other.inclusion_fee),
+            resource_fee: ResourceFee {
+                compute: r.compute.saturating_add(o.compute),
+                read_entries: r.read_entries.saturating_add(o.read_entries),
+                write_entries: r.write_entries.saturating_add(o.write_entries),
+                read_bytes: r.read_bytes.saturating_add(o.read_bytes),
+                write_bytes: r.write_bytes.saturating_add(o.write_bytes),
+                historical: r.historical.saturating_add(o.historical),
+                bandwidth: r.bandwidth.saturating_add(o.bandwidth),
+                events: r.events.saturating_add(o.events),
+            },
+            non_refundable_fee: self
+                .non_refundable_fee
+                .saturating_add(other.non_refundable_fee),
+            refundable_fee: self.refundable_fee.saturating_add(other.refundable_fee),
+            rent_fee: self.rent_fee.saturating_add(other.rent_fee),
+            total_fee: self.total_fee.saturating_add(other.total_fee),
+            ttl_extension_fee: self
+                .ttl_extension_fee
+                .saturating_add(other.ttl_extension_fee),
+            horizon_ledgers: self.horizon_ledgers.max(other.horizon_ledgers),
+        }
+    }
+
+    /// The cost of repeating the same call `count` times.
+    pub fn repeated(&self, count: u32) -> Self {
+        let n = i64::from(count);
+        let r = &self.resource_fee;
+        Self {
+            inclusion_fee: self.inclusion_fee.saturating_mul(n),
+            resource_fee: ResourceFee {
+                compute: r.compute.saturating_mul(n),
+                read_entries: r.read_entries.saturating_mul(n),
+                write_entries: r.write_entries.saturating_mul(n),
+                read_bytes: r.read_bytes.saturating_mul(n),
+                write_bytes: r.write_bytes.saturating_mul(n),
+                historical: r.historical.saturating_mul(n),
+                bandwidth: r.bandwidth.saturating_mul(n),
+                events: r.events.saturating_mul(n),
+            },
+            non_refundable_fee: self.non_refundable_fee.saturating_mul(n),
+            refundable_fee: self.refundable_fee.saturating_mul(n),
+            rent_fee: self.rent_fee.saturating_mul(n),
+            total_fee: self.total_fee.saturating_mul(n),
+            // Entries touched repeatedly only need extending once.
+            ttl_extension_fee: self.ttl_extension_fee,
+            horizon_ledgers: self.horizon_ledgers,
+        }
+    }
+}
+
+impl FeeSchedule {
+    /// Resource fee for a transaction using `usage`.
+    pub fn resource_fee(&self, usage: &ResourceUsage) -> ResourceFee {
+        ResourceFee {
+            compute: fee_per_increment(
+                usage.instructions,
+                self.fee_per_instruction_increment,
+                INSTRUCTIONS_INCREMENT,
+            ),
+            read_entries: self.fee_per_read_entry.saturating_mul(
+                usage
+                    .read_entries
+                    .saturating_add(usage.write_entries)
+                    .into(),
+            ),
+            write_entries: self
+                .fee_per_write_entry
+                .saturating_mul(usage.write_entries.into()),
+            read_bytes: fee_per_increment(
+                usage.read_bytes,
+                self.fee_per_read_1kb,
+                DATA_SIZE_1KB_INCREMENT,
+            ),
+            write_bytes: fee_per_increment(
+                usage.write_bytes,
+                self.fee_per_write_1kb,
+                DATA_SIZE_1KB_INCREMENT,
+            ),
+            historical: fee_per_increment(
+                usage
+                    .transaction_size_bytes
+                    .saturating_add(TX_BASE_RESULT_SIZE),
+                self.fee_per_historical_1kb,
+                DATA_SIZE_1KB_INCREMENT,
+            ),
+            bandwidth: fee_per_increment(
+                usage.transaction_size_bytes,
+                self.fee_per_transaction_size_1kb,
+                DATA_SIZE_1KB_INCREMENT,
+            ),
+            events: fee_per_increment(
+                usage.contract_events_size_bytes,
+                self.fee_per_contract_event_1kb,
+                DATA_SIZE_1KB_INCREMENT,
+            ),
+        }
+    }
+
+    /// Rent charged for the entry changes of a transaction applied at
+    /// `current_ledger`.
+    pub fn rent_fee(&self, changes: &[RentChange], current_ledger: u32) -> i64 {
+        let mut fee: i64 = 0;
+        let mut extended_entries: i64 = 0;
+        let mut ttl_bytes: u32 = 0;
+        for change in changes {
+            if let Some(ledgers) = change.extension_ledgers(current_ledger) {
+                fee = fee.saturating_add(self.rent_for(
+                    change.is_persistent,
+                    change.new_size_bytes,
+                    ledgers,
+                ));
+            }
+            if let (Some(ledgers), Some(growth)) = (
+                change.prepaid_ledgers(current_ledger),
+                change.new_size_bytes.checked_sub(change.old_size_bytes),
+            ) {
+                fee = fee.saturating_add(self.rent_for(change.is_persistent, growth, ledgers));
+            }
+            if change.old_live_until_ledger < change.new_live_until_ledger {
+                extended_entries = extended_entries.saturating_add(1);
+                ttl_bytes = ttl_bytes.saturating_add(TTL_ENTRY_SIZE);
+            }
+        }
+        fee.saturating_add(self.fee_per_write_entry.saturating_mul(extended_entries))
+            .saturating_add(fee_per_increment(
+                ttl_bytes,
+                self.fee_per_write_1kb,
+                DATA_SIZE_1KB_INCREMENT,
+            ))
+    }
+
+    /// Rent for extending the lifetime of `entries` by `ledgers`.
+    ///
+    /// Covers the rent portion only; the extension transaction itself also
+    /// pays a small resource fee for reading the entries.
+    pub fn ttl_extension_fee(&self, entries: &[StoredEntry], ledgers: u32) -> i64 {
+        if ledgers == 0 {
+            return 0;
+        }
+        let changes: Vec<RentChange> = entries
+            .iter()
+            .map(|entry| RentChange {
+                is_persistent: entry.is_persistent,
+                old_size_bytes: entry.size_bytes,
+                new_size_bytes: entry.size_bytes,
+                old_live_until_ledger: 1,
+                new_live_until_ledger: ledgers.saturating_add(1),
+            })
+            .collect();
+        self.rent_fee(&changes, 1)
+    }
+
+    /// Prices one call: resource fee, rent for its writes and the cost of
+    /// keeping `live_entries` alive for `horizon_ledgers`.
+    pub fn estimate(
+        &self,
+        usage: &ResourceUsage,
+        rent_changes: &[RentChange],
+        current_ledger: u32,
+        live_entries: &[StoredEntry],
+        horizon_ledgers: u32,
+    ) -> FeeEstimate {
+        let resource_fee = self.resource_fee(usage);
+        let non_refundable_fee = resource_fee.non_refundable();
+        let refundable_fee = resource_fee.refundable();
+        let rent_fee = self.rent_fee(rent_changes, current_ledger);
+        FeeEstimate {
+            inclusion_fee: self.inclusion_fee,
+            resource_fee,
+            non_refundable_fee,
+            refundable_fee,
+            rent_fee,
+            total_fee: self
+                .inclusion_fee
+                .saturating_add(non_refundable_fee)
+                .saturating_add(refundable_fee)
+                .saturating_add(rent_fee),
+            ttl_extension_fee: self.ttl_extension_fee(live_entries, horizon_ledgers),
+            horizon_ledgers,
+        }
+    }
+
+    fn rent_for(&self, is_persistent: bool, size_bytes: u32, ledgers: u32) -> i64 {
+        let denominator = if is_persistent {
+            self.persistent_rent_rate_denominator
+        } else {
+            self.temporary_rent_rate_denominator
+        };
+        let num = i64::from(size_bytes)
+            .saturating_mul(self.fee_per_write_1kb)
+            .saturating_mul(i64::from(ledgers));
+        div_ceil(
+            num,
+            DATA_SIZE_1KB_INCREMENT.saturating_mul(denominator).max(1),
+        )
+    }
+}
+
+fn fee_per_increment(value: u32, rate: i64, increment: i64) -> i64 {
+    div_ceil(i64::from(value).saturating_mul(rate), increment.max(1))
+}
+
+fn div_ceil(num: i64, denom: i64) -> i64 {
+    let quotient = num / denom;
+    if num % denom > 0 {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_fee_rounds_up_per_increment() {
+        let schedule = FeeSchedule::default();
+        let fee = schedule.resource_fee(&ResourceUsage {
+            instructions: 10_001,
+            read_entries: 2,
+            write_entries: 1,
+            read_bytes: 1025,
+            write_bytes: 100,
+            contract_events_size_bytes: 0,
+            transaction_size_bytes: 724,
+        });
+        assert_eq!(fee.compute, 26);
+        assert_eq!(fee.read_entries, 3 * 6_250);
+        assert_eq!(fee.write_entries, 10_000);
+        assert_eq!(fee.read_bytes, (1025 * 1_786 + 1023) / 1024);
+        assert_eq!(fee.historical, 16_235);
+        assert_eq!(fee.events, 0);
+        assert_eq!(fee.refundable(), 0);
+    }
+
+    #[test]
+    fn test_rent_for_new_entry() {
+        let schedule = FeeSchedule::default();
+        let fee = schedule.rent_fee(
+            &[RentChange {
+                is_persistent: true,
+                old_size_bytes: 0,
+                new_size_bytes: 1024,
+                old_live_until_ledger: 0,
+                new_live_until_ledger: 100 + 2_103 - 1,
+            }],
+            100,
+        );
+        // One kilobyte for one rent period, plus the TTL entry write.
+        let ttl_write = (48 * 11_800 + 1023) / 1024;
+        assert_eq!(fee, 11_800 + 10_000 + ttl_write);
+    }
+
+    #[test]
+    fn test_rent_for_growth_within_prepaid_period() {
+        let schedule = FeeSchedule::default();
+        let fee = schedule.rent_fee(
+            &[RentChange {
+                is_persistent: false,
+                old_size_bytes: 100,
+                new_size_bytes: 1124,
+                old_live_until_ledger: 4_205,
+                new_live_until_ledger: 4_205,
+            }],
+            0,
+        );
+        assert_eq!(fee, 11_800);
+    }
+
+    #[test]
+    fn test_ttl_extension_scales_with_horizon() {
+        let schedule = FeeSchedule::default();
+        let entry = [StoredEntry {
+            is_persistent: true,
+            size_bytes: 512,
+        }];
+        assert_eq!(schedule.ttl_extension_fee(&entry, 0), 0);
+        let short = schedule.ttl_extension_fee(&entry, 10_000);
+        let long = schedule.ttl_extension_fee(&entry, 100_000);
+        assert!(long > short);
+    }
+
+    #[test]
+    fn test_estimate_totals() {
+        let schedule = FeeSchedule::default();
+        let usage = ResourceUsage {
+            instructions: 1_000_000,
+            contract_events_size_bytes: 200,
+            transaction_size_bytes: 300,
+            ..Default::default()
+        };
+        let estimate = schedule.estimate(&usage, &[], 10, &[], 0);
+        assert_eq!(
+            estimate.total_fee,
+            100 + estimate.non_refundable_fee + estimate.refundable_fee
+        );
+        let batch = estimate.saturating_add(&estimate);
+        assert_eq!(batch.total_fee, 2 * estimate.total_fee);
+        assert_eq!(estimate.repeated(2), batch);
+    }
+}
@@ -1,8 +1,13 @@
 pub mod abi;
+pub mod advisory;
+pub mod audit_chain;
 pub mod error;
+pub mod fees;
+pub mod lockfile;
 pub mod models;
 pub mod pagination;
 pub mod semver;
+pub mod transparency;
 pub mod upgrade;
 
 pub use abi::*;
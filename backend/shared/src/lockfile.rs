@@ -0,0 +1,132 @@
+//! Dependency extraction from `Cargo.lock`.
+//!
+//! Every package with a `source` is a third-party crate, direct or
+//! transitive. Packages without one belong to the workspace being scanned;
+//! the crates they list are the direct dependencies.
+
+use crate::error::{RegistryError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// e.g. `registry+https://github.com/rust-lang/crates.io-index`
+    pub source: String,
+    /// Listed by a workspace package, rather than pulled in transitively
+    pub is_direct: bool,
+}
+
+#[derive(Deserialize)]
+struct RawLockfile {
+    #[serde(default, rename = "package")]
+    packages: Vec<RawPackage>,
+}
+
+#[derive(Deserialize)]
+struct RawPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+/// Third-party packages locked in a `Cargo.lock` (format v1–v4), sorted by
+/// name and version.
+pub fn parse_cargo_lock(content: &str) -> Result<Vec<LockedPackage>> {
+    let lockfile: RawLockfile = toml::from_str(content)
+        .map_err(|e| RegistryError::InvalidInput(format!("Invalid Cargo.lock: {}", e)))?;
+
+    // Dependency entries are `name`, `name version` or
+    // `name version (source)`; the version is only given when ambiguous.
+    let mut direct: HashSet<(String, Option<String>)> = HashSet::new();
+    for package in lockfile.packages.iter().filter(|p| p.source.is_none()) {
+        for dependency in &package.dependencies {
+            let mut parts = dependency.split_whitespace();
+            if let Some(name) = parts.next() {
+                direct.insert((name.to_string(), parts.next().map(str::to_string)));
+            }
+        }
+    }
+
+    let mut packages: Vec<LockedPackage> = lockfile
+        .packages
+        .into_iter()
+        .filter_map(|p| {
+            let source = p.source?;
+            let is_direct = direct.contains(&(p.name.clone(), None))
+                || direct.contains(&(p.name.clone(), Some(p.version.clone())));
+            Some(LockedPackage {
+                name: p.name,
+                version: p.version,
+                source,
+                is_direct,
+            })
+        })
+        .collect();
+    packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    packages.dedup_by(|a, b| a.name == b.name && a.version == b.version);
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCKFILE: &str = r#"
+# This file is automatically @generated by Cargo.
+version = 3
+
+[[package]]
+name = "my-contract"
+version = "0.1.0"
+dependencies = [
+ "soroban-sdk",
+ "syn 2.0.48",
+]
+
+[[package]]
+name = "soroban-sdk"
+version = "21.7.7"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "abc"
+dependencies = [
+ "syn 1.0.109",
+]
+
+[[package]]
+name = "syn"
+version = "1.0.109"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "syn"
+version = "2.0.48"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    #[test]
+    fn extracts_direct_and_transitive_crates() {
+        let packages = parse_cargo_lock(LOCKFILE).unwrap();
+        let summary: Vec<(&str, &str, bool)> = packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str(), p.is_direct))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("soroban-sdk", "21.7.7", true),
+                ("syn", "1.0.109", false),
+                ("syn", "2.0.48", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lockfiles() {
+        assert!(parse_cargo_lock("[[package]]\nname = 1").is_err());
+        assert!(parse_cargo_lock("").unwrap().is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::fees::{FeeEstimate, FeeSchedule, RentChange, ResourceUsage, StoredEntry};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -112,6 +113,12 @@ pub struct ContractVersion {
     /// Signature algorithm identifier (e.g. "ed25519")
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub signature_algorithm: Option<String>,
+    /// SHA-256 of the stored WASM binary, when it has been uploaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm_artifact_sha256: Option<String>,
+    /// SHA-256 of the stored source tarball, when it has been uploaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_artifact_sha256: Option<String>,
 }
 
 /// Verification status and details
@@ -209,6 +216,9 @@ pub struct PublishRequest {
     // Dependencies (new field)
     #[serde(default)]
     pub dependencies: Vec<DependencyDeclaration>,
+    /// Base64-encoded WASM binary; stored and used as the contract's wasm_hash
+    #[serde(default)]
+    pub wasm_base64: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -250,6 +260,12 @@ pub struct CreateContractVersionRequest {
     pub publisher_key: Option<String>,
     #[serde(default)]
     pub signature_algorithm: Option<String>,
+    /// Base64-encoded WASM binary; its SHA-256 must equal `wasm_hash`
+    #[serde(default)]
+    pub wasm_base64: Option<String>,
+    /// Base64-encoded `.tar.gz` of the version's source
+    #[serde(default)]
+    pub source_base64: Option<String>,
 }
 
 // ────────────────────────────────────────────────────────────────────────────
@@ -377,10 +393,13 @@ pub enum SortOrder {
 pub struct ContractSearchParams {
     pub query: Option<String>,
     pub network: Option<Network>,
-    /// Multiple networks filter (e.g. ?network=mainnet&network=testnet)
+    /// Multiple networks filter (e.g. ?networks=mainnet,testnet)
+    #[serde(default, deserialize_with = "comma_separated")]
     pub networks: Option<Vec<Network>>,
     pub verified_only: Option<bool>,
     pub category: Option<String>,
+    /// Contracts must carry every listed tag (e.g. ?tags=defi,amm)
+    #[serde(default, deserialize_with = "comma_separated")]
     pub tags: Option<Vec<String>>,
     pub maturity: Option<MaturityLevel>,
     pub page: Option<i64>,
@@ -389,6 +408,92 @@ pub struct ContractSearchParams {
     pub sort_by: Option<SortBy>,
     pub sort_order: Option<SortOrder>,
     pub cursor: Option<String>,
+    /// Match misspelled terms by trigram similarity (default: true)
+    pub fuzzy: Option<bool>,
+    /// Include facet counts in the response (default: true when searching)
+    pub facets: Option<bool>,
+}
+
+/// Accepts either a list or a single comma-separated string, so list filters
+/// work both in JSON bodies and in query strings.
+fn comma_separated<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    use serde::de::{Error, IntoDeserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        List(Vec<String>),
+        Csv(String),
+    }
+
+    let items = match Option::<Raw>::deserialize(deserializer)? {
+        None => return Ok(None),
+        Some(Raw::List(items)) => items,
+        Some(Raw::Csv(csv)) => csv
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+    };
+    items
+        .into_iter()
+        .map(|item| {
+            T::deserialize(IntoDeserializer::<serde::de::value::Error>::into_deserializer(item))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+/// Number of matching contracts that share one facet value
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Facet counts for a contract search. Each facet is counted with every
+/// other filter applied but its own, so clients can offer alternatives.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchFacets {
+    pub category: Vec<FacetCount>,
+    pub network: Vec<FacetCount>,
+    pub verified: Vec<FacetCount>,
+    pub tags: Vec<FacetCount>,
+}
+
+/// Matched terms wrapped in `<mark>…</mark>`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchHighlights {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A contract search result with its ranking details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractSearchHit {
+    #[serde(flatten)]
+    pub contract: Contract,
+    pub relevance: f32,
+    pub highlights: SearchHighlights,
+    /// ABI functions whose names matched the query
+    #[serde(default)]
+    pub matched_functions: Vec<String>,
+    /// ABI events whose names matched the query
+    #[serde(default)]
+    pub matched_events: Vec<String>,
+}
+
+/// Search results: a page of hits plus facet counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractSearchResponse {
+    #[serde(flatten)]
+    pub page: PaginatedResponse<ContractSearchHit>,
+    pub facets: Option<SearchFacets>,
 }
 
 /// Pagination params for contract versions (limit/offset style)
@@ -589,7 +694,8 @@ pub struct UpdateMigrationStatusRequest {
     pub log_output: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
 #[sqlx(type_name = "deployment_environment", rename_all = "lowercase")]
 pub enum DeploymentEnvironment {
     Blue,
@@ -604,7 +710,18 @@ impl std::fmt::Display for DeploymentEnvironment {
         }
     }
 }
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+
+impl DeploymentEnvironment {
+    pub fn other(self) -> Self {
+        match self {
+            DeploymentEnvironment::Blue => DeploymentEnvironment::Green,
+            DeploymentEnvironment::Green => DeploymentEnvironment::Blue,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
 #[sqlx(type_name = "deployment_status", rename_all = "lowercase")]
 pub enum DeploymentStatus {
     Active,
@@ -626,20 +743,50 @@ pub struct ContractDeployment {
     pub health_checks_failed: i32,
     pub last_health_check_at: Option<DateTime<Utc>>,
     pub error_message: Option<String>,
+    /// Consecutive passing checks needed before a testing slot is switched in
+    pub required_health_checks: i32,
+    /// Consecutive failing checks that fail a testing slot or roll back a live one
+    pub max_failed_health_checks: i32,
+    pub consecutive_passes: i32,
+    pub consecutive_failures: i32,
+    pub auto_switch: bool,
+    /// A newly switched-in slot is rolled back on failure until this time
+    pub monitor_until: Option<DateTime<Utc>>,
+    pub deployed_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DeploymentHealthCheck {
+    pub id: Uuid,
+    pub deployment_id: Uuid,
+    pub wasm_hash: String,
+    pub passed: bool,
+    /// `probe` (run by the API) or `reported`
+    pub source: String,
+    pub detail: Option<String>,
+    pub latency_ms: Option<i32>,
+    pub checked_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct DeploymentSwitch {
     pub id: Uuid,
     pub contract_id: Uuid,
-    pub from_environment: DeploymentEnvironment,
+    /// None for a contract's first activation
+    pub from_environment: Option<DeploymentEnvironment>,
     pub to_environment: DeploymentEnvironment,
     pub switched_at: DateTime<Utc>,
     pub switched_by: Option<String>,
     pub rollback: bool,
+    pub from_deployment_id: Option<Uuid>,
+    pub to_deployment_id: Option<Uuid>,
+    pub from_wasm_hash: Option<String>,
+    pub to_wasm_hash: Option<String>,
+    pub reason: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
 #[sqlx(type_name = "canary_status", rename_all = "snake_case")]
 pub enum CanaryStatus {
     Pending,
@@ -650,13 +797,24 @@ pub enum CanaryStatus {
     Failed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "rollout_stage", rename_all = "snake_case")]
+// Explicit names: snake_case would render `Stage1` as `stage1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "rollout_stage")]
 pub enum RolloutStage {
+    #[serde(rename = "stage_1")]
+    #[sqlx(rename = "stage_1")]
     Stage1,
+    #[serde(rename = "stage_2")]
+    #[sqlx(rename = "stage_2")]
     Stage2,
+    #[serde(rename = "stage_3")]
+    #[sqlx(rename = "stage_3")]
     Stage3,
+    #[serde(rename = "stage_4")]
+    #[sqlx(rename = "stage_4")]
     Stage4,
+    #[serde(rename = "complete")]
+    #[sqlx(rename = "complete")]
     Complete,
 }
 
@@ -677,6 +835,14 @@ pub struct CanaryRelease {
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_by: Option<String>,
+    /// Requests the current stage must see before it can be promoted
+    pub min_requests_per_stage: i32,
+    /// Seconds the current stage must run before it can be promoted
+    pub min_stage_duration_secs: i32,
+    /// Promote healthy stages automatically; otherwise only roll back
+    pub auto_advance: bool,
+    pub stage_started_at: DateTime<Utc>,
+    pub rollback_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -700,18 +866,34 @@ pub struct CanaryUserAssignment {
     pub assigned_at: DateTime<Utc>,
     pub notified: bool,
     pub notified_at: Option<DateTime<Utc>>,
+    /// Stage at which this caller first reached the new deployment
+    pub stage: RolloutStage,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateCanaryRequest {
     pub contract_id: String,
     pub to_deployment_id: String,
+    /// Defaults to the contract's active deployment
+    #[serde(default)]
+    pub from_deployment_id: Option<String>,
     pub error_rate_threshold: Option<f64>,
+    /// Final share of traffic (percent); the canary completes on reaching it
+    #[serde(default)]
+    pub target_percentage: Option<i32>,
+    #[serde(default)]
+    pub min_requests_per_stage: Option<i32>,
+    #[serde(default)]
+    pub min_stage_duration_secs: Option<i32>,
+    #[serde(default)]
+    pub auto_advance: Option<bool>,
     pub created_by: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdvanceCanaryRequest {
+    /// Taken from the path when omitted
+    #[serde(default)]
     pub canary_id: String,
     pub target_percentage: Option<i32>,
     pub advanced_by: Option<String>,
@@ -719,6 +901,8 @@ pub struct AdvanceCanaryRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordCanaryMetricRequest {
+    /// Taken from the path when omitted
+    #[serde(default)]
     pub canary_id: String,
     pub requests: i32,
     pub errors: i32,
@@ -727,7 +911,8 @@ pub struct RecordCanaryMetricRequest {
     pub p99_response_time_ms: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
 #[sqlx(type_name = "ab_test_status", rename_all = "snake_case")]
 pub enum AbTestStatus {
     Draft,
@@ -737,7 +922,8 @@ pub enum AbTestStatus {
     Cancelled,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
 #[sqlx(type_name = "variant_type", rename_all = "snake_case")]
 pub enum VariantType {
     Control,
@@ -762,6 +948,15 @@ pub struct AbTest {
     pub ended_at: Option<DateTime<Utc>>,
     pub created_by: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// `conversion` (0/1 outcomes) or `continuous`
+    pub metric_kind: String,
+    pub higher_is_better: bool,
+    /// Smallest absolute difference worth detecting; drives sample-size planning
+    pub minimum_detectable_effect: Option<Decimal>,
+    /// Target power (percent) for sample-size planning
+    pub statistical_power: Decimal,
+    pub winner: Option<VariantType>,
+    pub stop_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -808,6 +1003,8 @@ pub struct AbTestResult {
     pub statistical_significance: Option<Decimal>,
     pub is_winner: bool,
     pub calculated_at: DateTime<Utc>,
+    pub test_method: Option<String>,
+    pub test_statistic: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -823,12 +1020,28 @@ pub struct CreateAbTestRequest {
     pub significance_threshold: Option<f64>,
     pub min_sample_size: Option<i32>,
     pub created_by: Option<String>,
+    /// `conversion` or `continuous` (default)
+    #[serde(default)]
+    pub metric_kind: Option<String>,
+    #[serde(default)]
+    pub higher_is_better: Option<bool>,
+    #[serde(default)]
+    pub minimum_detectable_effect: Option<f64>,
+    /// Percent, default 80
+    #[serde(default)]
+    pub statistical_power: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordAbTestMetricRequest {
+    /// Taken from the path when omitted
+    #[serde(default)]
     pub test_id: String,
     pub user_address: Option<String>,
+    /// Required when no `user_address` is given; otherwise the caller's
+    /// assigned variant is used
+    #[serde(default)]
+    pub variant: Option<VariantType>,
     pub metric_name: String,
     pub metric_value: f64,
     pub metadata: Option<serde_json::Value>,
@@ -840,7 +1053,8 @@ pub struct GetVariantRequest {
     pub user_address: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
 #[sqlx(type_name = "metric_type", rename_all = "snake_case")]
 pub enum MetricType {
     ExecutionTime,
@@ -850,7 +1064,10 @@ pub enum MetricType {
     ErrorRate,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
+)]
+#[serde(rename_all = "lowercase")]
 #[sqlx(type_name = "alert_severity", rename_all = "lowercase")]
 pub enum AlertSeverity {
     Info,
@@ -886,6 +1103,11 @@ pub struct PerformanceAnomaly {
     pub resolved: bool,
     pub resolved_at: Option<DateTime<Utc>>,
     pub description: Option<String>,
+    /// `zscore`, `ewma` or `seasonal`
+    pub detector: String,
+    /// Standardised distance from the detector's baseline
+    pub score: Option<f64>,
+    pub last_evaluated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -904,6 +1126,7 @@ pub struct PerformanceAlert {
     pub resolved: bool,
     pub resolved_at: Option<DateTime<Utc>>,
     pub message: Option<String>,
+    pub alert_config_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -924,6 +1147,8 @@ pub struct PerformanceTrend {
     pub trend_direction: Option<String>,
     pub change_percent: Option<Decimal>,
     pub calculated_at: DateTime<Utc>,
+    /// `hour` or `day`
+    pub granularity: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -1140,12 +1365,33 @@ pub struct TimelineEntry {
 pub struct DeployGreenRequest {
     pub contract_id: String,
     pub wasm_hash: String,
+    #[serde(default)]
+    pub required_health_checks: Option<i32>,
+    #[serde(default)]
+    pub max_failed_health_checks: Option<i32>,
+    /// Switch in automatically once the checks pass (default true)
+    #[serde(default)]
+    pub auto_switch: Option<bool>,
+    #[serde(default)]
+    pub deployed_by: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwitchDeploymentRequest {
     pub contract_id: String,
+    /// Switch even if the candidate has not passed its health checks
     pub force: Option<bool>,
+    #[serde(default)]
+    pub switched_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackDeploymentRequest {
+    pub contract_id: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub rolled_back_by: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1153,6 +1399,8 @@ pub struct HealthCheckRequest {
     pub contract_id: String,
     pub environment: DeploymentEnvironment,
     pub passed: bool,
+    #[serde(default)]
+    pub detail: Option<String>,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -1203,6 +1451,9 @@ pub enum AuditActionType {
     PublisherChanged,
     VersionCreated,
     Rollback,
+    CanaryTransition,
+    GovernanceExecuted,
+    MultisigExecuted,
 }
 
 impl std::fmt::Display for AuditActionType {
@@ -1214,6 +1465,9 @@ impl std::fmt::Display for AuditActionType {
             Self::PublisherChanged => "publisher_changed",
             Self::VersionCreated => "version_created",
             Self::Rollback => "rollback",
+            Self::CanaryTransition => "canary_transition",
+            Self::GovernanceExecuted => "governance_executed",
+            Self::MultisigExecuted => "multisig_executed",
         };
         write!(f, "{}", s)
     }
@@ -1232,6 +1486,17 @@ pub struct ContractAuditLog {
     pub previous_hash: Option<String>,
     pub hash: Option<String>,
     pub signature: Option<String>,
+    /// Position in the contract's hash chain
+    #[sqlx(default)]
+    #[serde(default)]
+    pub seq: i64,
+    #[sqlx(default)]
+    #[serde(default)]
+    pub hash_version: i16,
+    /// Hex SHA-256 of the public key that produced `signature`
+    #[sqlx(default)]
+    #[serde(default)]
+    pub signing_key_id: Option<String>,
 }
 
 /// Full contract state captured at each audited change in `contract_snapshots`.
@@ -1275,29 +1540,110 @@ pub struct RollbackRequest {
 }
 
 // Multisig deployment types
+
+/// Lifecycle of a multisig deploy proposal (`proposal_status`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "proposal_status", rename_all = "lowercase")]
+pub enum ProposalStatus {
+    Pending,
+    Approved,
+    Executed,
+    Expired,
+    Rejected,
+}
+
+impl std::fmt::Display for ProposalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ProposalStatus::Pending => "pending",
+            ProposalStatus::Approved => "approved",
+            ProposalStatus::Executed => "executed",
+            ProposalStatus::Expired => "expired",
+            ProposalStatus::Rejected => "rejected",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// What a deploy proposal's transaction does on chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "deploy_operation", rename_all = "lowercase")]
+pub enum DeployOperation {
+    /// Install the WASM (`UploadContractWasm`)
+    Upload,
+    /// Instantiate a contract from installed WASM (`CreateContract`)
+    Create,
+    /// Call the contract's `upgrade(wasm_hash)` (`InvokeContract`)
+    Upgrade,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct MultisigPolicy {
     pub id: Uuid,
     pub name: String,
+    /// Total signer weight required
     pub threshold: i32,
-    pub required_signatures: i32,
     pub signer_addresses: Vec<String>,
+    /// Parallel to `signer_addresses`; `None` gives every signer weight 1
+    #[sqlx(default)]
+    #[serde(default)]
+    pub signer_weights: Option<Vec<i32>>,
     pub expiry_seconds: i32,
     pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MultisigPolicy {
+    pub fn weight_of(&self, signer: &str) -> Option<i32> {
+        let index = self.signer_addresses.iter().position(|s| s == signer)?;
+        Some(
+            self.signer_weights
+                .as_ref()
+                .and_then(|weights| weights.get(index).copied())
+                .unwrap_or(1),
+        )
+    }
+
+    pub fn total_weight(&self) -> i64 {
+        match &self.signer_weights {
+            Some(weights) => weights.iter().map(|w| i64::from(*w)).sum(),
+            None => self.signer_addresses.len() as i64,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct DeployProposal {
     pub id: Uuid,
     pub contract_name: String,
-    pub contract_id: Uuid,
+    /// On-chain contract address (`C...`)
+    pub contract_id: String,
     pub wasm_hash: String,
-    pub network: String,
+    pub network: Network,
     pub description: Option<String>,
     pub policy_id: Uuid,
-    pub status: String,
+    pub status: ProposalStatus,
     pub expires_at: DateTime<Utc>,
+    pub executed_at: Option<DateTime<Utc>>,
     pub proposer: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(default)]
+    pub operation: Option<DeployOperation>,
+    /// Unsigned `TransactionEnvelope`, base64 XDR
+    #[sqlx(default)]
+    pub transaction_xdr: Option<String>,
+    /// Hex transaction hash on `network`; what signers sign
+    #[sqlx(default)]
+    pub transaction_hash: Option<String>,
+    #[sqlx(default)]
+    pub signed_envelope_xdr: Option<String>,
+    #[sqlx(default)]
+    pub submitted_at: Option<DateTime<Utc>>,
+    #[sqlx(default)]
+    pub submission_error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -1305,17 +1651,62 @@ pub struct ProposalSignature {
     pub id: Uuid,
     pub proposal_id: Uuid,
     pub signer_address: String,
+    /// Base64 XDR `DecoratedSignature` over the transaction hash
+    pub signature_data: Option<String>,
+    #[sqlx(default)]
+    pub weight: i32,
+    pub signed_at: DateTime<Utc>,
 }
 
-/// Paginated response for audit log
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProposalWithSignatures {
     pub proposal: DeployProposal,
     pub policy: MultisigPolicy,
     pub signatures: Vec<ProposalSignature>,
+    pub collected_weight: i64,
+    /// Weight still missing (signature count when every weight is 1)
     pub signatures_needed: i32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePolicyRequest {
+    pub name: String,
+    pub threshold: i32,
+    pub signer_addresses: Vec<String>,
+    pub signer_weights: Option<Vec<i32>>,
+    pub expiry_seconds: Option<i32>,
+    pub created_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateProposalRequest {
+    pub contract_name: String,
+    pub contract_id: String,
+    pub wasm_hash: String,
+    pub network: Network,
+    pub description: Option<String>,
+    pub policy_id: Uuid,
+    pub proposer: String,
+    pub operation: DeployOperation,
+    /// Unsigned `TransactionEnvelope`, base64 XDR
+    pub transaction_xdr: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignProposalRequest {
+    pub signer_address: String,
+    /// Base64 XDR `DecoratedSignature` over the proposal's transaction hash
+    pub signature_data: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecuteProposalRequest {
+    /// Submit the signed envelope through the registry's RPC node
+    #[serde(default)]
+    pub submit: bool,
+}
+
+/// Paginated response for audit log
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditLogPage {
     pub items: Vec<ContractAuditLog>,
@@ -1324,6 +1715,224 @@ pub struct AuditLogPage {
     pub total_pages: i64,
 }
 
+// ════════════════════════════════════════════════════════════════════════════
+// Governance types
+// ════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "governance_model", rename_all = "snake_case")]
+pub enum GovernanceModel {
+    /// One vote per unit of member weight
+    TokenWeighted,
+    /// Integer square root of member weight
+    Quadratic,
+    /// One vote per member
+    Multisig,
+    /// Weighted like `token_weighted`, with a mandatory execution delay
+    Timelock,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "governance_proposal_status", rename_all = "snake_case")]
+pub enum GovernanceProposalStatus {
+    Pending,
+    Active,
+    Passed,
+    Rejected,
+    Executed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "vote_choice", rename_all = "snake_case")]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+}
+
+/// The registry change a governance proposal makes when executed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GovernanceAction {
+    DeprecateContract {
+        retirement_at: DateTime<Utc>,
+        #[serde(default)]
+        replacement_contract_id: Option<Uuid>,
+        #[serde(default)]
+        migration_guide_url: Option<String>,
+        #[serde(default)]
+        notes: Option<String>,
+    },
+    ChangePublisher {
+        publisher_id: Uuid,
+    },
+    /// Only the fields given are changed
+    UpdateMetadata {
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
+        #[serde(default)]
+        category: Option<String>,
+        #[serde(default)]
+        tags: Option<Vec<String>>,
+    },
+    SetMaturity {
+        /// alpha, beta, stable, mature or legacy
+        maturity: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    ApproveVersion {
+        version: String,
+    },
+    /// The policy must not govern deploy proposals for other contracts
+    ChangeMultisigPolicy {
+        policy_id: Uuid,
+        #[serde(default)]
+        threshold: Option<i32>,
+        #[serde(default)]
+        signer_addresses: Option<Vec<String>>,
+        #[serde(default)]
+        expiry_seconds: Option<i32>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GovernanceProposal {
+    pub id: Uuid,
+    pub contract_id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub governance_model: GovernanceModel,
+    pub proposer: Uuid,
+    pub status: GovernanceProposalStatus,
+    pub voting_starts_at: DateTime<Utc>,
+    pub voting_ends_at: DateTime<Utc>,
+    pub execution_delay_hours: Option<i32>,
+    /// Percent of the electorate's voting power that must vote
+    pub quorum_required: i32,
+    /// Percent of for + against votes that must be for
+    pub approval_threshold: i32,
+    pub created_at: DateTime<Utc>,
+    pub executed_at: Option<DateTime<Utc>>,
+    /// None for proposals created before actions existed
+    pub action: Option<sqlx::types::Json<GovernanceAction>>,
+    pub execution_result: Option<serde_json::Value>,
+    pub execution_error: Option<String>,
+}
+
+impl GovernanceProposal {
+    /// End of the timelock: voting end plus the execution delay.
+    pub fn executable_at(&self) -> DateTime<Utc> {
+        self.voting_ends_at
+            + chrono::Duration::hours(self.execution_delay_hours.unwrap_or(0).max(0) as i64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GovernanceVote {
+    pub id: Uuid,
+    pub proposal_id: Uuid,
+    pub voter: Uuid,
+    pub vote_choice: VoteChoice,
+    /// The voter's own power; delegated power is added when tallying
+    pub voting_power: i64,
+    pub delegated_from: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct VoteDelegation {
+    pub id: Uuid,
+    pub delegator: Uuid,
+    pub delegate: Uuid,
+    /// None delegates for every contract
+    pub contract_id: Option<Uuid>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GovernanceMember {
+    pub contract_id: Uuid,
+    pub publisher_id: Uuid,
+    pub weight: i64,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateGovernanceProposalRequest {
+    pub title: String,
+    pub description: String,
+    pub governance_model: GovernanceModel,
+    /// Publisher ID; must be in the contract's electorate
+    pub proposer: Uuid,
+    pub action: GovernanceAction,
+    pub voting_duration_hours: i32,
+    #[serde(default)]
+    pub execution_delay_hours: Option<i32>,
+    #[serde(default)]
+    pub quorum_required: Option<i32>,
+    #[serde(default)]
+    pub approval_threshold: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastVoteRequest {
+    /// Publisher ID of the voter
+    pub voter: Uuid,
+    pub vote_choice: VoteChoice,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegateVoteRequest {
+    pub delegator: Uuid,
+    pub delegate: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetGovernanceMemberRequest {
+    pub publisher_id: Uuid,
+    /// 0 removes the member
+    pub weight: i64,
+}
+
+/// Voting power per choice, after weighting and delegation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GovernanceTally {
+    pub votes_for: i64,
+    pub votes_against: i64,
+    pub votes_abstain: i64,
+    /// Power of the whole electorate
+    pub eligible_power: i64,
+    /// Of the counted votes, power that arrived through delegation
+    pub delegated_power: i64,
+}
+
+impl GovernanceTally {
+    pub fn total_votes(&self) -> i64 {
+        self.votes_for + self.votes_against + self.votes_abstain
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposalResults {
+    pub proposal: GovernanceProposal,
+    #[serde(flatten)]
+    pub tally: GovernanceTally,
+    pub total_votes: i64,
+    pub quorum_met: bool,
+    pub approved: bool,
+    pub voting_closed: bool,
+    pub executable_at: DateTime<Utc>,
+}
+
 // ════════════════════════════════════════════════════════════════════════════
 // Config Management types
 // ════════════════════════════════════════════════════════════════════════════
@@ -1908,3 +2517,112 @@ pub struct ContractChangelogResponse {
     pub contract_id: Uuid,
     pub entries: Vec<ContractChangelogEntry>,
 }
+
+// ────────────────────────────────────────────────────────────────────────────
+// Cost estimation
+// ────────────────────────────────────────────────────────────────────────────
+
+/// Where the resource figures behind a cost estimate came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostSource {
+    /// Measured by the caller (e.g. a local simulation) and submitted.
+    Provided,
+    /// Measured by simulating the transaction against a Soroban RPC node.
+    Simulation,
+    /// Averaged from previous estimates recorded for the method.
+    Historical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEstimateRequest {
+    pub method_name: String,
+    pub invocations: Option<u32>,
+    /// Resource usage measured by the caller.
+    #[serde(default)]
+    pub resources: Option<ResourceUsage>,
+    #[serde(default)]
+    pub rent_changes: Vec<RentChange>,
+    #[serde(default)]
+    pub live_entries: Vec<StoredEntry>,
+    /// Base64 transaction envelope to simulate when no resources are given.
+    #[serde(default)]
+    pub transaction_xdr: Option<String>,
+    /// Overrides the server's fee schedule.
+    #[serde(default)]
+    pub fee_schedule: Option<FeeSchedule>,
+    /// Ledgers to keep touched entries alive for when pricing TTL extension.
+    #[serde(default)]
+    pub ttl_horizon_ledgers: Option<u32>,
+    /// Ledger the call would be applied at; used for rent.
+    #[serde(default)]
+    pub current_ledger: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub method_name: String,
+    pub invocations: u32,
+    pub source: CostSource,
+    pub resources: ResourceUsage,
+    #[serde(default)]
+    pub live_entries: Vec<StoredEntry>,
+    pub per_call: FeeEstimate,
+    pub total: FeeEstimate,
+    pub total_xlm: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCostEstimate {
+    pub estimates: Vec<CostEstimate>,
+    pub total: FeeEstimate,
+    pub total_xlm: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostOptimization {
+    pub current_cost: i64,
+    pub optimized_cost: i64,
+    pub savings_percent: f64,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostForecast {
+    pub daily_cost_xlm: f64,
+    pub monthly_cost_xlm: f64,
+    pub yearly_cost_xlm: f64,
+    /// Rent for keeping the method's entries alive, included in the totals.
+    pub daily_rent_xlm: f64,
+    pub usage_pattern: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_params_accept_comma_separated_lists() {
+        let params: ContractSearchParams = serde_json::from_value(serde_json::json!({
+            "networks": "mainnet, testnet",
+            "tags": ["defi", "amm"],
+        }))
+        .unwrap();
+        let networks: Vec<String> = params
+            .networks
+            .unwrap()
+            .iter()
+            .map(Network::to_string)
+            .collect();
+        assert_eq!(networks, vec!["mainnet", "testnet"]);
+        assert_eq!(params.tags, Some(vec!["defi".into(), "amm".into()]));
+
+        let params: ContractSearchParams = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(params.networks.is_none());
+
+        let bad = serde_json::from_value::<ContractSearchParams>(serde_json::json!({
+            "networks": "mainnet,moonnet",
+        }));
+        assert!(bad.is_err());
+    }
+}
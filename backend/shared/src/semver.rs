@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 /// Semantic Versioning (SemVer) implementation
-/// Supports parsing MAJOR.MINOR.PATCH and constraints like ^1.0.0, ~2.3.0
+/// Supports parsing MAJOR.MINOR.PATCH and Cargo/npm-style ranges such as
+/// ^1.0.0, ~2.3, >=1.2, <2, 1.*, 1.2 - 1.4 and ^1 || ^2
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SemVer {
@@ -130,51 +131,458 @@ fn compare_pre_release(a: &str, b: &str) -> std::cmp::Ordering {
     a_parts.len().cmp(&b_parts.len())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum VersionConstraint {
-    Exact(SemVer),
-    Caret(SemVer), // ^1.2.3 := >=1.2.3 <2.0.0
-    Tilde(SemVer), // ~1.2.3 := >=1.2.3 <1.3.0
+/// Comparison operator of a single [`Comparator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Exact,     // =1.2.3, =1.2 := >=1.2.0 <1.3.0
+    Greater,   // >1.2.3, >1.2 := >=1.3.0
+    GreaterEq, // >=1.2.3
+    Less,      // <1.2.3
+    LessEq,    // <=1.2.3, <=1.2 := <1.3.0
+    Tilde,     // ~1.2.3 := >=1.2.3 <1.3.0, ~1 := >=1.0.0 <2.0.0
+    Caret,     // ^1.2.3 := >=1.2.3 <2.0.0, ^0.2.3 := >=0.2.3 <0.3.0
+    Wildcard,  // 1.*, 1.2.x, or a bare partial version such as 1.2
 }
 
-impl VersionConstraint {
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Tilde => "~",
+            Op::Caret => "^",
+            Op::Wildcard => "",
+        }
+    }
+}
+
+/// One operator applied to a possibly partial version, e.g. `>=1.2` or
+/// `~0.3.1-beta.2`. Missing components behave like Cargo's: `>1.2` means
+/// `>=1.3.0` and `<=1.2` means `<1.3.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comparator {
+    pub op: Op,
+    pub major: u64,
+    pub minor: Option<u64>,
+    pub patch: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_release: Option<String>,
+}
+
+/// The result of parsing one range term: a comparator, or `*` which
+/// accepts every release.
+enum Term {
+    Any,
+    Comparator(Comparator),
+}
+
+impl Comparator {
     pub fn parse(s: &str) -> Option<Self> {
-        let s = s.trim();
-        if let Some(rest) = s.strip_prefix('^') {
-            SemVer::parse(rest).map(VersionConstraint::Caret)
-        } else if let Some(rest) = s.strip_prefix('~') {
-            SemVer::parse(rest).map(VersionConstraint::Tilde)
-        } else {
-            SemVer::parse(s).map(VersionConstraint::Exact)
+        match parse_term(s)? {
+            Term::Comparator(c) => Some(c),
+            Term::Any => None,
         }
     }
 
     pub fn matches(&self, version: &SemVer) -> bool {
-        match self {
-            VersionConstraint::Exact(req) => version == req,
-            VersionConstraint::Caret(req) => {
-                if version < req {
-                    return false;
+        match self.op {
+            Op::Exact | Op::Wildcard => self.matches_exact(version),
+            Op::Greater => self.matches_greater(version),
+            Op::GreaterEq => self.matches_exact(version) || self.matches_greater(version),
+            Op::Less => self.matches_less(version),
+            Op::LessEq => self.matches_exact(version) || self.matches_less(version),
+            Op::Tilde => self.matches_tilde(version),
+            Op::Caret => self.matches_caret(version),
+        }
+    }
+
+    /// The lowest version this comparator accepts, if it has a lower bound.
+    pub fn lower_bound(&self) -> Option<SemVer> {
+        let floor = |major, minor, patch, pre_release| SemVer {
+            major,
+            minor,
+            patch,
+            pre_release,
+            build_metadata: None,
+        };
+        match self.op {
+            Op::Less | Op::LessEq => None,
+            Op::Greater => Some(match (self.minor, self.patch) {
+                (None, _) => floor(self.major + 1, 0, 0, None),
+                (Some(minor), None) => floor(self.major, minor + 1, 0, None),
+                // The release is the first version above its own pre-releases.
+                (Some(minor), Some(patch)) if self.pre_release.is_some() => {
+                    floor(self.major, minor, patch, None)
+                }
+                (Some(minor), Some(patch)) => floor(self.major, minor, patch + 1, None),
+            }),
+            _ => Some(floor(
+                self.major,
+                self.minor.unwrap_or(0),
+                self.patch.unwrap_or(0),
+                self.pre_release.clone(),
+            )),
+        }
+    }
+
+    fn matches_exact(&self, v: &SemVer) -> bool {
+        v.major == self.major
+            && self.minor.is_none_or(|minor| v.minor == minor)
+            && self.patch.is_none_or(|patch| v.patch == patch)
+            && (self.patch.is_none() || v.pre_release == self.pre_release)
+    }
+
+    fn matches_greater(&self, v: &SemVer) -> bool {
+        if v.major != self.major {
+            return v.major > self.major;
+        }
+        match self.minor {
+            None => return false,
+            Some(minor) if v.minor != minor => return v.minor > minor,
+            Some(_) => {}
+        }
+        match self.patch {
+            None => return false,
+            Some(patch) if v.patch != patch => return v.patch > patch,
+            Some(_) => {}
+        }
+        cmp_pre_release(&v.pre_release, &self.pre_release).is_gt()
+    }
+
+    fn matches_less(&self, v: &SemVer) -> bool {
+        if v.major != self.major {
+            return v.major < self.major;
+        }
+        match self.minor {
+            None => return false,
+            Some(minor) if v.minor != minor => return v.minor < minor,
+            Some(_) => {}
+        }
+        match self.patch {
+            None => return false,
+            Some(patch) if v.patch != patch => return v.patch < patch,
+            Some(_) => {}
+        }
+        cmp_pre_release(&v.pre_release, &self.pre_release).is_lt()
+    }
+
+    fn matches_tilde(&self, v: &SemVer) -> bool {
+        if v.major != self.major || self.minor.is_some_and(|minor| v.minor != minor) {
+            return false;
+        }
+        match self.patch {
+            None => true,
+            Some(patch) if v.patch != patch => v.patch > patch,
+            Some(_) => cmp_pre_release(&v.pre_release, &self.pre_release).is_ge(),
+        }
+    }
+
+    fn matches_caret(&self, v: &SemVer) -> bool {
+        if v.major != self.major {
+            return false;
+        }
+        let Some(minor) = self.minor else {
+            return true;
+        };
+        let Some(patch) = self.patch else {
+            // ^1.2 := >=1.2.0 <2.0.0, ^0.2 := >=0.2.0 <0.3.0
+            return if self.major > 0 {
+                v.minor >= minor
+            } else {
+                v.minor == minor
+            };
+        };
+
+        if self.major > 0 {
+            if v.minor != minor {
+                return v.minor > minor;
+            }
+            if v.patch != patch {
+                return v.patch > patch;
+            }
+        } else if minor > 0 {
+            // ^0.x.y := >=0.x.y <0.(x+1).0
+            if v.minor != minor {
+                return false;
+            }
+            if v.patch != patch {
+                return v.patch > patch;
+            }
+        } else if v.minor != minor || v.patch != patch {
+            // ^0.0.x := =0.0.x
+            return false;
+        }
+        cmp_pre_release(&v.pre_release, &self.pre_release).is_ge()
+    }
+
+    /// Whether a pre-release of `v` may match: only comparators that name a
+    /// pre-release of the same `major.minor.patch` opt in to them, as in
+    /// Cargo and npm.
+    fn allows_pre_release_of(&self, v: &SemVer) -> bool {
+        self.pre_release.is_some()
+            && self.major == v.major
+            && self.minor == Some(v.minor)
+            && self.patch == Some(v.patch)
+    }
+}
+
+impl std::fmt::Display for Comparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.op.symbol(), self.major)?;
+        match (self.minor, self.patch) {
+            (Some(minor), Some(patch)) => {
+                write!(f, ".{}.{}", minor, patch)?;
+                if let Some(ref pre) = self.pre_release {
+                    write!(f, "-{}", pre)?;
                 }
-                if req.major == 0 {
-                    if req.minor == 0 {
-                        // ^0.0.x := >=0.0.x <0.0.(x+1) - exact patch match with major=0 and minor=0
-                        return version.major == 0 && version.minor == 0 && version.patch == req.patch;
+            }
+            (Some(minor), None) => write!(f, ".{}", minor)?,
+            _ => {}
+        }
+        if self.op == Op::Wildcard {
+            write!(f, ".*")?;
+        }
+        Ok(())
+    }
+}
+
+/// A missing pre-release sorts after every pre-release of the same version.
+fn cmp_pre_release(a: &Option<String>, b: &Option<String>) -> std::cmp::Ordering {
+    match (a, b) {
+        (None, None) => std::cmp::Ordering::Equal,
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (Some(a), Some(b)) => compare_pre_release(a, b),
+    }
+}
+
+fn parse_term(s: &str) -> Option<Term> {
+    let s = s.trim();
+    // Two-character operators first so ">=" isn't read as ">".
+    let operators = [
+        (">=", Op::GreaterEq),
+        ("<=", Op::LessEq),
+        (">", Op::Greater),
+        ("<", Op::Less),
+        ("=", Op::Exact),
+        ("^", Op::Caret),
+        ("~", Op::Tilde),
+    ];
+    let (op, rest) = operators
+        .iter()
+        .find_map(|(symbol, op)| s.strip_prefix(symbol).map(|rest| (Some(*op), rest.trim())))
+        .unwrap_or((None, s));
+
+    // Build metadata never affects matching.
+    let rest = rest.split_once('+').map_or(rest, |(version, _)| version);
+    let (core, pre_release) = match rest.split_once('-') {
+        Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+        Some(_) => return None,
+        None => (rest, None),
+    };
+
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    let mut numbers: Vec<u64> = Vec::with_capacity(3);
+    let mut wildcard = false;
+    for part in &parts {
+        if matches!(*part, "*" | "x" | "X") {
+            wildcard = true;
+        } else if wildcard || part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            // Nothing may follow a wildcard component.
+            return None;
+        } else {
+            numbers.push(part.parse().ok()?);
+        }
+    }
+    let partial = numbers.len() < 3;
+    if pre_release.is_some() && partial {
+        return None;
+    }
+
+    let Some(&major) = numbers.first() else {
+        // `*`, `x`, `>=*`: every version. Upper or strict bounds on a bare
+        // wildcard are meaningless.
+        return match op {
+            None | Some(Op::Exact | Op::GreaterEq | Op::LessEq | Op::Caret | Op::Tilde) => {
+                Some(Term::Any)
+            }
+            _ => None,
+        };
+    };
+    let op = match op {
+        Some(op) => op,
+        None if partial => Op::Wildcard,
+        None => Op::Exact,
+    };
+
+    Some(Term::Comparator(Comparator {
+        op,
+        major,
+        minor: numbers.get(1).copied(),
+        patch: numbers.get(2).copied(),
+        pre_release,
+    }))
+}
+
+/// Comparators that must all hold, e.g. `>= 1.2, < 2` (Cargo style, comma
+/// separated), `>=1.2.0 <2.0.0` (npm style, space separated) or the hyphen
+/// range `1.2 - 1.4.5`. No comparators means any release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct VersionReq {
+    pub comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut comparators = Vec::new();
+        for group in s.split(',') {
+            let tokens: Vec<&str> = group.split_whitespace().collect();
+            if tokens.is_empty() {
+                return None;
+            }
+            if let [from, "-", to] = tokens.as_slice() {
+                // 1.2 - 1.4.5 := >=1.2.0 <=1.4.5
+                for (op, bound) in [(">=", from), ("<=", to)] {
+                    if bound.starts_with(['<', '>', '=', '^', '~']) {
+                        return None;
+                    }
+                    if let Term::Comparator(c) = parse_term(&format!("{}{}", op, bound))? {
+                        comparators.push(c);
                     }
-                    // ^0.x.y := >=0.x.y <0.(x+1).0
-                    return version.major == 0 && version.minor == req.minor;
                 }
-                // ^1.x.y := >=1.x.y <2.0.0
-                version.major == req.major
+                continue;
             }
-            VersionConstraint::Tilde(req) => {
-                if version < req {
-                    return false;
+
+            let mut tokens = tokens.into_iter();
+            while let Some(token) = tokens.next() {
+                // `>= 1.2` arrives as two tokens.
+                let term = if token.bytes().all(|b| b"<>=^~".contains(&b)) {
+                    parse_term(&format!("{}{}", token, tokens.next()?))?
+                } else {
+                    parse_term(token)?
+                };
+                if let Term::Comparator(c) = term {
+                    comparators.push(c);
                 }
-                // ~1.2.3 := >=1.2.3 <1.3.0
-                version.major == req.major && version.minor == req.minor
             }
         }
+        Some(VersionReq { comparators })
+    }
+
+    pub fn matches(&self, version: &SemVer) -> bool {
+        if !self.comparators.iter().all(|c| c.matches(version)) {
+            return false;
+        }
+        version.pre_release.is_none()
+            || self
+                .comparators
+                .iter()
+                .any(|c| c.allows_pre_release_of(version))
+    }
+}
+
+impl std::fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.comparators.is_empty() {
+            return write!(f, "*");
+        }
+        let parts: Vec<String> = self.comparators.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl TryFrom<String> for VersionReq {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        VersionReq::parse(&s).ok_or_else(|| format!("invalid version requirement: {}", s))
+    }
+}
+
+impl From<VersionReq> for String {
+    fn from(req: VersionReq) -> Self {
+        req.to_string()
+    }
+}
+
+/// A full version range: one or more [`VersionReq`]s joined by `||`, any of
+/// which may match, e.g. `^1.4 || >=2.1, <3`.
+///
+/// A bare full version (`1.2.3`) means exactly that version, and a bare
+/// partial version (`1.2`) means `1.2.*`. Pre-releases only match when a
+/// comparator names a pre-release of the same `major.minor.patch`, so
+/// `>=1.0.0-beta` accepts `1.0.0-rc.1` but not `1.1.0-alpha`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct VersionConstraint {
+    pub alternatives: Vec<VersionReq>,
+}
+
+impl VersionConstraint {
+    pub fn parse(s: &str) -> Option<Self> {
+        let alternatives = s
+            .split("||")
+            .map(VersionReq::parse)
+            .collect::<Option<Vec<_>>>()?;
+        Some(VersionConstraint { alternatives })
+    }
+
+    /// `*`: any release.
+    pub fn any() -> Self {
+        VersionConstraint {
+            alternatives: vec![VersionReq {
+                comparators: Vec::new(),
+            }],
+        }
+    }
+
+    pub fn matches(&self, version: &SemVer) -> bool {
+        self.alternatives.iter().any(|req| req.matches(version))
+    }
+
+    /// The candidate with the highest matching version, the way Cargo and
+    /// npm resolve a dependency. Candidates whose version isn't valid semver
+    /// are ignored.
+    pub fn best_match<'a, T>(
+        &self,
+        candidates: &'a [T],
+        version_of: impl Fn(&T) -> &str,
+    ) -> Option<&'a T> {
+        candidates
+            .iter()
+            .filter_map(|c| SemVer::parse(version_of(c)).map(|v| (v, c)))
+            .filter(|(v, _)| self.matches(v))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, c)| c)
+    }
+}
+
+impl std::fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self.alternatives.iter().map(|r| r.to_string()).collect();
+        write!(f, "{}", parts.join(" || "))
+    }
+}
+
+impl TryFrom<String> for VersionConstraint {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        VersionConstraint::parse(&s).ok_or_else(|| format!("invalid version constraint: {}", s))
+    }
+}
+
+impl From<VersionConstraint> for String {
+    fn from(constraint: VersionConstraint) -> Self {
+        constraint.to_string()
     }
 }
 
@@ -404,6 +812,155 @@ mod tests {
         assert!(!constraint.matches(&v));
     }
 
+    #[test]
+    fn test_comparison_constraints() {
+        let v = |s: &str| SemVer::parse(s).unwrap();
+
+        let gte = VersionConstraint::parse(">= 1.2.3").unwrap();
+        assert!(gte.matches(&v("1.2.3")));
+        assert!(gte.matches(&v("3.0.0")));
+        assert!(!gte.matches(&v("1.2.2")));
+
+        let gt = VersionConstraint::parse(">1.2.3").unwrap();
+        assert!(!gt.matches(&v("1.2.3")));
+        assert!(gt.matches(&v("1.2.4")));
+
+        let lt = VersionConstraint::parse("< 2.0.0").unwrap();
+        assert!(lt.matches(&v("1.9.9")));
+        assert!(!lt.matches(&v("2.0.0")));
+
+        let lte = VersionConstraint::parse("<=2.0.0").unwrap();
+        assert!(lte.matches(&v("2.0.0")));
+
+        let eq = VersionConstraint::parse("= 1.0.0").unwrap();
+        assert!(eq.matches(&v("1.0.0")));
+        assert!(!eq.matches(&v("1.0.1")));
+
+        assert!(VersionConstraint::parse(">= ").is_none());
+    }
+
+    #[test]
+    fn test_version_req_intersection() {
+        let v = |s: &str| SemVer::parse(s).unwrap();
+        let req = VersionReq::parse(">= 0.9.3, < 0.10.0").unwrap();
+        assert!(req.matches(&v("0.9.3")));
+        assert!(req.matches(&v("0.9.20")));
+        assert!(!req.matches(&v("0.10.0")));
+        assert!(!req.matches(&v("0.9.2")));
+        assert_eq!(req.to_string(), ">=0.9.3, <0.10.0");
+
+        assert!(VersionReq::parse("^1.2.3").unwrap().matches(&v("1.9.0")));
+        assert!(VersionReq::parse(">= 1.0.0,").is_none());
+    }
+
+    #[test]
+    fn test_partial_versions_and_wildcards() {
+        let c = |s: &str| VersionConstraint::parse(s).unwrap();
+        let v = |s: &str| SemVer::parse(s).unwrap();
+
+        let req = c(">=1.2, <2");
+        assert!(req.matches(&v("1.2.0")));
+        assert!(req.matches(&v("1.99.3")));
+        assert!(!req.matches(&v("1.1.9")));
+        assert!(!req.matches(&v("2.0.0")));
+
+        assert!(c(">1.2").matches(&v("1.3.0")));
+        assert!(!c(">1.2").matches(&v("1.2.9")));
+        assert!(c("<=1.2").matches(&v("1.2.9")));
+        assert!(!c("<=1.2").matches(&v("1.3.0")));
+        assert!(c("^1.2").matches(&v("1.9.0")));
+        assert!(!c("^0.2").matches(&v("0.3.0")));
+        assert!(c("~1").matches(&v("1.5.0")));
+
+        for wildcard in ["1.*", "1.x", "1", "=1"] {
+            assert!(c(wildcard).matches(&v("1.4.2")), "{}", wildcard);
+            assert!(!c(wildcard).matches(&v("2.0.0")), "{}", wildcard);
+        }
+        assert!(c("1.2.*").matches(&v("1.2.7")));
+        assert!(!c("1.2.*").matches(&v("1.3.0")));
+        assert!(c("*").matches(&v("42.0.0")));
+
+        assert_eq!(c(">= 1.2, < 2").to_string(), ">=1.2, <2");
+        assert_eq!(c("1.x").to_string(), "1.*");
+    }
+
+    #[test]
+    fn test_hyphen_ranges_and_unions() {
+        let c = |s: &str| VersionConstraint::parse(s).unwrap();
+        let v = |s: &str| SemVer::parse(s).unwrap();
+
+        let hyphen = c("1.2 - 1.4");
+        assert!(hyphen.matches(&v("1.2.0")));
+        assert!(hyphen.matches(&v("1.4.9")));
+        assert!(!hyphen.matches(&v("1.5.0")));
+        assert!(!c("1.2.3 - 1.4.0").matches(&v("1.4.1")));
+
+        let union = c("^1.4 || >=2.1.0 <3");
+        assert!(union.matches(&v("1.4.0")));
+        assert!(!union.matches(&v("2.0.5")));
+        assert!(union.matches(&v("2.1.0")));
+        assert!(!union.matches(&v("3.0.0")));
+        assert_eq!(union.to_string(), "^1.4 || >=2.1.0, <3");
+
+        for invalid in [
+            "", "||", "^1 ||", "1.*.3", "1.2-beta", ">", ">*", "1 - >2", "abc",
+        ] {
+            assert!(VersionConstraint::parse(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_pre_release_matching() {
+        let c = |s: &str| VersionConstraint::parse(s).unwrap();
+        let v = |s: &str| SemVer::parse(s).unwrap();
+
+        // Pre-releases only match comparators naming the same release.
+        assert!(!c("^1.2.3").matches(&v("1.3.0-alpha")));
+        assert!(!c(">=1.0.0").matches(&v("2.0.0-rc.1")));
+        assert!(!c("*").matches(&v("1.0.0-beta")));
+        assert!(c(">=1.0.0-beta").matches(&v("1.0.0-rc.1")));
+        assert!(c(">=1.0.0-beta").matches(&v("1.0.0")));
+        assert!(c(">=1.0.0-beta").matches(&v("1.1.0")));
+        assert!(!c(">=1.0.0-beta").matches(&v("1.0.0-alpha")));
+        assert!(!c(">=1.0.0-beta").matches(&v("1.1.0-alpha")));
+        assert!(c("=1.0.0-beta.2").matches(&v("1.0.0-beta.2")));
+        assert!(c("~1.2.3-beta.2").matches(&v("1.2.3-beta.11")));
+        assert!(!c("<1.0.0").matches(&v("1.0.0-rc.1")));
+        // Build metadata is ignored.
+        assert!(c("=1.0.0+build.7").matches(&v("1.0.0+other")));
+    }
+
+    #[test]
+    fn test_best_match_picks_highest_version() {
+        let published = [
+            "1.0.0",
+            "1.4.2",
+            "1.10.0",
+            "2.0.0-beta.1",
+            "2.0.0",
+            "not-a-version",
+        ];
+        let best = |s: &str| {
+            VersionConstraint::parse(s)
+                .unwrap()
+                .best_match(&published, |v| v)
+                .copied()
+        };
+        assert_eq!(best("^1.2"), Some("1.10.0"));
+        assert_eq!(best("1.4.*"), Some("1.4.2"));
+        assert_eq!(best("*"), Some("2.0.0"));
+        assert_eq!(best(">=2.0.0-beta, <2.0.0"), Some("2.0.0-beta.1"));
+        assert_eq!(best("^3"), None);
+    }
+
+    #[test]
+    fn test_constraints_serialize_as_strings() {
+        let c: VersionConstraint = serde_json::from_str("\"^1.2 || ~2.0.1\"").unwrap();
+        assert_eq!(c.alternatives.len(), 2);
+        assert_eq!(serde_json::to_string(&c).unwrap(), "\"^1.2 || ~2.0.1\"");
+        assert!(serde_json::from_str::<VersionConstraint>("\"^^1\"").is_err());
+    }
+
     #[test]
     fn test_invalid_versions() {
         assert!(SemVer::parse("1.0").is_none());
@@ -0,0 +1,347 @@
+//! RFC 6962 Merkle tree over the signing transparency log.
+//!
+//! Leaves are the canonical JSON of a [`LogLeaf`], hashed with a `0x00`
+//! prefix; interior nodes hash their children with a `0x01` prefix. The
+//! registry periodically signs a [`TreeHead`] over the current root, and
+//! clients check inclusion and consistency proofs against a signed head
+//! without trusting the server's copy of the log.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub type Hash = [u8; 32];
+
+/// Prefix of the message a tree head signature covers
+const TREE_HEAD_CONTEXT: &str = "soroban-registry-transparency-log/v1";
+
+/// One sequenced log entry, as committed to by its leaf hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogLeaf {
+    pub entry_id: Uuid,
+    pub entry_type: String,
+    pub contract_id: Option<Uuid>,
+    pub signature_id: Option<Uuid>,
+    pub actor_address: String,
+    pub entry_hash: String,
+    /// Signed package fields, for `package_signed` entries
+    pub version: Option<String>,
+    pub wasm_hash: Option<String>,
+    pub signature: Option<String>,
+    pub public_key: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl LogLeaf {
+    /// Canonical leaf bytes. Stored verbatim so proofs never depend on
+    /// re-serialising the entry.
+    pub fn to_leaf_data(&self) -> String {
+        serde_json::to_string(self).expect("LogLeaf serialises")
+    }
+}
+
+/// A signed commitment to the first `tree_size` leaves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeHead {
+    pub tree_size: u64,
+    /// Hex-encoded Merkle root
+    pub root_hash: String,
+    pub timestamp: DateTime<Utc>,
+    /// Base64 Ed25519 signature over [`TreeHead::signing_message`]
+    pub signature: String,
+    /// Hex SHA-256 of the log's public key
+    pub key_id: String,
+}
+
+impl TreeHead {
+    pub fn signing_message(tree_size: u64, root_hash: &str, timestamp: DateTime<Utc>) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}\n{}\n",
+            TREE_HEAD_CONTEXT,
+            tree_size,
+            timestamp.timestamp_millis(),
+            root_hash
+        )
+        .into_bytes()
+    }
+}
+
+/// Everything needed to check one entry's inclusion offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub leaf_index: u64,
+    /// Canonical [`LogLeaf`] JSON
+    pub leaf_data: String,
+    /// Hex-encoded sibling hashes, leaf to root
+    pub audit_path: Vec<String>,
+    pub tree_head: TreeHead,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    pub first: TreeHead,
+    pub second: TreeHead,
+    /// Hex-encoded hashes per RFC 6962 §2.1.2
+    pub proof: Vec<String>,
+}
+
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+pub fn key_id(public_key: &[u8]) -> String {
+    hex::encode(Sha256::digest(public_key))
+}
+
+pub fn encode_hash(hash: &Hash) -> String {
+    hex::encode(hash)
+}
+
+pub fn decode_hash(hex_hash: &str) -> Option<Hash> {
+    hex::decode(hex_hash).ok()?.try_into().ok()
+}
+
+/// Largest power of two strictly less than `n` (n ≥ 2).
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+/// Merkle tree hash of `leaves`, which are already leaf hashes.
+pub fn root(leaves: &[Hash]) -> Hash {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n);
+            node_hash(&root(&leaves[..k]), &root(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path for leaf `index` in the tree over `leaves`.
+pub fn inclusion_path(index: usize, leaves: &[Hash]) -> Vec<Hash> {
+    let n = leaves.len();
+    if n <= 1 || index >= n {
+        return Vec::new();
+    }
+    let k = split_point(n);
+    let (mut path, sibling) = if index < k {
+        (inclusion_path(index, &leaves[..k]), root(&leaves[k..]))
+    } else {
+        (inclusion_path(index - k, &leaves[k..]), root(&leaves[..k]))
+    };
+    path.push(sibling);
+    path
+}
+
+/// Proof that the tree over `leaves[..first]` is a prefix of the tree over
+/// `leaves`.
+pub fn consistency_proof(first: usize, leaves: &[Hash]) -> Vec<Hash> {
+    if first == 0 || first >= leaves.len() {
+        return Vec::new();
+    }
+    subproof(first, leaves, true)
+}
+
+fn subproof(m: usize, leaves: &[Hash], complete: bool) -> Vec<Hash> {
+    let n = leaves.len();
+    if m == n {
+        return if complete {
+            Vec::new()
+        } else {
+            vec![root(leaves)]
+        };
+    }
+    let k = split_point(n);
+    let (mut proof, sibling) = if m <= k {
+        (subproof(m, &leaves[..k], complete), root(&leaves[k..]))
+    } else {
+        (subproof(m - k, &leaves[k..], false), root(&leaves[..k]))
+    };
+    proof.push(sibling);
+    proof
+}
+
+/// RFC 9162 §2.1.3.2.
+pub fn verify_inclusion(
+    leaf: &Hash,
+    index: u64,
+    tree_size: u64,
+    path: &[Hash],
+    expected_root: &Hash,
+) -> bool {
+    if index >= tree_size {
+        return false;
+    }
+    let (mut fn_, mut sn) = (index, tree_size - 1);
+    let mut r = *leaf;
+    for p in path {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            r = node_hash(p, &r);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            r = node_hash(&r, p);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && r == *expected_root
+}
+
+/// RFC 9162 §2.1.4.2.
+pub fn verify_consistency(
+    first: u64,
+    second: u64,
+    proof: &[Hash],
+    first_root: &Hash,
+    second_root: &Hash,
+) -> bool {
+    if first > second {
+        return false;
+    }
+    if first == second {
+        return proof.is_empty() && first_root == second_root;
+    }
+    if first == 0 {
+        return proof.is_empty();
+    }
+    if proof.is_empty() {
+        return false;
+    }
+
+    let mut nodes = Vec::with_capacity(proof.len() + 1);
+    if first.is_power_of_two() {
+        nodes.push(*first_root);
+    }
+    nodes.extend_from_slice(proof);
+
+    let (mut fn_, mut sn) = (first - 1, second - 1);
+    while fn_ & 1 == 1 {
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    let (mut fr, mut sr) = (nodes[0], nodes[0]);
+    for c in &nodes[1..] {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            fr = node_hash(c, &fr);
+            sr = node_hash(c, &sr);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            sr = node_hash(&sr, c);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && fr == *first_root && sr == *second_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<Hash> {
+        (0..n)
+            .map(|i| leaf_hash(format!("entry-{i}").as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn empty_and_single_leaf_roots() {
+        assert_eq!(
+            encode_hash(&root(&[])),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        let one = leaves(1);
+        assert_eq!(root(&one), one[0]);
+    }
+
+    #[test]
+    fn every_inclusion_proof_verifies() {
+        for n in 1..=17 {
+            let tree = leaves(n);
+            let r = root(&tree);
+            for i in 0..n {
+                let path = inclusion_path(i, &tree);
+                assert!(
+                    verify_inclusion(&tree[i], i as u64, n as u64, &path, &r),
+                    "n={n} i={i}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inclusion_rejects_wrong_leaf_index_or_root() {
+        let tree = leaves(7);
+        let r = root(&tree);
+        let path = inclusion_path(3, &tree);
+        assert!(!verify_inclusion(&tree[4], 3, 7, &path, &r));
+        assert!(!verify_inclusion(&tree[3], 2, 7, &path, &r));
+        assert!(!verify_inclusion(&tree[3], 3, 7, &path, &root(&tree[..6])));
+        assert!(!verify_inclusion(&tree[3], 7, 7, &path, &r));
+    }
+
+    #[test]
+    fn every_consistency_proof_verifies() {
+        let tree = leaves(17);
+        for second in 1..=17 {
+            let second_root = root(&tree[..second]);
+            for first in 1..=second {
+                let proof = consistency_proof(first, &tree[..second]);
+                assert!(
+                    verify_consistency(
+                        first as u64,
+                        second as u64,
+                        &proof,
+                        &root(&tree[..first]),
+                        &second_root
+                    ),
+                    "{first} -> {second}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn consistency_detects_rewritten_history() {
+        let tree = leaves(10);
+        let mut rewritten = tree.clone();
+        rewritten[2] = leaf_hash(b"tampered");
+        let proof = consistency_proof(4, &rewritten);
+        assert!(!verify_consistency(
+            4,
+            10,
+            &proof,
+            &root(&tree[..4]),
+            &root(&rewritten)
+        ));
+    }
+}
@@ -0,0 +1,627 @@
+// Compatibility fixture runner
+// Rebuilds a contract against a pinned soroban-sdk version and executes its
+// recorded fixtures in a generated test crate that links that SDK's
+// `testutils`, so every call runs on the host (and protocol) the SDK ships.
+
+use serde::{Deserialize, Serialize};
+use shared::RegistryError;
+use std::{fmt::Write as _, fs, path::Path, process::Stdio, time::Duration};
+use tempfile::TempDir;
+use tokio::{process::Command, time::timeout};
+
+use crate::{compile_contract, truncate_for_error};
+
+const HARNESS_TIMEOUT: Duration = Duration::from_secs(600);
+const LOAD_TEST: &str = "contract_loads";
+const MAX_OUTPUT_LEN: usize = 64 * 1024;
+
+/// A typed argument or return value. Serialized as `{"type": "u32", "value": 5}`;
+/// 128-bit integers are decimal strings, bytes are hex, and addresses are
+/// labels that map to one generated address per label within a fixture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum FixtureValue {
+    Void,
+    Bool(bool),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    U128(String),
+    I128(String),
+    Symbol(String),
+    String(String),
+    Bytes(String),
+    Address(String),
+}
+
+/// One recorded contract call and what it should produce.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub name: String,
+    pub function: String,
+    #[serde(default)]
+    pub args: Vec<FixtureValue>,
+    /// Expected return value; when absent only success is checked
+    #[serde(default)]
+    pub expected: Option<FixtureValue>,
+    /// The call must fail
+    #[serde(default)]
+    pub expect_error: bool,
+    /// With `expect_error`, the contract error code it must fail with
+    #[serde(default)]
+    pub error_code: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureOutcome {
+    pub name: String,
+    pub passed: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompatibilityReport {
+    /// Compiler output when the contract does not build against the SDK
+    pub build_error: Option<String>,
+    /// Why the host rejected the contract's wasm, if it did
+    pub load_error: Option<String>,
+    pub fixtures: Vec<FixtureOutcome>,
+    /// Raw harness output
+    pub output: String,
+}
+
+impl CompatibilityReport {
+    pub fn passed(&self) -> usize {
+        self.fixtures.iter().filter(|f| f.passed).count()
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.build_error.is_none() && self.load_error.is_none() && self.fixtures.iter().all(|f| f.passed)
+    }
+}
+
+fn is_symbol(value: &str) -> bool {
+    (1..=32).contains(&value.len()) && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl FixtureValue {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            FixtureValue::U128(v) => v
+                .parse::<u128>()
+                .map(|_| ())
+                .map_err(|_| format!("'{}' is not a u128", v)),
+            FixtureValue::I128(v) => v
+                .parse::<i128>()
+                .map(|_| ())
+                .map_err(|_| format!("'{}' is not an i128", v)),
+            FixtureValue::Symbol(v) if !is_symbol(v) => Err(format!(
+                "'{}' is not a symbol (1-32 characters of [A-Za-z0-9_])",
+                v
+            )),
+            FixtureValue::Bytes(v) => hex::decode(v)
+                .map(|_| ())
+                .map_err(|_| format!("'{}' is not hex", v)),
+            FixtureValue::Address(label) if label.is_empty() => {
+                Err("address labels cannot be empty".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn rust_type(&self) -> &'static str {
+        match self {
+            FixtureValue::Void => "()",
+            FixtureValue::Bool(_) => "bool",
+            FixtureValue::U32(_) => "u32",
+            FixtureValue::I32(_) => "i32",
+            FixtureValue::U64(_) => "u64",
+            FixtureValue::I64(_) => "i64",
+            FixtureValue::U128(_) => "u128",
+            FixtureValue::I128(_) => "i128",
+            FixtureValue::Symbol(_) => "Symbol",
+            FixtureValue::String(_) => "String",
+            FixtureValue::Bytes(_) => "Bytes",
+            FixtureValue::Address(_) => "Address",
+        }
+    }
+
+    /// Rust expression for the value. Strings go through `{:?}`, which always
+    /// yields a valid literal; everything else is validated first.
+    fn rust_expr(&self, addresses: &mut Vec<String>) -> String {
+        match self {
+            FixtureValue::Void => "()".to_string(),
+            FixtureValue::Bool(v) => v.to_string(),
+            FixtureValue::U32(v) => format!("{}u32", v),
+            FixtureValue::I32(v) => format!("({}i32)", v),
+            FixtureValue::U64(v) => format!("{}u64", v),
+            FixtureValue::I64(v) => format!("({}i64)", v),
+            FixtureValue::U128(v) => format!("{}u128", v),
+            FixtureValue::I128(v) => format!("({}i128)", v),
+            FixtureValue::Symbol(v) => format!("Symbol::new(&env, {:?})", v),
+            FixtureValue::String(v) => format!("String::from_str(&env, {:?})", v),
+            FixtureValue::Bytes(v) => format!(
+                "Bytes::from_slice(&env, &{:?})",
+                hex::decode(v).unwrap_or_default()
+            ),
+            FixtureValue::Address(label) => {
+                let index = match addresses.iter().position(|a| a == label) {
+                    Some(index) => index,
+                    None => {
+                        addresses.push(label.clone());
+                        addresses.len() - 1
+                    }
+                };
+                format!("addresses[{}].clone()", index)
+            }
+        }
+    }
+}
+
+impl Fixture {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("fixture name cannot be empty".to_string());
+        }
+        if !is_symbol(&self.function) {
+            return Err(format!(
+                "'{}' is not a valid contract function name",
+                self.function
+            ));
+        }
+        if self.expect_error && self.expected.is_some() {
+            return Err("expected and expect_error are mutually exclusive".to_string());
+        }
+        if self.error_code.is_some() && !self.expect_error {
+            return Err("error_code requires expect_error".to_string());
+        }
+        self.args
+            .iter()
+            .chain(self.expected.iter())
+            .try_for_each(FixtureValue::validate)
+    }
+}
+
+/// `soroban-sdk` requirement pinning exactly `sdk_version` unless it is
+/// already a requirement such as `=21.7.7` or `~22`.
+pub fn exact_requirement(sdk_version: &str) -> String {
+    let version = sdk_version.trim();
+    if version.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("={}", version)
+    } else {
+        version.to_string()
+    }
+}
+
+fn sdk_major(sdk_version: &str) -> u32 {
+    sdk_version
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .split('.')
+        .next()
+        .and_then(|major| major.parse().ok())
+        .unwrap_or(0)
+}
+
+fn test_name(index: usize) -> String {
+    format!("fixture_{:03}", index)
+}
+
+/// Generate the harness crate's `src/lib.rs`.
+pub fn render_harness(sdk_version: &str, fixtures: &[Fixture]) -> String {
+    // SDK 22 replaced `register_contract_wasm` with `register`.
+    let register = if sdk_major(sdk_version) >= 22 {
+        "env.register(WASM, ())"
+    } else {
+        "env.register_contract_wasm(None, WASM)"
+    };
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        r#"#![cfg(test)]
+#![allow(deprecated, unused_imports, clippy::all)]
+// Generated by the registry compatibility runner; do not edit.
+
+use soroban_sdk::{{testutils::Address as _, Address, Bytes, Env, IntoVal, String, Symbol, Val}};
+
+const WASM: &[u8] = include_bytes!("../contract.wasm");
+
+fn setup() -> (Env, Address) {{
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract = {register};
+    (env, contract)
+}}
+
+#[test]
+fn {LOAD_TEST}() {{
+    setup();
+}}
+"#
+    );
+
+    for (index, fixture) in fixtures.iter().enumerate() {
+        let mut addresses = Vec::new();
+        let args: Vec<String> = fixture
+            .args
+            .iter()
+            .map(|arg| format!("{}.into_val(&env)", arg.rust_expr(&mut addresses)))
+            .collect();
+        let expected = fixture
+            .expected
+            .as_ref()
+            .map(|value| (value.rust_type(), value.rust_expr(&mut addresses)));
+
+        let _ = writeln!(out, "\n// {:?}", fixture.name);
+        let _ = writeln!(out, "#[test]\nfn {}() {{", test_name(index));
+        let _ = writeln!(out, "    let (env, contract) = setup();");
+        let _ = writeln!(
+            out,
+            "    let addresses: [Address; {}] = [{}];",
+            addresses.len(),
+            vec!["Address::generate(&env)"; addresses.len()].join(", ")
+        );
+        let _ = writeln!(
+            out,
+            "    let args: soroban_sdk::Vec<Val> = soroban_sdk::vec![&env{}];",
+            args.iter().map(|a| format!(", {}", a)).collect::<String>()
+        );
+        let _ = writeln!(
+            out,
+            "    let func = Symbol::new(&env, {:?});",
+            fixture.function
+        );
+
+        if fixture.expect_error {
+            let _ = writeln!(
+                out,
+                "    let result = env.try_invoke_contract::<Val, soroban_sdk::Error>(&contract, &func, args);"
+            );
+            match fixture.error_code {
+                Some(code) => {
+                    let _ = writeln!(
+                        out,
+                        "    assert!(matches!(result, Err(Ok(e)) if e == soroban_sdk::Error::from_contract_error({code})), \"expected contract error #{code}, got {{:?}}\", result);"
+                    );
+                }
+                None => {
+                    let _ = writeln!(
+                        out,
+                        "    assert!(result.is_err(), \"expected the call to fail, got {{:?}}\", result);"
+                    );
+                }
+            }
+        } else if let Some((ty, expr)) = expected {
+            let _ = writeln!(
+                out,
+                "    let got: {} = env.invoke_contract(&contract, &func, args);",
+                ty
+            );
+            let _ = writeln!(out, "    assert_eq!(got, {});", expr);
+        } else {
+            let _ = writeln!(
+                out,
+                "    let _: Val = env.invoke_contract(&contract, &func, args);"
+            );
+        }
+        let _ = writeln!(out, "}}");
+    }
+
+    out
+}
+
+/// Per-test results and failure messages parsed from libtest output.
+#[derive(Debug, Default)]
+struct TestRun {
+    results: Vec<(String, bool)>,
+    failures: Vec<(String, String)>,
+}
+
+fn parse_test_output(output: &str) -> TestRun {
+    let mut results = Vec::new();
+    let mut failures: Vec<(String, String)> = Vec::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for line in output.lines() {
+        if let Some(rest) = line.strip_prefix("test ") {
+            if let Some((name, status)) = rest.rsplit_once(" ... ") {
+                match status.trim() {
+                    "ok" => results.push((name.to_string(), true)),
+                    "FAILED" => results.push((name.to_string(), false)),
+                    _ => {}
+                }
+                continue;
+            }
+        }
+
+        let header = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"));
+        if header.is_some() || line == "failures:" {
+            if let Some((name, lines)) = current.take() {
+                failures.push((name, lines.join("\n").trim().to_string()));
+            }
+            current = header.map(|name| (name.to_string(), Vec::new()));
+        } else if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    if let Some((name, lines)) = current {
+        failures.push((name, lines.join("\n").trim().to_string()));
+    }
+
+    TestRun { results, failures }
+}
+
+fn unknown_sdk_version(error: &str) -> bool {
+    error.contains("failed to select a version for the requirement `soroban-sdk")
+}
+
+/// Rebuild `source_code` against `sdk_version` with the verifier's build
+/// pipeline, then execute `fixtures` against the result.
+///
+/// `Err` means the run itself could not happen (unknown SDK version, missing
+/// toolchain, timeout); a contract that fails to build or to pass its
+/// fixtures is reported in the `Ok` value.
+pub async fn run_compatibility(
+    source_code: &str,
+    build_params: Option<&serde_json::Value>,
+    sdk_version: &str,
+    fixtures: &[Fixture],
+    target_dir: Option<&Path>,
+) -> Result<CompatibilityReport, RegistryError> {
+    if let Some(invalid) = fixtures.iter().find_map(|f| f.validate().err()) {
+        return Err(RegistryError::InvalidInput(invalid));
+    }
+    let requirement = exact_requirement(sdk_version);
+
+    let wasm = match compile_contract(source_code, Some(&requirement), build_params).await {
+        Ok(wasm) => wasm,
+        Err(RegistryError::VerificationFailed(details)) if unknown_sdk_version(&details) => {
+            return Err(RegistryError::InvalidInput(format!(
+                "soroban-sdk {} does not exist",
+                sdk_version
+            )));
+        }
+        Err(RegistryError::VerificationFailed(details)) => {
+            return Ok(CompatibilityReport {
+                build_error: Some(details.clone()),
+                load_error: None,
+                fixtures: Vec::new(),
+                output: details,
+            });
+        }
+        Err(e) => return Err(e),
+    };
+
+    let temp_dir = TempDir::new()
+        .map_err(|e| RegistryError::Internal(format!("Failed to create temp dir: {}", e)))?;
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("src"))
+        .map_err(|e| RegistryError::Internal(format!("Failed to create src dir: {}", e)))?;
+    let cargo_toml = format!(
+        "[package]\nname = \"compat_fixtures\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nsoroban-sdk = {{ version = \"{}\", features = [\"testutils\"] }}\n",
+        requirement
+    );
+    fs::write(root.join("Cargo.toml"), cargo_toml)
+        .and_then(|_| fs::write(root.join("contract.wasm"), &wasm))
+        .and_then(|_| {
+            fs::write(
+                root.join("src").join("lib.rs"),
+                render_harness(sdk_version, fixtures),
+            )
+        })
+        .map_err(|e| RegistryError::Internal(format!("Failed to write harness: {}", e)))?;
+
+    let mut command = Command::new("cargo");
+    command
+        .arg("test")
+        .arg("--lib")
+        .arg("--no-fail-fast")
+        .arg("--")
+        .arg("--test-threads=1")
+        .current_dir(root)
+        .env("RUST_BACKTRACE", "0")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = target_dir {
+        command.env("CARGO_TARGET_DIR", dir);
+    }
+
+    let output = timeout(HARNESS_TIMEOUT, command.output())
+        .await
+        .map_err(|_| RegistryError::Internal("Fixture run timed out".to_string()))?
+        .map_err(|e| RegistryError::Internal(format!("Failed to execute cargo test: {}", e)))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let run = parse_test_output(&stdout);
+
+    // No test lines at all means the harness never ran. Precompiled
+    // sources skip the contract build, so the SDK is first resolved here.
+    if run.results.is_empty() {
+        if unknown_sdk_version(&stderr) {
+            return Err(RegistryError::InvalidInput(format!(
+                "soroban-sdk {} does not exist",
+                sdk_version
+            )));
+        }
+        return Err(RegistryError::Internal(format!(
+            "Fixture harness failed to build: {}",
+            truncate_for_error(&stderr)
+        )));
+    }
+
+    let outcome = |test: &str| -> (bool, Option<String>) {
+        let passed = run
+            .results
+            .iter()
+            .find(|(name, _)| name == test)
+            .map(|(_, passed)| *passed);
+        let message = run
+            .failures
+            .iter()
+            .find(|(name, _)| name == test)
+            .map(|(_, message)| message.clone());
+        match passed {
+            Some(passed) => (passed, message),
+            None => (false, Some("no result reported".to_string())),
+        }
+    };
+
+    let load_error = match outcome(LOAD_TEST) {
+        (true, _) => None,
+        (false, message) => Some(message.unwrap_or_default()),
+    };
+    let fixtures = fixtures
+        .iter()
+        .enumerate()
+        .map(|(index, fixture)| {
+            let (passed, message) = outcome(&test_name(index));
+            FixtureOutcome {
+                name: fixture.name.clone(),
+                passed,
+                message,
+            }
+        })
+        .collect();
+
+    let mut output = stdout.into_owned();
+    if output.len() > MAX_OUTPUT_LEN {
+        let mut end = MAX_OUTPUT_LEN;
+        while !output.is_char_boundary(end) {
+            end -= 1;
+        }
+        output.truncate(end);
+        output.push_str("\n...[truncated]");
+    }
+
+    Ok(CompatibilityReport {
+        build_error: None,
+        load_error,
+        fixtures,
+        output,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(function: &str, args: Vec<FixtureValue>) -> Fixture {
+        Fixture {
+            name: function.to_string(),
+            function: function.to_string(),
+            args,
+            expected: None,
+            expect_error: false,
+            error_code: None,
+        }
+    }
+
+    #[test]
+    fn values_deserialize_from_tagged_json() {
+        let values: Vec<FixtureValue> = serde_json::from_str(
+            r#"[{"type":"void"},{"type":"u32","value":7},{"type":"i128","value":"-5"},{"type":"address","value":"alice"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                FixtureValue::Void,
+                FixtureValue::U32(7),
+                FixtureValue::I128("-5".into()),
+                FixtureValue::Address("alice".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unrenderable_values() {
+        assert!(fixture("transfer", vec![]).validate().is_ok());
+        assert!(fixture("bad-name", vec![]).validate().is_err());
+        assert!(fixture("f", vec![FixtureValue::I128("1e9".into())])
+            .validate()
+            .is_err());
+        assert!(fixture("f", vec![FixtureValue::Bytes("zz".into())])
+            .validate()
+            .is_err());
+        assert!(fixture("f", vec![FixtureValue::Symbol("a b".into())])
+            .validate()
+            .is_err());
+
+        let mut conflicting = fixture("f", vec![]);
+        conflicting.expected = Some(FixtureValue::Bool(true));
+        conflicting.expect_error = true;
+        assert!(conflicting.validate().is_err());
+    }
+
+    #[test]
+    fn harness_shares_addresses_by_label_and_picks_register_api() {
+        let mut transfer = fixture(
+            "transfer",
+            vec![
+                FixtureValue::Address("alice".into()),
+                FixtureValue::Address("bob".into()),
+                FixtureValue::I128("100".into()),
+            ],
+        );
+        transfer.expected = Some(FixtureValue::Address("alice".into()));
+        let mut fails = fixture("burn", vec![FixtureValue::String("x\"y".into())]);
+        fails.expect_error = true;
+        fails.error_code = Some(3);
+
+        let harness = render_harness("21.7.7", &[transfer, fails]);
+        assert!(harness.contains("register_contract_wasm(None, WASM)"));
+        assert!(harness.contains("let addresses: [Address; 2]"));
+        assert!(harness.contains("let got: Address ="));
+        assert!(harness.contains("assert_eq!(got, addresses[0].clone());"));
+        assert!(harness.contains(r#"String::from_str(&env, "x\"y")"#));
+        assert!(harness.contains("from_contract_error(3)"));
+
+        assert!(render_harness("22.0.1", &[]).contains("env.register(WASM, ())"));
+    }
+
+    #[test]
+    fn parses_libtest_results_and_failures() {
+        let output = "\
+running 3 tests
+test contract_loads ... ok
+test fixture_000 ... ok
+test fixture_001 ... FAILED
+
+failures:
+
+---- fixture_001 stdout ----
+thread 'fixture_001' panicked at src/lib.rs:30:5:
+assertion `left == right` failed
+
+failures:
+    fixture_001
+
+test result: FAILED. 2 passed; 1 failed";
+        let run = parse_test_output(output);
+        assert_eq!(
+            run.results,
+            vec![
+                ("contract_loads".to_string(), true),
+                ("fixture_000".to_string(), true),
+                ("fixture_001".to_string(), false),
+            ]
+        );
+        assert_eq!(run.failures.len(), 1);
+        assert_eq!(run.failures[0].0, "fixture_001");
+        assert!(run.failures[0]
+            .1
+            .contains("assertion `left == right` failed"));
+    }
+
+    #[test]
+    fn pins_plain_versions_exactly() {
+        assert_eq!(exact_requirement("21.7.7"), "=21.7.7");
+        assert_eq!(exact_requirement("~22.0"), "~22.0");
+        assert_eq!(sdk_major("=22.0.1"), 22);
+    }
+}
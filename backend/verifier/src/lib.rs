@@ -9,6 +9,8 @@ use std::{fs, process::Stdio, time::Duration};
 use tempfile::TempDir;
 use tokio::{process::Command, time::timeout};
 
+pub mod fixtures;
+
 const DEFAULT_SOROBAN_SDK_VERSION: &str = "21.7.7";
 const BUILD_TIMEOUT: Duration = Duration::from_secs(120);
 
@@ -92,7 +94,9 @@ pub async fn compile_contract(
         .arg("wasm32-unknown-unknown")
         .current_dir(temp_dir.path())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+        .stderr(Stdio::piped())
+        // A timed-out or cancelled build must not leave cargo running.
+        .kill_on_drop(true);
 
     if let Some(params) = build_params {
         apply_build_params(&mut command, params);
@@ -189,7 +193,7 @@ pub fn normalize_hash(value: &str) -> Option<String> {
     Some(stripped.to_ascii_lowercase())
 }
 
-fn truncate_for_error(value: &str) -> String {
+pub(crate) fn truncate_for_error(value: &str) -> String {
     const MAX_ERROR_LEN: usize = 1_000;
     if value.len() <= MAX_ERROR_LEN {
         return value.to_string();
@@ -0,0 +1,342 @@
+//! Pushing and pulling contract artifacts.
+//!
+//! The registry stores each version's WASM binary and source tarball under
+//! their SHA-256. `push` uploads them to an existing version (the WASM must
+//! match the version's registered `wasm_hash`), each signed with the
+//! publisher's key over `{contract_id}:{version}:{sha256}`; `pull` downloads
+//! them and checks the bytes against the hash the registry reports.
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use colored::Colorize;
+use ed25519_dalek::{Signer, SigningKey};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tar::Builder;
+
+/// Directories never worth shipping as contract source.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", ".git"];
+
+#[derive(Debug, Deserialize)]
+struct UploadResponse {
+    sha256: String,
+    size_bytes: i64,
+    deduplicated: bool,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Signature the registry checks against the version's `publisher_key`.
+fn upload_signature(key: &SigningKey, contract_id: &str, version: &str, sha256: &str) -> String {
+    let message = format!("{}:{}:{}", contract_id, version, sha256);
+    BASE64.encode(key.sign(message.as_bytes()).to_bytes())
+}
+
+/// The on-chain contract ID that upload signatures cover; `id` may be the
+/// registry UUID instead.
+async fn on_chain_id(client: &reqwest::Client, api_url: &str, id: &str) -> Result<String> {
+    if uuid::Uuid::parse_str(id).is_err() {
+        return Ok(id.to_string());
+    }
+    let contract: serde_json::Value = client
+        .get(format!("{}/api/contracts/{}", api_url, id))
+        .send()
+        .await
+        .context("Failed to look up contract")?
+        .error_for_status()
+        .context("Failed to look up contract")?
+        .json()
+        .await
+        .context("Failed to parse contract")?;
+    contract["contract_id"]
+        .as_str()
+        .map(str::to_string)
+        .context("Registry response has no contract_id")
+}
+
+fn artifact_url(api_url: &str, contract_id: &str, version: &str, file: &str) -> String {
+    format!(
+        "{}/api/contracts/{}/versions/{}/{}",
+        api_url, contract_id, version, file
+    )
+}
+
+/// Pack `dir` as a `.tar.gz` with sorted entries and zeroed timestamps, so
+/// the same tree always produces the same bytes (and the same hash).
+pub fn pack_source(dir: &Path) -> Result<Vec<u8>> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.sort();
+
+    let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for rel in &files {
+        let data = fs::read(dir.join(rel)).with_context(|| format!("reading {}", rel.display()))?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_cksum();
+        builder.append_data(
+            &mut header,
+            rel.to_string_lossy().replace('\\', "/"),
+            data.as_slice(),
+        )?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+fn collect_files(base: &Path, dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                collect_files(base, &path, out)?;
+            }
+        } else if path.is_file() {
+            out.push(path.strip_prefix(base).unwrap_or(&path).to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// A `.tar.gz` file is uploaded as is; a directory is packed first.
+fn load_source(path: &Path) -> Result<Vec<u8>> {
+    if path.is_dir() {
+        pack_source(path)
+    } else {
+        fs::read(path).with_context(|| format!("reading {}", path.display()))
+    }
+}
+
+async fn upload(
+    client: &reqwest::Client,
+    url: &str,
+    content_type: &str,
+    signature: &str,
+    bytes: Vec<u8>,
+) -> Result<UploadResponse> {
+    let response = client
+        .put(url)
+        .header(reqwest::header::CONTENT_TYPE, content_type)
+        .header("x-publisher-signature", signature)
+        .body(bytes)
+        .send()
+        .await
+        .context("Failed to upload artifact")?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Upload failed: {}",
+            response.text().await.unwrap_or_default()
+        );
+    }
+    response.json().await.context("Failed to parse upload response")
+}
+
+pub async fn push(
+    api_url: &str,
+    contract_id: &str,
+    version: &str,
+    wasm: Option<&Path>,
+    source: Option<&Path>,
+    secret: &str,
+    json: bool,
+) -> Result<()> {
+    if wasm.is_none() && source.is_none() {
+        anyhow::bail!("Nothing to push: pass --wasm and/or --source");
+    }
+    let seed = stellar_strkey::ed25519::PrivateKey::from_string(secret.trim())
+        .map_err(|_| anyhow!("--secret must be a secret seed (S...)"))?;
+    let key = SigningKey::from_bytes(&seed.0);
+    let client = reqwest::Client::new();
+    let signed_id = on_chain_id(&client, api_url, contract_id).await?;
+    let mut pushed = serde_json::Map::new();
+
+    let uploads = [
+        ("wasm", wasm, "wasm", "application/wasm"),
+        ("source", source, "source.tar.gz", "application/gzip"),
+    ];
+    for (kind, path, file, content_type) in uploads {
+        let Some(path) = path else { continue };
+        let bytes = if kind == "wasm" {
+            fs::read(path).with_context(|| format!("reading {}", path.display()))?
+        } else {
+            load_source(path)?
+        };
+        let local_hash = sha256_hex(&bytes);
+        let url = artifact_url(api_url, contract_id, version, file);
+        let signature = upload_signature(&key, &signed_id, version, &local_hash);
+        let uploaded = upload(&client, &url, content_type, &signature, bytes).await?;
+        if uploaded.sha256 != local_hash {
+            anyhow::bail!(
+                "Registry stored {} as {} but the local hash is {}",
+                kind,
+                uploaded.sha256,
+                local_hash
+            );
+        }
+
+        if !json {
+            let note = if uploaded.deduplicated {
+                " (already stored)".bright_black().to_string()
+            } else {
+                String::new()
+            };
+            println!(
+                "{} {} {} ({} bytes){}",
+                "✓".green(),
+                kind.bold(),
+                uploaded.sha256,
+                uploaded.size_bytes,
+                note
+            );
+        }
+        pushed.insert(
+            kind.to_string(),
+            serde_json::json!({
+                "sha256": uploaded.sha256,
+                "size_bytes": uploaded.size_bytes,
+                "deduplicated": uploaded.deduplicated,
+            }),
+        );
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&pushed)?);
+    }
+    Ok(())
+}
+
+/// Download one artifact and check it against the registry's hash header.
+async fn download(client: &reqwest::Client, url: &str) -> Result<(Vec<u8>, String)> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .context("Failed to download artifact")?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Download failed: {}",
+            response.text().await.unwrap_or_default()
+        );
+    }
+    let expected = response
+        .headers()
+        .get("x-content-sha256")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .context("Registry response has no X-Content-SHA256 header")?;
+    let bytes = response.bytes().await?.to_vec();
+    let actual = sha256_hex(&bytes);
+    if actual != expected {
+        anyhow::bail!(
+            "Downloaded bytes hash to {} but the registry reported {}",
+            actual,
+            expected
+        );
+    }
+    Ok((bytes, actual))
+}
+
+pub async fn pull(
+    api_url: &str,
+    contract_id: &str,
+    version: &str,
+    output: &Path,
+    with_source: bool,
+    json: bool,
+) -> Result<()> {
+    let client = reqwest::Client::new();
+    fs::create_dir_all(output).with_context(|| format!("creating {}", output.display()))?;
+    let mut pulled = serde_json::Map::new();
+
+    let mut downloads = vec![("wasm", "wasm", format!("{}-{}.wasm", contract_id, version))];
+    if with_source {
+        downloads.push((
+            "source",
+            "source.tar.gz",
+            format!("{}-{}-source.tar.gz", contract_id, version),
+        ));
+    }
+    for (kind, file, name) in downloads {
+        let url = artifact_url(api_url, contract_id, version, file);
+        let (bytes, sha256) = download(&client, &url).await?;
+        let path = output.join(name);
+        fs::write(&path, &bytes).with_context(|| format!("writing {}", path.display()))?;
+
+        if !json {
+            println!(
+                "{} {} {} → {}",
+                "✓".green(),
+                kind.bold(),
+                sha256,
+                path.display()
+            );
+        }
+        pulled.insert(
+            kind.to_string(),
+            serde_json::json!({ "sha256": sha256, "path": path, "size_bytes": bytes.len() }),
+        );
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&pulled)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_source_is_reproducible_and_skips_build_output() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("target/release")).unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"c\"\n").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "#![no_std]\n").unwrap();
+        fs::write(dir.path().join("target/release/c.wasm"), b"\0asm").unwrap();
+
+        let first = pack_source(dir.path()).unwrap();
+        let second = pack_source(dir.path()).unwrap();
+        assert_eq!(sha256_hex(&first), sha256_hex(&second));
+
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(first.as_slice()));
+        let names: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["Cargo.toml", "src/lib.rs"]);
+    }
+
+    #[test]
+    fn upload_signature_covers_contract_version_and_hash() {
+        use ed25519_dalek::{Signature, Verifier};
+
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let hash = sha256_hex(b"source");
+        let signature = BASE64
+            .decode(upload_signature(&key, "C1", "1.0.0", &hash))
+            .unwrap();
+        let signature = Signature::from_slice(&signature).unwrap();
+        let message = format!("C1:1.0.0:{}", hash);
+        assert!(key
+            .verifying_key()
+            .verify(message.as_bytes(), &signature)
+            .is_ok());
+        assert!(key
+            .verifying_key()
+            .verify(b"C1:1.0.1:00", &signature)
+            .is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Checking a contract's signed audit log.
+//!
+//! `audit verify` asks the registry to walk the hash chain; `audit export`
+//! saves a signed bundle of the whole chain, and `audit verify-bundle` checks
+//! such a bundle offline against pinned registry keys without trusting the
+//! server that produced it.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use ed25519_dalek::VerifyingKey;
+use serde::Deserialize;
+use shared::audit_chain::{self, AuditBundle, ChainBreak, ChainReport, ChainStatus};
+use shared::transparency;
+use std::collections::BTreeMap;
+use std::fs;
+
+#[derive(Debug, Deserialize)]
+struct ChainVerificationResponse {
+    #[serde(flatten)]
+    report: ChainReport,
+    public_key: Option<String>,
+}
+
+fn print_break(first_break: &ChainBreak) {
+    println!(
+        "  {}: entry {} (position {}, {})",
+        "First break".bold(),
+        first_break.entry_id,
+        first_break.position,
+        first_break.timestamp.to_rfc3339()
+    );
+    println!("  {}: {}", "Reason".bold(), first_break.reason);
+}
+
+fn print_report(report: &ChainReport) {
+    match report.status {
+        ChainStatus::Verified => println!("{}", "✓ Audit chain is intact".green().bold()),
+        ChainStatus::LegacyOnly => println!(
+            "{}",
+            "! Audit chain has no signed entries; its legacy hashes alone cannot be trusted"
+                .yellow()
+                .bold()
+        ),
+        ChainStatus::Broken => println!("{}", "✗ Audit chain is broken".red().bold()),
+    }
+    println!(
+        "  {}: {} of {} ({} legacy, hash-only)",
+        "Verified entries".bold(),
+        report.verified_entries,
+        report.total_entries,
+        report.legacy_entries
+    );
+    if let Some(head) = &report.head_hash {
+        println!("  {}: {}", "Head hash".bold(), head.bright_black());
+    }
+    if let Some(first_break) = &report.first_break {
+        print_break(first_break);
+    }
+}
+
+pub async fn verify_chain(api_url: &str, contract_id: &str) -> Result<()> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/api/contracts/{}/history/verify", api_url, contract_id))
+        .send()
+        .await
+        .context("Failed to reach registry API")?;
+    if !response.status().is_success() {
+        let err = response.text().await?;
+        bail!("Failed to verify audit chain: {}", err);
+    }
+
+    let verification: ChainVerificationResponse = response.json().await?;
+    print_report(&verification.report);
+    if let Some(key) = &verification.public_key {
+        println!("  {}: {}", "Signing key".bold(), key.bright_black());
+    }
+    if !verification.report.valid {
+        bail!("Audit chain verification failed");
+    }
+    Ok(())
+}
+
+pub async fn export_bundle(api_url: &str, contract_id: &str, output: Option<&str>) -> Result<()> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}/api/contracts/{}/history/bundle", api_url, contract_id))
+        .send()
+        .await
+        .context("Failed to reach registry API")?;
+    if !response.status().is_success() {
+        let err = response.text().await?;
+        bail!("Failed to export audit bundle: {}", err);
+    }
+
+    let bundle: AuditBundle = response.json().await?;
+    let path = output
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.audit.json", contract_id));
+    fs::write(&path, serde_json::to_string_pretty(&bundle)?)
+        .with_context(|| format!("Failed to write bundle file: {}", path))?;
+
+    println!("{}", "✓ Audit bundle saved".green().bold());
+    println!("  {}: {}", "File".bold(), path.bright_black());
+    println!("  {}: {}", "Entries".bold(), bundle.entries.len());
+    println!(
+        "\n  {} Verify offline with: soroban-registry audit verify-bundle {} --audit-key <key>\n",
+        "→".bright_black(),
+        path
+    );
+    Ok(())
+}
+
+/// `keys` are base64 Ed25519 public keys; every key the chain was signed
+/// with must be among them.
+pub fn verify_bundle(path: &str, keys: &[String]) -> Result<()> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read bundle: {}", path))?;
+    let bundle: AuditBundle = serde_json::from_str(&contents).context("Invalid audit bundle")?;
+
+    let mut trusted: BTreeMap<String, VerifyingKey> = BTreeMap::new();
+    for key in keys {
+        let key = audit_chain::decode_public_key(key)
+            .with_context(|| format!("Not a base64 Ed25519 public key: {}", key))?;
+        trusted.insert(transparency::key_id(key.as_bytes()), key);
+    }
+
+    let report = audit_chain::verify_bundle(&bundle, &trusted)
+        .map_err(|err| anyhow::anyhow!("Audit bundle rejected: {}", err))?;
+
+    println!("  {}: {}", "Contract".bold(), bundle.contract_id);
+    println!(
+        "  {}: {}",
+        "Exported at".bold(),
+        bundle.exported_at.to_rfc3339()
+    );
+    print_report(&report);
+    if !report.valid {
+        bail!("Audit bundle verification failed");
+    }
+    Ok(())
+}
@@ -5,8 +5,13 @@ use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
+use crate::jobs;
+
 const MAX_BATCH_SIZE: usize = 50;
-const BATCH_TIMEOUT_SECS: u64 = 30;
+const SUBMIT_TIMEOUT_SECS: u64 = 30;
+/// How long to follow the queued verifications before reporting the rest
+/// as timed out. They keep running on the registry either way.
+const WAIT_TIMEOUT_SECS: u64 = 600;
 
 // ── Request / response types ──────────────────────────────────────────────────
 
@@ -22,8 +27,31 @@ pub struct BatchContractEntry {
     pub version: Option<String>,
 }
 
+/// `202 Accepted` body: one queued verification job per contract.
 #[derive(Debug, Deserialize)]
 pub struct BatchVerifyResponse {
+    pub batch_id: String,
+    pub total: usize,
+    pub queued: usize,
+    pub skipped: usize,
+    pub skipped_duplicates: usize,
+    pub results: Vec<BatchEntry>,
+    pub initiated_by: String,
+    pub initiated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchEntry {
+    pub contract_id: String,
+    pub version: Option<String>,
+    pub status: String, // "queued" | "skipped"
+    pub job_id: Option<String>,
+    pub verification_id: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchVerifyReport {
     pub batch_id: String,
     pub total: usize,
     pub succeeded: usize,
@@ -35,13 +63,13 @@ pub struct BatchVerifyResponse {
     pub duration_ms: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize)]
 pub struct ContractVerifyResult {
     pub contract_id: String,
     pub version: Option<String>,
     pub status: String, // "verified" | "failed" | "timeout" | "skipped"
+    pub job_id: Option<String>,
     pub error: Option<String>,
-    pub verified_at: Option<String>,
 }
 
 // ── Main batch verify command ─────────────────────────────────────────────────
@@ -53,6 +81,9 @@ pub struct ContractVerifyResult {
 ///   e.g. "abc123,def456@1.0.0,ghi789"
 ///
 /// `initiated_by` is the Stellar address or username initiating the batch.
+///
+/// The registry queues one verification job per contract; this command then
+/// follows the jobs until they finish.
 pub async fn run_batch_verify(
     api_url: &str,
     contracts_input: &str,
@@ -81,19 +112,26 @@ pub async fn run_batch_verify(
         );
     }
 
-    println!("\n{}", "Batch Contract Verification".bold().cyan());
-    println!("{}", "=".repeat(60).cyan());
-    println!("  {}: {}", "Contracts".bold(), deduped_count);
-    if skipped_duplicates > 0 {
+    if !json {
+        println!("\n{}", "Batch Contract Verification".bold().cyan());
+        println!("{}", "=".repeat(60).cyan());
+        println!("  {}: {}", "Contracts".bold(), deduped_count);
+        if skipped_duplicates > 0 {
+            println!(
+                "  {}: {} (deduplicated)",
+                "Duplicates removed".bold(),
+                skipped_duplicates.to_string().yellow()
+            );
+        }
         println!(
-            "  {}: {} (deduplicated)",
-            "Duplicates removed".bold(),
-            skipped_duplicates.to_string().yellow()
+            "  {}: {}",
+            "Initiated by".bold(),
+            initiated_by.bright_black()
         );
+        println!("  {}: {}s", "Wait timeout".bold(), WAIT_TIMEOUT_SECS);
+        println!();
+        println!("{}", "Submitting batch to registry...".bright_black());
     }
-    println!("  {}: {}", "Initiated by".bold(), initiated_by.bright_black());
-    println!("  {}: {}s total / 5s per contract", "Timeout".bold(), BATCH_TIMEOUT_SECS);
-    println!();
 
     let request = BatchVerifyRequest {
         contracts: entries,
@@ -101,11 +139,10 @@ pub async fn run_batch_verify(
     };
 
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(BATCH_TIMEOUT_SECS))
+        .timeout(std::time::Duration::from_secs(SUBMIT_TIMEOUT_SECS))
         .build()?;
 
-    println!("{}", "Submitting batch to registry...".bright_black());
-
+    let started = std::time::Instant::now();
     let response = client
         .post(format!("{}/api/contracts/batch-verify", api_url))
         .json(&request)
@@ -122,28 +159,104 @@ pub async fn run_batch_verify(
         anyhow::bail!("API error (HTTP {}): {}", status, err);
     }
 
-    let result: BatchVerifyResponse = response
+    let accepted: BatchVerifyResponse = response
         .json()
         .await
         .context("Failed to parse batch verify response")?;
 
+    if !json {
+        println!(
+            "{}",
+            format!(
+                "Queued {} verification job(s); waiting for results...",
+                accepted.queued
+            )
+            .bright_black()
+        );
+    }
+
+    let deadline = started + std::time::Duration::from_secs(WAIT_TIMEOUT_SECS);
+    let mut results = Vec::with_capacity(accepted.results.len());
+    for entry in accepted.results {
+        results.push(follow_entry(&client, api_url, entry, deadline).await?);
+    }
+
+    let succeeded = results.iter().filter(|r| r.status == "verified").count();
+    let report = BatchVerifyReport {
+        batch_id: accepted.batch_id,
+        total: accepted.total,
+        succeeded,
+        failed: results.len() - succeeded,
+        skipped_duplicates: skipped_duplicates + accepted.skipped_duplicates,
+        results,
+        initiated_by: accepted.initiated_by,
+        initiated_at: accepted.initiated_at,
+        duration_ms: Some(started.elapsed().as_millis() as u64),
+    };
+
     if json {
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        println!("{}", serde_json::to_string_pretty(&report)?);
         return Ok(());
     }
 
-    display_results(&result);
+    display_results(&report);
 
     Ok(())
 }
 
+/// Wait for one queued verification and turn its job into a result row.
+async fn follow_entry(
+    client: &reqwest::Client,
+    api_url: &str,
+    entry: BatchEntry,
+    deadline: std::time::Instant,
+) -> Result<ContractVerifyResult> {
+    let mut result = ContractVerifyResult {
+        contract_id: entry.contract_id,
+        version: entry.version,
+        status: "skipped".to_string(),
+        job_id: entry.job_id.clone(),
+        error: entry.error,
+    };
+    let Some(job_id) = entry.job_id else {
+        return Ok(result);
+    };
+
+    let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+    match jobs::wait_for_job(client, api_url, &job_id, remaining, true).await {
+        Ok(job) => match jobs::into_result(job) {
+            Ok(value) if value["verified"].as_bool() == Some(true) => {
+                result.status = "verified".to_string();
+            }
+            Ok(_) => {
+                result.status = "failed".to_string();
+                result.error = Some("Verification did not match the deployed WASM".to_string());
+            }
+            Err(e) => {
+                result.status = "failed".to_string();
+                result.error = Some(e.to_string());
+            }
+        },
+        Err(_) => result.status = "timeout".to_string(),
+    }
+    Ok(result)
+}
+
 // ── Display ───────────────────────────────────────────────────────────────────
 
-fn display_results(result: &BatchVerifyResponse) {
+fn display_results(result: &BatchVerifyReport) {
     println!("\n{}", "Batch Results".bold().cyan());
     println!("{}", "=".repeat(60).cyan());
-    println!("  {}: {}", "Batch ID".bold(), result.batch_id.bright_black());
-    println!("  {}: {}", "Initiated at".bold(), result.initiated_at.bright_black());
+    println!(
+        "  {}: {}",
+        "Batch ID".bold(),
+        result.batch_id.bright_black()
+    );
+    println!(
+        "  {}: {}",
+        "Initiated at".bold(),
+        result.initiated_at.bright_black()
+    );
     if let Some(ms) = result.duration_ms {
         println!("  {}: {}ms", "Duration".bold(), ms);
     }
@@ -152,7 +265,7 @@ fn display_results(result: &BatchVerifyResponse) {
 
     // Summary line
     let succeeded_str = format!("{} verified", result.succeeded).green();
-    let failed_str = format!("{} failed", result.failed).red();
+    let failed_str = format!("{} not verified", result.failed).red();
     let skipped_str = if result.skipped_duplicates > 0 {
         format!(", {} duplicates skipped", result.skipped_duplicates)
             .bright_black()
@@ -169,7 +282,6 @@ fn display_results(result: &BatchVerifyResponse) {
         skipped_str
     );
 
-    // All-or-nothing result banner
     println!();
     if result.failed == 0 {
         println!(
@@ -179,14 +291,10 @@ fn display_results(result: &BatchVerifyResponse) {
         );
     } else {
         println!(
-            "  {} Batch rolled back — {} contract(s) failed verification.",
+            "  {} {} contract(s) were not verified.",
             "✗".red().bold(),
             result.failed
         );
-        println!(
-            "  {} No contracts were marked as verified (atomic transaction).",
-            "↩".yellow()
-        );
     }
 
     println!("\n{}", "Per-contract results:".bold());
@@ -214,18 +322,15 @@ fn display_results(result: &BatchVerifyResponse) {
         );
 
         match r.status.as_str() {
-            "verified" => {
-                if let Some(at) = &r.verified_at {
-                    println!("    Verified at: {}", at.bright_black());
-                }
-            }
             "timeout" => {
-                println!(
-                    "    {}",
-                    "Exceeded 5s per-contract timeout — skipped.".yellow()
-                );
+                if let Some(job_id) = &r.job_id {
+                    println!(
+                        "    {}",
+                        format!("Still running — follow job {}", job_id).yellow()
+                    );
+                }
             }
-            "failed" => {
+            "failed" | "skipped" => {
                 if let Some(err) = &r.error {
                     println!("    Error: {}", err.red());
                 }
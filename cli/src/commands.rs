@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use anyhow::{Context, Result};
+use base64::Engine as _;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -125,56 +126,86 @@ pub async fn search(
     verified_only: bool,
     networks: Vec<String>,
     category: Option<&str>,
+    tags: Vec<String>,
+    fuzzy: bool,
     limit: usize,
     offset: usize,
     json: bool,
 ) -> Result<()> {
     let client = reqwest::Client::new();
 
-    let mut url = format!(
-        "{}/api/contracts?query={}&limit={}&offset={}",
-        api_url, query, limit, offset
-    );
+    let limit = limit.max(1);
+    let mut params: Vec<(&str, String)> = vec![
+        ("query", query.to_string()),
+        ("limit", limit.to_string()),
+        ("page", (offset / limit + 1).to_string()),
+    ];
 
     if !networks.is_empty() {
-        url.push_str(&format!("&networks={}", networks.join(",")));
+        params.push(("networks", networks.join(",")));
     } else {
-        url.push_str(&format!("&network={}", network));
+        params.push(("network", network.to_string()));
     }
 
     if verified_only {
-        url.push_str("&verified_only=true");
+        params.push(("verified_only", "true".to_string()));
     }
 
     if let Some(cat) = category {
-        url.push_str(&format!("&category={}", cat));
+        params.push(("category", cat.to_string()));
+    }
+
+    if !tags.is_empty() {
+        params.push(("tags", tags.join(",")));
+    }
+
+    if !fuzzy {
+        params.push(("fuzzy", "false".to_string()));
     }
 
     let response = client
-        .get(&url)
+        .get(format!("{}/api/contracts", api_url))
+        .query(&params)
         .send()
         .await
         .context("Failed to search contracts")?;
 
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("Search failed ({}): {}", status, body);
+    }
+
     let data: serde_json::Value = response.json().await?;
-    let items = data["items"].as_array().context("Invalid response")?;
+    let items = data["contracts"]
+        .as_array()
+        .or_else(|| data["items"].as_array())
+        .context("Invalid response")?;
 
     if json {
         let contracts: Vec<serde_json::Value> = items
             .iter()
             .map(|c| -> Result<_> {
                 Ok(serde_json::json!({
-                    "id":          crate::conversions::as_str(&c["contract_id"], "contract_id")?,
-                    "name":        crate::conversions::as_str(&c["name"], "name")?,
-                    "is_verified": crate::conversions::as_bool(&c["is_verified"], "is_verified")?,
-                    "network":     crate::conversions::as_str(&c["network"], "network")?,
-                    "category":    c["category"].as_str().unwrap_or(""),
+                    "id":                crate::conversions::as_str(&c["contract_id"], "contract_id")?,
+                    "name":              crate::conversions::as_str(&c["name"], "name")?,
+                    "is_verified":       crate::conversions::as_bool(&c["is_verified"], "is_verified")?,
+                    "network":           crate::conversions::as_str(&c["network"], "network")?,
+                    "category":          c["category"].as_str().unwrap_or(""),
+                    "tags":              c["tags"].clone(),
+                    "relevance":         c["relevance"].as_f64(),
+                    "matched_functions": c["matched_functions"].clone(),
+                    "matched_events":    c["matched_events"].clone(),
                 }))
             })
             .collect::<Result<_, _>>()?;
         println!(
             "{}",
-            serde_json::to_string_pretty(&serde_json::json!({ "contracts": contracts }))?
+            serde_json::to_string_pretty(&serde_json::json!({
+                "contracts": contracts,
+                "total":     data["total"],
+                "facets":    data["facets"],
+            }))?
         );
         return Ok(());
     }
@@ -190,6 +221,9 @@ pub async fn search(
     if let Some(cat) = category {
         active_filters.push(format!("category: {}", cat));
     }
+    if !tags.is_empty() {
+        active_filters.push(format!("tags: {}", tags.join(", ")));
+    }
     if verified_only {
         active_filters.push("verified only".to_string());
     }
@@ -205,9 +239,15 @@ pub async fn search(
         println!("{}", "No contracts found matching your filters.".yellow());
         println!("\n{}", "Suggestions:".bold());
         println!("  • Try a broader search query");
+        if !fuzzy {
+            println!("  • Drop --no-fuzzy to match misspelled terms");
+        }
         if category.is_some() {
             println!("  • Remove the --category filter to see all contract types");
         }
+        if !tags.is_empty() {
+            println!("  • Remove some --tag filters");
+        }
         if !networks.is_empty() {
             println!("  • Try adding more networks: --networks mainnet,testnet,futurenet");
         }
@@ -224,7 +264,15 @@ pub async fn search(
         let is_verified = crate::conversions::as_bool(&contract["is_verified"], "is_verified")?;
         let network = crate::conversions::as_str(&contract["network"], "network")?;
 
-        println!("\n{} {}", "●".green(), name.bold());
+        let title = contract["highlights"]["name"]
+            .as_str()
+            .map(highlight_marks)
+            .unwrap_or_else(|| name.bold().to_string());
+        print!("\n{} {}", "●".green(), title);
+        if let Some(relevance) = contract["relevance"].as_f64() {
+            print!(" {}", format!("({:.2})", relevance).bright_black());
+        }
+        println!();
         println!("  ID: {}", contract_id.bright_black());
         print!(
             "  Status: {} | Network: {}",
@@ -243,17 +291,76 @@ pub async fn search(
         }
         println!();
 
-        if let Some(desc) = contract["description"].as_str() {
-            println!("  {}", desc.bright_black());
+        if let Some(desc) = contract["highlights"]["description"]
+            .as_str()
+            .or_else(|| contract["description"].as_str())
+        {
+            println!("  {}", highlight_marks(desc));
+        }
+
+        for (label, key) in [("Functions", "matched_functions"), ("Events", "matched_events")] {
+            let matched: Vec<&str> = contract[key]
+                .as_array()
+                .map(|names| names.iter().filter_map(|n| n.as_str()).collect())
+                .unwrap_or_default();
+            if !matched.is_empty() {
+                println!("  {}: {}", label, matched.join(", ").bright_cyan());
+            }
         }
     }
 
     println!("\n{}", "=".repeat(80).cyan());
-    println!("Found {} contract(s) (offset: {})\n", items.len(), offset);
+    match data["total"].as_i64() {
+        Some(total) => println!(
+            "Showing {} of {} contract(s) (offset: {})",
+            items.len(),
+            total,
+            offset
+        ),
+        None => println!("Found {} contract(s) (offset: {})", items.len(), offset),
+    }
+
+    if let Some(facets) = data["facets"].as_object() {
+        for (label, key) in [
+            ("Categories", "category"),
+            ("Networks", "network"),
+            ("Tags", "tags"),
+        ] {
+            let counts: Vec<String> = facets
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|f| Some(format!("{} ({})", f["value"].as_str()?, f["count"])))
+                        .collect()
+                })
+                .unwrap_or_default();
+            if !counts.is_empty() {
+                println!("  {}: {}", label.bold(), counts.join(", ").bright_black());
+            }
+        }
+    }
+    println!();
 
     Ok(())
 }
 
+/// Render the search API's `<mark>…</mark>` highlights for the terminal.
+fn highlight_marks(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<mark>") {
+        out.push_str(&rest[..start]);
+        let marked = &rest[start + "<mark>".len()..];
+        let end = marked.find("</mark>").unwrap_or(marked.len());
+        out.push_str(&marked[..end].yellow().bold().to_string());
+        rest = marked.get(end + "</mark>".len()..).unwrap_or("");
+    }
+    out.push_str(rest);
+    out
+}
+
 /// Analyze two contract versions or schema files for breaking changes.
 pub async fn upgrade_analyze(api_url: &str, old_id: &str, new_id: &str, json_out: bool) -> Result<()> {
     use reqwest::StatusCode;
@@ -431,10 +538,20 @@ pub async fn publish(
     category: Option<&str>,
     tags: Vec<String>,
     publisher: &str,
+    wasm: Option<&Path>,
 ) -> Result<()> {
     let client = reqwest::Client::new();
     let url = format!("{}/api/contracts", api_url);
 
+    let wasm_base64 = match wasm {
+        Some(path) => Some(
+            base64::engine::general_purpose::STANDARD.encode(
+                fs::read(path).with_context(|| format!("reading {}", path.display()))?,
+            ),
+        ),
+        None => None,
+    };
+
     let payload = json!({
         "contract_id": contract_id,
         "name": name,
@@ -443,6 +560,7 @@ pub async fn publish(
         "category": category,
         "tags": tags,
         "publisher_address": publisher,
+        "wasm_base64": wasm_base64,
     });
 
     println!("\n{}", "Publishing contract...".bold().cyan());
@@ -1177,87 +1295,94 @@ pub async fn run_tests(
     println!("\n{}", "Running Integration Tests...".bold().cyan());
     println!("{}", "=".repeat(80).cyan());
 
-    let scenario = test_framework::load_test_scenario(test_path)?;
-
-    if verbose {
-        println!("\n{}: {}", "Scenario".bold(), scenario.name);
-        if let Some(desc) = &scenario.description {
-            println!("{}: {}", "Description".bold(), desc);
-        }
-        println!("{}: {}", "Steps".bold(), scenario.steps.len());
+    let scenarios = test_framework::load_test_scenarios(test_path)?;
+    if scenarios.is_empty() {
+        anyhow::bail!("No test scenarios found in {}", test_file);
     }
 
     let start_time = std::time::Instant::now();
-    let result = runner.run_scenario(scenario).await?;
+    let mut results = Vec::with_capacity(scenarios.len());
+    for (_, scenario) in scenarios {
+        if verbose {
+            println!("\n{}: {}", "Scenario".bold(), scenario.name);
+            if let Some(desc) = &scenario.description {
+                println!("{}: {}", "Description".bold(), desc);
+            }
+            println!("{}: {}", "Steps".bold(), scenario.steps.len());
+        }
+        results.push(runner.run_scenario(scenario).await?);
+    }
     let total_time = start_time.elapsed();
 
     println!("\n{}", "Test Results:".bold().green());
     println!("{}", "=".repeat(80).cyan());
 
-    let status_icon = if result.passed { "✓" } else { "✗" };
+    for result in &results {
+        let status_icon = if result.passed { "✓" } else { "✗" };
 
-    println!(
-        "\n{} {} {} ({:.2}ms)",
-        status_icon,
-        "Scenario:".bold(),
-        result.scenario.bold(),
-        result.duration.as_secs_f64() * 1000.0
-    );
+        println!(
+            "\n{} {} {} ({:.2}ms)",
+            status_icon,
+            "Scenario:".bold(),
+            result.scenario.bold(),
+            result.duration.as_secs_f64() * 1000.0
+        );
 
-    if !result.passed {
-        if let Some(ref err) = result.error {
-            println!("{} {}", "Error:".bold().red(), err);
+        if !result.passed {
+            if let Some(ref err) = result.error {
+                println!("{} {}", "Error:".bold().red(), err);
+            }
         }
-    }
 
-    println!("\n{}", "Step Results:".bold());
-    for (i, step) in result.steps.iter().enumerate() {
-        let step_icon = if step.passed { "✓" } else { "✗" };
+        println!("\n{}", "Step Results:".bold());
+        for (i, step) in result.steps.iter().enumerate() {
+            let step_icon = if step.passed { "✓" } else { "✗" };
 
-        println!(
-            "  {}. {} {} ({:.2}ms)",
-            i + 1,
-            step_icon,
-            step.step_name.bold(),
-            step.duration.as_secs_f64() * 1000.0
-        );
-
-        if verbose {
             println!(
-                "     Assertions: {}/{} passed",
-                step.assertions_passed,
-                step.assertions_passed + step.assertions_failed
+                "  {}. {} {} ({:.2}ms)",
+                i + 1,
+                step_icon,
+                step.step_name.bold(),
+                step.duration.as_secs_f64() * 1000.0
             );
-        }
 
-        if let Some(ref err) = step.error {
-            println!("     {}", err.red());
+            if verbose {
+                println!(
+                    "     Assertions: {}/{} passed",
+                    step.assertions_passed,
+                    step.assertions_passed + step.assertions_failed
+                );
+            }
+
+            if let Some(ref err) = step.error {
+                println!("     {}", err.red());
+            }
         }
     }
 
-    if show_coverage {
+    // Method coverage accumulates across scenarios, so the last result
+    // holds the totals.
+    if let (true, Some(last)) = (show_coverage, results.last()) {
         println!("\n{}", "Coverage Report:".bold().magenta());
-        println!("  Contracts Tested: {}", result.coverage.contracts_tested);
+        println!("  Contracts Tested: {}", last.coverage.contracts_tested);
         println!(
             "  Methods Tested: {}/{}",
-            result.coverage.methods_tested, result.coverage.total_methods
+            last.coverage.methods_tested, last.coverage.total_methods
         );
-        println!("  Coverage: {:.2}%", result.coverage.coverage_percent);
+        println!("  Coverage: {:.2}%", last.coverage.coverage_percent);
 
-        if result.coverage.coverage_percent < 80.0 {
+        if last.coverage.coverage_percent < 80.0 {
             println!("  {} Low coverage detected!", "⚠".yellow());
         }
     }
 
     if let Some(junit_path) = junit_output {
-        test_framework::generate_junit_xml(&[result], Path::new(junit_path))?;
+        test_framework::generate_junit_xml(&results, Path::new(junit_path))?;
         println!(
             "\n{} JUnit XML report exported to: {}",
             "✓".green(),
             junit_path
         );
-        test_framework::generate_junit_xml(&[result.clone()], Path::new(junit_path))?;
-        println!("\n{} JUnit XML report exported to: {}", "✓".green(), junit_path);
     }
 
     if total_time.as_secs() > 5 {
@@ -1271,7 +1396,7 @@ pub async fn run_tests(
     println!("\n{}", "=".repeat(80).cyan());
     println!();
 
-    if !result.passed {
+    if results.iter().any(|r| !r.passed) {
         anyhow::bail!("Tests failed");
     }
 
@@ -1519,6 +1644,7 @@ pub async fn scan_deps(
     api_url: &str,
     contract_id: &str,
     dependencies: &str,
+    lockfile: Option<&str>,
     fail_on_high: bool,
 ) -> Result<()> {
     println!("\n{}", "Scanning Dependencies...".bold().cyan());
@@ -1541,8 +1667,15 @@ pub async fn scan_deps(
         }
     }
 
+    let cargo_lock = lockfile
+        .map(|path| {
+            fs::read_to_string(path).with_context(|| format!("Failed to read lockfile: {}", path))
+        })
+        .transpose()?;
+
     let payload = json!({
         "dependencies": deps_list,
+        "cargo_lock": cargo_lock,
     });
 
     let response = client
@@ -1556,9 +1689,32 @@ pub async fn scan_deps(
         anyhow::bail!("Scan failed: {}", response.text().await.unwrap_or_default());
     }
 
-    let report: serde_json::Value = response.json().await?;
+    // The scan runs as a registry job; wait for its report.
+    let accepted: serde_json::Value = response.json().await?;
+    let job_id = crate::conversions::as_str(&accepted["job_id"], "job_id")?;
+    let job = crate::jobs::wait_for_job(
+        &client,
+        api_url,
+        &job_id,
+        std::time::Duration::from_secs(300),
+        true,
+    )
+    .await?;
+    let report = crate::jobs::into_result(job).context("Scan failed")?;
     let findings = crate::conversions::as_array(&report["findings"], "findings")?;
 
+    if let Some(skipped) = report["skipped"].as_array() {
+        for dep in skipped {
+            println!(
+                "{} Skipped {}@{}: {}",
+                "⚠".yellow(),
+                dep["package_name"].as_str().unwrap_or("?"),
+                dep["version"].as_str().unwrap_or("?"),
+                dep["reason"].as_str().unwrap_or("")
+            );
+        }
+    }
+
     if findings.is_empty() {
         println!("{}", "✓ No vulnerabilities found!".green().bold());
         return Ok(());
@@ -1573,15 +1729,34 @@ pub async fn scan_deps(
         let version = crate::conversions::as_str(&finding["current_version"], "current_version")?;
         let severity = crate::conversions::as_str(&finding["severity"], "severity")?;
         let cve_id = crate::conversions::as_str(&finding["cve_id"], "cve_id")?;
-        let recommended = crate::conversions::as_str(&finding["recommended_version"], "recommended_version")?;
+        let aliases: Vec<&str> = finding["aliases"]
+            .as_array()
+            .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
 
         let sev_enum = severity.parse::<Severity>().context("Invalid severity string")?;
         if matches!(sev_enum, Severity::Critical | Severity::High) {
             has_high_severity = true;
         }
 
-        println!("  {} {}@{} - {}", severity_colored(&sev_enum), package, version, cve_id.bold());
-        println!("    {} Recommended patch: {}", "↳".bright_black(), recommended.green());
+        if aliases.is_empty() {
+            println!("  {} {}@{} - {}", severity_colored(&sev_enum), package, version, cve_id.bold());
+        } else {
+            println!(
+                "  {} {}@{} - {} ({})",
+                severity_colored(&sev_enum),
+                package,
+                version,
+                cve_id.bold(),
+                aliases.join(", ")
+            );
+        }
+        match finding["recommended_version"].as_str() {
+            Some(recommended) => {
+                println!("    {} Recommended patch: {}", "↳".bright_black(), recommended.green())
+            }
+            None => println!("    {} No patched release available", "↳".bright_black()),
+        }
     }
 
     println!("\n{}", "=".repeat(80).red());
@@ -1,86 +1,504 @@
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
+#![allow(dead_code)]
+
+//! Fee and resource estimation.
+//!
+//! Local contracts are priced by running the calls in the sandbox ledger,
+//! measuring the resources the host records and applying a network fee
+//! schedule to them. Registry contract ids are priced by the API, which
+//! simulates against an RPC node or falls back to recorded usage.
+
+use crate::sandbox::Invocation;
+use crate::test_framework::{self, TestRunner, TestScenario, TestStep, TestValue};
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use shared::fees::{FeeEstimate, FeeSchedule, RentChange, ResourceUsage, StoredEntry};
+use shared::models::{
+    CostEstimate, CostEstimateRequest, CostForecast, CostOptimization, CostSource,
+};
+use soroban_env_host::xdr::{
+    ContractDataDurability, DecoratedSignature, ExtensionPoint, InvokeHostFunctionOp, LedgerKey,
+    Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions, ScBytes, ScMap,
+    ScMapEntry, ScSymbol, ScVal, ScVec, SequenceNumber, Signature, SignatureHint,
+    SorobanAuthorizationEntry, SorobanCredentials, SorobanTransactionData, Transaction,
+    TransactionEnvelope, TransactionExt, TransactionV1Envelope, Uint256, WriteXdr,
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const STROOPS_PER_XLM: f64 = 10_000_000.0;
+const LEDGERS_PER_DAY: u32 = 17_280;
+
+pub struct EstimateOptions<'a> {
+    /// Local `.wasm`, contract crate or directory, or a registry contract id.
+    pub contract: &'a str,
+    pub method: Option<&'a str>,
+    /// JSON array of arguments for `method`.
+    pub args: Option<&'a str>,
+    /// Scenario file or directory whose calls are priced as one batch.
+    pub scenario: Option<&'a str>,
+    pub invocations: u32,
+    pub fee_schedule: Option<&'a str>,
+    pub ttl_horizon: u32,
+    pub json: bool,
+    pub optimize: bool,
+    pub forecast: bool,
+}
+
+pub async fn estimate_costs(api_url: &str, opts: &EstimateOptions<'_>) -> Result<()> {
+    let schedule = load_fee_schedule(opts.fee_schedule)?;
+    if Path::new(opts.contract).exists() {
+        let report = estimate_local(opts, &schedule)?;
+        if opts.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print_local_report(&report);
+        }
+        return Ok(());
+    }
+
+    let method = opts
+        .method
+        .context("--method is required when estimating a registry contract")?;
+    let request = CostEstimateRequest {
+        method_name: method.to_string(),
+        invocations: Some(opts.invocations),
+        resources: None,
+        rent_changes: Vec::new(),
+        live_entries: Vec::new(),
+        transaction_xdr: None,
+        fee_schedule: opts.fee_schedule.map(|_| schedule.clone()),
+        ttl_horizon_ledgers: Some(opts.ttl_horizon),
+        current_ledger: None,
+    };
+    estimate_remote(api_url, opts, &request).await
+}
+
+fn load_fee_schedule(path: Option<&str>) -> Result<FeeSchedule> {
+    let Some(path) = path else {
+        return Ok(FeeSchedule::default());
+    };
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read fee schedule {}", path))?;
+    if path.ends_with(".toml") {
+        toml::from_str(&content).with_context(|| format!("Invalid fee schedule {}", path))
+    } else {
+        serde_json::from_str(&content).with_context(|| format!("Invalid fee schedule {}", path))
+    }
+}
+
+// ─── Local simulation ────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+struct CallCost {
+    function: String,
+    ok: bool,
+    resources: ResourceUsage,
+    fee: FeeEstimate,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionCost {
+    function: String,
+    calls: u32,
+    failures: u32,
+    mean_instructions: u64,
+    mean_fee: i64,
+    total_fee: i64,
+}
 
 #[derive(Debug, Serialize)]
-struct CostEstimateRequest {
-    method_name: String,
-    invocations: Option<i64>,
-    storage_growth_kb: Option<i64>,
-}
-
-#[derive(Debug, Deserialize)]
-struct CostEstimate {
-    method_name: String,
-    gas_cost: i64,
-    storage_cost: i64,
-    bandwidth_cost: i64,
-    total_stroops: i64,
+struct LocalReport {
+    schedule: FeeSchedule,
+    calls: Vec<CallCost>,
+    functions: Vec<FunctionCost>,
+    /// Sum over all calls; TTL extension covers each touched entry once.
+    total: FeeEstimate,
     total_xlm: f64,
-    invocations: i64,
 }
 
-#[derive(Debug, Deserialize)]
-struct CostOptimization {
-    current_cost: i64,
-    optimized_cost: i64,
-    savings_percent: f64,
-    suggestions: Vec<String>,
+fn estimate_local(opts: &EstimateOptions<'_>, schedule: &FeeSchedule) -> Result<LocalReport> {
+    let scenarios = match (opts.scenario, opts.method) {
+        (Some(path), _) => test_framework::load_test_scenarios(Path::new(path))?
+            .into_iter()
+            .map(|(_, scenario)| scenario)
+            .collect(),
+        (None, Some(method)) => vec![repeated_call(method, opts.args, opts.invocations)?],
+        (None, None) => bail!("Specify --method or --scenario to choose the calls to price"),
+    };
+
+    println!(
+        "{} Simulating calls against a local ledger...",
+        "→".bright_black()
+    );
+    let mut runner = TestRunner::new(opts.contract)?;
+    let mut calls = Vec::new();
+    let mut live_entries = BTreeMap::new();
+    let mut failure = None;
+    for scenario in &scenarios {
+        runner.run_scenario_observed(scenario, &mut |_, method, invocation| {
+            match price_invocation(schedule, method, invocation, opts.ttl_horizon) {
+                Ok(call) => {
+                    track_live_entries(&mut live_entries, invocation);
+                    calls.push(call);
+                }
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        })?;
+    }
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    if calls.is_empty() {
+        bail!("No contract calls were made");
+    }
+
+    let mut total = calls
+        .iter()
+        .fold(FeeEstimate::default(), |acc, call| acc.saturating_add(&call.fee));
+    let entries: Vec<StoredEntry> = live_entries.into_values().collect();
+    total.ttl_extension_fee = schedule.ttl_extension_fee(&entries, opts.ttl_horizon);
+    total.horizon_ledgers = opts.ttl_horizon;
+
+    Ok(LocalReport {
+        schedule: schedule.clone(),
+        functions: per_function(&calls),
+        total_xlm: total.total_fee as f64 / STROOPS_PER_XLM,
+        calls,
+        total,
+    })
 }
 
-#[derive(Debug, Deserialize)]
-struct CostForecast {
-    daily_cost_xlm: f64,
-    monthly_cost_xlm: f64,
-    yearly_cost_xlm: f64,
-    usage_pattern: String,
+/// A scenario calling `method` `count` times with the same arguments.
+fn repeated_call(method: &str, args: Option<&str>, count: u32) -> Result<TestScenario> {
+    let args: Vec<TestValue> = match args {
+        Some(raw) => serde_json::from_str(raw).context("--args must be a JSON array")?,
+        None => Vec::new(),
+    };
+    Ok(TestScenario {
+        name: format!("{} x{}", method, count),
+        description: None,
+        setup: None,
+        steps: (0..count.max(1))
+            .map(|i| TestStep {
+                name: format!("call {}", i + 1),
+                contract: "contract".to_string(),
+                method: method.to_string(),
+                args: Some(args.clone()),
+                assertions: None,
+                expected_error: None,
+            })
+            .collect(),
+        teardown: None,
+    })
 }
 
-pub async fn estimate_costs(
-    api_url: &str,
-    contract_id: &str,
+fn price_invocation(
+    schedule: &FeeSchedule,
     method: &str,
-    invocations: Option<i64>,
-    storage_kb: Option<i64>,
-    optimize: bool,
-    forecast: bool,
-) -> Result<()> {
-    let client = reqwest::Client::new();
+    invocation: &Invocation,
+    horizon: u32,
+) -> Result<CallCost> {
+    let resources = resource_usage(invocation)?;
+    let live: Vec<StoredEntry> = live_entries(invocation).into_values().collect();
+    let fee = schedule.estimate(
+        &resources,
+        &rent_changes(invocation),
+        invocation.ledger_sequence,
+        &live,
+        horizon,
+    );
+    Ok(CallCost {
+        function: method.to_string(),
+        ok: invocation.is_ok(),
+        resources,
+        fee,
+    })
+}
 
-    let request = CostEstimateRequest {
-        method_name: method.to_string(),
-        invocations,
-        storage_growth_kb: storage_kb,
+fn resource_usage(invocation: &Invocation) -> Result<ResourceUsage> {
+    let footprint = &invocation.resources.footprint;
+    Ok(ResourceUsage {
+        instructions: invocation.resources.instructions,
+        read_entries: footprint.read_only.len() as u32,
+        write_entries: footprint.read_write.len() as u32,
+        read_bytes: invocation.resources.read_bytes,
+        write_bytes: invocation.resources.write_bytes,
+        contract_events_size_bytes: invocation.events_and_return_value_size,
+        transaction_size_bytes: transaction_size(invocation)?,
+    })
+}
+
+/// Size of the signed transaction that would carry `invocation`.
+fn transaction_size(invocation: &Invocation) -> Result<u32> {
+    let auth: Vec<SorobanAuthorizationEntry> = invocation
+        .auth
+        .iter()
+        .cloned()
+        .map(|mut entry| {
+            if let SorobanCredentials::Address(creds) = &mut entry.credentials {
+                creds.signature = placeholder_signature()?;
+            }
+            Ok(entry)
+        })
+        .collect::<Result<_>>()?;
+    let envelope = TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx: Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([0; 32])),
+            fee: 0,
+            seq_num: SequenceNumber(0),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                    host_function: invocation.host_function.clone(),
+                    auth: auth.try_into()?,
+                }),
+            }]
+            .try_into()?,
+            ext: TransactionExt::V1(SorobanTransactionData {
+                ext: ExtensionPoint::V0,
+                resources: invocation.resources.clone(),
+                resource_fee: 0,
+            }),
+        },
+        signatures: vec![DecoratedSignature {
+            hint: SignatureHint([0; 4]),
+            signature: Signature(vec![0; 64].try_into()?),
+        }]
+        .try_into()?,
+    });
+    Ok(envelope.to_xdr(Limits::none())?.len() as u32)
+}
+
+/// Stands in for an account's signature in an address credential, which
+/// recording mode leaves empty.
+fn placeholder_signature() -> Result<ScVal> {
+    let entry = |key: &str, len: usize| -> Result<ScMapEntry> {
+        Ok(ScMapEntry {
+            key: ScVal::Symbol(ScSymbol(key.try_into()?)),
+            val: ScVal::Bytes(ScBytes(vec![0; len].try_into()?)),
+        })
     };
+    let map = ScMap(vec![entry("public_key", 32)?, entry("signature", 64)?].try_into()?);
+    Ok(ScVal::Vec(Some(ScVec(
+        vec![ScVal::Map(Some(map))].try_into()?,
+    ))))
+}
+
+/// Rent-relevant entry changes, selected the way the network does.
+fn rent_changes(invocation: &Invocation) -> Vec<RentChange> {
+    invocation
+        .ledger_changes
+        .iter()
+        .filter_map(|change| {
+            let durability = change.durability?;
+            let new_size_bytes = change.new_size_bytes?;
+            let old_live_until_ledger = change.old_live_until.unwrap_or_default();
+            let new_live_until_ledger = change.new_live_until.unwrap_or_default();
+            if old_live_until_ledger >= new_live_until_ledger
+                && change.old_size_bytes >= new_size_bytes
+            {
+                return None;
+            }
+            Some(RentChange {
+                is_persistent: durability == ContractDataDurability::Persistent,
+                old_size_bytes: change.old_size_bytes,
+                new_size_bytes,
+                old_live_until_ledger,
+                new_live_until_ledger,
+            })
+        })
+        .collect()
+}
+
+/// Entries with a TTL that the invocation touched and left in the ledger.
+fn live_entries(invocation: &Invocation) -> BTreeMap<LedgerKey, StoredEntry> {
+    let mut entries = BTreeMap::new();
+    track_live_entries(&mut entries, invocation);
+    entries
+}
+
+fn track_live_entries(entries: &mut BTreeMap<LedgerKey, StoredEntry>, invocation: &Invocation) {
+    for change in &invocation.ledger_changes {
+        let Some(durability) = change.durability else {
+            continue;
+        };
+        let size_bytes = if change.read_only {
+            change.old_size_bytes
+        } else if let Some(size) = change.new_size_bytes {
+            size
+        } else {
+            entries.remove(&change.key);
+            continue;
+        };
+        entries.insert(
+            change.key.clone(),
+            StoredEntry {
+                is_persistent: durability == ContractDataDurability::Persistent,
+                size_bytes,
+            },
+        );
+    }
+}
 
-    // Get base estimate
-    let estimate: CostEstimate = client
-        .post(format!("{}/api/contracts/{}/cost-estimate", api_url, contract_id))
-        .json(&request)
-        .send()
-        .await?
-        .json()
-        .await?;
+fn per_function(calls: &[CallCost]) -> Vec<FunctionCost> {
+    let mut grouped: BTreeMap<&str, Vec<&CallCost>> = BTreeMap::new();
+    for call in calls {
+        grouped.entry(call.function.as_str()).or_default().push(call);
+    }
+    grouped
+        .into_iter()
+        .map(|(function, calls)| {
+            let count = calls.len() as u32;
+            let total_fee: i64 = calls.iter().map(|c| c.fee.total_fee).sum();
+            let instructions: u64 = calls
+                .iter()
+                .map(|c| u64::from(c.resources.instructions))
+                .sum();
+            FunctionCost {
+                function: function.to_string(),
+                calls: count,
+                failures: calls.iter().filter(|c| !c.ok).count() as u32,
+                mean_instructions: instructions / u64::from(count),
+                mean_fee: total_fee / i64::from(count),
+                total_fee,
+            }
+        })
+        .collect()
+}
 
+fn print_local_report(report: &LocalReport) {
+    println!();
     println!("╔═══════════════════════════════════════════════════════╗");
     println!("║           CONTRACT COST ESTIMATION                   ║");
     println!("╚═══════════════════════════════════════════════════════╝");
     println!();
-    println!("Method: {}", estimate.method_name);
-    println!("Invocations: {}", estimate.invocations);
+    println!(
+        "  {:<3} {:<20} {:>11} {:>13} {:>13} {:>8} {:>7} {:>11}",
+        "#", "Function", "Instr", "Read (e/B)", "Write (e/B)", "Events", "Tx B", "Fee"
+    );
+    for (i, call) in report.calls.iter().enumerate() {
+        let r = &call.resources;
+        let name = if call.ok {
+            call.function.normal()
+        } else {
+            call.function.red()
+        };
+        println!(
+            "  {:<3} {:<20} {:>11} {:>13} {:>13} {:>8} {:>7} {:>11}",
+            i + 1,
+            name,
+            r.instructions,
+            format!("{}/{}", r.read_entries, r.read_bytes),
+            format!("{}/{}", r.write_entries, r.write_bytes),
+            r.contract_events_size_bytes,
+            r.transaction_size_bytes,
+            call.fee.total_fee
+        );
+    }
+
+    if report.calls.len() > 1 {
+        println!();
+        println!("Per Function:");
+        for function in &report.functions {
+            let failures = if function.failures > 0 {
+                format!(" ({} failed)", function.failures).red().to_string()
+            } else {
+                String::new()
+            };
+            println!(
+                "  {:<20} {:>4} call(s)  mean {:>10} stroops  total {:>12} stroops{}",
+                function.function,
+                function.calls,
+                function.mean_fee,
+                function.total_fee,
+                failures
+            );
+        }
+    }
+
+    print_fee_breakdown(&report.total, report.total_xlm);
     println!();
-    println!("Cost Breakdown:");
-    println!("  Gas Cost:       {:>12} stroops", estimate.gas_cost);
-    println!("  Storage Cost:   {:>12} stroops", estimate.storage_cost);
-    println!("  Bandwidth Cost: {:>12} stroops", estimate.bandwidth_cost);
-    println!("  ─────────────────────────────────────");
-    println!("  Total:          {:>12} stroops", estimate.total_stroops);
-    println!("  Total:          {:>12.6} XLM", estimate.total_xlm);
+}
+
+fn print_fee_breakdown(fee: &FeeEstimate, total_xlm: f64) {
+    let r = &fee.resource_fee;
     println!();
+    println!("Fee Breakdown:");
+    println!("  Inclusion Fee:  {:>12} stroops", fee.inclusion_fee);
+    println!(
+        "  Resource Fee:   {:>12} stroops",
+        fee.non_refundable_fee + fee.refundable_fee
+    );
+    println!("    Compute:        {:>10}", r.compute);
+    println!("    Ledger Reads:   {:>10}", r.read_entries + r.read_bytes);
+    println!("    Ledger Writes:  {:>10}", r.write_entries + r.write_bytes);
+    println!("    Transaction:    {:>10}", r.historical + r.bandwidth);
+    println!("    Events:         {:>10}", r.events);
+    println!("  Rent:           {:>12} stroops", fee.rent_fee);
+    println!("  ─────────────────────────────────────");
+    println!("  Total:          {:>12} stroops", fee.total_fee);
+    println!("  Total:          {:>12.7} XLM", total_xlm);
+    if fee.horizon_ledgers > 0 {
+        println!();
+        println!(
+            "TTL Extension ({} ledgers, ~{} days): {} stroops",
+            fee.horizon_ledgers,
+            fee.horizon_ledgers / LEDGERS_PER_DAY,
+            fee.ttl_extension_fee
+        );
+    }
+}
+
+// ─── Registry estimates ──────────────────────────────────────────────────────
+
+async fn estimate_remote(
+    api_url: &str,
+    opts: &EstimateOptions<'_>,
+    request: &CostEstimateRequest,
+) -> Result<()> {
+    let client = reqwest::Client::new();
+    let base = format!("{}/api/contracts/{}/cost-estimate", api_url, opts.contract);
+
+    let response = client.post(&base).json(request).send().await?;
+    if !response.status().is_success() {
+        bail!(
+            "Cost estimate failed ({}): {}",
+            response.status(),
+            response.text().await.unwrap_or_default()
+        );
+    }
+    let estimate: CostEstimate = response.json().await?;
+
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&estimate)?);
+    } else {
+        println!("╔═══════════════════════════════════════════════════════╗");
+        println!("║           CONTRACT COST ESTIMATION                   ║");
+        println!("╚═══════════════════════════════════════════════════════╝");
+        println!();
+        println!("Method: {}", estimate.method_name);
+        println!("Invocations: {}", estimate.invocations);
+        println!(
+            "Source: {}",
+            match estimate.source {
+                CostSource::Provided => "submitted measurements",
+                CostSource::Simulation => "RPC simulation",
+                CostSource::Historical => "recorded usage",
+            }
+        );
+        print_fee_breakdown(&estimate.total, estimate.total_xlm);
+        println!();
+    }
 
-    // Show optimization suggestions
-    if optimize {
+    if opts.optimize {
         let optimization: CostOptimization = client
-            .post(format!("{}/api/contracts/{}/cost-estimate/optimize", api_url, contract_id))
+            .post(format!("{}/optimize", base))
             .json(&estimate)
             .send()
             .await?
@@ -102,11 +520,10 @@ pub async fn estimate_costs(
         println!();
     }
 
-    // Show forecast
-    if forecast {
+    if opts.forecast {
         let forecast_data: CostForecast = client
-            .post(format!("{}/api/contracts/{}/cost-estimate/forecast", api_url, contract_id))
-            .json(&request)
+            .post(format!("{}/forecast", base))
+            .json(request)
             .send()
             .await?
             .json()
@@ -122,8 +539,136 @@ pub async fn estimate_costs(
         println!("  Daily:   {:>10.6} XLM", forecast_data.daily_cost_xlm);
         println!("  Monthly: {:>10.6} XLM", forecast_data.monthly_cost_xlm);
         println!("  Yearly:  {:>10.6} XLM", forecast_data.yearly_cost_xlm);
+        println!("  (includes {:.6} XLM/day of rent)", forecast_data.daily_rent_xlm);
         println!();
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::tests::test_contract_wasm;
+    use soroban_env_host::fees::{
+        compute_rent_fee, compute_transaction_resource_fee, FeeConfiguration,
+        LedgerEntryRentChange, RentFeeConfiguration, TransactionResources,
+    };
+
+    fn options<'a>(contract: &'a str, method: &'a str, args: &'a str) -> EstimateOptions<'a> {
+        EstimateOptions {
+            contract,
+            method: Some(method),
+            args: Some(args),
+            scenario: None,
+            invocations: 2,
+            fee_schedule: None,
+            ttl_horizon: LEDGERS_PER_DAY * 30,
+            json: false,
+            optimize: false,
+            forecast: false,
+        }
+    }
+
+    #[test]
+    fn prices_repeated_calls_from_simulation() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm = dir.path().join("contract.wasm");
+        fs::write(&wasm, test_contract_wasm()).unwrap();
+        let path = wasm.to_str().unwrap();
+        let schedule = FeeSchedule::default();
+
+        let report = estimate_local(&options(path, "store", "[7]"), &schedule).unwrap();
+        assert_eq!(report.calls.len(), 2);
+        let (first, second) = (&report.calls[0], &report.calls[1]);
+        assert!(first.ok && second.ok);
+        assert!(first.resources.instructions > 0);
+        assert!(first.resources.write_entries >= 1);
+        assert!(first.resources.transaction_size_bytes > 0);
+        // Rewriting the same value does not grow or extend the instance.
+        assert!(second.fee.rent_fee <= first.fee.rent_fee);
+        assert!(report.total.ttl_extension_fee > 0);
+        assert_eq!(report.functions.len(), 1);
+        assert_eq!(
+            report.total.total_fee,
+            first.fee.total_fee + second.fee.total_fee
+        );
+
+        let events = estimate_local(&options(path, "ping", "[1]"), &schedule).unwrap();
+        assert!(events.calls[0].resources.contract_events_size_bytes > 0);
+        assert!(events.calls[0].fee.refundable_fee > 0);
+    }
+
+    #[test]
+    fn fee_model_matches_host() {
+        let schedule = FeeSchedule::default();
+        let usage = ResourceUsage {
+            instructions: 3_456_789,
+            read_entries: 3,
+            write_entries: 2,
+            read_bytes: 5_000,
+            write_bytes: 777,
+            contract_events_size_bytes: 321,
+            transaction_size_bytes: 1_234,
+        };
+        let ours = schedule.resource_fee(&usage);
+        let host = compute_transaction_resource_fee(
+            &TransactionResources {
+                instructions: usage.instructions,
+                read_entries: usage.read_entries,
+                write_entries: usage.write_entries,
+                read_bytes: usage.read_bytes,
+                write_bytes: usage.write_bytes,
+                contract_events_size_bytes: usage.contract_events_size_bytes,
+                transaction_size_bytes: usage.transaction_size_bytes,
+            },
+            &FeeConfiguration {
+                fee_per_instruction_increment: schedule.fee_per_instruction_increment,
+                fee_per_read_entry: schedule.fee_per_read_entry,
+                fee_per_write_entry: schedule.fee_per_write_entry,
+                fee_per_read_1kb: schedule.fee_per_read_1kb,
+                fee_per_write_1kb: schedule.fee_per_write_1kb,
+                fee_per_historical_1kb: schedule.fee_per_historical_1kb,
+                fee_per_contract_event_1kb: schedule.fee_per_contract_event_1kb,
+                fee_per_transaction_size_1kb: schedule.fee_per_transaction_size_1kb,
+            },
+        );
+        assert_eq!((ours.non_refundable(), ours.refundable()), host);
+
+        let changes = [
+            (true, 0, 300, 0, 1_000 + 120_959),
+            (false, 100, 900, 5_000, 5_000),
+            (true, 400, 400, 2_000, 90_000),
+        ];
+        let ours: Vec<RentChange> = changes
+            .iter()
+            .map(|&(is_persistent, old, new, old_ttl, new_ttl)| RentChange {
+                is_persistent,
+                old_size_bytes: old,
+                new_size_bytes: new,
+                old_live_until_ledger: old_ttl,
+                new_live_until_ledger: new_ttl,
+            })
+            .collect();
+        let host: Vec<LedgerEntryRentChange> = changes
+            .iter()
+            .map(|&(is_persistent, old, new, old_ttl, new_ttl)| LedgerEntryRentChange {
+                is_persistent,
+                old_size_bytes: old,
+                new_size_bytes: new,
+                old_live_until_ledger: old_ttl,
+                new_live_until_ledger: new_ttl,
+            })
+            .collect();
+        let rent_config = RentFeeConfiguration {
+            fee_per_write_1kb: schedule.fee_per_write_1kb,
+            fee_per_write_entry: schedule.fee_per_write_entry,
+            persistent_rent_rate_denominator: schedule.persistent_rent_rate_denominator,
+            temporary_rent_rate_denominator: schedule.temporary_rent_rate_denominator,
+        };
+        assert_eq!(
+            schedule.rent_fee(&ours, 1_000),
+            compute_rent_fee(&host, &rent_config, 1_000)
+        );
+    }
+}
@@ -1,93 +1,126 @@
 #![allow(dead_code)]
 
-use anyhow::{Context, Result};
+//! Contract coverage measured on the WASM that actually runs on-chain.
+//!
+//! The contract is built for `wasm32-unknown-unknown`, instrumented at the
+//! basic-block level and executed in a local Soroban host against the test
+//! scenarios (and optionally spec-driven fuzz cases). Hits are mapped back to
+//! source lines through the module's DWARF line table when one is present.
+
+use crate::fuzz::{ArgType, FuzzValue, Fuzzer};
+use crate::sandbox::{build_contract, ContractSpec, Invocation, LocalLedger, SandboxLimits};
+use crate::test_framework::{load_test_scenarios, TestRunner, TestScenario};
+use crate::wasm_instrument::{self, BasicBlock, InstrumentedModule};
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
-use serde_json::Value;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use serde_json::{json, Value};
+use soroban_env_host::xdr::{ScAddress, ScSpecTypeDef};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+
+/// Fuzz cases are seeded so repeated runs report the same coverage.
+const FUZZ_SEED: u64 = 0x5eed_c0de;
 
-pub async fn run(contract_path: &str, _tests: &str, threshold: f64, output: &str) -> Result<()> {
+/// DWARF tombstone for code that the linker discarded.
+const DEAD_CODE_ADDRESS: u64 = 0xffff_fff0;
+
+pub async fn run(
+    contract_path: &str,
+    tests: &str,
+    threshold: f64,
+    output: &str,
+    fuzz_cases: usize,
+) -> Result<()> {
     println!("\n{}", "Running Code Coverage Analysis...".bold().cyan());
     println!("{}", "=".repeat(80).cyan());
 
-    ensure_tarpaulin_installed()?;
-
     let path = Path::new(contract_path);
     if !path.exists() {
         anyhow::bail!("Contract path does not exist: {}", contract_path);
     }
 
-    let out_dir = Path::new(contract_path).join(output);
-    if !out_dir.exists() {
-        fs::create_dir_all(&out_dir).context("Failed to create output directory")?;
-    }
+    let artifact = resolve_artifact(path)?;
+    println!(
+        "{} Instrumenting {}",
+        "→".bright_black(),
+        artifact.wasm_path.display()
+    );
+    let module = wasm_instrument::instrument(&artifact.wasm)?;
+    let spec = ContractSpec::from_wasm(&artifact.wasm)?;
 
-    // We output paths relative to out_dir
-    let out_dir_abs = fs::canonicalize(&out_dir).unwrap_or(out_dir.clone());
-    let out_dir_str = out_dir_abs.to_string_lossy().to_string();
-
-    let mut cmd = Command::new("cargo");
-    cmd.current_dir(path);
-    cmd.args(&[
-        "tarpaulin",
-        "--out",
-        "Html",
-        "--out",
-        "Json",
-        "--output-dir",
-        &out_dir_str,
-        "--branch",
-    ]);
-
-    if threshold > 0.0 {
-        cmd.arg("--fail-under");
-        cmd.arg(threshold.to_string());
-    }
-
-    let covignore_path = path.join(".covignore");
-    if covignore_path.exists() {
-        if let Ok(content) = fs::read_to_string(&covignore_path) {
-            let excludes: Vec<String> = content
-                .lines()
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty() && !s.starts_with('#'))
-                .map(String::from)
-                .collect();
+    let mut collector = Collector::new(&module, spec);
 
-            if !excludes.is_empty() {
-                cmd.arg("--exclude-files");
-                for ext in excludes {
-                    cmd.arg(&ext);
-                }
-            }
-        }
+    let scenarios = load_test_scenarios(Path::new(tests))?;
+    if scenarios.is_empty() && fuzz_cases == 0 {
+        bail!("No test scenarios found at {}", tests);
+    }
+    for (file, scenario) in &scenarios {
+        println!("{} Scenario: {}", "→".bright_black(), scenario.name);
+        collector
+            .run_scenario(scenario)
+            .with_context(|| format!("scenario '{}' ({})", scenario.name, file.display()))?;
+    }
+    if fuzz_cases > 0 {
+        println!(
+            "{} Fuzzing {} case(s) per exported function",
+            "→".bright_black(),
+            fuzz_cases
+        );
+        collector.run_fuzz(fuzz_cases)?;
     }
 
-    println!("{} Executing cargo tarpaulin...", "→".bright_black());
-
-    let status = cmd.status().context("Failed to execute cargo tarpaulin")?;
+    let excludes = read_covignore(artifact.source_root.as_deref().unwrap_or(path));
+    let lines = read_line_table(&artifact.wasm)?;
+    let report = Report::build(&collector, &lines, &artifact, &excludes);
 
-    let report_json = out_dir_abs.join("tarpaulin-report.json");
-    if report_json.exists() {
-        let json_content = fs::read_to_string(&report_json)?;
-        if let Ok(report) = serde_json::from_str::<Value>(&json_content) {
-            parse_and_print_report(&report);
-        }
+    let out_dir = if Path::new(output).is_absolute() {
+        PathBuf::from(output)
+    } else if path.is_dir() {
+        path.join(output)
     } else {
-        println!("{}", "⚠ Warning: tarpaulin-report.json not found".yellow());
-    }
+        path.parent().unwrap_or(Path::new(".")).join(output)
+    };
+    fs::create_dir_all(&out_dir).context("Failed to create output directory")?;
+    let out_dir_abs = fs::canonicalize(&out_dir).unwrap_or(out_dir.clone());
+
+    fs::write(out_dir_abs.join("lcov.info"), report.to_lcov(&artifact))?;
+    fs::write(
+        out_dir_abs.join("coverage.json"),
+        serde_json::to_string_pretty(&report.to_json())?,
+    )?;
+    report.write_html(&out_dir_abs)?;
+
+    report.print_summary();
+
+    let percent = report.percent();
+    track_trend(&out_dir_abs.join("coverage-trend.json"), percent).unwrap_or_else(|e| {
+        println!("{} Failed to save trend data: {}", "⚠".yellow(), e);
+    });
 
     println!(
-        "\n{} HTML report generated at: {}",
+        "\n{} LCOV report written to: {}",
         "✓".green(),
-        out_dir_abs.join("tarpaulin-report.html").display()
+        out_dir_abs.join("lcov.info").display()
+    );
+    println!(
+        "{} HTML report generated at: {}",
+        "✓".green(),
+        out_dir_abs.join("index.html").display()
     );
 
-    if !status.success() {
+    if percent < threshold {
         println!(
             "\n{}",
-            "Coverage checks failed or threshold not met.".red().bold()
+            format!(
+                "Coverage {:.2}% is below the threshold of {:.2}%.",
+                percent, threshold
+            )
+            .red()
+            .bold()
         );
         std::process::exit(1);
     } else {
@@ -100,82 +133,847 @@ pub async fn run(contract_path: &str, _tests: &str, threshold: f64, output: &str
     Ok(())
 }
 
-fn ensure_tarpaulin_installed() -> Result<()> {
-    let check = Command::new("cargo")
-        .args(&["tarpaulin", "--version"])
-        .output();
+struct Artifact {
+    wasm: Vec<u8>,
+    wasm_path: PathBuf,
+    /// Crate directory, when coverage was requested for a source tree.
+    source_root: Option<PathBuf>,
+}
 
-    if check.is_err() || !check.unwrap().status.success() {
-        println!(
-            "{}",
-            "cargo-tarpaulin not found. Installing... (this may take a while)"
-                .yellow()
-                .bold()
-        );
-        let install = Command::new("cargo")
-            .args(&["install", "cargo-tarpaulin"])
-            .status()
-            .context("Failed to run cargo install cargo-tarpaulin")?;
-
-        if !install.success() {
-            anyhow::bail!(
-                "Installation failed. Please install manually: cargo install cargo-tarpaulin"
+fn resolve_artifact(path: &Path) -> Result<Artifact> {
+    if path.is_file() {
+        let wasm = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        return Ok(Artifact {
+            wasm,
+            wasm_path: path.to_path_buf(),
+            source_root: None,
+        });
+    }
+
+    println!(
+        "{} Building contract for wasm32-unknown-unknown...",
+        "→".bright_black()
+    );
+    let wasm_path = build_contract(path)?;
+    let wasm =
+        fs::read(&wasm_path).with_context(|| format!("Failed to read {}", wasm_path.display()))?;
+    Ok(Artifact {
+        wasm,
+        wasm_path,
+        source_root: Some(fs::canonicalize(path).unwrap_or(path.to_path_buf())),
+    })
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+struct ExportCalls {
+    calls: u64,
+    failures: u64,
+}
+
+/// Executes the instrumented contract and accumulates per-block hit counts.
+struct Collector<'a> {
+    module: &'a InstrumentedModule,
+    spec: ContractSpec,
+    /// Number of invocations that reached each block.
+    block_hits: Vec<u64>,
+    calls: BTreeMap<String, ExportCalls>,
+    fuzz_skipped: Vec<String>,
+}
+
+impl<'a> Collector<'a> {
+    fn new(module: &'a InstrumentedModule, spec: ContractSpec) -> Self {
+        Self {
+            module,
+            spec,
+            block_hits: vec![0; module.map.blocks.len()],
+            calls: BTreeMap::new(),
+            fuzz_skipped: Vec::new(),
+        }
+    }
+
+    fn deploy(&self) -> Result<(LocalLedger, ScAddress)> {
+        let mut ledger = LocalLedger::with_limits(SandboxLimits::unbounded());
+        let contract = ledger.deploy(&self.module.wasm)?;
+        Ok((ledger, contract))
+    }
+
+    fn record(&mut self, contract: &ScAddress, function: &str, invocation: &Invocation) {
+        for block in wasm_instrument::hit_blocks(contract, &invocation.diagnostic_events) {
+            if let Some(hits) = self.block_hits.get_mut(block as usize) {
+                *hits += 1;
+            }
+        }
+        let entry = self.calls.entry(function.to_string()).or_default();
+        entry.calls += 1;
+        if !invocation.is_ok() {
+            entry.failures += 1;
+        }
+    }
+
+    /// Replays a scenario through the test runner, counting the blocks each
+    /// invocation reaches. Failing assertions don't affect coverage but are
+    /// reported so that a broken scenario isn't mistaken for missing tests.
+    fn run_scenario(&mut self, scenario: &TestScenario) -> Result<()> {
+        let mut runner = TestRunner::from_wasm("contract", self.module.wasm.clone())?;
+        // Instrumented, unoptimised builds are far larger than what is
+        // deployed, so network limits would reject them.
+        runner.set_limits(SandboxLimits::unbounded());
+        let result = runner
+            .run_scenario_observed(scenario, &mut |contract, function, invocation| {
+                self.record(contract, function, invocation)
+            })?;
+        if !result.passed {
+            println!(
+                "  {} scenario failed: {}",
+                "⚠".yellow(),
+                result.error.as_deref().unwrap_or("unknown error")
             );
         }
-        println!("{}", "✓ cargo-tarpaulin installed successfully.".green());
+        Ok(())
     }
 
-    Ok(())
+    /// Calls every exported function with random arguments derived from its
+    /// spec. Functions taking types the fuzzer cannot generate are skipped.
+    fn run_fuzz(&mut self, cases: usize) -> Result<()> {
+        let (mut ledger, contract) = self.deploy()?;
+        let accounts: Vec<String> = (0..3).map(|i| format!("fuzz{}", i)).collect();
+        let mut addresses = HashMap::new();
+        for alias in &accounts {
+            addresses.insert(alias.clone(), ScAddress::Account(ledger.create_account()));
+        }
+
+        let mut rng = StdRng::seed_from_u64(FUZZ_SEED);
+        let functions: Vec<_> = self.spec.functions().cloned().collect();
+        for function in functions {
+            let name = function.name.to_utf8_string_lossy();
+            let Some(types) = function
+                .inputs
+                .iter()
+                .map(|input| arg_type(&input.type_))
+                .collect::<Option<Vec<_>>>()
+            else {
+                self.fuzz_skipped.push(name);
+                continue;
+            };
+            for _ in 0..cases {
+                let args: Vec<Value> = types
+                    .iter()
+                    .map(|ty| {
+                        let value = Fuzzer::generate_value_static(ty, &mut rng);
+                        fuzz_value_to_json(&value, &accounts, &mut rng)
+                    })
+                    .collect();
+                // Randomly generated maps may repeat keys; such inputs can't
+                // be encoded and are simply dropped.
+                let Ok(args) = self.spec.function_args(&name, &args, &addresses) else {
+                    continue;
+                };
+                let invocation = ledger.invoke(&contract, &name, args)?;
+                self.record(&contract, &name, &invocation);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn arg_type(ty: &ScSpecTypeDef) -> Option<ArgType> {
+    Some(match ty {
+        ScSpecTypeDef::I32 => ArgType::I32,
+        ScSpecTypeDef::I64 => ArgType::I64,
+        ScSpecTypeDef::U32 => ArgType::U32,
+        ScSpecTypeDef::U64 => ArgType::U64,
+        ScSpecTypeDef::Bool => ArgType::Bool,
+        ScSpecTypeDef::Bytes => ArgType::Bytes,
+        ScSpecTypeDef::BytesN(n) => ArgType::BytesN(n.n as usize),
+        ScSpecTypeDef::String => ArgType::String,
+        ScSpecTypeDef::Symbol => ArgType::Symbol,
+        ScSpecTypeDef::Address => ArgType::Address,
+        ScSpecTypeDef::Vec(inner) => ArgType::Vec(Box::new(arg_type(&inner.element_type)?)),
+        ScSpecTypeDef::Map(inner) => ArgType::Map(
+            Box::new(arg_type(&inner.key_type)?),
+            Box::new(arg_type(&inner.value_type)?),
+        ),
+        _ => return None,
+    })
+}
+
+/// Addresses are drawn from a small pool of funded accounts, since random
+/// strkeys would fail checksum validation before reaching the contract.
+fn fuzz_value_to_json(value: &FuzzValue, accounts: &[String], rng: &mut StdRng) -> Value {
+    match value {
+        FuzzValue::I32(v) => json!(v),
+        FuzzValue::I64(v) => json!(v),
+        FuzzValue::U32(v) => json!(v),
+        FuzzValue::U64(v) => json!(v),
+        FuzzValue::Bool(v) => json!(v),
+        FuzzValue::Bytes(bytes) => json!(hex::encode(bytes)),
+        FuzzValue::String(s) | FuzzValue::Symbol(s) => json!(s),
+        FuzzValue::Address(_) => json!(format!("@{}", accounts[rng.gen_range(0..accounts.len())])),
+        FuzzValue::Vec(items) => Value::Array(
+            items
+                .iter()
+                .map(|v| fuzz_value_to_json(v, accounts, rng))
+                .collect(),
+        ),
+        FuzzValue::Map(entries) => Value::Array(
+            entries
+                .iter()
+                .map(|(k, v)| {
+                    json!([
+                        fuzz_value_to_json(k, accounts, rng),
+                        fuzz_value_to_json(v, accounts, rng)
+                    ])
+                })
+                .collect(),
+        ),
+        FuzzValue::Null => Value::Null,
+    }
+}
+
+/// One row of the DWARF line table, covering `start..end` in the code section.
+#[derive(Debug, Clone)]
+struct LineRow {
+    start: u64,
+    end: u64,
+    file: PathBuf,
+    line: u32,
+}
+
+fn read_line_table(wasm: &[u8]) -> Result<Vec<LineRow>> {
+    let sections = wasm_instrument::debug_sections(wasm)?;
+    if !sections.contains_key(".debug_line") {
+        return Ok(Vec::new());
+    }
+    let load =
+        |id: gimli::SectionId| -> Result<gimli::EndianSlice<gimli::LittleEndian>, gimli::Error> {
+            let data = sections.get(id.name()).map(Vec::as_slice).unwrap_or(&[]);
+            Ok(gimli::EndianSlice::new(data, gimli::LittleEndian))
+        };
+    let dwarf = gimli::Dwarf::load(load).context("Failed to load DWARF sections")?;
+
+    let mut rows_out = Vec::new();
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let Some(program) = unit.line_program.clone() else {
+            continue;
+        };
+        let mut rows = program.rows();
+        let mut previous: Option<(u64, PathBuf, u32)> = None;
+        let mut sequence_start = true;
+        let mut dead = false;
+        while let Some((header, row)) = rows.next_row()? {
+            let address = row.address();
+            if sequence_start {
+                dead = address == 0 || address >= DEAD_CODE_ADDRESS;
+                sequence_start = false;
+            }
+            if let Some((start, file, line)) = previous.take() {
+                if address > start {
+                    rows_out.push(LineRow {
+                        start,
+                        end: address,
+                        file,
+                        line,
+                    });
+                }
+            }
+            if row.end_sequence() {
+                sequence_start = true;
+                continue;
+            }
+            if dead {
+                continue;
+            }
+            let Some(line) = row.line() else {
+                continue;
+            };
+            let Some(file) = row
+                .file(header)
+                .and_then(|file| file_path(&dwarf, &unit, header, file))
+            else {
+                continue;
+            };
+            previous = Some((address, file, line.get() as u32));
+        }
+    }
+    rows_out.sort_by_key(|row| row.start);
+    Ok(rows_out)
+}
+
+fn file_path<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    header: &gimli::LineProgramHeader<R>,
+    file: &gimli::FileEntry<R>,
+) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    if let Some(comp_dir) = &unit.comp_dir {
+        path.push(comp_dir.to_string_lossy().ok()?.as_ref());
+    }
+    if let Some(dir) = file.directory(header) {
+        let dir = dwarf.attr_string(unit, dir).ok()?;
+        path.push(dir.to_string_lossy().ok()?.as_ref());
+    }
+    let name = dwarf.attr_string(unit, file.path_name()).ok()?;
+    path.push(name.to_string_lossy().ok()?.as_ref());
+    Some(path)
+}
+
+fn read_covignore(root: &Path) -> Vec<String> {
+    fs::read_to_string(root.join(".covignore"))
+        .map(|content| {
+            content
+                .lines()
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty() && !s.starts_with('#'))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Matches `*`, `**` and `?` against a `/`-separated path.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn matches(p: &[u8], s: &[u8]) -> bool {
+        match p.split_first() {
+            None => s.is_empty(),
+            Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+                let rest = rest[1..].strip_prefix(b"/").unwrap_or(&rest[1..]);
+                (0..=s.len()).any(|i| matches(rest, &s[i..]))
+            }
+            Some((b'*', rest)) => (0..=s.len())
+                .take_while(|&i| i == 0 || s[i - 1] != b'/')
+                .any(|i| matches(rest, &s[i..])),
+            Some((b'?', rest)) => !s.is_empty() && s[0] != b'/' && matches(rest, &s[1..]),
+            Some((c, rest)) => s.first() == Some(c) && matches(rest, &s[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), path.as_bytes())
+}
+
+/// Decides whether a source file belongs in the report: files inside the
+/// contract crate when building from source, otherwise anything that isn't
+/// the standard library or a registry dependency. `.covignore` patterns are
+/// matched against the crate-relative path and the full path.
+fn include_file(file: &Path, root: Option<&Path>, excludes: &[String]) -> bool {
+    let full = file.to_string_lossy();
+    let relative = match root {
+        Some(root) => match file.strip_prefix(root) {
+            Ok(relative) => relative.to_string_lossy().into_owned(),
+            Err(_) => return false,
+        },
+        None => {
+            let foreign = ["/rustc/", "/.cargo/registry/", "/.cargo/git/", "/.rustup/"];
+            if foreign.iter().any(|marker| full.contains(marker)) {
+                return false;
+            }
+            full.to_string()
+        }
+    };
+    !excludes
+        .iter()
+        .any(|pattern| glob_match(pattern, &relative) || glob_match(pattern, &full))
+}
+
+#[derive(Debug, Default, Serialize)]
+struct FunctionCoverage {
+    name: String,
+    line: u32,
+    hits: u64,
+    blocks: usize,
+    blocks_hit: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct FileCoverage {
+    path: PathBuf,
+    /// Hit count per coverable line.
+    lines: BTreeMap<u32, u64>,
+    functions: Vec<FunctionCoverage>,
+}
+
+impl FileCoverage {
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|&&hits| hits > 0).count()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ExportCoverage {
+    name: String,
+    calls: u64,
+    failures: u64,
+    functions: usize,
+    blocks: usize,
+    blocks_hit: usize,
+}
+
+impl ExportCoverage {
+    fn percent(&self) -> f64 {
+        percentage(self.blocks_hit, self.blocks)
+    }
+}
+
+struct Report {
+    files: Vec<FileCoverage>,
+    /// Functions without line information, reported by name only.
+    unmapped: Vec<FunctionCoverage>,
+    exports: Vec<ExportCoverage>,
+    blocks: usize,
+    blocks_hit: usize,
+    has_lines: bool,
+    fuzz_skipped: Vec<String>,
+}
+
+fn percentage(hit: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        hit as f64 / total as f64 * 100.0
+    }
 }
 
-fn parse_and_print_report(report: &Value) {
-    // Tarpaulin JSON format varies, but usually it lists coverage at the root or under 'files'.
-    // If we can't easily parse perfectly, we can at least show trend or summaries if tarpaulin provides them.
-    // Tarpaulin provides total coverage if we look deeply, or we can just let tarpaulin's cli output guide the user
-    // However, for "trend" track coverage over versions as per spec.
-
-    // Simplistic read of coverage % from tarpaulin's json if we wanted to process lines.
-    // Often tarpaulin prints the summary to stdout anyway. We'll leave stdout visible during run.
-
-    // We can save a small trend file
-    let mut covered_lines = 0;
-    let mut coverable_lines = 0;
-
-    if let Some(files) = report.get("files").and_then(|f| f.as_array()) {
-        for file in files {
-            if let Some(traces) = file.get("traces").and_then(|t| t.as_array()) {
-                for trace in traces {
-                    if let Some(line) = trace.get("line").and_then(|l| l.as_u64()) {
-                        coverable_lines += 1;
-                        if let Some(stats) = trace.get("stats").and_then(|s| s.as_object()) {
-                            if stats.values().any(|v| v.as_u64().unwrap_or(0) > 0) {
-                                covered_lines += 1;
-                            }
-                        }
+impl Report {
+    fn build(
+        collector: &Collector<'_>,
+        rows: &[LineRow],
+        artifact: &Artifact,
+        excludes: &[String],
+    ) -> Self {
+        let map = &collector.module.map;
+        let hits = &collector.block_hits;
+        let root = artifact.source_root.as_deref();
+        let names: HashMap<u32, &str> = map
+            .exports
+            .iter()
+            .map(|(name, index)| (*index, name.as_str()))
+            .collect();
+
+        let mut files: BTreeMap<PathBuf, FileCoverage> = BTreeMap::new();
+        for row in rows {
+            if !include_file(&row.file, root, excludes) {
+                continue;
+            }
+            let Some(row_hits) = blocks_overlapping(&map.blocks, row.start, row.end)
+                .map(|block| hits[block.id as usize])
+                .max()
+            else {
+                continue;
+            };
+            let file = files
+                .entry(row.file.clone())
+                .or_insert_with(|| FileCoverage {
+                    path: row.file.clone(),
+                    ..Default::default()
+                });
+            let line = file.lines.entry(row.line).or_insert(0);
+            *line = (*line).max(row_hits);
+        }
+
+        // A function is attributed to the file of its entry block.
+        let mut included = BTreeSet::new();
+        let mut unmapped = Vec::new();
+        for function in &map.functions {
+            let entry = &map.blocks[function.blocks.start as usize];
+            let blocks_hit = function
+                .blocks
+                .clone()
+                .filter(|&b| hits[b as usize] > 0)
+                .count();
+            let coverage = FunctionCoverage {
+                name: names
+                    .get(&function.index)
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| demangle(&function.name)),
+                line: 0,
+                hits: hits[entry.id as usize],
+                blocks: function.blocks.len(),
+                blocks_hit,
+            };
+            let location = rows.iter().find(|row| {
+                row.start <= entry.code_range.start as u64
+                    && (entry.code_range.start as u64) < row.end
+            });
+            match location {
+                Some(row) if !rows.is_empty() => {
+                    if let Some(file) = files.get_mut(&row.file) {
+                        included.insert(function.index);
+                        file.functions.push(FunctionCoverage {
+                            line: row.line,
+                            ..coverage
+                        });
                     }
                 }
+                _ if rows.is_empty() => {
+                    included.insert(function.index);
+                    unmapped.push(coverage);
+                }
+                _ => {}
             }
         }
+
+        let mut exports = Vec::new();
+        for (name, index) in &map.exports {
+            let mut reachable = map.reachable_from(*index);
+            reachable.retain(|f| f == index || included.contains(f));
+            let blocks: Vec<u32> = reachable
+                .iter()
+                .filter_map(|f| map.function(*f))
+                .flat_map(|f| f.blocks.clone())
+                .collect();
+            let calls = collector.calls.get(name).cloned().unwrap_or_default();
+            exports.push(ExportCoverage {
+                name: name.clone(),
+                calls: calls.calls,
+                failures: calls.failures,
+                functions: reachable.len(),
+                blocks: blocks.len(),
+                blocks_hit: blocks.iter().filter(|&&b| hits[b as usize] > 0).count(),
+            });
+        }
+
+        let counted: Vec<&BasicBlock> = map
+            .blocks
+            .iter()
+            .filter(|block| included.contains(&block.function))
+            .collect();
+
+        Self {
+            files: files.into_values().collect(),
+            unmapped,
+            exports,
+            blocks: counted.len(),
+            blocks_hit: counted.iter().filter(|b| hits[b.id as usize] > 0).count(),
+            has_lines: !rows.is_empty(),
+            fuzz_skipped: collector.fuzz_skipped.clone(),
+        }
     }
 
-    if coverable_lines > 0 {
-        let percent = (covered_lines as f64 / coverable_lines as f64) * 100.0;
+    fn lines_found(&self) -> usize {
+        self.files.iter().map(|f| f.lines.len()).sum()
+    }
+
+    fn lines_hit(&self) -> usize {
+        self.files.iter().map(FileCoverage::lines_hit).sum()
+    }
+
+    /// Line coverage when the module carries debug info, block coverage
+    /// otherwise.
+    fn percent(&self) -> f64 {
+        if self.has_lines {
+            percentage(self.lines_hit(), self.lines_found())
+        } else {
+            percentage(self.blocks_hit, self.blocks)
+        }
+    }
+
+    fn print_summary(&self) {
         println!("\n{}", "Coverage Summary".bold().magenta());
-        println!("  Lines covered: {}/{}", covered_lines, coverable_lines);
-        println!("  Total line coverage: {:.2}%", percent);
+        if self.has_lines {
+            println!(
+                "  Lines covered: {}/{}",
+                self.lines_hit(),
+                self.lines_found()
+            );
+        } else {
+            println!(
+                "  {}",
+                "No DWARF line table in the module; reporting block coverage only.".yellow()
+            );
+        }
+        println!("  Blocks covered: {}/{}", self.blocks_hit, self.blocks);
+        println!("  Total coverage: {:.2}%", self.percent());
 
-        // Save trend data
-        let trend_file = "coverage-trend.json";
-        track_trend(trend_file, percent).unwrap_or_else(|e| {
-            println!("{} Failed to save trend data: {}", "⚠".yellow(), e);
-        });
+        println!("\n{}", "Exported functions".bold());
+        for export in &self.exports {
+            let percent = format!("{:6.2}%", export.percent());
+            let percent = if export.calls == 0 {
+                percent.red()
+            } else if export.blocks_hit == export.blocks {
+                percent.green()
+            } else {
+                percent.yellow()
+            };
+            println!(
+                "  {} {:<32} {} call(s), {} failed",
+                percent, export.name, export.calls, export.failures
+            );
+        }
+        if !self.fuzz_skipped.is_empty() {
+            println!(
+                "\n  {} not fuzzed (unsupported argument types): {}",
+                "⚠".yellow(),
+                self.fuzz_skipped.join(", ")
+            );
+        }
+    }
+
+    fn to_lcov(&self, artifact: &Artifact) -> String {
+        let mut out = String::from("TN:\n");
+        for file in &self.files {
+            out.push_str(&format!("SF:{}\n", file.path.display()));
+            push_lcov_functions(&mut out, &file.functions);
+            for (line, hits) in &file.lines {
+                out.push_str(&format!("DA:{},{}\n", line, hits));
+            }
+            out.push_str(&format!("LF:{}\n", file.lines.len()));
+            out.push_str(&format!("LH:{}\n", file.lines_hit()));
+            out.push_str("end_of_record\n");
+        }
+        if !self.unmapped.is_empty() {
+            out.push_str(&format!("SF:{}\n", artifact.wasm_path.display()));
+            push_lcov_functions(&mut out, &self.unmapped);
+            out.push_str("end_of_record\n");
+        }
+        out
     }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "percent": self.percent(),
+            "lines_found": self.lines_found(),
+            "lines_hit": self.lines_hit(),
+            "blocks_found": self.blocks,
+            "blocks_hit": self.blocks_hit,
+            "exports": self.exports,
+            "files": self.files.iter().map(|file| json!({
+                "path": file.path,
+                "lines_found": file.lines.len(),
+                "lines_hit": file.lines_hit(),
+                "uncovered_lines": file.lines.iter()
+                    .filter(|(_, &hits)| hits == 0)
+                    .map(|(line, _)| line)
+                    .collect::<Vec<_>>(),
+                "functions": file.functions,
+            })).collect::<Vec<_>>(),
+            "functions": self.unmapped,
+            "fuzz_skipped": self.fuzz_skipped,
+        })
+    }
+
+    fn write_html(&self, out_dir: &Path) -> Result<()> {
+        let files_dir = out_dir.join("files");
+        fs::create_dir_all(&files_dir)?;
+
+        let mut body = format!(
+            "<h1>Contract coverage</h1>\n<p class=\"summary\">{:.2}% &mdash; {} of {} blocks",
+            self.percent(),
+            self.blocks_hit,
+            self.blocks
+        );
+        if self.has_lines {
+            body.push_str(&format!(
+                ", {} of {} lines",
+                self.lines_hit(),
+                self.lines_found()
+            ));
+        }
+        body.push_str("</p>\n<h2>Exported functions</h2>\n<table>\n");
+        body.push_str("<tr><th>Function</th><th>Calls</th><th>Failed</th><th>Blocks</th><th>Coverage</th></tr>\n");
+        for export in &self.exports {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}/{}</td>{}</tr>\n",
+                escape_html(&export.name),
+                export.calls,
+                export.failures,
+                export.blocks_hit,
+                export.blocks,
+                percent_cell(export.percent())
+            ));
+        }
+        body.push_str("</table>\n");
+
+        if !self.files.is_empty() {
+            body.push_str("<h2>Source files</h2>\n<table>\n<tr><th>File</th><th>Lines</th><th>Coverage</th></tr>\n");
+            for (i, file) in self.files.iter().enumerate() {
+                let page = format!("files/{}.html", i);
+                body.push_str(&format!(
+                    "<tr><td><a href=\"{}\">{}</a></td><td>{}/{}</td>{}</tr>\n",
+                    page,
+                    escape_html(&file.path.display().to_string()),
+                    file.lines_hit(),
+                    file.lines.len(),
+                    percent_cell(percentage(file.lines_hit(), file.lines.len()))
+                ));
+                fs::write(out_dir.join(&page), file_page(file))?;
+            }
+            body.push_str("</table>\n");
+        }
+
+        if !self.unmapped.is_empty() {
+            body.push_str("<h2>Functions</h2>\n<table>\n<tr><th>Function</th><th>Hits</th><th>Blocks</th><th>Coverage</th></tr>\n");
+            for function in &self.unmapped {
+                body.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}/{}</td>{}</tr>\n",
+                    escape_html(&function.name),
+                    function.hits,
+                    function.blocks_hit,
+                    function.blocks,
+                    percent_cell(percentage(function.blocks_hit, function.blocks))
+                ));
+            }
+            body.push_str("</table>\n");
+        }
+
+        fs::write(
+            out_dir.join("index.html"),
+            html_page("Contract coverage", &body),
+        )?;
+        Ok(())
+    }
+}
+
+fn blocks_overlapping(
+    blocks: &[BasicBlock],
+    start: u64,
+    end: u64,
+) -> impl Iterator<Item = &BasicBlock> {
+    let first = blocks.partition_point(|b| (b.code_range.end as u64) <= start);
+    blocks[first..]
+        .iter()
+        .take_while(move |b| (b.code_range.start as u64) < end)
+}
+
+fn push_lcov_functions(out: &mut String, functions: &[FunctionCoverage]) {
+    for function in functions {
+        out.push_str(&format!("FN:{},{}\n", function.line, function.name));
+    }
+    for function in functions {
+        out.push_str(&format!("FNDA:{},{}\n", function.hits, function.name));
+    }
+    out.push_str(&format!("FNF:{}\n", functions.len()));
+    out.push_str(&format!(
+        "FNH:{}\n",
+        functions.iter().filter(|f| f.hits > 0).count()
+    ));
+}
+
+fn file_page(file: &FileCoverage) -> String {
+    let title = file.path.display().to_string();
+    let mut body = format!(
+        "<p><a href=\"../index.html\">&larr; index</a></p>\n<h1>{}</h1>\n<p class=\"summary\">{} of {} lines covered</p>\n",
+        escape_html(&title),
+        file.lines_hit(),
+        file.lines.len()
+    );
+    match fs::read_to_string(&file.path) {
+        Ok(source) => {
+            body.push_str("<pre class=\"source\">");
+            for (i, text) in source.lines().enumerate() {
+                let number = i as u32 + 1;
+                let (class, hits) = match file.lines.get(&number) {
+                    Some(0) => ("miss", "0".to_string()),
+                    Some(hits) => ("hit", hits.to_string()),
+                    None => ("", String::new()),
+                };
+                body.push_str(&format!(
+                    "<span class=\"{}\"><span class=\"no\">{:>5}</span><span class=\"count\">{:>6}</span> {}</span>\n",
+                    class,
+                    number,
+                    hits,
+                    escape_html(text)
+                ));
+            }
+            body.push_str("</pre>\n");
+        }
+        Err(_) => {
+            body.push_str("<p>Source file not available; uncovered lines:</p>\n<p>");
+            let missed: Vec<String> = file
+                .lines
+                .iter()
+                .filter(|(_, &hits)| hits == 0)
+                .map(|(line, _)| line.to_string())
+                .collect();
+            body.push_str(&missed.join(", "));
+            body.push_str("</p>\n");
+        }
+    }
+    html_page(&title, &body)
+}
+
+fn percent_cell(percent: f64) -> String {
+    let class = if percent >= 80.0 {
+        "high"
+    } else if percent >= 50.0 {
+        "medium"
+    } else {
+        "low"
+    };
+    format!("<td class=\"{}\">{:.2}%</td>", class, percent)
+}
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+td, th {{ border: 1px solid #ccc; padding: 4px 10px; text-align: left; }}
+.high {{ background: #c8f7c5; }} .medium {{ background: #fbeeb8; }} .low {{ background: #f7c5c5; }}
+pre.source {{ font-size: 13px; }}
+pre.source .hit {{ background: #e6ffed; display: block; }}
+pre.source .miss {{ background: #ffeef0; display: block; }}
+.no, .count {{ color: #888; margin-right: 1em; }}
+</style>
+</head>
+<body>
+{}
+</body>
+</html>
+"#,
+        escape_html(title),
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
-fn track_trend(trend_file: &str, current_percent: f64) -> Result<()> {
+/// Demangles legacy Rust symbols (`_ZN...E`), dropping the trailing hash.
+fn demangle(name: &str) -> String {
+    let Some(mut rest) = name.strip_prefix("_ZN").and_then(|s| s.strip_suffix('E')) else {
+        return name.to_string();
+    };
+    let mut parts = Vec::new();
+    while !rest.is_empty() {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let Ok(len) = rest[..digits].parse::<usize>() else {
+            return name.to_string();
+        };
+        if digits + len > rest.len() {
+            return name.to_string();
+        }
+        parts.push(&rest[digits..digits + len]);
+        rest = &rest[digits + len..];
+    }
+    if parts.len() > 1
+        && parts
+            .last()
+            .is_some_and(|p| p.starts_with('h') && p.len() == 17)
+    {
+        parts.pop();
+    }
+    parts
+        .join("::")
+        .replace("$LT$", "<")
+        .replace("$GT$", ">")
+        .replace("$u20$", " ")
+        .replace("..", "::")
+}
+
+fn track_trend(trend_file: &Path, current_percent: f64) -> Result<()> {
     let mut trends: Vec<f64> = Vec::new();
 
-    if Path::new(trend_file).exists() {
+    if trend_file.exists() {
         let content = fs::read_to_string(trend_file)?;
         if let Ok(parsed) = serde_json::from_str::<Vec<f64>>(&content) {
             trends = parsed;
@@ -185,9 +983,12 @@ fn track_trend(trend_file: &str, current_percent: f64) -> Result<()> {
     if let Some(last) = trends.last() {
         let diff = current_percent - last;
         if diff > 0.0 {
-            println!("  Coverage changed: {:+.2}% 📈", diff.to_string().green());
+            println!(
+                "  Coverage changed: {} 📈",
+                format!("{:+.2}%", diff).green()
+            );
         } else if diff < 0.0 {
-            println!("  Coverage changed: {:+.2}% 📉", diff.to_string().red());
+            println!("  Coverage changed: {} 📉", format!("{:+.2}%", diff).red());
         } else {
             println!("  Coverage unchanged ➖");
         }
@@ -197,3 +998,152 @@ fn track_trend(trend_file: &str, current_percent: f64) -> Result<()> {
     fs::write(trend_file, serde_json::to_string_pretty(&trends)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::tests::test_contract_wasm;
+    use crate::test_framework::{TestStep, TestValue};
+
+    fn scenario(steps: &[(&str, Vec<TestValue>)]) -> TestScenario {
+        TestScenario {
+            name: "cover".to_string(),
+            description: None,
+            setup: None,
+            steps: steps
+                .iter()
+                .map(|(method, args)| TestStep {
+                    name: method.to_string(),
+                    contract: "counter".to_string(),
+                    method: method.to_string(),
+                    args: Some(args.clone()),
+                    assertions: None,
+                    expected_error: None,
+                })
+                .collect(),
+            teardown: None,
+        }
+    }
+
+    #[test]
+    fn scenarios_mark_exports_as_covered() {
+        let wasm = test_contract_wasm();
+        let module = wasm_instrument::instrument(&wasm).unwrap();
+        let spec = ContractSpec::default();
+        let mut collector = Collector::new(&module, spec);
+        let (mut ledger, contract) = collector.deploy().unwrap();
+        let invocation = ledger
+            .invoke(
+                &contract,
+                "add",
+                vec![
+                    soroban_env_host::xdr::ScVal::U64(1),
+                    soroban_env_host::xdr::ScVal::U64(2),
+                ],
+            )
+            .unwrap();
+        collector.record(&contract, "add", &invocation);
+
+        let artifact = Artifact {
+            wasm,
+            wasm_path: PathBuf::from("counter.wasm"),
+            source_root: None,
+        };
+        let report = Report::build(&collector, &[], &artifact, &[]);
+        let add = report.exports.iter().find(|e| e.name == "add").unwrap();
+        assert_eq!(add.calls, 1);
+        assert_eq!(add.blocks_hit, add.blocks);
+        let store = report.exports.iter().find(|e| e.name == "store").unwrap();
+        assert_eq!(store.calls, 0);
+        assert_eq!(store.blocks_hit, 0);
+        assert!(report.percent() > 0.0 && report.percent() < 100.0);
+        assert!(report.to_lcov(&artifact).contains("FNDA:1,add"));
+    }
+
+    #[test]
+    fn replays_scenarios_and_fuzz_cases() {
+        let wasm = test_contract_wasm();
+        let module = wasm_instrument::instrument(&wasm).unwrap();
+        let spec = ContractSpec::from_wasm(&wasm).unwrap();
+        let mut collector = Collector::new(&module, spec);
+        collector
+            .run_scenario(&scenario(&[
+                ("add", vec![TestValue::Number(1), TestValue::Number(2)]),
+                ("ping", vec![TestValue::Number(3)]),
+            ]))
+            .unwrap();
+        assert_eq!(collector.calls["add"].calls, 1);
+        assert_eq!(collector.calls["ping"].calls, 1);
+        assert!(!collector.calls.contains_key("store"));
+
+        collector.run_fuzz(4).unwrap();
+        for function in ["add", "store", "ping", "approve"] {
+            assert!(
+                collector.calls[function].calls > 0,
+                "{} not fuzzed",
+                function
+            );
+        }
+        assert!(collector.fuzz_skipped.is_empty());
+    }
+
+    #[test]
+    fn attributes_line_rows_to_blocks() {
+        let blocks = vec![
+            BasicBlock {
+                id: 0,
+                function: 0,
+                code_range: 10..20,
+            },
+            BasicBlock {
+                id: 1,
+                function: 0,
+                code_range: 20..30,
+            },
+        ];
+        let ids: Vec<u32> = blocks_overlapping(&blocks, 15, 25).map(|b| b.id).collect();
+        assert_eq!(ids, vec![0, 1]);
+        let ids: Vec<u32> = blocks_overlapping(&blocks, 20, 21).map(|b| b.id).collect();
+        assert_eq!(ids, vec![1]);
+        assert_eq!(blocks_overlapping(&blocks, 30, 40).count(), 0);
+    }
+
+    #[test]
+    fn covignore_globs() {
+        assert!(glob_match("src/test*.rs", "src/tests.rs"));
+        assert!(!glob_match("src/*.rs", "src/a/b.rs"));
+        assert!(glob_match("**/mock.rs", "src/a/mock.rs"));
+        assert!(glob_match("src/**", "src/a/b.rs"));
+        let root = Path::new("/work/counter");
+        let excludes = vec!["src/testutils.rs".to_string()];
+        assert!(include_file(
+            Path::new("/work/counter/src/lib.rs"),
+            Some(root),
+            &excludes
+        ));
+        assert!(!include_file(
+            Path::new("/work/counter/src/testutils.rs"),
+            Some(root),
+            &excludes
+        ));
+        assert!(!include_file(
+            Path::new("/home/u/.cargo/registry/src/x.rs"),
+            Some(root),
+            &[]
+        ));
+        assert!(!include_file(
+            Path::new("/rustc/abc/library/core/src/num.rs"),
+            None,
+            &[]
+        ));
+    }
+
+    #[test]
+    fn demangles_legacy_symbols() {
+        assert_eq!(
+            demangle("_ZN7counter8Contract9increment17h0123456789abcdefE"),
+            "counter::Contract::increment"
+        );
+        assert_eq!(demangle("increment"), "increment");
+    }
+}
@@ -446,7 +446,7 @@ impl Fuzzer {
         Self::generate_value_static(arg_type, rng)
     }
 
-    fn generate_value_static(arg_type: &ArgType, rng: &mut StdRng) -> FuzzValue {
+    pub(crate) fn generate_value_static(arg_type: &ArgType, rng: &mut StdRng) -> FuzzValue {
         match arg_type {
             ArgType::I32 => FuzzValue::I32(rng.gen()),
             ArgType::I64 => FuzzValue::I64(rng.gen()),
@@ -0,0 +1,1265 @@
+#![allow(dead_code)]
+
+//! Typed contract invocation.
+//!
+//! The contract is resolved through the registry, arguments are checked and
+//! converted against the ABI the registry stores for it, and the call is
+//! simulated on a Soroban RPC node. Calls that write state are then signed
+//! (transaction and any auth entries for the source account) and submitted.
+
+use crate::config::Network;
+use crate::rpc::{self, RpcClient, SimulateTransaction};
+use crate::sandbox::{parse_address, parse_bytes, parse_integer, scval_to_json, sorted_map};
+use anyhow::{anyhow, bail, Context, Result};
+use colored::Colorize;
+use contract_abi::{ContractABI, ContractFunction, SorobanType};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use soroban_env_host::xdr::{
+    AccountId, ContractEvent, ContractEventBody, ContractEventType, DecoratedSignature,
+    DiagnosticEvent, Hash, HashIdPreimage, HashIdPreimageSorobanAuthorization, HostFunction,
+    Int128Parts, Int256Parts, InvokeContractArgs, InvokeHostFunctionOp, Limits, Memo, MuxedAccount,
+    Operation, OperationBody, Preconditions, PublicKey, ReadXdr, ScAddress, ScBytes, ScMapEntry,
+    ScString, ScSymbol, ScVal, ScVec, SequenceNumber, Signature, SignatureHint,
+    SorobanAuthorizationEntry, SorobanCredentials, SorobanTransactionData, Transaction,
+    TransactionEnvelope, TransactionExt, TransactionMeta, TransactionResult,
+    TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
+    TransactionV1Envelope, UInt128Parts, UInt256Parts, Uint256, WriteXdr,
+};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Ledgers a signed auth entry stays valid for after the latest ledger.
+const AUTH_VALIDITY_LEDGERS: u32 = 100;
+
+pub struct InvokeOptions<'a> {
+    /// Registry contract UUID or a `C...` contract address.
+    pub contract: &'a str,
+    pub function: &'a str,
+    /// `name=value` pairs, one per function parameter.
+    pub args: &'a [String],
+    /// Secret seed (`S...`) of the source account.
+    pub source: Option<&'a str>,
+    pub rpc_url: Option<&'a str>,
+    pub network: Network,
+    pub simulate_only: bool,
+    /// ABI version to type the call against (latest if unset).
+    pub contract_version: Option<&'a str>,
+    /// Inclusion fee bid in stroops, on top of the resource fee.
+    pub fee: u32,
+    pub json: bool,
+}
+
+struct ResolvedContract {
+    /// Identifier the registry API accepts for this contract.
+    registry_id: String,
+    address: ScAddress,
+    network: Network,
+}
+
+/// A decoded contract event.
+struct Event {
+    contract_id: Option<String>,
+    topics: Vec<JsonValue>,
+    data: JsonValue,
+}
+
+impl Event {
+    fn from_contract_event(event: &ContractEvent) -> Self {
+        let ContractEventBody::V0(body) = &event.body;
+        Self {
+            contract_id: event
+                .contract_id
+                .as_ref()
+                .map(|id| ScAddress::Contract(id.clone()).to_string()),
+            topics: body.topics.iter().map(scval_to_json).collect(),
+            data: scval_to_json(&body.data),
+        }
+    }
+
+    fn to_json(&self) -> JsonValue {
+        json!({
+            "contract_id": self.contract_id,
+            "topics": self.topics,
+            "data": self.data,
+        })
+    }
+}
+
+pub async fn run(api_url: &str, opts: &InvokeOptions<'_>) -> Result<()> {
+    let client = reqwest::Client::new();
+    let contract = resolve_contract(&client, api_url, opts.contract, opts.network).await?;
+    let abi = fetch_abi(
+        &client,
+        api_url,
+        &contract.registry_id,
+        opts.contract_version,
+    )
+    .await?;
+    let function = abi.find_function(opts.function).ok_or_else(|| {
+        let available: Vec<&str> = abi.functions.iter().map(|f| f.name.as_str()).collect();
+        anyhow!(
+            "Contract has no function '{}'. Available: {}",
+            opts.function,
+            available.join(", ")
+        )
+    })?;
+    let args = build_args(function, &abi.types, opts.args)?;
+
+    let rpc_url = match opts.rpc_url {
+        Some(url) => url.to_string(),
+        None => rpc::default_rpc_url(contract.network)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No public RPC endpoint for {}; pass --rpc-url or set SOROBAN_RPC_URL",
+                    contract.network
+                )
+            })?,
+    };
+    let rpc = RpcClient::new(&rpc_url);
+    let network_id = rpc::network_id(rpc::network_passphrase(contract.network));
+    let signer = opts.source.map(parse_secret).transpose()?;
+
+    if !opts.json {
+        println!(
+            "{} {}.{} on {} via {}",
+            "→".bright_black(),
+            contract.address,
+            function.name,
+            contract.network,
+            rpc.url()
+        );
+    }
+
+    let source_key = signer
+        .as_ref()
+        .map(|key| key.verifying_key().to_bytes())
+        .unwrap_or([0; 32]);
+    let seq_num = match &signer {
+        Some(_) => rpc.get_account_sequence(&source_key).await? + 1,
+        None => 0,
+    };
+    let host_function = HostFunction::InvokeContract(InvokeContractArgs {
+        contract_address: contract.address.clone(),
+        function_name: ScSymbol(function.name.as_str().try_into()?),
+        args: args.try_into()?,
+    });
+    let mut tx = build_transaction(source_key, seq_num, opts.fee, host_function, Vec::new())?;
+
+    let simulation = simulate(&rpc, &tx).await?;
+    let (result, auth) = decode_simulation(&simulation)?;
+    let events = simulation_events(&simulation)?;
+    let transaction_data = decode_transaction_data(&simulation)?;
+    let read_only = transaction_data.resources.footprint.read_write.is_empty() && auth.is_empty();
+
+    if opts.simulate_only || read_only {
+        let reason = if opts.simulate_only {
+            "simulate-only"
+        } else {
+            "read-only call, not submitted"
+        };
+        report(opts.json, &result, &events, None, &simulation, reason)?;
+        return Ok(());
+    }
+
+    let signer = signer.ok_or_else(|| {
+        anyhow!(
+            "This call changes state and must be signed; pass --source (or set \
+             SOROBAN_SECRET_KEY), or use --simulate-only"
+        )
+    })?;
+
+    // Sign the auth entries the source account can satisfy, then simulate
+    // again so the footprint and fees cover signature verification.
+    let expiration = simulation.latest_ledger + AUTH_VALIDITY_LEDGERS;
+    let auth = auth
+        .into_iter()
+        .map(|entry| sign_auth_entry(entry, &signer, &network_id, expiration))
+        .collect::<Result<Vec<_>>>()?;
+    tx = build_transaction(
+        source_key,
+        seq_num,
+        opts.fee,
+        invoke_host_function(&tx)?.host_function.clone(),
+        auth,
+    )?;
+    let simulation = simulate(&rpc, &tx).await?;
+    decode_simulation(&simulation)?;
+    let transaction_data = decode_transaction_data(&simulation)?;
+    let resource_fee = u32::try_from(simulation.min_resource_fee()?)
+        .context("Resource fee exceeds the maximum transaction fee")?;
+    tx.fee = opts
+        .fee
+        .checked_add(resource_fee)
+        .ok_or_else(|| anyhow!("Transaction fee overflows u32"))?;
+    tx.ext = TransactionExt::V1(transaction_data);
+
+    let envelope = sign_transaction(tx, &signer, &network_id)?;
+    let sent = rpc
+        .send_transaction(&envelope.to_xdr_base64(Limits::none())?)
+        .await?;
+    match sent.status.as_str() {
+        "PENDING" | "DUPLICATE" => {}
+        "ERROR" => bail!(
+            "Transaction rejected: {}",
+            describe_result(sent.error_result_xdr.as_deref())
+        ),
+        other => bail!("Transaction not accepted ({}), try again later", other),
+    }
+    if !opts.json {
+        println!(
+            "{} Submitted {}, waiting for inclusion...",
+            "→".bright_black(),
+            sent.hash
+        );
+    }
+
+    let outcome = rpc
+        .wait_for_transaction(&sent.hash, Duration::from_secs(60), Duration::from_secs(1))
+        .await?;
+    if outcome.status != "SUCCESS" {
+        bail!(
+            "Transaction {} {}: {}",
+            sent.hash,
+            outcome.status.to_lowercase(),
+            describe_result(outcome.result_xdr.as_deref())
+        );
+    }
+    let (result, events) = decode_meta(outcome.result_meta_xdr.as_deref())?;
+    let status = format!(
+        "included in ledger {}",
+        outcome.ledger.unwrap_or(outcome.latest_ledger)
+    );
+    report(
+        opts.json,
+        &result,
+        &events,
+        Some(&sent.hash),
+        &simulation,
+        &status,
+    )
+}
+
+async fn resolve_contract(
+    client: &reqwest::Client,
+    api_url: &str,
+    contract: &str,
+    network: Network,
+) -> Result<ResolvedContract> {
+    if uuid::Uuid::parse_str(contract).is_err() {
+        let address = parse_address(contract, &HashMap::new())?;
+        if !matches!(address, ScAddress::Contract(_)) {
+            bail!("'{}' is not a contract address", contract);
+        }
+        return Ok(ResolvedContract {
+            registry_id: contract.to_string(),
+            address,
+            network,
+        });
+    }
+
+    let url = format!(
+        "{}/api/contracts/{}",
+        api_url.trim_end_matches('/'),
+        contract
+    );
+    let mut request = client.get(&url);
+    if network != Network::Auto {
+        request = request.query(&[("network", network.to_string())]);
+    }
+    let response = request
+        .send()
+        .await
+        .context("Failed to reach registry API")?;
+    if !response.status().is_success() {
+        bail!(
+            "Failed to fetch contract {}: {}",
+            contract,
+            response.status()
+        );
+    }
+    let body: JsonValue = response.json().await?;
+    let address = body["network_config"]["contract_id"]
+        .as_str()
+        .or_else(|| body["contract_id"].as_str())
+        .ok_or_else(|| anyhow!("Registry returned no address for contract {}", contract))?;
+    let network = match network {
+        Network::Auto => body["network"]
+            .as_str()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(Network::Testnet),
+        explicit => explicit,
+    };
+    Ok(ResolvedContract {
+        registry_id: contract.to_string(),
+        address: parse_address(address, &HashMap::new())?,
+        network,
+    })
+}
+
+async fn fetch_abi(
+    client: &reqwest::Client,
+    api_url: &str,
+    registry_id: &str,
+    version: Option<&str>,
+) -> Result<ContractABI> {
+    let url = format!(
+        "{}/api/contracts/{}/abi",
+        api_url.trim_end_matches('/'),
+        registry_id
+    );
+    let mut request = client.get(&url);
+    if let Some(version) = version {
+        request = request.query(&[("version", version)]);
+    }
+    let response = request
+        .send()
+        .await
+        .context("Failed to reach registry API")?;
+    if !response.status().is_success() {
+        bail!(
+            "Failed to fetch ABI for {}: {}",
+            registry_id,
+            response.status()
+        );
+    }
+    let body: JsonValue = response.json().await?;
+    let spec = match &body["abi"] {
+        JsonValue::String(raw) => raw.clone(),
+        JsonValue::Null => bail!("Registry has no ABI for {}", registry_id),
+        other => other.to_string(),
+    };
+    contract_abi::parse_json_spec(&spec, registry_id)
+        .map_err(|e| anyhow!("Registry ABI for {} is invalid: {}", registry_id, e))
+}
+
+/// Matches `name=value` arguments to the function's parameters, in
+/// parameter order.
+fn build_args(
+    function: &ContractFunction,
+    types: &HashMap<String, SorobanType>,
+    raw_args: &[String],
+) -> Result<Vec<ScVal>> {
+    let mut provided = HashMap::new();
+    for raw in raw_args {
+        let (name, value) = raw
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Argument '{}' is not in name=value form", raw))?;
+        if !function.params.iter().any(|p| p.name == name) {
+            bail!(
+                "{} has no parameter '{}' (expected: {})",
+                function.name,
+                name,
+                signature(function)
+            );
+        }
+        if provided.insert(name, value).is_some() {
+            bail!("Parameter '{}' given more than once", name);
+        }
+    }
+
+    function
+        .params
+        .iter()
+        .map(|param| {
+            let raw = match provided.get(param.name.as_str()) {
+                Some(raw) => *raw,
+                None if matches!(param.param_type, SorobanType::Option { .. }) => "null",
+                None => bail!(
+                    "Missing argument '{}' for {}",
+                    param.name,
+                    signature(function)
+                ),
+            };
+            let value = parse_raw_arg(raw, &param.param_type, types);
+            to_scval(&value, &param.param_type, types).with_context(|| {
+                format!(
+                    "Invalid value for '{}' ({})",
+                    param.name,
+                    param.param_type.display_name()
+                )
+            })
+        })
+        .collect()
+}
+
+fn signature(function: &ContractFunction) -> String {
+    let params: Vec<String> = function
+        .params
+        .iter()
+        .map(|p| format!("{}: {}", p.name, p.param_type.display_name()))
+        .collect();
+    format!("{}({})", function.name, params.join(", "))
+}
+
+/// Command-line values are JSON, except that scalars which are usually
+/// written unquoted (strings, symbols, addresses, bytes and big integers) may
+/// be given bare.
+fn parse_raw_arg(raw: &str, ty: &SorobanType, types: &HashMap<String, SorobanType>) -> JsonValue {
+    let ty = resolve(ty, types);
+    let bare = match ty {
+        SorobanType::Option { value_type } if raw != "null" => {
+            return parse_raw_arg(raw, value_type, types)
+        }
+        SorobanType::String
+        | SorobanType::Symbol
+        | SorobanType::Address
+        | SorobanType::Bytes
+        | SorobanType::BytesN { .. }
+        | SorobanType::I128
+        | SorobanType::U128
+        | SorobanType::I256
+        | SorobanType::U256 => !raw.starts_with('"'),
+        _ => false,
+    };
+    if bare {
+        return JsonValue::String(raw.to_string());
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| JsonValue::String(raw.to_string()))
+}
+
+fn resolve<'a>(ty: &'a SorobanType, types: &'a HashMap<String, SorobanType>) -> &'a SorobanType {
+    match ty {
+        SorobanType::Custom { name } => types.get(name).unwrap_or(ty),
+        _ => ty,
+    }
+}
+
+/// Converts a JSON value to an `ScVal`, checking it against `ty`.
+pub fn to_scval(
+    value: &JsonValue,
+    ty: &SorobanType,
+    types: &HashMap<String, SorobanType>,
+) -> Result<ScVal> {
+    let no_aliases = HashMap::new();
+    let scval = match ty {
+        SorobanType::Bool => ScVal::Bool(
+            value
+                .as_bool()
+                .ok_or_else(|| anyhow!("expected true or false, got {}", value))?,
+        ),
+        SorobanType::Void => {
+            if !value.is_null() {
+                bail!("expected null, got {}", value);
+            }
+            ScVal::Void
+        }
+        SorobanType::U32 => ScVal::U32(small_int(value, "u32")?),
+        SorobanType::I32 => ScVal::I32(small_int(value, "i32")?),
+        SorobanType::U64 => ScVal::U64(small_int(value, "u64")?),
+        SorobanType::I64 => ScVal::I64(small_int(value, "i64")?),
+        SorobanType::Timepoint => ScVal::Timepoint(small_int::<u64>(value, "Timepoint")?.into()),
+        SorobanType::Duration => ScVal::Duration(small_int::<u64>(value, "Duration")?.into()),
+        SorobanType::I128 => {
+            let v = parse_integer(value)?;
+            ScVal::I128(Int128Parts {
+                hi: (v >> 64) as i64,
+                lo: v as u64,
+            })
+        }
+        SorobanType::U128 => {
+            let v = parse_u128(value)?;
+            ScVal::U128(UInt128Parts {
+                hi: (v >> 64) as u64,
+                lo: v as u64,
+            })
+        }
+        SorobanType::U256 => {
+            let (negative, limbs) = parse_256(value)?;
+            if negative {
+                bail!("u256 cannot be negative");
+            }
+            ScVal::U256(UInt256Parts {
+                hi_hi: limbs[0],
+                hi_lo: limbs[1],
+                lo_hi: limbs[2],
+                lo_lo: limbs[3],
+            })
+        }
+        SorobanType::I256 => {
+            let (negative, limbs) = parse_256(value)?;
+            let limbs = if negative { negate(limbs) } else { limbs };
+            // The sign bit must agree with the sign of the input.
+            if limbs != [0; 4] && (limbs[0] >> 63 == 1) != negative {
+                bail!("{} is out of range for i256", value);
+            }
+            ScVal::I256(Int256Parts {
+                hi_hi: limbs[0] as i64,
+                hi_lo: limbs[1],
+                lo_hi: limbs[2],
+                lo_lo: limbs[3],
+            })
+        }
+        SorobanType::Symbol => {
+            let s = expect_str(value)?;
+            if s.len() > 32 || !s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                bail!(
+                    "'{}' is not a valid symbol (up to 32 characters of a-z, A-Z, 0-9 and _)",
+                    s
+                );
+            }
+            ScVal::Symbol(ScSymbol(s.try_into()?))
+        }
+        SorobanType::String => ScVal::String(ScString(expect_str(value)?.try_into()?)),
+        SorobanType::Bytes => ScVal::Bytes(ScBytes(parse_bytes(value)?.try_into()?)),
+        SorobanType::BytesN { n } => {
+            let bytes = parse_bytes(value)?;
+            if bytes.len() != *n as usize {
+                bail!("expected {} bytes, got {}", n, bytes.len());
+            }
+            ScVal::Bytes(ScBytes(bytes.try_into()?))
+        }
+        SorobanType::Address => ScVal::Address(parse_address(expect_str(value)?, &no_aliases)?),
+        SorobanType::Option { value_type } => {
+            if value.is_null() {
+                ScVal::Void
+            } else {
+                to_scval(value, value_type, types)?
+            }
+        }
+        SorobanType::Result { .. } => bail!("Result values cannot be passed as arguments"),
+        SorobanType::Vec { element_type } => {
+            let items = expect_array(value)?
+                .iter()
+                .map(|item| to_scval(item, element_type, types))
+                .collect::<Result<Vec<_>>>()?;
+            ScVal::Vec(Some(ScVec(items.try_into()?)))
+        }
+        SorobanType::Map {
+            key_type,
+            value_type,
+        } => {
+            let pairs: Vec<(JsonValue, &JsonValue)> = match value {
+                JsonValue::Object(map) => map
+                    .iter()
+                    .map(|(k, v)| (parse_raw_arg(k, key_type, types), v))
+                    .collect(),
+                JsonValue::Array(items) => items
+                    .iter()
+                    .map(|pair| match pair.as_array().map(Vec::as_slice) {
+                        Some([k, v]) => Ok((k.clone(), v)),
+                        _ => Err(anyhow!("map entries must be [key, value] pairs")),
+                    })
+                    .collect::<Result<_>>()?,
+                _ => bail!("expected an object or list of pairs, got {}", value),
+            };
+            let mut entries = Vec::with_capacity(pairs.len());
+            for (k, v) in pairs {
+                entries.push(ScMapEntry {
+                    key: to_scval(&k, key_type, types)?,
+                    val: to_scval(v, value_type, types)?,
+                });
+            }
+            sorted_map(entries)?
+        }
+        SorobanType::Tuple { elements } => {
+            let items = expect_array(value)?;
+            if items.len() != elements.len() {
+                bail!(
+                    "expected a {}-tuple, got {} values",
+                    elements.len(),
+                    items.len()
+                );
+            }
+            let vals = items
+                .iter()
+                .zip(elements)
+                .map(|(item, ty)| to_scval(item, ty, types))
+                .collect::<Result<Vec<_>>>()?;
+            ScVal::Vec(Some(ScVec(vals.try_into()?)))
+        }
+        SorobanType::Struct { name, fields } => {
+            let is_tuple = fields.iter().all(|f| f.name.parse::<u32>().is_ok());
+            if is_tuple {
+                let items = expect_array(value)?;
+                if items.len() != fields.len() {
+                    bail!("{} expects {} values", name, fields.len());
+                }
+                let vals = fields
+                    .iter()
+                    .zip(items)
+                    .map(|(field, item)| to_scval(item, &field.field_type, types))
+                    .collect::<Result<Vec<_>>>()?;
+                ScVal::Vec(Some(ScVec(vals.try_into()?)))
+            } else {
+                let object = value
+                    .as_object()
+                    .ok_or_else(|| anyhow!("{} expects an object, got {}", name, value))?;
+                if let Some(unknown) = object
+                    .keys()
+                    .find(|k| !fields.iter().any(|f| &f.name == *k))
+                {
+                    bail!("{} has no field '{}'", name, unknown);
+                }
+                let mut entries = Vec::with_capacity(fields.len());
+                for field in fields {
+                    let field_value = object
+                        .get(&field.name)
+                        .ok_or_else(|| anyhow!("{} is missing field '{}'", name, field.name))?;
+                    entries.push(ScMapEntry {
+                        key: ScVal::Symbol(ScSymbol(field.name.as_str().try_into()?)),
+                        val: to_scval(field_value, &field.field_type, types)
+                            .with_context(|| format!("in field '{}'", field.name))?,
+                    });
+                }
+                sorted_map(entries)?
+            }
+        }
+        SorobanType::Enum { name, variants } => {
+            let is_integer_enum = variants
+                .iter()
+                .all(|v| v.value.is_some() && v.fields.as_ref().is_none_or(Vec::is_empty));
+            if is_integer_enum {
+                let discriminant = match value {
+                    JsonValue::String(s) => variants
+                        .iter()
+                        .find(|v| v.name == *s)
+                        .and_then(|v| v.value)
+                        .ok_or_else(|| anyhow!("{} has no variant '{}'", name, s))?,
+                    other => {
+                        let v: u32 = small_int(other, name)?;
+                        if !variants.iter().any(|variant| variant.value == Some(v)) {
+                            bail!("{} has no variant with value {}", name, v);
+                        }
+                        v
+                    }
+                };
+                ScVal::U32(discriminant)
+            } else {
+                // Accepts "Variant" for unit variants and {"Variant": value-or-[values]}.
+                let (variant_name, payload) = match value {
+                    JsonValue::String(s) => (s.as_str(), None),
+                    JsonValue::Object(map) if map.len() == 1 => {
+                        let (k, v) = map.iter().next().expect("length checked");
+                        (k.as_str(), Some(v))
+                    }
+                    _ => bail!("{} expects \"Variant\" or {{\"Variant\": value}}", name),
+                };
+                let variant = variants
+                    .iter()
+                    .find(|v| v.name == variant_name)
+                    .ok_or_else(|| anyhow!("{} has no variant '{}'", name, variant_name))?;
+                let fields = variant.fields.as_deref().unwrap_or_default();
+                let mut items = vec![ScVal::Symbol(ScSymbol(variant_name.try_into()?))];
+                match (fields.len(), payload) {
+                    (0, None) => {}
+                    (0, Some(_)) => bail!("variant '{}' takes no value", variant_name),
+                    (_, None) => bail!("variant '{}' needs a value", variant_name),
+                    (1, Some(v)) => items.push(to_scval(v, &fields[0].field_type, types)?),
+                    (n, Some(JsonValue::Array(values))) if values.len() == n => {
+                        for (field, v) in fields.iter().zip(values) {
+                            items.push(to_scval(v, &field.field_type, types)?);
+                        }
+                    }
+                    (n, Some(_)) => bail!("variant '{}' expects {} values", variant_name, n),
+                }
+                ScVal::Vec(Some(ScVec(items.try_into()?)))
+            }
+        }
+        SorobanType::Custom { name } => {
+            let resolved = types
+                .get(name)
+                .ok_or_else(|| anyhow!("ABI does not define type '{}'", name))?;
+            if matches!(resolved, SorobanType::Custom { name: inner } if inner == name) {
+                bail!("ABI type '{}' refers to itself", name);
+            }
+            to_scval(value, resolved, types)?
+        }
+    };
+    Ok(scval)
+}
+
+fn expect_str(value: &JsonValue) -> Result<&str> {
+    value
+        .as_str()
+        .ok_or_else(|| anyhow!("expected a string, got {}", value))
+}
+
+fn expect_array(value: &JsonValue) -> Result<&Vec<JsonValue>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("expected an array, got {}", value))
+}
+
+fn small_int<T: TryFrom<i128>>(value: &JsonValue, type_name: &str) -> Result<T> {
+    let v = parse_integer(value)?;
+    T::try_from(v).map_err(|_| anyhow!("{} is out of range for {}", v, type_name))
+}
+
+fn parse_u128(value: &JsonValue) -> Result<u128> {
+    match value {
+        JsonValue::Number(n) => n
+            .as_u64()
+            .map(u128::from)
+            .ok_or_else(|| anyhow!("{} is out of range for u128", n)),
+        JsonValue::String(s) => s
+            .replace('_', "")
+            .parse::<u128>()
+            .with_context(|| format!("'{}' is not a u128", s)),
+        other => bail!("expected an integer, got {}", other),
+    }
+}
+
+/// Parses a decimal or `0x` hex integer into its sign and 256-bit magnitude
+/// (most significant limb first).
+fn parse_256(value: &JsonValue) -> Result<(bool, [u64; 4])> {
+    let text = match value {
+        JsonValue::Number(n) => n.to_string(),
+        JsonValue::String(s) => s.replace('_', ""),
+        other => bail!("expected an integer, got {}", other),
+    };
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.as_str()),
+    };
+    let overflow = || anyhow!("'{}' does not fit in 256 bits", text);
+    let mut limbs = [0u64; 4];
+    if let Some(hex_digits) = digits.strip_prefix("0x") {
+        if hex_digits.is_empty() || hex_digits.len() > 64 {
+            return Err(overflow());
+        }
+        let padded = format!("{:0>64}", hex_digits);
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = u64::from_str_radix(&padded[i * 16..(i + 1) * 16], 16)
+                .with_context(|| format!("'{}' is not a hex integer", text))?;
+        }
+    } else {
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            bail!("'{}' is not an integer", text);
+        }
+        for digit in digits.bytes() {
+            // limbs = limbs * 10 + digit
+            let mut carry = u128::from(digit - b'0');
+            for limb in limbs.iter_mut().rev() {
+                let v = u128::from(*limb) * 10 + carry;
+                *limb = v as u64;
+                carry = v >> 64;
+            }
+            if carry != 0 {
+                return Err(overflow());
+            }
+        }
+    }
+    Ok((negative, limbs))
+}
+
+/// Two's complement of a 256-bit value.
+fn negate(limbs: [u64; 4]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    let mut carry = 1u128;
+    for i in (0..4).rev() {
+        let v = u128::from(!limbs[i]) + carry;
+        out[i] = v as u64;
+        carry = v >> 64;
+    }
+    out
+}
+
+fn parse_secret(secret: &str) -> Result<SigningKey> {
+    let key = stellar_strkey::ed25519::PrivateKey::from_string(secret.trim())
+        .map_err(|_| anyhow!("--source must be a secret seed (S...)"))?;
+    Ok(SigningKey::from_bytes(&key.0))
+}
+
+fn build_transaction(
+    source_key: [u8; 32],
+    seq_num: i64,
+    fee: u32,
+    host_function: HostFunction,
+    auth: Vec<SorobanAuthorizationEntry>,
+) -> Result<Transaction> {
+    let operation = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function,
+            auth: auth.try_into()?,
+        }),
+    };
+    Ok(Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(source_key)),
+        fee,
+        seq_num: SequenceNumber(seq_num),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![operation].try_into()?,
+        ext: TransactionExt::V0,
+    })
+}
+
+fn invoke_host_function(tx: &Transaction) -> Result<&InvokeHostFunctionOp> {
+    match tx.operations.first().map(|op| &op.body) {
+        Some(OperationBody::InvokeHostFunction(op)) => Ok(op),
+        _ => bail!("transaction has no InvokeHostFunction operation"),
+    }
+}
+
+async fn simulate(rpc: &RpcClient, tx: &Transaction) -> Result<SimulateTransaction> {
+    let envelope = TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx: tx.clone(),
+        signatures: Default::default(),
+    });
+    rpc.simulate_transaction(&envelope.to_xdr_base64(Limits::none())?)
+        .await
+}
+
+/// Returns the simulated return value and the auth entries the call needs,
+/// or the simulation error with its diagnostic events.
+fn decode_simulation(
+    simulation: &SimulateTransaction,
+) -> Result<(ScVal, Vec<SorobanAuthorizationEntry>)> {
+    if let Some(error) = &simulation.error {
+        let mut message = format!("Simulation failed: {}", error);
+        for event in &simulation.events {
+            if let Ok(event) = DiagnosticEvent::from_xdr_base64(event, Limits::none()) {
+                let event = Event::from_contract_event(&event.event);
+                message.push_str(&format!(
+                    "\n  {} {}",
+                    JsonValue::Array(event.topics),
+                    event.data
+                ));
+            }
+        }
+        bail!(message);
+    }
+    if let Some(preamble) = &simulation.restore_preamble {
+        bail!(
+            "The call reads archived ledger entries that must be restored first \
+             (restore fee {} stroops)",
+            preamble.min_resource_fee
+        );
+    }
+    let result = simulation
+        .results
+        .first()
+        .ok_or_else(|| anyhow!("Simulation returned no result"))?;
+    let value = ScVal::from_xdr_base64(&result.xdr, Limits::none())
+        .context("Simulation returned an undecodable result")?;
+    let auth = result
+        .auth
+        .iter()
+        .map(|entry| SorobanAuthorizationEntry::from_xdr_base64(entry, Limits::none()))
+        .collect::<Result<Vec<_>, _>>()
+        .context("Simulation returned undecodable auth entries")?;
+    Ok((value, auth))
+}
+
+fn decode_transaction_data(simulation: &SimulateTransaction) -> Result<SorobanTransactionData> {
+    let data = simulation
+        .transaction_data
+        .as_deref()
+        .ok_or_else(|| anyhow!("Simulation returned no transaction data"))?;
+    SorobanTransactionData::from_xdr_base64(data, Limits::none())
+        .context("Simulation returned undecodable transaction data")
+}
+
+/// Contract events from calls that succeeded; diagnostics are left out.
+fn simulation_events(simulation: &SimulateTransaction) -> Result<Vec<Event>> {
+    let mut events = Vec::new();
+    for raw in &simulation.events {
+        let event = DiagnosticEvent::from_xdr_base64(raw, Limits::none())
+            .context("Simulation returned an undecodable event")?;
+        if event.in_successful_contract_call && event.event.type_ == ContractEventType::Contract {
+            events.push(Event::from_contract_event(&event.event));
+        }
+    }
+    Ok(events)
+}
+
+fn decode_meta(meta: Option<&str>) -> Result<(ScVal, Vec<Event>)> {
+    let meta = meta.ok_or_else(|| anyhow!("RPC returned no transaction meta"))?;
+    match TransactionMeta::from_xdr_base64(meta, Limits::none())
+        .context("RPC returned undecodable transaction meta")?
+    {
+        TransactionMeta::V3(v3) => {
+            let soroban = v3
+                .soroban_meta
+                .ok_or_else(|| anyhow!("Transaction meta has no Soroban section"))?;
+            let events = soroban
+                .events
+                .iter()
+                .map(Event::from_contract_event)
+                .collect();
+            Ok((soroban.return_value, events))
+        }
+        _ => bail!("Unsupported transaction meta version"),
+    }
+}
+
+fn describe_result(result_xdr: Option<&str>) -> String {
+    result_xdr
+        .and_then(|xdr| TransactionResult::from_xdr_base64(xdr, Limits::none()).ok())
+        .map(|result| format!("{:?}", result.result))
+        .unwrap_or_else(|| "no result returned".to_string())
+}
+
+/// Signs `entry` if it asks for the signer's address; entries authorized by
+/// the transaction source are left unchanged.
+fn sign_auth_entry(
+    mut entry: SorobanAuthorizationEntry,
+    signer: &SigningKey,
+    network_id: &[u8; 32],
+    expiration_ledger: u32,
+) -> Result<SorobanAuthorizationEntry> {
+    let public_key = signer.verifying_key().to_bytes();
+    let SorobanCredentials::Address(credentials) = &mut entry.credentials else {
+        return Ok(entry);
+    };
+    let signer_address = ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+        public_key,
+    ))));
+    if credentials.address != signer_address {
+        bail!(
+            "The call needs authorization from {}, which --source cannot sign for",
+            credentials.address
+        );
+    }
+    credentials.signature_expiration_ledger = expiration_ledger;
+    let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+        network_id: Hash(*network_id),
+        nonce: credentials.nonce,
+        signature_expiration_ledger: expiration_ledger,
+        invocation: entry.root_invocation.clone(),
+    });
+    let payload = Sha256::digest(preimage.to_xdr(Limits::none())?);
+    let signature = signer.sign(&payload);
+    let signature_map = sorted_map(vec![
+        ScMapEntry {
+            key: ScVal::Symbol(ScSymbol("public_key".try_into()?)),
+            val: ScVal::Bytes(ScBytes(public_key.to_vec().try_into()?)),
+        },
+        ScMapEntry {
+            key: ScVal::Symbol(ScSymbol("signature".try_into()?)),
+            val: ScVal::Bytes(ScBytes(signature.to_bytes().to_vec().try_into()?)),
+        },
+    ])?;
+    credentials.signature = ScVal::Vec(Some(ScVec(vec![signature_map].try_into()?)));
+    Ok(entry)
+}
+
+pub(crate) fn transaction_hash(tx: &Transaction, network_id: &[u8; 32]) -> Result<[u8; 32]> {
+    let payload = TransactionSignaturePayload {
+        network_id: Hash(*network_id),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+    };
+    Ok(Sha256::digest(payload.to_xdr(Limits::none())?).into())
+}
+
+fn sign_transaction(
+    tx: Transaction,
+    signer: &SigningKey,
+    network_id: &[u8; 32],
+) -> Result<TransactionEnvelope> {
+    let hash = transaction_hash(&tx, network_id)?;
+    let public_key = signer.verifying_key().to_bytes();
+    let signature = DecoratedSignature {
+        hint: SignatureHint(public_key[28..].try_into()?),
+        signature: Signature(signer.sign(&hash).to_bytes().to_vec().try_into()?),
+    };
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: vec![signature].try_into()?,
+    }))
+}
+
+fn report(
+    json: bool,
+    result: &ScVal,
+    events: &[Event],
+    hash: Option<&str>,
+    simulation: &SimulateTransaction,
+    status: &str,
+) -> Result<()> {
+    let resource_fee = simulation.min_resource_fee()?;
+    if json {
+        let output = json!({
+            "status": status,
+            "result": scval_to_json(result),
+            "events": events.iter().map(Event::to_json).collect::<Vec<_>>(),
+            "transaction_hash": hash,
+            "min_resource_fee": resource_fee,
+            "latest_ledger": simulation.latest_ledger,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "Result:".bold());
+    println!(
+        "  {}",
+        serde_json::to_string_pretty(&scval_to_json(result))?
+    );
+    if !events.is_empty() {
+        println!("\n{}", "Events:".bold());
+        for event in events {
+            println!(
+                "  {} {} {}",
+                event.contract_id.as_deref().unwrap_or("-").bright_black(),
+                JsonValue::Array(event.topics.clone()),
+                event.data
+            );
+        }
+    }
+    println!();
+    if let Some(hash) = hash {
+        println!("  Transaction:   {}", hash);
+    }
+    println!("  Resource fee:  {} stroops", resource_fee);
+    println!("  {} {}", "✓".green(), status);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature as Ed25519Signature, Verifier};
+    use soroban_env_host::xdr::{
+        SorobanAddressCredentials, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+    };
+
+    fn token_abi() -> ContractABI {
+        let spec = json!([
+            {
+                "type": "function",
+                "name": "transfer",
+                "inputs": [
+                    { "name": "from", "value": { "type": "address" } },
+                    { "name": "to", "value": { "type": "address" } },
+                    { "name": "amount", "value": { "type": "i128" } }
+                ],
+                "outputs": []
+            },
+            {
+                "type": "function",
+                "name": "configure",
+                "inputs": [
+                    { "name": "config", "value": { "type": "Config" } },
+                    { "name": "memo", "value": { "type": "option", "element": { "type": "symbol" } } }
+                ],
+                "outputs": []
+            },
+            {
+                "type": "struct",
+                "name": "Config",
+                "fields": [
+                    { "name": "limit", "value": { "type": "u32" } },
+                    { "name": "admins", "value": { "type": "vec", "element": { "type": "address" } } }
+                ]
+            }
+        ]);
+        contract_abi::parse_json_spec(&spec.to_string(), "token").unwrap()
+    }
+
+    fn alice() -> String {
+        stellar_strkey::ed25519::PublicKey([1; 32]).to_string()
+    }
+
+    fn contract() -> String {
+        stellar_strkey::Contract([2; 32]).to_string()
+    }
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn converts_arguments_in_parameter_order() {
+        let abi = token_abi();
+        let transfer = abi.find_function("transfer").unwrap();
+        let vals = build_args(
+            transfer,
+            &abi.types,
+            &args(&[
+                "amount=-5",
+                &format!("to={}", contract()),
+                &format!("from={}", alice()),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(vals.len(), 3);
+        assert!(matches!(&vals[0], ScVal::Address(ScAddress::Account(_))));
+        assert!(matches!(&vals[1], ScVal::Address(ScAddress::Contract(_))));
+        assert_eq!(
+            vals[2],
+            ScVal::I128(Int128Parts {
+                hi: -1,
+                lo: u64::MAX - 4
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_missing_unknown_and_mistyped_arguments() {
+        let abi = token_abi();
+        let transfer = abi.find_function("transfer").unwrap();
+        let missing = build_args(transfer, &abi.types, &args(&["amount=1"])).unwrap_err();
+        assert!(missing.to_string().contains("Missing argument 'from'"));
+
+        let unknown = build_args(transfer, &abi.types, &args(&["value=1"])).unwrap_err();
+        assert!(unknown.to_string().contains("no parameter 'value'"));
+
+        let mistyped = build_args(
+            transfer,
+            &abi.types,
+            &args(&[&format!("from={}", alice()), "to=bob", "amount=1"]),
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", mistyped).contains("Invalid value for 'to' (Address)"));
+    }
+
+    #[test]
+    fn converts_custom_structs_and_optional_symbols() {
+        let abi = token_abi();
+        let configure = abi.find_function("configure").unwrap();
+        let config = format!(r#"config={{"limit": 7, "admins": ["{}"]}}"#, alice());
+        let vals = build_args(configure, &abi.types, &args(&[&config])).unwrap();
+        let ScVal::Map(Some(map)) = &vals[0] else {
+            panic!("expected a map, got {:?}", vals[0]);
+        };
+        let keys: Vec<JsonValue> = map.iter().map(|e| scval_to_json(&e.key)).collect();
+        assert_eq!(keys, vec![json!("admins"), json!("limit")]);
+        assert_eq!(vals[1], ScVal::Void);
+
+        let with_memo = build_args(configure, &abi.types, &args(&[&config, "memo=hello"])).unwrap();
+        assert_eq!(
+            with_memo[1],
+            ScVal::Symbol(ScSymbol("hello".try_into().unwrap()))
+        );
+
+        let bad_struct = build_args(configure, &abi.types, &args(&["config={\"limit\": 7}"]));
+        assert!(format!("{:#}", bad_struct.unwrap_err()).contains("missing field 'admins'"));
+        let bad_symbol = build_args(configure, &abi.types, &args(&[&config, "memo=not ok"]));
+        assert!(format!("{:#}", bad_symbol.unwrap_err()).contains("not a valid symbol"));
+    }
+
+    #[test]
+    fn checks_integer_ranges() {
+        let types = HashMap::new();
+        assert!(to_scval(&json!(256), &SorobanType::U32, &types).is_ok());
+        assert!(to_scval(&json!(-1), &SorobanType::U32, &types).is_err());
+        assert!(to_scval(&json!(1u64 << 40), &SorobanType::I32, &types).is_err());
+
+        let max_u128 = u128::MAX.to_string();
+        assert_eq!(
+            to_scval(&json!(max_u128), &SorobanType::U128, &types).unwrap(),
+            ScVal::U128(UInt128Parts {
+                hi: u64::MAX,
+                lo: u64::MAX
+            })
+        );
+
+        let big = "0x0100000000000000000000000000000000000000000000000000000000000002";
+        assert_eq!(
+            to_scval(&json!(big), &SorobanType::U256, &types).unwrap(),
+            ScVal::U256(UInt256Parts {
+                hi_hi: 1 << 56,
+                hi_lo: 0,
+                lo_hi: 0,
+                lo_lo: 2
+            })
+        );
+        assert_eq!(
+            to_scval(&json!("-2"), &SorobanType::I256, &types).unwrap(),
+            ScVal::I256(Int256Parts {
+                hi_hi: -1,
+                hi_lo: u64::MAX,
+                lo_hi: u64::MAX,
+                lo_lo: u64::MAX - 1,
+            })
+        );
+        let i256_min =
+            "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+        assert!(to_scval(&json!(i256_min), &SorobanType::I256, &types).is_ok());
+        let past_max =
+            "57896044618658097711785492504343953926634992332820282019728792003956564819968";
+        assert!(to_scval(&json!(past_max), &SorobanType::I256, &types).is_err());
+        assert!(to_scval(&json!("-1"), &SorobanType::U256, &types).is_err());
+    }
+
+    #[test]
+    fn signs_transactions_and_source_auth_entries() {
+        let signer = SigningKey::from_bytes(&[7; 32]);
+        let public_key = signer.verifying_key();
+        let network_id = rpc::network_id(rpc::TESTNET_PASSPHRASE);
+        let host_function = HostFunction::InvokeContract(InvokeContractArgs {
+            contract_address: contract().parse().unwrap(),
+            function_name: ScSymbol("transfer".try_into().unwrap()),
+            args: Default::default(),
+        });
+        let invocation = SorobanAuthorizedInvocation {
+            function: match &host_function {
+                HostFunction::InvokeContract(args) => {
+                    SorobanAuthorizedFunction::ContractFn(args.clone())
+                }
+                _ => unreachable!(),
+            },
+            sub_invocations: Default::default(),
+        };
+        let entry = SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+                address: ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+                    public_key.to_bytes(),
+                )))),
+                nonce: 42,
+                signature_expiration_ledger: 0,
+                signature: ScVal::Void,
+            }),
+            root_invocation: invocation.clone(),
+        };
+
+        let signed = sign_auth_entry(entry, &signer, &network_id, 1_100).unwrap();
+        let SorobanCredentials::Address(credentials) = &signed.credentials else {
+            panic!("credentials changed kind");
+        };
+        assert_eq!(credentials.signature_expiration_ledger, 1_100);
+        let payload = Sha256::digest(
+            HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+                network_id: Hash(network_id),
+                nonce: 42,
+                signature_expiration_ledger: 1_100,
+                invocation,
+            })
+            .to_xdr(Limits::none())
+            .unwrap(),
+        );
+        let rendered = scval_to_json(&credentials.signature);
+        let signature = hex::decode(rendered[0]["signature"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            rendered[0]["public_key"],
+            json!(hex::encode(public_key.to_bytes()))
+        );
+        public_key
+            .verify(&payload, &Ed25519Signature::from_slice(&signature).unwrap())
+            .unwrap();
+
+        let other = SigningKey::from_bytes(&[8; 32]);
+        assert!(sign_auth_entry(signed.clone(), &other, &network_id, 1_100).is_err());
+
+        let tx =
+            build_transaction(public_key.to_bytes(), 5, 100, host_function, vec![signed]).unwrap();
+        let hash = transaction_hash(&tx, &network_id).unwrap();
+        let TransactionEnvelope::Tx(envelope) = sign_transaction(tx, &signer, &network_id).unwrap()
+        else {
+            panic!("expected a v1 envelope");
+        };
+        let decorated = &envelope.signatures[0];
+        assert_eq!(decorated.hint.0, public_key.to_bytes()[28..]);
+        public_key
+            .verify(
+                &hash,
+                &Ed25519Signature::from_slice(decorated.signature.as_slice()).unwrap(),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn parses_secret_seeds() {
+        let seed = stellar_strkey::ed25519::PrivateKey([7; 32]).to_string();
+        assert_eq!(
+            parse_secret(&seed).unwrap().to_bytes(),
+            SigningKey::from_bytes(&[7; 32]).to_bytes()
+        );
+        assert!(parse_secret(&alice()).is_err());
+    }
+}
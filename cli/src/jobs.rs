@@ -0,0 +1,127 @@
+//! Following registry background jobs.
+//!
+//! Verification, dependency scans and compatibility runs are queued by the
+//! API, which answers `202 Accepted` with a `job_id`. [`wait_for_job`] polls
+//! `/api/jobs/:id` until the job finishes and returns its result.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub kind: String,
+    pub status: String,
+    pub progress: i16,
+    pub progress_message: Option<String>,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+impl Job {
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status.as_str(), "succeeded" | "failed" | "cancelled")
+    }
+}
+
+pub async fn get_job(client: &reqwest::Client, api_url: &str, job_id: &str) -> Result<Job> {
+    let response = client
+        .get(format!("{}/api/jobs/{}", api_url, job_id))
+        .send()
+        .await
+        .context("Failed to fetch job status")?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Failed to fetch job {}: {}",
+            job_id,
+            response.text().await.unwrap_or_default()
+        );
+    }
+    response.json().await.context("Failed to parse job status")
+}
+
+/// Poll a job until it finishes or `timeout` elapses. Progress messages are
+/// printed as they change unless `quiet` is set.
+pub async fn wait_for_job(
+    client: &reqwest::Client,
+    api_url: &str,
+    job_id: &str,
+    timeout: Duration,
+    quiet: bool,
+) -> Result<Job> {
+    let started = Instant::now();
+    let mut last_message: Option<String> = None;
+    loop {
+        let job = get_job(client, api_url, job_id).await?;
+        if !quiet && job.progress_message.is_some() && job.progress_message != last_message {
+            println!(
+                "  {} [{:>3}%] {}",
+                "…".bright_black(),
+                job.progress,
+                job.progress_message.as_deref().unwrap_or_default()
+            );
+            last_message = job.progress_message.clone();
+        }
+        if job.is_finished() {
+            return Ok(job);
+        }
+        if started.elapsed() >= timeout {
+            anyhow::bail!(
+                "Job {} is still {} after {}s; check it later with GET /api/jobs/{}",
+                job_id,
+                job.status,
+                timeout.as_secs(),
+                job_id
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// The result of a finished job, or its error.
+pub fn into_result(job: Job) -> Result<serde_json::Value> {
+    match job.status.as_str() {
+        "succeeded" => Ok(job.result.unwrap_or(serde_json::Value::Null)),
+        "cancelled" => anyhow::bail!("Job {} was cancelled", job.id),
+        _ => anyhow::bail!(
+            "Job {} failed: {}",
+            job.id,
+            job.error.as_deref().unwrap_or("unknown error")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(status: &str) -> Job {
+        Job {
+            id: "j1".into(),
+            kind: "scan_contract".into(),
+            status: status.into(),
+            progress: 100,
+            progress_message: None,
+            result: Some(serde_json::json!({ "findings": [] })),
+            error: Some("boom".into()),
+        }
+    }
+
+    #[test]
+    fn finished_jobs_yield_result_or_error() {
+        assert!(!job("running").is_finished());
+        assert_eq!(
+            into_result(job("succeeded")).unwrap(),
+            serde_json::json!({ "findings": [] })
+        );
+        assert!(into_result(job("failed"))
+            .unwrap_err()
+            .to_string()
+            .contains("boom"));
+        assert!(into_result(job("cancelled")).is_err());
+    }
+}
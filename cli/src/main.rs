@@ -1,10 +1,13 @@
 #![allow(unused_variables)]
 
+mod artifacts;
+mod audit;
 mod backup;
 mod batch_verify;
 mod commands;
 mod config;
 mod conversions;
+mod costs;
 mod coverage;
 mod events;
 mod export;
@@ -12,7 +15,9 @@ mod formal_verification;
 mod fuzz;
 mod import;
 mod incident;
+mod invoke;
 mod io_utils;
+mod jobs;
 mod manifest;
 mod migration;
 mod multisig;
@@ -20,14 +25,18 @@ mod package_signing;
 mod patch;
 mod release_notes;
 mod profiler;
+mod rpc;
+mod sandbox;
 mod sla;
 mod test_framework;
+mod wasm_instrument;
 mod webhook;
 mod wizard;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use patch::Severity;
+use std::path::PathBuf;
 
 /// Soroban Registry CLI — discover, publish, verify, and deploy Soroban contracts
 #[derive(Debug, Parser)]
@@ -68,6 +77,12 @@ pub enum Commands {
         /// Filter by contract category (e.g. DEX, token, lending, oracle)
         #[arg(long)]
         category: Option<String>,
+        /// Only show contracts carrying this tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Disable matching of misspelled terms
+        #[arg(long)]
+        no_fuzzy: bool,
         /// Maximum number of results to return
         #[arg(long, default_value = "20")]
         limit: usize,
@@ -114,6 +129,57 @@ pub enum Commands {
         /// Publisher Stellar address
         #[arg(long)]
         publisher: String,
+
+        /// Compiled contract WASM to store with the contract
+        #[arg(long)]
+        wasm: Option<PathBuf>,
+    },
+
+    /// Upload the WASM binary and/or source of a published version
+    Push {
+        /// Contract registry ID or on-chain contract ID
+        contract_id: String,
+
+        /// Version to attach the artifacts to
+        version: String,
+
+        /// Compiled WASM; must match the version's registered wasm_hash
+        #[arg(long)]
+        wasm: Option<PathBuf>,
+
+        /// Source directory (packed reproducibly) or an existing .tar.gz
+        #[arg(long)]
+        source: Option<PathBuf>,
+
+        /// Publisher's secret seed (S...); signs each upload for the
+        /// version's publisher_key
+        #[arg(long, env = "SOROBAN_REGISTRY_PUBLISHER_SECRET", hide_env_values = true)]
+        secret: String,
+
+        /// Output results as machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Download the WASM binary (and optionally source) of a version
+    Pull {
+        /// Contract registry ID or on-chain contract ID
+        contract_id: String,
+
+        /// Version to download
+        version: String,
+
+        /// Directory to write the artifacts to
+        #[arg(long, short = 'o', default_value = ".")]
+        output: PathBuf,
+
+        /// Also download the source tarball
+        #[arg(long)]
+        source: bool,
+
+        /// Output results as machine-readable JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// List recent contracts
@@ -282,10 +348,10 @@ pub enum Commands {
 
     /// Run integration tests
     Test {
-        /// Path to test file (YAML or JSON)
+        /// Path to a test scenario (YAML or JSON) or a directory of scenarios
         test_file: String,
 
-        /// Path to contract directory or file
+        /// Contract .wasm file, directory of .wasm files, or contract crate to build
         #[arg(long)]
         contract_path: Option<String>,
 
@@ -336,16 +402,19 @@ pub enum Commands {
         contract_id: String,
         #[arg(long, default_value = ",")]
         dependencies: String,
+        /// Cargo.lock to scan, including transitive dependencies
+        #[arg(long)]
+        lockfile: Option<String>,
         #[arg(long, default_value_t = false)]
         fail_on_high: bool,
     },
 
     /// Measure and report code coverage for contract tests
     Coverage {
-        /// Path to contract directory
+        /// Path to contract directory or compiled .wasm
         contract_path: String,
 
-        /// Path to test directory or file
+        /// Path to test scenario directory or file
         #[arg(long)]
         tests: String,
 
@@ -353,9 +422,92 @@ pub enum Commands {
         #[arg(long, default_value_t = 0.0)]
         threshold: f64,
 
-        /// Output directory for HTML reports
+        /// Output directory for HTML and LCOV reports
         #[arg(long, default_value = "coverage_report")]
         output: String,
+
+        /// Random inputs to generate per exported function, in addition to the scenarios
+        #[arg(long, default_value_t = 0)]
+        fuzz_cases: usize,
+    },
+
+    /// Estimate fees and resource usage for contract calls
+    Costs {
+        /// Contract .wasm, contract crate, or registry contract ID
+        contract: String,
+
+        /// Function to price
+        #[arg(long)]
+        method: Option<String>,
+
+        /// Arguments for --method as a JSON array
+        #[arg(long)]
+        args: Option<String>,
+
+        /// Test scenario file or directory whose calls are priced as a batch
+        #[arg(long)]
+        scenario: Option<String>,
+
+        /// Number of consecutive calls of --method to price
+        #[arg(long, default_value_t = 1)]
+        invocations: u32,
+
+        /// Network fee schedule (JSON or TOML) to price against
+        #[arg(long)]
+        fee_schedule: Option<String>,
+
+        /// Ledgers to keep touched entries alive for when pricing TTL extension
+        #[arg(long, default_value_t = 535_680)]
+        ttl_horizon: u32,
+
+        /// Print the estimate as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Show optimization suggestions (registry contracts)
+        #[arg(long)]
+        optimize: bool,
+
+        /// Show a daily/monthly/yearly forecast (registry contracts)
+        #[arg(long)]
+        forecast: bool,
+    },
+
+    /// Call a contract function with arguments typed against its registry ABI
+    Invoke {
+        /// Registry contract UUID or contract address (C...)
+        contract: String,
+
+        /// Function to call
+        function: String,
+
+        /// Function argument as name=value (repeatable)
+        #[arg(long = "arg", value_name = "NAME=VALUE")]
+        args: Vec<String>,
+
+        /// Secret seed (S...) of the account that signs and pays
+        #[arg(long, env = "SOROBAN_SECRET_KEY", hide_env_values = true)]
+        source: Option<String>,
+
+        /// Soroban RPC endpoint (defaults to the network's public endpoint)
+        #[arg(long, env = "SOROBAN_RPC_URL")]
+        rpc_url: Option<String>,
+
+        /// Only simulate the call; never sign or submit
+        #[arg(long)]
+        simulate_only: bool,
+
+        /// Contract version whose ABI types the call (latest by default)
+        #[arg(long)]
+        contract_version: Option<String>,
+
+        /// Inclusion fee in stroops, added to the simulated resource fee
+        #[arg(long, default_value_t = 100)]
+        fee: u32,
+
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Sign a contract package with your private key
@@ -396,6 +548,14 @@ pub enum Commands {
         /// Signature (base64, optional - will lookup from registry if not provided)
         #[arg(long)]
         signature: Option<String>,
+
+        /// Saved transparency log inclusion proof; verifies offline when set
+        #[arg(long, requires = "log_key")]
+        inclusion_proof: Option<String>,
+
+        /// Pinned transparency log public key (base64)
+        #[arg(long, env = "SOROBAN_REGISTRY_LOG_KEY")]
+        log_key: Option<String>,
     },
 
     /// Verify a contract binary against an Ed25519 signature locally
@@ -426,6 +586,12 @@ pub enum Commands {
         action: KeysCommands,
     },
 
+    /// Verify and export a contract's signed audit log
+    Audit {
+        #[command(subcommand)]
+        action: AuditCommands,
+    },
+    /// Queue verification of multiple contracts and wait for the results
     /// Verify multiple contracts in a single atomic batch (all succeed or all rollback)
     BatchVerify {
         /// Comma-separated list of contract IDs to verify.
@@ -624,13 +790,16 @@ pub enum MultisigCommands {
         threshold: u32,
         #[arg(long)]
         signers: String,
+        /// Comma-separated signer weights, in --signers order (default 1 each)
+        #[arg(long)]
+        weights: Option<String>,
         #[arg(long)]
         expiry_secs: Option<u32>,
         #[arg(long)]
         created_by: String,
     },
 
-    /// Create an unsigned deployment proposal
+    /// Create a deployment proposal around an unsigned transaction
     CreateProposal {
         #[arg(long)]
         contract_name: String,
@@ -646,19 +815,39 @@ pub enum MultisigCommands {
         proposer: String,
         #[arg(long)]
         description: Option<String>,
+        /// What the transaction does: upload, create or upgrade
+        #[arg(long, value_parser = ["upload", "create", "upgrade"])]
+        operation: String,
+        /// Unsigned transaction envelope as base64 XDR, or a file holding it
+        /// (e.g. from `stellar contract upload --build-only`)
+        #[arg(long)]
+        transaction: String,
     },
 
-    /// Sign a deployment proposal (add your approval)
+    /// Sign a deployment proposal's transaction (add your approval)
     Sign {
         proposal_id: String,
-        #[arg(long)]
-        signer: String,
-        #[arg(long)]
+        /// Secret seed (S...) of the signer; the envelope is signed locally
+        #[arg(long, env = "SOROBAN_SECRET_KEY", hide_env_values = true)]
+        secret: Option<String>,
+        /// Signer address (G...) for a signature made elsewhere
+        #[arg(long, requires = "signature_data")]
+        signer: Option<String>,
+        /// Base64 XDR DecoratedSignature over the proposal's transaction hash
+        #[arg(long, requires = "signer")]
         signature_data: Option<String>,
     },
 
     /// Execute an approved deployment proposal
-    Execute { proposal_id: String },
+    Execute {
+        proposal_id: String,
+        /// Submit the signed transaction through the registry's RPC node
+        #[arg(long)]
+        submit: bool,
+        /// Write the signed envelope (base64 XDR) to this file
+        #[arg(long)]
+        output: Option<String>,
+    },
 
     /// Show full info for a proposal (signatures, policy, status)
     Info { proposal_id: String },
@@ -758,6 +947,15 @@ pub enum KeysCommands {
         contract_id: String,
     },
 
+    /// Download a signature's transparency log inclusion proof
+    Proof {
+        /// Signature ID
+        signature_id: String,
+        /// Output file (defaults to <signature_id>.proof.json)
+        #[arg(long)]
+        output: Option<String>,
+    },
+
     /// View transparency log
     Log {
         /// Filter by contract ID
@@ -772,6 +970,40 @@ pub enum KeysCommands {
     },
 }
 
+/// Sub-commands for the `audit` group
+#[derive(Debug, Subcommand)]
+pub enum AuditCommands {
+    /// Ask the registry to walk a contract's audit chain and report the
+    /// first broken entry
+    Verify {
+        /// Contract registry ID (UUID)
+        contract_id: String,
+    },
+
+    /// Download a signed bundle of a contract's whole audit chain
+    Export {
+        /// Contract registry ID (UUID)
+        contract_id: String,
+        /// Output file (defaults to <contract_id>.audit.json)
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Verify an exported audit bundle offline
+    VerifyBundle {
+        /// Bundle file from `audit export`
+        file: String,
+        /// Trusted registry audit key (base64); repeat for rotated keys
+        #[arg(
+            long = "audit-key",
+            env = "SOROBAN_REGISTRY_AUDIT_KEY",
+            value_delimiter = ',',
+            required = true
+        )]
+        audit_keys: Vec<String>,
+    },
+}
+
 /// Sub-commands for the `webhook` group
 #[derive(Debug, Subcommand)]
 pub enum WebhookCommands {
@@ -783,13 +1015,23 @@ pub enum WebhookCommands {
 
         /// Comma-separated list of events to subscribe to.
         /// Valid: contract.published, contract.verified,
-        ///        contract.failed_verification, version.created
+        ///        contract.failed_verification, version.created,
+        ///        contract.deprecated, vulnerability.detected,
+        ///        governance.proposal_executed
         #[arg(long)]
         events: String,
 
         /// Optional HMAC-SHA256 secret key (auto-generated if omitted)
         #[arg(long)]
         secret: Option<String>,
+
+        /// Only receive events for this contract (registry UUID)
+        #[arg(long)]
+        contract_id: Option<String>,
+
+        /// Only receive events for this publisher's contracts
+        #[arg(long)]
+        publisher_id: Option<String>,
     },
 
     /// List all registered webhooks
@@ -910,6 +1152,8 @@ async fn main() -> Result<()> {
             verified_only,
             networks,
             category,
+            tags,
+            no_fuzzy,
             limit,
             offset,
             json,
@@ -931,6 +1175,8 @@ async fn main() -> Result<()> {
                 verified_only,
                 networks_vec,
                 category.as_deref(),
+                tags,
+                !no_fuzzy,
                 limit,
                 offset,
                 json,
@@ -949,6 +1195,7 @@ async fn main() -> Result<()> {
             category,
             tags,
             publisher,
+            wasm,
         } => {
             let tags_vec = tags
                 .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
@@ -968,9 +1215,40 @@ async fn main() -> Result<()> {
                 category.as_deref(),
                 tags_vec,
                 &publisher,
+                wasm.as_deref(),
+            )
+            .await?;
+        }
+        Commands::Push {
+            contract_id,
+            version,
+            wasm,
+            source,
+            secret,
+            json,
+        } => {
+            log::debug!("Command: push | contract_id={} version={}", contract_id, version);
+            artifacts::push(
+                &cli.api_url,
+                &contract_id,
+                &version,
+                wasm.as_deref(),
+                source.as_deref(),
+                &secret,
+                json,
             )
             .await?;
         }
+        Commands::Pull {
+            contract_id,
+            version,
+            output,
+            source,
+            json,
+        } => {
+            log::debug!("Command: pull | contract_id={} version={}", contract_id, version);
+            artifacts::pull(&cli.api_url, &contract_id, &version, &output, source, json).await?;
+        }
         Commands::List { limit, json } => {
             log::debug!("Command: list | limit={}", limit);
             commands::list(&cli.api_url, limit, network, json).await?;
@@ -1137,11 +1415,20 @@ async fn main() -> Result<()> {
                 name,
                 threshold,
                 signers,
+                weights,
                 expiry_secs,
                 created_by,
             } => {
                 let signer_vec: Vec<String> =
                     signers.split(',').map(|s| s.trim().to_string()).collect();
+                let weight_vec = weights
+                    .map(|w| {
+                        w.split(',')
+                            .map(|s| s.trim().parse::<u32>())
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .transpose()
+                    .map_err(|_| anyhow::anyhow!("--weights must be comma-separated integers"))?;
                 log::debug!(
                     "Command: multisig create-policy | name={} threshold={} signers={:?}",
                     name,
@@ -1153,6 +1440,7 @@ async fn main() -> Result<()> {
                     &name,
                     threshold,
                     signer_vec,
+                    weight_vec,
                     expiry_secs,
                     &created_by,
                 )
@@ -1166,11 +1454,14 @@ async fn main() -> Result<()> {
                 policy_id,
                 proposer,
                 description,
+                operation,
+                transaction,
             } => {
                 log::debug!(
-                    "Command: multisig create-proposal | contract_id={} policy_id={}",
+                    "Command: multisig create-proposal | contract_id={} policy_id={} operation={}",
                     contract_id,
-                    policy_id
+                    policy_id,
+                    operation
                 );
                 multisig::create_proposal(
                     &cli.api_url,
@@ -1181,11 +1472,14 @@ async fn main() -> Result<()> {
                     &policy_id,
                     &proposer,
                     description.as_deref(),
+                    &operation,
+                    &transaction,
                 )
                 .await?;
             }
             MultisigCommands::Sign {
                 proposal_id,
+                secret,
                 signer,
                 signature_data,
             } => {
@@ -1193,14 +1487,24 @@ async fn main() -> Result<()> {
                 multisig::sign_proposal(
                     &cli.api_url,
                     &proposal_id,
-                    &signer,
+                    secret.as_deref(),
+                    signer.as_deref(),
                     signature_data.as_deref(),
                 )
                 .await?;
             }
-            MultisigCommands::Execute { proposal_id } => {
-                log::debug!("Command: multisig execute | proposal_id={}", proposal_id);
-                multisig::execute_proposal(&cli.api_url, &proposal_id).await?;
+            MultisigCommands::Execute {
+                proposal_id,
+                submit,
+                output,
+            } => {
+                log::debug!(
+                    "Command: multisig execute | proposal_id={} submit={}",
+                    proposal_id,
+                    submit
+                );
+                multisig::execute_proposal(&cli.api_url, &proposal_id, submit, output.as_deref())
+                    .await?;
             }
             MultisigCommands::Info { proposal_id } => {
                 log::debug!("Command: multisig info | proposal_id={}", proposal_id);
@@ -1356,17 +1660,77 @@ async fn main() -> Result<()> {
         Commands::ScanDeps {
             contract_id,
             dependencies,
+            lockfile,
             fail_on_high,
         } => {
-            commands::scan_deps(&cli.api_url, &contract_id, &dependencies, fail_on_high).await?;
+            commands::scan_deps(
+                &cli.api_url,
+                &contract_id,
+                &dependencies,
+                lockfile.as_deref(),
+                fail_on_high,
+            )
+            .await?;
         }
         Commands::Coverage {
             contract_path,
             tests,
             threshold,
             output,
+            fuzz_cases,
+        } => {
+            coverage::run(&contract_path, &tests, threshold, &output, fuzz_cases).await?;
+        }
+        Commands::Costs {
+            contract,
+            method,
+            args,
+            scenario,
+            invocations,
+            fee_schedule,
+            ttl_horizon,
+            json,
+            optimize,
+            forecast,
         } => {
-            coverage::run(&contract_path, &tests, threshold, &output).await?;
+            let opts = costs::EstimateOptions {
+                contract: &contract,
+                method: method.as_deref(),
+                args: args.as_deref(),
+                scenario: scenario.as_deref(),
+                invocations,
+                fee_schedule: fee_schedule.as_deref(),
+                ttl_horizon,
+                json,
+                optimize,
+                forecast,
+            };
+            costs::estimate_costs(&cli.api_url, &opts).await?;
+        }
+        Commands::Invoke {
+            contract,
+            function,
+            args,
+            source,
+            rpc_url,
+            simulate_only,
+            contract_version,
+            fee,
+            json,
+        } => {
+            let opts = invoke::InvokeOptions {
+                contract: &contract,
+                function: &function,
+                args: &args,
+                source: source.as_deref(),
+                rpc_url: rpc_url.as_deref(),
+                network: cfg_network,
+                simulate_only,
+                contract_version: contract_version.as_deref(),
+                fee,
+                json,
+            };
+            invoke::run(&cli.api_url, &opts).await?;
         }
         Commands::Sign {
             package,
@@ -1396,20 +1760,32 @@ async fn main() -> Result<()> {
             contract_id,
             version,
             signature,
+            inclusion_proof,
+            log_key,
         } => {
             log::debug!(
                 "Command: verify | package={} contract_id={}",
                 package,
                 contract_id
             );
-            package_signing::verify_package(
-                &cli.api_url,
-                &package,
-                &contract_id,
-                version.as_deref(),
-                signature.as_deref(),
-            )
-            .await?;
+            if let (Some(proof), Some(log_key)) = (&inclusion_proof, &log_key) {
+                package_signing::verify_inclusion_offline(
+                    &package,
+                    &contract_id,
+                    version.as_deref(),
+                    proof,
+                    log_key,
+                )?;
+            } else {
+                package_signing::verify_package(
+                    &cli.api_url,
+                    &package,
+                    &contract_id,
+                    version.as_deref(),
+                    signature.as_deref(),
+                )
+                .await?;
+            }
         }
         Commands::VerifyContract {
             wasm_path,
@@ -1455,6 +1831,18 @@ async fn main() -> Result<()> {
                 log::debug!("Command: keys custody | contract_id={}", contract_id);
                 package_signing::get_chain_of_custody(&cli.api_url, &contract_id).await?;
             }
+            KeysCommands::Proof {
+                signature_id,
+                output,
+            } => {
+                log::debug!("Command: keys proof | signature_id={}", signature_id);
+                package_signing::save_inclusion_proof(
+                    &cli.api_url,
+                    &signature_id,
+                    output.as_deref(),
+                )
+                .await?;
+            }
             KeysCommands::Log {
                 contract_id,
                 entry_type,
@@ -1482,13 +1870,43 @@ async fn main() -> Result<()> {
             );
             batch_verify::run_batch_verify(&cli.api_url, &contracts, &initiated_by, json).await?;
         }
+        Commands::Audit { action } => match action {
+            AuditCommands::Verify { contract_id } => {
+                log::debug!("Command: audit verify | contract_id={}", contract_id);
+                audit::verify_chain(&cli.api_url, &contract_id).await?;
+            }
+            AuditCommands::Export {
+                contract_id,
+                output,
+            } => {
+                log::debug!("Command: audit export | contract_id={}", contract_id);
+                audit::export_bundle(&cli.api_url, &contract_id, output.as_deref()).await?;
+            }
+            AuditCommands::VerifyBundle { file, audit_keys } => {
+                log::debug!("Command: audit verify-bundle | file={}", file);
+                audit::verify_bundle(&file, &audit_keys)?;
+            }
+        },
         Commands::Webhook { action } => match action {
-            WebhookCommands::Create { url, events, secret } => {
+            WebhookCommands::Create {
+                url,
+                events,
+                secret,
+                contract_id,
+                publisher_id,
+            } => {
                 let event_list: Vec<String> =
                     events.split(',').map(|s| s.trim().to_string()).collect();
                 log::debug!("Command: webhook create | url={} events={:?}", url, event_list);
-                webhook::create_webhook(&cli.api_url, &url, event_list, secret.as_deref())
-                    .await?;
+                webhook::create_webhook(
+                    &cli.api_url,
+                    &url,
+                    event_list,
+                    secret.as_deref(),
+                    contract_id.as_deref(),
+                    publisher_id.as_deref(),
+                )
+                .await?;
             }
             WebhookCommands::List {} => {
                 log::debug!("Command: webhook list");
@@ -1,9 +1,49 @@
 // cli/src/multisig.rs
 // CLI functions for Multi-Signature Contract Deployment (issue #47)
 
-use anyhow::{Context, Result};
+use crate::config::Network;
+use crate::invoke;
+use crate::rpc;
+use anyhow::{anyhow, bail, Context, Result};
 use colored::Colorize;
+use ed25519_dalek::{Signer, SigningKey};
 use serde_json::json;
+use soroban_env_host::xdr::{
+    DecoratedSignature, Limits, ReadXdr, Signature, SignatureHint, TransactionEnvelope, WriteXdr,
+};
+use std::path::Path;
+
+/// Base64 XDR given inline or as a path to a file containing it.
+fn read_envelope_arg(value: &str) -> Result<String> {
+    if Path::new(value).is_file() {
+        let contents = std::fs::read_to_string(value)
+            .with_context(|| format!("Failed to read transaction file: {}", value))?;
+        return Ok(contents.trim().to_string());
+    }
+    Ok(value.trim().to_string())
+}
+
+/// Signs the proposal's unsigned envelope for `network`, returning the hex
+/// transaction hash and the base64 `DecoratedSignature`.
+fn sign_envelope(
+    transaction_xdr: &str,
+    network: Network,
+    signer: &SigningKey,
+) -> Result<(String, String)> {
+    let envelope = TransactionEnvelope::from_xdr_base64(transaction_xdr, Limits::none())
+        .context("Proposal carries an undecodable transaction envelope")?;
+    let TransactionEnvelope::Tx(envelope) = envelope else {
+        bail!("Proposal envelope is not a v1 transaction");
+    };
+    let network_id = rpc::network_id(rpc::network_passphrase(network));
+    let hash = invoke::transaction_hash(&envelope.tx, &network_id)?;
+    let public_key = signer.verifying_key().to_bytes();
+    let signature = DecoratedSignature {
+        hint: SignatureHint(public_key[28..].try_into()?),
+        signature: Signature(signer.sign(&hash).to_bytes().to_vec().try_into()?),
+    };
+    Ok((hex::encode(hash), signature.to_xdr_base64(Limits::none())?))
+}
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Create a new multi-sig policy
@@ -14,6 +54,7 @@ pub async fn create_policy(
     name: &str,
     threshold: u32,
     signers: Vec<String>,
+    weights: Option<Vec<u32>>,
     expiry_secs: Option<u32>,
     created_by: &str,
 ) -> Result<()> {
@@ -24,6 +65,7 @@ pub async fn create_policy(
         "name": name,
         "threshold": threshold,
         "signer_addresses": signers,
+        "signer_weights": weights,
         "expiry_seconds": expiry_secs,
         "created_by": created_by,
     });
@@ -71,9 +113,18 @@ pub async fn create_policy(
     );
 
     if let Some(signers) = policy["signer_addresses"].as_array() {
+        let weights = policy["signer_weights"].as_array();
         println!("\n  {} Authorized signers:", "→".bright_black());
-        for s in signers {
-            println!("    • {}", s.as_str().unwrap_or("?").bright_magenta());
+        for (i, s) in signers.iter().enumerate() {
+            let weight = weights
+                .and_then(|w| w.get(i))
+                .and_then(|w| w.as_i64())
+                .unwrap_or(1);
+            println!(
+                "    • {} (weight {})",
+                s.as_str().unwrap_or("?").bright_magenta(),
+                weight
+            );
         }
     }
     println!();
@@ -94,9 +145,12 @@ pub async fn create_proposal(
     policy_id: &str,
     proposer: &str,
     description: Option<&str>,
+    operation: &str,
+    transaction: &str,
 ) -> Result<()> {
     let client = reqwest::Client::new();
     let url = format!("{}/api/contracts/deploy-proposal", api_url);
+    let transaction_xdr = read_envelope_arg(transaction)?;
 
     let payload = json!({
         "contract_name": contract_name,
@@ -106,6 +160,8 @@ pub async fn create_proposal(
         "policy_id": policy_id,
         "proposer": proposer,
         "description": description,
+        "operation": operation,
+        "transaction_xdr": transaction_xdr,
     });
 
     println!("\n{}", "Creating deployment proposal...".bold().cyan());
@@ -148,6 +204,19 @@ pub async fn create_proposal(
         "Network".bold(),
         proposal["network"].as_str().unwrap_or("?").bright_blue()
     );
+    println!(
+        "  {}: {}",
+        "Operation".bold(),
+        proposal["operation"].as_str().unwrap_or("?")
+    );
+    println!(
+        "  {}: {}",
+        "Transaction".bold(),
+        proposal["transaction_hash"]
+            .as_str()
+            .unwrap_or("?")
+            .bright_black()
+    );
     println!(
         "  {}: {}",
         "Status".bold(),
@@ -170,15 +239,67 @@ pub async fn create_proposal(
 // Sign a proposal
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Signs with `secret` (the signer's S... seed) after checking the envelope
+/// locally, or forwards a `signature_data` produced elsewhere for `signer`.
 pub async fn sign_proposal(
     api_url: &str,
     proposal_id: &str,
-    signer_address: &str,
+    secret: Option<&str>,
+    signer: Option<&str>,
     signature_data: Option<&str>,
 ) -> Result<()> {
     let client = reqwest::Client::new();
     let url = format!("{}/api/contracts/{}/sign", api_url, proposal_id);
 
+    let (signer_address, signature_data) = match (secret, signer, signature_data) {
+        (Some(secret), _, _) => {
+            let seed = stellar_strkey::ed25519::PrivateKey::from_string(secret.trim())
+                .map_err(|_| anyhow!("--secret must be a secret seed (S...)"))?;
+            let key = SigningKey::from_bytes(&seed.0);
+            let response = client
+                .get(format!(
+                    "{}/api/contracts/{}/proposal",
+                    api_url, proposal_id
+                ))
+                .send()
+                .await
+                .context("Failed to fetch proposal info")?;
+            if !response.status().is_success() {
+                let err = response.text().await?;
+                bail!("API error: {}", err);
+            }
+            let data: serde_json::Value = response.json().await?;
+            let proposal = &data["proposal"];
+            let transaction_xdr = proposal["transaction_xdr"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Proposal has no transaction envelope to sign"))?;
+            let network: Network = proposal["network"].as_str().unwrap_or("testnet").parse()?;
+            let (hash, signature) = sign_envelope(transaction_xdr, network, &key)?;
+            if proposal["transaction_hash"].as_str() != Some(hash.as_str()) {
+                bail!(
+                    "Registry reports transaction hash {} but the envelope hashes to {}",
+                    proposal["transaction_hash"].as_str().unwrap_or("?"),
+                    hash
+                );
+            }
+            println!(
+                "  {}: {} {} ({})",
+                "Signing".bold(),
+                proposal["operation"].as_str().unwrap_or("?"),
+                proposal["wasm_hash"].as_str().unwrap_or("?").bright_black(),
+                network
+            );
+            println!("  {}: {}", "Transaction".bold(), hash.bright_black());
+            let address =
+                stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string();
+            (address, signature)
+        }
+        (None, Some(signer), Some(signature_data)) => {
+            (signer.to_string(), signature_data.to_string())
+        }
+        _ => bail!("Pass --secret, or both --signer and --signature-data"),
+    };
+
     let payload = json!({
         "signer_address": signer_address,
         "signature_data": signature_data,
@@ -218,7 +339,7 @@ pub async fn sign_proposal(
         );
     } else {
         println!(
-            "  Signatures: {}/{} collected — {} more needed",
+            "  Signer weight: {}/{} collected — {} more needed",
             collected,
             collected + needed,
             needed.to_string().yellow().bold()
@@ -234,7 +355,12 @@ pub async fn sign_proposal(
 // Execute a proposal
 // ─────────────────────────────────────────────────────────────────────────────
 
-pub async fn execute_proposal(api_url: &str, proposal_id: &str) -> Result<()> {
+pub async fn execute_proposal(
+    api_url: &str,
+    proposal_id: &str,
+    submit: bool,
+    output: Option<&str>,
+) -> Result<()> {
     let client = reqwest::Client::new();
     let url = format!("{}/api/contracts/{}/execute", api_url, proposal_id);
 
@@ -243,6 +369,7 @@ pub async fn execute_proposal(api_url: &str, proposal_id: &str) -> Result<()> {
 
     let response = client
         .post(&url)
+        .json(&json!({ "submit": submit }))
         .send()
         .await
         .context("Failed to execute proposal")?;
@@ -255,7 +382,17 @@ pub async fn execute_proposal(api_url: &str, proposal_id: &str) -> Result<()> {
         anyhow::bail!("API error ({}): {}", status, err);
     }
 
-    println!("{}", "✓ Deployment executed successfully!".green().bold());
+    let signed_envelope = body["signed_envelope_xdr"].as_str().unwrap_or_default();
+    if body["submitted"].as_bool().unwrap_or(false) {
+        println!("{}", "✓ Deployment transaction submitted!".green().bold());
+        println!(
+            "  {}: {}",
+            "RPC status".bold(),
+            body["submission_status"].as_str().unwrap_or("?")
+        );
+    } else {
+        println!("{}", "✓ Deployment transaction signed!".green().bold());
+    }
     println!(
         "  {}: {}",
         "Contract".bold(),
@@ -266,11 +403,30 @@ pub async fn execute_proposal(api_url: &str, proposal_id: &str) -> Result<()> {
         "WASM Hash".bold(),
         body["wasm_hash"].as_str().unwrap_or("?").bright_black()
     );
+    println!(
+        "  {}: {}",
+        "Transaction".bold(),
+        body["transaction_hash"]
+            .as_str()
+            .unwrap_or("?")
+            .bright_black()
+    );
     println!(
         "  {}: {}",
         "Executed at".bold(),
         body["executed_at"].as_str().unwrap_or("?")
     );
+    match output {
+        Some(path) => {
+            std::fs::write(path, signed_envelope)
+                .with_context(|| format!("Failed to write signed envelope: {}", path))?;
+            println!("  {}: {}", "Signed envelope".bold(), path.bright_black());
+        }
+        None if !signed_envelope.is_empty() => {
+            println!("  {}:\n{}", "Signed envelope".bold(), signed_envelope);
+        }
+        None => {}
+    }
     println!();
 
     Ok(())
@@ -341,6 +497,12 @@ pub async fn proposal_info(api_url: &str, proposal_id: &str) -> Result<()> {
         proposal["network"].as_str().unwrap_or("?").bright_blue()
     );
     println!("  {}: {}", "Status".bold(), status_colored);
+    if let Some(operation) = proposal["operation"].as_str() {
+        println!("  {}: {}", "Operation".bold(), operation);
+    }
+    if let Some(hash) = proposal["transaction_hash"].as_str() {
+        println!("  {}: {}", "Transaction".bold(), hash.bright_black());
+    }
     println!(
         "  {}: {}",
         "Proposer".bold(),
@@ -373,10 +535,11 @@ pub async fn proposal_info(api_url: &str, proposal_id: &str) -> Result<()> {
     );
 
     println!(
-        "\n  {} Signatures: {}/{} collected{}",
+        "\n  {} Signer weight: {}/{} collected from {} signature(s){}",
         "→".bright_black(),
-        signatures.len(),
+        data["collected_weight"].as_i64().unwrap_or(0),
         policy["threshold"].as_i64().unwrap_or(0),
+        signatures.len(),
         if needed > 0 {
             format!(" ({} more needed)", needed.to_string().yellow())
         } else {
@@ -386,11 +549,12 @@ pub async fn proposal_info(api_url: &str, proposal_id: &str) -> Result<()> {
 
     for sig in &signatures {
         println!(
-            "    ✓ {} at {}",
+            "    ✓ {} (weight {}) at {}",
             sig["signer_address"]
                 .as_str()
                 .unwrap_or("?")
                 .bright_magenta(),
+            sig["weight"].as_i64().unwrap_or(1),
             sig["signed_at"].as_str().unwrap_or("?")
         );
     }
@@ -475,3 +639,56 @@ pub async fn list_proposals(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Verifier;
+    use soroban_env_host::xdr::{
+        HostFunction, InvokeHostFunctionOp, Memo, MuxedAccount, Operation, OperationBody,
+        Preconditions, SequenceNumber, Transaction, TransactionExt, TransactionV1Envelope, Uint256,
+    };
+
+    #[test]
+    fn signs_the_network_specific_transaction_hash() {
+        let key = SigningKey::from_bytes(&[4; 32]);
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(key.verifying_key().to_bytes())),
+            fee: 100,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                    host_function: HostFunction::UploadContractWasm(
+                        b"\0asm".to_vec().try_into().unwrap(),
+                    ),
+                    auth: Default::default(),
+                }),
+            }]
+            .try_into()
+            .unwrap(),
+            ext: TransactionExt::V0,
+        };
+        let xdr = TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx: tx.clone(),
+            signatures: Default::default(),
+        })
+        .to_xdr_base64(Limits::none())
+        .unwrap();
+
+        let (hash, signature) = sign_envelope(&xdr, Network::Testnet, &key).unwrap();
+        let (mainnet_hash, _) = sign_envelope(&xdr, Network::Mainnet, &key).unwrap();
+        assert_ne!(hash, mainnet_hash);
+
+        let decorated = DecoratedSignature::from_xdr_base64(&signature, Limits::none()).unwrap();
+        assert_eq!(decorated.hint.0, key.verifying_key().to_bytes()[28..]);
+        let signature =
+            ed25519_dalek::Signature::from_slice(decorated.signature.0.as_slice()).unwrap();
+        assert!(key
+            .verifying_key()
+            .verify(&hex::decode(hash).unwrap(), &signature)
+            .is_ok());
+    }
+}
@@ -2,10 +2,11 @@ use anyhow::{bail, Context, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chrono::Utc;
 use colored::Colorize;
-use ed25519_dalek::{Signer, SigningKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use rand::rngs::OsRng;
 use serde_json::json;
 use sha2::{Digest, Sha256};
+use shared::transparency::{self, InclusionProof, LogLeaf, TreeHead};
 use std::fs;
 use std::io::Read;
 use std::path::Path;
@@ -285,6 +286,170 @@ async fn verify_from_registry(
     Ok(())
 }
 
+/// Check a package against a saved inclusion proof without contacting the
+/// registry. The tree head must be signed by the pinned log key, the logged
+/// entry must be a signature over this package, and the entry must be in the
+/// signed tree.
+pub fn verify_inclusion_offline(
+    package_path: &str,
+    contract_id: &str,
+    version: Option<&str>,
+    proof_path: &str,
+    log_key_b64: &str,
+) -> Result<()> {
+    println!(
+        "\n{}",
+        "Verifying transparency log inclusion...".bold().cyan()
+    );
+
+    let package_data = read_package_file(package_path)?;
+    let package_hash = compute_hash(&package_data);
+    println!("  {}: {}", "Package".bold(), package_path.bright_black());
+    println!("  {}: {}", "Hash".bold(), package_hash.bright_black());
+
+    let proof: InclusionProof = serde_json::from_str(
+        &fs::read_to_string(proof_path)
+            .with_context(|| format!("Failed to read proof file: {}", proof_path))?,
+    )
+    .context("Proof file is not a transparency log inclusion proof")?;
+    let head = &proof.tree_head;
+
+    // 1. The tree head is signed by the pinned log key.
+    let log_key = decode_public_key(log_key_b64).context("Invalid --log-key")?;
+    if transparency::key_id(log_key.as_bytes()) != head.key_id {
+        bail!(
+            "Tree head is signed by key {}, not the pinned log key",
+            head.key_id
+        );
+    }
+    let head_signature =
+        decode_signature(&head.signature).context("Invalid tree head signature")?;
+    let message = TreeHead::signing_message(head.tree_size, &head.root_hash, head.timestamp);
+    if log_key.verify(&message, &head_signature).is_err() {
+        bail!("Tree head signature does not verify against the pinned log key");
+    }
+    println!(
+        "  {} Tree head of size {} signed by the pinned log key",
+        "✓".green(),
+        head.tree_size
+    );
+
+    // 2. The logged entry is a signature over this package.
+    let leaf: LogLeaf =
+        serde_json::from_str(&proof.leaf_data).context("Proof leaf is not a log entry")?;
+    if leaf.entry_type != "package_signed" {
+        bail!(
+            "Proof is for a {} entry, not a package signature",
+            leaf.entry_type
+        );
+    }
+    if leaf.wasm_hash.as_deref() != Some(package_hash.as_str()) {
+        bail!(
+            "Logged signature covers hash {}, not this package",
+            leaf.wasm_hash.as_deref().unwrap_or("?")
+        );
+    }
+    let leaf_version = leaf.version.as_deref().unwrap_or_default();
+    if version.is_some_and(|v| v != leaf_version) {
+        bail!("Logged signature is for version {}", leaf_version);
+    }
+    let (Some(signature_b64), Some(public_key_b64)) = (&leaf.signature, &leaf.public_key) else {
+        bail!("Logged entry does not carry the package signature");
+    };
+    let signer_key =
+        decode_public_key(public_key_b64).context("Invalid signer public key in proof")?;
+    let signature =
+        decode_signature(signature_b64).context("Invalid package signature in proof")?;
+    let signed_message = create_signing_message(&package_hash, contract_id, leaf_version);
+    if signer_key.verify(&signed_message, &signature).is_err() {
+        bail!("Logged signature does not verify for this package and contract ID");
+    }
+    println!(
+        "  {} Signature by {} over this package",
+        "✓".green(),
+        leaf.actor_address.bright_magenta()
+    );
+
+    // 3. The entry is in the signed tree.
+    let audit_path = proof
+        .audit_path
+        .iter()
+        .map(|h| transparency::decode_hash(h))
+        .collect::<Option<Vec<_>>>()
+        .context("Proof audit path contains an invalid hash")?;
+    let root =
+        transparency::decode_hash(&head.root_hash).context("Tree head root is not a hash")?;
+    let leaf_hash = transparency::leaf_hash(proof.leaf_data.as_bytes());
+    if !transparency::verify_inclusion(
+        &leaf_hash,
+        proof.leaf_index,
+        head.tree_size,
+        &audit_path,
+        &root,
+    ) {
+        bail!("Inclusion proof does not match the signed tree head");
+    }
+    println!(
+        "  {} Entry {} included in the log",
+        "✓".green(),
+        proof.leaf_index
+    );
+
+    println!("{}", "\n✓ Signature is LOGGED".green().bold());
+    println!(
+        "  {}",
+        "Revocations logged after this tree head are not checked offline.".bright_black()
+    );
+    println!();
+    Ok(())
+}
+
+/// Download a signature's inclusion proof for offline verification.
+pub async fn save_inclusion_proof(
+    api_url: &str,
+    signature_id: &str,
+    output: Option<&str>,
+) -> Result<()> {
+    let client = reqwest::Client::new();
+    let url = format!(
+        "{}/api/signatures/transparency/proof/inclusion?signature_id={}",
+        api_url, signature_id
+    );
+
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .context("Failed to reach registry API")?;
+
+    if !response.status().is_success() {
+        let err = response.text().await?;
+        bail!("Failed to get inclusion proof: {}", err);
+    }
+
+    let proof: InclusionProof = response.json().await?;
+    let path = output
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.proof.json", signature_id));
+    fs::write(&path, serde_json::to_string_pretty(&proof)?)
+        .with_context(|| format!("Failed to write proof file: {}", path))?;
+
+    println!("{}", "✓ Inclusion proof saved".green().bold());
+    println!("  {}: {}", "File".bold(), path.bright_black());
+    println!(
+        "  {}: {} (tree size {})",
+        "Leaf".bold(),
+        proof.leaf_index,
+        proof.tree_head.tree_size
+    );
+    println!(
+        "\n  {} Verify offline with: soroban-registry verify <package> --contract-id <id> --inclusion-proof {} --log-key <key>\n",
+        "→".bright_black(),
+        path
+    );
+    Ok(())
+}
+
 pub async fn revoke_signature(
     api_url: &str,
     signature_id: &str,
@@ -530,6 +695,29 @@ fn decode_private_key(key: &str) -> Result<SigningKey> {
     Ok(SigningKey::from_bytes(&bytes))
 }
 
+fn decode_public_key(key_b64: &str) -> Result<VerifyingKey> {
+    let bytes = BASE64
+        .decode(key_b64.trim())
+        .context("expected a base64-encoded Ed25519 public key")?;
+    let array: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Public key must decode to 32 bytes"))?;
+    VerifyingKey::from_bytes(&array)
+        .map_err(|_| anyhow::anyhow!("Public key is not a valid Ed25519 key"))
+}
+
+fn decode_signature(signature_b64: &str) -> Result<Signature> {
+    let bytes = BASE64
+        .decode(signature_b64.trim())
+        .context("expected a base64-encoded Ed25519 signature")?;
+    let array: [u8; 64] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Signature must decode to 64 bytes"))?;
+    Ok(Signature::from_bytes(&array))
+}
+
 fn create_signing_message(hash: &str, contract_id: &str, version: &str) -> Vec<u8> {
     format!("{}:{}:{}", contract_id, version, hash).into_bytes()
 }
@@ -0,0 +1,408 @@
+#![allow(dead_code)]
+
+//! Minimal Soroban RPC client (JSON-RPC 2.0 over HTTP).
+
+use crate::config::Network;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use soroban_env_host::xdr::{
+    AccountId, LedgerEntry, LedgerEntryData, LedgerKey, LedgerKeyAccount, Limits, PublicKey,
+    ReadXdr, Uint256, WriteXdr,
+};
+use std::time::Duration;
+
+pub const MAINNET_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+pub const TESTNET_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+pub const FUTURENET_PASSPHRASE: &str = "Test SDF Future Network ; October 2022";
+
+pub fn network_passphrase(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => MAINNET_PASSPHRASE,
+        Network::Futurenet => FUTURENET_PASSPHRASE,
+        Network::Testnet | Network::Auto => TESTNET_PASSPHRASE,
+    }
+}
+
+pub fn network_id(passphrase: &str) -> [u8; 32] {
+    Sha256::digest(passphrase.as_bytes()).into()
+}
+
+/// Public RPC endpoint for networks that have one. Mainnet has no
+/// canonical endpoint, so one must be configured.
+pub fn default_rpc_url(network: Network) -> Option<&'static str> {
+    match network {
+        Network::Testnet | Network::Auto => Some("https://soroban-testnet.stellar.org"),
+        Network::Futurenet => Some("https://rpc-futurenet.stellar.org"),
+        Network::Mainnet => None,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestLedger {
+    pub id: String,
+    pub protocol_version: u32,
+    pub sequence: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntryResult {
+    pub key: String,
+    pub xdr: String,
+    pub last_modified_ledger_seq: u32,
+    #[serde(default)]
+    pub live_until_ledger_seq: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntries {
+    #[serde(default)]
+    pub entries: Vec<LedgerEntryResult>,
+    pub latest_ledger: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SimulateHostFunctionResult {
+    #[serde(default)]
+    pub auth: Vec<String>,
+    pub xdr: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePreamble {
+    pub transaction_data: String,
+    pub min_resource_fee: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateTransaction {
+    #[serde(default)]
+    pub transaction_data: Option<String>,
+    #[serde(default)]
+    pub min_resource_fee: Option<String>,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub results: Vec<SimulateHostFunctionResult>,
+    #[serde(default)]
+    pub restore_preamble: Option<RestorePreamble>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub latest_ledger: u32,
+}
+
+impl SimulateTransaction {
+    pub fn min_resource_fee(&self) -> Result<i64> {
+        self.min_resource_fee
+            .as_deref()
+            .unwrap_or("0")
+            .parse()
+            .context("RPC returned an invalid minResourceFee")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTransaction {
+    pub status: String,
+    pub hash: String,
+    pub latest_ledger: u32,
+    #[serde(default)]
+    pub error_result_xdr: Option<String>,
+    #[serde(default)]
+    pub diagnostic_events_xdr: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransaction {
+    pub status: String,
+    pub latest_ledger: u32,
+    #[serde(default)]
+    pub ledger: Option<u32>,
+    #[serde(default)]
+    pub envelope_xdr: Option<String>,
+    #[serde(default)]
+    pub result_xdr: Option<String>,
+    #[serde(default)]
+    pub result_meta_xdr: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+pub struct RpcClient {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: JsonValue) -> Result<T> {
+        log::debug!("RPC {} {}", method, params);
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach RPC server at {}", self.url))?;
+        if !response.status().is_success() {
+            bail!("RPC {} failed with HTTP {}", method, response.status());
+        }
+        let body: RpcResponse<T> = response
+            .json()
+            .await
+            .with_context(|| format!("RPC {} returned an invalid response", method))?;
+        if let Some(error) = body.error {
+            bail!("RPC {} failed ({}): {}", method, error.code, error.message);
+        }
+        body.result
+            .ok_or_else(|| anyhow!("RPC {} returned neither result nor error", method))
+    }
+
+    pub async fn get_health(&self) -> Result<String> {
+        #[derive(Deserialize)]
+        struct Health {
+            status: String,
+        }
+        Ok(self
+            .call::<Health>("getHealth", JsonValue::Null)
+            .await?
+            .status)
+    }
+
+    pub async fn get_latest_ledger(&self) -> Result<LatestLedger> {
+        self.call("getLatestLedger", JsonValue::Null).await
+    }
+
+    pub async fn get_ledger_entries(&self, keys: &[LedgerKey]) -> Result<LedgerEntries> {
+        let keys = keys
+            .iter()
+            .map(|key| key.to_xdr_base64(Limits::none()))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.call("getLedgerEntries", json!({ "keys": keys })).await
+    }
+
+    /// Current sequence number of `account`.
+    pub async fn get_account_sequence(&self, account: &[u8; 32]) -> Result<i64> {
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(*account))),
+        });
+        let entries = self.get_ledger_entries(&[key]).await?;
+        let entry = entries.entries.first().ok_or_else(|| {
+            anyhow!(
+                "Account {} does not exist on this network",
+                stellar_strkey::ed25519::PublicKey(*account)
+            )
+        })?;
+        // RPC returns the entry data; some servers send the full entry.
+        let data = match LedgerEntryData::from_xdr_base64(&entry.xdr, Limits::none()) {
+            Ok(data) => data,
+            Err(_) => {
+                LedgerEntry::from_xdr_base64(&entry.xdr, Limits::none())
+                    .context("RPC returned an undecodable account entry")?
+                    .data
+            }
+        };
+        match data {
+            LedgerEntryData::Account(account) => Ok(account.seq_num.0),
+            _ => bail!("RPC returned a non-account entry for an account key"),
+        }
+    }
+
+    pub async fn simulate_transaction(&self, envelope_xdr: &str) -> Result<SimulateTransaction> {
+        self.call(
+            "simulateTransaction",
+            json!({ "transaction": envelope_xdr }),
+        )
+        .await
+    }
+
+    pub async fn send_transaction(&self, envelope_xdr: &str) -> Result<SendTransaction> {
+        self.call("sendTransaction", json!({ "transaction": envelope_xdr }))
+            .await
+    }
+
+    pub async fn get_transaction(&self, hash: &str) -> Result<GetTransaction> {
+        self.call("getTransaction", json!({ "hash": hash })).await
+    }
+
+    /// Polls `getTransaction` until the transaction leaves `NOT_FOUND`.
+    pub async fn wait_for_transaction(
+        &self,
+        hash: &str,
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<GetTransaction> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let tx = self.get_transaction(hash).await?;
+            if tx.status != "NOT_FOUND" {
+                return Ok(tx);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                bail!(
+                    "Transaction {} was not included within {}s",
+                    hash,
+                    timeout.as_secs()
+                );
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock_rpc::{
+        Fault, Fixture, LedgerEntryFixture, MockRpcServer, SimulationFixture, TransactionFixture,
+    };
+    use soroban_env_host::xdr::{
+        AccountEntry, AccountEntryExt, SequenceNumber, String32, Thresholds, VecM,
+    };
+
+    const ACCOUNT: [u8; 32] = [7; 32];
+
+    fn account_fixture(seq_num: i64) -> LedgerEntryFixture {
+        let account_id = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(ACCOUNT)));
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: account_id.clone(),
+        });
+        let data = LedgerEntryData::Account(AccountEntry {
+            account_id,
+            balance: 100_0000000,
+            seq_num: SequenceNumber(seq_num),
+            num_sub_entries: 0,
+            inflation_dest: None,
+            flags: 0,
+            home_domain: String32::default(),
+            thresholds: Thresholds([1, 0, 0, 0]),
+            signers: VecM::default(),
+            ext: AccountEntryExt::V0,
+        });
+        LedgerEntryFixture {
+            key: key.to_xdr_base64(Limits::none()).unwrap(),
+            xdr: data.to_xdr_base64(Limits::none()).unwrap(),
+            last_modified_ledger_seq: 90,
+            live_until_ledger_seq: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_account_sequence() {
+        let mut fixture = Fixture::with_ledgers(90, 100);
+        fixture.ledger_entries = vec![account_fixture(4_294_967_301)];
+        let server = MockRpcServer::start(fixture).await.unwrap();
+        let rpc = RpcClient::new(&server.url());
+
+        assert_eq!(rpc.get_health().await.unwrap(), "healthy");
+        assert_eq!(rpc.get_latest_ledger().await.unwrap().sequence, 100);
+        assert_eq!(
+            rpc.get_account_sequence(&ACCOUNT).await.unwrap(),
+            4_294_967_301
+        );
+        let missing = rpc.get_account_sequence(&[8; 32]).await.unwrap_err();
+        assert!(missing.to_string().contains("does not exist"));
+    }
+
+    #[tokio::test]
+    async fn simulates_submits_and_waits_for_inclusion() {
+        let mut fixture = Fixture::with_ledgers(90, 100);
+        fixture.simulations = vec![SimulationFixture {
+            transaction: None,
+            response: json!({ "minResourceFee": "1234", "results": [{ "xdr": "AAAAAQ==" }] }),
+        }];
+        fixture.transactions = vec![TransactionFixture {
+            transaction: None,
+            hash: Some("aa".repeat(32)),
+            send_status: "PENDING".to_string(),
+            error_result_xdr: None,
+            status: "SUCCESS".to_string(),
+            pending_polls: 2,
+            result_xdr: Some("AAAAAA==".to_string()),
+            result_meta_xdr: None,
+        }];
+        let server = MockRpcServer::start(fixture).await.unwrap();
+        let rpc = RpcClient::new(&server.url());
+
+        let simulation = rpc.simulate_transaction("AAAAAg==").await.unwrap();
+        assert_eq!(simulation.min_resource_fee().unwrap(), 1234);
+        assert_eq!(simulation.latest_ledger, 100);
+        assert_eq!(simulation.results[0].xdr, "AAAAAQ==");
+
+        let sent = rpc.send_transaction("AAAAAg==").await.unwrap();
+        assert_eq!(sent.status, "PENDING");
+        assert_eq!(sent.hash, "aa".repeat(32));
+
+        let tx = rpc
+            .wait_for_transaction(
+                &sent.hash,
+                Duration::from_secs(5),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap();
+        assert_eq!(tx.status, "SUCCESS");
+        assert_eq!(tx.result_xdr.as_deref(), Some("AAAAAA=="));
+        assert_eq!(server.call_count("getTransaction"), 3);
+    }
+
+    #[tokio::test]
+    async fn reports_rpc_failures() {
+        let server = MockRpcServer::start(Fixture::with_ledgers(1, 5))
+            .await
+            .unwrap();
+        let rpc = RpcClient::new(&server.url());
+
+        server.fail_next("getLatestLedger", 1, Fault::HttpStatus { status: 503 });
+        let err = rpc.get_latest_ledger().await.unwrap_err();
+        assert!(err.to_string().contains("HTTP 503"));
+
+        server.fail_next(
+            "getLatestLedger",
+            1,
+            Fault::RpcError {
+                code: -32001,
+                message: "node is catching up".to_string(),
+            },
+        );
+        let err = rpc.get_latest_ledger().await.unwrap_err();
+        assert!(err.to_string().contains("node is catching up"));
+
+        let unknown = rpc.get_transaction(&"bb".repeat(32)).await.unwrap();
+        assert_eq!(unknown.status, "NOT_FOUND");
+    }
+}
@@ -0,0 +1,1154 @@
+#![allow(dead_code)]
+
+//! In-process Soroban ledger used by the scenario runner, coverage and cost
+//! tooling. Every call goes through the host's recording-mode entry point, so
+//! results, events, auth and resources match what a network simulation would
+//! report, and the resulting ledger changes are applied to a local snapshot.
+
+use anyhow::{anyhow, bail, Context, Result};
+use rand::RngCore;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use soroban_env_host::{
+    budget::Budget,
+    e2e_invoke::{invoke_host_function_in_recording_mode, LedgerEntryChange},
+    storage::{EntryWithLiveUntil, SnapshotSource},
+    xdr::{
+        AccountEntry, AccountEntryExt, AccountId, ContractDataDurability, ContractEvent,
+        ContractEventBody, ContractExecutable, ContractIdPreimage, ContractIdPreimageFromAddress,
+        CreateContractArgs, DiagnosticEvent, Hash, HashIdPreimage, HashIdPreimageContractId,
+        HostFunction, Int128Parts, Int256Parts, InvokeContractArgs, LedgerEntry, LedgerEntryData,
+        LedgerEntryExt, LedgerKey, LedgerKeyAccount, LedgerKeyContractData, Limits, PublicKey,
+        ReadXdr, ScAddress, ScBytes, ScMap, ScMapEntry, ScSpecEntry, ScSpecFunctionV0,
+        ScSpecTypeDef, ScSpecUdtUnionCaseV0, ScString, ScSymbol, ScVal, ScVec, SequenceNumber,
+        SorobanAuthorizationEntry, SorobanCredentials, SorobanResources, Thresholds, UInt128Parts,
+        UInt256Parts, Uint256, WriteXdr,
+    },
+    HostError, LedgerInfo,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+
+/// Network passphrase hashed into contract ids created by the sandbox.
+pub const SANDBOX_NETWORK_PASSPHRASE: &str = "Soroban Registry Sandbox ; October 2026";
+
+/// Resource limits applied to every invocation.
+#[derive(Debug, Clone, Copy)]
+pub struct SandboxLimits {
+    pub cpu_instructions: u64,
+    pub memory_bytes: u64,
+}
+
+impl SandboxLimits {
+    /// Per-transaction limits of the public networks.
+    pub fn network() -> Self {
+        Self {
+            cpu_instructions: 100_000_000,
+            memory_bytes: 40 * 1024 * 1024,
+        }
+    }
+
+    /// Effectively unbounded limits, for instrumented builds whose probes
+    /// would otherwise eat into the transaction budget.
+    pub fn unbounded() -> Self {
+        Self {
+            cpu_instructions: u64::MAX / 4,
+            memory_bytes: u64::MAX / 4,
+        }
+    }
+}
+
+/// Everything observed while running a single host function.
+#[derive(Debug)]
+pub struct Invocation {
+    pub result: std::result::Result<ScVal, String>,
+    /// Events emitted by calls that completed successfully.
+    pub events: Vec<ContractEvent>,
+    /// Every event including diagnostics and events of rolled-back frames.
+    pub diagnostic_events: Vec<DiagnosticEvent>,
+    /// Authorizations recorded while the call ran.
+    pub auth: Vec<SorobanAuthorizationEntry>,
+    pub resources: SorobanResources,
+    pub memory_bytes: u64,
+    /// Size of the encoded events plus the encoded return value.
+    pub events_and_return_value_size: u32,
+    pub ledger_changes: Vec<AppliedChange>,
+    /// The host function that was run, as it would appear in a transaction.
+    pub host_function: HostFunction,
+    /// Ledger sequence the invocation ran at.
+    pub ledger_sequence: u32,
+}
+
+impl Invocation {
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Addresses whose authorization the invocation required.
+    pub fn authorized_addresses(&self) -> Vec<ScAddress> {
+        self.auth
+            .iter()
+            .filter_map(|entry| match &entry.credentials {
+                SorobanCredentials::Address(creds) => Some(creds.address.clone()),
+                SorobanCredentials::SourceAccount => None,
+            })
+            .collect()
+    }
+}
+
+/// A ledger entry written, extended or removed by an invocation.
+#[derive(Debug, Clone)]
+pub struct AppliedChange {
+    pub key: LedgerKey,
+    pub read_only: bool,
+    pub old_size_bytes: u32,
+    pub new_size_bytes: Option<u32>,
+    pub durability: Option<ContractDataDurability>,
+    pub old_live_until: Option<u32>,
+    pub new_live_until: Option<u32>,
+}
+
+#[derive(Clone, Default)]
+struct LedgerSnapshot(BTreeMap<LedgerKey, EntryWithLiveUntil>);
+
+impl SnapshotSource for LedgerSnapshot {
+    fn get(
+        &self,
+        key: &Rc<LedgerKey>,
+    ) -> std::result::Result<Option<EntryWithLiveUntil>, HostError> {
+        Ok(self.0.get(key.as_ref()).cloned())
+    }
+}
+
+/// A local ledger holding accounts, uploaded code and contract data.
+pub struct LocalLedger {
+    entries: LedgerSnapshot,
+    info: LedgerInfo,
+    source: AccountId,
+    limits: SandboxLimits,
+    nonce: u64,
+}
+
+impl LocalLedger {
+    pub fn new() -> Self {
+        Self::with_limits(SandboxLimits::network())
+    }
+
+    pub fn with_limits(limits: SandboxLimits) -> Self {
+        let network_id: [u8; 32] = Sha256::digest(SANDBOX_NETWORK_PASSPHRASE.as_bytes()).into();
+        let info = LedgerInfo {
+            protocol_version: soroban_env_host::Host::current_test_protocol(),
+            sequence_number: 1_000,
+            timestamp: 1_700_000_000,
+            network_id,
+            base_reserve: 5_000_000,
+            min_temp_entry_ttl: 16,
+            min_persistent_entry_ttl: 4_096,
+            max_entry_ttl: 6_312_000,
+        };
+        let mut ledger = Self {
+            entries: LedgerSnapshot::default(),
+            info,
+            source: account_id([0u8; 32]),
+            limits,
+            nonce: 0,
+        };
+        ledger.source = ledger.create_account();
+        ledger
+    }
+
+    pub fn info(&self) -> &LedgerInfo {
+        &self.info
+    }
+
+    pub fn set_limits(&mut self, limits: SandboxLimits) {
+        self.limits = limits;
+    }
+
+    pub fn source_account(&self) -> &AccountId {
+        &self.source
+    }
+
+    /// Moves the ledger forward, e.g. to exercise TTL expiry.
+    pub fn advance(&mut self, ledgers: u32) {
+        self.info.sequence_number += ledgers;
+        self.info.timestamp += u64::from(ledgers) * 5;
+    }
+
+    /// Creates a funded classic account and returns its id.
+    pub fn create_account(&mut self) -> AccountId {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        let id = account_id(key);
+        let entry = LedgerEntry {
+            last_modified_ledger_seq: self.info.sequence_number,
+            data: LedgerEntryData::Account(AccountEntry {
+                account_id: id.clone(),
+                balance: 10_000_000_000,
+                seq_num: SequenceNumber(0),
+                num_sub_entries: 0,
+                inflation_dest: None,
+                flags: 0,
+                home_domain: Default::default(),
+                thresholds: Thresholds([1, 0, 0, 0]),
+                signers: Default::default(),
+                ext: AccountEntryExt::V0,
+            }),
+            ext: LedgerEntryExt::V0,
+        };
+        self.entries.0.insert(
+            LedgerKey::Account(LedgerKeyAccount {
+                account_id: id.clone(),
+            }),
+            (Rc::new(entry), None),
+        );
+        id
+    }
+
+    /// Uploads `wasm` and instantiates a contract from it.
+    pub fn deploy(&mut self, wasm: &[u8]) -> Result<ScAddress> {
+        let upload = HostFunction::UploadContractWasm(
+            wasm.to_vec()
+                .try_into()
+                .map_err(|_| anyhow!("contract wasm is too large"))?,
+        );
+        let hash = match self.execute(&upload)?.result {
+            Ok(ScVal::Bytes(bytes)) => Hash(
+                bytes
+                    .as_slice()
+                    .try_into()
+                    .context("upload returned a malformed wasm hash")?,
+            ),
+            Ok(other) => bail!("unexpected upload result: {:?}", other),
+            Err(e) => bail!("wasm upload failed: {}", e),
+        };
+
+        self.nonce += 1;
+        let salt: [u8; 32] = Sha256::digest(self.nonce.to_be_bytes()).into();
+        let preimage = ContractIdPreimage::Address(ContractIdPreimageFromAddress {
+            address: ScAddress::Account(self.source.clone()),
+            salt: Uint256(salt),
+        });
+        let create = HostFunction::CreateContract(CreateContractArgs {
+            contract_id_preimage: preimage,
+            executable: ContractExecutable::Wasm(hash),
+        });
+        match self.execute(&create)?.result {
+            Ok(ScVal::Address(address)) => Ok(address),
+            Ok(other) => bail!("unexpected create result: {:?}", other),
+            Err(e) => bail!("contract creation failed: {}", e),
+        }
+    }
+
+    /// Invokes `function` on `contract` and applies the resulting changes.
+    pub fn invoke(
+        &mut self,
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<Invocation> {
+        let host_fn = HostFunction::InvokeContract(InvokeContractArgs {
+            contract_address: contract.clone(),
+            function_name: ScSymbol(
+                function
+                    .try_into()
+                    .map_err(|_| anyhow!("invalid function name '{}'", function))?,
+            ),
+            args: args
+                .try_into()
+                .map_err(|_| anyhow!("too many arguments for '{}'", function))?,
+        });
+        self.execute(&host_fn)
+    }
+
+    /// Reads a contract data entry.
+    pub fn contract_data(
+        &self,
+        contract: &ScAddress,
+        key: &ScVal,
+        durability: ContractDataDurability,
+    ) -> Option<ScVal> {
+        let key = LedgerKey::ContractData(LedgerKeyContractData {
+            contract: contract.clone(),
+            key: key.clone(),
+            durability,
+        });
+        match self.entries.0.get(&key).map(|(entry, _)| &entry.data) {
+            Some(LedgerEntryData::ContractData(data)) => Some(data.val.clone()),
+            _ => None,
+        }
+    }
+
+    /// Reads a value from the contract's instance storage.
+    pub fn instance_data(&self, contract: &ScAddress, key: &ScVal) -> Option<ScVal> {
+        let instance = self.contract_data(
+            contract,
+            &ScVal::LedgerKeyContractInstance,
+            ContractDataDurability::Persistent,
+        )?;
+        match instance {
+            ScVal::ContractInstance(instance) => instance
+                .storage
+                .as_ref()?
+                .iter()
+                .find(|entry| &entry.key == key)
+                .map(|entry| entry.val.clone()),
+            _ => None,
+        }
+    }
+
+    /// Looks a key up in instance, persistent and temporary storage, in that
+    /// order.
+    pub fn storage_value(&self, contract: &ScAddress, key: &ScVal) -> Option<ScVal> {
+        self.instance_data(contract, key)
+            .or_else(|| self.contract_data(contract, key, ContractDataDurability::Persistent))
+            .or_else(|| self.contract_data(contract, key, ContractDataDurability::Temporary))
+    }
+
+    /// Runs a host function in recording mode and applies its ledger changes.
+    pub fn execute(&mut self, host_fn: &HostFunction) -> Result<Invocation> {
+        let budget = Budget::default();
+        budget
+            .reset_limits(self.limits.cpu_instructions, self.limits.memory_bytes)
+            .map_err(|e| anyhow!("failed to configure budget: {:?}", e))?;
+
+        self.nonce += 1;
+        let seed: [u8; 32] = Sha256::digest(self.nonce.to_le_bytes()).into();
+        let mut diagnostic_events = Vec::new();
+        let snapshot = Rc::new(self.entries.clone());
+        let recorded = invoke_host_function_in_recording_mode(
+            &budget,
+            true,
+            host_fn,
+            &self.source,
+            None,
+            self.info.clone(),
+            snapshot,
+            seed,
+            &mut diagnostic_events,
+        )
+        .map_err(|e| anyhow!("host failed to run invocation: {:?}", e.error))?;
+
+        let ledger_changes = self.apply_changes(&recorded.ledger_changes)?;
+        Ok(Invocation {
+            result: recorded
+                .invoke_result
+                .map_err(|e| describe_host_error(&e, &diagnostic_events)),
+            events: recorded.contract_events,
+            diagnostic_events,
+            auth: recorded.auth,
+            resources: recorded.resources,
+            memory_bytes: budget.get_mem_bytes_consumed().unwrap_or_default(),
+            events_and_return_value_size: recorded.contract_events_and_return_value_size,
+            ledger_changes,
+            host_function: host_fn.clone(),
+            ledger_sequence: self.info.sequence_number,
+        })
+    }
+
+    fn apply_changes(&mut self, changes: &[LedgerEntryChange]) -> Result<Vec<AppliedChange>> {
+        let mut applied = Vec::with_capacity(changes.len());
+        for change in changes {
+            let key = LedgerKey::from_xdr(&change.encoded_key, Limits::none())
+                .context("host returned an undecodable ledger key")?;
+            let previous_ttl = self.entries.0.get(&key).and_then(|(_, ttl)| *ttl);
+            let new_ttl = change
+                .ttl_change
+                .as_ref()
+                .map(|ttl| ttl.new_live_until_ledger)
+                .or(previous_ttl);
+
+            let mut new_size_bytes = None;
+            if change.read_only {
+                if let Some(entry) = self.entries.0.get_mut(&key) {
+                    entry.1 = new_ttl;
+                }
+            } else if let Some(encoded) = &change.encoded_new_value {
+                let mut entry = LedgerEntry::from_xdr(encoded, Limits::none())
+                    .context("host returned an undecodable ledger entry")?;
+                entry.last_modified_ledger_seq = self.info.sequence_number;
+                new_size_bytes = Some(encoded.len() as u32);
+                self.entries
+                    .0
+                    .insert(key.clone(), (Rc::new(entry), new_ttl));
+            } else {
+                self.entries.0.remove(&key);
+            }
+
+            applied.push(AppliedChange {
+                key,
+                read_only: change.read_only,
+                old_size_bytes: change.old_entry_size_bytes,
+                new_size_bytes,
+                durability: change.ttl_change.as_ref().map(|ttl| ttl.durability),
+                old_live_until: change
+                    .ttl_change
+                    .as_ref()
+                    .map(|ttl| ttl.old_live_until_ledger),
+                new_live_until: change
+                    .ttl_change
+                    .as_ref()
+                    .map(|ttl| ttl.new_live_until_ledger),
+            });
+        }
+        Ok(applied)
+    }
+
+    /// Contract id the sandbox would assign for a deployer/salt pair.
+    pub fn contract_id_for(&self, deployer: &AccountId, salt: [u8; 32]) -> Result<ScAddress> {
+        let preimage = HashIdPreimage::ContractId(HashIdPreimageContractId {
+            network_id: Hash(self.info.network_id),
+            contract_id_preimage: ContractIdPreimage::Address(ContractIdPreimageFromAddress {
+                address: ScAddress::Account(deployer.clone()),
+                salt: Uint256(salt),
+            }),
+        });
+        let bytes = preimage.to_xdr(Limits::none())?;
+        Ok(ScAddress::Contract(Hash(Sha256::digest(bytes).into())))
+    }
+}
+
+impl Default for LocalLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn account_id(key: [u8; 32]) -> AccountId {
+    AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key)))
+}
+
+/// Renders a host error together with any contract log lines that were
+/// emitted before the failure.
+fn describe_host_error(error: &HostError, events: &[DiagnosticEvent]) -> String {
+    let mut message = format!("{:?}", error.error);
+    for event in events {
+        let ContractEventBody::V0(body) = &event.event.body;
+        let is_log = body
+            .topics
+            .first()
+            .map(|topic| matches!(topic, ScVal::Symbol(s) if s.to_utf8_string_lossy() == "log"))
+            .unwrap_or(false);
+        if is_log {
+            message.push_str(&format!(" | {}", scval_to_json(&body.data)));
+        }
+    }
+    message
+}
+
+/// Builds the crate in `dir` for `wasm32-unknown-unknown` with the dev
+/// profile (which keeps DWARF line info) and returns the `.wasm` artifact.
+pub fn build_contract(dir: &Path) -> Result<PathBuf> {
+    let output = Command::new("cargo")
+        .current_dir(dir)
+        .args([
+            "build",
+            "--target",
+            "wasm32-unknown-unknown",
+            "--message-format=json-render-diagnostics",
+        ])
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Failed to execute cargo build")?;
+    if !output.status.success() {
+        bail!("Contract build failed in {}", dir.display());
+    }
+
+    let mut wasm = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(message) = serde_json::from_str::<JsonValue>(line) else {
+            continue;
+        };
+        if message["reason"] != "compiler-artifact" {
+            continue;
+        }
+        if let Some(files) = message["filenames"].as_array() {
+            for file in files.iter().filter_map(JsonValue::as_str) {
+                if file.ends_with(".wasm") {
+                    wasm = Some(PathBuf::from(file));
+                }
+            }
+        }
+    }
+    wasm.ok_or_else(|| anyhow!("cargo build did not produce a .wasm artifact"))
+}
+
+/// Function and type definitions read from a contract's `contractspecv0`
+/// custom section.
+#[derive(Debug, Clone, Default)]
+pub struct ContractSpec {
+    pub entries: Vec<ScSpecEntry>,
+}
+
+impl ContractSpec {
+    pub fn from_wasm(wasm: &[u8]) -> Result<Self> {
+        let mut entries = Vec::new();
+        for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+            if let wasmparser::Payload::CustomSection(section) =
+                payload.context("failed to parse contract wasm")?
+            {
+                if section.name() == "contractspecv0" {
+                    let mut cursor =
+                        soroban_env_host::xdr::Limited::new(section.data(), Limits::none());
+                    for entry in ScSpecEntry::read_xdr_iter(&mut cursor) {
+                        entries.push(entry.context("malformed contract spec entry")?);
+                    }
+                }
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &ScSpecFunctionV0> {
+        self.entries.iter().filter_map(|entry| match entry {
+            ScSpecEntry::FunctionV0(function) => Some(function),
+            _ => None,
+        })
+    }
+
+    pub fn function(&self, name: &str) -> Option<&ScSpecFunctionV0> {
+        self.functions()
+            .find(|function| function.name.to_utf8_string_lossy() == name)
+    }
+
+    fn udt(&self, name: &str) -> Option<&ScSpecEntry> {
+        self.entries.iter().find(|entry| match entry {
+            ScSpecEntry::UdtStructV0(s) => s.name.to_utf8_string_lossy() == name,
+            ScSpecEntry::UdtUnionV0(u) => u.name.to_utf8_string_lossy() == name,
+            ScSpecEntry::UdtEnumV0(e) => e.name.to_utf8_string_lossy() == name,
+            ScSpecEntry::UdtErrorEnumV0(e) => e.name.to_utf8_string_lossy() == name,
+            ScSpecEntry::FunctionV0(_) => false,
+        })
+    }
+
+    /// Converts positional JSON arguments for `function` into `ScVal`s.
+    pub fn function_args(
+        &self,
+        function: &str,
+        args: &[JsonValue],
+        addresses: &HashMap<String, ScAddress>,
+    ) -> Result<Vec<ScVal>> {
+        let spec = self
+            .function(function)
+            .ok_or_else(|| anyhow!("function '{}' is not in the contract spec", function))?;
+        if spec.inputs.len() != args.len() {
+            bail!(
+                "'{}' takes {} argument(s) but {} were given",
+                function,
+                spec.inputs.len(),
+                args.len()
+            );
+        }
+        spec.inputs
+            .iter()
+            .zip(args)
+            .map(|(input, value)| {
+                self.to_scval(value, &input.type_, addresses)
+                    .with_context(|| {
+                        format!(
+                            "argument '{}' of '{}'",
+                            input.name.to_utf8_string_lossy(),
+                            function
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Converts a JSON value into an `ScVal` of the given spec type. Strings
+    /// starting with `@` are looked up in `addresses`.
+    pub fn to_scval(
+        &self,
+        value: &JsonValue,
+        ty: &ScSpecTypeDef,
+        addresses: &HashMap<String, ScAddress>,
+    ) -> Result<ScVal> {
+        let scval = match ty {
+            ScSpecTypeDef::Val => infer_scval(value, addresses)?,
+            ScSpecTypeDef::Bool => ScVal::Bool(
+                value
+                    .as_bool()
+                    .ok_or_else(|| anyhow!("expected a boolean, got {}", value))?,
+            ),
+            ScSpecTypeDef::Void => ScVal::Void,
+            ScSpecTypeDef::U32 => ScVal::U32(u32::try_from(parse_integer(value)?)?),
+            ScSpecTypeDef::I32 => ScVal::I32(i32::try_from(parse_integer(value)?)?),
+            ScSpecTypeDef::U64 => ScVal::U64(u64::try_from(parse_integer(value)?)?),
+            ScSpecTypeDef::I64 => ScVal::I64(i64::try_from(parse_integer(value)?)?),
+            ScSpecTypeDef::Timepoint => {
+                ScVal::Timepoint(u64::try_from(parse_integer(value)?)?.into())
+            }
+            ScSpecTypeDef::Duration => {
+                ScVal::Duration(u64::try_from(parse_integer(value)?)?.into())
+            }
+            ScSpecTypeDef::U128 => {
+                let v = u128::try_from(parse_integer(value)?)?;
+                ScVal::U128(UInt128Parts {
+                    hi: (v >> 64) as u64,
+                    lo: v as u64,
+                })
+            }
+            ScSpecTypeDef::I128 => {
+                let v = parse_integer(value)?;
+                ScVal::I128(Int128Parts {
+                    hi: (v >> 64) as i64,
+                    lo: v as u64,
+                })
+            }
+            ScSpecTypeDef::U256 => {
+                let v = u128::try_from(parse_integer(value)?)?;
+                ScVal::U256(UInt256Parts {
+                    hi_hi: 0,
+                    hi_lo: 0,
+                    lo_hi: (v >> 64) as u64,
+                    lo_lo: v as u64,
+                })
+            }
+            ScSpecTypeDef::I256 => {
+                let v = parse_integer(value)?;
+                let sign = if v < 0 { u64::MAX } else { 0 };
+                ScVal::I256(Int256Parts {
+                    hi_hi: sign as i64,
+                    hi_lo: sign,
+                    lo_hi: (v >> 64) as u64,
+                    lo_lo: v as u64,
+                })
+            }
+            ScSpecTypeDef::Bytes => ScVal::Bytes(ScBytes(parse_bytes(value)?.try_into()?)),
+            ScSpecTypeDef::BytesN(n) => {
+                let bytes = parse_bytes(value)?;
+                if bytes.len() != n.n as usize {
+                    bail!("expected {} bytes, got {}", n.n, bytes.len());
+                }
+                ScVal::Bytes(ScBytes(bytes.try_into()?))
+            }
+            ScSpecTypeDef::String => ScVal::String(ScString(expect_str(value)?.try_into()?)),
+            ScSpecTypeDef::Symbol => ScVal::Symbol(ScSymbol(expect_str(value)?.try_into()?)),
+            ScSpecTypeDef::Address => ScVal::Address(parse_address(expect_str(value)?, addresses)?),
+            ScSpecTypeDef::Option(inner) => {
+                if value.is_null() {
+                    ScVal::Void
+                } else {
+                    self.to_scval(value, &inner.value_type, addresses)?
+                }
+            }
+            ScSpecTypeDef::Result(inner) => self.to_scval(value, &inner.ok_type, addresses)?,
+            ScSpecTypeDef::Vec(inner) => {
+                let items = value
+                    .as_array()
+                    .ok_or_else(|| anyhow!("expected an array, got {}", value))?
+                    .iter()
+                    .map(|item| self.to_scval(item, &inner.element_type, addresses))
+                    .collect::<Result<Vec<_>>>()?;
+                ScVal::Vec(Some(ScVec(items.try_into()?)))
+            }
+            ScSpecTypeDef::Map(inner) => {
+                let pairs: Vec<(JsonValue, &JsonValue)> = match value {
+                    JsonValue::Object(map) => map
+                        .iter()
+                        .map(|(k, v)| (JsonValue::String(k.clone()), v))
+                        .collect(),
+                    JsonValue::Array(items) => items
+                        .iter()
+                        .map(|pair| match pair.as_array().map(Vec::as_slice) {
+                            Some([k, v]) => Ok((k.clone(), v)),
+                            _ => Err(anyhow!("map entries must be [key, value] pairs")),
+                        })
+                        .collect::<Result<_>>()?,
+                    _ => bail!("expected an object or list of pairs, got {}", value),
+                };
+                let mut entries = Vec::with_capacity(pairs.len());
+                for (k, v) in pairs {
+                    entries.push(ScMapEntry {
+                        key: self.to_scval(&k, &inner.key_type, addresses)?,
+                        val: self.to_scval(v, &inner.value_type, addresses)?,
+                    });
+                }
+                sorted_map(entries)?
+            }
+            ScSpecTypeDef::Tuple(inner) => {
+                let items = value
+                    .as_array()
+                    .ok_or_else(|| anyhow!("expected an array, got {}", value))?;
+                if items.len() != inner.value_types.len() {
+                    bail!("expected a {}-tuple", inner.value_types.len());
+                }
+                let vals = items
+                    .iter()
+                    .zip(inner.value_types.iter())
+                    .map(|(item, ty)| self.to_scval(item, ty, addresses))
+                    .collect::<Result<Vec<_>>>()?;
+                ScVal::Vec(Some(ScVec(vals.try_into()?)))
+            }
+            ScSpecTypeDef::Error => ScVal::Error(soroban_env_host::xdr::ScError::Contract(
+                u32::try_from(parse_integer(value)?)?,
+            )),
+            ScSpecTypeDef::Udt(udt) => {
+                let name = udt.name.to_utf8_string_lossy();
+                self.udt_to_scval(&name, value, addresses)?
+            }
+        };
+        Ok(scval)
+    }
+
+    fn udt_to_scval(
+        &self,
+        name: &str,
+        value: &JsonValue,
+        addresses: &HashMap<String, ScAddress>,
+    ) -> Result<ScVal> {
+        match self.udt(name) {
+            Some(ScSpecEntry::UdtStructV0(def)) => {
+                let is_tuple = def
+                    .fields
+                    .iter()
+                    .all(|f| f.name.to_utf8_string_lossy().parse::<u32>().is_ok());
+                if is_tuple {
+                    let items = value
+                        .as_array()
+                        .ok_or_else(|| anyhow!("{} expects an array", name))?;
+                    let vals = def
+                        .fields
+                        .iter()
+                        .zip(items)
+                        .map(|(field, item)| self.to_scval(item, &field.type_, addresses))
+                        .collect::<Result<Vec<_>>>()?;
+                    return Ok(ScVal::Vec(Some(ScVec(vals.try_into()?))));
+                }
+                let object = value
+                    .as_object()
+                    .ok_or_else(|| anyhow!("{} expects an object", name))?;
+                let mut entries = Vec::with_capacity(def.fields.len());
+                for field in def.fields.iter() {
+                    let field_name = field.name.to_utf8_string_lossy();
+                    let field_value = object
+                        .get(&field_name)
+                        .ok_or_else(|| anyhow!("{} is missing field '{}'", name, field_name))?;
+                    entries.push(ScMapEntry {
+                        key: ScVal::Symbol(ScSymbol(field_name.as_str().try_into()?)),
+                        val: self.to_scval(field_value, &field.type_, addresses)?,
+                    });
+                }
+                sorted_map(entries)
+            }
+            Some(ScSpecEntry::UdtUnionV0(def)) => {
+                // Accepts "Case" for void cases and {"Case": value-or-[values]}.
+                let (case_name, payload) = match value {
+                    JsonValue::String(s) => (s.clone(), None),
+                    JsonValue::Object(map) if map.len() == 1 => {
+                        let (k, v) = map.iter().next().expect("length checked");
+                        (k.clone(), Some(v))
+                    }
+                    _ => bail!("{} expects \"Case\" or {{\"Case\": value}}", name),
+                };
+                let case = def
+                    .cases
+                    .iter()
+                    .find(|case| match case {
+                        ScSpecUdtUnionCaseV0::VoidV0(c) => {
+                            c.name.to_utf8_string_lossy() == case_name
+                        }
+                        ScSpecUdtUnionCaseV0::TupleV0(c) => {
+                            c.name.to_utf8_string_lossy() == case_name
+                        }
+                    })
+                    .ok_or_else(|| anyhow!("{} has no case '{}'", name, case_name))?;
+                let mut items = vec![ScVal::Symbol(ScSymbol(case_name.as_str().try_into()?))];
+                if let ScSpecUdtUnionCaseV0::TupleV0(c) = case {
+                    let payload =
+                        payload.ok_or_else(|| anyhow!("case '{}' needs a value", case_name))?;
+                    let values: Vec<JsonValue> = match (c.type_.len(), payload) {
+                        (1, v) => vec![v.clone()],
+                        (_, JsonValue::Array(vs)) => vs.clone(),
+                        _ => bail!("case '{}' expects {} values", case_name, c.type_.len()),
+                    };
+                    for (ty, v) in c.type_.iter().zip(values.iter()) {
+                        items.push(self.to_scval(v, ty, addresses)?);
+                    }
+                }
+                Ok(ScVal::Vec(Some(ScVec(items.try_into()?))))
+            }
+            Some(ScSpecEntry::UdtEnumV0(def)) => {
+                let discriminant = match value {
+                    JsonValue::String(s) => def
+                        .cases
+                        .iter()
+                        .find(|case| case.name.to_utf8_string_lossy() == *s)
+                        .map(|case| case.value)
+                        .ok_or_else(|| anyhow!("{} has no case '{}'", name, s))?,
+                    other => u32::try_from(parse_integer(other)?)?,
+                };
+                Ok(ScVal::U32(discriminant))
+            }
+            Some(ScSpecEntry::UdtErrorEnumV0(_)) => Ok(ScVal::Error(
+                soroban_env_host::xdr::ScError::Contract(u32::try_from(parse_integer(value)?)?),
+            )),
+            _ => infer_scval(value, addresses),
+        }
+    }
+}
+
+pub fn sorted_map(mut entries: Vec<ScMapEntry>) -> Result<ScVal> {
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(ScVal::Map(Some(ScMap(entries.try_into()?))))
+}
+
+fn expect_str(value: &JsonValue) -> Result<&str> {
+    value
+        .as_str()
+        .ok_or_else(|| anyhow!("expected a string, got {}", value))
+}
+
+pub fn parse_integer(value: &JsonValue) -> Result<i128> {
+    match value {
+        JsonValue::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .ok_or_else(|| anyhow!("expected an integer, got {}", n)),
+        JsonValue::String(s) => s
+            .replace('_', "")
+            .parse::<i128>()
+            .with_context(|| format!("'{}' is not an integer", s)),
+        other => bail!("expected an integer, got {}", other),
+    }
+}
+
+pub fn parse_bytes(value: &JsonValue) -> Result<Vec<u8>> {
+    match value {
+        JsonValue::String(s) => {
+            hex::decode(s.trim_start_matches("0x")).with_context(|| format!("'{}' is not hex", s))
+        }
+        JsonValue::Array(items) => items
+            .iter()
+            .map(|b| {
+                b.as_u64()
+                    .and_then(|b| u8::try_from(b).ok())
+                    .ok_or_else(|| anyhow!("byte arrays must contain 0-255 values"))
+            })
+            .collect(),
+        other => bail!("expected hex bytes, got {}", other),
+    }
+}
+
+/// Resolves `@alias` references and Stellar strkeys (`G...`/`C...`).
+pub fn parse_address(raw: &str, addresses: &HashMap<String, ScAddress>) -> Result<ScAddress> {
+    if let Some(alias) = raw.strip_prefix('@') {
+        return addresses
+            .get(alias)
+            .cloned()
+            .ok_or_else(|| anyhow!("unknown address alias '@{}'", alias));
+    }
+    raw.parse::<ScAddress>()
+        .map_err(|_| anyhow!("'{}' is not a valid Stellar address", raw))
+}
+
+/// Best-effort conversion for untyped (`Val`) arguments.
+pub fn infer_scval(value: &JsonValue, addresses: &HashMap<String, ScAddress>) -> Result<ScVal> {
+    Ok(match value {
+        JsonValue::Null => ScVal::Void,
+        JsonValue::Bool(b) => ScVal::Bool(*b),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(v) => ScVal::I64(v),
+            None => ScVal::U64(
+                n.as_u64()
+                    .ok_or_else(|| anyhow!("unsupported number {}", n))?,
+            ),
+        },
+        JsonValue::String(s) => {
+            if let Ok(address) = parse_address(s, addresses) {
+                ScVal::Address(address)
+            } else if s.len() <= 32 && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                ScVal::Symbol(ScSymbol(s.as_str().try_into()?))
+            } else {
+                ScVal::String(ScString(s.as_str().try_into()?))
+            }
+        }
+        JsonValue::Array(items) => {
+            let vals = items
+                .iter()
+                .map(|item| infer_scval(item, addresses))
+                .collect::<Result<Vec<_>>>()?;
+            ScVal::Vec(Some(ScVec(vals.try_into()?)))
+        }
+        JsonValue::Object(map) => {
+            let mut entries = Vec::with_capacity(map.len());
+            for (k, v) in map {
+                entries.push(ScMapEntry {
+                    key: ScVal::Symbol(ScSymbol(k.as_str().try_into()?)),
+                    val: infer_scval(v, addresses)?,
+                });
+            }
+            sorted_map(entries)?
+        }
+    })
+}
+
+/// Renders an `ScVal` as JSON: integers that don't fit an `i64`/`u64` become
+/// decimal strings, bytes become hex and addresses become strkeys.
+pub fn scval_to_json(value: &ScVal) -> JsonValue {
+    match value {
+        ScVal::Bool(b) => json!(b),
+        ScVal::Void => JsonValue::Null,
+        ScVal::Error(e) => json!(format!("{:?}", e)),
+        ScVal::U32(v) => json!(v),
+        ScVal::I32(v) => json!(v),
+        ScVal::U64(v) => json!(v),
+        ScVal::I64(v) => json!(v),
+        ScVal::Timepoint(v) => json!(v.0),
+        ScVal::Duration(v) => json!(v.0),
+        ScVal::U128(parts) => {
+            json!((((parts.hi as u128) << 64) | parts.lo as u128).to_string())
+        }
+        ScVal::I128(parts) => {
+            json!((((parts.hi as i128) << 64) | parts.lo as i128).to_string())
+        }
+        ScVal::U256(parts) if parts.hi_hi == 0 && parts.hi_lo == 0 => {
+            json!((((parts.lo_hi as u128) << 64) | parts.lo_lo as u128).to_string())
+        }
+        ScVal::I256(parts)
+            if (parts.hi_hi == 0 && parts.hi_lo == 0)
+                || (parts.hi_hi == -1 && parts.hi_lo == u64::MAX) =>
+        {
+            json!((((parts.lo_hi as i128) << 64) | parts.lo_lo as i128).to_string())
+        }
+        ScVal::U256(parts) => json!(format!(
+            "0x{:016x}{:016x}{:016x}{:016x}",
+            parts.hi_hi, parts.hi_lo, parts.lo_hi, parts.lo_lo
+        )),
+        ScVal::I256(parts) => json!(format!(
+            "0x{:016x}{:016x}{:016x}{:016x}",
+            parts.hi_hi, parts.hi_lo, parts.lo_hi, parts.lo_lo
+        )),
+        ScVal::Bytes(bytes) => json!(hex::encode(bytes.as_slice())),
+        ScVal::String(s) => json!(s.to_utf8_string_lossy()),
+        ScVal::Symbol(s) => json!(s.to_utf8_string_lossy()),
+        ScVal::Address(address) => json!(address.to_string()),
+        ScVal::Vec(Some(items)) => JsonValue::Array(items.iter().map(scval_to_json).collect()),
+        ScVal::Vec(None) => JsonValue::Array(Vec::new()),
+        ScVal::Map(Some(entries)) => {
+            let keys_are_names = entries
+                .iter()
+                .all(|e| matches!(e.key, ScVal::Symbol(_) | ScVal::String(_)));
+            if keys_are_names {
+                let mut object = JsonMap::new();
+                for entry in entries.iter() {
+                    if let JsonValue::String(k) = scval_to_json(&entry.key) {
+                        object.insert(k, scval_to_json(&entry.val));
+                    }
+                }
+                JsonValue::Object(object)
+            } else {
+                JsonValue::Array(
+                    entries
+                        .iter()
+                        .map(|e| json!([scval_to_json(&e.key), scval_to_json(&e.val)]))
+                        .collect(),
+                )
+            }
+        }
+        ScVal::Map(None) => JsonValue::Object(JsonMap::new()),
+        other => json!(format!("{:?}", other)),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A minimal hand-written contract: `add(a, b)` returns `a + b` for small
+    /// u64 values, `store(value)` writes instance storage, `ping(value)`
+    /// publishes a `["ping"]` event and `approve(owner)` requires `owner`'s
+    /// authorization.
+    pub(crate) fn test_contract_wasm() -> Vec<u8> {
+        let meta = soroban_env_host::xdr::ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion(
+            u64::from(soroban_env_host::Host::current_test_protocol()) << 32,
+        )
+        .to_xdr(Limits::none())
+        .unwrap();
+        let mut wasm = wat::parse_str(format!(
+            r#"(module
+                (import "l" "_" (func $put (param i64 i64 i64) (result i64)))
+                (import "v" "_" (func $vec_new (result i64)))
+                (import "v" "6" (func $vec_push_back (param i64 i64) (result i64)))
+                (import "x" "1" (func $event (param i64 i64) (result i64)))
+                (import "a" "0" (func $require_auth (param i64) (result i64)))
+                (func $add (export "add") (param $a i64) (param $b i64) (result i64)
+                    ;; U64Small values carry the payload above an 8-bit tag.
+                    (i64.or
+                        (i64.shl
+                            (i64.add
+                                (i64.shr_u (local.get $a) (i64.const 8))
+                                (i64.shr_u (local.get $b) (i64.const 8)))
+                            (i64.const 8))
+                        (i64.const 6)))
+                (func $store (export "store") (param $v i64) (result i64)
+                    (drop (call $put (i64.const 0x0e) (local.get $v) (i64.const 2)))
+                    (i64.const 2))
+                (func $ping (export "ping") (param $v i64) (result i64)
+                    (drop (call $event
+                        (call $vec_push_back (call $vec_new) (i64.const {ping}))
+                        (local.get $v)))
+                    (i64.const 2))
+                (func $approve (export "approve") (param $owner i64) (result i64)
+                    (drop (call $require_auth (local.get $owner)))
+                    (i64.const 2))
+                (memory (export "memory") 1)
+            )"#,
+            ping = small_symbol("ping")
+        ))
+        .unwrap();
+        wasm.extend(custom_section("contractenvmetav0", &meta));
+        wasm.extend(custom_section("contractspecv0", &test_contract_spec()));
+        wasm
+    }
+
+    fn test_contract_spec() -> Vec<u8> {
+        use soroban_env_host::xdr::ScSpecFunctionInputV0;
+
+        let function =
+            |name: &str, inputs: &[(&str, ScSpecTypeDef)], output: Option<ScSpecTypeDef>| {
+                ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+                    doc: Default::default(),
+                    name: ScSymbol(name.try_into().unwrap()),
+                    inputs: inputs
+                        .iter()
+                        .map(|(name, ty)| ScSpecFunctionInputV0 {
+                            doc: Default::default(),
+                            name: (*name).try_into().unwrap(),
+                            type_: ty.clone(),
+                        })
+                        .collect::<Vec<_>>()
+                        .try_into()
+                        .unwrap(),
+                    outputs: output.into_iter().collect::<Vec<_>>().try_into().unwrap(),
+                })
+            };
+        let entries = [
+            function(
+                "add",
+                &[("a", ScSpecTypeDef::U64), ("b", ScSpecTypeDef::U64)],
+                Some(ScSpecTypeDef::U64),
+            ),
+            function("store", &[("value", ScSpecTypeDef::U64)], None),
+            function("ping", &[("value", ScSpecTypeDef::U64)], None),
+            function("approve", &[("owner", ScSpecTypeDef::Address)], None),
+        ];
+        entries
+            .iter()
+            .flat_map(|entry| entry.to_xdr(Limits::none()).unwrap())
+            .collect()
+    }
+
+    /// Encodes a short symbol as a `SymbolSmall` value.
+    fn small_symbol(symbol: &str) -> i64 {
+        let body = symbol.bytes().fold(0i64, |body, c| {
+            let code = match c {
+                b'_' => 1,
+                b'0'..=b'9' => 2 + (c - b'0'),
+                b'A'..=b'Z' => 12 + (c - b'A'),
+                _ => 38 + (c - b'a'),
+            };
+            (body << 6) | i64::from(code)
+        });
+        (body << 8) | 0x0e
+    }
+
+    pub(crate) fn custom_section(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        leb128(&mut payload, name.len() as u64);
+        payload.extend_from_slice(name.as_bytes());
+        payload.extend_from_slice(data);
+        let mut section = vec![0u8];
+        leb128(&mut section, payload.len() as u64);
+        section.extend(payload);
+        section
+    }
+
+    fn leb128(out: &mut Vec<u8>, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                break;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    #[test]
+    fn deploys_and_invokes_contract() {
+        let mut ledger = LocalLedger::new();
+        let contract = ledger.deploy(&test_contract_wasm()).unwrap();
+        let invocation = ledger
+            .invoke(&contract, "add", vec![ScVal::U64(2), ScVal::U64(40)])
+            .unwrap();
+        assert_eq!(invocation.result.unwrap(), ScVal::U64(42));
+        assert!(invocation.resources.instructions > 0);
+    }
+
+    #[test]
+    fn instance_storage_writes_are_applied() {
+        let mut ledger = LocalLedger::new();
+        let contract = ledger.deploy(&test_contract_wasm()).unwrap();
+        let invocation = ledger
+            .invoke(&contract, "store", vec![ScVal::U64(7)])
+            .unwrap();
+        assert!(invocation.is_ok(), "{:?}", invocation.result);
+
+        // Symbol "" encodes as the small-symbol tag 0x0e with an empty body.
+        let key = ScVal::Symbol(ScSymbol("".try_into().unwrap()));
+        assert_eq!(ledger.storage_value(&contract, &key), Some(ScVal::U64(7)));
+    }
+
+    #[test]
+    fn converts_json_by_spec_type() {
+        let spec = ContractSpec::default();
+        let none = HashMap::new();
+        assert_eq!(
+            spec.to_scval(
+                &json!("170141183460469231731687303715884105727"),
+                &ScSpecTypeDef::I128,
+                &none
+            )
+            .unwrap(),
+            ScVal::I128(Int128Parts {
+                hi: i64::MAX,
+                lo: u64::MAX
+            })
+        );
+        assert_eq!(
+            spec.to_scval(&json!("0x0102"), &ScSpecTypeDef::Bytes, &none)
+                .unwrap(),
+            ScVal::Bytes(ScBytes(vec![1, 2].try_into().unwrap()))
+        );
+        assert!(spec
+            .to_scval(&json!(-1), &ScSpecTypeDef::U32, &none)
+            .is_err());
+    }
+
+    #[test]
+    fn resolves_address_aliases() {
+        let mut ledger = LocalLedger::new();
+        let alice = ScAddress::Account(ledger.create_account());
+        let mut aliases = HashMap::new();
+        aliases.insert("alice".to_string(), alice.clone());
+        assert_eq!(parse_address("@alice", &aliases).unwrap(), alice);
+        assert!(parse_address("@bob", &aliases).is_err());
+        let strkey = alice.to_string();
+        assert_eq!(parse_address(&strkey, &aliases).unwrap(), alice);
+    }
+
+    #[test]
+    fn renders_scvals_as_json() {
+        let value = ScVal::Vec(Some(ScVec(
+            vec![ScVal::U32(1), ScVal::Bool(true), ScVal::Void]
+                .try_into()
+                .unwrap(),
+        )));
+        assert_eq!(scval_to_json(&value), json!([1, true, null]));
+    }
+}
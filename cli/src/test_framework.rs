@@ -1,10 +1,21 @@
 #![allow(dead_code)]
 
-use anyhow::{Context, Result};
+//! Scenario tests run against an in-process Soroban host: contracts are
+//! deployed to a fresh local ledger per scenario, `@name` arguments resolve
+//! to generated accounts (or to deployed contracts of that name) and steps
+//! assert on return values, events, storage and required authorizations.
+
+use crate::sandbox::{
+    build_contract, infer_scval, scval_to_json, ContractSpec, Invocation, LocalLedger,
+    SandboxLimits,
+};
+use anyhow::{anyhow, bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value as JsonValue;
+use soroban_env_host::xdr::{ContractEventBody, ContractEventType, ScAddress, ScVal};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +48,35 @@ pub enum TestValue {
     Null,
 }
 
+impl TestValue {
+    /// Converts to JSON so the value can be typed against a contract spec.
+    /// The externally tagged form (`{String: "x"}`, `{Number: 1}`) is
+    /// accepted as well as plain values.
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            TestValue::String(s) => JsonValue::String(s.clone()),
+            TestValue::Number(n) => JsonValue::from(*n),
+            TestValue::Boolean(b) => JsonValue::Bool(*b),
+            TestValue::Array(items) => {
+                JsonValue::Array(items.iter().map(TestValue::to_json).collect())
+            }
+            TestValue::Object(map) => {
+                if map.len() == 1 {
+                    let (tag, inner) = map.iter().next().expect("length checked");
+                    if matches!(
+                        tag.as_str(),
+                        "String" | "Number" | "Boolean" | "Array" | "Object" | "Null"
+                    ) {
+                        return inner.to_json();
+                    }
+                }
+                JsonValue::Object(map.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+            }
+            TestValue::Null => JsonValue::Null,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestAction {
     pub action: String,
@@ -80,34 +120,39 @@ pub struct CoverageMetrics {
     pub methods_tested: usize,
     pub total_methods: usize,
     pub coverage_percent: f64,
+    /// Line coverage needs instrumented builds; see `soroban-registry coverage`.
     pub lines_covered: usize,
     pub lines_total: usize,
 }
 
+/// Called with every invocation a scenario makes.
+pub type InvocationObserver<'a> = &'a mut dyn FnMut(&ScAddress, &str, &Invocation);
+
 pub struct TestRunner {
     contract_path: String,
     contracts: HashMap<String, ContractInfo>,
     coverage: CoverageTracker,
+    limits: SandboxLimits,
 }
 
 #[derive(Debug, Clone)]
 struct ContractInfo {
     name: String,
+    wasm: Vec<u8>,
+    spec: ContractSpec,
     methods: Vec<String>,
 }
 
 struct CoverageTracker {
-    contracts: std::collections::HashSet<String>,
-    methods: std::collections::HashSet<(String, String)>,
-    lines: std::collections::HashSet<u32>,
+    contracts: HashSet<String>,
+    methods: HashSet<(String, String)>,
 }
 
 impl CoverageTracker {
     fn new() -> Self {
         Self {
-            contracts: std::collections::HashSet::new(),
-            methods: std::collections::HashSet::new(),
-            lines: std::collections::HashSet::new(),
+            contracts: HashSet::new(),
+            methods: HashSet::new(),
         }
     }
 
@@ -130,96 +175,128 @@ impl CoverageTracker {
             methods_tested,
             total_methods,
             coverage_percent,
-            lines_covered: self.lines.len(),
-            lines_total: total_methods * 10,
+            lines_covered: 0,
+            lines_total: 0,
         }
     }
 }
 
+/// Ledger and name bindings for the scenario being run.
+struct ScenarioState {
+    ledger: LocalLedger,
+    /// Contract name to deployed address.
+    deployed: HashMap<String, ScAddress>,
+    /// `@alias` bindings, including deployed contracts by name.
+    addresses: HashMap<String, ScAddress>,
+}
+
+/// What a step produced, for assertions to inspect.
+struct StepOutcome {
+    contract: ScAddress,
+    invocation: Invocation,
+    value: ScVal,
+}
+
 impl TestRunner {
+    /// Loads the contract(s) under test from a `.wasm` file, a directory of
+    /// `.wasm` files, or a contract crate (which is built first).
     pub fn new(contract_path: &str) -> Result<Self> {
         let contracts = Self::discover_contracts(contract_path)?;
         Ok(Self {
             contract_path: contract_path.to_string(),
             contracts,
             coverage: CoverageTracker::new(),
+            limits: SandboxLimits::network(),
+        })
+    }
+
+    /// Runs scenarios against already loaded WASM, registered under `name`.
+    pub fn from_wasm(name: &str, wasm: Vec<u8>) -> Result<Self> {
+        let mut contracts = HashMap::new();
+        contracts.insert(name.to_string(), ContractInfo::load(name, wasm)?);
+        Ok(Self {
+            contract_path: name.to_string(),
+            contracts,
+            coverage: CoverageTracker::new(),
+            limits: SandboxLimits::network(),
         })
     }
 
+    /// Overrides the per-invocation budget, which defaults to network limits.
+    pub fn set_limits(&mut self, limits: SandboxLimits) {
+        self.limits = limits;
+    }
+
     fn discover_contracts(contract_path: &str) -> Result<HashMap<String, ContractInfo>> {
         let mut contracts = HashMap::new();
         let path = Path::new(contract_path);
 
+        let mut wasm_files = Vec::new();
         if path.is_file() {
-            let methods = Self::extract_methods(path)?;
-            let name = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("contract")
-                .to_string();
-            contracts.insert(
-                name.clone(),
-                ContractInfo {
-                    name: name.clone(),
-                    methods,
-                },
-            );
+            wasm_files.push(path.to_path_buf());
+        } else if path.join("Cargo.toml").is_file() {
+            wasm_files.push(build_contract(path)?);
         } else if path.is_dir() {
             for entry in fs::read_dir(path)? {
-                let entry = entry?;
-                let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("rs") {
-                    let methods = Self::extract_methods(&path)?;
-                    let name = path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("contract")
-                        .to_string();
-                    contracts.insert(
-                        name.clone(),
-                        ContractInfo {
-                            name: name.clone(),
-                            methods,
-                        },
-                    );
+                let file = entry?.path();
+                if file.extension().and_then(|s| s.to_str()) == Some("wasm") {
+                    wasm_files.push(file);
                 }
             }
+        } else {
+            bail!("Contract path does not exist: {}", contract_path);
+        }
+
+        for file in wasm_files {
+            let name = file
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("contract")
+                .to_string();
+            let wasm = fs::read(&file)
+                .with_context(|| format!("Failed to read contract: {}", file.display()))?;
+            contracts.insert(name.clone(), ContractInfo::load(&name, wasm)?);
+        }
+        if contracts.is_empty() {
+            bail!("No contract WASM found in {}", contract_path);
         }
 
         Ok(contracts)
     }
 
-    fn extract_methods(path: &Path) -> Result<Vec<String>> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read contract: {}", path.display()))?;
-
-        let mut methods = Vec::new();
-        for line in content.lines() {
-            let trimmed = line.trim();
-            if trimmed.starts_with("pub fn ") || trimmed.starts_with("fn ") {
-                if let Some(start) = trimmed.find("fn ") {
-                    let after_fn = &trimmed[start + 3..];
-                    if let Some(end) = after_fn.find('(') {
-                        let method_name = after_fn[..end].trim();
-                        if !method_name.is_empty() && !method_name.contains('<') {
-                            methods.push(method_name.to_string());
-                        }
-                    }
-                }
-            }
+    /// Resolves a scenario's contract name. A single loaded contract answers
+    /// to any name, so scenarios needn't match the artifact's file name.
+    fn contract(&self, name: &str) -> Result<&ContractInfo> {
+        if let Some(info) = self.contracts.get(name) {
+            return Ok(info);
         }
-
-        Ok(methods)
+        if self.contracts.len() == 1 {
+            return Ok(self.contracts.values().next().expect("length checked"));
+        }
+        Err(anyhow!("Contract not found: {}", name))
     }
 
     pub async fn run_scenario(&mut self, scenario: TestScenario) -> Result<TestResult> {
+        self.run_scenario_observed(&scenario, &mut |_, _, _| {})
+    }
+
+    /// Like [`TestRunner::run_scenario`], reporting each invocation to
+    /// `observer` as it happens.
+    pub fn run_scenario_observed(
+        &mut self,
+        scenario: &TestScenario,
+        observer: InvocationObserver<'_>,
+    ) -> Result<TestResult> {
         let start_time = Instant::now();
         let mut step_results = Vec::new();
         let mut error = None;
 
+        let mut state = self.prepare(scenario)?;
+
         if let Some(ref setup) = scenario.setup {
             for action in setup {
-                self.execute_action(action).await?;
+                self.execute_action(&mut state, action, observer)
+                    .with_context(|| format!("setup action '{}' failed", action.action))?;
             }
         }
 
@@ -229,25 +306,30 @@ impl TestRunner {
             let mut assertions_failed = 0;
             let mut step_error = None;
 
-            self.coverage
-                .record_contract_call(&step.contract, &step.method);
-
-            let step_result = self.execute_step(step).await;
+            let step_result = self.execute_step(&mut state, step, observer);
 
             match step_result {
-                Ok(result) => {
+                Ok(outcome) => {
                     if step.expected_error.is_some() {
                         step_error = Some("Expected error but none occurred".to_string());
                         assertions_failed += 1;
                     } else if let Some(ref assertions) = step.assertions {
                         for assertion in assertions {
-                            match self.check_assertion(assertion, &result) {
+                            match self.check_assertion(&state, assertion, &outcome) {
                                 Ok(true) => assertions_passed += 1,
                                 Ok(false) => {
                                     assertions_failed += 1;
                                     if step_error.is_none() {
-                                        step_error =
-                                            Some(format!("Assertion failed: {}", assertion.r#type));
+                                        step_error = Some(format!(
+                                            "Assertion failed: {} (expected {}, got {})",
+                                            assertion.r#type,
+                                            resolve_aliases(
+                                                &assertion.expected.to_json(),
+                                                &state.addresses
+                                            ),
+                                            self.actual_for(&state, assertion, &outcome)
+                                                .unwrap_or(JsonValue::Null)
+                                        ));
                                     }
                                 }
                                 Err(e) => {
@@ -263,16 +345,19 @@ impl TestRunner {
                     }
                 }
                 Err(e) => {
+                    let message = format!("{:#}", e);
                     if let Some(ref expected_err) = step.expected_error {
-                        if e.to_string().contains(expected_err) {
+                        if message.contains(expected_err) {
                             assertions_passed += 1;
                         } else {
-                            step_error =
-                                Some(format!("Expected error '{}' but got: {}", expected_err, e));
+                            step_error = Some(format!(
+                                "Expected error '{}' but got: {}",
+                                expected_err, message
+                            ));
                             assertions_failed += 1;
                         }
                     } else {
-                        step_error = Some(e.to_string());
+                        step_error = Some(message);
                         assertions_failed += 1;
                     }
                 }
@@ -295,7 +380,7 @@ impl TestRunner {
 
         if let Some(ref teardown) = scenario.teardown {
             for action in teardown {
-                let _ = self.execute_action(action).await;
+                let _ = self.execute_action(&mut state, action, observer);
             }
         }
 
@@ -303,7 +388,7 @@ impl TestRunner {
         let coverage = self.coverage.calculate_metrics(total_methods);
 
         Ok(TestResult {
-            scenario: scenario.name,
+            scenario: scenario.name.clone(),
             passed: step_results.iter().all(|s| s.passed),
             duration: start_time.elapsed(),
             steps: step_results,
@@ -312,39 +397,160 @@ impl TestRunner {
         })
     }
 
-    async fn execute_step(&self, step: &TestStep) -> Result<TestValue> {
-        let contract_info = self
-            .contracts
-            .get(&step.contract)
-            .ok_or_else(|| anyhow::anyhow!("Contract not found: {}", step.contract))?;
+    /// Deploys every contract the scenario names onto a fresh ledger and
+    /// creates an account for each `@alias` that isn't a contract.
+    fn prepare(&self, scenario: &TestScenario) -> Result<ScenarioState> {
+        let mut state = ScenarioState {
+            ledger: LocalLedger::with_limits(self.limits),
+            deployed: HashMap::new(),
+            addresses: HashMap::new(),
+        };
 
-        if !contract_info.methods.contains(&step.method) {
-            return Err(anyhow::anyhow!(
-                "Method '{}' not found in contract '{}'",
-                step.method,
-                step.contract
-            ));
+        let actions = scenario
+            .setup
+            .iter()
+            .flatten()
+            .chain(scenario.teardown.iter().flatten());
+        let names = actions
+            .filter_map(|a| a.contract.as_deref())
+            .chain(scenario.steps.iter().map(|s| s.contract.as_str()));
+        for name in names {
+            if state.addresses.contains_key(name) {
+                continue;
+            }
+            let info = self.contract(name)?;
+            let address = match state.deployed.get(&info.name) {
+                Some(address) => address.clone(),
+                None => {
+                    let address = state
+                        .ledger
+                        .deploy(&info.wasm)
+                        .with_context(|| format!("Failed to deploy {}", info.name))?;
+                    state.deployed.insert(info.name.clone(), address.clone());
+                    address
+                }
+            };
+            state.addresses.insert(name.to_string(), address);
         }
 
-        tokio::time::sleep(Duration::from_millis(10)).await;
+        let mut aliases = BTreeSet::new();
+        for value in scenario_values(scenario) {
+            collect_aliases(value, &mut aliases);
+        }
+        for alias in aliases {
+            state
+                .addresses
+                .entry(alias)
+                .or_insert_with(|| ScAddress::Account(state.ledger.create_account()));
+        }
+        Ok(state)
+    }
+
+    fn invoke(
+        &mut self,
+        state: &mut ScenarioState,
+        contract: &str,
+        method: &str,
+        args: Option<&[TestValue]>,
+        observer: InvocationObserver<'_>,
+    ) -> Result<(ScAddress, Invocation)> {
+        let info = self.contract(contract)?;
+        if !info.methods.is_empty() && !info.methods.iter().any(|m| m == method) {
+            bail!("Method '{}' not found in contract '{}'", method, contract);
+        }
+        let json: Vec<JsonValue> = args
+            .unwrap_or_default()
+            .iter()
+            .map(TestValue::to_json)
+            .collect();
+        let args = if info.spec.entries.is_empty() {
+            json.iter()
+                .map(|value| infer_scval(value, &state.addresses))
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            info.spec.function_args(method, &json, &state.addresses)?
+        };
+        let name = info.name.clone();
+        let address = state
+            .addresses
+            .get(contract)
+            .cloned()
+            .ok_or_else(|| anyhow!("Contract not found: {}", contract))?;
 
-        Ok(TestValue::String(format!(
-            "result_from_{}_{}",
-            step.contract, step.method
-        )))
+        let invocation = state.ledger.invoke(&address, method, args)?;
+        self.coverage.record_contract_call(&name, method);
+        observer(&address, method, &invocation);
+        Ok((address, invocation))
     }
 
-    async fn execute_action(&mut self, action: &TestAction) -> Result<()> {
+    fn execute_step(
+        &mut self,
+        state: &mut ScenarioState,
+        step: &TestStep,
+        observer: InvocationObserver<'_>,
+    ) -> Result<StepOutcome> {
+        let (contract, invocation) = self.invoke(
+            state,
+            &step.contract,
+            &step.method,
+            step.args.as_deref(),
+            observer,
+        )?;
+        let value = invocation.result.clone().map_err(|e| anyhow!(e))?;
+        Ok(StepOutcome {
+            contract,
+            invocation,
+            value,
+        })
+    }
+
+    fn execute_action(
+        &mut self,
+        state: &mut ScenarioState,
+        action: &TestAction,
+        observer: InvocationObserver<'_>,
+    ) -> Result<()> {
         match action.action.as_str() {
+            // Contracts are deployed up front; a deploy action with a method
+            // calls it as the initializer.
             "deploy" => {
-                tokio::time::sleep(Duration::from_millis(5)).await;
+                let contract = action
+                    .contract
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("deploy action requires a contract"))?;
+                if let Some(method) = &action.method {
+                    self.invoke_checked(state, contract, method, action.args.as_deref(), observer)?;
+                }
                 Ok(())
             }
             "invoke" => {
-                if let (Some(contract), Some(method)) = (&action.contract, &action.method) {
-                    self.coverage.record_contract_call(contract, method);
-                }
-                tokio::time::sleep(Duration::from_millis(5)).await;
+                let (contract, method) =
+                    action
+                        .contract
+                        .as_deref()
+                        .zip(action.method.as_deref())
+                        .ok_or_else(|| anyhow!("invoke action requires a contract and method"))?;
+                self.invoke_checked(state, contract, method, action.args.as_deref(), observer)
+            }
+            "advance" => {
+                let ledgers = match action.value {
+                    Some(TestValue::Number(n)) => u32::try_from(n)?,
+                    None => 1,
+                    _ => bail!("advance expects a number of ledgers"),
+                };
+                state.ledger.advance(ledgers);
+                Ok(())
+            }
+            "account" => {
+                let alias = match &action.value {
+                    Some(value) => match value.to_json() {
+                        JsonValue::String(s) => s.trim_start_matches('@').to_string(),
+                        other => bail!("account expects an alias, got {}", other),
+                    },
+                    None => bail!("account action requires a value"),
+                };
+                let account = state.ledger.create_account();
+                state.addresses.insert(alias, ScAddress::Account(account));
                 Ok(())
             }
             "set" => Ok(()),
@@ -352,47 +558,67 @@ impl TestRunner {
         }
     }
 
-    fn check_assertion(&self, assertion: &Assertion, result: &TestValue) -> Result<bool> {
+    fn invoke_checked(
+        &mut self,
+        state: &mut ScenarioState,
+        contract: &str,
+        method: &str,
+        args: Option<&[TestValue]>,
+        observer: InvocationObserver<'_>,
+    ) -> Result<()> {
+        let (_, invocation) = self.invoke(state, contract, method, args, observer)?;
+        invocation
+            .result
+            .map(|_| ())
+            .map_err(|e| anyhow!("{}.{} failed: {}", contract, method, e))
+    }
+
+    fn check_assertion(
+        &self,
+        state: &ScenarioState,
+        assertion: &Assertion,
+        outcome: &StepOutcome,
+    ) -> Result<bool> {
+        let expected = resolve_aliases(&assertion.expected.to_json(), &state.addresses);
         let operator = assertion.operator.as_deref().unwrap_or("eq");
 
         match assertion.r#type.as_str() {
-            "equals" | "eq" => Ok(self.compare_values(result, &assertion.expected, operator)),
-            "not_equals" | "ne" => Ok(!self.compare_values(result, &assertion.expected, operator)),
+            "equals" | "eq" | "return" | "state" | "storage" => {
+                let actual = self.actual_for(state, assertion, outcome)?;
+                compare_values(&actual, &expected, operator)
+            }
+            "not_equals" | "ne" => {
+                let actual = self.actual_for(state, assertion, outcome)?;
+                compare_values(&actual, &expected, "ne")
+            }
             "contains" => {
-                if let TestValue::String(s) = result {
-                    if let TestValue::String(expected) = &assertion.expected {
-                        Ok(s.contains(expected))
-                    } else {
-                        Ok(false)
-                    }
-                } else {
-                    Ok(false)
-                }
+                let actual = self.actual_for(state, assertion, outcome)?;
+                compare_values(&actual, &expected, "contains")
             }
             "greater_than" | "gt" => {
-                if let (TestValue::Number(a), TestValue::Number(b)) = (result, &assertion.expected)
-                {
-                    Ok(*a > *b)
-                } else {
-                    Ok(false)
-                }
+                let actual = self.actual_for(state, assertion, outcome)?;
+                compare_values(&actual, &expected, "gt")
             }
             "less_than" | "lt" => {
-                if let (TestValue::Number(a), TestValue::Number(b)) = (result, &assertion.expected)
-                {
-                    Ok(*a < *b)
-                } else {
-                    Ok(false)
-                }
+                let actual = self.actual_for(state, assertion, outcome)?;
+                compare_values(&actual, &expected, "lt")
             }
-            "state" => {
-                if let Some(ref field) = assertion.field {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
+            "event" => Ok(event_json(&outcome.invocation)
+                .iter()
+                .any(|event| event_matches(event, &expected))),
+            "auth" => {
+                let required: Vec<JsonValue> = outcome
+                    .invocation
+                    .authorized_addresses()
+                    .iter()
+                    .map(|address| JsonValue::String(address.to_string()))
+                    .collect();
+                let expected = match expected {
+                    JsonValue::Array(items) => items,
+                    single => vec![single],
+                };
+                Ok(expected.iter().all(|e| required.contains(e)))
             }
-            "event" => Ok(true),
             _ => Err(anyhow::anyhow!(
                 "Unknown assertion type: {}",
                 assertion.r#type
@@ -400,24 +626,219 @@ impl TestRunner {
         }
     }
 
-    fn compare_values(&self, a: &TestValue, b: &TestValue, op: &str) -> bool {
-        match (a, b) {
-            (TestValue::String(s1), TestValue::String(s2)) => match op {
-                "eq" => s1 == s2,
-                "ne" => s1 != s2,
-                _ => s1 == s2,
-            },
-            (TestValue::Number(n1), TestValue::Number(n2)) => match op {
-                "eq" => n1 == n2,
-                "ne" => n1 != n2,
-                _ => n1 == n2,
-            },
-            (TestValue::Boolean(b1), TestValue::Boolean(b2)) => b1 == b2,
-            _ => false,
+    /// The value an assertion inspects: a storage entry for `state`/`storage`
+    /// (keyed by `field`), otherwise the return value or the part of it
+    /// selected by a dotted `field` path.
+    fn actual_for(
+        &self,
+        state: &ScenarioState,
+        assertion: &Assertion,
+        outcome: &StepOutcome,
+    ) -> Result<JsonValue> {
+        match assertion.r#type.as_str() {
+            "state" | "storage" => {
+                let field = assertion
+                    .field
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("{} assertions require a field", assertion.r#type))?;
+                let key_json = serde_json::from_str(field)
+                    .unwrap_or_else(|_| JsonValue::String(field.to_string()));
+                let key = infer_scval(&key_json, &state.addresses)?;
+                Ok(state
+                    .ledger
+                    .storage_value(&outcome.contract, &key)
+                    .map(|value| scval_to_json(&value))
+                    .unwrap_or(JsonValue::Null))
+            }
+            "event" | "auth" => Ok(JsonValue::Array(event_json(&outcome.invocation))),
+            _ => {
+                let mut value = scval_to_json(&outcome.value);
+                if let Some(path) = assertion.field.as_deref() {
+                    for segment in path.split('.').filter(|s| !s.is_empty()) {
+                        value = match &value {
+                            JsonValue::Object(map) => map.get(segment).cloned(),
+                            JsonValue::Array(items) => segment
+                                .parse::<usize>()
+                                .ok()
+                                .and_then(|i| items.get(i).cloned()),
+                            _ => None,
+                        }
+                        .ok_or_else(|| anyhow!("return value has no field '{}'", path))?;
+                    }
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+impl ContractInfo {
+    fn load(name: &str, wasm: Vec<u8>) -> Result<Self> {
+        let spec = ContractSpec::from_wasm(&wasm)
+            .with_context(|| format!("Failed to read the spec of {}", name))?;
+        let methods = spec
+            .functions()
+            .map(|f| f.name.to_utf8_string_lossy())
+            .collect();
+        Ok(Self {
+            name: name.to_string(),
+            wasm,
+            spec,
+            methods,
+        })
+    }
+}
+
+/// Every value a scenario passes to the contract.
+pub fn scenario_values(scenario: &TestScenario) -> impl Iterator<Item = &TestValue> {
+    let actions = scenario
+        .setup
+        .iter()
+        .flatten()
+        .chain(scenario.teardown.iter().flatten())
+        .flat_map(|a| a.args.iter().flatten());
+    let steps = scenario.steps.iter().flat_map(|s| {
+        s.args.iter().flatten().chain(
+            s.assertions
+                .iter()
+                .flatten()
+                .map(|assertion| &assertion.expected),
+        )
+    });
+    actions.chain(steps)
+}
+
+fn collect_aliases(value: &TestValue, aliases: &mut BTreeSet<String>) {
+    match value {
+        TestValue::String(s) => {
+            if let Some(alias) = s.strip_prefix('@') {
+                aliases.insert(alias.to_string());
+            }
         }
+        TestValue::Array(items) => items.iter().for_each(|v| collect_aliases(v, aliases)),
+        TestValue::Object(map) => map.values().for_each(|v| collect_aliases(v, aliases)),
+        _ => {}
+    }
+}
+
+/// Replaces `@alias` strings with the strkey they are bound to, so expected
+/// values compare against rendered addresses.
+fn resolve_aliases(value: &JsonValue, addresses: &HashMap<String, ScAddress>) -> JsonValue {
+    match value {
+        JsonValue::String(s) => match s.strip_prefix('@').and_then(|a| addresses.get(a)) {
+            Some(address) => JsonValue::String(address.to_string()),
+            None => value.clone(),
+        },
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .map(|v| resolve_aliases(v, addresses))
+                .collect(),
+        ),
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), resolve_aliases(v, addresses)))
+                .collect(),
+        ),
+        other => other.clone(),
     }
 }
 
+fn event_json(invocation: &Invocation) -> Vec<JsonValue> {
+    invocation
+        .events
+        .iter()
+        .filter(|event| event.type_ == ContractEventType::Contract)
+        .map(|event| {
+            let ContractEventBody::V0(body) = &event.body;
+            serde_json::json!({
+                "contract": event.contract_id.as_ref().map(|id| ScAddress::Contract(id.clone()).to_string()),
+                "topics": body.topics.iter().map(scval_to_json).collect::<Vec<_>>(),
+                "data": scval_to_json(&body.data),
+            })
+        })
+        .collect()
+}
+
+/// `expected` is a topic (first-topic match), a list of leading topics, or an
+/// object with optional `topics`, `data` and `contract`.
+fn event_matches(event: &JsonValue, expected: &JsonValue) -> bool {
+    let topics = event["topics"].as_array().cloned().unwrap_or_default();
+    let prefix_matches = |wanted: &[JsonValue]| {
+        wanted.len() <= topics.len() && wanted.iter().zip(&topics).all(|(w, t)| loose_eq(t, w))
+    };
+    match expected {
+        JsonValue::Array(wanted) => prefix_matches(wanted),
+        JsonValue::Object(map) => {
+            let topics_ok = match map.get("topics") {
+                Some(JsonValue::Array(wanted)) => prefix_matches(wanted),
+                Some(single) => prefix_matches(std::slice::from_ref(single)),
+                None => true,
+            };
+            let data_ok = map.get("data").is_none_or(|d| loose_eq(&event["data"], d));
+            let contract_ok = map
+                .get("contract")
+                .is_none_or(|c| loose_eq(&event["contract"], c));
+            topics_ok && data_ok && contract_ok
+        }
+        single => prefix_matches(std::slice::from_ref(single)),
+    }
+}
+
+/// Integers may be rendered as numbers or decimal strings (for 128-bit
+/// values); compare them numerically.
+fn as_integer(value: &JsonValue) -> Option<i128> {
+    match value {
+        JsonValue::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from)),
+        JsonValue::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn loose_eq(actual: &JsonValue, expected: &JsonValue) -> bool {
+    if let (Some(a), Some(b)) = (as_integer(actual), as_integer(expected)) {
+        return a == b;
+    }
+    match (actual, expected) {
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(x, y)| loose_eq(x, y))
+        }
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, v)| b.get(k).is_some_and(|other| loose_eq(v, other)))
+        }
+        _ => actual == expected,
+    }
+}
+
+fn compare_values(actual: &JsonValue, expected: &JsonValue, op: &str) -> Result<bool> {
+    let numbers = || -> Result<(i128, i128)> {
+        Ok((
+            as_integer(actual).ok_or_else(|| anyhow!("{} is not a number", actual))?,
+            as_integer(expected).ok_or_else(|| anyhow!("{} is not a number", expected))?,
+        ))
+    };
+    Ok(match op {
+        "eq" => loose_eq(actual, expected),
+        "ne" => !loose_eq(actual, expected),
+        "gt" => numbers().map(|(a, b)| a > b)?,
+        "gte" => numbers().map(|(a, b)| a >= b)?,
+        "lt" => numbers().map(|(a, b)| a < b)?,
+        "lte" => numbers().map(|(a, b)| a <= b)?,
+        "contains" => match (actual, expected) {
+            (JsonValue::String(a), JsonValue::String(b)) => a.contains(b.as_str()),
+            (JsonValue::Array(items), _) => items.iter().any(|item| loose_eq(item, expected)),
+            (JsonValue::Object(map), JsonValue::String(key)) => map.contains_key(key),
+            _ => false,
+        },
+        other => bail!("Unknown operator: {}", other),
+    })
+}
+
 pub fn load_test_scenario(path: &Path) -> Result<TestScenario> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read test file: {}", path.display()))?;
@@ -433,44 +854,101 @@ pub fn load_test_scenario(path: &Path) -> Result<TestScenario> {
     }
 }
 
+/// Loads one scenario file, or every YAML/JSON scenario in a directory.
+pub fn load_test_scenarios(path: &Path) -> Result<Vec<(PathBuf, TestScenario)>> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let file = entry?.path();
+            let ext = file.extension().and_then(|s| s.to_str());
+            if matches!(ext, Some("json" | "yaml" | "yml")) {
+                files.push(file);
+            }
+        }
+        files.sort();
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    } else {
+        bail!("Test path does not exist: {}", path.display());
+    }
+
+    files
+        .into_iter()
+        .map(|file| load_test_scenario(&file).map(|scenario| (file, scenario)))
+        .collect()
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Writes one `<testsuite>` per scenario with a `<testcase>` per executed step.
 pub fn generate_junit_xml(results: &[TestResult], output_path: &Path) -> Result<()> {
     let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    xml.push_str("<testsuites>\n");
 
-    let total_tests = results.len();
-    let total_failures = results.iter().filter(|r| !r.passed).count();
+    let total_tests: usize = results.iter().map(|r| r.steps.len().max(1)).sum();
+    let total_failures: usize = results
+        .iter()
+        .map(|r| r.steps.iter().filter(|s| !s.passed).count())
+        .sum();
     let total_time: f64 = results.iter().map(|r| r.duration.as_secs_f64()).sum();
 
     xml.push_str(&format!(
-        "  <testsuite name=\"contract-tests\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        "<testsuites name=\"contract-tests\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
         total_tests, total_failures, total_time
     ));
 
     for result in results {
-        let _status = if result.passed { "pass" } else { "fail" };
+        let failures = result.steps.iter().filter(|s| !s.passed).count();
         xml.push_str(&format!(
-            "    <testcase name=\"{}\" classname=\"contract-test\" time=\"{:.3}\">\n",
-            result.scenario,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+            escape_xml(&result.scenario),
+            result.steps.len().max(1),
+            failures,
             result.duration.as_secs_f64()
         ));
 
-        if !result.passed {
+        if result.steps.is_empty() {
             xml.push_str(&format!(
-                "      <failure message=\"{}\"/>\n",
-                result
-                    .error
-                    .as_deref()
-                    .unwrap_or("Test failed")
-                    .replace('"', "&quot;")
-                    .replace('<', "&lt;")
-                    .replace('>', "&gt;")
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&result.scenario),
+                escape_xml(&result.scenario),
+                result.duration.as_secs_f64()
             ));
+            match &result.error {
+                Some(error) if !result.passed => xml.push_str(&format!(
+                    ">\n      <failure message=\"{}\"/>\n    </testcase>\n",
+                    escape_xml(error)
+                )),
+                _ => xml.push_str("/>\n"),
+            }
         }
 
-        xml.push_str("    </testcase>\n");
+        for step in &result.steps {
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&step.step_name),
+                escape_xml(&result.scenario),
+                step.duration.as_secs_f64()
+            ));
+            if step.passed {
+                xml.push_str("/>\n");
+            } else {
+                let message = step.error.as_deref().unwrap_or("Test failed");
+                xml.push_str(&format!(
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                    escape_xml(message),
+                    escape_xml(message)
+                ));
+            }
+        }
+
+        xml.push_str("  </testsuite>\n");
     }
 
-    xml.push_str("  </testsuite>\n");
     xml.push_str("</testsuites>\n");
 
     fs::write(output_path, xml)
@@ -478,3 +956,136 @@ pub fn generate_junit_xml(results: &[TestResult], output_path: &Path) -> Result<
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::tests::test_contract_wasm;
+
+    fn scenario(yaml: &str) -> TestScenario {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn runner() -> TestRunner {
+        TestRunner::from_wasm("counter", test_contract_wasm()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn asserts_on_return_values_storage_events_and_auth() {
+        let result = runner()
+            .run_scenario(scenario(
+                r#"
+name: happy path
+steps:
+  - name: add
+    contract: counter
+    method: add
+    args: [2, 40]
+    assertions:
+      - { type: equals, expected: 42 }
+      - { type: greater_than, expected: 41 }
+  - name: store
+    contract: counter
+    method: store
+    args: [7]
+    assertions:
+      - { type: state, field: "\"\"", expected: 7 }
+  - name: ping
+    contract: counter
+    method: ping
+    args: [{ Number: 5 }]
+    assertions:
+      - { type: event, expected: { topics: [ping], data: 5 } }
+  - name: approve
+    contract: counter
+    method: approve
+    args: ["@alice"]
+    assertions:
+      - { type: auth, expected: "@alice" }
+"#,
+            ))
+            .await
+            .unwrap();
+        assert!(result.passed, "{:?}", result.error);
+        assert_eq!(result.steps.len(), 4);
+        assert_eq!(result.coverage.methods_tested, 4);
+    }
+
+    #[tokio::test]
+    async fn reports_failed_assertions_and_expected_errors() {
+        let result = runner()
+            .run_scenario(scenario(
+                r#"
+name: failures
+steps:
+  - name: missing method
+    contract: counter
+    method: burn
+    expected_error: "not found"
+  - name: wrong sum
+    contract: counter
+    method: add
+    args: [1, 1]
+    assertions:
+      - { type: equals, expected: 3 }
+  - name: never runs
+    contract: counter
+    method: add
+    args: [1, 1]
+"#,
+            ))
+            .await
+            .unwrap();
+        assert!(!result.passed);
+        assert!(result.steps[0].passed);
+        assert!(!result.steps[1].passed);
+        assert_eq!(result.steps.len(), 2);
+        assert!(result.error.unwrap().contains("expected 3, got 2"));
+    }
+
+    #[test]
+    fn compares_large_integers_rendered_as_strings() {
+        let big = JsonValue::String("170141183460469231731687303715884105727".into());
+        assert!(compare_values(&big, &big.clone(), "eq").unwrap());
+        assert!(compare_values(&big, &JsonValue::from(1), "gt").unwrap());
+        assert!(
+            compare_values(&serde_json::json!([1, 2]), &JsonValue::from(2), "contains").unwrap()
+        );
+    }
+
+    #[test]
+    fn junit_has_a_testcase_per_step() {
+        let results = vec![TestResult {
+            scenario: "a <b>".into(),
+            passed: false,
+            duration: Duration::from_millis(3),
+            steps: vec![
+                StepResult {
+                    step_name: "ok".into(),
+                    passed: true,
+                    duration: Duration::from_millis(1),
+                    error: None,
+                    assertions_passed: 1,
+                    assertions_failed: 0,
+                },
+                StepResult {
+                    step_name: "bad".into(),
+                    passed: false,
+                    duration: Duration::from_millis(2),
+                    error: Some("expected \"x\"".into()),
+                    assertions_passed: 0,
+                    assertions_failed: 1,
+                },
+            ],
+            error: Some("expected \"x\"".into()),
+            coverage: CoverageTracker::new().calculate_metrics(0),
+        }];
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("junit.xml");
+        generate_junit_xml(&results, &path).unwrap();
+        let xml = fs::read_to_string(path).unwrap();
+        assert!(xml.contains("<testsuite name=\"a &lt;b&gt;\" tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<testcase name=\"ok\""));
+        assert!(xml.contains("<failure message=\"expected &quot;x&quot;\">"));
+    }
+}
@@ -0,0 +1,621 @@
+#![allow(dead_code)]
+
+//! Basic-block instrumentation for contract WASM.
+//!
+//! Every basic block gets a probe that, the first time the block runs within
+//! an invocation, emits a contract event whose body is the block id. Events
+//! survive rolled-back frames as diagnostics, so panicking paths are covered
+//! too. Block ids map back to code-section offsets of the *original* module,
+//! which is what DWARF line tables refer to.
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use wasm_encoder::reencode::{self, Reencode, RoundtripReencoder};
+use wasm_encoder::{
+    BlockType, CodeSection, ConstExpr, EntityType, GlobalSection, GlobalType, ImportSection,
+    Instruction, Module, SectionId, TypeSection, ValType,
+};
+use wasmparser::{KnownCustom, Name, Operator, Parser, Payload, TypeRef};
+
+/// Host functions the probes call: `vec_new` and `contract_event`.
+const VEC_NEW: (&str, &str) = ("v", "_");
+const CONTRACT_EVENT: (&str, &str) = ("x", "1");
+
+/// `U32Val` tag in the low byte of a Soroban `Val`.
+const U32_VAL_TAG: i64 = 4;
+
+/// A straight-line run of instructions with a single entry point.
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    pub id: u32,
+    /// Function index in the original module.
+    pub function: u32,
+    /// Code-section relative byte range, as used by DWARF.
+    pub code_range: Range<u32>,
+}
+
+/// A function defined (not imported) by the module.
+#[derive(Debug, Clone)]
+pub struct FunctionInfo {
+    pub index: u32,
+    pub name: String,
+    pub blocks: Range<u32>,
+    /// Functions reached through direct `call` instructions.
+    pub calls: BTreeSet<u32>,
+}
+
+/// Block layout of a module, shared by the instrumented and original builds.
+#[derive(Debug, Clone, Default)]
+pub struct CoverageMap {
+    pub blocks: Vec<BasicBlock>,
+    pub functions: Vec<FunctionInfo>,
+    /// Exported function names and their function indices.
+    pub exports: Vec<(String, u32)>,
+}
+
+impl CoverageMap {
+    pub fn function(&self, index: u32) -> Option<&FunctionInfo> {
+        self.functions.iter().find(|f| f.index == index)
+    }
+
+    /// Defined functions statically reachable from `root` through direct calls.
+    pub fn reachable_from(&self, root: u32) -> BTreeSet<u32> {
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            if let Some(function) = self.function(index) {
+                if seen.insert(index) {
+                    stack.extend(function.calls.iter().copied());
+                }
+            }
+        }
+        seen
+    }
+}
+
+pub struct InstrumentedModule {
+    pub wasm: Vec<u8>,
+    pub map: CoverageMap,
+}
+
+struct FunctionLayout {
+    index: u32,
+    /// Original byte offsets at which a probe is inserted, one per block.
+    leaders: Vec<usize>,
+    end: usize,
+    calls: BTreeSet<u32>,
+}
+
+struct Analysis {
+    type_count: u32,
+    imported_functions: u32,
+    global_count: u32,
+    has_type_section: bool,
+    has_import_section: bool,
+    has_global_section: bool,
+    code_start: usize,
+    vec_new: Option<u32>,
+    contract_event: Option<u32>,
+    layouts: Vec<FunctionLayout>,
+    names: HashMap<u32, String>,
+    exports: Vec<(String, u32)>,
+}
+
+fn analyze(wasm: &[u8]) -> Result<Analysis> {
+    let mut analysis = Analysis {
+        type_count: 0,
+        imported_functions: 0,
+        global_count: 0,
+        has_type_section: false,
+        has_import_section: false,
+        has_global_section: false,
+        code_start: 0,
+        vec_new: None,
+        contract_event: None,
+        layouts: Vec::new(),
+        names: HashMap::new(),
+        exports: Vec::new(),
+    };
+    let mut next_function = 0u32;
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.context("failed to parse contract wasm")? {
+            Payload::TypeSection(section) => {
+                analysis.has_type_section = true;
+                analysis.type_count = section.count();
+            }
+            Payload::ImportSection(section) => {
+                analysis.has_import_section = true;
+                for import in section.into_imports() {
+                    let import = import?;
+                    match import.ty {
+                        TypeRef::Func(_) => {
+                            let key = (import.module, import.name);
+                            if key == VEC_NEW {
+                                analysis.vec_new = Some(analysis.imported_functions);
+                            } else if key == CONTRACT_EVENT {
+                                analysis.contract_event = Some(analysis.imported_functions);
+                            }
+                            analysis.imported_functions += 1;
+                        }
+                        TypeRef::Global(_) => analysis.global_count += 1,
+                        _ => {}
+                    }
+                }
+                next_function = analysis.imported_functions;
+            }
+            Payload::GlobalSection(section) => {
+                analysis.has_global_section = true;
+                analysis.global_count += section.count();
+            }
+            Payload::ExportSection(section) => {
+                for export in section {
+                    let export = export?;
+                    if export.kind == wasmparser::ExternalKind::Func {
+                        analysis
+                            .exports
+                            .push((export.name.to_string(), export.index));
+                    }
+                }
+            }
+            Payload::CodeSectionStart { range, .. } => {
+                analysis.code_start = range.start;
+            }
+            Payload::CodeSectionEntry(body) => {
+                let index = next_function;
+                next_function += 1;
+                analysis.layouts.push(layout_function(index, &body)?);
+            }
+            Payload::CustomSection(section) => {
+                if let KnownCustom::Name(names) = section.as_known() {
+                    for name in names {
+                        if let Ok(Name::Function(map)) = name {
+                            for naming in map.into_iter().flatten() {
+                                analysis.names.insert(naming.index, naming.name.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(analysis)
+}
+
+/// Finds block leaders: the function entry and every instruction that follows
+/// a branch target or a conditional branch.
+fn layout_function(index: u32, body: &wasmparser::FunctionBody<'_>) -> Result<FunctionLayout> {
+    let mut reader = body.get_operators_reader()?;
+    let mut leaders = Vec::new();
+    let mut calls = BTreeSet::new();
+    let mut starts_block = true;
+    let mut end = body.range().end;
+    while !reader.eof() {
+        let (op, offset) = reader.read_with_offset()?;
+        if starts_block {
+            leaders.push(offset);
+        }
+        starts_block = matches!(
+            op,
+            Operator::Loop { .. }
+                | Operator::If { .. }
+                | Operator::Else
+                | Operator::End
+                | Operator::BrIf { .. }
+        );
+        if let Operator::Call { function_index } = op {
+            calls.insert(function_index);
+        }
+        if reader.eof() {
+            // The final `end` closes the function body; nothing follows it.
+            end = offset + 1;
+            if leaders.last() == Some(&offset) && leaders.len() > 1 {
+                leaders.pop();
+            }
+        }
+    }
+    Ok(FunctionLayout {
+        index,
+        leaders,
+        end,
+        calls,
+    })
+}
+
+/// Rewrites `wasm` so each basic block reports its first execution.
+pub fn instrument(wasm: &[u8]) -> Result<InstrumentedModule> {
+    let analysis = analyze(wasm)?;
+
+    let mut blocks = Vec::new();
+    let mut functions = Vec::new();
+    for layout in &analysis.layouts {
+        let first = blocks.len() as u32;
+        for (i, leader) in layout.leaders.iter().enumerate() {
+            let next = layout.leaders.get(i + 1).copied().unwrap_or(layout.end);
+            blocks.push(BasicBlock {
+                id: blocks.len() as u32,
+                function: layout.index,
+                code_range: (leader - analysis.code_start) as u32
+                    ..(next - analysis.code_start) as u32,
+            });
+        }
+        functions.push(FunctionInfo {
+            index: layout.index,
+            name: analysis
+                .names
+                .get(&layout.index)
+                .cloned()
+                .unwrap_or_else(|| format!("func[{}]", layout.index)),
+            blocks: first..blocks.len() as u32,
+            calls: layout.calls.clone(),
+        });
+    }
+
+    let added_imports =
+        u32::from(analysis.vec_new.is_none()) + u32::from(analysis.contract_event.is_none());
+    let mut next_import = analysis.imported_functions;
+    let vec_new = analysis.vec_new.unwrap_or_else(|| {
+        next_import += 1;
+        next_import - 1
+    });
+    let contract_event = analysis.contract_event.unwrap_or_else(|| {
+        next_import += 1;
+        next_import - 1
+    });
+
+    let mut instrumenter = Instrumenter {
+        analysis: &analysis,
+        added_imports,
+        vec_new,
+        contract_event,
+        bitmap_base: analysis.global_count,
+        bitmap_words: blocks.len().div_ceil(64) as u32,
+        next_function: 0,
+        next_block: 0,
+        emitted_types: false,
+        emitted_imports: false,
+        emitted_globals: false,
+    };
+    let mut module = Module::new();
+    instrumenter
+        .parse_core_module(&mut module, Parser::new(0), wasm)
+        .map_err(|e| anyhow!("failed to instrument contract wasm: {:?}", e))?;
+
+    let map = CoverageMap {
+        blocks,
+        functions,
+        exports: analysis.exports.clone(),
+    };
+    Ok(InstrumentedModule {
+        wasm: module.finish(),
+        map,
+    })
+}
+
+struct Instrumenter<'a> {
+    analysis: &'a Analysis,
+    added_imports: u32,
+    vec_new: u32,
+    contract_event: u32,
+    bitmap_base: u32,
+    bitmap_words: u32,
+    next_function: usize,
+    next_block: u32,
+    emitted_types: bool,
+    emitted_imports: bool,
+    emitted_globals: bool,
+}
+
+impl Instrumenter<'_> {
+    fn vec_new_type(&self) -> u32 {
+        self.analysis.type_count
+    }
+
+    fn contract_event_type(&self) -> u32 {
+        self.analysis.type_count + 1
+    }
+
+    fn append_types(&mut self, types: &mut TypeSection) {
+        types.ty().function([], [ValType::I64]);
+        types
+            .ty()
+            .function([ValType::I64, ValType::I64], [ValType::I64]);
+        self.emitted_types = true;
+    }
+
+    fn append_imports(&mut self, imports: &mut ImportSection) {
+        if self.analysis.vec_new.is_none() {
+            imports.import(
+                VEC_NEW.0,
+                VEC_NEW.1,
+                EntityType::Function(self.vec_new_type()),
+            );
+        }
+        if self.analysis.contract_event.is_none() {
+            imports.import(
+                CONTRACT_EVENT.0,
+                CONTRACT_EVENT.1,
+                EntityType::Function(self.contract_event_type()),
+            );
+        }
+        self.emitted_imports = true;
+    }
+
+    fn append_globals(&mut self, globals: &mut GlobalSection) {
+        for _ in 0..self.bitmap_words {
+            globals.global(
+                GlobalType {
+                    val_type: ValType::I64,
+                    mutable: true,
+                    shared: false,
+                },
+                &ConstExpr::i64_const(0),
+            );
+        }
+        self.emitted_globals = true;
+    }
+
+    fn emit_probe(&self, function: &mut wasm_encoder::Function, block: u32) {
+        let word = self.bitmap_base + block / 64;
+        let bit = (1u64 << (block % 64)) as i64;
+        function
+            .instruction(&Instruction::GlobalGet(word))
+            .instruction(&Instruction::I64Const(bit))
+            .instruction(&Instruction::I64And)
+            .instruction(&Instruction::I64Eqz)
+            .instruction(&Instruction::If(BlockType::Empty))
+            .instruction(&Instruction::GlobalGet(word))
+            .instruction(&Instruction::I64Const(bit))
+            .instruction(&Instruction::I64Or)
+            .instruction(&Instruction::GlobalSet(word))
+            .instruction(&Instruction::Call(self.vec_new))
+            .instruction(&Instruction::I64Const(
+                (i64::from(block) << 32) | U32_VAL_TAG,
+            ))
+            .instruction(&Instruction::Call(self.contract_event))
+            .instruction(&Instruction::Drop)
+            .instruction(&Instruction::End);
+    }
+}
+
+/// Position of a section in the binary format's required ordering.
+fn section_order(id: SectionId) -> u8 {
+    match id {
+        SectionId::Custom => 0,
+        SectionId::Type => 1,
+        SectionId::Import => 2,
+        SectionId::Function => 3,
+        SectionId::Table => 4,
+        SectionId::Memory => 5,
+        SectionId::Tag => 6,
+        SectionId::Global => 7,
+        SectionId::Export => 8,
+        SectionId::Start => 9,
+        SectionId::Element => 10,
+        SectionId::DataCount => 11,
+        SectionId::Code => 12,
+        SectionId::Data => 13,
+    }
+}
+
+impl Reencode for Instrumenter<'_> {
+    type Error = std::convert::Infallible;
+
+    fn function_index(&mut self, func: u32) -> Result<u32, reencode::Error> {
+        Ok(if func >= self.analysis.imported_functions {
+            func + self.added_imports
+        } else {
+            func
+        })
+    }
+
+    fn parse_type_section(
+        &mut self,
+        types: &mut TypeSection,
+        section: wasmparser::TypeSectionReader<'_>,
+    ) -> Result<(), reencode::Error> {
+        RoundtripReencoder.parse_type_section(types, section)?;
+        self.append_types(types);
+        Ok(())
+    }
+
+    fn parse_import_section(
+        &mut self,
+        imports: &mut ImportSection,
+        section: wasmparser::ImportSectionReader<'_>,
+    ) -> Result<(), reencode::Error> {
+        reencode::utils::parse_import_section(self, imports, section)?;
+        self.append_imports(imports);
+        Ok(())
+    }
+
+    fn parse_global_section(
+        &mut self,
+        globals: &mut GlobalSection,
+        section: wasmparser::GlobalSectionReader<'_>,
+    ) -> Result<(), reencode::Error> {
+        reencode::utils::parse_global_section(self, globals, section)?;
+        self.append_globals(globals);
+        Ok(())
+    }
+
+    fn intersperse_section_hook(
+        &mut self,
+        module: &mut Module,
+        after: Option<SectionId>,
+        before: Option<SectionId>,
+    ) -> Result<(), reencode::Error> {
+        // Synthesise any section the probes need but the module lacks.
+        let after = after.map(section_order).unwrap_or(0);
+        let before = before.map(section_order).unwrap_or(u8::MAX);
+        let slot = |id: SectionId| after < section_order(id) && section_order(id) < before;
+        if !self.analysis.has_type_section && !self.emitted_types && slot(SectionId::Type) {
+            let mut types = TypeSection::new();
+            self.append_types(&mut types);
+            module.section(&types);
+        }
+        if !self.analysis.has_import_section && !self.emitted_imports && slot(SectionId::Import) {
+            let mut imports = ImportSection::new();
+            self.append_imports(&mut imports);
+            module.section(&imports);
+        }
+        if !self.analysis.has_global_section && !self.emitted_globals && slot(SectionId::Global) {
+            let mut globals = GlobalSection::new();
+            self.append_globals(&mut globals);
+            module.section(&globals);
+        }
+        Ok(())
+    }
+
+    fn parse_function_body(
+        &mut self,
+        code: &mut CodeSection,
+        func: wasmparser::FunctionBody<'_>,
+    ) -> Result<(), reencode::Error> {
+        let layout = &self.analysis.layouts[self.next_function];
+        self.next_function += 1;
+
+        let mut function = self.new_function_with_parsed_locals(&func)?;
+        let mut reader = func.get_operators_reader()?;
+        let mut leaders = layout.leaders.iter().peekable();
+        while !reader.eof() {
+            let (op, offset) = reader.read_with_offset()?;
+            if leaders.peek() == Some(&&offset) {
+                leaders.next();
+                self.emit_probe(&mut function, self.next_block);
+                self.next_block += 1;
+            }
+            function.instruction(&self.instruction(op)?);
+        }
+        code.function(&function);
+        Ok(())
+    }
+
+    fn parse_custom_section(
+        &mut self,
+        module: &mut Module,
+        section: wasmparser::CustomSectionReader<'_>,
+    ) -> Result<(), reencode::Error> {
+        // Names and debug info describe the original layout; keep them out of
+        // the instrumented build and read them from the original instead.
+        if section.name() == "name" || section.name().starts_with(".debug") {
+            return Ok(());
+        }
+        reencode::utils::parse_custom_section(self, module, section)
+    }
+}
+
+/// Extracts the block ids reported by the probes of `contract` from an
+/// invocation's diagnostic events.
+pub fn hit_blocks(
+    contract: &soroban_env_host::xdr::ScAddress,
+    events: &[soroban_env_host::xdr::DiagnosticEvent],
+) -> BTreeSet<u32> {
+    use soroban_env_host::xdr::{ContractEventBody, ContractEventType, Hash, ScAddress, ScVal};
+
+    let contract_id: Option<&Hash> = match contract {
+        ScAddress::Contract(id) => Some(id),
+        ScAddress::Account(_) => None,
+    };
+    events
+        .iter()
+        .filter(|e| e.event.type_ == ContractEventType::Contract)
+        .filter(|e| e.event.contract_id.as_ref() == contract_id)
+        .filter_map(|e| {
+            let ContractEventBody::V0(body) = &e.event.body;
+            match (&body.data, body.topics.is_empty()) {
+                (ScVal::U32(id), true) => Some(*id),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Reads the custom sections holding DWARF debug info, keyed by name.
+pub fn debug_sections(wasm: &[u8]) -> Result<HashMap<String, Vec<u8>>> {
+    let mut sections = HashMap::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection(section) = payload? {
+            if section.name().starts_with(".debug") {
+                sections.insert(section.name().to_string(), section.data().to_vec());
+            }
+        }
+    }
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::{tests::test_contract_wasm, LocalLedger, SandboxLimits};
+    use soroban_env_host::xdr::ScVal;
+
+    fn branching_wasm() -> Vec<u8> {
+        wat::parse_str(
+            r#"(module
+                (func $pick (export "pick") (param $flag i64) (result i64)
+                    (if (result i64) (i64.eqz (local.get $flag))
+                        (then (i64.const 10))
+                        (else (call $helper))))
+                (func $helper (result i64) (i64.const 20))
+                (func $unused (export "unused") (result i64) (i64.const 30))
+            )"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn splits_branches_into_blocks() {
+        let module = instrument(&branching_wasm()).unwrap();
+        let pick = module.map.function(0).unwrap();
+        // entry, then-arm, else-arm; the join is only the function's `end`
+        assert_eq!(pick.blocks.len(), 3);
+        assert!(pick.calls.contains(&1));
+        assert_eq!(module.map.reachable_from(0), [0, 1].into_iter().collect());
+        wasmparser::Validator::new()
+            .validate_all(&module.wasm)
+            .unwrap();
+    }
+
+    #[test]
+    fn instrumented_module_reports_hit_blocks() {
+        let module = instrument(&test_contract_wasm()).unwrap();
+        let mut ledger = LocalLedger::with_limits(SandboxLimits::unbounded());
+        let contract = ledger.deploy(&module.wasm).unwrap();
+
+        let invocation = ledger
+            .invoke(&contract, "add", vec![ScVal::U64(1), ScVal::U64(2)])
+            .unwrap();
+        assert_eq!(invocation.result.unwrap(), ScVal::U64(3));
+
+        let hits = hit_blocks(&contract, &invocation.diagnostic_events);
+        let add = module
+            .map
+            .functions
+            .iter()
+            .find(|f| {
+                module
+                    .map
+                    .exports
+                    .iter()
+                    .any(|(n, i)| n == "add" && *i == f.index)
+            })
+            .unwrap();
+        assert!(hits.contains(&add.blocks.start));
+        let store = module
+            .map
+            .functions
+            .iter()
+            .find(|f| {
+                module
+                    .map
+                    .exports
+                    .iter()
+                    .any(|(n, i)| n == "store" && *i == f.index)
+            })
+            .unwrap();
+        assert!(!hits.contains(&store.blocks.start));
+    }
+}
@@ -19,6 +19,9 @@ pub enum WebhookEvent {
     ContractVerified,
     ContractFailedVerification,
     VersionCreated,
+    ContractDeprecated,
+    VulnerabilityDetected,
+    GovernanceProposalExecuted,
 }
 
 impl std::fmt::Display for WebhookEvent {
@@ -28,6 +31,9 @@ impl std::fmt::Display for WebhookEvent {
             WebhookEvent::ContractVerified => "contract.verified",
             WebhookEvent::ContractFailedVerification => "contract.failed_verification",
             WebhookEvent::VersionCreated => "version.created",
+            WebhookEvent::ContractDeprecated => "contract.deprecated",
+            WebhookEvent::VulnerabilityDetected => "vulnerability.detected",
+            WebhookEvent::GovernanceProposalExecuted => "governance.proposal_executed",
         };
         write!(f, "{}", s)
     }
@@ -42,8 +48,11 @@ impl std::str::FromStr for WebhookEvent {
             "contract.verified" => Ok(WebhookEvent::ContractVerified),
             "contract.failed_verification" => Ok(WebhookEvent::ContractFailedVerification),
             "version.created" => Ok(WebhookEvent::VersionCreated),
+            "contract.deprecated" => Ok(WebhookEvent::ContractDeprecated),
+            "vulnerability.detected" => Ok(WebhookEvent::VulnerabilityDetected),
+            "governance.proposal_executed" => Ok(WebhookEvent::GovernanceProposalExecuted),
             _ => anyhow::bail!(
-                "Unknown event type: {}. Valid: contract.published, contract.verified, contract.failed_verification, version.created",
+                "Unknown event type: {}. Valid: contract.published, contract.verified, contract.failed_verification, version.created, contract.deprecated, vulnerability.detected, governance.proposal_executed",
                 s
             ),
         }
@@ -57,7 +66,13 @@ pub struct WebhookSubscription {
     pub id: String,
     pub url: String,
     pub events: Vec<String>,
+    /// Only present in the response to `create`
+    #[serde(default)]
     pub secret_key: String,
+    #[serde(default)]
+    pub contract_id: Option<String>,
+    #[serde(default)]
+    pub publisher_id: Option<String>,
     pub created_at: String,
     pub active: bool,
 }
@@ -69,6 +84,8 @@ pub struct WebhookDelivery {
     pub event: String,
     pub payload: serde_json::Value,
     pub attempt: u32,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
     pub status: DeliveryStatus,
     pub response_code: Option<u16>,
     pub delivered_at: Option<String>,
@@ -76,6 +93,10 @@ pub struct WebhookDelivery {
     pub next_retry_at: Option<String>,
 }
 
+fn default_max_attempts() -> u32 {
+    5
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum DeliveryStatus {
@@ -171,6 +192,7 @@ pub async fn deliver_with_retry(
                         event: event.to_string(),
                         payload,
                         attempt,
+                        max_attempts: MAX_ATTEMPTS,
                         status: DeliveryStatus::Delivered,
                         response_code: Some(code),
                         delivered_at: Some(chrono::Utc::now().to_rfc3339()),
@@ -194,6 +216,7 @@ pub async fn deliver_with_retry(
         event: event.to_string(),
         payload,
         attempt: MAX_ATTEMPTS,
+        max_attempts: MAX_ATTEMPTS,
         status: DeliveryStatus::DeadLetter,
         response_code: last_code,
         delivered_at: None,
@@ -210,7 +233,13 @@ pub async fn create_webhook(
     url: &str,
     events: Vec<String>,
     secret_key: Option<&str>,
+    contract_id: Option<&str>,
+    publisher_id: Option<&str>,
 ) -> Result<()> {
+    for event in &events {
+        event.parse::<WebhookEvent>()?;
+    }
+
     let client = reqwest::Client::new();
 
     // Generate a secret key if not provided
@@ -227,6 +256,8 @@ pub async fn create_webhook(
         "url": url,
         "events": events,
         "secret_key": secret,
+        "contract_id": contract_id,
+        "publisher_id": publisher_id,
     });
 
     let response = client
@@ -248,6 +279,12 @@ pub async fn create_webhook(
     println!("  {}: {}", "ID".bold(), webhook.id.bright_black());
     println!("  {}: {}", "URL".bold(), webhook.url);
     println!("  {}: {}", "Events".bold(), webhook.events.join(", ").bright_blue());
+    if let Some(contract_id) = &webhook.contract_id {
+        println!("  {}: {}", "Contract".bold(), contract_id);
+    }
+    if let Some(publisher_id) = &webhook.publisher_id {
+        println!("  {}: {}", "Publisher".bold(), publisher_id);
+    }
     println!(
         "  {}: {}",
         "Secret Key".bold(),
@@ -290,6 +327,12 @@ pub async fn list_webhooks(api_url: &str) -> Result<()> {
             };
             println!("\n  {} {} {}", status, wh.id.bright_black(), wh.url.bold());
             println!("    Events: {}", wh.events.join(", ").bright_blue());
+            if let Some(contract_id) = &wh.contract_id {
+                println!("    Contract: {}", contract_id);
+            }
+            if let Some(publisher_id) = &wh.publisher_id {
+                println!("    Publisher: {}", publisher_id);
+            }
             println!("    Created: {}", wh.created_at.bright_black());
         }
     }
@@ -338,12 +381,15 @@ pub async fn test_webhook(api_url: &str, webhook_id: &str) -> Result<()> {
         anyhow::bail!("API error: {}", err);
     }
 
+    let delivery: WebhookDelivery = response.json().await?;
+
     println!(
-        "{} Test event sent to webhook {}.",
+        "{} Test event queued for webhook {} (delivery {}).",
         "✓".green(),
-        webhook_id.bright_black()
+        webhook_id.bright_black(),
+        delivery.id.bright_black()
     );
-    println!("  Check your endpoint for the incoming request.\n");
+    println!("  Check your endpoint, or run 'webhook logs' to see the result.\n");
 
     Ok(())
 }
@@ -384,7 +430,7 @@ pub async fn webhook_logs(api_url: &str, webhook_id: &str, limit: usize) -> Resu
             };
 
             println!("\n  {} {} — {}", status_str, d.id.bright_black(), d.event.bold());
-            println!("    Attempt: {}/5", d.attempt);
+            println!("    Attempt: {}/{}", d.attempt, d.max_attempts);
 
             if let Some(code) = d.response_code {
                 println!("    Response: HTTP {}", code);
@@ -395,6 +441,11 @@ pub async fn webhook_logs(api_url: &str, webhook_id: &str, limit: usize) -> Resu
             if let Some(delivered_at) = &d.delivered_at {
                 println!("    Delivered at: {}", delivered_at.bright_black());
             }
+            if d.status == DeliveryStatus::Failed {
+                if let Some(next_retry_at) = &d.next_retry_at {
+                    println!("    Next retry: {}", next_retry_at.bright_black());
+                }
+            }
             if d.status == DeliveryStatus::DeadLetter {
                 println!(
                     "    {} Max retries exhausted. Use 'webhook retry' to manually re-queue.",
@@ -409,7 +460,7 @@ pub async fn webhook_logs(api_url: &str, webhook_id: &str, limit: usize) -> Resu
     Ok(())
 }
 
-/// Manually retry a dead-letter delivery.
+/// Manually retry a failed or dead-letter delivery.
 pub async fn retry_delivery(api_url: &str, delivery_id: &str) -> Result<()> {
     let client = reqwest::Client::new();
 
@@ -0,0 +1,10 @@
+-- Per-method resource averages so historical estimates can be priced with
+-- the current fee schedule instead of a stored fee.
+ALTER TABLE cost_estimates
+    ADD COLUMN avg_instructions BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN avg_read_entries BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN avg_write_entries BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN avg_read_bytes BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN avg_write_bytes BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN avg_events_bytes BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN avg_tx_size_bytes BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,201 @@
+-- Migration: 047_contract_search_index.sql
+-- Weighted, faceted contract search over metadata, tags, publisher and ABI
+--
+-- Strategy:
+--   • One search document per contract in contract_search_documents, holding
+--     a weighted tsvector over everything a user might search for:
+--       A  name
+--       B  tags, ABI function names
+--       C  description, category, ABI event names
+--       D  publisher username / address
+--   • The document lives in its own table because its inputs span
+--     contracts, contract_abis and publishers; triggers on all three keep it
+--     in sync, so writers never have to remember to reindex.
+--   • `terms` is the same text flattened to lower case for pg_trgm, which
+--     powers fuzzy matching of misspelled queries (word_similarity).
+--   • Identifiers like `transfer_from` are split on '_' before indexing so
+--     "transfer" finds them.
+--   • The 'english' configuration matches contracts_build_tsquery (026).
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- ── 1. Search documents ─────────────────────────────────────────────────────
+CREATE TABLE IF NOT EXISTS contract_search_documents (
+    contract_id    UUID PRIMARY KEY REFERENCES contracts(id) ON DELETE CASCADE,
+    publisher_name TEXT,
+    function_names TEXT[] NOT NULL DEFAULT '{}',
+    event_names    TEXT[] NOT NULL DEFAULT '{}',
+    document       tsvector NOT NULL,
+    terms          TEXT NOT NULL,
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_contract_search_documents_document
+  ON contract_search_documents USING GIN (document);
+
+CREATE INDEX IF NOT EXISTS idx_contract_search_documents_terms_trgm
+  ON contract_search_documents USING GIN (terms gin_trgm_ops);
+
+-- Facet and tag filters
+CREATE INDEX IF NOT EXISTS idx_contracts_tags
+  ON contracts USING GIN (tags);
+
+-- ── 2. Helpers ──────────────────────────────────────────────────────────────
+
+-- Turn identifiers into words: "transfer_from" → "transfer from"
+CREATE OR REPLACE FUNCTION search_identifier_words(identifiers TEXT[])
+RETURNS TEXT
+LANGUAGE sql
+IMMUTABLE
+AS $$
+  SELECT COALESCE(string_agg(replace(i, '_', ' '), ' '), '')
+  FROM unnest(identifiers) AS i
+$$;
+
+-- Names of the ABI's functions or events. Handles both the registry's
+-- parsed ABI ({"functions": [{"name": ...}], "events": [...]}) and the raw
+-- contract spec (an array of entries tagged with "type").
+CREATE OR REPLACE FUNCTION abi_entry_names(abi JSONB, kind TEXT)
+RETURNS TEXT[]
+LANGUAGE sql
+IMMUTABLE
+AS $$
+  SELECT COALESCE(array_agg(DISTINCT name ORDER BY name), '{}')
+  FROM (
+    SELECT entry->>'name' AS name
+    FROM jsonb_array_elements(
+      CASE WHEN jsonb_typeof(abi -> (kind || 's')) = 'array'
+           THEN abi -> (kind || 's') ELSE '[]'::jsonb END
+    ) AS entry
+    UNION ALL
+    SELECT entry->>'name'
+    FROM jsonb_array_elements(
+      CASE WHEN jsonb_typeof(abi) = 'array' THEN abi ELSE '[]'::jsonb END
+    ) AS entry
+    WHERE entry->>'type' = kind
+  ) names
+  WHERE name IS NOT NULL AND name <> ''
+$$;
+
+-- Rebuild the search document for one contract from the latest ABI.
+CREATE OR REPLACE FUNCTION refresh_contract_search_document(p_contract_id UUID)
+RETURNS VOID
+LANGUAGE plpgsql
+AS $$
+DECLARE
+  c          RECORD;
+  latest_abi JSONB;
+  publisher  TEXT;
+  functions  TEXT[];
+  events     TEXT[];
+BEGIN
+  SELECT * INTO c FROM contracts WHERE id = p_contract_id;
+  IF NOT FOUND THEN
+    RETURN;
+  END IF;
+
+  SELECT abi INTO latest_abi
+  FROM contract_abis
+  WHERE contract_id = p_contract_id
+  ORDER BY created_at DESC
+  LIMIT 1;
+
+  SELECT COALESCE(NULLIF(p.username, ''), p.stellar_address) INTO publisher
+  FROM publishers p
+  WHERE p.id = c.publisher_id;
+
+  functions := COALESCE(abi_entry_names(latest_abi, 'function'), '{}');
+  events    := COALESCE(abi_entry_names(latest_abi, 'event'), '{}');
+
+  INSERT INTO contract_search_documents AS d
+    (contract_id, publisher_name, function_names, event_names, document, terms, updated_at)
+  VALUES (
+    p_contract_id,
+    publisher,
+    functions,
+    events,
+    setweight(to_tsvector('english', replace(c.name, '_', ' ')), 'A') ||
+    setweight(to_tsvector('english',
+      array_to_string(COALESCE(c.tags, '{}'), ' ') || ' ' ||
+      search_identifier_words(functions)), 'B') ||
+    setweight(to_tsvector('english',
+      COALESCE(c.description, '') || ' ' ||
+      COALESCE(c.category, '') || ' ' ||
+      search_identifier_words(events)), 'C') ||
+    setweight(to_tsvector('simple', COALESCE(publisher, '')), 'D'),
+    lower(concat_ws(' ',
+      c.name,
+      array_to_string(COALESCE(c.tags, '{}'), ' '),
+      c.category,
+      publisher,
+      NULLIF(search_identifier_words(functions), ''),
+      NULLIF(search_identifier_words(events), ''))),
+    NOW()
+  )
+  ON CONFLICT (contract_id) DO UPDATE SET
+    publisher_name = EXCLUDED.publisher_name,
+    function_names = EXCLUDED.function_names,
+    event_names    = EXCLUDED.event_names,
+    document       = EXCLUDED.document,
+    terms          = EXCLUDED.terms,
+    updated_at     = EXCLUDED.updated_at;
+END;
+$$;
+
+-- ── 3. Sync triggers ────────────────────────────────────────────────────────
+CREATE OR REPLACE FUNCTION contracts_search_sync()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+BEGIN
+  PERFORM refresh_contract_search_document(NEW.id);
+  RETURN NULL;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS trg_contracts_search_sync ON contracts;
+CREATE TRIGGER trg_contracts_search_sync
+  AFTER INSERT OR UPDATE OF name, description, category, tags, publisher_id
+  ON contracts
+  FOR EACH ROW EXECUTE FUNCTION contracts_search_sync();
+
+CREATE OR REPLACE FUNCTION contract_abis_search_sync()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    PERFORM refresh_contract_search_document(OLD.contract_id);
+  ELSE
+    PERFORM refresh_contract_search_document(NEW.contract_id);
+  END IF;
+  RETURN NULL;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS trg_contract_abis_search_sync ON contract_abis;
+CREATE TRIGGER trg_contract_abis_search_sync
+  AFTER INSERT OR UPDATE OR DELETE ON contract_abis
+  FOR EACH ROW EXECUTE FUNCTION contract_abis_search_sync();
+
+CREATE OR REPLACE FUNCTION publishers_search_sync()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+BEGIN
+  PERFORM refresh_contract_search_document(c.id)
+  FROM contracts c
+  WHERE c.publisher_id = NEW.id;
+  RETURN NULL;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS trg_publishers_search_sync ON publishers;
+CREATE TRIGGER trg_publishers_search_sync
+  AFTER UPDATE OF username, stellar_address ON publishers
+  FOR EACH ROW EXECUTE FUNCTION publishers_search_sync();
+
+-- ── 4. Backfill ─────────────────────────────────────────────────────────────
+SELECT refresh_contract_search_document(id) FROM contracts;
+
+ANALYZE contract_search_documents;
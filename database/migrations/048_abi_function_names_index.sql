@@ -0,0 +1,10 @@
+-- Migration: 048_abi_function_names_index.sql
+-- Index ABI function names for interface-conformance search
+--
+-- POST /api/contracts/interface-search narrows candidates to contracts whose
+-- latest ABI defines the required function names (`@>` for full conformance,
+-- `&&` for partial) before checking signatures in the API. Both operators
+-- use this index.
+
+CREATE INDEX IF NOT EXISTS idx_contract_search_documents_function_names
+  ON contract_search_documents USING GIN (function_names);
@@ -0,0 +1,37 @@
+-- Migration: 049_advisory_version_ranges.sql
+-- Version-range advisories and lockfile-based dependency scanning
+--
+--   • cve_vulnerabilities.patched_versions now holds version requirements
+--     (">= 1.2.3", "^0.9.30", ">= 0.10.4, < 0.11.0") rather than exact
+--     versions; a bare "1.2.3" still means exactly that version.
+--     unaffected_versions lists ranges that never had the bug. A version is
+--     vulnerable only if it satisfies neither list.
+--   • Advisories imported from RustSec keep their RUSTSEC id in cve_id, with
+--     CVE/GHSA ids in aliases.
+--   • withdrawn_at marks advisories RustSec has since withdrawn. A re-import
+--     sets it rather than deleting the row; scans, scan history and trust
+--     scores ignore withdrawn advisories.
+--   • A Cargo.lock can lock several versions of one crate, so dependencies
+--     and findings are unique per (package, version).
+
+ALTER TABLE cve_vulnerabilities
+    ADD COLUMN IF NOT EXISTS unaffected_versions TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS aliases TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS title TEXT,
+    ADD COLUMN IF NOT EXISTS url TEXT,
+    ADD COLUMN IF NOT EXISTS source VARCHAR(20) NOT NULL DEFAULT 'manual',
+    ADD COLUMN IF NOT EXISTS withdrawn_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_cve_aliases ON cve_vulnerabilities USING GIN (aliases);
+
+ALTER TABLE contract_package_dependencies
+    ADD COLUMN IF NOT EXISTS is_direct BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN IF NOT EXISTS source TEXT,
+    DROP CONSTRAINT IF EXISTS contract_package_dependencies_contract_id_package_name_key,
+    ADD CONSTRAINT contract_package_dependencies_contract_package_version_key
+        UNIQUE (contract_id, package_name, version);
+
+ALTER TABLE contract_scan_results
+    DROP CONSTRAINT IF EXISTS contract_scan_results_contract_id_cve_id_key,
+    ADD CONSTRAINT contract_scan_results_contract_cve_package_version_key
+        UNIQUE (contract_id, cve_id, package_name, current_version);
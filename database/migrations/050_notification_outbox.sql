@@ -0,0 +1,62 @@
+-- Migration: 050_notification_outbox.sql
+-- Persistent notification outbox with per-attempt delivery tracking
+--
+--   • send_notification renders the template once and queues one outbox row
+--     per (channel, destination); a background dispatcher delivers them with
+--     exponential backoff and moves exhausted rows to 'dead_letter'. A
+--     claimed row stays 'pending' with next_attempt_at pushed out as a lease.
+--   • Preferences carry the destination for each channel they enable.
+--   • notification_logs keeps one row per send request; its status is
+--     'queued' until every outbox row for it has settled.
+
+ALTER TABLE user_notification_preferences
+    ADD COLUMN IF NOT EXISTS email_address TEXT,
+    ADD COLUMN IF NOT EXISTS webhook_url TEXT,
+    ADD COLUMN IF NOT EXISTS webhook_secret TEXT,
+    ADD COLUMN IF NOT EXISTS slack_webhook_url TEXT,
+    ADD COLUMN IF NOT EXISTS matrix_webhook_url TEXT;
+
+CREATE TABLE IF NOT EXISTS notification_outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    notification_log_id UUID REFERENCES notification_logs(id) ON DELETE SET NULL,
+    contract_id UUID REFERENCES contracts(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES publishers(id) ON DELETE SET NULL,
+    notification_type VARCHAR(50) NOT NULL,
+    channel VARCHAR(20) NOT NULL
+        CHECK (channel IN ('email', 'webhook', 'slack', 'matrix')),
+    recipient TEXT NOT NULL,                      -- email address or endpoint URL
+    signing_secret TEXT,                          -- HMAC key for 'webhook' deliveries
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'delivered', 'dead_letter')),
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL DEFAULT 5,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_outbox_due
+    ON notification_outbox(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_notification_outbox_user
+    ON notification_outbox(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_notification_outbox_log
+    ON notification_outbox(notification_log_id);
+
+CREATE TABLE IF NOT EXISTS notification_delivery_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    outbox_id UUID NOT NULL REFERENCES notification_outbox(id) ON DELETE CASCADE,
+    attempt INT NOT NULL,
+    outcome VARCHAR(20) NOT NULL CHECK (outcome IN ('delivered', 'retry', 'rejected')),
+    response_code INT,                            -- HTTP status or SMTP reply code
+    error TEXT,
+    duration_ms INT NOT NULL,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_delivery_attempts_outbox
+    ON notification_delivery_attempts(outbox_id, attempt);
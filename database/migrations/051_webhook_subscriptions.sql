@@ -0,0 +1,50 @@
+-- Migration: 051_webhook_subscriptions.sql
+-- Server-side webhook subscriptions and their delivery log
+--
+--   • A subscription lists the events it wants and can be scoped to one
+--     publisher and/or one contract; unscoped subscriptions see every event.
+--   • Each emitted event queues one delivery per matching subscription. The
+--     dispatcher signs the payload with the subscription secret, retries with
+--     exponential backoff ('failed' + next_retry_at) and dead-letters rows
+--     that run out of attempts or are refused by the receiver.
+
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    publisher_id UUID REFERENCES publishers(id) ON DELETE CASCADE,
+    contract_id UUID REFERENCES contracts(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    events TEXT[] NOT NULL,
+    secret_key TEXT NOT NULL,                     -- HMAC-SHA256 signing key
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_events
+    ON webhook_subscriptions USING GIN (events) WHERE active;
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_publisher
+    ON webhook_subscriptions(publisher_id);
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_contract
+    ON webhook_subscriptions(contract_id);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+    event VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    attempt INT NOT NULL DEFAULT 0,               -- attempts made so far
+    max_attempts INT NOT NULL DEFAULT 5,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'delivered', 'failed', 'dead_letter')),
+    response_code INT,
+    error TEXT,
+    delivered_at TIMESTAMPTZ,
+    next_retry_at TIMESTAMPTZ DEFAULT NOW(),      -- NULL once settled
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON webhook_deliveries(next_retry_at) WHERE status IN ('pending', 'failed');
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook
+    ON webhook_deliveries(webhook_id, created_at DESC);
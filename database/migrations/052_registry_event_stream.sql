@@ -0,0 +1,147 @@
+-- Migration: 052_registry_event_stream.sql
+-- Ordered registry event log backing the SSE / WebSocket stream
+--
+--   • Triggers copy activity-feed entries, final verification results,
+--     indexer deployments and indexed contract events into one table, so the
+--     stream sees every writer (API replicas, indexer, seeders).
+--   • The BIGSERIAL id is the stream cursor clients resume from with
+--     Last-Event-ID.
+--   • Each insert is announced with pg_notify('registry_stream', id); every
+--     API replica LISTENs and fans the row out to its connected clients.
+
+CREATE TABLE IF NOT EXISTS registry_stream_events (
+    id BIGSERIAL PRIMARY KEY,
+    kind VARCHAR(20) NOT NULL
+        CHECK (kind IN ('activity', 'verification', 'deployment', 'contract_event')),
+    event_type TEXT NOT NULL,                     -- analytics type, verification status or event topic
+    contract_id UUID,
+    contract_stellar_id TEXT,
+    publisher_id UUID,
+    category TEXT,
+    network TEXT,
+    payload JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_registry_stream_events_created
+    ON registry_stream_events(created_at);
+
+CREATE OR REPLACE FUNCTION notify_registry_stream()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('registry_stream', NEW.id::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS registry_stream_notify ON registry_stream_events;
+CREATE TRIGGER registry_stream_notify AFTER INSERT ON registry_stream_events
+    FOR EACH ROW EXECUTE FUNCTION notify_registry_stream();
+
+-- Activity feed (analytics_events)
+CREATE OR REPLACE FUNCTION stream_analytics_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO registry_stream_events
+        (kind, event_type, contract_id, contract_stellar_id, publisher_id, category, network, payload, created_at)
+    SELECT 'activity', NEW.event_type::text, NEW.contract_id, c.contract_id,
+           COALESCE(NEW.publisher_id, c.publisher_id), c.category,
+           COALESCE(NEW.network, c.network)::text,
+           jsonb_build_object(
+               'id', NEW.id,
+               'contract_name', c.name,
+               'user_address', NEW.user_address,
+               'metadata', COALESCE(NEW.metadata, '{}'::jsonb)
+           ),
+           NEW.created_at
+    FROM (SELECT 1) AS one
+    LEFT JOIN contracts c ON c.id = NEW.contract_id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS analytics_events_stream ON analytics_events;
+CREATE TRIGGER analytics_events_stream AFTER INSERT ON analytics_events
+    FOR EACH ROW EXECUTE FUNCTION stream_analytics_event();
+
+-- Verification results (only once a run reaches a final status)
+CREATE OR REPLACE FUNCTION stream_verification_result()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.status = 'pending'
+       OR (TG_OP = 'UPDATE' AND OLD.status IS NOT DISTINCT FROM NEW.status) THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO registry_stream_events
+        (kind, event_type, contract_id, contract_stellar_id, publisher_id, category, network, payload)
+    SELECT 'verification', NEW.status::text, c.id, c.contract_id, c.publisher_id, c.category,
+           c.network::text,
+           jsonb_build_object(
+               'verification_id', NEW.id,
+               'status', NEW.status,
+               'compiler_version', NEW.compiler_version,
+               'verified_at', NEW.verified_at,
+               'error_message', NEW.error_message
+           )
+    FROM contracts c
+    WHERE c.id = NEW.contract_id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS verifications_stream ON verifications;
+CREATE TRIGGER verifications_stream AFTER INSERT OR UPDATE OF status ON verifications
+    FOR EACH ROW EXECUTE FUNCTION stream_verification_result();
+
+-- Deployments discovered by the indexer
+CREATE OR REPLACE FUNCTION stream_deployment()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO registry_stream_events
+        (kind, event_type, contract_id, contract_stellar_id, publisher_id, category, network, payload, created_at)
+    SELECT 'deployment', 'contract_deployed', c.id, c.contract_id, c.publisher_id, c.category,
+           NEW.network::text,
+           jsonb_build_object(
+               'deployer', NEW.user_address,
+               'transaction_hash', NEW.transaction_hash,
+               'wasm_hash', c.wasm_hash
+           ),
+           NEW.created_at
+    FROM contracts c
+    WHERE c.id = NEW.contract_id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS contract_interactions_deploy_stream ON contract_interactions;
+CREATE TRIGGER contract_interactions_deploy_stream AFTER INSERT ON contract_interactions
+    FOR EACH ROW WHEN (NEW.interaction_type = 'deploy')
+    EXECUTE FUNCTION stream_deployment();
+
+-- Indexed contract events
+CREATE OR REPLACE FUNCTION stream_contract_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO registry_stream_events
+        (kind, event_type, contract_id, contract_stellar_id, publisher_id, category, network, payload, created_at)
+    SELECT 'contract_event', NEW.topic, c.id, NEW.contract_id, c.publisher_id, c.category,
+           NEW.network::text,
+           jsonb_build_object(
+               'id', NEW.id,
+               'topic', NEW.topic,
+               'data', NEW.data,
+               'ledger_sequence', NEW.ledger_sequence,
+               'transaction_hash', NEW.transaction_hash,
+               'timestamp', NEW.timestamp
+           ),
+           NEW.created_at
+    FROM (SELECT 1) AS one
+    LEFT JOIN contracts c ON c.contract_id = NEW.contract_id AND c.network = NEW.network;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS contract_events_stream ON contract_events;
+CREATE TRIGGER contract_events_stream AFTER INSERT ON contract_events
+    FOR EACH ROW EXECUTE FUNCTION stream_contract_event();
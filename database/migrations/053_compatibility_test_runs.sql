@@ -0,0 +1,49 @@
+-- Migration: 053_compatibility_test_runs.sql
+-- Executable compatibility testing
+--
+--   • contract_test_fixtures: recorded contract calls (function, typed args,
+--     expected result or expected failure) replayed against every SDK version.
+--   • compatibility_test_runs: queued rebuild-and-run requests picked up by
+--     the compatibility worker; the latest result per SDK/runtime/network is
+--     mirrored into contract_compatibility for the matrix and dashboard.
+
+CREATE TABLE IF NOT EXISTS contract_test_fixtures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_id UUID NOT NULL REFERENCES contracts(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    function_name VARCHAR(32) NOT NULL,
+    args JSONB NOT NULL DEFAULT '[]',              -- [{"type": "u32", "value": 5}, ...]
+    expected JSONB,                                -- expected return value, if checked
+    expect_error BOOLEAN NOT NULL DEFAULT FALSE,
+    error_code INTEGER,                            -- contract error code, with expect_error
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(contract_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_contract_test_fixtures_contract
+    ON contract_test_fixtures(contract_id);
+
+CREATE TABLE IF NOT EXISTS compatibility_test_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_id UUID NOT NULL REFERENCES contracts(id) ON DELETE CASCADE,
+    sdk_version VARCHAR(50) NOT NULL,
+    wasm_runtime VARCHAR(50) NOT NULL,
+    network VARCHAR(50) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'completed', 'failed')),
+    result compatibility_status,                   -- set when completed
+    fixtures_total INTEGER,
+    fixtures_passed INTEGER,
+    fixture_results JSONB,                         -- [{"name", "passed", "message"}]
+    duration_ms INTEGER,
+    output TEXT,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_compatibility_test_runs_pending
+    ON compatibility_test_runs(created_at) WHERE status IN ('queued', 'running');
+CREATE INDEX IF NOT EXISTS idx_compatibility_test_runs_contract
+    ON compatibility_test_runs(contract_id, created_at DESC);
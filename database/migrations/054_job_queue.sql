@@ -0,0 +1,73 @@
+-- Migration: 054_job_queue.sql
+-- Durable background jobs
+--
+--   • jobs: work handed off by API handlers (verification builds, dependency
+--     scans, compatibility runs). Workers claim the highest-priority due job
+--     with FOR UPDATE SKIP LOCKED and hold it under a renewable lease; a job
+--     whose lease expires is requeued (or failed once out of attempts).
+--     Failed attempts are retried with backoff via run_at.
+--   • job_logs: append-only per-job log lines streamed by /api/jobs/:id/stream.
+--   • compatibility_test_runs.job_id: the job executing each run. Runs that
+--     were waiting on the old polling worker are moved onto the queue.
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    status VARCHAR(20) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'succeeded', 'failed', 'cancelled')),
+    priority SMALLINT NOT NULL DEFAULT 0,          -- higher runs first
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3 CHECK (max_attempts > 0),
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),     -- not claimed before this (backoff)
+    locked_by VARCHAR(255),                        -- worker id holding the lease
+    locked_until TIMESTAMPTZ,
+    cancel_requested BOOLEAN NOT NULL DEFAULT FALSE,
+    progress SMALLINT NOT NULL DEFAULT 0 CHECK (progress BETWEEN 0 AND 100),
+    progress_message TEXT,
+    result JSONB,
+    error TEXT,
+    contract_id UUID REFERENCES contracts(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_claim
+    ON jobs(priority DESC, run_at) WHERE status = 'queued';
+CREATE INDEX IF NOT EXISTS idx_jobs_lease
+    ON jobs(locked_until) WHERE status = 'running';
+CREATE INDEX IF NOT EXISTS idx_jobs_contract
+    ON jobs(contract_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_jobs_kind_status
+    ON jobs(kind, status, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS job_logs (
+    id BIGSERIAL PRIMARY KEY,
+    job_id UUID NOT NULL REFERENCES jobs(id) ON DELETE CASCADE,
+    level VARCHAR(10) NOT NULL DEFAULT 'info'
+        CHECK (level IN ('debug', 'info', 'warn', 'error')),
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_logs_job ON job_logs(job_id, id);
+
+ALTER TABLE compatibility_test_runs
+    ADD COLUMN IF NOT EXISTS job_id UUID REFERENCES jobs(id) ON DELETE SET NULL;
+
+WITH pending AS (
+    SELECT id AS run_id, contract_id, gen_random_uuid() AS job_id
+    FROM compatibility_test_runs
+    WHERE status IN ('queued', 'running') AND job_id IS NULL
+),
+queued AS (
+    INSERT INTO jobs (id, kind, payload, contract_id)
+    SELECT job_id, 'compatibility_run', jsonb_build_object('run_id', run_id), contract_id
+    FROM pending
+)
+UPDATE compatibility_test_runs r
+SET job_id = p.job_id, status = 'queued', started_at = NULL
+FROM pending p
+WHERE r.id = p.run_id;
@@ -0,0 +1,26 @@
+-- Migration: 055_artifact_storage.sql
+-- Content-addressed WASM and source artifacts
+--
+--   • artifacts: one row per stored blob, keyed by the lowercase hex SHA-256
+--     of its bytes. Identical uploads share a row (and a stored object).
+--   • contract_versions.wasm_artifact_sha256 / source_artifact_sha256: the
+--     WASM binary and source tarball served for each version. The WASM
+--     artifact's hash always equals the version's wasm_hash.
+
+CREATE TABLE IF NOT EXISTS artifacts (
+    sha256 CHAR(64) PRIMARY KEY CHECK (sha256 ~ '^[0-9a-f]{64}$'),
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('wasm', 'source')),
+    size_bytes BIGINT NOT NULL CHECK (size_bytes > 0),
+    content_type VARCHAR(100) NOT NULL,
+    storage_backend VARCHAR(20) NOT NULL,          -- 'local' | 's3'
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE contract_versions
+    ADD COLUMN IF NOT EXISTS wasm_artifact_sha256 CHAR(64) REFERENCES artifacts(sha256),
+    ADD COLUMN IF NOT EXISTS source_artifact_sha256 CHAR(64) REFERENCES artifacts(sha256);
+
+CREATE INDEX IF NOT EXISTS idx_contract_versions_wasm_artifact
+    ON contract_versions(wasm_artifact_sha256) WHERE wasm_artifact_sha256 IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_contract_versions_source_artifact
+    ON contract_versions(source_artifact_sha256) WHERE source_artifact_sha256 IS NOT NULL;
@@ -0,0 +1,33 @@
+-- Migration: 056_canary_controller.sql
+-- Canary releases driven by the API's canary controller
+--
+--   • canary_releases: per-canary promotion policy (minimum traffic and time
+--     per stage, auto-advance switch) and the start of the current stage,
+--     which bounds the metrics window each decision is made on.
+--   • canary_user_assignments.stage: the rollout stage at which a caller
+--     first lands on the new deployment.
+--   • The row-level auto-rollback trigger from 009 is dropped. It judged the
+--     cumulative error rate from the first request, rolled back on a single
+--     early error and left no audit trail; the controller now makes that
+--     decision on the current stage's window and records every transition.
+--   • audit_action_type gains 'canary_transition'.
+
+DROP TRIGGER IF EXISTS canary_auto_rollback_trigger ON canary_releases;
+DROP FUNCTION IF EXISTS check_canary_error_rate();
+
+ALTER TABLE canary_releases
+    ADD COLUMN IF NOT EXISTS min_requests_per_stage INTEGER NOT NULL DEFAULT 100
+        CHECK (min_requests_per_stage >= 0),
+    ADD COLUMN IF NOT EXISTS min_stage_duration_secs INTEGER NOT NULL DEFAULT 300
+        CHECK (min_stage_duration_secs >= 0),
+    ADD COLUMN IF NOT EXISTS auto_advance BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN IF NOT EXISTS stage_started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN IF NOT EXISTS rollback_reason TEXT;
+
+ALTER TABLE canary_user_assignments
+    ADD COLUMN IF NOT EXISTS stage rollout_stage NOT NULL DEFAULT 'stage_1';
+
+CREATE INDEX IF NOT EXISTS idx_canary_metrics_canary_ts
+    ON canary_metrics(canary_id, timestamp DESC);
+
+ALTER TYPE audit_action_type ADD VALUE IF NOT EXISTS 'canary_transition';
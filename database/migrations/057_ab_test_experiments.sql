@@ -0,0 +1,35 @@
+-- Migration: 057_ab_test_experiments.sql
+-- A/B experiments analysed by the API's experiment service
+--
+--   • ab_tests: how the primary metric is analysed (conversion → two-proportion
+--     z-test, continuous → Welch's t-test), which direction is better, the
+--     minimum detectable effect and power the sample size is planned for, and
+--     the outcome once the stopping rule ends the experiment.
+--   • ab_test_results: the test used and its statistic.
+--   • assign_variant() and calculate_statistical_significance() from 013 are
+--     dropped. Assignment and analysis now live in the API; the old function
+--     compared a pooled-variance t statistic against a normal CDF and had no
+--     sample-size planning.
+
+DROP FUNCTION IF EXISTS assign_variant(UUID, VARCHAR);
+DROP FUNCTION IF EXISTS calculate_statistical_significance(UUID);
+DROP FUNCTION IF EXISTS normal_cdf(DECIMAL);
+DROP FUNCTION IF EXISTS erf(DECIMAL);
+
+ALTER TABLE ab_tests
+    ADD COLUMN IF NOT EXISTS metric_kind VARCHAR(20) NOT NULL DEFAULT 'continuous'
+        CHECK (metric_kind IN ('conversion', 'continuous')),
+    ADD COLUMN IF NOT EXISTS higher_is_better BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN IF NOT EXISTS minimum_detectable_effect DECIMAL(15,4)
+        CHECK (minimum_detectable_effect IS NULL OR minimum_detectable_effect > 0),
+    ADD COLUMN IF NOT EXISTS statistical_power DECIMAL(5,2) NOT NULL DEFAULT 80.0
+        CHECK (statistical_power > 0 AND statistical_power < 100),
+    ADD COLUMN IF NOT EXISTS winner variant_type,
+    ADD COLUMN IF NOT EXISTS stop_reason TEXT;
+
+ALTER TABLE ab_test_results
+    ADD COLUMN IF NOT EXISTS test_method VARCHAR(40),
+    ADD COLUMN IF NOT EXISTS test_statistic DECIMAL(15,6);
+
+CREATE INDEX IF NOT EXISTS idx_ab_test_metrics_test_metric
+    ON ab_test_metrics(test_id, metric_name, variant_type);
@@ -0,0 +1,58 @@
+-- Migration: 058_blue_green_orchestration.sql
+-- Blue/green deployments managed by the API's deployment controller
+--
+--   • contract_deployments: a new release goes into whichever slot is idle
+--     and stays 'testing' until `required_health_checks` consecutive checks
+--     pass (then it is switched in when `auto_switch` is set) or
+--     `max_failed_health_checks` consecutive checks fail. After a switch the
+--     new live slot is watched until `monitor_until`; failing it then rolls
+--     traffic back to the previous slot.
+--   • deployment_health_checks: every probe or reported check.
+--   • deployment_switches: which deployments and WASM hashes were swapped
+--     and why. `from_environment` is NULL for a contract's first activation.
+
+ALTER TABLE contract_deployments
+    ADD COLUMN IF NOT EXISTS required_health_checks INTEGER NOT NULL DEFAULT 3
+        CHECK (required_health_checks >= 1),
+    ADD COLUMN IF NOT EXISTS max_failed_health_checks INTEGER NOT NULL DEFAULT 2
+        CHECK (max_failed_health_checks >= 1),
+    ADD COLUMN IF NOT EXISTS consecutive_passes INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS auto_switch BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN IF NOT EXISTS monitor_until TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS deployed_by VARCHAR(255);
+
+UPDATE contract_deployments
+SET health_checks_passed = COALESCE(health_checks_passed, 0),
+    health_checks_failed = COALESCE(health_checks_failed, 0);
+ALTER TABLE contract_deployments
+    ALTER COLUMN health_checks_passed SET NOT NULL,
+    ALTER COLUMN health_checks_failed SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_contract_deployments_watched
+    ON contract_deployments(status, monitor_until)
+    WHERE status IN ('testing', 'active');
+
+CREATE TABLE IF NOT EXISTS deployment_health_checks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    deployment_id UUID NOT NULL REFERENCES contract_deployments(id) ON DELETE CASCADE,
+    wasm_hash VARCHAR(64) NOT NULL,
+    passed BOOLEAN NOT NULL,
+    source VARCHAR(20) NOT NULL CHECK (source IN ('probe', 'reported')),
+    detail TEXT,
+    latency_ms INTEGER,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_deployment_health_checks_deployment
+    ON deployment_health_checks(deployment_id, checked_at DESC);
+
+ALTER TABLE deployment_switches
+    ALTER COLUMN from_environment DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS from_deployment_id UUID
+        REFERENCES contract_deployments(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS to_deployment_id UUID
+        REFERENCES contract_deployments(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS from_wasm_hash VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS to_wasm_hash VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS reason TEXT;
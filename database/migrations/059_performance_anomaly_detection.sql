@@ -0,0 +1,72 @@
+-- Migration: 059_performance_anomaly_detection.sql
+-- Anomaly detection and threshold alerts move from row triggers to the API's
+-- performance monitor
+--
+--   • The 020 triggers ran on every inserted sample and could not complete:
+--     format() has no `%.2f` specifier, so any sample that tripped them made
+--     the insert fail. Both are dropped.
+--   • performance_anomalies: which detector fired and its score. At most one
+--     open anomaly per (contract, metric, function) series; it is updated
+--     while the series stays abnormal and resolved once it recovers.
+--   • performance_alerts: the config that raised the alert. At most one open
+--     alert per config.
+--   • performance_trends: hourly and daily roll-ups, keyed per series.
+
+DROP TRIGGER IF EXISTS performance_anomaly_detection ON performance_metrics;
+DROP TRIGGER IF EXISTS performance_threshold_check ON performance_metrics;
+DROP FUNCTION IF EXISTS detect_performance_anomaly();
+DROP FUNCTION IF EXISTS check_performance_thresholds();
+
+CREATE INDEX IF NOT EXISTS idx_performance_metrics_series
+    ON performance_metrics(contract_id, metric_type, function_name, timestamp DESC);
+
+ALTER TABLE performance_anomalies
+    ALTER COLUMN deviation_percent TYPE DECIMAL(10,2),
+    ADD COLUMN IF NOT EXISTS detector VARCHAR(20) NOT NULL DEFAULT 'zscore'
+        CHECK (detector IN ('zscore', 'ewma', 'seasonal')),
+    ADD COLUMN IF NOT EXISTS score DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS last_evaluated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- Keep the newest open anomaly per series before enforcing uniqueness.
+UPDATE performance_anomalies a
+SET resolved = TRUE, resolved_at = NOW()
+WHERE NOT a.resolved
+  AND EXISTS (
+      SELECT 1 FROM performance_anomalies b
+      WHERE NOT b.resolved
+        AND b.contract_id = a.contract_id
+        AND b.metric_type = a.metric_type
+        AND COALESCE(b.function_name, '') = COALESCE(a.function_name, '')
+        AND (b.detected_at, b.id) > (a.detected_at, a.id)
+  );
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_performance_anomalies_open_series
+    ON performance_anomalies(contract_id, metric_type, (COALESCE(function_name, '')))
+    WHERE NOT resolved;
+
+ALTER TABLE performance_alerts
+    ADD COLUMN IF NOT EXISTS alert_config_id UUID
+        REFERENCES performance_alert_configs(id) ON DELETE SET NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_performance_alerts_open_config
+    ON performance_alerts(alert_config_id)
+    WHERE NOT resolved;
+
+ALTER TABLE performance_trends
+    ALTER COLUMN change_percent TYPE DECIMAL(10,2),
+    ADD COLUMN IF NOT EXISTS granularity VARCHAR(10) NOT NULL DEFAULT 'hour'
+        CHECK (granularity IN ('hour', 'day'));
+
+DELETE FROM performance_trends a
+USING performance_trends b
+WHERE a.contract_id = b.contract_id
+  AND a.metric_type = b.metric_type
+  AND COALESCE(a.function_name, '') = COALESCE(b.function_name, '')
+  AND a.granularity = b.granularity
+  AND a.timeframe_start = b.timeframe_start
+  AND (a.calculated_at, a.id) < (b.calculated_at, b.id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_performance_trends_bucket
+    ON performance_trends(
+        contract_id, metric_type, (COALESCE(function_name, '')), granularity, timeframe_start
+    );
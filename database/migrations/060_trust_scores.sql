@@ -0,0 +1,58 @@
+-- Migration: 060_trust_scores.sql
+-- Trust score inputs and history
+--
+--   • security_audits / audit_checks: the audit checklist records that the
+--     audit handlers and the trust score read. No earlier migration created
+--     them. Each check row keeps the checklist severity it had when the audit
+--     ran, so unresolved critical findings can be counted in SQL.
+--   • contract_trust_scores: one row per computed score, with the factor
+--     breakdown and raw inputs, for trend charts.
+
+CREATE TABLE IF NOT EXISTS security_audits (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_id UUID NOT NULL REFERENCES contracts(id) ON DELETE CASCADE,
+    contract_source TEXT,
+    auditor VARCHAR(255) NOT NULL,
+    audit_date TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    overall_score DOUBLE PRECISION NOT NULL DEFAULT 0,
+    summary TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_security_audits_contract_date
+    ON security_audits(contract_id, audit_date DESC);
+
+CREATE TABLE IF NOT EXISTS audit_checks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    audit_id UUID NOT NULL REFERENCES security_audits(id) ON DELETE CASCADE,
+    check_id VARCHAR(100) NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('passed', 'failed', 'not_applicable', 'pending')),
+    notes TEXT,
+    auto_detected BOOLEAN NOT NULL DEFAULT FALSE,
+    evidence TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(audit_id, check_id)
+);
+
+ALTER TABLE audit_checks
+    ADD COLUMN IF NOT EXISTS severity VARCHAR(20)
+        CHECK (severity IN ('info', 'low', 'medium', 'high', 'critical'));
+
+CREATE INDEX IF NOT EXISTS idx_audit_checks_failed
+    ON audit_checks(audit_id, severity)
+    WHERE status = 'failed';
+
+CREATE TABLE IF NOT EXISTS contract_trust_scores (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_id UUID NOT NULL REFERENCES contracts(id) ON DELETE CASCADE,
+    score NUMERIC(5,2) NOT NULL CHECK (score >= 0 AND score <= 100),
+    badge VARCHAR(20) NOT NULL,
+    factors JSONB NOT NULL,
+    inputs JSONB NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_contract_trust_scores_contract_computed
+    ON contract_trust_scores(contract_id, computed_at DESC);
@@ -0,0 +1,59 @@
+-- Migration: 061_transparency_merkle_log.sql
+-- Merkle tree and signed tree heads over the signing transparency log
+--
+--   • transparency_log: entries are sequenced by the API's log sequencer,
+--     which gives each one a dense leaf_index in (timestamp, id) order and
+--     stores the canonical leaf JSON and its RFC 6962 leaf hash. Once set,
+--     those columns cannot change or be removed.
+--   • transparency_tree_heads: Ed25519-signed roots over the first tree_size
+--     leaves, published after each sequencing pass that added entries.
+
+ALTER TABLE transparency_log
+    ADD COLUMN IF NOT EXISTS leaf_index BIGINT,
+    ADD COLUMN IF NOT EXISTS leaf_data TEXT,
+    ADD COLUMN IF NOT EXISTS leaf_hash VARCHAR(64),
+    ADD CONSTRAINT transparency_log_leaf_complete CHECK (
+        (leaf_index IS NULL AND leaf_data IS NULL AND leaf_hash IS NULL)
+        OR (leaf_index IS NOT NULL AND leaf_data IS NOT NULL AND leaf_hash IS NOT NULL)
+    );
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transparency_log_leaf_index
+    ON transparency_log(leaf_index);
+
+CREATE INDEX IF NOT EXISTS idx_transparency_log_unsequenced
+    ON transparency_log(timestamp, id)
+    WHERE leaf_index IS NULL;
+
+CREATE OR REPLACE FUNCTION protect_transparency_leaf()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        IF OLD.leaf_index IS NOT NULL THEN
+            RAISE EXCEPTION 'transparency log entry % is sequenced and cannot be deleted', OLD.id;
+        END IF;
+        RETURN OLD;
+    END IF;
+    IF OLD.leaf_index IS NOT NULL AND (
+        NEW.leaf_index IS DISTINCT FROM OLD.leaf_index
+        OR NEW.leaf_data IS DISTINCT FROM OLD.leaf_data
+        OR NEW.leaf_hash IS DISTINCT FROM OLD.leaf_hash
+    ) THEN
+        RAISE EXCEPTION 'transparency log entry % is sequenced and cannot be rewritten', OLD.id;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS transparency_log_protect_leaf ON transparency_log;
+CREATE TRIGGER transparency_log_protect_leaf
+    BEFORE UPDATE OR DELETE ON transparency_log
+    FOR EACH ROW EXECUTE FUNCTION protect_transparency_leaf();
+
+CREATE TABLE IF NOT EXISTS transparency_tree_heads (
+    tree_size   BIGINT PRIMARY KEY CHECK (tree_size > 0),
+    root_hash   VARCHAR(64) NOT NULL,
+    signature   TEXT NOT NULL,
+    key_id      VARCHAR(64) NOT NULL,
+    timestamp   TIMESTAMPTZ NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,65 @@
+-- Migration: 062_signed_audit_chain.sql
+-- Signed, explicitly ordered hash chain over contract_audit_log
+--
+--   • seq: global append order. Chains are walked by seq rather than
+--     timestamp, which is equal for every row written in one transaction.
+--     Existing rows are numbered in (timestamp, id) order.
+--   • hash_version: 1 for rows written before signing (hash covers previous
+--     hash, contract, action, actor, new value), 2 for signed rows (hash also
+--     covers old value and timestamp).
+--   • signing_key_id: hex SHA-256 of the Ed25519 key that signed the row, so
+--     chains stay verifiable across key rotation.
+--   • audit_chain_settings.legacy_seq_cutoff: the highest seq backfilled
+--     here. Only these rows may be unsigned v1; verification rejects v1 rows
+--     numbered above it.
+--   • audit_action_type gains 'governance_executed' and 'multisig_executed'.
+
+CREATE SEQUENCE IF NOT EXISTS contract_audit_log_seq_seq;
+
+ALTER TABLE contract_audit_log
+    ADD COLUMN IF NOT EXISTS seq BIGINT,
+    ADD COLUMN IF NOT EXISTS hash_version SMALLINT NOT NULL DEFAULT 1,
+    ADD COLUMN IF NOT EXISTS signing_key_id VARCHAR(64);
+
+-- Backfilling seq is the only rewrite this table will ever see.
+ALTER TABLE contract_audit_log DISABLE TRIGGER prevent_audit_log_modification;
+
+UPDATE contract_audit_log l
+SET seq = ordered.n
+FROM (
+    SELECT id, ROW_NUMBER() OVER (ORDER BY timestamp, id) AS n
+    FROM contract_audit_log
+) ordered
+WHERE l.id = ordered.id AND l.seq IS NULL;
+
+ALTER TABLE contract_audit_log ENABLE TRIGGER prevent_audit_log_modification;
+
+SELECT setval(
+    'contract_audit_log_seq_seq',
+    GREATEST((SELECT MAX(seq) FROM contract_audit_log), 0) + 1,
+    false
+);
+
+CREATE TABLE IF NOT EXISTS audit_chain_settings (
+    singleton BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (singleton),
+    legacy_seq_cutoff BIGINT NOT NULL
+);
+
+INSERT INTO audit_chain_settings (legacy_seq_cutoff)
+SELECT COALESCE(MAX(seq), 0) FROM contract_audit_log
+ON CONFLICT (singleton) DO NOTHING;
+
+ALTER TABLE contract_audit_log
+    ALTER COLUMN seq SET DEFAULT nextval('contract_audit_log_seq_seq'),
+    ALTER COLUMN seq SET NOT NULL;
+
+ALTER SEQUENCE contract_audit_log_seq_seq OWNED BY contract_audit_log.seq;
+
+-- New rows are always written with the current scheme.
+ALTER TABLE contract_audit_log ALTER COLUMN hash_version SET DEFAULT 2;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_contract_audit_log_contract_seq
+    ON contract_audit_log(contract_id, seq);
+
+ALTER TYPE audit_action_type ADD VALUE IF NOT EXISTS 'governance_executed';
+ALTER TYPE audit_action_type ADD VALUE IF NOT EXISTS 'multisig_executed';
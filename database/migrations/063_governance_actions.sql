@@ -0,0 +1,47 @@
+-- Migration: 063_governance_actions.sql
+-- Executable governance proposals
+--
+--   • governance_proposals.action: the typed registry change a proposal makes
+--     when executed (deprecate, change publisher, update metadata, set
+--     maturity, approve a version, change a multisig policy).
+--     execution_result / execution_error record the last execution attempt.
+--   • governance_members: a contract's electorate and each member's weight.
+--     Contracts without members are governed by their publisher alone.
+--   • governance_proposal_voters: the electorate snapshotted when a proposal
+--     is created, so membership changes cannot move a running vote.
+--   • vote_delegations: at most one active delegation per delegator and
+--     scope, instead of one per (delegator, scope, active) which blocked a
+--     second revocation.
+--   • contract_versions.approved_at / approved_by_proposal.
+
+ALTER TABLE governance_proposals
+    ADD COLUMN IF NOT EXISTS action JSONB,
+    ADD COLUMN IF NOT EXISTS execution_result JSONB,
+    ADD COLUMN IF NOT EXISTS execution_error TEXT;
+
+CREATE TABLE IF NOT EXISTS governance_members (
+    contract_id  UUID NOT NULL REFERENCES contracts(id) ON DELETE CASCADE,
+    publisher_id UUID NOT NULL REFERENCES publishers(id) ON DELETE CASCADE,
+    weight       BIGINT NOT NULL CHECK (weight > 0),
+    added_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (contract_id, publisher_id)
+);
+
+CREATE TABLE IF NOT EXISTS governance_proposal_voters (
+    proposal_id UUID NOT NULL REFERENCES governance_proposals(id) ON DELETE CASCADE,
+    voter       UUID NOT NULL REFERENCES publishers(id),
+    weight      BIGINT NOT NULL CHECK (weight > 0),
+    PRIMARY KEY (proposal_id, voter)
+);
+
+ALTER TABLE vote_delegations
+    DROP CONSTRAINT IF EXISTS vote_delegations_delegator_contract_id_active_key,
+    ADD CONSTRAINT vote_delegations_not_self CHECK (delegator <> delegate);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_vote_delegations_active
+    ON vote_delegations(delegator, COALESCE(contract_id, '00000000-0000-0000-0000-000000000000'::uuid))
+    WHERE active;
+
+ALTER TABLE contract_versions
+    ADD COLUMN IF NOT EXISTS approved_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS approved_by_proposal UUID REFERENCES governance_proposals(id);
@@ -0,0 +1,46 @@
+-- Migration: 064_multisig_envelopes.sql
+-- Multisig deploy proposals backed by Stellar transaction envelopes
+--
+--   • multisig_policies.signer_weights: weight of each signer, parallel to
+--     signer_addresses (NULL = weight 1 each). threshold is now the total
+--     weight required, so it may exceed the number of signers.
+--   • deploy_proposals.operation / transaction_xdr: the unsigned
+--     TransactionEnvelope (base64 XDR) that uploads, creates or upgrades the
+--     contract. transaction_hash is its network-specific hash, the message
+--     every signer signs and the on-chain transaction id.
+--   • deploy_proposals.signed_envelope_xdr: the envelope with collected
+--     signatures, assembled on execution; submitted_at / submission_error
+--     record the last submission through SOROBAN_RPC_URL.
+--   • proposal_signatures.signature_data now holds a base64 DecoratedSignature
+--     over transaction_hash; weight is the signer's weight when it signed.
+
+ALTER TABLE multisig_policies
+    ADD COLUMN IF NOT EXISTS signer_weights INT[];
+
+ALTER TABLE multisig_policies
+    DROP CONSTRAINT IF EXISTS multisig_policies_signer_addresses_check,
+    ADD CONSTRAINT multisig_policies_signers_check CHECK (
+        array_length(signer_addresses, 1) >= 1
+        AND (signer_weights IS NULL
+             OR array_length(signer_weights, 1) = array_length(signer_addresses, 1))
+    );
+
+DO $$ BEGIN
+    CREATE TYPE deploy_operation AS ENUM ('upload', 'create', 'upgrade');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+ALTER TABLE deploy_proposals
+    ADD COLUMN IF NOT EXISTS operation deploy_operation,
+    ADD COLUMN IF NOT EXISTS transaction_xdr TEXT,
+    ADD COLUMN IF NOT EXISTS transaction_hash VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS signed_envelope_xdr TEXT,
+    ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS submission_error TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_deploy_proposals_transaction_hash
+    ON deploy_proposals(transaction_hash);
+
+ALTER TABLE proposal_signatures
+    ADD COLUMN IF NOT EXISTS weight INT NOT NULL DEFAULT 1 CHECK (weight >= 1);
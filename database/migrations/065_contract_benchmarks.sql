@@ -0,0 +1,63 @@
+-- Migration: 065_contract_benchmarks.sql
+-- Contract benchmarks run as background jobs
+--
+--   • benchmark_records: one requested benchmark of a contract method. Rows
+--     start 'queued' and are run by a `benchmark` job (see job_id); timing
+--     statistics are filled in when the run completes.
+--   • benchmark_runs: per-iteration timings of a benchmark.
+--   • benchmark_alerts: p95 regressions against the previous completed
+--     benchmark of the same method. Kept apart from performance_alerts, which
+--     are raised by the metric monitor against alert configs.
+
+CREATE TABLE IF NOT EXISTS benchmark_records (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_id UUID NOT NULL REFERENCES contracts(id) ON DELETE CASCADE,
+    contract_version VARCHAR(50) NOT NULL,
+    method_name VARCHAR(64) NOT NULL,
+    iterations INTEGER NOT NULL CHECK (iterations BETWEEN 1 AND 1000),
+    args_json JSONB,
+    alert_threshold_pct DOUBLE PRECISION NOT NULL DEFAULT 10.0,
+    status VARCHAR(20) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'completed', 'failed')),
+    min_ms DOUBLE PRECISION,
+    max_ms DOUBLE PRECISION,
+    avg_ms DOUBLE PRECISION,
+    p95_ms DOUBLE PRECISION,
+    p99_ms DOUBLE PRECISION,
+    stddev_ms DOUBLE PRECISION,
+    error_message TEXT,
+    job_id UUID REFERENCES jobs(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_benchmark_records_contract_method
+    ON benchmark_records(contract_id, method_name, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS benchmark_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    benchmark_id UUID NOT NULL REFERENCES benchmark_records(id) ON DELETE CASCADE,
+    iteration INTEGER NOT NULL,
+    execution_time_ms DOUBLE PRECISION NOT NULL,
+    cpu_instructions BIGINT,
+    memory_bytes BIGINT,
+    UNIQUE(benchmark_id, iteration)
+);
+
+CREATE TABLE IF NOT EXISTS benchmark_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_id UUID NOT NULL REFERENCES contracts(id) ON DELETE CASCADE,
+    method_name VARCHAR(64) NOT NULL,
+    baseline_benchmark_id UUID NOT NULL REFERENCES benchmark_records(id) ON DELETE CASCADE,
+    current_benchmark_id UUID NOT NULL UNIQUE REFERENCES benchmark_records(id) ON DELETE CASCADE,
+    baseline_p95_ms DOUBLE PRECISION NOT NULL,
+    current_p95_ms DOUBLE PRECISION NOT NULL,
+    regression_pct DOUBLE PRECISION NOT NULL,
+    alert_threshold_pct DOUBLE PRECISION NOT NULL,
+    resolved BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_benchmark_alerts_open
+    ON benchmark_alerts(contract_id, created_at DESC) WHERE NOT resolved;
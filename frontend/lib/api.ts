@@ -126,6 +126,10 @@ export interface ContractVersion {
   commit_hash?: string;
   release_notes?: string;
   created_at: string;
+  /** SHA-256 of the stored WASM, downloadable from `/versions/:v/wasm` */
+  wasm_artifact_sha256?: string;
+  /** SHA-256 of the stored source, downloadable from `/versions/:v/source.tar.gz` */
+  source_artifact_sha256?: string;
 }
 
 export interface Publisher {
@@ -908,8 +912,8 @@ export const api = {
     );
   },
 
-  async runCompatibilityTest(id: string, data: RunCompatibilityTestRequest): Promise<CompatibilityTestEntry> {
-    return handleApiCall<CompatibilityTestEntry>(
+  async runCompatibilityTest(id: string, data: RunCompatibilityTestRequest): Promise<CompatibilityTestRun[]> {
+    return handleApiCall<CompatibilityTestRun[]>(
       () => fetch(`${API_URL}/api/contracts/${id}/compatibility-matrix/test`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
@@ -919,6 +923,21 @@ export const api = {
     );
   },
 
+  // Background jobs (verification, scans, compatibility runs)
+  async getJob(jobId: string): Promise<Job> {
+    return handleApiCall<Job>(
+      () => fetch(`${API_URL}/api/jobs/${jobId}`),
+      `/api/jobs/${jobId}`
+    );
+  },
+
+  async cancelJob(jobId: string): Promise<Job> {
+    return handleApiCall<Job>(
+      () => fetch(`${API_URL}/api/jobs/${jobId}/cancel`, { method: 'POST' }),
+      `/api/jobs/${jobId}/cancel`
+    );
+  },
+
   async getCompatibilityHistory(id: string, limit?: number, offset?: number): Promise<CompatibilityHistoryResponse> {
     const params = new URLSearchParams();
     if (limit != null) params.set('limit', String(limit));
@@ -1189,12 +1208,58 @@ export interface CompatibilityTestMatrixResponse {
 }
 
 export interface RunCompatibilityTestRequest {
+  sdk_version?: string;
+  sdk_versions?: string[];
+  wasm_runtime: string;
+  network: string;
+}
+
+export interface CompatibilityFixtureResult {
+  name: string;
+  passed: boolean;
+  message?: string;
+}
+
+export interface CompatibilityTestRun {
+  id: string;
+  contract_id: string;
   sdk_version: string;
   wasm_runtime: string;
   network: string;
+  status: 'queued' | 'running' | 'completed' | 'failed';
+  result?: CompatibilityTestStatus;
+  fixtures_total?: number;
+  fixtures_passed?: number;
+  fixture_results?: CompatibilityFixtureResult[];
+  duration_ms?: number;
+  output?: string;
+  error_message?: string;
+  created_at: string;
+  started_at?: string;
+  finished_at?: string;
+  job_id?: string;
 }
 
-export interface CompatibilityHistoryEntry {
+export type JobStatus = 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled';
+
+export interface Job {
+  id: string;
+  kind: 'verify_contract' | 'scan_contract' | 'compatibility_run' | 'benchmark';
+  status: JobStatus;
+  priority: number;
+  attempts: number;
+  max_attempts: number;
+  run_at: string;
+  cancel_requested: boolean;
+  progress: number;
+  progress_message?: string;
+  result?: unknown;
+  error?: string;
+  contract_id?: string;
+  created_at: string;
+  started_at?: string;
+  finished_at?: string;
+}
   id: string;
   contract_id: string;
   sdk_version: string;
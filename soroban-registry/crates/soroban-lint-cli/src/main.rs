@@ -2,10 +2,12 @@ use anyhow::Result;
 use clap::{Parser, Subcommand};
 use colored::*;
 use serde_json::json;
+use soroban_lint_core::audit::{rule_for_check, Checklist};
 use soroban_lint_core::{Analyzer, AutoFixer, Diagnostic, LintConfig, Severity};
 use soroban_load_balancer::{
     BalancingAlgorithm, LoadBalancer, LoadBalancerConfig, Region,
 };
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 use std::time::Instant;
@@ -53,6 +55,22 @@ enum Commands {
         ignore: Option<String>,
     },
 
+    /// Run the security audit checklist rules (the same engine the registry
+    /// uses for automatic audit checks)
+    Audit {
+        /// Path to contract or directory
+        #[arg(default_value = ".")]
+        path: String,
+
+        /// Output format
+        #[arg(long, default_value = "human")]
+        format: String,
+
+        /// Path to config file (for ignored paths)
+        #[arg(long)]
+        config: Option<String>,
+    },
+
     /// List all available rules
     Rules {
         /// Output format
@@ -172,6 +190,13 @@ fn main() -> Result<()> {
         } => {
             lint_command(path, level, format, fix, config, rules, ignore)?;
         }
+        Commands::Audit {
+            path,
+            format,
+            config,
+        } => {
+            audit_command(path, format, config)?;
+        }
         Commands::Rules { format } => {
             rules_command(format)?;
         }
@@ -319,6 +344,90 @@ fn lint_command(
     }
 }
 
+fn audit_command(path: String, format: String, config_path: Option<String>) -> Result<()> {
+    let config = LintConfig::load(config_path.as_deref())?;
+    let checklist = Checklist::new();
+
+    let path_obj = PathBuf::from(&path);
+    let files: Vec<String> = if path_obj.is_file() {
+        vec![path.clone()]
+    } else {
+        WalkDir::new(&path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
+            .map(|e| e.path().to_string_lossy().to_string())
+            .filter(|file| !config.should_ignore(file))
+            .collect()
+    };
+
+    let mut results: BTreeMap<String, Vec<Diagnostic>> = checklist
+        .check_ids()
+        .into_iter()
+        .map(|check| (check, Vec::new()))
+        .collect();
+    for file in &files {
+        let content = fs::read_to_string(file)?;
+        for (check, diagnostics) in checklist.run(file, &content)? {
+            results.entry(check).or_default().extend(diagnostics);
+        }
+    }
+
+    let failed = results.values().filter(|d| !d.is_empty()).count();
+
+    if format == "json" {
+        let checks: Vec<_> = results
+            .iter()
+            .map(|(check, diagnostics)| {
+                json!({
+                    "check_id": check,
+                    "rule_id": rule_for_check(check),
+                    "status": if diagnostics.is_empty() { "passed" } else { "failed" },
+                    "findings": diagnostics
+                })
+            })
+            .collect();
+        let output = json!({
+            "summary": {
+                "files": files.len(),
+                "passed": results.len() - failed,
+                "failed": failed
+            },
+            "checks": checks
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        for (check, diagnostics) in &results {
+            let rule = rule_for_check(check).unwrap_or("expected_patterns");
+            if diagnostics.is_empty() {
+                println!("{} {} {}", "✓".green(), check, rule.bright_black());
+                continue;
+            }
+            println!("{} {} {}", "✗".red().bold(), check.bold(), rule);
+            for diag in diagnostics {
+                println!("    {} {}", diag.span, diag.message);
+            }
+        }
+        println!();
+        let summary = format!(
+            "{} of {} automated checks passed across {} file(s)",
+            results.len() - failed,
+            results.len(),
+            files.len()
+        );
+        if failed > 0 {
+            println!("{}", summary.red().bold());
+        } else {
+            println!("{}", summary.green().bold());
+        }
+    }
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 fn rules_command(format: String) -> Result<()> {
     let analyzer = Analyzer::new();
     let rules = analyzer.list_rules();
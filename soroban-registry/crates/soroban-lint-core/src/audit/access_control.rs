@@ -0,0 +1,269 @@
+use super::{calls_any, functions, idents, method_calls, without_tests, Findings, Function};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::rules::LintRule;
+use quote::ToTokens;
+
+/// AC-001: a callable function touching an admin/owner/operator role
+/// without calling `require_auth`.
+pub struct PrivilegedFnWithoutAuthRule;
+
+/// AC-002: a callable function that moves funds via `transfer` or
+/// `transfer_from` without calling `require_auth`.
+pub struct TransferWithoutAuthRule;
+
+/// AC-007: `initialize`/`init` without a guard against being called twice.
+pub struct ReinitializationRule;
+
+/// AC-008: an upgrade entry point without `require_auth`.
+pub struct UnguardedUpgradeRule;
+
+/// AA-001: the contract never calls `require_auth` at all.
+pub struct NoRequireAuthRule;
+
+const AUTH_CALLS: &[&str] = &["require_auth", "require_auth_for_args"];
+const PRIVILEGED_ROLES: &[&str] = &["admin", "owner", "operator"];
+
+fn requires_auth(function: &Function<'_>) -> bool {
+    calls_any(function.block, AUTH_CALLS)
+}
+
+fn mentions_role(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    PRIVILEGED_ROLES.iter().any(|role| name.contains(role))
+}
+
+/// Whether the function is named after, or takes, a privileged role.
+fn is_privileged(function: &Function<'_>) -> bool {
+    if mentions_role(&function.sig.ident.to_string()) {
+        return true;
+    }
+    function.sig.inputs.iter().any(|input| match input {
+        syn::FnArg::Typed(arg) => match &*arg.pat {
+            syn::Pat::Ident(pat) => mentions_role(&pat.ident.to_string()),
+            _ => false,
+        },
+        syn::FnArg::Receiver(_) => false,
+    })
+}
+
+impl LintRule for PrivilegedFnWithoutAuthRule {
+    fn rule_id(&self) -> &'static str {
+        "privileged_fn_without_auth"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for function in functions(&syntax) {
+            let name = function.sig.ident.to_string();
+            // Read-only accessors such as `get_admin` need no authorization.
+            if !function.public || name.starts_with("get") || name.starts_with("is_") {
+                continue;
+            }
+            if is_privileged(&function) && !requires_auth(&function) {
+                findings.push(
+                    function.sig.ident.span(),
+                    format!(
+                        "Privileged function `{}` does not call require_auth()",
+                        name
+                    ),
+                    "Load the stored admin and call admin.require_auth() before acting",
+                );
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+impl LintRule for TransferWithoutAuthRule {
+    fn rule_id(&self) -> &'static str {
+        "transfer_without_auth"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for function in functions(&syntax) {
+            if !function.public || requires_auth(&function) {
+                continue;
+            }
+            let transfer = method_calls(function.block)
+                .into_iter()
+                .find(|call| call.method == "transfer" || call.method == "transfer_from");
+            if let Some(call) = transfer {
+                findings.push(
+                    call.method.span(),
+                    format!(
+                        "`{}` moves funds without require_auth() in `{}`",
+                        call.method, function.sig.ident
+                    ),
+                    "Call from.require_auth() before transferring",
+                );
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+impl LintRule for ReinitializationRule {
+    fn rule_id(&self) -> &'static str {
+        "reinitialization"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for function in functions(&syntax) {
+            let name = function.sig.ident.to_string();
+            if name != "initialize" && name != "init" {
+                continue;
+            }
+            // A storage `has` check or an "initialized" flag/error, including
+            // inside require!/assert! style macros.
+            let guarded = calls_any(function.block, &["has"])
+                || idents(function.block.to_token_stream())
+                    .iter()
+                    .any(|ident| {
+                        let ident = ident.to_ascii_lowercase();
+                        ident.contains("initialized")
+                    });
+            if !guarded {
+                findings.push(
+                    function.sig.ident.span(),
+                    format!("`{}` can be called more than once", name),
+                    "Check an Initialized flag (or the admin key) and fail with AlreadyInitialized",
+                );
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+impl LintRule for UnguardedUpgradeRule {
+    fn rule_id(&self) -> &'static str {
+        "unguarded_upgrade"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for function in functions(&syntax) {
+            let upgrades = function.sig.ident.to_string().starts_with("upgrade")
+                || calls_any(function.block, &["update_current_contract_wasm"]);
+            if upgrades && function.public && !requires_auth(&function) {
+                findings.push(
+                    function.sig.ident.span(),
+                    format!(
+                        "`{}` replaces the contract code without require_auth()",
+                        function.sig.ident
+                    ),
+                    "Require the stored admin's authorization before upgrading",
+                );
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+impl LintRule for NoRequireAuthRule {
+    fn rule_id(&self) -> &'static str {
+        "no_require_auth"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let functions = functions(&syntax);
+        if functions.iter().any(requires_auth) {
+            return Vec::new();
+        }
+        let mut findings = Findings::new(self, file);
+        let location = functions
+            .iter()
+            .find(|function| function.public)
+            .map(|function| function.sig.ident.span())
+            .unwrap_or_else(proc_macro2::Span::call_site);
+        findings.push(
+            location,
+            "No require_auth() call found anywhere in the contract".to_string(),
+            "Authorize the acting address with address.require_auth()",
+        );
+        findings.diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rule: &dyn LintRule, source: &str) -> Vec<Diagnostic> {
+        rule.check("lib.rs", &syn::parse_file(source).unwrap())
+    }
+
+    #[test]
+    fn auth_is_checked_per_function() {
+        let source = r#"
+            impl Token {
+                pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+                    from.require_auth();
+                    client.transfer(&from, &to, &amount);
+                }
+                pub fn sweep(env: Env, to: Address) {
+                    client.transfer(&env.current_contract_address(), &to, &1);
+                }
+            }
+        "#;
+        let found = check(&TransferWithoutAuthRule, source);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("sweep"));
+        assert!(check(&NoRequireAuthRule, source).is_empty());
+    }
+
+    #[test]
+    fn admin_setter_needs_auth_but_getter_does_not() {
+        let source = r#"
+            impl C {
+                pub fn get_admin(env: Env) -> Address { read_admin(&env) }
+                pub fn set_admin(env: Env, new_admin: Address) { write_admin(&env, &new_admin); }
+            }
+        "#;
+        let found = check(&PrivilegedFnWithoutAuthRule, source);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("set_admin"));
+    }
+
+    #[test]
+    fn initialize_guard_inside_macro_counts() {
+        let guarded = r#"
+            pub fn initialize(env: Env, admin: Address) {
+                require!(!is_initialized(&env), Error::AlreadyInitialized);
+            }
+        "#;
+        let unguarded = r#"
+            pub fn initialize(env: Env, admin: Address) {
+                env.storage().instance().set(&DataKey::Admin, &admin);
+            }
+        "#;
+        assert!(check(&ReinitializationRule, guarded).is_empty());
+        assert_eq!(check(&ReinitializationRule, unguarded).len(), 1);
+    }
+}
@@ -0,0 +1,408 @@
+use super::{functions, idents, method_calls, normalize, storage_access, without_tests, Findings};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::rules::LintRule;
+use quote::ToTokens;
+use syn::visit::Visit;
+
+/// EH-002: `let _ = call(..);` silently drops a result.
+pub struct DiscardedResultRule;
+
+/// SM-003: contract state written after a cross-contract call in the same
+/// function (checks-effects-interactions).
+pub struct StateWriteAfterCallRule;
+
+/// TS-001: the `Result` of `try_transfer`/`try_transfer_from` is dropped.
+pub struct IgnoredTokenTransferResultRule;
+
+/// EL-001: a fund-moving entry point that publishes no event.
+pub struct TransferWithoutEventRule;
+
+/// RL-001: a `for` loop over a collection with no size bound in sight.
+pub struct UnboundedIterationRule;
+
+fn is_call(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Call(_) | syn::Expr::MethodCall(_) | syn::Expr::Await(_) => true,
+        syn::Expr::Try(expr_try) => is_call(&expr_try.expr),
+        _ => false,
+    }
+}
+
+struct DiscardVisitor {
+    findings: Findings,
+}
+
+impl<'ast> Visit<'ast> for DiscardVisitor {
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        if let (syn::Pat::Wild(wild), Some(init)) = (&node.pat, &node.init) {
+            if is_call(&init.expr) {
+                self.findings.push(
+                    wild.underscore_token.span,
+                    "Result of a call is silently discarded with `let _ =`".to_string(),
+                    "Propagate the error with ? or handle it explicitly",
+                );
+            }
+        }
+        syn::visit::visit_local(self, node);
+    }
+}
+
+impl LintRule for DiscardedResultRule {
+    fn rule_id(&self) -> &'static str {
+        "discarded_result"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = DiscardVisitor {
+            findings: Findings::new(self, file),
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+fn ends_with_client(name: &str) -> bool {
+    name.to_ascii_lowercase().ends_with("client")
+}
+
+/// Whether a method call goes through a contract client, e.g.
+/// `token_client.transfer(..)` or `TokenClient::new(&env, &id).balance(..)`.
+fn is_external_call(call: &syn::ExprMethodCall) -> bool {
+    if call.method == "invoke_contract" || call.method == "try_invoke_contract" {
+        return true;
+    }
+    let mut receiver = &*call.receiver;
+    while let syn::Expr::Reference(reference) = receiver {
+        receiver = &reference.expr;
+    }
+    match receiver {
+        syn::Expr::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| ends_with_client(&segment.ident.to_string())),
+        syn::Expr::Field(field) => match &field.member {
+            syn::Member::Named(ident) => ends_with_client(&ident.to_string()),
+            syn::Member::Unnamed(_) => false,
+        },
+        syn::Expr::Call(ctor) => match &*ctor.func {
+            syn::Expr::Path(path) => {
+                let segments = &path.path.segments;
+                segments.len() >= 2
+                    && ends_with_client(&segments[segments.len() - 2].ident.to_string())
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+impl LintRule for StateWriteAfterCallRule {
+    fn rule_id(&self) -> &'static str {
+        "state_write_after_call"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for function in functions(&syntax) {
+            let mut external: Option<&syn::ExprMethodCall> = None;
+            for call in method_calls(function.block) {
+                if external.is_none() && is_external_call(call) {
+                    external = Some(call);
+                    continue;
+                }
+                let Some(previous) = external else {
+                    continue;
+                };
+                let writes = storage_access(call).is_some_and(|(_, method)| {
+                    matches!(method.as_str(), "set" | "remove" | "update")
+                });
+                if writes {
+                    let at = previous.method.span().start();
+                    findings.push(
+                        call.method.span(),
+                        format!(
+                            "State is written after the external call `{}` at line {}",
+                            previous.method, at.line
+                        ),
+                        "Commit state changes before calling other contracts",
+                    );
+                    break;
+                }
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+fn is_try_transfer(expr: &syn::Expr) -> Option<&syn::ExprMethodCall> {
+    match expr {
+        syn::Expr::MethodCall(call)
+            if call.method == "try_transfer" || call.method == "try_transfer_from" =>
+        {
+            Some(call)
+        }
+        _ => None,
+    }
+}
+
+struct TransferResultVisitor {
+    findings: Findings,
+}
+
+impl TransferResultVisitor {
+    fn flag(&mut self, call: &syn::ExprMethodCall) {
+        self.findings.push(
+            call.method.span(),
+            format!("Result of `{}` is ignored", call.method),
+            "Match on the result or use transfer(), which fails the invocation on error",
+        );
+    }
+}
+
+impl<'ast> Visit<'ast> for TransferResultVisitor {
+    fn visit_stmt(&mut self, node: &'ast syn::Stmt) {
+        match node {
+            syn::Stmt::Expr(expr, Some(_)) => {
+                if let Some(call) = is_try_transfer(expr) {
+                    self.flag(call);
+                }
+            }
+            syn::Stmt::Local(local) if matches!(local.pat, syn::Pat::Wild(_)) => {
+                if let Some(call) = local
+                    .init
+                    .as_ref()
+                    .and_then(|init| is_try_transfer(&init.expr))
+                {
+                    self.flag(call);
+                }
+            }
+            _ => {}
+        }
+        syn::visit::visit_stmt(self, node);
+    }
+}
+
+impl LintRule for IgnoredTokenTransferResultRule {
+    fn rule_id(&self) -> &'static str {
+        "ignored_token_transfer_result"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = TransferResultVisitor {
+            findings: Findings::new(self, file),
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+const FUND_MOVING: &[&str] = &[
+    "transfer",
+    "transfer_from",
+    "withdraw",
+    "deposit",
+    "mint",
+    "burn",
+    "burn_from",
+];
+
+impl LintRule for TransferWithoutEventRule {
+    fn rule_id(&self) -> &'static str {
+        "transfer_without_event"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for function in functions(&syntax) {
+            if !function.public {
+                continue;
+            }
+            let name = function.sig.ident.to_string();
+            let moves_funds = FUND_MOVING.contains(&name.as_str())
+                || method_calls(function.block)
+                    .iter()
+                    .any(|call| call.method == "transfer" || call.method == "transfer_from");
+            if !moves_funds {
+                continue;
+            }
+            let emits = idents(function.block.to_token_stream())
+                .iter()
+                .any(|ident| ident == "publish" || ident.starts_with("emit"));
+            if !emits {
+                findings.push(
+                    function.sig.ident.span(),
+                    format!("`{}` moves funds without publishing an event", name),
+                    "Publish an event with env.events().publish(..)",
+                );
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+/// Whether the iterated expression is a range or explicitly capped with
+/// `.take(..)`.
+fn is_bounded_iterable(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Range(_) => true,
+        syn::Expr::Paren(paren) => is_bounded_iterable(&paren.expr),
+        syn::Expr::MethodCall(call) => call.method == "take" || is_bounded_iterable(&call.receiver),
+        _ => false,
+    }
+}
+
+struct LoopVisitor<'a> {
+    loops: Vec<&'a syn::ExprForLoop>,
+}
+
+impl<'a> Visit<'a> for LoopVisitor<'a> {
+    fn visit_expr_for_loop(&mut self, node: &'a syn::ExprForLoop) {
+        if !is_bounded_iterable(&node.expr) {
+            self.loops.push(node);
+        }
+        syn::visit::visit_expr_for_loop(self, node);
+    }
+}
+
+impl LintRule for UnboundedIterationRule {
+    fn rule_id(&self) -> &'static str {
+        "unbounded_iteration"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for function in functions(&syntax) {
+            let mut visitor = LoopVisitor { loops: Vec::new() };
+            visitor.visit_block(function.block);
+            if visitor.loops.is_empty() {
+                continue;
+            }
+
+            // A length check or a MAX_* limit anywhere in the function.
+            let body = normalize(&function.block.to_token_stream());
+            let bounded = [".len()<", ".len()<=", ".len()>", ".len()>="]
+                .iter()
+                .any(|check| body.contains(check))
+                || idents(function.block.to_token_stream())
+                    .iter()
+                    .any(|ident| ident.starts_with("MAX") || ident.starts_with("max_"));
+            if bounded {
+                continue;
+            }
+            for for_loop in visitor.loops {
+                findings.push(
+                    for_loop.for_token.span,
+                    format!(
+                        "Loop over `{}` has no size limit",
+                        normalize(&for_loop.expr.to_token_stream())
+                    ),
+                    "Cap the collection length (e.g. require!(items.len() <= MAX_ITEMS)) before iterating",
+                );
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rule: &dyn LintRule, source: &str) -> Vec<Diagnostic> {
+        rule.check("lib.rs", &syn::parse_file(source).unwrap())
+    }
+
+    #[test]
+    fn state_written_after_client_call() {
+        let source = r#"
+            pub fn withdraw(env: Env, to: Address, amount: i128) {
+                let token_client = TokenClient::new(&env, &token);
+                token_client.transfer(&env.current_contract_address(), &to, &amount);
+                env.storage().persistent().set(&DataKey::Balance(to), &0);
+            }
+            pub fn deposit(env: Env, from: Address, amount: i128) {
+                env.storage().persistent().set(&DataKey::Balance(from.clone()), &amount);
+                TokenClient::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
+            }
+        "#;
+        let found = check(&StateWriteAfterCallRule, source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].span.line, 5);
+    }
+
+    #[test]
+    fn discarded_results_only_for_calls() {
+        let source = r#"
+            pub fn f(env: Env, unused: u32) {
+                let _ = unused;
+                let _ = do_something(&env);
+            }
+        "#;
+        let found = check(&DiscardedResultRule, source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].span.line, 4);
+    }
+
+    #[test]
+    fn ignored_try_transfer() {
+        let source = r#"
+            pub fn pay(env: Env, client: TokenClient, to: Address) -> Result<(), Error> {
+                client.try_transfer(&env.current_contract_address(), &to, &1);
+                match client.try_transfer(&env.current_contract_address(), &to, &1) {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(Error::TransferFailed),
+                }
+            }
+        "#;
+        assert_eq!(check(&IgnoredTokenTransferResultRule, source).len(), 1);
+    }
+
+    #[test]
+    fn events_and_loops() {
+        let source = r#"
+            impl Vault {
+                pub fn withdraw(env: Env, to: Address) {
+                    env.events().publish((symbol_short!("withdraw"),), to);
+                }
+                pub fn deposit(env: Env, from: Address) {}
+                pub fn pay_all(env: Env, users: Vec<Address>) {
+                    for user in users.iter() { pay(&env, &user); }
+                }
+                pub fn pay_some(env: Env, users: Vec<Address>) {
+                    require!(users.len() <= MAX_USERS, Error::TooMany);
+                    for user in users.iter() { pay(&env, &user); }
+                }
+            }
+        "#;
+        let events = check(&TransferWithoutEventRule, source);
+        assert_eq!(events.len(), 1);
+        assert!(events[0].message.contains("deposit"));
+        let loops = check(&UnboundedIterationRule, source);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].span.line, 8);
+    }
+}
@@ -0,0 +1,198 @@
+use super::{without_tests, Findings};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::rules::LintRule;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// IV-001: `.unwrap()` outside test code.
+pub struct UnwrapCallRule;
+
+/// IV-002: `.expect(..)` outside test code.
+pub struct ExpectCallRule;
+
+/// IV-006: `panic!`, `unreachable!`, `todo!` and `unimplemented!` in
+/// contract code.
+pub struct PanicMacroRule;
+
+/// IV-009: indexing with `[..]` by anything but a range, which panics when
+/// out of bounds.
+pub struct UncheckedIndexRule;
+
+struct MethodVisitor {
+    method: &'static str,
+    findings: Findings,
+    suggestion: &'static str,
+}
+
+impl<'ast> Visit<'ast> for MethodVisitor {
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        if node.method == self.method {
+            self.findings.push(
+                node.method.span(),
+                format!("`.{}()` can panic and abort the contract", self.method),
+                self.suggestion,
+            );
+        }
+        syn::visit::visit_expr_method_call(self, node);
+    }
+}
+
+impl LintRule for UnwrapCallRule {
+    fn rule_id(&self) -> &'static str {
+        "unwrap_call"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = MethodVisitor {
+            method: "unwrap",
+            findings: Findings::new(self, file),
+            suggestion: "Handle the None/Err case, e.g. `.ok_or(ContractError::InvalidInput)?`",
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+impl LintRule for ExpectCallRule {
+    fn rule_id(&self) -> &'static str {
+        "expect_call"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = MethodVisitor {
+            method: "expect",
+            findings: Findings::new(self, file),
+            suggestion: "Return a typed ContractError instead",
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+const PANIC_MACROS: &[&str] = &["panic", "unreachable", "todo", "unimplemented"];
+
+struct PanicVisitor {
+    findings: Findings,
+}
+
+impl<'ast> Visit<'ast> for PanicVisitor {
+    fn visit_macro(&mut self, node: &'ast syn::Macro) {
+        if let Some(name) = node.path.segments.last().map(|s| s.ident.to_string()) {
+            if PANIC_MACROS.contains(&name.as_str()) {
+                self.findings.push(
+                    node.path.span(),
+                    format!("`{}!` aborts the contract", name),
+                    "Use panic_with_error! or return Err(ContractError::..) instead",
+                );
+            }
+        }
+        syn::visit::visit_macro(self, node);
+    }
+}
+
+impl LintRule for PanicMacroRule {
+    fn rule_id(&self) -> &'static str {
+        "panic_macro"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = PanicVisitor {
+            findings: Findings::new(self, file),
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+struct IndexVisitor {
+    findings: Findings,
+}
+
+impl<'ast> Visit<'ast> for IndexVisitor {
+    fn visit_expr_index(&mut self, node: &'ast syn::ExprIndex) {
+        if !matches!(*node.index, syn::Expr::Range(_)) {
+            self.findings.push(
+                node.bracket_token.span.open(),
+                "Indexing panics when the index is out of bounds".to_string(),
+                "Use .get(i) and handle None, or check the length first",
+            );
+        }
+        syn::visit::visit_expr_index(self, node);
+    }
+}
+
+impl LintRule for UncheckedIndexRule {
+    fn rule_id(&self) -> &'static str {
+        "unchecked_index"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = IndexVisitor {
+            findings: Findings::new(self, file),
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rule: &dyn LintRule, source: &str) -> Vec<Diagnostic> {
+        rule.check("lib.rs", &syn::parse_file(source).unwrap())
+    }
+
+    #[test]
+    fn unwrap_in_comments_and_strings_is_ignored() {
+        let source = r#"
+            // never call .unwrap() here
+            pub fn f(x: Option<u32>) -> &'static str {
+                let _ = x.unwrap_or(0);
+                "x.unwrap()"
+            }
+        "#;
+        assert!(check(&UnwrapCallRule, source).is_empty());
+    }
+
+    #[test]
+    fn array_types_are_not_indexing() {
+        let source = r#"
+            pub fn f(v: Vec<u32>, i: usize) -> [u8; 32] {
+                let _head = &v[..2];
+                let _x = v[i];
+                [0u8; 32]
+            }
+        "#;
+        let found = check(&UncheckedIndexRule, source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].span.line, 4);
+    }
+
+    #[test]
+    fn flags_every_panicking_macro() {
+        let source = r#"
+            pub fn f(x: u32) {
+                if x == 0 { panic!("zero"); }
+                if x == 1 { todo!() }
+            }
+        "#;
+        assert_eq!(check(&PanicMacroRule, source).len(), 2);
+    }
+}
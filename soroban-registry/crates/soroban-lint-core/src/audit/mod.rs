@@ -0,0 +1,435 @@
+//! Security audit checklist rules
+//!
+//! The registry's security audit checklist identifies checks by ID
+//! (`IV-001`, `AC-002`, ...). Checks that can be decided from the source are
+//! implemented here as [`LintRule`]s and [`CHECK_RULES`] maps each check to
+//! its rule. [`Checklist`] runs them and groups the diagnostics per check; a
+//! check passes when its rule reports nothing.
+//!
+//! Checks without a dedicated rule can still be registered with
+//! [`Checklist::with_expected_patterns`], which passes when one of the
+//! checklist's patterns appears in non-test code.
+//!
+//! Test code (`#[test]` functions and `#[cfg(test)]` modules) is removed
+//! before any rule runs.
+
+pub mod access_control;
+pub mod calls;
+pub mod input_validation;
+pub mod numeric;
+pub mod patterns;
+pub mod storage;
+
+use crate::analyzer::Analyzer;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::rules::LintRule;
+use anyhow::Result;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{BTreeMap, HashSet};
+use syn::visit::Visit;
+
+pub use patterns::ExpectedPatternsRule;
+
+/// Checklist ID → rule ID for every check with a dedicated rule.
+pub const CHECK_RULES: &[(&str, &str)] = &[
+    ("IV-001", "unwrap_call"),
+    ("IV-002", "expect_call"),
+    ("IV-006", "panic_macro"),
+    ("IV-009", "unchecked_index"),
+    ("AC-001", "privileged_fn_without_auth"),
+    ("AC-002", "transfer_without_auth"),
+    ("AC-007", "reinitialization"),
+    ("AC-008", "unguarded_upgrade"),
+    ("AA-001", "no_require_auth"),
+    ("NS-001", "unchecked_arithmetic_op"),
+    ("NS-002", "unguarded_division"),
+    ("NS-005", "truncating_cast"),
+    ("EH-002", "discarded_result"),
+    ("EH-004", "unwrapped_storage_read"),
+    ("SM-001", "persistent_ttl_not_extended"),
+    ("SM-002", "instance_ttl_not_extended"),
+    ("SM-003", "state_write_after_call"),
+    ("TS-001", "ignored_token_transfer_result"),
+    ("EL-001", "transfer_without_event"),
+    ("DS-001", "missing_contracttype"),
+    ("SP-001", "missing_datakey_enum"),
+    ("SP-005", "raw_storage_key"),
+    ("RL-001", "unbounded_iteration"),
+];
+
+/// All checklist rules, in [`CHECK_RULES`] order.
+pub fn checklist_rules() -> Vec<Box<dyn LintRule>> {
+    vec![
+        Box::new(input_validation::UnwrapCallRule),
+        Box::new(input_validation::ExpectCallRule),
+        Box::new(input_validation::PanicMacroRule),
+        Box::new(input_validation::UncheckedIndexRule),
+        Box::new(access_control::PrivilegedFnWithoutAuthRule),
+        Box::new(access_control::TransferWithoutAuthRule),
+        Box::new(access_control::ReinitializationRule),
+        Box::new(access_control::UnguardedUpgradeRule),
+        Box::new(access_control::NoRequireAuthRule),
+        Box::new(numeric::UncheckedArithmeticOpRule),
+        Box::new(numeric::UnguardedDivisionRule),
+        Box::new(numeric::TruncatingCastRule),
+        Box::new(calls::DiscardedResultRule),
+        Box::new(storage::UnwrappedStorageReadRule),
+        Box::new(storage::PersistentTtlRule),
+        Box::new(storage::InstanceTtlRule),
+        Box::new(calls::StateWriteAfterCallRule),
+        Box::new(calls::IgnoredTokenTransferResultRule),
+        Box::new(calls::TransferWithoutEventRule),
+        Box::new(storage::MissingContractTypeRule),
+        Box::new(storage::MissingDataKeyEnumRule),
+        Box::new(storage::RawStorageKeyRule),
+        Box::new(calls::UnboundedIterationRule),
+    ]
+}
+
+/// The rule implementing a checklist item, if it has one.
+pub fn rule_for_check(check_id: &str) -> Option<&'static str> {
+    CHECK_RULES
+        .iter()
+        .find(|(check, _)| *check == check_id)
+        .map(|(_, rule)| *rule)
+}
+
+/// The checklist item a rule implements.
+pub fn check_for_rule(rule_id: &str) -> Option<&'static str> {
+    CHECK_RULES
+        .iter()
+        .find(|(_, rule)| *rule == rule_id)
+        .map(|(check, _)| *check)
+}
+
+/// Runs the checklist rules over a source file.
+pub struct Checklist {
+    analyzer: Analyzer,
+    pattern_checks: Vec<(String, ExpectedPatternsRule)>,
+}
+
+impl Checklist {
+    pub fn new() -> Self {
+        Self {
+            analyzer: Analyzer::with_rules(checklist_rules()),
+            pattern_checks: Vec::new(),
+        }
+    }
+
+    /// Evaluate `check_id` by the presence of one of `patterns`. Ignored for
+    /// checks that have a dedicated rule.
+    pub fn with_expected_patterns(
+        mut self,
+        check_id: impl Into<String>,
+        patterns: Vec<String>,
+    ) -> Self {
+        let check_id = check_id.into();
+        if rule_for_check(&check_id).is_none() && !patterns.is_empty() {
+            self.pattern_checks
+                .push((check_id, ExpectedPatternsRule::new(patterns)));
+        }
+        self
+    }
+
+    /// IDs of every check this checklist evaluates.
+    pub fn check_ids(&self) -> Vec<String> {
+        CHECK_RULES
+            .iter()
+            .map(|(check, _)| check.to_string())
+            .chain(self.pattern_checks.iter().map(|(check, _)| check.clone()))
+            .collect()
+    }
+
+    /// Diagnostics per check ID. Every check is present; an empty list
+    /// means it passed.
+    pub fn run(&self, file: &str, content: &str) -> Result<BTreeMap<String, Vec<Diagnostic>>> {
+        let mut results: BTreeMap<String, Vec<Diagnostic>> = self
+            .check_ids()
+            .into_iter()
+            .map(|check| (check, Vec::new()))
+            .collect();
+
+        let mut diagnostics = self.analyzer.analyze_file(file, content)?;
+        Analyzer::sort_diagnostics(&mut diagnostics);
+        for diagnostic in diagnostics {
+            if let Some(check) = check_for_rule(&diagnostic.rule_id) {
+                if let Some(found) = results.get_mut(check) {
+                    found.push(diagnostic);
+                }
+            }
+        }
+
+        if !self.pattern_checks.is_empty() {
+            let syntax = syn::parse_file(content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", file, e))?;
+            for (check, rule) in &self.pattern_checks {
+                if let Some(found) = results.get_mut(check) {
+                    found.extend(rule.check(file, &syntax));
+                }
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+impl Default for Checklist {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ── Shared helpers ───────────────────────────────────────────────────────────
+
+/// Collects diagnostics for one rule.
+pub(crate) struct Findings {
+    rule_id: &'static str,
+    severity: Severity,
+    file: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Findings {
+    pub fn new(rule: &dyn LintRule, file: &str) -> Self {
+        Self {
+            rule_id: rule.rule_id(),
+            severity: rule.default_severity(),
+            file: file.to_string(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, span: proc_macro2::Span, message: String, suggestion: &str) {
+        self.diagnostics.push(
+            Diagnostic::at(self.rule_id, self.severity, message, &self.file, span)
+                .with_suggestion(suggestion),
+        );
+    }
+}
+
+fn is_test_attr(attr: &syn::Attribute) -> bool {
+    let path = attr.path();
+    if path.is_ident("test") || path.segments.last().is_some_and(|s| s.ident == "test") {
+        return true;
+    }
+    if !path.is_ident("cfg") {
+        return false;
+    }
+    let cfg = normalize(&attr.meta.to_token_stream());
+    cfg.contains("test") && !cfg.contains("not(")
+}
+
+fn is_test(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(is_test_attr)
+}
+
+/// A copy of `file` without test functions and test modules.
+pub(crate) fn without_tests(file: &syn::File) -> syn::File {
+    let mut file = file.clone();
+    strip_items(&mut file.items);
+    file
+}
+
+fn strip_items(items: &mut Vec<syn::Item>) {
+    items.retain(|item| match item {
+        syn::Item::Fn(f) => !is_test(&f.attrs),
+        syn::Item::Mod(m) => !is_test(&m.attrs),
+        syn::Item::Impl(i) => !is_test(&i.attrs),
+        _ => true,
+    });
+    for item in items.iter_mut() {
+        match item {
+            syn::Item::Mod(m) => {
+                if let Some((_, items)) = &mut m.content {
+                    strip_items(items);
+                }
+            }
+            syn::Item::Impl(i) => i.items.retain(|item| match item {
+                syn::ImplItem::Fn(f) => !is_test(&f.attrs),
+                _ => true,
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// A function or method body, with whether it is part of the contract's
+/// callable surface (`pub`, or a trait impl method).
+pub(crate) struct Function<'a> {
+    pub sig: &'a syn::Signature,
+    pub block: &'a syn::Block,
+    pub public: bool,
+}
+
+pub(crate) fn functions(file: &syn::File) -> Vec<Function<'_>> {
+    struct Collector<'a> {
+        functions: Vec<Function<'a>>,
+        in_trait_impl: bool,
+    }
+    impl<'a> Visit<'a> for Collector<'a> {
+        fn visit_item_fn(&mut self, node: &'a syn::ItemFn) {
+            self.functions.push(Function {
+                sig: &node.sig,
+                block: &node.block,
+                public: matches!(node.vis, syn::Visibility::Public(_)),
+            });
+            syn::visit::visit_item_fn(self, node);
+        }
+
+        fn visit_item_impl(&mut self, node: &'a syn::ItemImpl) {
+            let previous = self.in_trait_impl;
+            self.in_trait_impl = node.trait_.is_some();
+            syn::visit::visit_item_impl(self, node);
+            self.in_trait_impl = previous;
+        }
+
+        fn visit_impl_item_fn(&mut self, node: &'a syn::ImplItemFn) {
+            self.functions.push(Function {
+                sig: &node.sig,
+                block: &node.block,
+                public: self.in_trait_impl || matches!(node.vis, syn::Visibility::Public(_)),
+            });
+            syn::visit::visit_impl_item_fn(self, node);
+        }
+    }
+
+    let mut collector = Collector {
+        functions: Vec::new(),
+        in_trait_impl: false,
+    };
+    collector.visit_file(file);
+    collector.functions
+}
+
+/// Method calls in `block`, in source order.
+pub(crate) fn method_calls(block: &syn::Block) -> Vec<&syn::ExprMethodCall> {
+    struct Collector<'a> {
+        calls: Vec<&'a syn::ExprMethodCall>,
+    }
+    impl<'a> Visit<'a> for Collector<'a> {
+        fn visit_expr_method_call(&mut self, node: &'a syn::ExprMethodCall) {
+            // The receiver runs first.
+            self.visit_expr(&node.receiver);
+            self.calls.push(node);
+            for arg in &node.args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+
+    let mut collector = Collector { calls: Vec::new() };
+    collector.visit_block(block);
+    collector.calls
+}
+
+/// Whether `block` calls any of `methods`.
+pub(crate) fn calls_any(block: &syn::Block, methods: &[&str]) -> bool {
+    method_calls(block)
+        .iter()
+        .any(|call| methods.iter().any(|m| call.method == m))
+}
+
+/// Names of the methods called along a receiver chain, innermost first:
+/// `env.storage().persistent().set(..)` gives `storage, persistent, set`.
+pub(crate) fn method_chain(call: &syn::ExprMethodCall) -> Vec<String> {
+    let mut chain = vec![call.method.to_string()];
+    let mut receiver = &*call.receiver;
+    while let syn::Expr::MethodCall(inner) = receiver {
+        chain.push(inner.method.to_string());
+        receiver = &inner.receiver;
+    }
+    chain.reverse();
+    chain
+}
+
+/// `env.storage().<tier>().<method>(..)` — the storage tier and method.
+pub(crate) fn storage_access(call: &syn::ExprMethodCall) -> Option<(String, String)> {
+    let chain = method_chain(call);
+    let position = chain.iter().position(|m| m == "storage")?;
+    let tier = chain.get(position + 1)?.clone();
+    let method = chain.get(position + 2)?.clone();
+    (position + 3 == chain.len()).then_some((tier, method))
+}
+
+/// Every identifier in `tokens`, including those inside macro invocations.
+pub(crate) fn idents(tokens: TokenStream) -> HashSet<String> {
+    let mut found = HashSet::new();
+    collect_idents(tokens, &mut found);
+    found
+}
+
+fn collect_idents(tokens: TokenStream, found: &mut HashSet<String>) {
+    for tree in tokens {
+        match tree {
+            TokenTree::Ident(ident) => {
+                found.insert(ident.to_string());
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), found),
+            _ => {}
+        }
+    }
+}
+
+/// Token text with all whitespace removed, so that `a . b ()` and `a.b()`
+/// compare equal.
+pub(crate) fn normalize(tokens: &TokenStream) -> String {
+    tokens
+        .to_string()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_check_has_its_rule() {
+        let rules = checklist_rules();
+        assert_eq!(rules.len(), CHECK_RULES.len());
+        for (rule, (_, rule_id)) in rules.iter().zip(CHECK_RULES) {
+            assert_eq!(rule.rule_id(), *rule_id);
+        }
+    }
+
+    #[test]
+    fn test_code_is_ignored() {
+        let source = r#"
+            pub fn ok(x: Option<u32>) -> Option<u32> { x }
+
+            #[cfg(test)]
+            mod tests {
+                #[test]
+                fn it_works() { Some(1).unwrap(); }
+            }
+        "#;
+        let results = Checklist::new().run("lib.rs", source).unwrap();
+        assert!(results["IV-001"].is_empty());
+    }
+
+    #[test]
+    fn diagnostics_carry_spans() {
+        let source = "pub fn f(x: Option<u32>) -> u32 {\n    x.unwrap()\n}\n";
+        let results = Checklist::new().run("lib.rs", source).unwrap();
+        let found = &results["IV-001"];
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].span.line, found[0].span.column), (2, 7));
+    }
+
+    #[test]
+    fn pattern_checks_only_cover_checks_without_rules() {
+        let checklist = Checklist::new()
+            .with_expected_patterns("IV-001", vec![".unwrap()".into()])
+            .with_expected_patterns("AA-002", vec!["nonce".into()]);
+        let ids = checklist.check_ids();
+        assert_eq!(ids.iter().filter(|id| *id == "IV-001").count(), 1);
+        assert!(ids.contains(&"AA-002".to_string()));
+
+        let results = checklist
+            .run("lib.rs", "pub fn f(nonce: u64) -> u64 { nonce }")
+            .unwrap();
+        assert!(results["AA-002"].is_empty());
+    }
+}
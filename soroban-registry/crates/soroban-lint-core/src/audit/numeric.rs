@@ -0,0 +1,247 @@
+use super::{functions, normalize, without_tests, Findings};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::rules::LintRule;
+use quote::ToTokens;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// NS-001: `+`, `-` and `*` (and their assigning forms) on non-constant
+/// operands, which wrap or trap on overflow.
+pub struct UncheckedArithmeticOpRule;
+
+/// NS-002: `/` or `%` by a value the function never compares against zero.
+pub struct UnguardedDivisionRule;
+
+/// NS-005: `as` casts to a narrower integer type.
+pub struct TruncatingCastRule;
+
+fn is_literal(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Lit(_) => true,
+        syn::Expr::Paren(paren) => is_literal(&paren.expr),
+        syn::Expr::Unary(unary) => is_literal(&unary.expr),
+        _ => false,
+    }
+}
+
+struct ArithmeticVisitor {
+    findings: Findings,
+}
+
+impl<'ast> Visit<'ast> for ArithmeticVisitor {
+    fn visit_expr_binary(&mut self, node: &'ast syn::ExprBinary) {
+        let op = match node.op {
+            syn::BinOp::Add(_) | syn::BinOp::AddAssign(_) => Some("addition"),
+            syn::BinOp::Sub(_) | syn::BinOp::SubAssign(_) => Some("subtraction"),
+            syn::BinOp::Mul(_) | syn::BinOp::MulAssign(_) => Some("multiplication"),
+            _ => None,
+        };
+        if let Some(op) = op {
+            if !(is_literal(&node.left) && is_literal(&node.right)) {
+                self.findings.push(
+                    node.op.span(),
+                    format!("Unchecked {} can overflow", op),
+                    "Use checked_add/checked_sub/checked_mul and handle None",
+                );
+            }
+        }
+        syn::visit::visit_expr_binary(self, node);
+    }
+
+    // Constant expressions are evaluated by the compiler.
+    fn visit_item_const(&mut self, _node: &'ast syn::ItemConst) {}
+    fn visit_item_static(&mut self, _node: &'ast syn::ItemStatic) {}
+    fn visit_impl_item_const(&mut self, _node: &'ast syn::ImplItemConst) {}
+    fn visit_type(&mut self, _node: &'ast syn::Type) {}
+}
+
+impl LintRule for UncheckedArithmeticOpRule {
+    fn rule_id(&self) -> &'static str {
+        "unchecked_arithmetic_op"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = ArithmeticVisitor {
+            findings: Findings::new(self, file),
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+struct DivisionVisitor<'a> {
+    divisions: Vec<&'a syn::ExprBinary>,
+}
+
+impl<'a> Visit<'a> for DivisionVisitor<'a> {
+    fn visit_expr_binary(&mut self, node: &'a syn::ExprBinary) {
+        if matches!(
+            node.op,
+            syn::BinOp::Div(_)
+                | syn::BinOp::Rem(_)
+                | syn::BinOp::DivAssign(_)
+                | syn::BinOp::RemAssign(_)
+        ) {
+            self.divisions.push(node);
+        }
+        syn::visit::visit_expr_binary(self, node);
+    }
+}
+
+fn is_nonzero_literal(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Lit(lit) => match &lit.lit {
+            syn::Lit::Int(int) => int.base10_digits().chars().any(|c| c != '0'),
+            syn::Lit::Float(_) => true,
+            _ => false,
+        },
+        syn::Expr::Paren(paren) => is_nonzero_literal(&paren.expr),
+        _ => false,
+    }
+}
+
+impl LintRule for UnguardedDivisionRule {
+    fn rule_id(&self) -> &'static str {
+        "unguarded_division"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for function in functions(&syntax) {
+            let mut visitor = DivisionVisitor {
+                divisions: Vec::new(),
+            };
+            visitor.visit_block(function.block);
+            if visitor.divisions.is_empty() {
+                continue;
+            }
+
+            let body = normalize(&function.block.to_token_stream());
+            for division in visitor.divisions {
+                if is_nonzero_literal(&division.right) {
+                    continue;
+                }
+                let divisor = normalize(&division.right.to_token_stream());
+                let guarded = [
+                    format!("{}!=0", divisor),
+                    format!("{}>0", divisor),
+                    format!("{}==0", divisor),
+                    format!("{}<=0", divisor),
+                    format!("0<{}", divisor),
+                ]
+                .iter()
+                .any(|guard| body.contains(guard.as_str()));
+                if !guarded {
+                    findings.push(
+                        division.op.span(),
+                        format!("Division by `{}` is not guarded against zero", divisor),
+                        "Check the denominator is non-zero first, or use checked_div",
+                    );
+                }
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+const NARROW_TYPES: &[&str] = &[
+    "i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "isize", "usize",
+];
+
+struct CastVisitor {
+    findings: Findings,
+}
+
+impl<'ast> Visit<'ast> for CastVisitor {
+    fn visit_expr_cast(&mut self, node: &'ast syn::ExprCast) {
+        if let syn::Type::Path(path) = &*node.ty {
+            if let Some(ty) = path.path.get_ident().map(|ident| ident.to_string()) {
+                if NARROW_TYPES.contains(&ty.as_str()) && !is_literal(&node.expr) {
+                    self.findings.push(
+                        node.as_token.span,
+                        format!("`as {}` silently truncates out-of-range values", ty),
+                        "Use try_into() and handle the conversion error",
+                    );
+                }
+            }
+        }
+        syn::visit::visit_expr_cast(self, node);
+    }
+}
+
+impl LintRule for TruncatingCastRule {
+    fn rule_id(&self) -> &'static str {
+        "truncating_cast"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = CastVisitor {
+            findings: Findings::new(self, file),
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rule: &dyn LintRule, source: &str) -> Vec<Diagnostic> {
+        rule.check("lib.rs", &syn::parse_file(source).unwrap())
+    }
+
+    #[test]
+    fn constant_arithmetic_is_not_flagged() {
+        let source = r#"
+            const DAY: u64 = 24 * 60 * 60;
+            pub fn f(a: i128, b: i128) -> i128 {
+                let _week = 7 * 24;
+                a + b
+            }
+        "#;
+        let found = check(&UncheckedArithmeticOpRule, source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].span.line, 5);
+    }
+
+    #[test]
+    fn guarded_division_passes() {
+        let source = r#"
+            pub fn share(total: i128, parts: i128) -> i128 {
+                require!(parts > 0, Error::ZeroParts);
+                total / parts
+            }
+            pub fn half(total: i128) -> i128 { total / 2 }
+            pub fn ratio(a: i128, b: i128) -> i128 { a / b }
+        "#;
+        let found = check(&UnguardedDivisionRule, source);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("`b`"));
+    }
+
+    #[test]
+    fn widening_casts_are_fine() {
+        let source = r#"
+            pub fn f(x: u32, y: i128) -> (i128, u32) {
+                (x as i128, y as u32)
+            }
+        "#;
+        let found = check(&TruncatingCastRule, source);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("as u32"));
+    }
+}
@@ -0,0 +1,116 @@
+use super::without_tests;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::rules::LintRule;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+
+/// Passes when any of the expected patterns occurs in the file's code.
+///
+/// Matching runs on the token text rather than the raw source, so comments,
+/// doc comments and test code never satisfy a pattern. Whitespace is ignored
+/// on both sides.
+pub struct ExpectedPatternsRule {
+    patterns: Vec<String>,
+}
+
+impl ExpectedPatternsRule {
+    pub fn new(patterns: Vec<String>) -> Self {
+        Self { patterns }
+    }
+}
+
+impl LintRule for ExpectedPatternsRule {
+    fn rule_id(&self) -> &'static str {
+        "expected_patterns"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let code = code_text(without_tests(syntax).to_token_stream());
+        let found = self.patterns.iter().any(|pattern| {
+            let pattern: String = pattern.chars().filter(|c| !c.is_whitespace()).collect();
+            !pattern.is_empty() && code.contains(&pattern)
+        });
+        if found {
+            return Vec::new();
+        }
+
+        vec![Diagnostic::new(
+            self.rule_id(),
+            self.default_severity(),
+            format!(
+                "None of the expected patterns found: {}",
+                self.patterns.join(", ")
+            ),
+            file,
+            1,
+            0,
+        )]
+    }
+}
+
+/// Token text without whitespace or `#[doc = ".."]` attributes.
+fn code_text(tokens: TokenStream) -> String {
+    let mut text = String::new();
+    push_tokens(tokens, &mut text);
+    text
+}
+
+fn push_tokens(tokens: TokenStream, text: &mut String) {
+    let mut trees = tokens.into_iter().peekable();
+    while let Some(tree) = trees.next() {
+        match tree {
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                if let Some(TokenTree::Group(group)) = trees.peek() {
+                    let is_doc = matches!(
+                        group.stream().into_iter().next(),
+                        Some(TokenTree::Ident(ident)) if ident == "doc"
+                    );
+                    if is_doc {
+                        trees.next();
+                        continue;
+                    }
+                }
+                text.push('#');
+            }
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    proc_macro2::Delimiter::Parenthesis => ("(", ")"),
+                    proc_macro2::Delimiter::Brace => ("{", "}"),
+                    proc_macro2::Delimiter::Bracket => ("[", "]"),
+                    proc_macro2::Delimiter::None => ("", ""),
+                };
+                text.push_str(open);
+                push_tokens(group.stream(), text);
+                text.push_str(close);
+            }
+            other => text.push_str(&other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(patterns: &[&str], source: &str) -> Vec<Diagnostic> {
+        let rule = ExpectedPatternsRule::new(patterns.iter().map(|p| p.to_string()).collect());
+        rule.check("lib.rs", &syn::parse_file(source).unwrap())
+    }
+
+    #[test]
+    fn matches_code_but_not_comments() {
+        let source = r#"
+            /// Checks the nonce
+            pub fn f(env: Env) {
+                // nonce is checked elsewhere
+                env.storage().temporary();
+            }
+        "#;
+        assert!(check(&["storage().temporary()"], source).is_empty());
+        assert_eq!(check(&["nonce"], source).len(), 1);
+    }
+}
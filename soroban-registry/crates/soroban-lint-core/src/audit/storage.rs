@@ -0,0 +1,364 @@
+use super::{storage_access, without_tests, Findings};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::rules::LintRule;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// EH-004: a storage read whose missing-key case is `.unwrap()`ed or
+/// `.expect()`ed away.
+pub struct UnwrappedStorageReadRule;
+
+/// SM-001: persistent storage is written but no persistent entry's TTL is
+/// ever extended.
+pub struct PersistentTtlRule;
+
+/// SM-002: instance storage is used but the instance TTL is never extended.
+pub struct InstanceTtlRule;
+
+/// DS-001: a struct or enum without `#[contracttype]` (or another contract
+/// attribute such as `#[contracterror]`).
+pub struct MissingContractTypeRule;
+
+/// SP-001: storage is used without a `#[contracttype]` key enum.
+pub struct MissingDataKeyEnumRule;
+
+/// SP-005: a raw string or `Symbol` used as a storage key.
+pub struct RawStorageKeyRule;
+
+const STORAGE_KEYED: &[&str] = &["get", "set", "has", "remove", "update", "extend_ttl"];
+
+/// Every `env.storage().<tier>().<method>(..)` call in the file.
+fn storage_calls(file: &syn::File) -> Vec<(&syn::ExprMethodCall, String, String)> {
+    struct Collector<'a> {
+        calls: Vec<(&'a syn::ExprMethodCall, String, String)>,
+    }
+    impl<'a> Visit<'a> for Collector<'a> {
+        fn visit_expr_method_call(&mut self, node: &'a syn::ExprMethodCall) {
+            if let Some((tier, method)) = storage_access(node) {
+                self.calls.push((node, tier, method));
+            }
+            syn::visit::visit_expr_method_call(self, node);
+        }
+    }
+
+    let mut collector = Collector { calls: Vec::new() };
+    collector.visit_file(file);
+    collector.calls
+}
+
+struct UnwrappedReadVisitor {
+    findings: Findings,
+}
+
+impl<'ast> Visit<'ast> for UnwrappedReadVisitor {
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        if node.method == "unwrap" || node.method == "expect" {
+            if let syn::Expr::MethodCall(read) = &*node.receiver {
+                if storage_access(read).is_some_and(|(_, method)| method == "get") {
+                    self.findings.push(
+                        node.method.span(),
+                        format!("Missing storage key is handled with `.{}()`", node.method),
+                        "Use .ok_or(ContractError::NotFound)? or .unwrap_or(default)",
+                    );
+                }
+            }
+        }
+        syn::visit::visit_expr_method_call(self, node);
+    }
+}
+
+impl LintRule for UnwrappedStorageReadRule {
+    fn rule_id(&self) -> &'static str {
+        "unwrapped_storage_read"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = UnwrappedReadVisitor {
+            findings: Findings::new(self, file),
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+fn ttl_check(rule: &dyn LintRule, file: &str, syntax: &syn::File, tier: &str) -> Vec<Diagnostic> {
+    let syntax = without_tests(syntax);
+    let calls = storage_calls(&syntax);
+    let extended = calls
+        .iter()
+        .any(|(_, t, method)| t == tier && method == "extend_ttl");
+    let mut findings = Findings::new(rule, file);
+    if extended {
+        return findings.diagnostics;
+    }
+    if let Some((call, _, _)) = calls
+        .iter()
+        .find(|(_, t, method)| t == tier && method == "set")
+    {
+        findings.push(
+            call.method.span(),
+            format!("{} storage is written but its TTL is never extended", tier),
+            "Call extend_ttl on the entry (or instance) when it is written or read",
+        );
+    }
+    findings.diagnostics
+}
+
+impl LintRule for PersistentTtlRule {
+    fn rule_id(&self) -> &'static str {
+        "persistent_ttl_not_extended"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        ttl_check(self, file, syntax, "persistent")
+    }
+}
+
+impl LintRule for InstanceTtlRule {
+    fn rule_id(&self) -> &'static str {
+        "instance_ttl_not_extended"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        ttl_check(self, file, syntax, "instance")
+    }
+}
+
+const CONTRACT_ATTRS: &[&str] = &[
+    "contracttype",
+    "contracterror",
+    "contractevent",
+    "contract",
+    "contractclient",
+];
+
+fn contract_attr(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| {
+        let name = attr.path().segments.last()?.ident.to_string();
+        CONTRACT_ATTRS.contains(&name.as_str()).then_some(name)
+    })
+}
+
+struct TypeVisitor {
+    findings: Findings,
+}
+
+impl TypeVisitor {
+    fn check_type(&mut self, attrs: &[syn::Attribute], ident: &syn::Ident) {
+        if contract_attr(attrs).is_none() {
+            self.findings.push(
+                ident.span(),
+                format!("`{}` is not a #[contracttype]", ident),
+                "Add #[contracttype] so the type has a stable XDR representation",
+            );
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for TypeVisitor {
+    fn visit_item_struct(&mut self, node: &'ast syn::ItemStruct) {
+        self.check_type(&node.attrs, &node.ident);
+    }
+
+    fn visit_item_enum(&mut self, node: &'ast syn::ItemEnum) {
+        self.check_type(&node.attrs, &node.ident);
+    }
+}
+
+impl LintRule for MissingContractTypeRule {
+    fn rule_id(&self) -> &'static str {
+        "missing_contracttype"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let mut visitor = TypeVisitor {
+            findings: Findings::new(self, file),
+        };
+        visitor.visit_file(&without_tests(syntax));
+        visitor.findings.diagnostics
+    }
+}
+
+fn key_enums(file: &syn::File) -> Vec<&syn::ItemEnum> {
+    struct Collector<'a> {
+        enums: Vec<&'a syn::ItemEnum>,
+    }
+    impl<'a> Visit<'a> for Collector<'a> {
+        fn visit_item_enum(&mut self, node: &'a syn::ItemEnum) {
+            if node.ident.to_string().ends_with("Key") {
+                self.enums.push(node);
+            }
+        }
+    }
+
+    let mut collector = Collector { enums: Vec::new() };
+    collector.visit_file(file);
+    collector.enums
+}
+
+impl LintRule for MissingDataKeyEnumRule {
+    fn rule_id(&self) -> &'static str {
+        "missing_datakey_enum"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        let enums = key_enums(&syntax);
+
+        for key_enum in &enums {
+            if contract_attr(&key_enum.attrs).as_deref() != Some("contracttype") {
+                findings.push(
+                    key_enum.ident.span(),
+                    format!("Key enum `{}` is not a #[contracttype]", key_enum.ident),
+                    "Add #[contracttype] so each variant serializes to a distinct key",
+                );
+            }
+        }
+
+        if enums.is_empty() {
+            if let Some((call, _, _)) = storage_calls(&syntax).first() {
+                findings.push(
+                    call.method.span(),
+                    "Storage is used without a DataKey enum".to_string(),
+                    "Define a #[contracttype] enum DataKey with one variant per stored value",
+                );
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+fn raw_key(expr: &syn::Expr) -> Option<&'static str> {
+    match expr {
+        syn::Expr::Reference(reference) => raw_key(&reference.expr),
+        syn::Expr::Paren(paren) => raw_key(&paren.expr),
+        syn::Expr::Lit(lit) if matches!(lit.lit, syn::Lit::Str(_)) => Some("string literal"),
+        syn::Expr::Macro(mac) if mac.mac.path.is_ident("symbol_short") => Some("symbol_short!"),
+        syn::Expr::Call(call) => match &*call.func {
+            syn::Expr::Path(path)
+                if path.path.segments.len() >= 2
+                    && path.path.segments[path.path.segments.len() - 2].ident == "Symbol" =>
+            {
+                Some("Symbol")
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl LintRule for RawStorageKeyRule {
+    fn rule_id(&self) -> &'static str {
+        "raw_storage_key"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, file: &str, syntax: &syn::File) -> Vec<Diagnostic> {
+        let syntax = without_tests(syntax);
+        let mut findings = Findings::new(self, file);
+        for (call, _, method) in storage_calls(&syntax) {
+            if !STORAGE_KEYED.contains(&method.as_str()) {
+                continue;
+            }
+            if let Some(key) = call.args.first() {
+                if let Some(kind) = raw_key(key) {
+                    findings.push(
+                        key.span(),
+                        format!("Raw {} used as a storage key", kind),
+                        "Migrate to an exhaustive #[contracttype] DataKey enum",
+                    );
+                }
+            }
+        }
+        findings.diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rule: &dyn LintRule, source: &str) -> Vec<Diagnostic> {
+        rule.check("lib.rs", &syn::parse_file(source).unwrap())
+    }
+
+    #[test]
+    fn raw_keys_are_flagged_at_the_key() {
+        let source = r#"
+            pub fn store(env: Env) {
+                env.storage().instance().set(&symbol_short!("admin"), &1);
+                env.storage().instance().set(&DataKey::Admin, &1);
+                env.storage().persistent().get::<_, u32>(&Symbol::new(&env, "count"));
+            }
+        "#;
+        let found = check(&RawStorageKeyRule, source);
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].span.line, found[0].span.column), (3, 46));
+    }
+
+    #[test]
+    fn handled_storage_reads_pass() {
+        let source = r#"
+            pub fn f(env: Env) -> Result<u32, Error> {
+                let a: u32 = env.storage().persistent().get(&DataKey::A).unwrap_or(0);
+                let b: u32 = env.storage().persistent().get(&DataKey::B).ok_or(Error::Missing)?;
+                let c: u32 = env.storage().persistent().get(&DataKey::C).unwrap();
+                Ok(a + b + c)
+            }
+        "#;
+        let found = check(&UnwrappedStorageReadRule, source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].span.line, 5);
+    }
+
+    #[test]
+    fn ttl_extension_is_per_tier() {
+        let source = r#"
+            pub fn f(env: Env) {
+                env.storage().persistent().set(&DataKey::A, &1);
+                env.storage().persistent().extend_ttl(&DataKey::A, 100, 200);
+                env.storage().instance().set(&DataKey::B, &1);
+            }
+        "#;
+        assert!(check(&PersistentTtlRule, source).is_empty());
+        assert_eq!(check(&InstanceTtlRule, source).len(), 1);
+    }
+
+    #[test]
+    fn contract_types_and_key_enum() {
+        let source = r#"
+            #[contract]
+            pub struct Token;
+            #[contracterror]
+            pub enum Error { Missing = 1 }
+            pub struct Config { fee: u32 }
+            pub enum DataKey { Admin }
+        "#;
+        assert_eq!(check(&MissingContractTypeRule, source).len(), 2);
+        assert_eq!(check(&MissingDataKeyEnumRule, source).len(), 1);
+    }
+}
@@ -79,6 +79,19 @@ impl Diagnostic {
         }
     }
 
+    /// Create a diagnostic located at a syntax node's span. Lines and
+    /// columns are 1-based, as editors show them.
+    pub fn at(
+        rule_id: impl Into<String>,
+        severity: Severity,
+        message: impl Into<String>,
+        file: impl Into<String>,
+        span: proc_macro2::Span,
+    ) -> Self {
+        let start = span.start();
+        Self::new(rule_id, severity, message, file, start.line, start.column + 1)
+    }
+
     pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
         self.suggestion = Some(suggestion.into());
         self
@@ -1,10 +1,12 @@
 pub mod analyzer;
+pub mod audit;
 pub mod config;
 pub mod diagnostic;
 pub mod fixer;
 pub mod rules;
 
 pub use analyzer::Analyzer;
+pub use audit::Checklist;
 pub use config::LintConfig;
 pub use diagnostic::{Diagnostic, Severity, Span};
 pub use fixer::AutoFixer;
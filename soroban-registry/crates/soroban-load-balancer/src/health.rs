@@ -32,10 +32,26 @@ impl HealthChecker {
             .send()
             .await;
 
+        // A node that answers with a JSON-RPC error, or reports anything but
+        // "healthy", is failing even though the HTTP request succeeded.
+        let healthy = match result {
+            Ok(resp) if resp.status().is_success() => resp
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|body| {
+                    body.pointer("/result/status")
+                        .and_then(|s| s.as_str())
+                        .map(|s| s == "healthy")
+                })
+                .unwrap_or(false),
+            _ => false,
+        };
+
         let elapsed_ms = start.elapsed().as_millis() as f64;
 
-        match result {
-            Ok(resp) if resp.status().is_success() => {
+        match healthy {
+            true => {
                 let consecutive_ok = instance.consecutive_successes
                     .fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
                 instance.consecutive_failures
@@ -59,7 +75,7 @@ impl HealthChecker {
                 *instance.health.write() = new_status.clone();
                 new_status
             }
-            _ => {
+            false => {
                 let consecutive_failures = instance.consecutive_failures
                     .fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
                 instance.consecutive_successes
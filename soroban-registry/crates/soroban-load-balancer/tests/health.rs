@@ -0,0 +1,54 @@
+//! Health checks against the mock Stellar RPC server.
+
+use mock_rpc::{Fault, FaultFixture, Fixture, MockRpcServer};
+use soroban_load_balancer::health::HealthChecker;
+use soroban_load_balancer::instance::ContractInstance;
+use soroban_load_balancer::{HealthStatus, LoadBalancerConfig, Region};
+
+fn instance(endpoint: &str) -> std::sync::Arc<ContractInstance> {
+    ContractInstance::new("node-1", "CCONTRACT", endpoint, Region::UsEast, 1)
+}
+
+#[tokio::test]
+async fn test_healthy_after_consecutive_successes() {
+    let server = MockRpcServer::start(Fixture::with_ledgers(1, 10))
+        .await
+        .unwrap();
+    let checker = HealthChecker::new(LoadBalancerConfig::default());
+    let node = instance(&server.url());
+
+    assert_eq!(checker.check_instance(&node).await, HealthStatus::Degraded);
+    assert_eq!(checker.check_instance(&node).await, HealthStatus::Healthy);
+    assert!(node.is_available());
+    assert_eq!(server.call_count("getHealth"), 2);
+}
+
+#[tokio::test]
+async fn test_unhealthy_after_consecutive_failures() {
+    let server = MockRpcServer::start(Fixture::with_ledgers(1, 10))
+        .await
+        .unwrap();
+    let checker = HealthChecker::new(LoadBalancerConfig::default());
+    let node = instance(&server.url());
+
+    server.fail_next("getHealth", 2, Fault::HttpStatus { status: 503 });
+    server.inject_fault(FaultFixture {
+        method: Some("getHealth".to_string()),
+        skip: 2,
+        times: Some(1),
+        fault: Fault::RpcError {
+            code: -32603,
+            message: "latency too high".to_string(),
+        },
+    });
+
+    // Two 503s, then a JSON-RPC error
+    assert_eq!(checker.check_instance(&node).await, HealthStatus::Degraded);
+    assert_eq!(checker.check_instance(&node).await, HealthStatus::Degraded);
+    assert_eq!(checker.check_instance(&node).await, HealthStatus::Unhealthy);
+    assert!(!node.is_available());
+
+    // Recovery needs the healthy threshold again
+    assert_eq!(checker.check_instance(&node).await, HealthStatus::Degraded);
+    assert_eq!(checker.check_instance(&node).await, HealthStatus::Healthy);
+}
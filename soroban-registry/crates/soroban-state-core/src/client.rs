@@ -80,7 +80,7 @@ impl StellarRpcClient {
                 "contractIds": [contract_id]
             }],
             "startLedger": start_ledger,
-            "limit": 100
+            "pagination": { "limit": 100 }
         });
 
         if let Some(e) = end_ledger {
@@ -92,11 +92,7 @@ impl StellarRpcClient {
         let events = response
             .get("events")
             .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|e| serde_json::from_value::<ContractEvent>(e.clone()).ok())
-                    .collect()
-            })
+            .map(|arr| arr.iter().filter_map(parse_event).collect())
             .unwrap_or_default();
 
         Ok(events)
@@ -139,7 +135,20 @@ impl StellarRpcClient {
                 .send()
                 .await
             {
+                Ok(response)
+                    if (response.status().is_server_error()
+                        || response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS)
+                        && retries < max_retries =>
+                {
+                    retries += 1;
+                    let backoff = Duration::from_millis(100 * 2_u64.pow(retries - 1));
+                    tokio::time::sleep(backoff).await;
+                }
                 Ok(response) => {
+                    if !response.status().is_success() {
+                        return Err(anyhow!("RPC call failed: HTTP {}", response.status()));
+                    }
+
                     let result = response.json::<serde_json::Value>().await?;
 
                     if let Some(error) = result.get("error") {
@@ -168,6 +177,37 @@ impl StellarRpcClient {
     }
 }
 
+/// Convert an RPC event into a `ContractEvent`.
+///
+/// Event ids are `<toid>-<event index>`, where the TOID packs the ledger,
+/// transaction order and operation order; topics and value become `data`.
+fn parse_event(event: &serde_json::Value) -> Option<ContractEvent> {
+    let (toid, event_index) = event.get("id")?.as_str()?.split_once('-')?;
+    let toid: u64 = toid.parse().ok()?;
+
+    let mut data: Vec<String> = event
+        .get("topic")
+        .and_then(|v| v.as_array())
+        .map(|topics| {
+            topics
+                .iter()
+                .filter_map(|t| t.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(value) = event.get("value").and_then(|v| v.as_str()) {
+        data.push(value.to_string());
+    }
+
+    Some(ContractEvent {
+        ledger: event.get("ledger")?.as_u64()? as u32,
+        tx_index: ((toid >> 12) & 0xF_FFFF) as u32,
+        event_index: event_index.parse().ok()?,
+        event_type: event.get("type")?.as_str()?.to_string(),
+        data,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,8 +138,11 @@ pub struct ContractEvent {
 /// Response from Stellar RPC for ledger entries
 #[derive(Debug, Deserialize)]
 pub struct LedgerEntriesResponse {
+    #[serde(rename = "entries", default)]
     pub ledger_entries: Option<Vec<LedgerEntry>>,
     pub latestLedger: u32,
+    /// Not returned by every RPC version
+    #[serde(default)]
     pub latestLedgerCloseTime: u64,
 }
 
@@ -0,0 +1,114 @@
+//! `StellarRpcClient` against the mock Stellar RPC server.
+
+use mock_rpc::{EventFixture, Fault, Fixture, LedgerEntryFixture, MockRpcServer};
+use soroban_state_core::StellarRpcClient;
+
+const CONTRACT: &str = "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC";
+const OTHER_CONTRACT: &str = "CAS3J7GYLGXMF6TDJBBYYSE3HQ6BBSMLNUQ34T6TZMYMW2EVH34XOWMA";
+
+fn event(ledger: u32, contract: &str, topic: &str) -> EventFixture {
+    EventFixture {
+        ledger,
+        event_type: "contract".to_string(),
+        contract_id: Some(contract.to_string()),
+        topic: vec![topic.to_string()],
+        value: "AAAAAQ==".to_string(),
+        tx_hash: None,
+        in_successful_contract_call: true,
+    }
+}
+
+fn fixture() -> Fixture {
+    let mut fixture = Fixture::with_ledgers(500, 510);
+    fixture.ledger_entries = vec![LedgerEntryFixture {
+        key: "AAAABg==".to_string(),
+        xdr: "AAAABgAAAAE=".to_string(),
+        last_modified_ledger_seq: 505,
+        live_until_ledger_seq: Some(9000),
+    }];
+    fixture.events = vec![
+        event(502, CONTRACT, "AAAADwAAAAhpbmNyZW1lbnQ="),
+        event(502, OTHER_CONTRACT, "AAAADwAAAAhpbmNyZW1lbnQ="),
+        event(502, CONTRACT, "AAAADwAAAARtaW50"),
+        event(508, CONTRACT, "AAAADwAAAARidXJu"),
+    ];
+    fixture
+}
+
+#[tokio::test]
+async fn test_get_latest_ledger() {
+    let server = MockRpcServer::start(fixture()).await.unwrap();
+    let client = StellarRpcClient::new(&server.url());
+
+    assert_eq!(client.get_latest_ledger().await.unwrap(), 510);
+    server.advance(2);
+    assert_eq!(client.get_latest_ledger().await.unwrap(), 512);
+}
+
+#[tokio::test]
+async fn test_get_ledger_entries() {
+    let server = MockRpcServer::start(fixture()).await.unwrap();
+    let client = StellarRpcClient::new(&server.url());
+
+    let response = client
+        .get_ledger_entries(vec!["AAAABg==".to_string(), "AAAABw==".to_string()], None)
+        .await
+        .unwrap();
+    let entries = response.ledger_entries.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].xdr, "AAAABgAAAAE=");
+    assert_eq!(entries[0].lastModifiedLedgerSeq, 505);
+    assert_eq!(response.latestLedger, 510);
+}
+
+#[tokio::test]
+async fn test_get_contract_events() {
+    let server = MockRpcServer::start(fixture()).await.unwrap();
+    let client = StellarRpcClient::new(&server.url());
+
+    let events = client
+        .get_contract_events(CONTRACT, 500, None)
+        .await
+        .unwrap();
+    let positions: Vec<(u32, u32)> = events.iter().map(|e| (e.ledger, e.event_index)).collect();
+    assert_eq!(positions, vec![(502, 0), (502, 2), (508, 0)]);
+    assert_eq!(events[1].event_type, "contract");
+    assert_eq!(events[1].data, vec!["AAAADwAAAARtaW50", "AAAAAQ=="]);
+
+    // endLedger is exclusive
+    let events = client
+        .get_contract_events(CONTRACT, 500, Some(508))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 2);
+}
+
+#[tokio::test]
+async fn test_retries_transient_failures() {
+    let server = MockRpcServer::start(fixture()).await.unwrap();
+    let client = StellarRpcClient::new(&server.url());
+
+    server.fail_next("getLatestLedger", 2, Fault::HttpStatus { status: 503 });
+    assert_eq!(client.get_latest_ledger().await.unwrap(), 510);
+    assert_eq!(server.call_count("getLatestLedger"), 3);
+
+    server.fail_next("getLatestLedger", 4, Fault::HttpStatus { status: 503 });
+    let err = client.get_latest_ledger().await.unwrap_err();
+    assert!(err.to_string().contains("503"));
+
+    // JSON-RPC errors are answers, not transport failures
+    server.fail_next(
+        "getLedgerEntries",
+        1,
+        Fault::RpcError {
+            code: -32602,
+            message: "invalid key".to_string(),
+        },
+    );
+    let err = client
+        .get_ledger_entries(vec!["AAAABg==".to_string()], None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid key"));
+    assert_eq!(server.call_count("getLedgerEntries"), 1);
+}